@@ -0,0 +1,865 @@
+//! NIST [ACVP] vector sets for RSA.
+//!
+//! Labs validating products built on this crate exchange JSON vector sets
+//! with the ACVP server. This module can play both sides for the FIPS 186-5
+//! `keyGen`, `sigGen` and `sigVer` modes and the SP 800-56B
+//! `decryptionPrimitive` mode:
+//!
+//! - [`respond`] answers the prompt of a vector set, as the implementation
+//!   under test does.
+//! - [`generate`] creates a vector set and its expected results with this
+//!   crate, as the server does.
+//! - [`validate`] checks a response against the prompt and the expected
+//!   results.
+//!
+//! Vector sets may be given as the bare vector set object, or as the array
+//! prefixed with the `acvVersion` object exchanged with the server. Responses
+//! are always returned in the latter form.
+//!
+//! This module is meant for test harnesses and must not be enabled in
+//! production builds.
+//!
+//! ```no_run
+//! let prompt = std::fs::read_to_string("prompt.json")?;
+//! let response = rsa::acvp::respond(&mut rand::thread_rng(), &prompt)?;
+//! std::fs::write("response.json", response)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [ACVP]: https://pages.nist.gov/ACVP/
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use crypto_bigint::{BoxedUint, Odd};
+use digest::{Digest, DynDigest};
+use pkcs8::AssociatedOid;
+use rand_core::CryptoRngCore;
+use serde_json::{json, Map, Value};
+
+use crate::algorithms::pad::uint_to_be_pad;
+use crate::algorithms::rsa::{rsa_decrypt_and_check, rsa_encrypt};
+use crate::dummy_rng::DummyRng;
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::traits::{PrivateKeyParts, PublicKeyParts};
+use crate::{Pkcs1v15Sign, Pss};
+
+/// Version of the protocol written in responses.
+const ACV_VERSION: &str = "1.0";
+
+/// Length of the messages of generated signature tests.
+const MESSAGE_LEN: usize = 128;
+
+/// Salt length of generated PSS tests.
+const PSS_SALT_LEN: usize = 32;
+
+/// Mode of a vector set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcvpMode {
+    /// Key generation, FIPS 186-5 Appendix A.1.
+    KeyGen,
+    /// Signature generation with PKCS#1 v1.5 and PSS.
+    SigGen,
+    /// Signature verification with PKCS#1 v1.5 and PSS.
+    SigVer,
+    /// RSADP, SP 800-56B §7.1.2.
+    DecryptionPrimitive,
+}
+
+impl AcvpMode {
+    /// Returns the name of the mode in vector sets.
+    pub fn name(self) -> &'static str {
+        match self {
+            AcvpMode::KeyGen => "keyGen",
+            AcvpMode::SigGen => "sigGen",
+            AcvpMode::SigVer => "sigVer",
+            AcvpMode::DecryptionPrimitive => "decryptionPrimitive",
+        }
+    }
+
+    fn revision(self) -> &'static str {
+        match self {
+            AcvpMode::DecryptionPrimitive => "Sp800-56Br2",
+            _ => "FIPS186-5",
+        }
+    }
+
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "keyGen" => AcvpMode::KeyGen,
+            "sigGen" => AcvpMode::SigGen,
+            "sigVer" => AcvpMode::SigVer,
+            "decryptionPrimitive" => AcvpMode::DecryptionPrimitive,
+            _ => return Err(Error::InvalidArguments),
+        })
+    }
+}
+
+/// Vector set created by [`generate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcvpVectorSet {
+    /// Prompt given to the implementation under test.
+    pub prompt: String,
+    /// Expected results, used by [`validate`].
+    pub expected_results: String,
+}
+
+/// Result of a test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcvpResult {
+    /// Identifier of the test group.
+    pub tg_id: u64,
+    /// Identifier of the test case.
+    pub tc_id: u64,
+    /// Whether the response to the test case is correct.
+    pub passed: bool,
+}
+
+/// Results of the test cases of a vector set, as returned by [`validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcvpReport {
+    /// Mode of the vector set.
+    pub mode: AcvpMode,
+    /// Results of the test cases, in the order of the prompt.
+    pub results: Vec<AcvpResult>,
+}
+
+impl AcvpReport {
+    /// Returns the test cases which failed.
+    pub fn failures(&self) -> impl Iterator<Item = &AcvpResult> {
+        self.results.iter().filter(|result| !result.passed)
+    }
+
+    /// Returns whether no test case failed.
+    pub fn is_success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+/// Answers the prompt of a vector set with the implementations of this
+/// crate, returning the response to send to the server.
+///
+/// Keys, signatures and key pairs are generated with `rng`. Fails with
+/// [`Error::InvalidArguments`] if the prompt is malformed or uses parameters
+/// this crate does not support.
+pub fn respond<R: CryptoRngCore>(rng: &mut R, prompt: &str) -> Result<String> {
+    let prompt = parse_vector_set(prompt)?;
+    let mode = AcvpMode::parse(str_field(&prompt, "mode")?)?;
+    let groups = array_field(&prompt, "testGroups")?
+        .iter()
+        .map(|group| respond_group(rng, mode, group))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(vector_set_json(&prompt, mode, groups))
+}
+
+fn respond_group<R: CryptoRngCore>(rng: &mut R, mode: AcvpMode, group: &Value) -> Result<Value> {
+    let tests = array_field(group, "tests")?;
+    let mut response = Map::new();
+    response.insert("tgId".into(), u64_field(group, "tgId")?.into());
+
+    let responses = match mode {
+        AcvpMode::KeyGen => {
+            let modulo = usize_field(group, "modulo")?;
+            match str_field(group, "testType")? {
+                "AFT" => {
+                    let e = match str_field(group, "pubExp") {
+                        Ok("fixed") => uint_field(group, "fixedPubExp")?,
+                        _ => BoxedUint::from(65_537u64),
+                    };
+                    let crt = str_field(group, "keyFormat") == Ok("crt");
+                    tests
+                        .iter()
+                        .map(|test| {
+                            let key = RsaPrivateKey::new_with_exp(rng, modulo, e.clone())?;
+                            let mut response = key_json(&key, crt)?;
+                            response.insert("tcId".into(), u64_field(test, "tcId")?.into());
+                            Ok(Value::Object(response))
+                        })
+                        .collect::<Result<Vec<_>>>()?
+                }
+                "KAT" => tests
+                    .iter()
+                    .map(|test| {
+                        let passed = check_primes(modulo, test).is_ok();
+                        Ok(json!({"tcId": u64_field(test, "tcId")?, "testPassed": passed}))
+                    })
+                    .collect::<Result<Vec<_>>>()?,
+                _ => return Err(Error::InvalidArguments),
+            }
+        }
+        AcvpMode::SigGen => {
+            let params = SigParams::parse(group)?;
+            let key = RsaPrivateKey::new(rng, usize_field(group, "modulo")?)?;
+            response.insert("n".into(), uint_hex(key.n().as_ref()).into());
+            response.insert("e".into(), uint_hex(key.e()).into());
+            tests
+                .iter()
+                .map(|test| {
+                    let signature = params.sign(rng, &key, &hex_field(test, "message")?)?;
+                    Ok(json!({"tcId": u64_field(test, "tcId")?, "signature": hex(&signature)}))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        AcvpMode::SigVer => {
+            let params = SigParams::parse(group)?;
+            let key = RsaPublicKey::new(uint_field(group, "n")?, uint_field(group, "e")?)?;
+            tests
+                .iter()
+                .map(|test| {
+                    let passed = params.verify(
+                        &key,
+                        &hex_field(test, "message")?,
+                        &hex_field(test, "signature")?,
+                    );
+                    Ok(json!({"tcId": u64_field(test, "tcId")?, "testPassed": passed}))
+                })
+                .collect::<Result<Vec<_>>>()?
+        }
+        AcvpMode::DecryptionPrimitive => tests
+            .iter()
+            .map(|test| {
+                let mut response = Map::new();
+                response.insert("tcId".into(), u64_field(test, "tcId")?.into());
+                match decryption_primitive(group, test)? {
+                    Some(plaintext) => {
+                        response.insert("testPassed".into(), true.into());
+                        response.insert("plainText".into(), hex(&plaintext).into());
+                    }
+                    None => {
+                        response.insert("testPassed".into(), false.into());
+                    }
+                }
+                Ok(Value::Object(response))
+            })
+            .collect::<Result<Vec<_>>>()?,
+    };
+    response.insert("tests".into(), responses.into());
+    Ok(Value::Object(response))
+}
+
+/// Creates a vector set of `mode` for `modulo`-bit keys, with `count` test
+/// cases per test group.
+///
+/// Signature tests use SHA2-256, and PSS a salt of 32 bytes. Half of the test
+/// cases of `sigVer`, `decryptionPrimitive` and `keyGen` known answer tests
+/// are corrupted, and must be rejected.
+pub fn generate<R: CryptoRngCore>(
+    rng: &mut R,
+    mode: AcvpMode,
+    modulo: usize,
+    count: usize,
+) -> Result<AcvpVectorSet> {
+    let mut prompt_groups = Vec::new();
+    let mut expected_groups = Vec::new();
+    let mut tc_ids = 1u64..;
+    let mut next_tc_id = || tc_ids.next().unwrap_or_default();
+
+    match mode {
+        AcvpMode::KeyGen => {
+            let ids = (0..count).map(|_| next_tc_id()).collect::<Vec<_>>();
+            prompt_groups.push(json!({
+                "tgId": 1,
+                "testType": "AFT",
+                "modulo": modulo,
+                "keyFormat": "standard",
+                "pubExp": "random",
+                "infoGeneratedByServer": false,
+                "randPQ": "probable",
+                "primeTest": "2powSecStr",
+                "tests": ids.iter().map(|id| json!({"tcId": id})).collect::<Vec<_>>(),
+            }));
+            expected_groups.push(json!({
+                "tgId": 1,
+                "tests": ids.iter().map(|id| json!({"tcId": id})).collect::<Vec<_>>(),
+            }));
+
+            let mut prompts = Vec::new();
+            let mut expected = Vec::new();
+            for i in 0..count {
+                let key = RsaPrivateKey::new(rng, modulo)?;
+                let (p, mut q) = match key.primes() {
+                    [p, q] => (p.clone(), q.clone()),
+                    _ => return Err(Error::InvalidPrime),
+                };
+                let passed = i % 2 == 0;
+                if !passed {
+                    // Even, hence never prime.
+                    q = q.wrapping_add(&BoxedUint::one());
+                }
+                let tc_id = next_tc_id();
+                prompts.push(json!({
+                    "tcId": tc_id,
+                    "e": uint_hex(key.e()),
+                    "p": uint_hex(&p),
+                    "q": uint_hex(&q),
+                }));
+                expected.push(json!({"tcId": tc_id, "testPassed": passed}));
+            }
+            prompt_groups.push(json!({
+                "tgId": 2,
+                "testType": "KAT",
+                "modulo": modulo,
+                "randPQ": "probable",
+                "primeTest": "2powSecStr",
+                "tests": prompts,
+            }));
+            expected_groups.push(json!({"tgId": 2, "tests": expected}));
+        }
+        AcvpMode::SigGen | AcvpMode::SigVer => {
+            let key = RsaPrivateKey::new(rng, modulo)?;
+            for (tg_id, params) in [(1, SigParams::pkcs1v15()), (2, SigParams::pss())] {
+                let mut group = params.to_json(modulo);
+                let mut prompts = Vec::new();
+                let mut expected = Vec::new();
+                for i in 0..count {
+                    let tc_id = next_tc_id();
+                    let mut message = vec![0; MESSAGE_LEN];
+                    rng.fill_bytes(&mut message);
+                    if mode == AcvpMode::SigGen {
+                        prompts.push(json!({"tcId": tc_id, "message": hex(&message)}));
+                        expected.push(json!({"tcId": tc_id}));
+                        continue;
+                    }
+
+                    let signature = params.sign(rng, &key, &message)?;
+                    let passed = i % 2 == 0;
+                    if !passed {
+                        message[0] ^= 1;
+                    }
+                    prompts.push(json!({
+                        "tcId": tc_id,
+                        "message": hex(&message),
+                        "signature": hex(&signature),
+                    }));
+                    expected.push(json!({"tcId": tc_id, "testPassed": passed}));
+                }
+                group.insert("tgId".into(), tg_id.into());
+                if mode == AcvpMode::SigVer {
+                    group.insert("n".into(), uint_hex(key.n().as_ref()).into());
+                    group.insert("e".into(), uint_hex(key.e()).into());
+                }
+                group.insert("tests".into(), prompts.into());
+                prompt_groups.push(Value::Object(group));
+                expected_groups.push(json!({"tgId": tg_id, "tests": expected}));
+            }
+        }
+        AcvpMode::DecryptionPrimitive => {
+            let mut prompts = Vec::new();
+            let mut expected = Vec::new();
+            for i in 0..count {
+                let key = RsaPrivateKey::new(rng, modulo)?;
+                let tc_id = next_tc_id();
+                let mut test = key_json(&key, false)?;
+                test.insert("tcId".into(), tc_id.into());
+                if i % 2 == 0 {
+                    // Messages one byte shorter than the modulus are in range.
+                    let mut message = vec![0; key.size() - 1];
+                    rng.fill_bytes(&mut message);
+                    let m = BoxedUint::from_be_slice(&message, key.n_bits_precision())?;
+                    let c = rsa_encrypt(&key, &m)?;
+                    test.insert(
+                        "cipherText".into(),
+                        hex(&uint_to_be_pad(c, key.size())?).into(),
+                    );
+                    expected.push(json!({
+                        "tcId": tc_id,
+                        "testPassed": true,
+                        "plainText": hex(&uint_to_be_pad(m, key.size())?),
+                    }));
+                } else {
+                    // n - 1 is just outside of the range of ciphertexts.
+                    let c = key.n().as_ref().wrapping_sub(&BoxedUint::one());
+                    test.insert(
+                        "cipherText".into(),
+                        hex(&uint_to_be_pad(c, key.size())?).into(),
+                    );
+                    expected.push(json!({"tcId": tc_id, "testPassed": false}));
+                }
+                prompts.push(Value::Object(test));
+            }
+            prompt_groups.push(json!({
+                "tgId": 1,
+                "testType": "AFT",
+                "modulo": modulo,
+                "keyFormat": "standard",
+                "pubExp": "random",
+                "tests": prompts,
+            }));
+            expected_groups.push(json!({"tgId": 1, "tests": expected}));
+        }
+    }
+
+    let source = json!({"vsId": 0});
+    Ok(AcvpVectorSet {
+        prompt: vector_set_json(&source, mode, prompt_groups),
+        expected_results: vector_set_json(&source, mode, expected_groups),
+    })
+}
+
+/// Checks `response` to the vector set `prompt`.
+///
+/// Generated keys and signatures are checked against the prompt, and the
+/// responses to the other test cases are compared with `expected_results`.
+/// Test cases missing from the response fail.
+pub fn validate(prompt: &str, expected_results: &str, response: &str) -> Result<AcvpReport> {
+    let prompt = parse_vector_set(prompt)?;
+    let expected = parse_vector_set(expected_results)?;
+    let response = parse_vector_set(response)?;
+    let mode = AcvpMode::parse(str_field(&prompt, "mode")?)?;
+
+    let mut report = AcvpReport {
+        mode,
+        results: Vec::new(),
+    };
+    for group in array_field(&prompt, "testGroups")? {
+        let tg_id = u64_field(group, "tgId")?;
+        let response_group = find(&response, "testGroups", "tgId", tg_id);
+        let expected_group = find(&expected, "testGroups", "tgId", tg_id);
+        for test in array_field(group, "tests")? {
+            let tc_id = u64_field(test, "tcId")?;
+            let response_test = response_group.and_then(|g| find(g, "tests", "tcId", tc_id));
+            let expected_test = expected_group.and_then(|g| find(g, "tests", "tcId", tc_id));
+            let passed = match (response_group, response_test) {
+                (Some(response_group), Some(response_test)) => {
+                    let check = || -> Result<bool> {
+                        Ok(match (mode, str_field(group, "testType")) {
+                            (AcvpMode::KeyGen, Ok("AFT")) => {
+                                check_key(usize_field(group, "modulo")?, response_test).is_ok()
+                            }
+                            (AcvpMode::SigGen, _) => {
+                                let key = RsaPublicKey::new(
+                                    uint_field(response_group, "n")?,
+                                    uint_field(response_group, "e")?,
+                                )?;
+                                SigParams::parse(group)?.verify(
+                                    &key,
+                                    &hex_field(test, "message")?,
+                                    &hex_field(response_test, "signature")?,
+                                )
+                            }
+                            _ => matches_expected(
+                                expected_test.ok_or(Error::InvalidArguments)?,
+                                response_test,
+                            ),
+                        })
+                    };
+                    check().unwrap_or(false)
+                }
+                _ => false,
+            };
+            report.results.push(AcvpResult {
+                tg_id,
+                tc_id,
+                passed,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Returns whether every field of `expected` but `tcId` has the same value
+/// in `response`, ignoring the case of hex strings.
+fn matches_expected(expected: &Value, response: &Value) -> bool {
+    let Some(expected) = expected.as_object() else {
+        return false;
+    };
+    expected
+        .iter()
+        .filter(|(name, _)| name.as_str() != "tcId")
+        .all(|(name, value)| match (value, response.get(name)) {
+            (Value::String(expected), Some(Value::String(actual))) => {
+                expected.eq_ignore_ascii_case(actual)
+            }
+            (expected, actual) => Some(expected) == actual,
+        })
+}
+
+/// Checks that the primes of a `keyGen` known answer test form a valid key.
+fn check_primes(modulo: usize, test: &Value) -> Result<()> {
+    let key = RsaPrivateKey::from_p_q(
+        uint_field(test, "p")?,
+        uint_field(test, "q")?,
+        uint_field(test, "e")?,
+    )?;
+    check_size(&key, modulo)?;
+    key.validate_fips()
+}
+
+/// Checks that a key generated in response to a `keyGen` test is valid.
+fn check_key(modulo: usize, test: &Value) -> Result<()> {
+    let n = Odd::new(uint_field(test, "n")?)
+        .into_option()
+        .ok_or(Error::InvalidModulus)?;
+    let key = RsaPrivateKey::from_components(
+        n,
+        uint_field(test, "e")?,
+        uint_field(test, "d")?,
+        vec![uint_field(test, "p")?, uint_field(test, "q")?],
+    )?;
+    check_size(&key, modulo)?;
+    key.validate_fips()
+}
+
+fn check_size(key: &RsaPrivateKey, modulo: usize) -> Result<()> {
+    if key.n().bits() as usize != modulo {
+        return Err(Error::InvalidModulus);
+    }
+    Ok(())
+}
+
+/// Runs RSADP on the ciphertext of `test`, returning `None` if it is out of
+/// range. The key is given in the test case, or else in its group.
+fn decryption_primitive(group: &Value, test: &Value) -> Result<Option<Vec<u8>>> {
+    let source = if test.get("n").is_some() { test } else { group };
+    let n = Odd::new(uint_field(source, "n")?)
+        .into_option()
+        .ok_or(Error::InvalidModulus)?;
+    let primes = match (source.get("p"), source.get("q")) {
+        (Some(_), Some(_)) => vec![uint_field(source, "p")?, uint_field(source, "q")?],
+        _ => Vec::new(),
+    };
+    let key = RsaPrivateKey::from_components(
+        n,
+        uint_field(source, "e")?,
+        uint_field(source, "d")?,
+        primes,
+    )?;
+
+    // SP 800-56B §7.1.2.1 requires 1 < c < n - 1.
+    let Ok(c) = BoxedUint::from_be_slice(
+        trim(&hex_field(test, "cipherText")?),
+        key.n_bits_precision(),
+    ) else {
+        return Ok(None);
+    };
+    let one = BoxedUint::one_with_precision(key.n_bits_precision());
+    if c <= one || c >= key.n().as_ref().wrapping_sub(&one) {
+        return Ok(None);
+    }
+    let m = rsa_decrypt_and_check(&key, None::<&mut DummyRng>, &c)?;
+    uint_to_be_pad(m, key.size()).map(Some)
+}
+
+/// Parameters of a `sigGen` or `sigVer` test group.
+struct SigParams {
+    hash: Hash,
+    /// Salt length for PSS, or `None` for PKCS#1 v1.5.
+    salt_len: Option<usize>,
+}
+
+impl SigParams {
+    fn pkcs1v15() -> Self {
+        Self {
+            hash: Hash::Sha256,
+            salt_len: None,
+        }
+    }
+
+    fn pss() -> Self {
+        Self {
+            hash: Hash::Sha256,
+            salt_len: Some(PSS_SALT_LEN),
+        }
+    }
+
+    fn parse(group: &Value) -> Result<Self> {
+        let hash = Hash::parse(str_field(group, "hashAlg")?)?;
+        let salt_len = match str_field(group, "sigType")? {
+            "pkcs1v1.5" => None,
+            "pss" => {
+                if str_field(group, "maskFunction").unwrap_or("mgf1") != "mgf1" {
+                    return Err(Error::InvalidArguments);
+                }
+                Some(usize_field(group, "saltLen")?)
+            }
+            _ => return Err(Error::InvalidArguments),
+        };
+        Ok(Self { hash, salt_len })
+    }
+
+    fn to_json(&self, modulo: usize) -> Map<String, Value> {
+        let mut group = Map::new();
+        group.insert("testType".into(), "GDT".into());
+        group.insert("modulo".into(), modulo.into());
+        group.insert("hashAlg".into(), self.hash.name().into());
+        match self.salt_len {
+            None => {
+                group.insert("sigType".into(), "pkcs1v1.5".into());
+            }
+            Some(salt_len) => {
+                group.insert("sigType".into(), "pss".into());
+                group.insert("saltLen".into(), salt_len.into());
+                group.insert("maskFunction".into(), "mgf1".into());
+            }
+        }
+        group
+    }
+
+    fn pss_scheme(&self, salt_len: usize) -> Pss {
+        Pss {
+            blinded: false,
+            digest: self.hash.dyn_digest(),
+            salt_len,
+        }
+    }
+
+    fn sign<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+        key: &RsaPrivateKey,
+        message: &[u8],
+    ) -> Result<Vec<u8>> {
+        let hashed = self.hash.digest(message);
+        match self.salt_len {
+            None => key.sign(self.hash.pkcs1v15(), &hashed),
+            Some(salt_len) => key.sign_with_rng(rng, self.pss_scheme(salt_len), &hashed),
+        }
+    }
+
+    fn verify(&self, key: &RsaPublicKey, message: &[u8], signature: &[u8]) -> bool {
+        let hashed = self.hash.digest(message);
+        match self.salt_len {
+            None => key.verify(self.hash.pkcs1v15(), &hashed, signature),
+            Some(salt_len) => key.verify(self.pss_scheme(salt_len), &hashed, signature),
+        }
+        .is_ok()
+    }
+}
+
+/// Digests of signature tests.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Hash {
+    #[cfg(feature = "sha1")]
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha512_224,
+    Sha512_256,
+}
+
+impl Hash {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            #[cfg(feature = "sha1")]
+            "SHA-1" => Hash::Sha1,
+            "SHA2-224" => Hash::Sha224,
+            "SHA2-256" => Hash::Sha256,
+            "SHA2-384" => Hash::Sha384,
+            "SHA2-512" => Hash::Sha512,
+            "SHA2-512/224" => Hash::Sha512_224,
+            "SHA2-512/256" => Hash::Sha512_256,
+            _ => return Err(Error::InvalidArguments),
+        })
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "sha1")]
+            Hash::Sha1 => "SHA-1",
+            Hash::Sha224 => "SHA2-224",
+            Hash::Sha256 => "SHA2-256",
+            Hash::Sha384 => "SHA2-384",
+            Hash::Sha512 => "SHA2-512",
+            Hash::Sha512_224 => "SHA2-512/224",
+            Hash::Sha512_256 => "SHA2-512/256",
+        }
+    }
+
+    fn digest(self, msg: &[u8]) -> Vec<u8> {
+        let mut digest = self.dyn_digest();
+        digest.update(msg);
+        digest.finalize().into_vec()
+    }
+
+    fn dyn_digest(self) -> Box<dyn DynDigest + Send + Sync> {
+        match self {
+            #[cfg(feature = "sha1")]
+            Hash::Sha1 => Box::new(sha1::Sha1::new()),
+            Hash::Sha224 => Box::new(sha2::Sha224::new()),
+            Hash::Sha256 => Box::new(sha2::Sha256::new()),
+            Hash::Sha384 => Box::new(sha2::Sha384::new()),
+            Hash::Sha512 => Box::new(sha2::Sha512::new()),
+            Hash::Sha512_224 => Box::new(sha2::Sha512_224::new()),
+            Hash::Sha512_256 => Box::new(sha2::Sha512_256::new()),
+        }
+    }
+
+    fn pkcs1v15(self) -> Pkcs1v15Sign {
+        fn new<D: Digest + AssociatedOid>() -> Pkcs1v15Sign {
+            Pkcs1v15Sign::new::<D>()
+        }
+        match self {
+            #[cfg(feature = "sha1")]
+            Hash::Sha1 => new::<sha1::Sha1>(),
+            Hash::Sha224 => new::<sha2::Sha224>(),
+            Hash::Sha256 => new::<sha2::Sha256>(),
+            Hash::Sha384 => new::<sha2::Sha384>(),
+            Hash::Sha512 => new::<sha2::Sha512>(),
+            Hash::Sha512_224 => new::<sha2::Sha512_224>(),
+            Hash::Sha512_256 => new::<sha2::Sha512_256>(),
+        }
+    }
+}
+
+/// Key pair of a `keyGen` response or a `decryptionPrimitive` test, with the
+/// CRT values if `crt` is set.
+fn key_json(key: &RsaPrivateKey, crt: bool) -> Result<Map<String, Value>> {
+    let mut json = Map::new();
+    json.insert("n".into(), uint_hex(key.n().as_ref()).into());
+    json.insert("e".into(), uint_hex(key.e()).into());
+    if let [p, q] = key.primes() {
+        json.insert("p".into(), uint_hex(p).into());
+        json.insert("q".into(), uint_hex(q).into());
+    }
+    json.insert("d".into(), uint_hex(key.d()).into());
+    if crt {
+        let (Some(dp), Some(dq), Some(qinv)) = (key.dp(), key.dq(), key.crt_coefficient()) else {
+            return Err(Error::InvalidCoefficient);
+        };
+        json.insert("dmp1".into(), uint_hex(dp).into());
+        json.insert("dmq1".into(), uint_hex(dq).into());
+        json.insert("iqmp".into(), uint_hex(&qinv).into());
+    }
+    Ok(json)
+}
+
+/// Wraps test groups into a vector set with the header of `source`.
+fn vector_set_json(source: &Value, mode: AcvpMode, groups: Vec<Value>) -> String {
+    json!([
+        {"acvVersion": ACV_VERSION},
+        {
+            "vsId": source.get("vsId").and_then(Value::as_u64).unwrap_or_default(),
+            "algorithm": "RSA",
+            "mode": mode.name(),
+            "revision": str_field(source, "revision").unwrap_or(mode.revision()),
+            "testGroups": groups,
+        }
+    ])
+    .to_string()
+}
+
+/// Parses a vector set, which may be prefixed with the `acvVersion` object.
+fn parse_vector_set(json: &str) -> Result<Value> {
+    let value: Value = serde_json::from_str(json).map_err(|_| Error::InvalidArguments)?;
+    match value {
+        Value::Array(values) => values
+            .into_iter()
+            .find(|value| value.get("testGroups").is_some())
+            .ok_or(Error::InvalidArguments),
+        value => Ok(value),
+    }
+}
+
+/// Finds the element of the array `name` of `value` whose `id` is `expected`.
+fn find<'a>(value: &'a Value, name: &str, id: &str, expected: u64) -> Option<&'a Value> {
+    array_field(value, name)
+        .ok()?
+        .iter()
+        .find(|item| item.get(id).and_then(Value::as_u64) == Some(expected))
+}
+
+fn str_field<'a>(value: &'a Value, name: &str) -> Result<&'a str> {
+    value
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or(Error::InvalidArguments)
+}
+
+fn u64_field(value: &Value, name: &str) -> Result<u64> {
+    value
+        .get(name)
+        .and_then(Value::as_u64)
+        .ok_or(Error::InvalidArguments)
+}
+
+fn usize_field(value: &Value, name: &str) -> Result<usize> {
+    usize::try_from(u64_field(value, name)?).map_err(|_| Error::InvalidArguments)
+}
+
+fn array_field<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>> {
+    value
+        .get(name)
+        .and_then(Value::as_array)
+        .ok_or(Error::InvalidArguments)
+}
+
+fn hex_field(value: &Value, name: &str) -> Result<Vec<u8>> {
+    let hex = str_field(value, name)?;
+    if hex.len() % 2 != 0 {
+        return Err(Error::InvalidArguments);
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| Error::InvalidArguments))
+        .collect()
+}
+
+/// Decodes a big-endian integer, ignoring the leading zeros of the encoding.
+fn uint_field(value: &Value, name: &str) -> Result<BoxedUint> {
+    let bytes = hex_field(value, name)?;
+    let bytes = trim(&bytes);
+    Ok(BoxedUint::from_be_slice(bytes, bytes.len() as u32 * 8)?)
+}
+
+fn trim(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Encodes bytes as upper case hex, as the ACVP server does.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| alloc::format!("{:02X}", b)).collect()
+}
+
+/// Encodes an integer as upper case hex without leading zero bytes.
+fn uint_hex(value: &BoxedUint) -> String {
+    hex(trim(&value.to_be_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_round_trip() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        for mode in [
+            AcvpMode::KeyGen,
+            AcvpMode::SigGen,
+            AcvpMode::SigVer,
+            AcvpMode::DecryptionPrimitive,
+        ] {
+            let vector_set = generate(&mut rng, mode, 2048, 2).unwrap();
+            let response = respond(&mut rng, &vector_set.prompt).unwrap();
+            let report =
+                validate(&vector_set.prompt, &vector_set.expected_results, &response).unwrap();
+            assert_eq!(report.mode, mode);
+            assert!(!report.results.is_empty());
+            assert!(report.is_success(), "{}: {:?}", mode.name(), report);
+        }
+    }
+
+    #[test]
+    fn test_validate_wrong_response() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let vector_set = generate(&mut rng, AcvpMode::SigVer, 2048, 2).unwrap();
+        let response = respond(&mut rng, &vector_set.prompt).unwrap().replacen(
+            "\"testPassed\":true",
+            "\"testPassed\":false",
+            1,
+        );
+        let report = validate(&vector_set.prompt, &vector_set.expected_results, &response).unwrap();
+        assert_eq!(report.failures().count(), 1);
+        assert_eq!(report.results[0].tc_id, 1);
+
+        assert_eq!(
+            respond(&mut rng, r#"{"mode": "legacyDecrypt", "testGroups": []}"#),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
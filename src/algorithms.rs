@@ -1,10 +1,18 @@
 //! Useful algorithms related to RSA.
 
-mod mgf;
+pub(crate) mod mgf;
 
+#[cfg(feature = "alloc")]
+pub(crate) mod arith;
+#[cfg(feature = "alloc")]
+pub(crate) mod backend;
+
+#[cfg(feature = "alloc")]
 pub(crate) mod generate;
 pub(crate) mod oaep;
+#[cfg(feature = "alloc")]
 pub(crate) mod pad;
 pub(crate) mod pkcs1v15;
 pub(crate) mod pss;
+#[cfg(feature = "alloc")]
 pub(crate) mod rsa;
@@ -0,0 +1,329 @@
+//! Helpers for modular arithmetic on `BoxedUint` not provided by `crypto-bigint`.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+use crypto_bigint::modular::{BoxedMontyForm, BoxedMontyParams};
+use crypto_bigint::{BoxedUint, Limb, NonZero, Odd};
+use crypto_primes::hazmat::{lucas_test, LucasCheck, MillerRabin, SelfridgeBase};
+use subtle::{ConditionallySelectable, ConstantTimeEq};
+
+use crate::errors::{Error, Result};
+
+/// Returns `2^k` with the given precision.
+///
+/// Panics if `k >= bits_precision`.
+pub(crate) fn pow2(k: u32, bits_precision: u32) -> BoxedUint {
+    assert!(
+        k < bits_precision,
+        "2^k does not fit the requested precision"
+    );
+    let len = (bits_precision as usize + 7) / 8;
+    let mut bytes = vec![0u8; len];
+    bytes[len - 1 - (k as usize / 8)] = 1 << (k % 8);
+    BoxedUint::from_be_slice(&bytes, bits_precision).expect("size checked")
+}
+
+/// Returns the value of the least significant byte of `x`.
+pub(crate) fn low_byte(x: &BoxedUint) -> u8 {
+    let bytes = x.to_be_bytes();
+    bytes[bytes.len() - 1]
+}
+
+/// Returns the 64 least significant bits of `x`.
+pub(crate) fn low_u64(x: &BoxedUint) -> u64 {
+    let bytes = x.to_be_bytes();
+    let mut low = [0u8; 8];
+    let n = core::cmp::min(bytes.len(), 8);
+    low[8 - n..].copy_from_slice(&bytes[bytes.len() - n..]);
+    u64::from_be_bytes(low)
+}
+
+/// Resizes `x` to exactly `bits_precision`, which must be large enough to hold its value.
+pub(crate) fn resize(x: &BoxedUint, bits_precision: u32) -> BoxedUint {
+    match x.bits_precision().cmp(&bits_precision) {
+        Ordering::Less => x.widen(bits_precision),
+        Ordering::Equal => x.clone(),
+        Ordering::Greater => x.shorten(bits_precision),
+    }
+}
+
+/// Computes `x mod m` for an arbitrary precision `x`, returning a value with the precision of `m`.
+pub(crate) fn reduce(x: &BoxedUint, m: &NonZero<BoxedUint>) -> BoxedUint {
+    let bits = core::cmp::max(x.bits_precision(), m.bits_precision());
+    let m_wide = NonZero::new(m.as_ref().widen(bits)).expect("non zero");
+    let r = resize(x, bits).rem_vartime(&m_wide);
+    resize(&r, m.bits_precision())
+}
+
+/// Computes `x mod m` for a non-zero `m`.
+pub(crate) fn rem_u64(x: &BoxedUint, m: u64) -> u64 {
+    let m = NonZero::new(BoxedUint::from(m)).expect("non zero");
+    low_u64(&reduce(x, &m))
+}
+
+/// Creates Montgomery parameters for an odd modulus.
+pub(crate) fn monty_params(m: &BoxedUint) -> Result<BoxedMontyParams> {
+    let m = Odd::new(m.clone())
+        .into_option()
+        .ok_or(Error::InvalidModulus)?;
+    Ok(BoxedMontyParams::new(m))
+}
+
+/// Converts `x` into Montgomery form, reducing it first if needed.
+pub(crate) fn to_monty(x: &BoxedUint, params: &BoxedMontyParams) -> BoxedMontyForm {
+    let m = params.modulus().as_nz_ref().clone();
+    BoxedMontyForm::new(reduce(x, &m), params.clone())
+}
+
+/// Computes `base^exp mod m` where `m` is described by `params`.
+pub(crate) fn pow_mod(base: &BoxedUint, exp: &BoxedUint, params: &BoxedMontyParams) -> BoxedUint {
+    to_monty(base, params).pow(exp).retrieve()
+}
+
+/// Computes `base^exp` in constant time with a fixed window of `window` bits.
+///
+/// All `2^window` powers of the base are computed up front, and every lookup reads the whole
+/// table, so neither the sequence of operations nor the memory access pattern depends on `exp`.
+pub(crate) fn pow_fixed_window(
+    base: &BoxedMontyForm,
+    exp: &BoxedUint,
+    window: u32,
+) -> BoxedMontyForm {
+    debug_assert!((1..=8).contains(&window), "unsupported window size");
+    let params = base.params().clone();
+
+    let mut table = Vec::with_capacity(1 << window);
+    table.push(BoxedMontyForm::one(params.clone()));
+    for i in 1..(1usize << window) {
+        let power = table[i - 1].mul(base);
+        table.push(power);
+    }
+
+    let bits = exp.bits_precision();
+    let mut acc = BoxedMontyForm::one(params.clone());
+    for w in (0..bits.div_ceil(window)).rev() {
+        for _ in 0..window {
+            acc = acc.square();
+        }
+
+        // Read the window of the exponent at a public position.
+        let mut index = 0u32;
+        for bit in (0..window).rev() {
+            let pos = w * window + bit;
+            let value = if pos < bits {
+                let limb = exp.as_limbs()[(pos / Limb::BITS) as usize].0;
+                ((limb >> (pos % Limb::BITS)) & 1) as u32
+            } else {
+                0
+            };
+            index = (index << 1) | value;
+        }
+
+        let mut selected = table[0].as_montgomery().clone();
+        for (i, entry) in table.iter().enumerate().skip(1) {
+            let choice = (i as u32).ct_eq(&index);
+            for (out, limb) in selected
+                .as_limbs_mut()
+                .iter_mut()
+                .zip(entry.as_montgomery().as_limbs())
+            {
+                out.conditional_assign(limb, choice);
+            }
+        }
+        acc = acc.mul(&BoxedMontyForm::from_montgomery(selected, params.clone()));
+    }
+    acc
+}
+
+/// Computes `base^exp` by left-to-right square-and-multiply, in variable time.
+///
+/// Only the bits of `exp` up to its most significant set bit are processed, so
+/// small public exponents such as 65537 take a handful of operations. Must not
+/// be used with secret bases or exponents.
+pub(crate) fn pow_vartime(base: &BoxedMontyForm, exp: &BoxedUint) -> BoxedMontyForm {
+    let mut acc = BoxedMontyForm::one(base.params().clone());
+    for pos in (0..exp.bits()).rev() {
+        acc = acc.square();
+        let limb = exp.as_limbs()[(pos / Limb::BITS) as usize].0;
+        if (limb >> (pos % Limb::BITS)) & 1 == 1 {
+            acc = acc.mul(base);
+        }
+    }
+    acc
+}
+
+/// Computes `x^(2^t) mod m` by `t` sequential squarings.
+pub(crate) fn square_repeatedly(x: &BoxedUint, t: u64, params: &BoxedMontyParams) -> BoxedUint {
+    let mut acc = to_monty(x, params);
+    for _ in 0..t {
+        acc = acc.square();
+    }
+    acc.retrieve()
+}
+
+/// Computes `2^t mod m` for an arbitrary (possibly even) modulus, in variable time.
+pub(crate) fn pow2_mod_vartime(t: u64, m: &NonZero<BoxedUint>) -> BoxedUint {
+    let bits = m.bits_precision();
+    let wide = 2 * bits;
+    let two = BoxedUint::from(2u64).widen(wide);
+    let mut acc = reduce(&BoxedUint::one(), m);
+    for i in (0..u64::BITS - t.leading_zeros()).rev() {
+        acc = reduce(&acc.widen(wide).wrapping_mul(&acc.widen(wide)), m);
+        if (t >> i) & 1 == 1 {
+            acc = reduce(&acc.widen(wide).wrapping_mul(&two), m);
+        }
+    }
+    acc
+}
+
+/// Computes `a * b mod m` where `m` is described by `params`.
+pub(crate) fn mul_mod(a: &BoxedUint, b: &BoxedUint, params: &BoxedMontyParams) -> BoxedUint {
+    (to_monty(a, params) * to_monty(b, params)).retrieve()
+}
+
+/// Computes `a^-1 mod m` where `m` is described by `params`.
+pub(crate) fn inv_mod(a: &BoxedUint, params: &BoxedMontyParams) -> Option<BoxedUint> {
+    to_monty(a, params)
+        .invert()
+        .into_option()
+        .map(|inv| inv.retrieve())
+}
+
+/// Computes the Jacobi symbol `(a / n)` for odd `n`, in variable time.
+///
+/// Returns `0`, `1` or `-1`.
+pub(crate) fn jacobi_vartime(a: &BoxedUint, n: &Odd<BoxedUint>) -> i8 {
+    let bits = core::cmp::max(a.bits_precision(), n.bits_precision());
+    let mut n = n.as_ref().widen(bits);
+    let mut a = reduce(a, &NonZero::new(n.clone()).expect("odd")).widen(bits);
+    let mut result = 1i8;
+
+    while !bool::from(a.is_zero()) {
+        let tz = a.trailing_zeros();
+        if tz > 0 {
+            a = &a >> tz;
+            let n_mod_8 = low_byte(&n) & 7;
+            if tz % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+                result = -result;
+            }
+        }
+
+        core::mem::swap(&mut a, &mut n);
+        if low_byte(&a) & 3 == 3 && low_byte(&n) & 3 == 3 {
+            result = -result;
+        }
+        a = a.rem_vartime(&NonZero::new(n.clone()).expect("odd"));
+    }
+
+    if bool::from(n.is_one()) {
+        result
+    } else {
+        0
+    }
+}
+
+/// Deterministic Baillie-PSW probable prime test, in variable time.
+///
+/// Unlike `crypto_primes::is_prime_with_rng` this needs no randomness, so that
+/// both sides of a protocol reach the same verdict for the same candidate.
+pub(crate) fn is_prime_vartime(x: &BoxedUint) -> bool {
+    if x.bits() <= 2 {
+        // 2 and 3 are the only primes below 4
+        return x.bits() == 2;
+    }
+    if low_byte(x) & 1 == 0 {
+        return false;
+    }
+    let candidate = match Odd::new(x.clone()).into_option() {
+        Some(c) => c,
+        None => return false,
+    };
+    MillerRabin::new(candidate.clone())
+        .test_base_two()
+        .is_probably_prime()
+        && lucas_test(candidate, SelfridgeBase, LucasCheck::Strong).is_probably_prime()
+}
+
+/// Returns `ceil(log2(x))` for a non-zero `x`.
+pub(crate) fn ceil_log2(x: usize) -> u32 {
+    debug_assert!(x > 0);
+    usize::BITS - (x - 1).leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pow_fixed_window() {
+        let params = monty_params(&BoxedUint::from(1_000_003u64)).unwrap();
+        let base = to_monty(&BoxedUint::from(12_345u64), &params);
+        let exp = BoxedUint::from(0xdead_beef_u64).widen(128);
+        let expected = base.pow(&exp);
+        for window in 1..=8 {
+            assert_eq!(pow_fixed_window(&base, &exp, window), expected);
+        }
+    }
+
+    #[test]
+    fn test_pow_vartime() {
+        let params = monty_params(&BoxedUint::from(1_000_003u64)).unwrap();
+        let base = to_monty(&BoxedUint::from(12_345u64), &params);
+        for exp in [0u64, 1, 3, 65_537, 0xdead_beef] {
+            let exp = BoxedUint::from(exp).widen(128);
+            assert_eq!(pow_vartime(&base, &exp), base.pow(&exp));
+        }
+    }
+
+    #[test]
+    fn test_pow2() {
+        assert_eq!(pow2(0, 64), BoxedUint::one());
+        assert_eq!(pow2(10, 64), BoxedUint::from(1024u64));
+        assert_eq!(pow2(100, 128).bits(), 101);
+    }
+
+    #[test]
+    fn test_jacobi() {
+        // (a / 15) for a = 0..15, from the standard table.
+        let expected = [0, 1, 1, 0, 1, 0, 0, -1, 1, 0, 0, -1, 0, -1, -1];
+        let n = Odd::new(BoxedUint::from(15u64)).unwrap();
+        for (a, e) in expected.iter().enumerate() {
+            assert_eq!(
+                jacobi_vartime(&BoxedUint::from(a as u64), &n),
+                *e,
+                "a = {a}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pow2_mod_vartime() {
+        let m = NonZero::new(BoxedUint::from(1_000_000u64)).unwrap();
+        assert_eq!(pow2_mod_vartime(0, &m), BoxedUint::one());
+        assert_eq!(pow2_mod_vartime(10, &m), BoxedUint::from(1024u64));
+        // 2^64 = 18446744073709551616
+        assert_eq!(pow2_mod_vartime(64, &m), BoxedUint::from(551_616u64));
+    }
+
+    #[test]
+    fn test_is_prime_vartime() {
+        let primes = [2u64, 3, 5, 7, 65_537, 4_294_967_291];
+        let composites = [0u64, 1, 4, 9, 561, 65_535, 4_294_967_297];
+        for p in primes {
+            assert!(is_prime_vartime(&BoxedUint::from(p)), "{p}");
+        }
+        for c in composites {
+            assert!(!is_prime_vartime(&BoxedUint::from(c)), "{c}");
+        }
+    }
+
+    #[test]
+    fn test_ceil_log2() {
+        assert_eq!(ceil_log2(1), 0);
+        assert_eq!(ceil_log2(2), 1);
+        assert_eq!(ceil_log2(3), 2);
+        assert_eq!(ceil_log2(8), 3);
+        assert_eq!(ceil_log2(9), 4);
+    }
+}
@@ -0,0 +1,86 @@
+//! Backends for the modular exponentiations of RSA operations.
+//!
+//! All exponentiations of the RSA primitives go through [`pow`], which
+//! dispatches to the backend selected by the crate features. The pure Rust
+//! [`CryptoBigint`] backend is the default. The `gmp` feature delegates to
+//! GMP's `mpz_powm_sec` instead, which is considerably faster for large keys.
+
+use crypto_bigint::modular::BoxedMontyForm;
+use crypto_bigint::BoxedUint;
+
+/// Modular exponentiation.
+pub(crate) trait PowBackend {
+    /// Computes `base^exp`, in constant time with respect to `base` and `exp`.
+    fn pow(base: &BoxedMontyForm, exp: &BoxedUint) -> BoxedMontyForm;
+}
+
+/// Exponentiation by `crypto-bigint`.
+#[cfg_attr(feature = "gmp", allow(dead_code))]
+pub(crate) struct CryptoBigint;
+
+impl PowBackend for CryptoBigint {
+    fn pow(base: &BoxedMontyForm, exp: &BoxedUint) -> BoxedMontyForm {
+        base.pow(exp)
+    }
+}
+
+/// Exponentiation by GMP, through `rug`.
+///
+/// Operands are copied into GMP integers, which are not zeroized when freed.
+#[cfg(feature = "gmp")]
+pub(crate) struct Gmp;
+
+#[cfg(feature = "gmp")]
+impl PowBackend for Gmp {
+    fn pow(base: &BoxedMontyForm, exp: &BoxedUint) -> BoxedMontyForm {
+        use rug::{integer::Order, Integer};
+        use zeroize::Zeroizing;
+
+        let params = base.params();
+        // `mpz_powm_sec` requires a positive exponent.
+        if bool::from(exp.is_zero()) {
+            return BoxedMontyForm::one(params.clone());
+        }
+
+        let to_gmp = |x: &BoxedUint| {
+            Integer::from_digits::<u8>(&Zeroizing::new(x.to_be_bytes()), Order::Msf)
+        };
+        let modulus = to_gmp(params.modulus().as_ref());
+        let result = to_gmp(&base.retrieve()).secure_pow_mod(&to_gmp(exp), &modulus);
+
+        let bytes = Zeroizing::new(result.to_digits::<u8>(Order::Msf));
+        let result =
+            BoxedUint::from_be_slice(&bytes, params.bits_precision()).expect("result is reduced");
+        BoxedMontyForm::new(result, params.clone())
+    }
+}
+
+/// Backend selected by the crate features.
+#[cfg(not(feature = "gmp"))]
+type Selected = CryptoBigint;
+
+/// Backend selected by the crate features.
+#[cfg(feature = "gmp")]
+type Selected = Gmp;
+
+/// Computes `base^exp` with the selected backend.
+#[inline]
+pub(crate) fn pow(base: &BoxedMontyForm, exp: &BoxedUint) -> BoxedMontyForm {
+    Selected::pow(base, exp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::arith::{monty_params, to_monty};
+
+    #[test]
+    fn test_pow() {
+        let params = monty_params(&BoxedUint::from(1_000_003u64)).unwrap();
+        let base = to_monty(&BoxedUint::from(12_345u64), &params);
+        for exp in [0u64, 1, 2, 65_537, 0xdead_beef] {
+            let exp = BoxedUint::from(exp);
+            assert_eq!(pow(&base, &exp), CryptoBigint::pow(&base, &exp));
+        }
+    }
+}
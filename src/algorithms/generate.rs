@@ -1,16 +1,28 @@
 //! Generate prime components for the RSA Private Key
 
 use alloc::vec::Vec;
-use crypto_bigint::{BoxedUint, Odd};
+use core::cell::{Cell, RefCell};
+use core::ops::ControlFlow;
+use crypto_bigint::{BoxedUint, Gcd, NonZero, Odd};
 use crypto_primes::{
-    hazmat::{SetBits, SmallPrimesSieveFactory},
-    is_prime_with_rng, sieve_and_find,
+    hazmat::{
+        lucas_test, LucasCheck, MillerRabin, SelfridgeBase, SetBits, SmallPrimesSieveFactory,
+    },
+    is_prime_with_rng, is_safe_prime_with_rng, sieve_and_find,
 };
+use digest::Digest;
 use rand_core::CryptoRngCore;
 
 use crate::{
-    algorithms::rsa::{compute_modulus, compute_private_exponent_euler_totient},
+    algorithms::arith::{
+        is_prime_vartime, low_u64, monty_params, pow2, pow_mod, reduce, rem_u64, resize,
+    },
+    algorithms::rsa::{
+        compute_modulus, compute_private_exponent_carmicheal,
+        compute_private_exponent_euler_totient,
+    },
     errors::{Error, Result},
+    key::check_size_cap,
 };
 
 pub struct RsaPrivateKeyComponents {
@@ -20,6 +32,71 @@ pub struct RsaPrivateKeyComponents {
     pub primes: Vec<BoxedUint>,
 }
 
+/// Maximum number of candidate keys tried before key generation gives up.
+///
+/// Valid parameters need only a handful of attempts, this guards against
+/// parameters for which no key exists, e.g. an exponent sharing a factor
+/// with every `p - 1`.
+const MAX_ATTEMPTS: usize = 1000;
+
+/// Maximum number of prime candidates rejected by the [`PrimeConstraints`],
+/// or by the primality test in the search for the auxiliary prime of a
+/// strong prime, before the search for a prime gives up.
+///
+/// This guards against constraints which few or no primes of the requested
+/// size satisfy, e.g. `top_bits` fixing most bits of a small prime.
+const MAX_REJECTED_CANDIDATES: usize = 1 << 24;
+
+/// Event reported to the progress callback during key generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeygenEvent {
+    /// A prime candidate which passed the sieve is being tested.
+    CandidateTested,
+    /// A prime factor of the modulus has been found.
+    PrimeFound {
+        /// Index of the prime among the factors of the modulus.
+        index: usize,
+        /// Bit length of the prime.
+        bits: u32,
+    },
+    /// The primes found so far have been rejected and the search starts
+    /// over.
+    Restarted,
+}
+
+/// Progress callback of key generation, which cancels it by returning
+/// [`ControlFlow::Break`].
+pub(crate) struct Progress<'a> {
+    callback: &'a mut dyn FnMut(KeygenEvent) -> ControlFlow<()>,
+    cancelled: bool,
+}
+
+impl<'a> Progress<'a> {
+    pub(crate) fn new(callback: &'a mut dyn FnMut(KeygenEvent) -> ControlFlow<()>) -> Self {
+        Self {
+            callback,
+            cancelled: false,
+        }
+    }
+
+    /// Report `event`, returning an error if key generation was cancelled.
+    fn report(&mut self, event: KeygenEvent) -> Result<()> {
+        if !self.cancelled && (self.callback)(event).is_break() {
+            self.cancelled = true;
+        }
+        self.check()
+    }
+
+    fn check(&self) -> Result<()> {
+        if self.cancelled {
+            Err(Error::KeyGenerationCancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
 /// Generates a multi-prime RSA keypair of the given bit size, public exponent,
 /// and the given random source, as suggested in [1]. Although the public
 /// keys are compatible (actually, indistinguishable) from the 2-prime case,
@@ -36,11 +113,169 @@ pub(crate) fn generate_multi_prime_key_with_exp<R: CryptoRngCore>(
     nprimes: usize,
     bit_size: usize,
     exp: BoxedUint,
+) -> Result<RsaPrivateKeyComponents> {
+    generate_multi_prime_key_with_strength(rng, nprimes, bit_size, exp, PrimeStrength::Probable)
+}
+
+/// Generates a multi-prime RSA keypair like
+/// [`generate_multi_prime_key_with_exp`], with primes of the given strength.
+pub(crate) fn generate_multi_prime_key_with_strength<R: CryptoRngCore>(
+    rng: &mut R,
+    nprimes: usize,
+    bit_size: usize,
+    exp: BoxedUint,
+    strength: PrimeStrength,
+) -> Result<RsaPrivateKeyComponents> {
+    generate_multi_prime_key_with_progress(
+        rng,
+        nprimes,
+        bit_size,
+        exp,
+        strength,
+        &PrimeSearch::default(),
+        &mut Progress::new(&mut |_| ControlFlow::Continue(())),
+    )
+}
+
+/// Generates a multi-prime RSA keypair like
+/// [`generate_multi_prime_key_with_strength`], searching primes as
+/// configured by `search` and reporting its progress.
+pub(crate) fn generate_multi_prime_key_with_progress<R: CryptoRngCore>(
+    rng: &mut R,
+    nprimes: usize,
+    bit_size: usize,
+    exp: BoxedUint,
+    strength: PrimeStrength,
+    search: &PrimeSearch,
+    progress: &mut Progress<'_>,
 ) -> Result<RsaPrivateKeyComponents> {
     if nprimes < 2 {
         return Err(Error::NprimesTooSmall);
     }
+    check_size_cap(bit_size)?;
+    search.constraints.validate(bit_size / nprimes)?;
 
+    if strength == PrimeStrength::Fips186 {
+        if nprimes != 2 {
+            return Err(Error::InvalidArguments);
+        }
+        return generate_fips186_key_with_exp(rng, bit_size, exp, search, progress);
+    }
+
+    if strength == PrimeStrength::Strong && bit_size / nprimes < STRONG_PRIME_MIN_BITS as usize {
+        return Err(Error::InvalidArguments);
+    }
+
+    generate_multi_prime_key_from(
+        rng,
+        nprimes,
+        bit_size,
+        exp,
+        |rng, bits, progress| strength.generate(rng, bits, search, progress),
+        progress,
+    )
+}
+
+/// Source of the prime factors used by key generation.
+///
+/// Implementations can provide primes from e.g. a hardware prime generator
+/// or a pool of precomputed primes. Key generation asks for one prime at a
+/// time and checks the resulting candidate key: primes are rejected if they
+/// are equal to another prime of the key, if `p - 1` is not coprime to the
+/// public exponent, or if the modulus ends up with the wrong size. Sources
+/// must therefore be able to provide further primes after a rejection.
+///
+/// Primes should have the two most significant bits set, so that the
+/// product of the primes has the expected size.
+pub trait PrimeSource {
+    /// Provide a prime of `bit_length` bits.
+    fn generate_prime<R: CryptoRngCore>(
+        &mut self,
+        rng: &mut R,
+        bit_length: u32,
+    ) -> Result<BoxedUint>;
+}
+
+/// The default [`PrimeSource`]: random candidates are sieved for small
+/// factors and tested with a [`PrimalityTest`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SievePrimeSource {
+    search: PrimeSearch,
+}
+
+impl SievePrimeSource {
+    /// Create a source with the default primality test and no constraints.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set additional constraints the primes have to satisfy.
+    pub fn constraints(mut self, constraints: PrimeConstraints) -> Self {
+        self.search.constraints = constraints;
+        self
+    }
+
+    /// Set the primality test applied to the candidates.
+    pub fn primality_test(mut self, test: PrimalityTest) -> Self {
+        self.search.test = test;
+        self
+    }
+}
+
+impl PrimeSource for SievePrimeSource {
+    fn generate_prime<R: CryptoRngCore>(
+        &mut self,
+        rng: &mut R,
+        bit_length: u32,
+    ) -> Result<BoxedUint> {
+        self.search.constraints.validate(bit_length as usize)?;
+        find_prime(
+            rng,
+            bit_length,
+            false,
+            &self.search,
+            &mut Progress::new(&mut |_| ControlFlow::Continue(())),
+        )
+    }
+}
+
+/// Generates a multi-prime RSA keypair like
+/// [`generate_multi_prime_key_with_exp`], with primes provided by `source`.
+pub(crate) fn generate_multi_prime_key_with_source<R: CryptoRngCore, S: PrimeSource>(
+    rng: &mut R,
+    nprimes: usize,
+    bit_size: usize,
+    exp: BoxedUint,
+    source: &mut S,
+    progress: &mut Progress<'_>,
+) -> Result<RsaPrivateKeyComponents> {
+    if nprimes < 2 {
+        return Err(Error::NprimesTooSmall);
+    }
+    check_size_cap(bit_size)?;
+    generate_multi_prime_key_from(
+        rng,
+        nprimes,
+        bit_size,
+        exp,
+        |rng, bits, _| source.generate_prime(rng, bits),
+        progress,
+    )
+}
+
+/// Key generation loop, taking the primes from `next_prime`.
+fn generate_multi_prime_key_from<'p, R, F>(
+    rng: &mut R,
+    nprimes: usize,
+    bit_size: usize,
+    exp: BoxedUint,
+    mut next_prime: F,
+    progress: &mut Progress<'p>,
+) -> Result<RsaPrivateKeyComponents>
+where
+    R: CryptoRngCore,
+    F: FnMut(&mut R, u32, &mut Progress<'p>) -> Result<BoxedUint>,
+{
     if bit_size < 64 {
         let prime_limit = (1u64 << (bit_size / nprimes) as u64) as f64;
 
@@ -60,10 +295,19 @@ pub(crate) fn generate_multi_prime_key_with_exp<R: CryptoRngCore>(
     let mut primes = vec![BoxedUint::zero(); nprimes];
     let n_final: Odd<BoxedUint>;
     let d_final: BoxedUint;
+    let mut attempts = 0usize;
 
     'next: loop {
+        if attempts == MAX_ATTEMPTS {
+            return Err(Error::KeyGenerationFailed { attempts });
+        }
+        if attempts > 0 {
+            progress.report(KeygenEvent::Restarted)?;
+        }
+        attempts += 1;
+
         let mut todo = bit_size;
-        // `generate_prime_with_rng` should set the top two bits in each prime.
+        // The prime source should set the top two bits in each prime.
         // Thus each prime has the form
         //   p_i = 2^bitlen(p_i) × 0.11... (in base 2).
         // And the product is:
@@ -80,8 +324,12 @@ pub(crate) fn generate_multi_prime_key_with_exp<R: CryptoRngCore>(
 
         for (i, prime) in primes.iter_mut().enumerate() {
             let bits = (todo / (nprimes - i)) as u32;
-            *prime = generate_prime_with_rng(rng, bits);
+            *prime = next_prime(rng, bits, progress)?;
             todo -= prime.bits() as usize;
+            progress.report(KeygenEvent::PrimeFound {
+                index: i,
+                bits: prime.bits(),
+            })?;
         }
 
         // Makes sure that primes is pairwise unequal.
@@ -93,16 +341,24 @@ pub(crate) fn generate_multi_prime_key_with_exp<R: CryptoRngCore>(
             }
         }
 
-        let n = compute_modulus(&primes);
+        let n = compute_modulus(&primes)?;
 
         if n.bits() as usize != bit_size {
             // This should never happen for nprimes == 2 because
-            // generate_prime_with_rng should set the top two bits in each prime.
+            // the prime source should set the top two bits in each prime.
             // For nprimes > 2 we hope it does not happen often.
             continue 'next;
         }
 
+        if nprimes == 2 && !primes_far_apart(&primes[0], &primes[1], bit_size) {
+            continue 'next;
+        }
+
         if let Ok(d) = compute_private_exponent_euler_totient(&primes, &exp) {
+            // FIPS 186-5 A.1.1: d > 2^(nlen/2), for 2-prime keys
+            if nprimes == 2 && !exceeds_pow2(&d, (bit_size / 2) as u32) {
+                continue 'next;
+            }
             n_final = n;
             d_final = d;
             break;
@@ -142,7 +398,7 @@ fn logf_approx(x: f32) -> f32 {
     -1.49278 + (2.11263 + (-0.729104 + 0.10969 * x) * x) * x + core::f32::consts::LN_2 * (t as f32)
 }
 
-fn generate_prime_with_rng<R: CryptoRngCore>(rng: &mut R, bit_length: u32) -> BoxedUint {
+pub(crate) fn generate_prime_with_rng<R: CryptoRngCore>(rng: &mut R, bit_length: u32) -> BoxedUint {
     sieve_and_find(
         rng,
         SmallPrimesSieveFactory::new(bit_length, SetBits::TwoMsb),
@@ -151,6 +407,934 @@ fn generate_prime_with_rng<R: CryptoRngCore>(rng: &mut R, bit_length: u32) -> Bo
     .expect("will produce a result eventually")
 }
 
+/// Kind of primes the RSA factors are generated as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PrimeStrength {
+    /// Random probable primes.
+    #[default]
+    Probable,
+    /// Safe primes `p = 2p' + 1`, where `p'` is prime as well.
+    ///
+    /// Safe primes are much rarer than random primes: generating a 2048-bit
+    /// key this way is expected to take several orders of magnitude longer
+    /// than with [`PrimeStrength::Probable`].
+    Safe,
+    /// Strong primes in the sense of ANSI X9.31: `p - 1` and `p + 1` both
+    /// have a large prime factor, and so does `r - 1` for the large prime
+    /// factor `r` of `p - 1`. Generated with Gordon's algorithm.
+    ///
+    /// Requires primes of at least 64 bits.
+    Strong,
+    /// Probable primes with conditions based on auxiliary probable primes,
+    /// as specified in [FIPS 186-5] Appendix A.1.6 (formerly FIPS 186-4
+    /// Appendix B.3.6).
+    ///
+    /// Only supported for 2-prime keys of at least 2048 bits, with an odd
+    /// public exponent `2^16 < e < 2^256`. The private exponent is computed
+    /// modulo `λ(n)` as required by the standard.
+    ///
+    /// [FIPS 186-5]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-5.pdf
+    Fips186,
+}
+
+impl PrimeStrength {
+    fn generate<R: CryptoRngCore>(
+        self,
+        rng: &mut R,
+        bit_length: u32,
+        search: &PrimeSearch,
+        progress: &mut Progress<'_>,
+    ) -> Result<BoxedUint> {
+        match self {
+            Self::Probable => find_prime(rng, bit_length, false, search, progress),
+            Self::Safe => find_prime(rng, bit_length, true, search, progress),
+            Self::Strong => generate_strong_prime_with_rng(rng, bit_length, search, progress),
+            Self::Fips186 => unreachable!("handled by generate_fips186_key_with_exp"),
+        }
+    }
+}
+
+/// Additional conditions on the generated primes.
+///
+/// Candidates which do not satisfy the constraints are discarded before the
+/// primality test, so every constraint slows down key generation by the
+/// inverse of the fraction of primes satisfying it. Key generation fails with
+/// [`Error::KeyGenerationFailed`] if too many candidates are discarded.
+///
+/// ```
+/// use rsa::keygen::PrimeConstraints;
+///
+/// // Blum primes with the top nibble 0b1101, suitable for Rabin
+/// let constraints = PrimeConstraints::new()
+///     .congruent(3, 4)
+///     .top_bits(0b1101, 4);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PrimeConstraints {
+    /// `(residue, modulus)` pairs with `p ≡ residue (mod modulus)`.
+    congruences: Vec<(u64, u64)>,
+    /// `(residue, modulus)` pairs with `p ≢ residue (mod modulus)`.
+    exclusions: Vec<(u64, u64)>,
+    /// `(pattern, count)`: the `count` most significant bits of `p`.
+    top_bits: Option<(u64, u32)>,
+}
+
+impl PrimeConstraints {
+    /// Create an empty set of constraints, satisfied by every prime.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require `p ≡ residue (mod modulus)`.
+    pub fn congruent(mut self, residue: u64, modulus: u64) -> Self {
+        self.congruences.push((residue, modulus));
+        self
+    }
+
+    /// Require `p ≢ residue (mod modulus)`.
+    pub fn not_congruent(mut self, residue: u64, modulus: u64) -> Self {
+        self.exclusions.push((residue, modulus));
+        self
+    }
+
+    /// Require the `count` most significant bits of `p` to be `pattern`.
+    ///
+    /// As the two most significant bits of the primes are always set, so that
+    /// the modulus has the requested size, `count` must be between 2 and 64
+    /// and `pattern` must start with `0b11`.
+    pub fn top_bits(mut self, pattern: u64, count: u32) -> Self {
+        self.top_bits = Some((pattern, count));
+        self
+    }
+
+    /// Check that the constraints can be satisfied by primes of
+    /// `bit_length` bits.
+    fn validate(&self, bit_length: usize) -> Result<()> {
+        for &(residue, modulus) in self.congruences.iter().chain(self.exclusions.iter()) {
+            if modulus < 2 || residue >= modulus {
+                return Err(Error::InvalidArguments);
+            }
+        }
+        // primes larger than the modulus are coprime to it
+        if self
+            .congruences
+            .iter()
+            .any(|&(residue, modulus)| gcd_u64(residue, modulus) != 1)
+        {
+            return Err(Error::InvalidArguments);
+        }
+        // the congruences must have a common solution, which is not excluded
+        for (i, &(residue, modulus)) in self.congruences.iter().enumerate() {
+            for &(other_residue, other_modulus) in &self.congruences[..i] {
+                let g = gcd_u64(modulus, other_modulus);
+                if residue % g != other_residue % g {
+                    return Err(Error::InvalidArguments);
+                }
+            }
+            if self.exclusions.iter().any(|&(excluded, excluded_modulus)| {
+                modulus % excluded_modulus == 0 && residue % excluded_modulus == excluded
+            }) {
+                return Err(Error::InvalidArguments);
+            }
+        }
+        if let Some((pattern, count)) = self.top_bits {
+            if !(2..=64).contains(&count)
+                || count as usize > bit_length
+                || (count < 64 && pattern >> count != 0)
+                || pattern >> (count - 2) != 0b11
+            {
+                return Err(Error::InvalidArguments);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `p` satisfies all constraints.
+    fn is_satisfied_by(&self, p: &BoxedUint) -> bool {
+        self.congruences
+            .iter()
+            .all(|&(residue, modulus)| rem_u64(p, modulus) == residue)
+            && self
+                .exclusions
+                .iter()
+                .all(|&(residue, modulus)| rem_u64(p, modulus) != residue)
+            && self.top_bits.is_none_or(|(pattern, count)| {
+                let bits = p.bits();
+                bits >= count && low_u64(&(p >> (bits - count))) == pattern
+            })
+    }
+}
+
+/// Primality test applied to the prime candidates during key generation.
+///
+/// Every candidate goes through a Miller-Rabin test to base 2, followed by
+/// an optional strong Lucas test and a configurable number of Miller-Rabin
+/// rounds with random bases. Each random round lets a composite pass with
+/// probability at most 1/4, and no composite passing both the base 2 and the
+/// Lucas test (the Baillie-PSW test) is known.
+///
+/// The default matches `crypto_primes::is_prime_with_rng`: Baillie-PSW
+/// followed by one random round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PrimalityTest {
+    miller_rabin_rounds: usize,
+    lucas: bool,
+}
+
+impl Default for PrimalityTest {
+    fn default() -> Self {
+        Self {
+            miller_rabin_rounds: 1,
+            lucas: true,
+        }
+    }
+}
+
+impl PrimalityTest {
+    /// Create the default test.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a test whose worst-case error probability is at most
+    /// `2^-security_bits` for any candidate, from Miller-Rabin rounds alone,
+    /// keeping the Lucas test.
+    pub fn with_error_bound(security_bits: u32) -> Self {
+        Self {
+            miller_rabin_rounds: security_bits.div_ceil(2) as usize,
+            lucas: true,
+        }
+    }
+
+    /// Set the number of Miller-Rabin rounds with random bases.
+    pub fn miller_rabin_rounds(mut self, rounds: usize) -> Self {
+        self.miller_rabin_rounds = rounds;
+        self
+    }
+
+    /// Enable or disable the strong Lucas test.
+    pub fn lucas(mut self, lucas: bool) -> Self {
+        self.lucas = lucas;
+        self
+    }
+
+    /// Upper bound `k` on the worst-case error probability `2^-k` of the
+    /// random Miller-Rabin rounds, not taking the base 2 and Lucas tests into
+    /// account.
+    pub fn error_bound_bits(&self) -> usize {
+        2 * self.miller_rabin_rounds
+    }
+
+    fn is_prime<R: CryptoRngCore>(&self, rng: &mut R, candidate: &BoxedUint) -> bool {
+        if *self == Self::default() {
+            return is_prime_with_rng(rng, candidate);
+        }
+        if candidate.bits() < 4 {
+            return is_prime_vartime(candidate);
+        }
+        let Some(odd) = Odd::new(candidate.clone()).into_option() else {
+            return false;
+        };
+        let mr = MillerRabin::new(odd.clone());
+        if !mr.test_base_two().is_probably_prime() {
+            return false;
+        }
+        if self.lucas && !lucas_test(odd, SelfridgeBase, LucasCheck::Strong).is_probably_prime() {
+            return false;
+        }
+        (0..self.miller_rabin_rounds).all(|_| mr.test_random_base(rng).is_probably_prime())
+    }
+
+    fn is_safe_prime<R: CryptoRngCore>(&self, rng: &mut R, candidate: &BoxedUint) -> bool {
+        if *self == Self::default() {
+            return is_safe_prime_with_rng(rng, candidate);
+        }
+        self.is_prime(rng, candidate) && self.is_prime(rng, &(candidate >> 1))
+    }
+}
+
+/// How the primes of a key are searched: the constraints they must satisfy
+/// and the primality test applied to the candidates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct PrimeSearch {
+    pub(crate) constraints: PrimeConstraints,
+    pub(crate) test: PrimalityTest,
+}
+
+impl PrimeSearch {
+    /// The same search without constraints, for auxiliary primes.
+    fn unconstrained(&self) -> Self {
+        Self {
+            constraints: PrimeConstraints::new(),
+            test: self.test,
+        }
+    }
+}
+
+/// Find a random (safe) prime of the given bit length as configured by
+/// `search`, like [`generate_prime_with_rng`] and
+/// [`generate_safe_prime_with_rng`], reporting every tested candidate.
+fn find_prime<R: CryptoRngCore>(
+    rng: &mut R,
+    bit_length: u32,
+    safe: bool,
+    search: &PrimeSearch,
+    progress: &mut Progress<'_>,
+) -> Result<BoxedUint> {
+    let progress = RefCell::new(progress);
+    let rejected = Cell::new(0);
+    let test = |rng: &mut R, candidate: &BoxedUint| {
+        if !search.constraints.is_satisfied_by(candidate) {
+            rejected.set(rejected.get() + 1);
+            // accept the candidate to end the search once giving up
+            return rejected.get() >= MAX_REJECTED_CANDIDATES;
+        }
+        // accept the candidate to end the search once cancelled
+        if progress
+            .borrow_mut()
+            .report(KeygenEvent::CandidateTested)
+            .is_err()
+        {
+            return true;
+        }
+        if safe {
+            search.test.is_safe_prime(rng, candidate)
+        } else {
+            search.test.is_prime(rng, candidate)
+        }
+    };
+    let prime = if safe {
+        sieve_and_find(
+            rng,
+            SmallPrimesSieveFactory::new_safe_primes(bit_length, SetBits::TwoMsb),
+            test,
+        )
+    } else {
+        sieve_and_find(
+            rng,
+            SmallPrimesSieveFactory::new(bit_length, SetBits::TwoMsb),
+            test,
+        )
+    }
+    .expect("will produce a result eventually");
+    progress.into_inner().check()?;
+    if rejected.get() >= MAX_REJECTED_CANDIDATES {
+        return Err(Error::KeyGenerationFailed {
+            attempts: rejected.get(),
+        });
+    }
+    Ok(prime)
+}
+
+/// Generate a safe prime `p = 2q + 1` (with `q` prime) of the given bit length.
+pub(crate) fn generate_safe_prime_with_rng<R: CryptoRngCore>(
+    rng: &mut R,
+    bit_length: u32,
+) -> BoxedUint {
+    sieve_and_find(
+        rng,
+        SmallPrimesSieveFactory::new_safe_primes(bit_length, SetBits::TwoMsb),
+        is_safe_prime_with_rng,
+    )
+    .expect("will produce a result eventually")
+}
+
+/// Smallest bit length supported by [`generate_strong_prime_with_rng`].
+const STRONG_PRIME_MIN_BITS: u32 = 64;
+
+/// Generate a strong prime of the given bit length with Gordon's algorithm.
+///
+/// Two random primes `s` and `t` are generated, then the first prime
+/// `r = 2it + 1` is searched for. The result is the first prime of the form
+/// `p = p0 + 2jrs` with the two most significant bits set, where
+/// `p0 = 2(s^(r-2) mod r)s - 1`, so that `r | p - 1` and `s | p + 1`.
+///
+/// Fails with [`Error::InvalidArguments`] if `bit_length` is below
+/// [`STRONG_PRIME_MIN_BITS`], and with [`Error::KeyGenerationFailed`] once
+/// [`MAX_REJECTED_CANDIDATES`] candidates for `r` or `p` were rejected.
+pub(crate) fn generate_strong_prime_with_rng<R: CryptoRngCore>(
+    rng: &mut R,
+    bit_length: u32,
+    search: &PrimeSearch,
+    progress: &mut Progress<'_>,
+) -> Result<BoxedUint> {
+    if bit_length < STRONG_PRIME_MIN_BITS {
+        return Err(Error::InvalidArguments);
+    }
+    let unconstrained = search.unconstrained();
+    // r and s get about half of the bits each, leaving about 20 bits for j
+    let s_bits = bit_length / 2 - 8;
+    let t_bits = bit_length / 2 - 16;
+
+    let precision = bit_length + 64;
+    let one = BoxedUint::one_with_precision(precision);
+    let two = one.shl1();
+    let lower = pow2(bit_length - 1, precision).wrapping_add(&pow2(bit_length - 2, precision));
+    let upper = pow2(bit_length, precision);
+    let mut rejected = 0;
+
+    loop {
+        let s = resize(
+            &find_prime(rng, s_bits, false, &unconstrained, progress)?,
+            precision,
+        );
+        let t = resize(
+            &find_prime(rng, t_bits, false, &unconstrained, progress)?,
+            precision,
+        );
+
+        let two_t = t.shl1();
+        let mut r = two_t.wrapping_add(&one);
+        loop {
+            progress.report(KeygenEvent::CandidateTested)?;
+            if search.test.is_prime(rng, &r) {
+                break;
+            }
+            rejected += 1;
+            if rejected >= MAX_REJECTED_CANDIDATES {
+                return Err(Error::KeyGenerationFailed { attempts: rejected });
+            }
+            r = r.wrapping_add(&two_t);
+        }
+
+        let r_params = monty_params(&r).expect("r is an odd prime");
+        let u = pow_mod(&s, &r.wrapping_sub(&two), &r_params);
+        let p0 = u.wrapping_mul(&s).shl1().wrapping_sub(&one);
+        let two_rs = r.wrapping_mul(&s).shl1();
+
+        // smallest p = p0 + 2jrs which is at least `lower`
+        let step = NonZero::new(two_rs.clone()).expect("non zero");
+        let j = lower
+            .wrapping_sub(&p0)
+            .wrapping_add(&two_rs)
+            .wrapping_sub(&one)
+            / step;
+        let mut p = p0.wrapping_add(&j.wrapping_mul(&two_rs));
+
+        while p < upper {
+            let p_short = p.shorten(bit_length);
+            if search.constraints.is_satisfied_by(&p_short) {
+                progress.report(KeygenEvent::CandidateTested)?;
+                if search.test.is_prime(rng, &p_short) {
+                    return Ok(p_short);
+                }
+            } else {
+                rejected += 1;
+                if rejected >= MAX_REJECTED_CANDIDATES {
+                    return Err(Error::KeyGenerationFailed { attempts: rejected });
+                }
+            }
+            p = p.wrapping_add(&two_rs);
+        }
+    }
+}
+
+/// Bit length of the auxiliary primes `p1`, `p2`, `q1` and `q2` for a modulus
+/// of `nlen` bits.
+///
+/// FIPS 186-5 Table A.1 requires them to be longer than 140, 170 and 200 bits
+/// for 2048, 3072 and 4096-bit moduli respectively, with the combined length
+/// of each pair below 1007, 1518 and 2030 bits.
+fn fips186_auxiliary_prime_bits(nlen: usize) -> Result<u32> {
+    match nlen {
+        0..=2047 => Err(Error::InvalidArguments),
+        2048..=3071 => Ok(141),
+        3072..=4095 => Ok(171),
+        _ => Ok(201),
+    }
+}
+
+/// Generates a 2-prime RSA keypair following FIPS 186-5 Appendix A.1.6.
+fn generate_fips186_key_with_exp<R: CryptoRngCore>(
+    rng: &mut R,
+    bit_size: usize,
+    exp: BoxedUint,
+    search: &PrimeSearch,
+    progress: &mut Progress<'_>,
+) -> Result<RsaPrivateKeyComponents> {
+    let auxiliary_bits = fips186_auxiliary_prime_bits(bit_size)?;
+    if bit_size % 2 != 0 {
+        return Err(Error::InvalidArguments);
+    }
+    if !bool::from(exp.is_odd()) {
+        return Err(Error::InvalidExponent);
+    }
+    if exp.bits() <= 16 {
+        return Err(Error::PublicExponentTooSmall);
+    }
+    if exp.bits() > 256 {
+        return Err(Error::PublicExponentTooLarge);
+    }
+
+    let length = (bit_size / 2) as u32;
+    let precision = length + 64;
+    let bound = pow2(length - 100, precision);
+    let e = resize(&exp, precision);
+    let mut attempts = 0usize;
+
+    loop {
+        if attempts == MAX_ATTEMPTS {
+            return Err(Error::KeyGenerationFailed { attempts });
+        }
+        if attempts > 0 {
+            progress.report(KeygenEvent::Restarted)?;
+        }
+        attempts += 1;
+
+        let Some((p, xp)) = fips186_prime(rng, length, auxiliary_bits, &e, search, progress)?
+        else {
+            continue;
+        };
+        progress.report(KeygenEvent::PrimeFound {
+            index: 0,
+            bits: length,
+        })?;
+        let Some((q, xq)) = fips186_prime(rng, length, auxiliary_bits, &e, search, progress)?
+        else {
+            continue;
+        };
+        progress.report(KeygenEvent::PrimeFound {
+            index: 1,
+            bits: length,
+        })?;
+        // |p - q| and |X_p - X_q| must both exceed 2^(nlen/2 - 100)
+        if abs_diff(&p, &q) <= bound || abs_diff(&xp, &xq) <= bound {
+            continue;
+        }
+
+        let primes = vec![p.shorten(length), q.shorten(length)];
+        let n = compute_modulus(&primes)?;
+        if n.bits() as usize != bit_size {
+            continue;
+        }
+        let Ok(d) = compute_private_exponent_carmicheal(&primes[0], &primes[1], &exp) else {
+            continue;
+        };
+        // d > 2^(nlen/2)
+        if d.bits() <= length {
+            continue;
+        }
+
+        return Ok(RsaPrivateKeyComponents {
+            n,
+            e: exp,
+            d,
+            primes,
+        });
+    }
+}
+
+/// Generate a probable prime of `length` bits from two auxiliary primes of
+/// `auxiliary_bits` bits, following FIPS 186-5 Appendix A.1.6 / B.3.6 and
+/// the procedure of FIPS 186-4 Appendix C.9.
+///
+/// Returns the prime `Y` together with the random starting point `X`, or
+/// `None` if the search failed and has to be restarted.
+fn fips186_prime<R: CryptoRngCore>(
+    rng: &mut R,
+    length: u32,
+    auxiliary_bits: u32,
+    e: &BoxedUint,
+    search: &PrimeSearch,
+    progress: &mut Progress<'_>,
+) -> Result<Option<(BoxedUint, BoxedUint)>> {
+    let precision = e.bits_precision();
+    let one = BoxedUint::one_with_precision(precision);
+    let unconstrained = search.unconstrained();
+
+    let r1 = resize(
+        &find_prime(rng, auxiliary_bits, false, &unconstrained, progress)?,
+        precision,
+    );
+    let r2 = resize(
+        &find_prime(rng, auxiliary_bits, false, &unconstrained, progress)?,
+        precision,
+    );
+    // GCD(2r1, r2) = 1
+    if r1 == r2 {
+        return Ok(None);
+    }
+    let two_r1 = r1.shl1();
+    let m = NonZero::new(two_r1.wrapping_mul(&r2)).expect("non zero");
+
+    // R ≡ 1 (mod 2r1) and R ≡ -1 (mod r2), reduced modulo 2r1r2
+    let (Some(a), Some(b)) = (
+        r2.inv_mod(&two_r1).into_option(),
+        two_r1.inv_mod(&r2).into_option(),
+    ) else {
+        return Ok(None);
+    };
+    let a = a.wrapping_mul(&r2);
+    let b = b.wrapping_mul(&two_r1);
+    let big_r = reduce(&a.wrapping_add(m.as_ref()).wrapping_sub(&b), &m);
+
+    let upper = pow2(length, precision);
+    let lower_square = pow2(2 * length - 1, 2 * precision);
+    let mut bytes = vec![0u8; length.div_ceil(8) as usize];
+
+    loop {
+        // sqrt(2) · 2^(length - 1) ≤ X < 2^length
+        rng.fill_bytes(&mut bytes);
+        truncate_bits(&mut bytes, length);
+        let x = BoxedUint::from_be_slice(&bytes, precision)
+            .expect("fits")
+            .bitor(&pow2(length - 1, precision));
+        let x_wide = resize(&x, 2 * precision);
+        if x_wide.wrapping_mul(&x_wide) < lower_square {
+            continue;
+        }
+
+        // Y = X + ((R - X) mod 2r1r2)
+        let offset = reduce(
+            &big_r.wrapping_add(m.as_ref()).wrapping_sub(&reduce(&x, &m)),
+            &m,
+        );
+        let mut y = x.wrapping_add(&offset);
+
+        for _ in 0..5 * length {
+            if y >= upper {
+                break;
+            }
+            if bool::from(y.wrapping_sub(&one).gcd(e).is_one())
+                && search.constraints.is_satisfied_by(&y)
+            {
+                progress.report(KeygenEvent::CandidateTested)?;
+                if search.test.is_prime(rng, &y) {
+                    return Ok(Some((y, x)));
+                }
+            }
+            y = y.wrapping_add(m.as_ref());
+        }
+        if y < upper {
+            // 5 · nlen/2 candidates tested without success
+            return Ok(None);
+        }
+    }
+}
+
+/// Whether `|p - q| > 2^(nlen/2 - 100)`, as required by FIPS 186-5 A.1.1.
+///
+/// For moduli of at most 200 bits the primes only have to differ.
+fn primes_far_apart(p: &BoxedUint, q: &BoxedUint, bit_size: usize) -> bool {
+    let precision = core::cmp::max(p.bits_precision(), q.bits_precision());
+    let diff = abs_diff(&resize(p, precision), &resize(q, precision));
+    match (bit_size / 2).checked_sub(100) {
+        Some(bits) if bits > 0 => exceeds_pow2(&diff, bits as u32),
+        _ => !bool::from(diff.is_zero()),
+    }
+}
+
+/// Whether `x > 2^k`.
+fn exceeds_pow2(x: &BoxedUint, k: u32) -> bool {
+    k < x.bits_precision() && *x > pow2(k, x.bits_precision())
+}
+
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn abs_diff(a: &BoxedUint, b: &BoxedUint) -> BoxedUint {
+    if a > b {
+        a.wrapping_sub(b)
+    } else {
+        b.wrapping_sub(a)
+    }
+}
+
+/// Construction certificate of a provable prime.
+///
+/// The prime was generated with the Shawe-Taylor algorithm
+/// ([FIPS 186-5] Appendix A.1.2 / [FIPS 186-4] Appendix C.6) from the
+/// recorded seed. Anyone can check the primality proof by running the
+/// construction again with [`PrimeCertificate::verify`].
+///
+/// [FIPS 186-5]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-5.pdf
+/// [FIPS 186-4]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.186-4.pdf
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrimeCertificate {
+    length: u32,
+    input_seed: Vec<u8>,
+    prime_seed: Vec<u8>,
+    prime_gen_counter: u64,
+}
+
+impl PrimeCertificate {
+    /// Bit length of the prime.
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    /// Seed the construction started from.
+    pub fn input_seed(&self) -> &[u8] {
+        &self.input_seed
+    }
+
+    /// Seed after the construction finished.
+    pub fn prime_seed(&self) -> &[u8] {
+        &self.prime_seed
+    }
+
+    /// Number of candidates tested during the construction.
+    pub fn prime_gen_counter(&self) -> u64 {
+        self.prime_gen_counter
+    }
+
+    /// Check that `prime` is the result of the construction recorded in this
+    /// certificate, using the same digest `D` it was generated with.
+    pub fn verify<D: Digest>(&self, prime: &BoxedUint) -> bool {
+        match st_random_prime::<D>(self.length, &self.input_seed) {
+            Some((c, prime_seed, prime_gen_counter)) => {
+                let bits = core::cmp::max(c.bits_precision(), prime.bits_precision());
+                resize(&c, bits) == resize(prime, bits)
+                    && prime_seed == self.prime_seed
+                    && prime_gen_counter == self.prime_gen_counter
+            }
+            None => false,
+        }
+    }
+}
+
+/// Generates a 2-prime RSA keypair from provable primes, together with the
+/// construction certificates of `p` and `q`.
+///
+/// Each prime is constructed with the Shawe-Taylor algorithm from a seed of
+/// twice the security strength of the modulus drawn from `rng`, as required
+/// by FIPS 186-5 A.1.2. Candidates which are too small for the modulus to
+/// have exactly `bit_size` bits, for which `p - 1` is not coprime to `exp`,
+/// which are too close to the first prime (see [`primes_far_apart`]), or for
+/// which `d = exp^-1 mod λ(n)` is not above `2^(bit_size/2)`, are discarded
+/// and the construction continues from the final seed of the previous
+/// attempt.
+pub(crate) fn generate_provable_key_with_exp<D: Digest, R: CryptoRngCore>(
+    rng: &mut R,
+    bit_size: usize,
+    exp: BoxedUint,
+) -> Result<(RsaPrivateKeyComponents, Vec<PrimeCertificate>)> {
+    if bit_size < 66 || bit_size % 2 != 0 {
+        return Err(Error::InvalidArguments);
+    }
+    check_size_cap(bit_size)?;
+    let length = (bit_size / 2) as u32;
+
+    let mut seed = vec![0u8; 2 * fips186_security_strength(bit_size) / 8];
+    rng.fill_bytes(&mut seed);
+
+    let mut primes = Vec::with_capacity(2);
+    let mut certificates = Vec::with_capacity(2);
+    while primes.len() < 2 {
+        let (prime, prime_seed, prime_gen_counter) =
+            st_random_prime::<D>(length, &seed).ok_or(Error::InvalidPrime)?;
+        let certificate = PrimeCertificate {
+            length,
+            input_seed: core::mem::replace(&mut seed, prime_seed.clone()),
+            prime_seed,
+            prime_gen_counter,
+        };
+
+        // p ≥ √2 · 2^(length - 1), so that n has exactly `bit_size` bits
+        let mut square = prime.clone();
+        square *= &prime;
+        if square.bits() != 2 * length {
+            continue;
+        }
+        let exp_wide = resize(
+            &exp,
+            core::cmp::max(exp.bits_precision(), prime.bits_precision()),
+        );
+        let p_minus_one = resize(&prime, exp_wide.bits_precision()).wrapping_sub(&BoxedUint::one());
+        if !bool::from(p_minus_one.gcd(&exp_wide).is_one()) {
+            continue;
+        }
+        if let Some(p) = primes.first() {
+            if !primes_far_apart(p, &prime, bit_size) {
+                continue;
+            }
+            // d = e^-1 mod λ(n) and d > 2^(nlen/2), as FIPS 186-5 A.1.1
+            // requires
+            if !compute_private_exponent_carmicheal(p, &prime, &exp)
+                .is_ok_and(|d| exceeds_pow2(&d, length))
+            {
+                continue;
+            }
+        }
+
+        primes.push(prime);
+        certificates.push(certificate);
+    }
+
+    let n = compute_modulus(&primes)?;
+    let d = compute_private_exponent_carmicheal(&primes[0], &primes[1], &exp)?;
+    Ok((
+        RsaPrivateKeyComponents {
+            n,
+            e: exp,
+            d,
+            primes,
+        },
+        certificates,
+    ))
+}
+
+/// Security strength in bits of a modulus of `nlen` bits, as estimated by
+/// NIST SP 800-56B Rev. 2 Table 2.
+///
+/// Moduli below 2048 bits, which FIPS 186-5 does not allow, get the strength
+/// of 2048-bit moduli.
+fn fips186_security_strength(nlen: usize) -> usize {
+    match nlen {
+        0..=3071 => 112,
+        3072..=4095 => 128,
+        4096..=6143 => 152,
+        6144..=8191 => 176,
+        _ => 200,
+    }
+}
+
+/// Shawe-Taylor random prime construction (FIPS 186-4 Appendix C.6).
+///
+/// Returns the prime, the final seed and the generation counter, or `None`
+/// if the construction failed.
+fn st_random_prime<D: Digest>(length: u32, input_seed: &[u8]) -> Option<(BoxedUint, Vec<u8>, u64)> {
+    if length < 2 {
+        return None;
+    }
+
+    if length < 33 {
+        let mut prime_seed = input_seed.to_vec();
+        let mut prime_gen_counter = 0u64;
+        loop {
+            let h0 = D::digest(&prime_seed);
+            let h1 = D::digest(seed_add(&prime_seed, 1));
+            let mut low = [0u8; 8];
+            for (i, b) in low.iter_mut().rev().enumerate() {
+                *b = h0[h0.len() - 1 - i] ^ h1[h1.len() - 1 - i];
+            }
+            let top = 1u64 << (length - 1);
+            let c = (top + (u64::from_be_bytes(low) % top)) | 1;
+
+            prime_gen_counter += 1;
+            prime_seed = seed_add(&prime_seed, 2);
+
+            if is_small_prime(c) {
+                return Some((BoxedUint::from(c), prime_seed, prime_gen_counter));
+            }
+            if prime_gen_counter > 4 * u64::from(length) {
+                return None;
+            }
+        }
+    }
+
+    let (c0, mut prime_seed, mut prime_gen_counter) =
+        st_random_prime::<D>(length.div_ceil(2) + 1, input_seed)?;
+
+    let outlen = (<D as Digest>::output_size() * 8) as u32;
+    let iterations = u64::from(length.div_ceil(outlen) - 1);
+    let old_counter = prime_gen_counter;
+    let precision = length + 2 * outlen;
+    let one = BoxedUint::one_with_precision(precision);
+
+    // x = 2^(length - 1) + (hash mod 2^(length - 1))
+    let mut x = hash_expand::<D>(&prime_seed, iterations);
+    truncate_bits(&mut x, length - 1);
+    let x = BoxedUint::from_be_slice(&x, precision)
+        .ok()?
+        .wrapping_add(&pow2(length - 1, precision));
+    prime_seed = seed_add(&prime_seed, iterations + 1);
+
+    let two_c0 = resize(&c0, precision).shl1();
+    let two_c0_nz = NonZero::new(two_c0.clone()).into_option()?;
+    let ceil_div = |a: &BoxedUint| a.wrapping_add(&two_c0).wrapping_sub(&one) / two_c0_nz.clone();
+    let mut t = ceil_div(&x);
+
+    loop {
+        if two_c0.wrapping_mul(&t).wrapping_add(&one) > pow2(length, precision) {
+            t = ceil_div(&pow2(length - 1, precision));
+        }
+        let c = two_c0.wrapping_mul(&t).wrapping_add(&one);
+        prime_gen_counter += 1;
+
+        let a = hash_expand::<D>(&prime_seed, iterations);
+        prime_seed = seed_add(&prime_seed, iterations + 1);
+        let a = BoxedUint::from_be_slice(&a, (a.len() * 8) as u32).ok()?;
+        let c_minus_three =
+            NonZero::new(c.wrapping_sub(&BoxedUint::from(3u64).widen(precision))).into_option()?;
+        let a = reduce(&a, &c_minus_three).wrapping_add(&BoxedUint::from(2u64).widen(precision));
+
+        let params = monty_params(&c).ok()?;
+        let z = pow_mod(&a, &t.shl1(), &params);
+        let z_minus_one = z.wrapping_sub(&one);
+        if !bool::from(z_minus_one.is_zero())
+            && bool::from(z_minus_one.gcd(&c).is_one())
+            && bool::from(pow_mod(&z, &c0, &params).is_one())
+        {
+            let c = c.shorten(length);
+            return Some((c, prime_seed, prime_gen_counter));
+        }
+
+        if prime_gen_counter >= 4 * u64::from(length) + old_counter {
+            return None;
+        }
+        t = t.wrapping_add(&one);
+    }
+}
+
+/// `Hash(seed + iterations) || ... || Hash(seed + 0)`, i.e. the sum of
+/// `Hash(seed + i) · 2^(i · outlen)` in big-endian byte order.
+fn hash_expand<D: Digest>(seed: &[u8], iterations: u64) -> Vec<u8> {
+    let mut out = Vec::new();
+    for i in (0..=iterations).rev() {
+        out.extend_from_slice(&D::digest(seed_add(seed, i)));
+    }
+    out
+}
+
+/// Add `k` to the big-endian integer `seed`, modulo `2^(8 · seed.len())`.
+fn seed_add(seed: &[u8], k: u64) -> Vec<u8> {
+    let mut out = seed.to_vec();
+    let mut carry = u128::from(k);
+    for byte in out.iter_mut().rev() {
+        if carry == 0 {
+            break;
+        }
+        let sum = u128::from(*byte) + (carry & 0xff);
+        *byte = sum as u8;
+        carry = (carry >> 8) + (sum >> 8);
+    }
+    out
+}
+
+/// Clear all bits of the big-endian integer `bytes` at positions `≥ bits`.
+fn truncate_bits(bytes: &mut [u8], bits: u32) {
+    let total = bytes.len() as u32 * 8;
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        let msb = total - 8 * i as u32;
+        if msb <= bits {
+            break;
+        }
+        let keep = bits.saturating_sub(msb - 8);
+        *byte &= ((1u16 << keep) - 1) as u8;
+    }
+}
+
+/// Deterministic primality test for values below `2^33` by trial division.
+fn is_small_prime(c: u64) -> bool {
+    if c < 2 {
+        return false;
+    }
+    let mut i = 2u64;
+    while i * i <= c {
+        if c % i == 0 {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -184,6 +1368,10 @@ mod tests {
                             .unwrap();
                     assert_eq!(components.n.bits(), $size);
                     assert_eq!(components.primes.len(), $multi);
+                    let nprimes: usize = $multi;
+                    if nprimes == 2 {
+                        assert!(components.d.bits() as usize > $size / 2);
+                    }
                 }
             }
         };
@@ -201,6 +1389,444 @@ mod tests {
     // TODO: reenable, currently slow
     // key_generation!(key_generation_multi_16_1024, 16, 1024);
 
+    #[test]
+    fn test_safe_prime_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let components = generate_multi_prime_key_with_strength(
+            &mut rng,
+            2,
+            256,
+            BoxedUint::from(EXP),
+            PrimeStrength::Safe,
+        )
+        .unwrap();
+        assert_eq!(components.n.bits(), 256);
+        for prime in &components.primes {
+            assert!(is_safe_prime_with_rng(&mut rng, prime));
+        }
+    }
+
+    #[test]
+    fn test_strong_prime() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        for bit_length in [64, 128, 200] {
+            let p = generate_strong_prime_with_rng(
+                &mut rng,
+                bit_length,
+                &PrimeSearch::default(),
+                &mut Progress::new(&mut |_| ControlFlow::Continue(())),
+            )
+            .unwrap();
+            assert_eq!(p.bits(), bit_length);
+            assert!(is_prime_with_rng(&mut rng, &p));
+        }
+
+        assert_eq!(
+            generate_strong_prime_with_rng(
+                &mut rng,
+                STRONG_PRIME_MIN_BITS - 1,
+                &PrimeSearch::default(),
+                &mut Progress::new(&mut |_| ControlFlow::Continue(())),
+            ),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    fn test_strong_prime_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let exp = BoxedUint::from(EXP);
+        let components = generate_multi_prime_key_with_strength(
+            &mut rng,
+            2,
+            256,
+            exp.clone(),
+            PrimeStrength::Strong,
+        )
+        .unwrap();
+        assert_eq!(components.n.bits(), 256);
+
+        assert!(generate_multi_prime_key_with_strength(
+            &mut rng,
+            2,
+            96,
+            exp,
+            PrimeStrength::Strong
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_fips186_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let exp = BoxedUint::from(EXP);
+        let components = generate_multi_prime_key_with_strength(
+            &mut rng,
+            2,
+            2048,
+            exp.clone(),
+            PrimeStrength::Fips186,
+        )
+        .unwrap();
+        assert_eq!(components.n.bits(), 2048);
+        for prime in &components.primes {
+            assert_eq!(prime.bits(), 1024);
+            assert!(is_prime_with_rng(&mut rng, prime));
+        }
+        assert!(components.d.bits() > 1024);
+
+        for (nprimes, bit_size, exp) in [
+            (2, 1024, BoxedUint::from(EXP)),
+            (3, 3072, BoxedUint::from(EXP)),
+            (2, 2048, BoxedUint::from(3u64)),
+            (2, 2048, BoxedUint::from(65536u64)),
+        ] {
+            assert!(generate_multi_prime_key_with_strength(
+                &mut rng,
+                nprimes,
+                bit_size,
+                exp,
+                PrimeStrength::Fips186
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn test_primes_far_apart() {
+        let p = BoxedUint::from(1_000_003u64);
+        assert!(!primes_far_apart(&p, &p, 128));
+        assert!(primes_far_apart(&p, &BoxedUint::from(1_000_033u64), 128));
+
+        // nlen = 220: |p - q| must exceed 2^10
+        let q = p.wrapping_add(&BoxedUint::from(1024u64));
+        assert!(!primes_far_apart(&p, &q, 220));
+        let q = p.wrapping_add(&BoxedUint::from(1026u64));
+        assert!(primes_far_apart(&p, &q, 220));
+    }
+
+    #[test]
+    fn test_exceeds_pow2() {
+        assert!(!exceeds_pow2(&BoxedUint::from(1024u64), 10));
+        assert!(exceeds_pow2(&BoxedUint::from(1025u64), 10));
+        assert!(!exceeds_pow2(&BoxedUint::from(u64::MAX), 64));
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let mut candidates = 0;
+        let mut found = Vec::new();
+        let mut callback = |event: KeygenEvent| {
+            match event {
+                KeygenEvent::CandidateTested => candidates += 1,
+                KeygenEvent::PrimeFound { index, bits } => found.push((index, bits)),
+                KeygenEvent::Restarted => found.clear(),
+            }
+            ControlFlow::Continue(())
+        };
+        generate_multi_prime_key_with_progress(
+            &mut rng,
+            2,
+            512,
+            BoxedUint::from(EXP),
+            PrimeStrength::Probable,
+            &PrimeSearch::default(),
+            &mut Progress::new(&mut callback),
+        )
+        .unwrap();
+        assert!(candidates >= 2);
+        assert_eq!(found, [(0, 256), (1, 256)]);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        for strength in [
+            PrimeStrength::Probable,
+            PrimeStrength::Safe,
+            PrimeStrength::Strong,
+        ] {
+            let mut candidates = 0;
+            let mut callback = |event: KeygenEvent| match event {
+                KeygenEvent::CandidateTested if candidates == 3 => ControlFlow::Break(()),
+                KeygenEvent::CandidateTested => {
+                    candidates += 1;
+                    ControlFlow::Continue(())
+                }
+                _ => ControlFlow::Continue(()),
+            };
+            let result = generate_multi_prime_key_with_progress(
+                &mut rng,
+                2,
+                1024,
+                BoxedUint::from(EXP),
+                strength,
+                &PrimeSearch::default(),
+                &mut Progress::new(&mut callback),
+            );
+            assert!(matches!(result, Err(Error::KeyGenerationCancelled)));
+        }
+    }
+
+    #[test]
+    fn test_prime_constraints() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let constraints = PrimeConstraints::new()
+            .congruent(3, 4)
+            .not_congruent(1, 3)
+            .top_bits(0b1101, 4);
+        for strength in [PrimeStrength::Probable, PrimeStrength::Strong] {
+            let components = generate_multi_prime_key_with_progress(
+                &mut rng,
+                2,
+                256,
+                BoxedUint::from(EXP),
+                strength,
+                &PrimeSearch {
+                    constraints: constraints.clone(),
+                    test: PrimalityTest::default(),
+                },
+                &mut Progress::new(&mut |_| ControlFlow::Continue(())),
+            )
+            .unwrap();
+            for p in &components.primes {
+                assert_eq!(rem_u64(p, 4), 3);
+                assert_eq!(rem_u64(p, 3), 2);
+                assert_eq!(low_u64(&(p >> 124)), 0b1101);
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_prime_constraints() {
+        for constraints in [
+            PrimeConstraints::new().congruent(2, 4),
+            PrimeConstraints::new().congruent(5, 4),
+            PrimeConstraints::new().not_congruent(0, 0),
+            PrimeConstraints::new().top_bits(0b1001, 4),
+            PrimeConstraints::new().top_bits(0b11, 3),
+            PrimeConstraints::new().top_bits(0b1, 1),
+            PrimeConstraints::new().congruent(3, 6),
+            PrimeConstraints::new().congruent(1, 4).congruent(3, 8),
+            PrimeConstraints::new().congruent(1, 4).not_congruent(1, 2),
+            PrimeConstraints::new().congruent(5, 12).not_congruent(2, 3),
+        ] {
+            assert_eq!(constraints.validate(128), Err(Error::InvalidArguments));
+        }
+        assert_eq!(
+            PrimeConstraints::new().top_bits(0b11, 2).validate(1),
+            Err(Error::InvalidArguments)
+        );
+        PrimeConstraints::new()
+            .congruent(3, 4)
+            .top_bits(u64::MAX, 64)
+            .validate(128)
+            .unwrap();
+        PrimeConstraints::new()
+            .congruent(3, 4)
+            .congruent(7, 12)
+            .not_congruent(2, 5)
+            .validate(128)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_unsatisfiable_prime_constraints() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        // no 64-bit prime has these top bits
+        let search = PrimeSearch {
+            constraints: PrimeConstraints::new().top_bits(u64::MAX, 64),
+            test: PrimalityTest::new(),
+        };
+        assert_eq!(
+            find_prime(
+                &mut rng,
+                64,
+                false,
+                &search,
+                &mut Progress::new(&mut |_| ControlFlow::Continue(())),
+            ),
+            Err(Error::KeyGenerationFailed {
+                attempts: MAX_REJECTED_CANDIDATES
+            })
+        );
+    }
+
+    #[test]
+    fn test_primality_test() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let tests = [
+            PrimalityTest::new(),
+            PrimalityTest::with_error_bound(128),
+            PrimalityTest::new().lucas(false).miller_rabin_rounds(10),
+            PrimalityTest::new().miller_rabin_rounds(0),
+        ];
+        assert_eq!(tests[1].error_bound_bits(), 128);
+
+        // 2^127 - 1 and its safe prime cousin 2 * 1019 + 1
+        let mersenne = BoxedUint::from(u128::MAX >> 1);
+        let safe = BoxedUint::from(2039u64);
+        // Carmichael number and a strong pseudoprime to base 2
+        let composites = [561u64, 2047, 3215031751, 1 << 40];
+        for test in tests {
+            assert!(test.is_prime(&mut rng, &mersenne));
+            assert!(test.is_prime(&mut rng, &BoxedUint::from(3u64)));
+            assert!(test.is_safe_prime(&mut rng, &safe));
+            assert!(!test.is_safe_prime(&mut rng, &mersenne));
+            for c in composites {
+                assert!(!test.is_prime(&mut rng, &BoxedUint::from(c)));
+            }
+        }
+
+        let components = generate_multi_prime_key_with_progress(
+            &mut rng,
+            2,
+            512,
+            BoxedUint::from(EXP),
+            PrimeStrength::Probable,
+            &PrimeSearch {
+                constraints: PrimeConstraints::new(),
+                test: PrimalityTest::with_error_bound(100),
+            },
+            &mut Progress::new(&mut |_| ControlFlow::Continue(())),
+        )
+        .unwrap();
+        assert_eq!(components.n.bits(), 512);
+    }
+
+    /// A pool of precomputed primes.
+    struct PrimePool(Vec<BoxedUint>);
+
+    impl PrimeSource for PrimePool {
+        fn generate_prime<R: CryptoRngCore>(
+            &mut self,
+            _rng: &mut R,
+            bit_length: u32,
+        ) -> Result<BoxedUint> {
+            let i = self
+                .0
+                .iter()
+                .position(|p| p.bits() == bit_length)
+                .ok_or(Error::InvalidPrime)?;
+            Ok(self.0.remove(i))
+        }
+    }
+
+    #[test]
+    fn test_prime_source() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let p = generate_prime_with_rng(&mut rng, 128);
+        let q = generate_prime_with_rng(&mut rng, 128);
+        let mut noop = |_: KeygenEvent| ControlFlow::Continue(());
+
+        // the first candidate key with p twice is skipped
+        let mut pool = PrimePool(vec![p.clone(), p.clone(), q.clone(), p.clone()]);
+        let components = generate_multi_prime_key_with_source(
+            &mut rng,
+            2,
+            256,
+            BoxedUint::from(EXP),
+            &mut pool,
+            &mut Progress::new(&mut noop),
+        )
+        .unwrap();
+        assert!(pool.0.is_empty());
+        assert_eq!(components.primes, [q, p.clone()]);
+
+        // an exhausted pool fails
+        let mut pool = PrimePool(vec![p.clone(), p]);
+        assert_eq!(
+            generate_multi_prime_key_with_source(
+                &mut rng,
+                2,
+                256,
+                BoxedUint::from(EXP),
+                &mut pool,
+                &mut Progress::new(&mut noop),
+            )
+            .err(),
+            Some(Error::InvalidPrime)
+        );
+
+        let mut source =
+            SievePrimeSource::new().constraints(PrimeConstraints::new().congruent(3, 4));
+        let components = generate_multi_prime_key_with_source(
+            &mut rng,
+            3,
+            384,
+            BoxedUint::from(EXP),
+            &mut source,
+            &mut Progress::new(&mut noop),
+        )
+        .unwrap();
+        assert_eq!(components.n.bits(), 384);
+        for p in &components.primes {
+            assert_eq!(rem_u64(p, 4), 3);
+        }
+    }
+
+    #[test]
+    fn test_attempts_bounded() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        // every p - 1 is even
+        assert_eq!(
+            generate_multi_prime_key_with_exp(&mut rng, 2, 64, BoxedUint::from(4u64)).err(),
+            Some(Error::KeyGenerationFailed {
+                attempts: MAX_ATTEMPTS
+            })
+        );
+    }
+
+    #[test]
+    fn test_seed_add() {
+        assert_eq!(seed_add(&[0x00, 0xff], 1), [0x01, 0x00]);
+        assert_eq!(seed_add(&[0xff, 0xff], 2), [0x00, 0x01]);
+        assert_eq!(seed_add(&[0x12, 0x34], 0x0100), [0x13, 0x34]);
+    }
+
+    #[test]
+    fn test_truncate_bits() {
+        let mut bytes = [0xff; 3];
+        truncate_bits(&mut bytes, 11);
+        assert_eq!(bytes, [0x00, 0x07, 0xff]);
+    }
+
+    #[test]
+    fn test_provable_prime() {
+        let seed = [0x42; 32];
+        for length in [16, 40, 128, 256] {
+            let (prime, _, _) = st_random_prime::<sha2::Sha256>(length, &seed).unwrap();
+            assert_eq!(prime.bits(), length);
+            let mut rng = ChaCha8Rng::from_seed([42; 32]);
+            assert!(is_prime_with_rng(&mut rng, &prime));
+        }
+    }
+
+    #[test]
+    fn test_provable_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let (components, certificates) =
+            generate_provable_key_with_exp::<sha2::Sha256, _>(&mut rng, 512, BoxedUint::from(EXP))
+                .unwrap();
+        assert_eq!(components.n.bits(), 512);
+        // 2 · 112 bits of security strength
+        assert_eq!(certificates[0].input_seed().len(), 28);
+        let [p, q] = &components.primes[..] else {
+            panic!("two primes expected");
+        };
+        assert!(primes_far_apart(p, q, 512));
+        let one = BoxedUint::one_with_precision(p.bits_precision());
+        let exp = BoxedUint::from(EXP).widen(p.bits_precision());
+        for prime in [p, q] {
+            assert!(bool::from(prime.wrapping_sub(&one).gcd(&exp).is_one()));
+        }
+        for (prime, certificate) in components.primes.iter().zip(certificates.iter()) {
+            assert!(certificate.verify::<sha2::Sha256>(prime));
+            assert!(!certificate.verify::<sha2::Sha256>(&prime.wrapping_add(&BoxedUint::one())));
+        }
+    }
+
     #[test]
     fn test_log_approx() {
         let mut rng = ChaCha8Rng::from_seed([42; 32]);
@@ -1,10 +1,13 @@
 //! Mask generation function common to both PSS and OAEP padding
 
-use digest::{Digest, DynDigest, FixedOutputReset};
+use digest::{Digest, FixedOutputReset};
+#[cfg(feature = "alloc")]
+use {digest::DynDigest, zeroize::Zeroizing};
 
 /// Mask generation function.
 ///
 /// Panics if out is larger than 2**32. This is in accordance with RFC 8017 - PKCS #1 B.2.1
+#[cfg(feature = "alloc")]
 pub(crate) fn mgf1_xor(out: &mut [u8], digest: &mut dyn DynDigest, seed: &[u8]) {
     let mut counter = [0u8; 4];
     let mut i = 0;
@@ -13,12 +16,9 @@ pub(crate) fn mgf1_xor(out: &mut [u8], digest: &mut dyn DynDigest, seed: &[u8])
     assert!(out.len() as u64 <= MAX_LEN);
 
     while i < out.len() {
-        let mut digest_input = vec![0u8; seed.len() + 4];
-        digest_input[0..seed.len()].copy_from_slice(seed);
-        digest_input[seed.len()..].copy_from_slice(&counter);
-
-        digest.update(digest_input.as_slice());
-        let digest_output = &*digest.finalize_reset();
+        digest.update(seed);
+        digest.update(&counter);
+        let digest_output = Zeroizing::new(digest.finalize_reset());
         let mut j = 0;
         loop {
             if j >= digest_output.len() || i >= out.len() {
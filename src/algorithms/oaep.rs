@@ -1,14 +1,18 @@
 //! Encryption and Decryption using [OAEP padding](https://datatracker.ietf.org/doc/html/rfc8017#section-7.1).
 //!
-use alloc::boxed::Box;
-use alloc::vec::Vec;
-
-use digest::{Digest, DynDigest, FixedOutputReset};
+#[cfg(feature = "alloc")]
+use {
+    super::mgf::mgf1_xor,
+    alloc::{boxed::Box, vec::Vec},
+    digest::DynDigest,
+    subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption},
+    zeroize::Zeroizing,
+};
+
+use digest::{Digest, FixedOutputReset};
 use rand_core::CryptoRngCore;
-use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
-use zeroize::Zeroizing;
 
-use super::mgf::{mgf1_xor, mgf1_xor_digest};
+use super::mgf::mgf1_xor_digest;
 use crate::errors::{Error, Result};
 
 /// Maximum label size (2^64 bits) for SHA-1 and SHA-256 hash functions.
@@ -18,6 +22,14 @@ use crate::errors::{Error, Result};
 /// for all hash functions.
 const MAX_LABEL_LEN: u64 = 1 << 61;
 
+/// Maximum length of a message encrypted with a modulus of `k` bytes and a
+/// label digest of `h_size` bytes.
+#[inline]
+pub(crate) const fn max_message_len(k: usize, h_size: usize) -> usize {
+    k.saturating_sub(2 * h_size + 2)
+}
+
+#[cfg(feature = "alloc")]
 #[inline]
 fn encrypt_internal<R: CryptoRngCore + ?Sized, MGF: FnMut(&mut [u8], &mut [u8])>(
     rng: &mut R,
@@ -25,13 +37,31 @@ fn encrypt_internal<R: CryptoRngCore + ?Sized, MGF: FnMut(&mut [u8], &mut [u8])>
     p_hash: &[u8],
     h_size: usize,
     k: usize,
-    mut mgf: MGF,
+    mgf: MGF,
 ) -> Result<Zeroizing<Vec<u8>>> {
-    if msg.len() + 2 * h_size + 2 > k {
-        return Err(Error::MessageTooLong);
+    let mut em = Zeroizing::new(vec![0u8; k]);
+    encrypt_internal_into(rng, msg, p_hash, h_size, &mut em, mgf)?;
+    Ok(em)
+}
+
+/// Like [`encrypt_internal`], but writes the encoded message into the whole
+/// of `em`.
+#[inline]
+fn encrypt_internal_into<R: CryptoRngCore + ?Sized, MGF: FnMut(&mut [u8], &mut [u8])>(
+    rng: &mut R,
+    msg: &[u8],
+    p_hash: &[u8],
+    h_size: usize,
+    em: &mut [u8],
+    mut mgf: MGF,
+) -> Result<()> {
+    let k = em.len();
+    let max_len = max_message_len(k, h_size);
+    if msg.len() > max_len {
+        return Err(Error::MessageTooLong { max_len });
     }
 
-    let mut em = Zeroizing::new(vec![0u8; k]);
+    em.fill(0);
 
     let (_, payload) = em.split_at_mut(1);
     let (seed, db) = payload.split_at_mut(h_size);
@@ -46,7 +76,7 @@ fn encrypt_internal<R: CryptoRngCore + ?Sized, MGF: FnMut(&mut [u8], &mut [u8])>
 
     mgf(seed, db);
 
-    Ok(em)
+    Ok(())
 }
 
 /// Encrypts the given message with RSA and the padding scheme from
@@ -56,6 +86,7 @@ fn encrypt_internal<R: CryptoRngCore + ?Sized, MGF: FnMut(&mut [u8], &mut [u8])>
 /// `2 + (2 * hash.size())`.
 ///
 /// [PKCS#1 OAEP]: https://datatracker.ietf.org/doc/html/rfc8017#section-7.1
+#[cfg(feature = "alloc")]
 #[inline]
 pub(crate) fn oaep_encrypt<R: CryptoRngCore + ?Sized>(
     rng: &mut R,
@@ -88,6 +119,7 @@ pub(crate) fn oaep_encrypt<R: CryptoRngCore + ?Sized>(
 /// `2 + (2 * hash.size())`.
 ///
 /// [PKCS#1 OAEP]: https://datatracker.ietf.org/doc/html/rfc8017#section-7.1
+#[cfg(feature = "alloc")]
 #[inline]
 pub(crate) fn oaep_encrypt_digest<
     R: CryptoRngCore + ?Sized,
@@ -99,16 +131,33 @@ pub(crate) fn oaep_encrypt_digest<
     label: Option<Box<[u8]>>,
     k: usize,
 ) -> Result<Zeroizing<Vec<u8>>> {
+    let mut em = Zeroizing::new(vec![0u8; k]);
+    oaep_encrypt_digest_into::<_, D, MGD>(rng, msg, label.as_deref().unwrap_or_default(), &mut em)?;
+    Ok(em)
+}
+
+/// Like [`oaep_encrypt_digest`], but writes the encoded message into the
+/// whole of `em`.
+#[inline]
+pub(crate) fn oaep_encrypt_digest_into<
+    R: CryptoRngCore + ?Sized,
+    D: Digest,
+    MGD: Digest + FixedOutputReset,
+>(
+    rng: &mut R,
+    msg: &[u8],
+    label: &[u8],
+    em: &mut [u8],
+) -> Result<()> {
     let h_size = <D as Digest>::output_size();
 
-    let label = label.unwrap_or_default();
     if label.len() as u64 >= MAX_LABEL_LEN {
         return Err(Error::LabelTooLong);
     }
 
-    let p_hash = D::digest(&label);
+    let p_hash = D::digest(label);
 
-    encrypt_internal(rng, msg, &p_hash, h_size, k, |seed, db| {
+    encrypt_internal_into(rng, msg, &p_hash, h_size, em, |seed, db| {
         let mut mgf_digest = MGD::new();
         mgf1_xor_digest(db, &mut mgf_digest, seed);
         mgf1_xor_digest(seed, &mut mgf_digest, db);
@@ -125,6 +174,7 @@ pub(crate) fn oaep_encrypt_digest<
 /// See `decrypt_session_key` for a way of solving this problem.
 ///
 /// [PKCS#1 OAEP]: https://datatracker.ietf.org/doc/html/rfc8017#section-7.1
+#[cfg(feature = "alloc")]
 #[inline]
 pub(crate) fn oaep_decrypt(
     em: &mut [u8],
@@ -152,9 +202,9 @@ pub(crate) fn oaep_decrypt(
         return Err(Error::Decryption);
     }
 
-    let (out, index) = res.unwrap();
+    let index = res.unwrap();
 
-    Ok(out[index as usize..].to_vec())
+    Ok(em[index as usize..].to_vec())
 }
 
 ///Decrypts OAEP padding.
@@ -167,6 +217,7 @@ pub(crate) fn oaep_decrypt(
 /// See `decrypt_session_key` for a way of solving this problem.
 ///
 /// [PKCS#1 OAEP]: https://datatracker.ietf.org/doc/html/rfc8017#section-7.1
+#[cfg(feature = "alloc")]
 #[inline]
 pub(crate) fn oaep_decrypt_digest<D: Digest, MGD: Digest + FixedOutputReset>(
     em: &mut [u8],
@@ -191,13 +242,14 @@ pub(crate) fn oaep_decrypt_digest<D: Digest, MGD: Digest + FixedOutputReset>(
         return Err(Error::Decryption);
     }
 
-    let (out, index) = res.unwrap();
+    let index = res.unwrap();
 
-    Ok(out[index as usize..].to_vec())
+    Ok(em[index as usize..].to_vec())
 }
 
-/// Decrypts OAEP padding. It returns one or zero in valid that indicates whether the
-/// plaintext was correctly structured.
+/// Decrypts OAEP padding in place. It returns the index of the message in `em`, which is
+/// only set if the plaintext was correctly structured.
+#[cfg(feature = "alloc")]
 #[inline]
 fn decrypt_inner<MGF: FnMut(&mut [u8], &mut [u8])>(
     em: &mut [u8],
@@ -205,7 +257,7 @@ fn decrypt_inner<MGF: FnMut(&mut [u8], &mut [u8])>(
     expected_p_hash: &[u8],
     k: usize,
     mut mgf: MGF,
-) -> Result<CtOption<(Vec<u8>, u32)>> {
+) -> Result<CtOption<u32>> {
     if k < 11 {
         return Err(Error::Decryption);
     }
@@ -242,8 +294,5 @@ fn decrypt_inner<MGF: FnMut(&mut [u8], &mut [u8])>(
 
     let valid = first_byte_is_zero & hash_are_equal & !nonzero_before_one & !looking_for_index;
 
-    Ok(CtOption::new(
-        (em.to_vec(), index + 2 + (h_size * 2) as u32),
-        valid,
-    ))
+    Ok(CtOption::new(index + 2 + (h_size * 2) as u32, valid))
 }
@@ -1,7 +1,7 @@
 //! Special handling for converting the BigUint to u8 vectors
 
 use alloc::vec::Vec;
-use crypto_bigint::BoxedUint;
+use crypto_bigint::{BoxedUint, Limb};
 use zeroize::Zeroizing;
 
 use crate::errors::{Error, Result};
@@ -30,14 +30,41 @@ pub(crate) fn uint_to_be_pad(input: BoxedUint, padded_len: usize) -> Result<Vec<
 /// Converts input to the new vector of the given length, using BE and with 0s left padded.
 /// In some cases BoxedUint might already have leading zeroes, this function removes them
 /// before padding again.
+///
+/// The input, its encoding and the output are all wiped when dropped.
 #[inline]
-pub(crate) fn uint_to_zeroizing_be_pad(input: BoxedUint, padded_len: usize) -> Result<Vec<u8>> {
+pub(crate) fn uint_to_zeroizing_be_pad(
+    input: BoxedUint,
+    padded_len: usize,
+) -> Result<Zeroizing<Vec<u8>>> {
     let leading_zeros = input.leading_zeros() as usize / 8;
 
     let m = Zeroizing::new(input);
     let m = Zeroizing::new(m.to_be_bytes());
 
-    left_pad(&m[leading_zeros..], padded_len)
+    left_pad(&m[leading_zeros..], padded_len).map(Zeroizing::new)
+}
+
+/// Writes input to the start of `out` using BE, with 0s left padded to `padded_len` bytes.
+///
+/// Unlike [`uint_to_be_pad`] no intermediate buffer is allocated. Returns `padded_len`.
+#[inline]
+pub(crate) fn uint_to_be_pad_into(
+    input: &BoxedUint,
+    padded_len: usize,
+    out: &mut [u8],
+) -> Result<usize> {
+    let out = out.get_mut(..padded_len).ok_or(Error::InvalidArguments)?;
+    if input.bits() as usize > padded_len * 8 {
+        return Err(Error::InvalidPadLen);
+    }
+
+    let limbs = input.as_limbs();
+    for (i, byte) in out.iter_mut().rev().enumerate() {
+        let limb = limbs.get(i / Limb::BYTES).map_or(0, |limb| limb.0);
+        *byte = (limb >> (8 * (i % Limb::BYTES))) as u8;
+    }
+    Ok(padded_len)
 }
 
 #[cfg(test)]
@@ -60,4 +87,26 @@ mod tests {
         let padded = left_pad(&input, INPUT_LEN - 1);
         assert!(padded.is_err());
     }
+
+    #[test]
+    fn test_uint_to_be_pad_into() {
+        let input = BoxedUint::from(0x0102_0304_0506_0708_090a_u128);
+        let mut out = [0xffu8; 16];
+        assert_eq!(uint_to_be_pad_into(&input, 12, &mut out), Ok(12));
+        assert_eq!(out[..12], [0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(out[12..], [0xff; 4]);
+        assert_eq!(
+            uint_to_be_pad(input.clone(), 12).unwrap(),
+            out[..12].to_vec()
+        );
+
+        assert_eq!(
+            uint_to_be_pad_into(&input, 9, &mut out),
+            Err(Error::InvalidPadLen)
+        );
+        assert_eq!(
+            uint_to_be_pad_into(&input, 17, &mut out),
+            Err(Error::InvalidArguments)
+        );
+    }
 }
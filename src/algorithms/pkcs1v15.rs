@@ -6,12 +6,13 @@
 //!
 //! [RFC8017 § 8.2]: https://datatracker.ietf.org/doc/html/rfc8017#section-8.2
 
-use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use {alloc::vec::Vec, const_oid::ObjectIdentifier, zeroize::Zeroizing};
+
 use digest::Digest;
 use pkcs8::AssociatedOid;
 use rand_core::CryptoRngCore;
 use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
-use zeroize::Zeroizing;
 
 use crate::errors::{Error, Result};
 
@@ -31,8 +32,15 @@ fn non_zero_random_bytes<R: CryptoRngCore + ?Sized>(rng: &mut R, data: &mut [u8]
     }
 }
 
+/// Maximum length of a message encrypted with a modulus of `k` bytes.
+#[inline]
+pub(crate) const fn pkcs1v15_encrypt_max_message_len(k: usize) -> usize {
+    k.saturating_sub(11)
+}
+
 /// Applied the padding scheme from PKCS#1 v1.5 for encryption.  The message must be no longer than
 /// the length of the public modulus minus 11 bytes.
+#[cfg(feature = "alloc")]
 pub(crate) fn pkcs1v15_encrypt_pad<R>(
     rng: &mut R,
     msg: &[u8],
@@ -41,17 +49,35 @@ pub(crate) fn pkcs1v15_encrypt_pad<R>(
 where
     R: CryptoRngCore + ?Sized,
 {
-    if msg.len() + 11 > k {
-        return Err(Error::MessageTooLong);
+    let mut em = Zeroizing::new(vec![0u8; k]);
+    pkcs1v15_encrypt_pad_into(rng, msg, &mut em)?;
+    Ok(em)
+}
+
+/// Writes the PKCS#1 v1.5 encryption encoding of `msg` into the whole of `em`.
+///
+/// Fails with [`Error::KeyTooSmall`] if `em` can't hold the 8 bytes of
+/// padding required even for an empty message.
+pub(crate) fn pkcs1v15_encrypt_pad_into<R>(rng: &mut R, msg: &[u8], em: &mut [u8]) -> Result<()>
+where
+    R: CryptoRngCore + ?Sized,
+{
+    let k = em.len();
+    if k < 11 {
+        return Err(Error::KeyTooSmall);
+    }
+    let max_len = pkcs1v15_encrypt_max_message_len(k);
+    if msg.len() > max_len {
+        return Err(Error::MessageTooLong { max_len });
     }
 
     // EM = 0x00 || 0x02 || PS || 0x00 || M
-    let mut em = Zeroizing::new(vec![0u8; k]);
+    em[0] = 0;
     em[1] = 2;
     non_zero_random_bytes(rng, &mut em[2..k - msg.len() - 1]);
     em[k - msg.len() - 1] = 0;
     em[k - msg.len()..].copy_from_slice(msg);
-    Ok(em)
+    Ok(())
 }
 
 /// Removes the encryption padding scheme from PKCS#1 v1.5.
@@ -61,23 +87,33 @@ where
 /// learn whether each instance returned an error then they can decrypt and
 /// forge signatures as if they had the private key. See
 /// `decrypt_session_key` for a way of solving this problem.
+#[cfg(feature = "alloc")]
+#[inline]
+pub(crate) fn pkcs1v15_encrypt_unpad(em: &[u8], k: usize) -> Result<Vec<u8>> {
+    let index = pkcs1v15_encrypt_unpad_index(em, k)?;
+    Ok(em[index..].to_vec())
+}
+
+/// Like [`pkcs1v15_encrypt_unpad`], but returns the index of the message in `em`.
+#[cfg(feature = "alloc")]
 #[inline]
-pub(crate) fn pkcs1v15_encrypt_unpad(em: Vec<u8>, k: usize) -> Result<Vec<u8>> {
-    let (valid, out, index) = decrypt_inner(em, k)?;
+pub(crate) fn pkcs1v15_encrypt_unpad_index(em: &[u8], k: usize) -> Result<usize> {
+    let (valid, index) = decrypt_inner(em, k)?;
     if valid == 0 {
         return Err(Error::Decryption);
     }
 
-    Ok(out[index as usize..].to_vec())
+    Ok(index as usize)
 }
 
 /// Removes the PKCS1v15 padding It returns one or zero in valid that indicates whether the
-/// plaintext was correctly structured. In either case, the plaintext is
-/// returned in em so that it may be read independently of whether it was valid
-/// in order to maintain constant memory access patterns. If the plaintext was
-/// valid then index contains the index of the original message in em.
+/// plaintext was correctly structured. The plaintext stays in em so that it may be read
+/// independently of whether it was valid in order to maintain constant memory access
+/// patterns. If the plaintext was valid then index contains the index of the original
+/// message in em.
+#[cfg(feature = "alloc")]
 #[inline]
-fn decrypt_inner(em: Vec<u8>, k: usize) -> Result<(u8, Vec<u8>, u32)> {
+fn decrypt_inner(em: &[u8], k: usize) -> Result<(u8, u32)> {
     if k < 11 {
         return Err(Error::Decryption);
     }
@@ -109,26 +145,38 @@ fn decrypt_inner(em: Vec<u8>, k: usize) -> Result<(u8, Vec<u8>, u32)> {
         first_byte_is_zero & second_byte_is_two & Choice::from(!looking_for_index & 1) & valid_ps;
     index = u32::conditional_select(&0, &(index + 1), valid);
 
-    Ok((valid.unwrap_u8(), em, index))
+    Ok((valid.unwrap_u8(), index))
 }
 
+#[cfg(feature = "alloc")]
 #[inline]
 pub(crate) fn pkcs1v15_sign_pad(prefix: &[u8], hashed: &[u8], k: usize) -> Result<Vec<u8>> {
+    let mut em = vec![0; k];
+    pkcs1v15_sign_pad_into(prefix, hashed, &mut em)?;
+    Ok(em)
+}
+
+/// Writes the PKCS#1 v1.5 signature encoding of `hashed` into the whole of `em`.
+#[cfg(feature = "alloc")]
+pub(crate) fn pkcs1v15_sign_pad_into(prefix: &[u8], hashed: &[u8], em: &mut [u8]) -> Result<()> {
+    let k = em.len();
     let hash_len = hashed.len();
     let t_len = prefix.len() + hashed.len();
     if k < t_len + 11 {
-        return Err(Error::MessageTooLong);
+        return Err(Error::MessageTooLong {
+            max_len: k.saturating_sub(prefix.len() + 11),
+        });
     }
 
     // EM = 0x00 || 0x01 || PS || 0x00 || T
-    let mut em = vec![0xff; k];
+    em.fill(0xff);
     em[0] = 0;
     em[1] = 1;
     em[k - t_len - 1] = 0;
     em[k - t_len..k - hash_len].copy_from_slice(prefix);
     em[k - hash_len..k].copy_from_slice(hashed);
 
-    Ok(em)
+    Ok(())
 }
 
 #[inline]
@@ -158,25 +206,91 @@ pub(crate) fn pkcs1v15_sign_unpad(prefix: &[u8], hashed: &[u8], em: &[u8], k: us
 }
 
 /// prefix = 0x30 <oid_len + 8 + digest_len> 0x30 <oid_len + 4> 0x06 <oid_len> oid 0x05 0x00 0x04 <digest_len>
+#[cfg(feature = "alloc")]
 #[inline]
 pub(crate) fn pkcs1v15_generate_prefix<D>() -> Vec<u8>
+where
+    D: Digest + AssociatedOid,
+{
+    let mut v = vec![0; D::OID.as_bytes().len() + 10];
+    pkcs1v15_generate_prefix_into::<D>(&mut v).expect("buffer fits the prefix");
+    v
+}
+
+/// Like [`pkcs1v15_generate_prefix`], but writes the prefix into the start of
+/// `buf` and returns it.
+pub(crate) fn pkcs1v15_generate_prefix_into<D>(buf: &mut [u8]) -> Result<&[u8]>
 where
     D: Digest + AssociatedOid,
 {
     let oid = D::OID.as_bytes();
     let oid_len = oid.len() as u8;
     let digest_len = <D as Digest>::output_size() as u8;
-    let mut v = vec![
+    let prefix = buf
+        .get_mut(..oid.len() + 10)
+        .ok_or(Error::InvalidArguments)?;
+
+    let (header, rest) = prefix.split_at_mut(6);
+    header.copy_from_slice(&[
         0x30,
         oid_len + 8 + digest_len,
         0x30,
         oid_len + 4,
         0x6,
         oid_len,
-    ];
-    v.extend_from_slice(oid);
-    v.extend_from_slice(&[0x05, 0x00, 0x04, digest_len]);
-    v
+    ]);
+    let (oid_bytes, trailer) = rest.split_at_mut(oid.len());
+    oid_bytes.copy_from_slice(oid);
+    trailer.copy_from_slice(&[0x05, 0x00, 0x04, digest_len]);
+    Ok(prefix)
+}
+
+/// Returns `prefix` without the `NULL` parameters of the digest algorithm, as
+/// some signers encode it, or `None` if it has no parameters.
+///
+/// prefix = 0x30 <oid_len + 6 + digest_len> 0x30 <oid_len + 2> 0x06 <oid_len> oid 0x04 <digest_len>
+#[cfg(feature = "alloc")]
+pub(crate) fn pkcs1v15_prefix_without_params(prefix: &[u8]) -> Option<Vec<u8>> {
+    let len = prefix.len();
+    if len < 10
+        || prefix[0] != 0x30
+        || prefix[2] != 0x30
+        || prefix[len - 4..len - 2] != [0x05, 0x00]
+    {
+        return None;
+    }
+
+    let mut v = Vec::with_capacity(len - 2);
+    v.extend_from_slice(&[
+        0x30,
+        prefix[1].checked_sub(2)?,
+        0x30,
+        prefix[3].checked_sub(2)?,
+    ]);
+    v.extend_from_slice(&prefix[4..len - 4]);
+    v.extend_from_slice(&prefix[len - 2..]);
+    Some(v)
+}
+
+/// Like [`pkcs1v15_generate_prefix`], but for the digest identified by `oid`
+/// with an output of `digest_len` bytes.
+#[cfg(feature = "alloc")]
+pub(crate) fn pkcs1v15_generate_prefix_for_oid(
+    oid: &ObjectIdentifier,
+    digest_len: usize,
+) -> Result<Vec<u8>> {
+    let oid = oid.as_bytes();
+    // All lengths of the `DigestInfo` must fit the short form.
+    let len = u8::try_from(oid.len() + 8 + digest_len)
+        .ok()
+        .filter(|&len| len < 0x80)
+        .ok_or(Error::InvalidArguments)?;
+
+    let mut prefix = Vec::with_capacity(oid.len() + 10);
+    prefix.extend_from_slice(&[0x30, len, 0x30, oid.len() as u8 + 4, 0x6, oid.len() as u8]);
+    prefix.extend_from_slice(oid);
+    prefix.extend_from_slice(&[0x05, 0x00, 0x04, digest_len as u8]);
+    Ok(prefix)
 }
 
 #[cfg(test)]
@@ -202,6 +316,37 @@ mod tests {
         let k = 8;
         let message = vec![1u8; 4];
         let res = pkcs1v15_encrypt_pad(&mut rng, &message, k);
-        assert_eq!(res, Err(Error::MessageTooLong));
+        assert_eq!(res, Err(Error::KeyTooSmall));
+    }
+
+    #[test]
+    fn test_encrypt_tiny_empty_message() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        for k in [0, 1, 2, 10] {
+            let mut em = vec![0u8; k];
+            let res = pkcs1v15_encrypt_pad_into(&mut rng, &[], &mut em);
+            assert_eq!(res, Err(Error::KeyTooSmall), "k = {k}");
+        }
+
+        let mut em = [0u8; 11];
+        pkcs1v15_encrypt_pad_into(&mut rng, &[], &mut em).unwrap();
+        assert_eq!(em[..2], [0, 2]);
+        assert!(em[2..10].iter().all(|&b| b != 0));
+        assert_eq!(em[10], 0);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn test_prefix_without_params() {
+        let prefix = pkcs1v15_generate_prefix::<sha2::Sha256>();
+        assert_eq!(
+            prefix,
+            hex_literal::hex!("3031300d060960864801650304020105000420")
+        );
+        assert_eq!(
+            pkcs1v15_prefix_without_params(&prefix).unwrap(),
+            hex_literal::hex!("302f300b06096086480165030402010420")
+        );
+        assert_eq!(pkcs1v15_prefix_without_params(&[]), None);
     }
 }
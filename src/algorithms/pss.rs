@@ -9,13 +9,16 @@
 //! [Probabilistic Signature Scheme]: https://en.wikipedia.org/wiki/Probabilistic_signature_scheme
 //! [RFC8017 § 8.1]: https://datatracker.ietf.org/doc/html/rfc8017#section-8.1
 
-use alloc::vec::Vec;
-use digest::{Digest, DynDigest, FixedOutputReset};
+#[cfg(feature = "alloc")]
+use {super::mgf::mgf1_xor, alloc::vec::Vec, digest::DynDigest};
+
+use digest::{Digest, FixedOutputReset};
 use subtle::{Choice, ConstantTimeEq};
 
-use super::mgf::{mgf1_xor, mgf1_xor_digest};
+use super::mgf::mgf1_xor_digest;
 use crate::errors::{Error, Result};
 
+#[cfg(feature = "alloc")]
 pub(crate) fn emsa_pss_encode(
     m_hash: &[u8],
     em_bits: usize,
@@ -38,8 +41,7 @@ pub(crate) fn emsa_pss_encode(
 
     // 3. If em_len < h_len + s_len + 2, output "encoding error" and stop.
     if em_len < h_len + s_len + 2 {
-        // TODO: Key size too small
-        return Err(Error::Internal);
+        return Err(Error::KeyTooSmall);
     }
 
     let mut em = vec![0; em_len];
@@ -89,6 +91,7 @@ pub(crate) fn emsa_pss_encode(
     Ok(em)
 }
 
+#[cfg(feature = "alloc")]
 pub(crate) fn emsa_pss_encode_digest<D>(
     m_hash: &[u8],
     em_bits: usize,
@@ -113,8 +116,7 @@ where
 
     // 3. If em_len < h_len + s_len + 2, output "encoding error" and stop.
     if em_len < h_len + s_len + 2 {
-        // TODO: Key size too small
-        return Err(Error::Internal);
+        return Err(Error::KeyTooSmall);
     }
 
     let mut em = vec![0; em_len];
@@ -227,6 +229,7 @@ fn emsa_pss_verify_salt(db: &[u8], em_len: usize, s_len: usize, h_len: usize) ->
     valid & rest[0].ct_eq(&0x01)
 }
 
+#[cfg(feature = "alloc")]
 pub(crate) fn emsa_pss_verify(
     m_hash: &[u8],
     em: &mut [u8],
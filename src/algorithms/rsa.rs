@@ -7,8 +7,19 @@ use crypto_bigint::{BoxedUint, Gcd, NonZero, Odd, RandomMod, Wrapping};
 use rand_core::CryptoRngCore;
 use zeroize::Zeroize;
 
+use crate::algorithms::arith::{low_u64, pow_fixed_window, pow_vartime, resize};
+use crate::algorithms::backend;
 use crate::errors::{Error, Result};
+#[cfg(feature = "std")]
+use crate::traits::keys::{BlindingCache, BlindingPair};
 use crate::traits::keys::{PrivateKeyParts, PublicKeyParts};
+#[cfg(any(
+    feature = "pkcs11",
+    feature = "tpm",
+    all(feature = "cng", windows),
+    all(feature = "apple", target_vendor = "apple")
+))]
+use {crate::algorithms::pad::uint_to_be_pad_into, alloc::vec::Vec, zeroize::Zeroizing};
 
 /// ⚠️ Raw RSA encryption of m with the public key. No padding is performed.
 ///
@@ -18,10 +29,30 @@ use crate::traits::keys::{PrivateKeyParts, PublicKeyParts};
 /// or signature scheme. See the [module-level documentation][crate::hazmat] for more information.
 #[inline]
 pub fn rsa_encrypt<K: PublicKeyParts>(key: &K, m: &BoxedUint) -> Result<BoxedUint> {
-    let res = pow_mod_params(m, key.e(), key.n_params());
+    let res = pow_public(m, key.e(), key.n_params());
     Ok(res)
 }
 
+/// ⚠️ Raw RSA encryption of m with the public key, in variable time.
+///
+/// Uses square-and-multiply on the bits of the public exponent, which is
+/// much faster than [`rsa_encrypt`] for small exponents. The running time
+/// depends on `m`, so it must only be used when `m` is public, e.g. to verify
+/// signatures, and never to encrypt secret messages.
+///
+/// # ☢️️ WARNING: HAZARDOUS API ☢️
+///
+/// Use this function with great care! Raw RSA should never be used without an appropriate padding
+/// or signature scheme. See the [module-level documentation][crate::hazmat] for more information.
+#[inline]
+pub fn rsa_encrypt_vartime<K: PublicKeyParts>(key: &K, m: &BoxedUint) -> Result<BoxedUint> {
+    let base = reduce_vartime(m, key.n_params());
+    if is_f4(key.e()) {
+        return Ok(pow_f4(&base).retrieve());
+    }
+    Ok(pow_vartime(&base, key.e()).retrieve())
+}
+
 /// ⚠️ Performs raw RSA decryption with no padding or error checking.
 ///
 /// Returns a plaintext `BoxedUint`. Performs RSA blinding if an `Rng` is passed.
@@ -49,7 +80,7 @@ pub fn rsa_decrypt<R: CryptoRngCore + ?Sized>(
     let bits = d.bits_precision();
 
     let c = if let Some(ref mut rng) = rng {
-        let (blinded, unblinder) = blind(rng, priv_key, c, n_params);
+        let (blinded, unblinder) = blind_with_cache(rng, priv_key, c, n_params);
         ir = Some(unblinder);
         blinded.widen(bits)
     } else {
@@ -74,12 +105,23 @@ pub fn rsa_decrypt<R: CryptoRngCore + ?Sized>(
             // precomputed: dP = (1/e) mod (p-1) = d mod (p-1)
             // precomputed: dQ = (1/e) mod (q-1) = d mod (q-1)
 
+            let window = priv_key.exp_window();
+            let pow = |base: BoxedMontyForm, exp| match window {
+                Some(window) => pow_fixed_window(&base, exp, window),
+                None => backend::pow(&base, exp),
+            };
+
             // m1 = c^dP mod p
             let cp = BoxedMontyForm::new(c.clone(), p_params.clone());
-            let mut m1 = cp.pow(dp);
             // m2 = c^dQ mod q
             let cq = BoxedMontyForm::new(c, q_params.clone());
-            let m2 = cq.pow(dq).retrieve();
+
+            // Both halves are independent, so they can be computed concurrently.
+            #[cfg(feature = "parallel")]
+            let (mut m1, m2) = rayon::join(|| pow(cp, dp), || pow(cq, dq));
+            #[cfg(not(feature = "parallel"))]
+            let (mut m1, m2) = (pow(cp, dp), pow(cq, dq));
+            let m2 = m2.retrieve();
 
             // (m1 - m2) mod p = (m1 mod p) - (m2 mod p) mod p
             let m2r = BoxedMontyForm::new(m2.clone(), p_params.clone());
@@ -135,12 +177,99 @@ pub fn rsa_decrypt_and_check<R: CryptoRngCore + ?Sized>(
     let check = rsa_encrypt(priv_key, &m)?;
 
     if c != &check {
-        return Err(Error::Internal);
+        return Err(Error::FaultDetected);
     }
 
     Ok(m)
 }
 
+/// Performs the raw private key operation on `c` with `op`, which maps the
+/// big-endian encoding of `c` to the encoding of the result, e.g. by
+/// delegating it to a hardware token. The result is checked with the public
+/// key, as faulty signatures leak the private key.
+#[cfg(any(
+    feature = "pkcs11",
+    feature = "tpm",
+    all(feature = "cng", windows),
+    all(feature = "apple", target_vendor = "apple")
+))]
+pub(crate) fn rsa_external_op(
+    pub_key: &impl PublicKeyParts,
+    c: &BoxedUint,
+    op: impl FnOnce(&[u8]) -> Result<Zeroizing<Vec<u8>>>,
+) -> Result<BoxedUint> {
+    let k = pub_key.size();
+    let mut input = vec![0u8; k];
+    uint_to_be_pad_into(c, k, &mut input)?;
+
+    let output = op(&input)?;
+    if output.len() > k {
+        return Err(Error::Backend);
+    }
+    let m = BoxedUint::from_be_slice(&output, pub_key.n_bits_precision())?;
+
+    if &rsa_encrypt(pub_key, &m)? != c {
+        return Err(Error::FaultDetected);
+    }
+    Ok(m)
+}
+
+/// Like [`blind`], but using the blinding cache of the key if it has one.
+fn blind_with_cache<R: CryptoRngCore, K: PrivateKeyParts>(
+    rng: &mut R,
+    key: &K,
+    c: &BoxedUint,
+    n_params: &BoxedMontyParams,
+) -> (BoxedUint, BoxedUint) {
+    #[cfg(feature = "std")]
+    if let Some(cache) = key.blinding_cache() {
+        return cache.blind(rng, key, c, n_params);
+    }
+    blind(rng, key, c, n_params)
+}
+
+#[cfg(feature = "std")]
+impl BlindingCache {
+    /// Returns the blinded c, along with the unblinding factor, updating the cached pair.
+    fn blind<R: CryptoRngCore, K: PublicKeyParts>(
+        &self,
+        rng: &mut R,
+        key: &K,
+        c: &BoxedUint,
+        n_params: &BoxedMontyParams,
+    ) -> (BoxedUint, BoxedUint) {
+        // Take the pair out of the cache, so that concurrent operations never use the same
+        // blinding factors. Operations finding the cache empty generate a fresh pair.
+        let cached = self.pair.lock().ok().and_then(|mut pair| pair.take());
+        let pair = match cached {
+            Some(pair) if pair.uses < Self::MAX_USES => pair,
+            _ => {
+                let (r_e, r_inv) = blinding_factors(rng, key, n_params);
+                BlindingPair {
+                    r_e,
+                    r_inv,
+                    uses: 0,
+                }
+            }
+        };
+
+        let blinded = mul_mod_params(c, &pair.r_e, n_params);
+        let unblinder = pair.r_inv.clone();
+
+        // (r^2)^e = (r^e)^2 and (r^2)^-1 = (r^-1)^2
+        let next = BlindingPair {
+            r_e: mul_mod_params(&pair.r_e, &pair.r_e, n_params),
+            r_inv: mul_mod_params(&pair.r_inv, &pair.r_inv, n_params),
+            uses: pair.uses + 1,
+        };
+        if let Ok(mut slot) = self.pair.lock() {
+            *slot = Some(next);
+        }
+
+        (blinded, unblinder)
+    }
+}
+
 /// Returns the blinded c, along with the unblinding factor.
 fn blind<R: CryptoRngCore, K: PublicKeyParts>(
     rng: &mut R,
@@ -152,6 +281,22 @@ fn blind<R: CryptoRngCore, K: PublicKeyParts>(
     // Then the decryption operation performs (m^e * r^e)^d mod n
     // which equals mr mod n. The factor of r can then be removed
     // by multiplying by the multiplicative inverse of r.
+    let (mut rpowe, ir) = blinding_factors(rng, key, n_params);
+
+    // c * r^e (mod n)
+    let blinded = mul_mod_params(c, &rpowe, n_params);
+    rpowe.zeroize();
+
+    debug_assert_eq!(blinded.bits_precision(), key.n_bits_precision());
+    (blinded, ir)
+}
+
+/// Returns `r^e` and `r^-1` for a random blinding factor `r`.
+fn blinding_factors<R: CryptoRngCore, K: PublicKeyParts>(
+    rng: &mut R,
+    key: &K,
+    n_params: &BoxedMontyParams,
+) -> (BoxedUint, BoxedUint) {
     debug_assert_eq!(&key.n().clone().get(), n_params.modulus());
     let bits = key.n_bits_precision();
 
@@ -167,21 +312,15 @@ fn blind<R: CryptoRngCore, K: PublicKeyParts>(
         ir = r.inv_mod(key.n()).into();
     }
 
-    let blinded = {
-        // r^e (mod n)
-        let mut rpowe = pow_mod_params(&r, key.e(), n_params);
-        // c * r^e (mod n)
-        let c = mul_mod_params(c, &rpowe, n_params);
-        rpowe.zeroize();
-
-        c
-    };
+    // r^e (mod n)
+    let rpowe = pow_public(&r, key.e(), n_params);
+    r.zeroize();
 
     let ir = ir.expect("loop exited");
-    debug_assert_eq!(blinded.bits_precision(), bits);
+    debug_assert_eq!(rpowe.bits_precision(), bits);
     debug_assert_eq!(ir.bits_precision(), bits);
 
-    (blinded, ir)
+    (rpowe, ir)
 }
 
 /// Given an m and and unblinding factor, unblind the m.
@@ -205,7 +344,38 @@ fn unblind(m: &BoxedUint, unblinder: &BoxedUint, n_params: &BoxedMontyParams) ->
 /// Computes `base.pow_mod(exp, n)` with precomputed `n_params`.
 fn pow_mod_params(base: &BoxedUint, exp: &BoxedUint, n_params: &BoxedMontyParams) -> BoxedUint {
     let base = reduce_vartime(base, n_params);
-    base.pow(exp).retrieve()
+    backend::pow(&base, exp).retrieve()
+}
+
+/// Computes `base.pow_mod(e, n)` for the public exponent `e`, with precomputed `n_params`.
+///
+/// The common exponent 65537 takes a dedicated path, see [`pow_f4`].
+fn pow_public(base: &BoxedUint, e: &BoxedUint, n_params: &BoxedMontyParams) -> BoxedUint {
+    if is_f4(e) {
+        pow_f4(&reduce_vartime(base, n_params)).retrieve()
+    } else {
+        pow_mod_params(base, e, n_params)
+    }
+}
+
+/// The Fermat number `F4 = 2^16 + 1`, used as public exponent by most keys.
+const F4: u64 = 65537;
+
+/// Returns `true` if `e` is [`F4`].
+fn is_f4(e: &BoxedUint) -> bool {
+    e.bits() == 17 && low_u64(e) == F4
+}
+
+/// Computes `base^65537` as 16 squarings followed by a multiplication.
+///
+/// This is a fixed sequence of operations, so it runs in constant time like
+/// the generic exponentiation, without scanning the bits of the exponent.
+fn pow_f4(base: &BoxedMontyForm) -> BoxedMontyForm {
+    let mut acc = base.square();
+    for _ in 1..16 {
+        acc = acc.square();
+    }
+    acc.mul(base)
 }
 
 fn reduce_vartime(n: &BoxedUint, p: &BoxedMontyParams) -> BoxedMontyForm {
@@ -230,13 +400,96 @@ fn mul_mod_params(lhs: &BoxedUint, rhs: &BoxedUint, n_params: &BoxedMontyParams)
     (lhs * rhs).retrieve()
 }
 
-/// The following (deterministic) algorithm also recovers the prime factors `p` and `q` of a modulus `n`, given the
-/// public exponent `e` and private exponent `d` using the method described in
-/// [NIST 800-56B Appendix C.2](https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-56Br2.pdf).
+/// Recovers the prime factors `p` and `q` of a modulus `n`, given the public exponent `e` and
+/// private exponent `d`.
+///
+/// For `e > 2^16` the deterministic method described in
+/// [NIST 800-56B Appendix C.2](https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-56Br2.pdf)
+/// is used. Smaller exponents, or inputs for which it fails, fall back to the probabilistic method
+/// of Appendix C.1, which works for any exponent.
 pub fn recover_primes(
     n: &NonZero<BoxedUint>,
     e: &BoxedUint,
     d: &BoxedUint,
+) -> Result<(BoxedUint, BoxedUint)> {
+    recover_primes_deterministic(n, e, d).or_else(|_| recover_primes_probabilistic(n, e, d))
+}
+
+/// Number of bases tried by [`recover_primes_probabilistic`], each of which succeeds with
+/// probability at least 1/2.
+const RECOVER_PRIMES_ATTEMPTS: u64 = 100;
+
+/// Recovers `p` and `q` with the method of NIST 800-56B Appendix C.1.
+///
+/// Instead of random values, the consecutive bases `2, 3, 4, ...` are tried, so that recovery
+/// needs no randomness and always yields the same result.
+fn recover_primes_probabilistic(
+    n: &NonZero<BoxedUint>,
+    e: &BoxedUint,
+    d: &BoxedUint,
+) -> Result<(BoxedUint, BoxedUint)> {
+    // 1. Let k = de – 1. If k is odd, then output an error indicator.
+    let bits = d
+        .bits_precision()
+        .max(e.bits_precision())
+        .max(n.bits_precision())
+        * 2;
+    let k = d
+        .widen(bits)
+        .wrapping_mul(&e.widen(bits))
+        .wrapping_sub(&BoxedUint::one());
+    let r = k.trailing_zeros();
+    if k.is_zero().into() || r == 0 {
+        return Err(Error::InvalidArguments);
+    }
+
+    // 2. Express k as 2^r·t, where t is the largest odd integer dividing k.
+    let t = &k >> r;
+
+    let params = BoxedMontyParams::new(
+        Odd::new(n.as_ref().clone())
+            .into_option()
+            .ok_or(Error::InvalidModulus)?,
+    );
+    let one = BoxedUint::one_with_precision(n.bits_precision());
+    let n_minus_one = n.as_ref().wrapping_sub(&one);
+
+    // 3. For i = 1 to 100, compute x = g^t mod n and square it until the square is 1.
+    for g in 2..RECOVER_PRIMES_ATTEMPTS + 2 {
+        let g = BoxedMontyForm::new(BoxedUint::from(g).widen(n.bits_precision()), params.clone());
+        let mut x = g.pow(&t).retrieve();
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 0..r {
+            let y = BoxedMontyForm::new(x.clone(), params.clone())
+                .square()
+                .retrieve();
+            if y == one {
+                // x is a nontrivial square root of 1 mod n.
+                let p = x.wrapping_sub(&one).gcd(n.as_ref());
+                let p_nz = NonZero::new(p.clone())
+                    .into_option()
+                    .ok_or(Error::InvalidArguments)?;
+                let q = n.as_ref().clone() / p_nz;
+                return Ok((p, q));
+            }
+            if y == n_minus_one {
+                break;
+            }
+            x = y;
+        }
+    }
+
+    Err(Error::InvalidArguments)
+}
+
+/// Recovers `p` and `q` with the deterministic method of NIST 800-56B Appendix C.2, which
+/// requires `e > 2^16`.
+fn recover_primes_deterministic(
+    n: &NonZero<BoxedUint>,
+    e: &BoxedUint,
+    d: &BoxedUint,
 ) -> Result<(BoxedUint, BoxedUint)> {
     // Check precondition
 
@@ -246,8 +499,16 @@ pub fn recover_primes(
         return Err(Error::InvalidArguments);
     }
 
+    if d.is_zero().into() {
+        return Err(Error::InvalidArguments);
+    }
+
     // 1. Let a = (de – 1) × GCD(n – 1, de – 1).
-    let bits = d.bits_precision() * 2;
+    let bits = d
+        .bits_precision()
+        .max(e.bits_precision())
+        .max(n.bits_precision())
+        * 2;
     let one = BoxedUint::one().widen(bits);
     let e = e.widen(bits);
     let d = d.widen(bits);
@@ -259,16 +520,22 @@ pub fn recover_primes(
     let n = n.widen(a.bits_precision());
 
     // 2. Let m = floor(a /n) and r = a – m n, so that a = m n + r and 0 ≤ r < n.
-    let m = &a / NonZero::new(n.clone()).expect("checked");
+    let n_nz = NonZero::new(n.clone())
+        .into_option()
+        .ok_or(Error::InvalidArguments)?;
+    let m = &a / n_nz;
     let r = a - &m * &n;
 
     // 3. Let b = ( (n – r)/(m + 1) ) + 1; if b is not an integer or b^2 ≤ 4n, then output an error indicator,
     //    and exit without further processing.
-    let modulus_check = (&n - &r) % NonZero::new(&m + &one).expect("adding 1");
+    let m_plus_one = NonZero::new(&m + &one)
+        .into_option()
+        .ok_or(Error::InvalidArguments)?;
+    let modulus_check = (&n - &r) % &m_plus_one;
     if (!modulus_check.is_zero()).into() {
         return Err(Error::InvalidArguments);
     }
-    let b = ((&n - &r) / NonZero::new(&m + &one).expect("adding one")) + one;
+    let b = ((&n - &r) / m_plus_one) + one;
 
     let four = BoxedUint::from(4u32);
     let four_n = &n * four;
@@ -289,24 +556,20 @@ pub fn recover_primes(
         return Err(Error::InvalidArguments);
     }
 
-    let bits = core::cmp::max(b.bits_precision(), y.bits_precision());
-    let two = NonZero::new(BoxedUint::from(2u64))
-        .expect("2 is non zero")
-        .widen(bits);
-    let p = (&b + &y) / &two;
-    let q = (b - y) / two;
+    let p = (&b + &y).shr1();
+    let q = (b - y).shr1();
 
     Ok((p, q))
 }
 
 /// Compute the modulus of a key from its primes.
-pub(crate) fn compute_modulus(primes: &[BoxedUint]) -> Odd<BoxedUint> {
+pub(crate) fn compute_modulus(primes: &[BoxedUint]) -> Result<Odd<BoxedUint>> {
     let mut primes = primes.iter();
-    let mut out = primes.next().expect("must at least be one prime").clone();
+    let mut out = primes.next().ok_or(Error::NprimesTooSmall)?.clone();
     for p in primes {
         out *= p;
     }
-    Odd::new(out).expect("modulus must be odd")
+    Odd::new(out).into_option().ok_or(Error::InvalidPrime)
 }
 
 /// Compute the private exponent from its primes (p and q) and public exponent
@@ -316,14 +579,15 @@ pub(crate) fn compute_private_exponent_euler_totient(
     primes: &[BoxedUint],
     exp: &BoxedUint,
 ) -> Result<BoxedUint> {
-    if primes.len() < 2 {
+    let one = BoxedUint::one();
+    if primes.len() < 2 || primes.iter().any(|prime| prime <= &one) {
         return Err(Error::InvalidPrime);
     }
     let bits = primes[0].bits_precision();
     let mut totient = BoxedUint::one_with_precision(bits);
 
     for prime in primes {
-        totient *= prime - &BoxedUint::one();
+        totient *= prime.wrapping_sub(&one);
     }
     let exp = exp.widen(totient.bits_precision());
 
@@ -350,8 +614,12 @@ pub(crate) fn compute_private_exponent_carmicheal(
     exp: &BoxedUint,
 ) -> Result<BoxedUint> {
     let one = BoxedUint::one();
-    let p1 = p - &one;
-    let q1 = q - &one;
+    if p <= &one || q <= &one {
+        return Err(Error::InvalidPrime);
+    }
+    let bits = p.bits_precision().max(q.bits_precision());
+    let p1 = resize(p, bits).wrapping_sub(&one);
+    let q1 = resize(q, bits).wrapping_sub(&one);
 
     // LCM inlined
     let gcd = p1.gcd(&q1);
@@ -369,6 +637,22 @@ pub(crate) fn compute_private_exponent_carmicheal(
 mod tests {
     use super::*;
 
+    #[test]
+    fn pow_f4_matches_generic() {
+        let n = BoxedUint::from(1_000_003u64 * 999_983);
+        let params = BoxedMontyParams::new(Odd::new(n).unwrap());
+        let e = BoxedUint::from(F4);
+        assert!(is_f4(&e));
+        assert!(is_f4(&e.widen(256)));
+        assert!(!is_f4(&BoxedUint::from(3u64)));
+
+        for m in [0u64, 1, 2, 123_456_789] {
+            let m = BoxedUint::from(m);
+            let expected = reduce_vartime(&m, &params).pow(&e).retrieve();
+            assert_eq!(pow_public(&m, &e, &params), expected);
+        }
+    }
+
     #[test]
     fn recover_primes_works() {
         let bits = 2048;
@@ -387,4 +671,20 @@ mod tests {
         assert_eq!(p, p1);
         assert_eq!(q, q1);
     }
+
+    #[test]
+    fn recover_primes_small_exponent() {
+        use crate::RsaPrivateKey;
+        use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new_with_exp(&mut rng, 512, BoxedUint::from(3u64)).unwrap();
+
+        let (mut p, mut q) = recover_primes(key.n(), key.e(), key.d()).unwrap();
+        if (p < q) != (key.primes()[0] < key.primes()[1]) {
+            core::mem::swap(&mut p, &mut q);
+        }
+        assert_eq!(&p, &key.primes()[0]);
+        assert_eq!(&q, &key.primes()[1]);
+    }
 }
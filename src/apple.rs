@@ -0,0 +1,125 @@
+//! Private keys held by the Apple Security framework.
+//!
+//! [`SecPrivateKey`] performs the raw private key operation with
+//! `SecKeyCreateDecryptedData` and `kSecKeyAlgorithmRSAEncryptionRaw`, while
+//! the padding is applied by this crate. The key can be stored in the keychain
+//! and does not need to be extractable. It implements [`RsaPrivateOps`], so it
+//! can be used with the padding schemes and the signing and decrypting keys of
+//! this crate:
+//!
+//! ```no_run
+//! use rsa::apple::SecPrivateKey;
+//! use rsa::pkcs1v15::SigningKey;
+//! use rsa::signature::Signer;
+//! use security_framework::item::{ItemClass, ItemSearchOptions, Reference, SearchResult};
+//! use sha2::Sha256;
+//!
+//! let results = ItemSearchOptions::new()
+//!     .class(ItemClass::key())
+//!     .label("code signing")
+//!     .load_refs(true)
+//!     .search()?;
+//! let Some(SearchResult::Ref(Reference::Key(key))) = results.into_iter().next() else {
+//!     panic!("key not found");
+//! };
+//!
+//! let key = SecPrivateKey::new(key)?;
+//! let signing_key = SigningKey::<Sha256, _>::new(key);
+//! let signature = signing_key.sign(b"hello world");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use core::fmt;
+
+use crypto_bigint::modular::BoxedMontyParams;
+use crypto_bigint::{BoxedUint, NonZero};
+use pkcs1::DecodeRsaPublicKey;
+use rand_core::CryptoRngCore;
+use security_framework::key::{Algorithm, SecKey};
+use zeroize::Zeroizing;
+
+use crate::algorithms::rsa::rsa_external_op;
+use crate::errors::{Error, Result};
+use crate::traits::{PublicKeyParts, RsaPrivateOps};
+use crate::RsaPublicKey;
+
+/// RSA private key referenced by a `SecKey`.
+///
+/// Blinding is left to the Security framework, and its results are checked
+/// with the public key before they are used.
+pub struct SecPrivateKey {
+    key: SecKey,
+    public_key: RsaPublicKey,
+}
+
+impl SecPrivateKey {
+    /// Use the RSA private key `key`, which must allow decryption.
+    ///
+    /// The public key is derived with `SecKeyCopyPublicKey`.
+    pub fn new(key: SecKey) -> Result<Self> {
+        let public_key = key
+            .public_key()
+            .and_then(|public_key| public_key.external_representation())
+            .ok_or(Error::Backend)?;
+        let public_key = RsaPublicKey::from_pkcs1_der(public_key.bytes())?;
+        Ok(Self { key, public_key })
+    }
+
+    /// Returns the `SecKey` of the key.
+    pub fn sec_key(&self) -> &SecKey {
+        &self.key
+    }
+
+    /// Returns the public key.
+    pub fn to_public_key(&self) -> RsaPublicKey {
+        self.public_key.clone()
+    }
+
+    /// Returns the `SecKey` of the key.
+    pub fn into_sec_key(self) -> SecKey {
+        self.key
+    }
+}
+
+impl fmt::Debug for SecPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecPrivateKey")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsRef<RsaPublicKey> for SecPrivateKey {
+    fn as_ref(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+}
+
+impl PublicKeyParts for SecPrivateKey {
+    fn n(&self) -> &NonZero<BoxedUint> {
+        self.public_key.n()
+    }
+
+    fn e(&self) -> &BoxedUint {
+        self.public_key.e()
+    }
+
+    fn n_params(&self) -> &BoxedMontyParams {
+        self.public_key.n_params()
+    }
+}
+
+impl RsaPrivateOps for SecPrivateKey {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        _rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        rsa_external_op(&self.public_key, c, |input| {
+            self.key
+                .decrypt_data(Algorithm::RSAEncryptionRaw, input)
+                .map(Zeroizing::new)
+                .map_err(|_| Error::Backend)
+        })
+    }
+}
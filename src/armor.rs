@@ -0,0 +1,348 @@
+//! PEM armor with custom labels, line widths and headers.
+//!
+//! The `to_*_pem` encoders of [`pkcs1`] and [`pkcs8`] follow [RFC7468]: a
+//! fixed label, 64 columns and no headers. Some key management systems
+//! expect other variants, which [`Armor`] produces:
+//!
+//! ```
+//! use rsa::armor::Armor;
+//!
+//! let mut rng = rand::thread_rng();
+//! let key = rsa::RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+//!
+//! let pem = Armor::new("TRUSTED PUBLIC KEY")
+//!     .line_width(76)
+//!     .header("Comment", "signing key")
+//!     .encode_public_key(&key.to_public_key())
+//!     .expect("failed to encode");
+//! assert!(pem.starts_with("-----BEGIN TRUSTED PUBLIC KEY-----\nComment: signing key\n\n"));
+//!
+//! let armored = rsa::armor::decode(&pem).expect("failed to decode");
+//! assert_eq!(armored.label(), "TRUSTED PUBLIC KEY");
+//! ```
+//!
+//! [`decode`] accepts any label, line width and line ending, headers, and
+//! text around the armor.
+//!
+//! [RFC7468]: https://datatracker.ietf.org/doc/html/rfc7468
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use base64ct::{Base64, Encoding};
+use pkcs8::der::pem;
+use pkcs8::{EncodePrivateKey, EncodePublicKey, LineEnding};
+use zeroize::Zeroizing;
+
+use crate::errors::{Error, Result};
+
+const BEGIN: &str = "-----BEGIN ";
+const END: &str = "-----END ";
+const DASHES: &str = "-----";
+
+/// Encoder of PEM documents with a custom armor.
+#[derive(Clone, Debug)]
+pub struct Armor<'a> {
+    label: &'a str,
+    line_width: usize,
+    line_ending: LineEnding,
+    headers: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Armor<'a> {
+    /// Create an armor with the given label, 64 columns, LF line endings
+    /// and no headers.
+    pub fn new(label: &'a str) -> Self {
+        Self {
+            label,
+            line_width: 64,
+            line_ending: LineEnding::LF,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Set the number of base64 characters per line.
+    pub fn line_width(mut self, line_width: usize) -> Self {
+        self.line_width = line_width;
+        self
+    }
+
+    /// Set the line ending.
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    /// Add an explanatory `name: value` header, as in [RFC1421 § 4.6].
+    ///
+    /// [RFC1421 § 4.6]: https://datatracker.ietf.org/doc/html/rfc1421#section-4.6
+    pub fn header(mut self, name: &'a str, value: &'a str) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    /// Encode `der` in this armor.
+    ///
+    /// Fails with [`Error::InvalidArguments`] if the label or a header
+    /// can't be represented, or if the line width is zero.
+    pub fn encode(&self, der: &[u8]) -> Result<String> {
+        if !is_label(self.label) || self.line_width == 0 {
+            return Err(Error::InvalidArguments);
+        }
+        let eol = match self.line_ending {
+            LineEnding::CR => "\r",
+            LineEnding::LF => "\n",
+            LineEnding::CRLF => "\r\n",
+        };
+
+        let mut out = String::new();
+        out.push_str(BEGIN);
+        out.push_str(self.label);
+        out.push_str(DASHES);
+        out.push_str(eol);
+        for (name, value) in &self.headers {
+            if !is_header(name, value) {
+                return Err(Error::InvalidArguments);
+            }
+            out.push_str(name);
+            out.push_str(": ");
+            out.push_str(value);
+            out.push_str(eol);
+        }
+        if !self.headers.is_empty() {
+            out.push_str(eol);
+        }
+
+        let encoded = Zeroizing::new(Base64::encode_string(der));
+        let mut rest = encoded.as_str();
+        while !rest.is_empty() {
+            let (line, tail) = rest.split_at(self.line_width.min(rest.len()));
+            out.push_str(line);
+            out.push_str(eol);
+            rest = tail;
+        }
+
+        out.push_str(END);
+        out.push_str(self.label);
+        out.push_str(DASHES);
+        out.push_str(eol);
+        Ok(out)
+    }
+
+    /// Encode the `SubjectPublicKeyInfo` of `key` in this armor.
+    pub fn encode_public_key(&self, key: &impl EncodePublicKey) -> Result<String> {
+        self.encode(key.to_public_key_der()?.as_bytes())
+    }
+
+    /// Encode the PKCS#8 `PrivateKeyInfo` of `key` in this armor.
+    pub fn encode_private_key(&self, key: &impl EncodePrivateKey) -> Result<Zeroizing<String>> {
+        self.encode(key.to_pkcs8_der()?.as_bytes())
+            .map(Zeroizing::new)
+    }
+}
+
+/// PEM document decoded by [`decode`].
+#[derive(Clone, Debug)]
+pub struct Armored {
+    label: String,
+    headers: Vec<(String, String)>,
+    der: Zeroizing<Vec<u8>>,
+}
+
+impl Armored {
+    /// Returns the label of the armor.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the `name: value` headers of the armor, in order.
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Returns the encapsulated DER document.
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+}
+
+/// Decode the first PEM document of `input`, whatever its label.
+///
+/// Text before and after the armor, headers (including folded ones), any
+/// line width, CR, LF or CRLF line endings and whitespace around lines are
+/// accepted. The labels of both boundaries must match.
+pub fn decode(input: &str) -> Result<Armored> {
+    let start = input
+        .find(BEGIN)
+        .ok_or_else(|| pem_error(pem::Error::PreEncapsulationBoundary))?;
+    let input = &input[start + BEGIN.len()..];
+    // Lines end with LF or CRLF, or with CR only if there is no LF.
+    let eol = if input.contains('\n') { '\n' } else { '\r' };
+    let mut lines = input.split(eol).map(|line| line.trim_end_matches('\r'));
+
+    let label = lines
+        .next()
+        .and_then(|line| line.trim_end().strip_suffix(DASHES))
+        .filter(|label| is_label(label))
+        .ok_or_else(|| pem_error(pem::Error::PreEncapsulationBoundary))?;
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut encoded = Zeroizing::new(String::new());
+    let mut end = None;
+    // Whether the previous line was a header, which the line may continue.
+    let mut folding = false;
+    for line in lines {
+        if let Some(rest) = line.trim().strip_prefix(END) {
+            end = Some(rest);
+            break;
+        }
+        match headers.last_mut() {
+            Some((_, value)) if folding && line.starts_with([' ', '\t']) => {
+                value.push(' ');
+                value.push_str(line.trim());
+                continue;
+            }
+            _ => {}
+        }
+        let line = line.trim();
+        folding = false;
+        match line.split_once(':') {
+            Some((name, value)) if encoded.is_empty() => {
+                headers.push((name.trim().into(), value.trim().into()));
+                folding = true;
+            }
+            Some(_) => return Err(pem_error(pem::Error::EncapsulatedText)),
+            None => encoded.push_str(line),
+        }
+    }
+
+    match end.and_then(|rest| rest.strip_suffix(DASHES)) {
+        Some(end_label) if end_label == label => {}
+        Some(_) => return Err(pem_error(pem::Error::Label)),
+        None => return Err(pem_error(pem::Error::PostEncapsulationBoundary)),
+    }
+
+    let der = Base64::decode_vec(&encoded).map_err(|_| pem_error(pem::Error::EncapsulatedText))?;
+    Ok(Armored {
+        label: label.into(),
+        headers,
+        der: Zeroizing::new(der),
+    })
+}
+
+/// Returns whether `label` is a label of [RFC7468 § 3].
+///
+/// [RFC7468 § 3]: https://datatracker.ietf.org/doc/html/rfc7468#section-3
+fn is_label(label: &str) -> bool {
+    let bytes = label.as_bytes();
+    bytes.iter().all(|&b| b == b' ' || b.is_ascii_graphic())
+        && !label.contains("--")
+        && !label.contains("  ")
+        && !label.starts_with([' ', '-'])
+        && !label.ends_with([' ', '-'])
+}
+
+/// Returns whether a header can be encoded on a single line.
+fn is_header(name: &str, value: &str) -> bool {
+    !name.is_empty()
+        && name.bytes().all(|b| b.is_ascii_graphic() && b != b':')
+        && !value.contains(['\r', '\n'])
+}
+
+fn pem_error(err: pem::Error) -> Error {
+    Error::Der(err.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RsaPrivateKey, RsaPublicKey};
+    use pkcs8::{DecodePrivateKey, DecodePublicKey};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_encode_decode() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let public_key = key.to_public_key();
+
+        let pem = Armor::new("TRUSTED PUBLIC KEY")
+            .line_width(40)
+            .line_ending(LineEnding::CRLF)
+            .header("Proc-Type", "4,CRYPT")
+            .header("Comment", "test key")
+            .encode_public_key(&public_key)
+            .unwrap();
+        let lines: Vec<_> = pem.split("\r\n").collect();
+        assert_eq!(lines[0], "-----BEGIN TRUSTED PUBLIC KEY-----");
+        assert_eq!(lines[1], "Proc-Type: 4,CRYPT");
+        assert_eq!(lines[3], "");
+        assert_eq!(lines[4].len(), 40);
+        assert!(pem.ends_with("\r\n-----END TRUSTED PUBLIC KEY-----\r\n"));
+
+        let armored = decode(&pem).unwrap();
+        assert_eq!(armored.label(), "TRUSTED PUBLIC KEY");
+        assert_eq!(armored.headers()[1], ("Comment".into(), "test key".into()));
+        assert_eq!(
+            RsaPublicKey::from_public_key_der(armored.der()).unwrap(),
+            public_key
+        );
+
+        let pem = Armor::new("PRIVATE KEY").encode_private_key(&key).unwrap();
+        assert_eq!(
+            pem.as_str(),
+            key.to_pkcs8_pem(LineEnding::LF).unwrap().as_str()
+        );
+        let armored = decode(&pem).unwrap();
+        assert_eq!(RsaPrivateKey::from_pkcs8_der(armored.der()).unwrap(), key);
+    }
+
+    #[test]
+    fn test_decode_tolerant() {
+        let pem = "Certificate of the signing service\n\
+            \t-----BEGIN RSA PUBLIC KEY-----  \r\n\
+            Comment: folded\r\n  \
+              header\r\n\
+            \r\n  \
+            MAoCAwDF  \r\n\
+            gwIDAQAB\n\
+            -----END RSA PUBLIC KEY-----\n\
+            trailing text";
+        let armored = decode(pem).unwrap();
+        assert_eq!(armored.label(), "RSA PUBLIC KEY");
+        assert_eq!(armored.headers().len(), 1);
+        assert_eq!(
+            armored.headers()[0],
+            ("Comment".into(), "folded header".into())
+        );
+        assert_eq!(
+            armored.der(),
+            [0x30, 0x0a, 0x02, 0x03, 0x00, 0xc5, 0x83, 0x02, 0x03, 0x01, 0x00, 0x01]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Armor::new("-KEY").encode(b"\x05\x00"),
+            Err(Error::InvalidArguments)
+        );
+        assert_eq!(
+            Armor::new("KEY").line_width(0).encode(b"\x05\x00"),
+            Err(Error::InvalidArguments)
+        );
+        assert_eq!(
+            Armor::new("KEY").header("A", "b\nc").encode(b"\x05\x00"),
+            Err(Error::InvalidArguments)
+        );
+
+        for pem in [
+            "BQA=",
+            "-----BEGIN KEY-----\nBQA=\n",
+            "-----BEGIN KEY-----\nBQA=\n-----END PUBLIC KEY-----\n",
+            "-----BEGIN KEY-----\nB!A=\n-----END KEY-----\n",
+            "-----BEGIN KEY-----\nBQA=\nA: b\n-----END KEY-----\n",
+        ] {
+            assert!(decode(pem).is_err(), "{pem}");
+        }
+    }
+}
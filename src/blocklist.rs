@@ -0,0 +1,138 @@
+//! Blocklists of weak or compromised RSA keys.
+//!
+//! Keys produced by broken generators, such as the Debian OpenSSL keys of
+//! CVE-2008-0166, are published as lists of modulus hashes. A
+//! [`KeyBlocklist`] decides whether a modulus appears on such a list, and
+//! [`ModulusHashBlocklist`] implements one over a set of hashes.
+//!
+//! Construct public keys with [`RsaPublicKey::new_with_blocklist`], and check
+//! decoded keys with [`RsaPublicKey::check_blocklist`]:
+//!
+#![cfg_attr(feature = "sha2", doc = "```")]
+#![cfg_attr(not(feature = "sha2"), doc = "```ignore")]
+//! use rsa::blocklist::ModulusHashBlocklist;
+//! use rsa::{sha2::Sha256, Error, RsaPrivateKey};
+//!
+//! let mut rng = rand::thread_rng();
+//! let compromised = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate a key");
+//!
+//! let mut blocklist = ModulusHashBlocklist::<Sha256>::new();
+//! blocklist.insert_key(&compromised);
+//!
+//! let public_key = compromised.to_public_key();
+//! assert_eq!(public_key.check_blocklist(&blocklist), Err(Error::BlockedKey));
+//! ```
+//!
+//! [`RsaPublicKey::new_with_blocklist`]: crate::RsaPublicKey::new_with_blocklist
+//! [`RsaPublicKey::check_blocklist`]: crate::RsaPublicKey::check_blocklist
+
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crypto_bigint::BoxedUint;
+use digest::Digest;
+
+use crate::traits::PublicKeyParts;
+
+/// A list of moduli which must not be accepted.
+pub trait KeyBlocklist {
+    /// Returns `true` if keys with the modulus `n` are blocked.
+    fn is_blocked(&self, n: &BoxedUint) -> bool;
+}
+
+/// A [`KeyBlocklist`] over a set of hashes of blocked moduli.
+///
+/// Moduli are hashed with `D` over their big-endian encoding without leading
+/// zero bytes.
+#[derive(Debug, Clone)]
+pub struct ModulusHashBlocklist<D> {
+    hashes: BTreeSet<Vec<u8>>,
+    digest: PhantomData<D>,
+}
+
+impl<D: Digest> ModulusHashBlocklist<D> {
+    /// Create an empty blocklist.
+    pub fn new() -> Self {
+        Self {
+            hashes: BTreeSet::new(),
+            digest: PhantomData,
+        }
+    }
+
+    /// Block the modulus with the given hash.
+    pub fn insert_hash(&mut self, hash: &[u8]) {
+        self.hashes.insert(hash.to_vec());
+    }
+
+    /// Block the modulus of `key`.
+    pub fn insert_key(&mut self, key: &impl PublicKeyParts) {
+        self.hashes.insert(Self::hash(key.n()));
+    }
+
+    /// Number of blocked moduli.
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Returns `true` if no modulus is blocked.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Hash of the modulus `n` as stored in the blocklist.
+    pub fn hash(n: &BoxedUint) -> Vec<u8> {
+        let bytes = n.to_be_bytes();
+        let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+        D::digest(&bytes[start..]).to_vec()
+    }
+}
+
+impl<D: Digest> Default for ModulusHashBlocklist<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: Digest> KeyBlocklist for ModulusHashBlocklist<D> {
+    fn is_blocked(&self, n: &BoxedUint) -> bool {
+        self.hashes.contains(&Self::hash(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, RsaPrivateKey, RsaPublicKey};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::Sha256;
+
+    #[test]
+    fn test_blocklist() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let blocked = RsaPrivateKey::new(&mut rng, 512).unwrap().to_public_key();
+        let allowed = RsaPrivateKey::new(&mut rng, 512).unwrap().to_public_key();
+
+        let mut blocklist = ModulusHashBlocklist::<Sha256>::new();
+        assert!(blocklist.is_empty());
+        blocklist.insert_hash(&ModulusHashBlocklist::<Sha256>::hash(blocked.n()));
+        assert_eq!(blocklist.len(), 1);
+
+        assert_eq!(blocked.check_blocklist(&blocklist), Err(Error::BlockedKey));
+        assert_eq!(allowed.check_blocklist(&blocklist), Ok(()));
+
+        // Moduli are hashed independently of their precision.
+        let n = blocked.n().widen(2 * blocked.n().bits_precision());
+        assert!(blocklist.is_blocked(&n));
+        assert_eq!(
+            RsaPublicKey::new_with_blocklist(n, blocked.e().clone(), &blocklist),
+            Err(Error::BlockedKey)
+        );
+        assert!(RsaPublicKey::new_with_blocklist(
+            allowed.n().as_ref().clone(),
+            allowed.e().clone(),
+            &blocklist
+        )
+        .is_ok());
+    }
+}
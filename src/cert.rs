@@ -0,0 +1,376 @@
+//! Self-signed X.509 certificates.
+//!
+//! [`generate_self_signed_cert`] issues a certificate for the public key of a
+//! signing key, signed by the key itself, as needed by test harnesses and by
+//! TLS listeners bootstrapping their configuration. The signature scheme is
+//! that of the signing key: e.g. a [`pkcs1v15::SigningKey`] with SHA-256 produces
+//! `sha256WithRSAEncryption` certificates, a [`pss::SigningKey`] produces
+//! `RSASSA-PSS` ones.
+//!
+//! ```
+//! use rsa::cert::{generate_self_signed_cert, KeyUsage, Validity};
+//! use rsa::csr::COMMON_NAME;
+//! use rsa::pkcs1v15::SigningKey;
+//! use rsa::sha2::Sha256;
+//!
+//! let mut rng = rand::thread_rng();
+//! let key = rsa::RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+//! let cert = generate_self_signed_cert(
+//!     &SigningKey::<Sha256>::new(key),
+//!     &[(COMMON_NAME, "localhost")],
+//!     Validity::new(1_700_000_000, 1_700_000_000 + 90 * 86_400),
+//!     KeyUsage::DIGITAL_SIGNATURE | KeyUsage::KEY_ENCIPHERMENT,
+//! )
+//! .expect("failed to generate a certificate");
+//! ```
+//!
+//! The certificates only carry the key usage extension, and the basic
+//! constraints extension for certification authorities, see [`KeyUsage`].
+//!
+//! [`pkcs1v15::SigningKey`]: crate::pkcs1v15::SigningKey
+//! [`pss::SigningKey`]: crate::pss::SigningKey
+
+use alloc::vec::Vec;
+use const_oid::ObjectIdentifier;
+use core::ops::{BitOr, BitOrAssign};
+use pkcs8::der::Encode;
+use pkcs8::spki::{DynSignatureAlgorithmIdentifier, EncodePublicKey, SignatureBitStringEncoding};
+use rand_core::CryptoRngCore;
+use signature::{Keypair, RandomizedSigner, Signer};
+
+use crate::csr::{
+    concat, encode_name, signed, tlv, TAG_BIT_STRING, TAG_INTEGER, TAG_OCTET_STRING, TAG_OID,
+    TAG_SEQUENCE,
+};
+use crate::errors::{Error, Result};
+
+/// `keyUsage` extension.
+const KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.15");
+/// `basicConstraints` extension.
+const BASIC_CONSTRAINTS: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.19");
+
+const TAG_BOOLEAN: u8 = 0x01;
+const TAG_UTC_TIME: u8 = 0x17;
+const TAG_GENERALIZED_TIME: u8 = 0x18;
+const TAG_VERSION: u8 = 0xa0;
+const TAG_EXTENSIONS: u8 = 0xa3;
+
+/// Validity period of a certificate, in seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Validity {
+    /// Start of the validity period.
+    pub not_before: u64,
+    /// End of the validity period, inclusive.
+    pub not_after: u64,
+}
+
+impl Validity {
+    /// Create a validity period from `not_before` to `not_after`.
+    pub fn new(not_before: u64, not_after: u64) -> Self {
+        Self {
+            not_before,
+            not_after,
+        }
+    }
+}
+
+/// Key usage bits of a certificate, as defined in [RFC5280 § 4.2.1.3].
+///
+/// Certificates with [`KeyUsage::KEY_CERT_SIGN`] are marked as
+/// certification authorities with the basic constraints extension. An empty
+/// key usage leaves the extension out.
+///
+/// [RFC5280 § 4.2.1.3]: https://datatracker.ietf.org/doc/html/rfc5280#section-4.2.1.3
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyUsage(u16);
+
+impl KeyUsage {
+    /// `digitalSignature`, e.g. for TLS server authentication with ECDHE.
+    pub const DIGITAL_SIGNATURE: Self = Self(1 << 0);
+    /// `nonRepudiation`, also known as `contentCommitment`.
+    pub const NON_REPUDIATION: Self = Self(1 << 1);
+    /// `keyEncipherment`, e.g. for TLS RSA key exchange.
+    pub const KEY_ENCIPHERMENT: Self = Self(1 << 2);
+    /// `dataEncipherment`.
+    pub const DATA_ENCIPHERMENT: Self = Self(1 << 3);
+    /// `keyCertSign`, for certification authorities.
+    pub const KEY_CERT_SIGN: Self = Self(1 << 5);
+    /// `cRLSign`.
+    pub const CRL_SIGN: Self = Self(1 << 6);
+
+    /// Returns whether all bits of `other` are set.
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Encodes the bits as a DER `BIT STRING`, bit 0 first and without
+    /// trailing zero bits.
+    fn to_der(self) -> Vec<u8> {
+        let len = 16 - self.0.leading_zeros() as usize;
+        let bytes = len.div_ceil(8);
+        let mut contents = vec![(bytes * 8 - len) as u8];
+        for i in 0..bytes {
+            contents.push((self.0 >> (8 * i)) as u8);
+        }
+        for byte in &mut contents[1..] {
+            *byte = byte.reverse_bits();
+        }
+        tlv(TAG_BIT_STRING, &contents)
+    }
+}
+
+impl BitOr for KeyUsage {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for KeyUsage {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Generate a certificate for the public key of `signer` with the given
+/// `subject`, which is also its issuer, and sign it with `signer`.
+///
+/// `subject` lists attributes of the distinguished name, encoded as for
+/// [`CsrBuilder::subject_attribute`]. The serial number is derived from the
+/// public key and the start of the validity period, so certificates issued
+/// for a key at different times differ.
+///
+/// Returns the DER encoding of the certificate. Fails with
+/// [`Error::InvalidArguments`] if the validity period ends before it starts
+/// or if an attribute of the subject cannot be encoded, and with
+/// [`Error::Backend`] if signing fails.
+///
+/// [`CsrBuilder::subject_attribute`]: crate::csr::CsrBuilder::subject_attribute
+pub fn generate_self_signed_cert<S, Sig>(
+    signer: &S,
+    subject: &[(ObjectIdentifier, &str)],
+    validity: Validity,
+    key_usage: KeyUsage,
+) -> Result<Vec<u8>>
+where
+    S: Keypair + DynSignatureAlgorithmIdentifier + Signer<Sig>,
+    S::VerifyingKey: EncodePublicKey,
+    Sig: SignatureBitStringEncoding,
+{
+    let public_key = signer.verifying_key().to_public_key_der()?;
+    // The SubjectPublicKeyInfo ends with the public exponent, so take the
+    // eight bytes before it, which belong to the modulus.
+    let spki = public_key.as_bytes();
+    let end = spki.len().saturating_sub(8);
+    let key_bytes = &spki[end.saturating_sub(8)..end];
+    let serial = [key_bytes, &validity.not_before.to_be_bytes()].concat();
+    let tbs = tbs_certificate(signer, spki, &serial, subject, validity, key_usage)?;
+    signed(signer, tbs, |tbs| signer.try_sign(tbs))
+}
+
+/// Generate a self-signed certificate like [`generate_self_signed_cert`],
+/// with a signer which needs randomness, e.g. a [`BlindedSigningKey`].
+///
+/// The serial number is drawn from `rng`.
+///
+/// [`BlindedSigningKey`]: crate::pss::BlindedSigningKey
+pub fn generate_self_signed_cert_with_rng<R, S, Sig>(
+    rng: &mut R,
+    signer: &S,
+    subject: &[(ObjectIdentifier, &str)],
+    validity: Validity,
+    key_usage: KeyUsage,
+) -> Result<Vec<u8>>
+where
+    R: CryptoRngCore,
+    S: Keypair + DynSignatureAlgorithmIdentifier + RandomizedSigner<Sig>,
+    S::VerifyingKey: EncodePublicKey,
+    Sig: SignatureBitStringEncoding,
+{
+    let public_key = signer.verifying_key().to_public_key_der()?;
+    let mut serial = [0u8; 16];
+    rng.fill_bytes(&mut serial);
+    let tbs = tbs_certificate(
+        signer,
+        public_key.as_bytes(),
+        &serial,
+        subject,
+        validity,
+        key_usage,
+    )?;
+    signed(signer, tbs, |tbs| signer.try_sign_with_rng(rng, tbs))
+}
+
+/// Encodes the `TBSCertificate`.
+fn tbs_certificate<S: DynSignatureAlgorithmIdentifier>(
+    signer: &S,
+    spki: &[u8],
+    serial: &[u8],
+    subject: &[(ObjectIdentifier, &str)],
+    validity: Validity,
+    key_usage: KeyUsage,
+) -> Result<Vec<u8>> {
+    if validity.not_after < validity.not_before {
+        return Err(Error::InvalidArguments);
+    }
+    let name = encode_name(subject)?;
+
+    let mut extensions = Vec::new();
+    if key_usage != KeyUsage::default() {
+        extensions.push(extension(KEY_USAGE, &key_usage.to_der()));
+    }
+    if key_usage.contains(KeyUsage::KEY_CERT_SIGN) {
+        let ca = tlv(TAG_SEQUENCE, &tlv(TAG_BOOLEAN, &[0xff]));
+        extensions.push(extension(BASIC_CONSTRAINTS, &ca));
+    }
+
+    let mut tbs = vec![
+        // v3
+        tlv(TAG_VERSION, &tlv(TAG_INTEGER, &[2])),
+        tlv(TAG_INTEGER, &positive_integer(serial)),
+        signer.signature_algorithm_identifier()?.to_der()?,
+        name.clone(),
+        tlv(
+            TAG_SEQUENCE,
+            &concat(&[time(validity.not_before)?, time(validity.not_after)?]),
+        ),
+        name,
+        spki.to_vec(),
+    ];
+    if !extensions.is_empty() {
+        tbs.push(tlv(
+            TAG_EXTENSIONS,
+            &tlv(TAG_SEQUENCE, &concat(&extensions)),
+        ));
+    }
+    Ok(tlv(TAG_SEQUENCE, &concat(&tbs)))
+}
+
+/// Encodes a critical `Extension`.
+fn extension(oid: ObjectIdentifier, value: &[u8]) -> Vec<u8> {
+    tlv(
+        TAG_SEQUENCE,
+        &concat(&[
+            tlv(TAG_OID, oid.as_bytes()),
+            tlv(TAG_BOOLEAN, &[0xff]),
+            tlv(TAG_OCTET_STRING, value),
+        ]),
+    )
+}
+
+/// Returns the contents of the DER `INTEGER` encoding the big endian
+/// unsigned integer `bytes`.
+fn positive_integer(bytes: &[u8]) -> Vec<u8> {
+    let skip = bytes.iter().take_while(|&&b| b == 0).count();
+    let bytes = &bytes[skip..];
+    match bytes.first() {
+        None => vec![0],
+        Some(&b) if b & 0x80 != 0 => [&[0], bytes].concat(),
+        Some(_) => bytes.to_vec(),
+    }
+}
+
+/// Encodes `secs` as a `UTCTime` until 2049, and as a `GeneralizedTime`
+/// after, as required by RFC 5280.
+fn time(secs: u64) -> Result<Vec<u8>> {
+    let days = secs / 86_400;
+    let rem = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    if year > 9999 {
+        return Err(Error::InvalidArguments);
+    }
+    let (hours, minutes, seconds) = (rem / 3600, rem / 60 % 60, rem % 60);
+    let time = format!("{month:02}{day:02}{hours:02}{minutes:02}{seconds:02}Z");
+    Ok(if year < 2050 {
+        tlv(TAG_UTC_TIME, format!("{:02}{time}", year % 100).as_bytes())
+    } else {
+        tlv(TAG_GENERALIZED_TIME, format!("{year:04}{time}").as_bytes())
+    })
+}
+
+/// Converts days since the Unix epoch to a proleptic Gregorian date.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::{COMMON_NAME, COUNTRY};
+    use crate::{pkcs1v15, pss, RsaPrivateKey};
+    use hex_literal::hex;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::Sha256;
+
+    #[test]
+    fn test_time() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(time(1_700_000_000).unwrap(), b"\x17\x0d231114221320Z");
+        assert_eq!(time(2_524_608_000).unwrap(), b"\x18\x0f20500101000000Z");
+    }
+
+    #[test]
+    fn test_key_usage() {
+        assert_eq!(KeyUsage::DIGITAL_SIGNATURE.to_der(), hex!("03020780"));
+        assert_eq!(
+            (KeyUsage::DIGITAL_SIGNATURE | KeyUsage::KEY_ENCIPHERMENT).to_der(),
+            hex!("030205a0")
+        );
+        assert_eq!(
+            (KeyUsage::KEY_CERT_SIGN | KeyUsage::CRL_SIGN).to_der(),
+            hex!("03020106")
+        );
+        assert_eq!(positive_integer(&[0, 0, 0x80, 1]), [0, 0x80, 1]);
+    }
+
+    #[test]
+    fn test_generate_self_signed_cert() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let subject = [(COMMON_NAME, "localhost"), (COUNTRY, "FR")];
+        let validity = Validity::new(1_700_000_000, 1_800_000_000);
+
+        let signing_key = pkcs1v15::SigningKey::<Sha256>::new(key.clone());
+        let cert =
+            generate_self_signed_cert(&signing_key, &subject, validity, KeyUsage::KEY_CERT_SIGN)
+                .unwrap();
+        assert_eq!(cert[0], TAG_SEQUENCE);
+        assert_eq!(
+            generate_self_signed_cert(&signing_key, &subject, validity, KeyUsage::KEY_CERT_SIGN)
+                .unwrap(),
+            cert
+        );
+        assert_eq!(
+            generate_self_signed_cert(
+                &signing_key,
+                &subject,
+                Validity::new(2, 1),
+                KeyUsage::default()
+            ),
+            Err(Error::InvalidArguments)
+        );
+
+        let cert = generate_self_signed_cert_with_rng(
+            &mut rng,
+            &pss::BlindedSigningKey::<Sha256>::new(key),
+            &subject,
+            validity,
+            KeyUsage::DIGITAL_SIGNATURE,
+        )
+        .unwrap();
+        assert_eq!(cert[0], TAG_SEQUENCE);
+    }
+}
@@ -0,0 +1,729 @@
+//! Cryptographic Message Syntax ([RFC5652]) signed and enveloped data.
+//!
+//! - [`SignedDataBuilder`] creates `SignedData` with a single signer, with
+//!   the content encapsulated or detached, and [`verify`] checks it.
+//! - [`encrypt`] creates `AuthEnvelopedData` ([RFC5083]) for one or more
+//!   recipients: the content is encrypted with AES-256-GCM ([RFC5084]) and
+//!   the content key is transported to each recipient with RSA-OAEP or
+//!   PKCS#1 v1.5 encryption. [`decrypt`] recovers the content.
+//!
+//! Signers and recipients are identified by the subject key identifier of
+//! their public key, computed with method 1 of [RFC7093 § 2], so no
+//! certificate is needed; certificates may still be included in signed data
+//! for the benefit of the verifier. Signatures use SHA-256, SHA-384 or
+//! SHA-512, with PKCS#1 v1.5 or PSS, and signed attributes holding the
+//! content type and message digest.
+//!
+//! Only DER is parsed: BER with indefinite lengths, as produced by some
+//! streaming encoders, is rejected.
+//!
+//! ```
+//! use rsa::cms::{self, KeyTransport, SignatureAlgorithm, SignedDataBuilder};
+//! use rsa::sha2::Sha256;
+//!
+//! let mut rng = rand::thread_rng();
+//! let key = rsa::RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+//! let public_key = key.to_public_key();
+//!
+//! let signed = SignedDataBuilder::new(b"hello")
+//!     .signature_algorithm(SignatureAlgorithm::Pss)
+//!     .sign::<Sha256, _>(&mut rng, &key)
+//!     .expect("failed to sign");
+//! assert_eq!(cms::verify(&signed, &public_key, None).unwrap(), b"hello");
+//!
+//! let enveloped = cms::encrypt(&mut rng, &[&public_key], KeyTransport::Oaep, b"secret")
+//!     .expect("failed to encrypt");
+//! assert_eq!(cms::decrypt(&mut rng, &key, &enveloped).unwrap(), b"secret");
+//! ```
+//!
+//! [RFC5652]: https://datatracker.ietf.org/doc/html/rfc5652
+//! [RFC5083]: https://datatracker.ietf.org/doc/html/rfc5083
+//! [RFC5084]: https://datatracker.ietf.org/doc/html/rfc5084
+//! [RFC7093 § 2]: https://datatracker.ietf.org/doc/html/rfc7093#section-2
+
+use aead::{Aead, KeyInit, Nonce};
+use aes_gcm::Aes256Gcm;
+use alloc::vec::Vec;
+use const_oid::{AssociatedOid, ObjectIdentifier};
+use digest::{Digest, DynDigest};
+use pkcs8::der::{Decode, Encode};
+use pkcs8::spki::AlgorithmIdentifierRef;
+use pkcs8::EncodePublicKey;
+use rand_core::CryptoRngCore;
+use sha2::{Sha256, Sha384, Sha512};
+use zeroize::Zeroizing;
+
+use crate::csr::{
+    concat, tlv, TAG_BIT_STRING, TAG_INTEGER, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE,
+};
+use crate::encoding::ID_RSASSA_PSS;
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::oaep::get_default_oaep_algo_id;
+use crate::pss::get_default_pss_signature_algo_id;
+use crate::{Oaep, Pkcs1v15Encrypt, Pkcs1v15Sign, Pss};
+
+const ID_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.1");
+const ID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const ID_AUTH_ENVELOPED_DATA: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.16.1.23");
+const ID_CONTENT_TYPE: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.3");
+const ID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+const RSA_ENCRYPTION: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.1");
+const ID_AES256_GCM: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.1.46");
+/// `sha256WithRSAEncryption`, `sha384WithRSAEncryption` and
+/// `sha512WithRSAEncryption`, accepted in place of `rsaEncryption`.
+const SHA_WITH_RSA_ENCRYPTION: [ObjectIdentifier; 3] = [
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11"),
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.12"),
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.13"),
+];
+
+const TAG_NULL: u8 = 0x05;
+const TAG_SET: u8 = 0x31;
+/// `[0]`, constructed.
+const TAG_EXPLICIT_0: u8 = 0xa0;
+/// `[0]`, primitive: subject key identifiers and encrypted content.
+const TAG_IMPLICIT_0: u8 = 0x80;
+/// `[2]`, constructed.
+const TAG_EXPLICIT_2: u8 = 0xa2;
+
+/// Length of the subject key identifiers.
+const SKI_LEN: usize = 20;
+/// Length of the AES-GCM nonces.
+const GCM_NONCE_LEN: usize = 12;
+/// Length of the AES-GCM authentication tags.
+const GCM_TAG_LEN: usize = 16;
+
+/// Signature scheme of signed data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+    /// `RSASSA-PKCS1-v1_5`, identified as `rsaEncryption` as specified by
+    /// RFC 3370.
+    #[default]
+    Pkcs1v15,
+    /// `RSASSA-PSS` with MGF1 and a salt as long as the digest.
+    Pss,
+}
+
+/// Key transport scheme of enveloped data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyTransport {
+    /// `RSAES-OAEP` with SHA-256 and MGF1 with SHA-256.
+    #[default]
+    Oaep,
+    /// `RSAES-PKCS1-v1_5`, for recipients which do not support OAEP.
+    Pkcs1v15,
+}
+
+/// Builder for CMS `SignedData` with a single signer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedDataBuilder<'a> {
+    content: &'a [u8],
+    detached: bool,
+    certificates: Vec<&'a [u8]>,
+    signature_algorithm: SignatureAlgorithm,
+}
+
+impl<'a> SignedDataBuilder<'a> {
+    /// Create a builder signing `content`, which is encapsulated in the
+    /// signed data by default.
+    pub fn new(content: &'a [u8]) -> Self {
+        Self {
+            content,
+            detached: false,
+            certificates: Vec::new(),
+            signature_algorithm: SignatureAlgorithm::default(),
+        }
+    }
+
+    /// Leave the content out of the signed data, as for detached S/MIME
+    /// signatures. The verifier has to provide it.
+    pub fn detached(mut self, detached: bool) -> Self {
+        self.detached = detached;
+        self
+    }
+
+    /// Include the DER encoding of a certificate, e.g. that of the signer.
+    pub fn certificate(mut self, certificate: &'a [u8]) -> Self {
+        self.certificates.push(certificate);
+        self
+    }
+
+    /// Set the signature scheme, PKCS#1 v1.5 by default.
+    pub fn signature_algorithm(mut self, signature_algorithm: SignatureAlgorithm) -> Self {
+        self.signature_algorithm = signature_algorithm;
+        self
+    }
+
+    /// Sign the content with `key` and the digest `D`, returning the DER
+    /// encoding of a `ContentInfo` holding the signed data.
+    ///
+    /// `rng` is used for blinding, and for the salt of PSS.
+    pub fn sign<D, R>(&self, rng: &mut R, key: &RsaPrivateKey) -> Result<Vec<u8>>
+    where
+        D: 'static + Digest + DynDigest + AssociatedOid + Send + Sync,
+        R: CryptoRngCore,
+    {
+        let digest_algorithm = tlv(TAG_SEQUENCE, &oid(&D::OID));
+        let signed_attrs = concat(&[
+            attribute(&ID_CONTENT_TYPE, &oid(&ID_DATA)),
+            attribute(
+                &ID_MESSAGE_DIGEST,
+                &tlv(TAG_OCTET_STRING, &D::digest(self.content)),
+            ),
+        ]);
+        // The signature covers the attributes encoded as a SET.
+        let hashed = D::digest(tlv(TAG_SET, &signed_attrs));
+        let (signature_algorithm, signature) = match self.signature_algorithm {
+            SignatureAlgorithm::Pkcs1v15 => (
+                tlv(
+                    TAG_SEQUENCE,
+                    &concat(&[oid(&RSA_ENCRYPTION), tlv(TAG_NULL, &[])]),
+                ),
+                key.sign_with_rng(rng, Pkcs1v15Sign::new::<D>(), &hashed)?,
+            ),
+            SignatureAlgorithm::Pss => (
+                get_default_pss_signature_algo_id::<D>()?.to_der()?,
+                key.sign_with_rng(rng, Pss::new::<D>(), &hashed)?,
+            ),
+        };
+
+        let signer_info = tlv(
+            TAG_SEQUENCE,
+            &concat(&[
+                tlv(TAG_INTEGER, &[3]),
+                tlv(
+                    TAG_IMPLICIT_0,
+                    &subject_key_identifier(&key.to_public_key())?,
+                ),
+                digest_algorithm.clone(),
+                tlv(TAG_EXPLICIT_0, &signed_attrs),
+                signature_algorithm,
+                tlv(TAG_OCTET_STRING, &signature),
+            ]),
+        );
+
+        let mut encap_content_info = oid(&ID_DATA);
+        if !self.detached {
+            let content = tlv(TAG_OCTET_STRING, self.content);
+            encap_content_info.extend_from_slice(&tlv(TAG_EXPLICIT_0, &content));
+        }
+
+        let mut signed_data = vec![
+            tlv(TAG_INTEGER, &[3]),
+            tlv(TAG_SET, &digest_algorithm),
+            tlv(TAG_SEQUENCE, &encap_content_info),
+        ];
+        if !self.certificates.is_empty() {
+            signed_data.push(tlv(TAG_EXPLICIT_0, &self.certificates.concat()));
+        }
+        signed_data.push(tlv(TAG_SET, &signer_info));
+        Ok(content_info(
+            &ID_SIGNED_DATA,
+            &tlv(TAG_SEQUENCE, &concat(&signed_data)),
+        ))
+    }
+}
+
+/// Verify CMS signed data signed by the owner of `public_key`, returning its
+/// content.
+///
+/// Detached signed data needs the `detached_content`, which is then
+/// returned. Fails with [`Error::Verification`] if the data is malformed or
+/// has no valid signature by `public_key` over the content.
+pub fn verify(
+    signed_data: &[u8],
+    public_key: &RsaPublicKey,
+    detached_content: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    verify_signed_data(signed_data, public_key, detached_content).map_err(|_| Error::Verification)
+}
+
+fn verify_signed_data(
+    signed_data: &[u8],
+    public_key: &RsaPublicKey,
+    detached_content: Option<&[u8]>,
+) -> Result<Vec<u8>> {
+    let signed_data = open_content_info(signed_data, &ID_SIGNED_DATA)?;
+    let (_version, rest) = expect(signed_data, TAG_INTEGER)?;
+    let (_digest_algorithms, rest) = expect(rest, TAG_SET)?;
+    let (encap_content_info, rest) = expect(rest, TAG_SEQUENCE)?;
+    let (content_type, encapsulated) = expect(encap_content_info, TAG_OID)?;
+    let content = match (encapsulated.is_empty(), detached_content) {
+        (true, Some(content)) => content,
+        (false, None) => {
+            let (explicit, _) = expect(encapsulated, TAG_EXPLICIT_0)?;
+            expect(explicit, TAG_OCTET_STRING)?.0
+        }
+        _ => return Err(Error::Verification),
+    };
+
+    // Skip the certificates and CRLs.
+    let mut rest = rest;
+    let signer_infos = loop {
+        let (tag, contents, next) = read(rest)?;
+        if tag == TAG_SET {
+            break contents;
+        }
+        rest = next;
+    };
+
+    let ski = subject_key_identifier(public_key)?;
+    let mut signer_infos = signer_infos;
+    while !signer_infos.is_empty() {
+        let (signer_info, next) = expect(signer_infos, TAG_SEQUENCE)?;
+        signer_infos = next;
+        let (_version, rest) = expect(signer_info, TAG_INTEGER)?;
+        let (sid, rest) = expect(rest, TAG_IMPLICIT_0).unwrap_or_default();
+        if sid != ski {
+            continue;
+        }
+        if verify_signer_info(rest, public_key, content_type, content).is_ok() {
+            return Ok(content.to_vec());
+        }
+    }
+    Err(Error::Verification)
+}
+
+/// Verifies a `SignerInfo` from its `digestAlgorithm` on.
+fn verify_signer_info(
+    signer_info: &[u8],
+    public_key: &RsaPublicKey,
+    content_type: &[u8],
+    content: &[u8],
+) -> Result<()> {
+    let (digest_algorithm, rest) = expect(signer_info, TAG_SEQUENCE)?;
+    let (digest_oid, _) = expect(digest_algorithm, TAG_OID)?;
+    let (signed_attrs, rest) = expect(rest, TAG_EXPLICIT_0)?;
+    let (signature_algorithm, rest) = expect(rest, TAG_SEQUENCE)?;
+    let (signature, _) = expect(rest, TAG_OCTET_STRING)?;
+
+    if digest_oid == Sha256::OID.as_bytes() {
+        verify_signature::<Sha256>(public_key, signed_attrs, signature_algorithm, signature)?;
+        check_signed_attrs::<Sha256>(signed_attrs, content_type, content)
+    } else if digest_oid == Sha384::OID.as_bytes() {
+        verify_signature::<Sha384>(public_key, signed_attrs, signature_algorithm, signature)?;
+        check_signed_attrs::<Sha384>(signed_attrs, content_type, content)
+    } else if digest_oid == Sha512::OID.as_bytes() {
+        verify_signature::<Sha512>(public_key, signed_attrs, signature_algorithm, signature)?;
+        check_signed_attrs::<Sha512>(signed_attrs, content_type, content)
+    } else {
+        Err(Error::Verification)
+    }
+}
+
+/// Verifies the signature over the signed attributes.
+fn verify_signature<D>(
+    public_key: &RsaPublicKey,
+    signed_attrs: &[u8],
+    signature_algorithm: &[u8],
+    signature: &[u8],
+) -> Result<()>
+where
+    D: 'static + Digest + DynDigest + AssociatedOid + Send + Sync,
+{
+    let hashed = D::digest(tlv(TAG_SET, signed_attrs));
+    let (algorithm, params) = expect(signature_algorithm, TAG_OID)?;
+    if algorithm == RSA_ENCRYPTION.as_bytes()
+        || SHA_WITH_RSA_ENCRYPTION
+            .iter()
+            .any(|oid| algorithm == oid.as_bytes())
+    {
+        public_key.verify(Pkcs1v15Sign::new::<D>(), &hashed, signature)
+    } else if algorithm == ID_RSASSA_PSS.as_bytes() {
+        let salt_len = pss_salt_len::<D>(params)?;
+        public_key.verify(Pss::new_with_salt::<D>(salt_len), &hashed, signature)
+    } else {
+        Err(Error::Verification)
+    }
+}
+
+/// Parses `RSASSA-PSS-params`, checking that the hash is `D` and returning
+/// the salt length.
+fn pss_salt_len<D: AssociatedOid>(params: &[u8]) -> Result<usize> {
+    let (mut params, _) = expect(params, TAG_SEQUENCE)?;
+    let mut hash_ok = false;
+    let mut salt_len = 20;
+    while !params.is_empty() {
+        let (tag, contents, rest) = read(params)?;
+        match tag {
+            TAG_EXPLICIT_0 => {
+                let (hash, _) = expect(contents, TAG_SEQUENCE)?;
+                hash_ok = expect(hash, TAG_OID)?.0 == D::OID.as_bytes();
+            }
+            TAG_EXPLICIT_2 => {
+                let (len, _) = expect(contents, TAG_INTEGER)?;
+                if len.len() > 2 {
+                    return Err(Error::Verification);
+                }
+                salt_len = len.iter().fold(0, |acc, &b| (acc << 8) | usize::from(b));
+            }
+            _ => {}
+        }
+        params = rest;
+    }
+    if !hash_ok {
+        return Err(Error::Verification);
+    }
+    Ok(salt_len)
+}
+
+/// Checks that the signed attributes hold the content type and the digest
+/// of the content.
+fn check_signed_attrs<D: Digest>(
+    mut signed_attrs: &[u8],
+    content_type: &[u8],
+    content: &[u8],
+) -> Result<()> {
+    let (mut has_content_type, mut has_digest) = (false, false);
+    while !signed_attrs.is_empty() {
+        let (attribute, rest) = expect(signed_attrs, TAG_SEQUENCE)?;
+        signed_attrs = rest;
+        let (attr_type, values) = expect(attribute, TAG_OID)?;
+        let (values, _) = expect(values, TAG_SET)?;
+        if attr_type == ID_CONTENT_TYPE.as_bytes() {
+            has_content_type = expect(values, TAG_OID)?.0 == content_type;
+        } else if attr_type == ID_MESSAGE_DIGEST.as_bytes() {
+            has_digest = expect(values, TAG_OCTET_STRING)?.0 == D::digest(content).as_slice();
+        }
+    }
+    if has_content_type && has_digest {
+        Ok(())
+    } else {
+        Err(Error::Verification)
+    }
+}
+
+/// Encrypt `content` for each of the `recipients`, returning the DER
+/// encoding of a `ContentInfo` holding `AuthEnvelopedData`.
+///
+/// The content is encrypted with AES-256-GCM under a random key, which is
+/// encrypted for each recipient with `key_transport`.
+pub fn encrypt<R: CryptoRngCore>(
+    rng: &mut R,
+    recipients: &[&RsaPublicKey],
+    key_transport: KeyTransport,
+    content: &[u8],
+) -> Result<Vec<u8>> {
+    if recipients.is_empty() {
+        return Err(Error::InvalidArguments);
+    }
+    let mut key = Zeroizing::new([0u8; 32]);
+    rng.fill_bytes(&mut key[..]);
+    let mut nonce = Nonce::<Aes256Gcm>::default();
+    rng.fill_bytes(&mut nonce);
+
+    let mut recipient_infos = Vec::with_capacity(recipients.len());
+    for recipient in recipients {
+        let encrypted_key = match key_transport {
+            KeyTransport::Oaep => recipient.encrypt(rng, Oaep::new::<Sha256>(), &key[..])?,
+            KeyTransport::Pkcs1v15 => recipient.encrypt(rng, Pkcs1v15Encrypt, &key[..])?,
+        };
+        recipient_infos.push(tlv(
+            TAG_SEQUENCE,
+            &concat(&[
+                tlv(TAG_INTEGER, &[2]),
+                tlv(TAG_IMPLICIT_0, &subject_key_identifier(recipient)?),
+                key_encryption_algorithm(key_transport)?,
+                tlv(TAG_OCTET_STRING, &encrypted_key),
+            ]),
+        ));
+    }
+    recipient_infos.sort();
+
+    let cipher = Aes256Gcm::new_from_slice(&key[..]).map_err(|_| Error::Aead)?;
+    let mut ciphertext = cipher.encrypt(&nonce, content).map_err(|_| Error::Aead)?;
+    let mac = ciphertext.split_off(ciphertext.len() - GCM_TAG_LEN);
+
+    let gcm_params = concat(&[
+        tlv(TAG_OCTET_STRING, &nonce),
+        tlv(TAG_INTEGER, &[GCM_TAG_LEN as u8]),
+    ]);
+    let content_encryption_algorithm = tlv(
+        TAG_SEQUENCE,
+        &concat(&[oid(&ID_AES256_GCM), tlv(TAG_SEQUENCE, &gcm_params)]),
+    );
+    let encrypted_content_info = tlv(
+        TAG_SEQUENCE,
+        &concat(&[
+            oid(&ID_DATA),
+            content_encryption_algorithm,
+            tlv(TAG_IMPLICIT_0, &ciphertext),
+        ]),
+    );
+    let auth_enveloped_data = concat(&[
+        tlv(TAG_INTEGER, &[0]),
+        tlv(TAG_SET, &concat(&recipient_infos)),
+        encrypted_content_info,
+        tlv(TAG_OCTET_STRING, &mac),
+    ]);
+    Ok(content_info(
+        &ID_AUTH_ENVELOPED_DATA,
+        &tlv(TAG_SEQUENCE, &auth_enveloped_data),
+    ))
+}
+
+/// Decrypt CMS `AuthEnvelopedData` created by [`encrypt`] or another
+/// implementation with AES-256-GCM, for the owner of `key`.
+///
+/// `rng` is used to blind the RSA decryption. Fails with
+/// [`Error::Decryption`] if the data is malformed, uses other algorithms,
+/// has no recipient matching `key`, or fails to decrypt.
+pub fn decrypt<R: CryptoRngCore>(
+    rng: &mut R,
+    key: &RsaPrivateKey,
+    enveloped_data: &[u8],
+) -> Result<Vec<u8>> {
+    decrypt_auth_enveloped_data(rng, key, enveloped_data).map_err(|_| Error::Decryption)
+}
+
+fn decrypt_auth_enveloped_data<R: CryptoRngCore>(
+    rng: &mut R,
+    key: &RsaPrivateKey,
+    enveloped_data: &[u8],
+) -> Result<Vec<u8>> {
+    let enveloped_data = open_content_info(enveloped_data, &ID_AUTH_ENVELOPED_DATA)?;
+    let (_version, rest) = expect(enveloped_data, TAG_INTEGER)?;
+    let (mut recipient_infos, rest) = expect(rest, TAG_SET)?;
+    let (encrypted_content_info, rest) = expect(rest, TAG_SEQUENCE)?;
+    let (mac, _) = expect(rest, TAG_OCTET_STRING)?;
+
+    let ski = subject_key_identifier(&key.to_public_key())?;
+    let content_key = loop {
+        let (recipient_info, next) = expect(recipient_infos, TAG_SEQUENCE)?;
+        recipient_infos = next;
+        let (_version, rest) = expect(recipient_info, TAG_INTEGER)?;
+        let Ok((rid, rest)) = expect(rest, TAG_IMPLICIT_0) else {
+            continue;
+        };
+        if rid != ski {
+            continue;
+        }
+        let (algorithm, rest) = expect(rest, TAG_SEQUENCE)?;
+        let (encrypted_key, _) = expect(rest, TAG_OCTET_STRING)?;
+        let algorithm = tlv(TAG_SEQUENCE, algorithm);
+        let algorithm = AlgorithmIdentifierRef::from_der(&algorithm)?;
+        break if algorithm.oid == RSA_ENCRYPTION {
+            Zeroizing::new(key.decrypt_blinded(rng, Pkcs1v15Encrypt, encrypted_key)?)
+        } else {
+            let oaep = Oaep::try_from(algorithm)?;
+            Zeroizing::new(key.decrypt_blinded(rng, oaep, encrypted_key)?)
+        };
+    };
+
+    let (_content_type, rest) = expect(encrypted_content_info, TAG_OID)?;
+    let (algorithm, rest) = expect(rest, TAG_SEQUENCE)?;
+    let (ciphertext, _) = expect(rest, TAG_IMPLICIT_0)?;
+    let (algorithm_oid, params) = expect(algorithm, TAG_OID)?;
+    let (params, _) = expect(params, TAG_SEQUENCE)?;
+    let (nonce, rest) = expect(params, TAG_OCTET_STRING)?;
+    let icv_len = match expect(rest, TAG_INTEGER) {
+        Ok((len, _)) => len,
+        Err(_) => &[12][..],
+    };
+    if algorithm_oid != ID_AES256_GCM.as_bytes()
+        || nonce.len() != GCM_NONCE_LEN
+        || icv_len != [GCM_TAG_LEN as u8]
+    {
+        return Err(Error::Decryption);
+    }
+
+    let cipher = Aes256Gcm::new_from_slice(&content_key).map_err(|_| Error::Decryption)?;
+    let nonce = Nonce::<Aes256Gcm>::try_from(nonce).map_err(|_| Error::Decryption)?;
+    cipher
+        .decrypt(&nonce, [ciphertext, mac].concat().as_slice())
+        .map_err(|_| Error::Decryption)
+}
+
+/// Returns the subject key identifier of `public_key`: the leftmost 160
+/// bits of the SHA-256 digest of the `subjectPublicKey` BIT STRING value.
+fn subject_key_identifier(public_key: &RsaPublicKey) -> Result<[u8; SKI_LEN]> {
+    let spki = public_key.to_public_key_der()?;
+    let (spki, _) = expect(spki.as_bytes(), TAG_SEQUENCE)?;
+    let (_algorithm, rest) = expect(spki, TAG_SEQUENCE)?;
+    let (subject_public_key, _) = expect(rest, TAG_BIT_STRING)?;
+    // Skip the count of unused bits.
+    let digest = Sha256::digest(subject_public_key.get(1..).unwrap_or_default());
+    let mut ski = [0u8; SKI_LEN];
+    ski.copy_from_slice(&digest[..SKI_LEN]);
+    Ok(ski)
+}
+
+/// Encodes the key encryption `AlgorithmIdentifier` of `key_transport`.
+fn key_encryption_algorithm(key_transport: KeyTransport) -> Result<Vec<u8>> {
+    Ok(match key_transport {
+        KeyTransport::Oaep => get_default_oaep_algo_id::<Sha256>()?.to_der()?,
+        KeyTransport::Pkcs1v15 => tlv(
+            TAG_SEQUENCE,
+            &concat(&[oid(&RSA_ENCRYPTION), tlv(TAG_NULL, &[])]),
+        ),
+    })
+}
+
+fn oid(oid: &ObjectIdentifier) -> Vec<u8> {
+    tlv(TAG_OID, oid.as_bytes())
+}
+
+/// Encodes an `Attribute` with a single value.
+fn attribute(attr_type: &ObjectIdentifier, value: &[u8]) -> Vec<u8> {
+    tlv(
+        TAG_SEQUENCE,
+        &concat(&[oid(attr_type), tlv(TAG_SET, value)]),
+    )
+}
+
+/// Encodes a `ContentInfo`.
+fn content_info(content_type: &ObjectIdentifier, content: &[u8]) -> Vec<u8> {
+    tlv(
+        TAG_SEQUENCE,
+        &concat(&[oid(content_type), tlv(TAG_EXPLICIT_0, content)]),
+    )
+}
+
+/// Returns the content of a `ContentInfo` of the given type.
+fn open_content_info<'a>(der: &'a [u8], content_type: &ObjectIdentifier) -> Result<&'a [u8]> {
+    let (content_info, _) = expect(der, TAG_SEQUENCE)?;
+    let (actual_type, rest) = expect(content_info, TAG_OID)?;
+    if actual_type != content_type.as_bytes() {
+        return Err(Error::InvalidArguments);
+    }
+    let (content, _) = expect(rest, TAG_EXPLICIT_0)?;
+    Ok(expect(content, TAG_SEQUENCE)?.0)
+}
+
+/// Splits the first TLV off `der`, returning its tag, its contents and the
+/// remaining bytes.
+fn read(der: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = der.split_first().ok_or(Error::InvalidArguments)?;
+    let (&first, rest) = rest.split_first().ok_or(Error::InvalidArguments)?;
+    let (len, rest) = match first {
+        len if len < 0x80 => (usize::from(len), rest),
+        0x81..=0x84 => {
+            let n = usize::from(first & 0x7f);
+            if rest.len() < n {
+                return Err(Error::InvalidArguments);
+            }
+            let (len, rest) = rest.split_at(n);
+            let len = len.iter().fold(0, |acc, &b| (acc << 8) | usize::from(b));
+            (len, rest)
+        }
+        // Indefinite lengths are not DER.
+        _ => return Err(Error::InvalidArguments),
+    };
+    if rest.len() < len {
+        return Err(Error::InvalidArguments);
+    }
+    let (contents, rest) = rest.split_at(len);
+    Ok((tag, contents, rest))
+}
+
+/// Like [`read`], but fails if the tag is not `tag`.
+fn expect(der: &[u8], tag: u8) -> Result<(&[u8], &[u8])> {
+    match read(der)? {
+        (actual, contents, rest) if actual == tag => Ok((contents, rest)),
+        _ => Err(Error::InvalidArguments),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cert::{generate_self_signed_cert, KeyUsage, Validity};
+    use crate::csr::COMMON_NAME;
+    use crate::pkcs1v15::SigningKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_read() {
+        assert_eq!(
+            read(&[0x04, 0x01, 0xaa, 0xbb]).unwrap(),
+            (0x04, &[0xaa][..], &[0xbb][..])
+        );
+        let long = tlv(TAG_OCTET_STRING, &[0; 300]);
+        assert_eq!(read(&long).unwrap().1.len(), 300);
+        assert!(read(&[0x30, 0x80, 0, 0]).is_err());
+        assert!(read(&[0x04, 0x02, 0xaa]).is_err());
+    }
+
+    #[test]
+    fn test_signed_data() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let public_key = key.to_public_key();
+        let other = RsaPrivateKey::new(&mut rng, 1024).unwrap().to_public_key();
+        let content = b"signed content";
+
+        let signed = SignedDataBuilder::new(content)
+            .sign::<Sha256, _>(&mut rng, &key)
+            .unwrap();
+        assert_eq!(verify(&signed, &public_key, None).unwrap(), content);
+        assert_eq!(verify(&signed, &other, None), Err(Error::Verification));
+        assert_eq!(
+            verify(&signed, &public_key, Some(content)),
+            Err(Error::Verification)
+        );
+
+        let mut tampered = signed.clone();
+        let pos = tampered
+            .windows(content.len())
+            .position(|w| w == content)
+            .unwrap();
+        tampered[pos] ^= 1;
+        assert_eq!(
+            verify(&tampered, &public_key, None),
+            Err(Error::Verification)
+        );
+
+        let cert = generate_self_signed_cert(
+            &SigningKey::<Sha256>::new(key.clone()),
+            &[(COMMON_NAME, "signer")],
+            Validity::new(1_700_000_000, 1_800_000_000),
+            KeyUsage::DIGITAL_SIGNATURE,
+        )
+        .unwrap();
+        let signed = SignedDataBuilder::new(content)
+            .detached(true)
+            .certificate(&cert)
+            .signature_algorithm(SignatureAlgorithm::Pss)
+            .sign::<Sha384, _>(&mut rng, &key)
+            .unwrap();
+        assert!(signed.windows(cert.len()).any(|w| w == cert));
+        assert_eq!(
+            verify(&signed, &public_key, Some(content)).unwrap(),
+            content
+        );
+        assert_eq!(
+            verify(&signed, &public_key, Some(b"other content")),
+            Err(Error::Verification)
+        );
+        assert_eq!(verify(&signed, &public_key, None), Err(Error::Verification));
+    }
+
+    #[test]
+    fn test_enveloped_data() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let alice = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let bob = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let eve = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let recipients = [&alice.to_public_key(), &bob.to_public_key()];
+        let content = b"enveloped content";
+
+        for key_transport in [KeyTransport::Oaep, KeyTransport::Pkcs1v15] {
+            let enveloped = encrypt(&mut rng, &recipients, key_transport, content).unwrap();
+            assert_eq!(decrypt(&mut rng, &alice, &enveloped).unwrap(), content);
+            assert_eq!(decrypt(&mut rng, &bob, &enveloped).unwrap(), content);
+            assert_eq!(decrypt(&mut rng, &eve, &enveloped), Err(Error::Decryption));
+
+            let mut tampered = enveloped.clone();
+            let last = tampered.len() - 1;
+            tampered[last] ^= 1;
+            assert_eq!(decrypt(&mut rng, &alice, &tampered), Err(Error::Decryption));
+        }
+
+        assert_eq!(
+            encrypt(&mut rng, &[], KeyTransport::Oaep, content),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
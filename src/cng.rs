@@ -0,0 +1,292 @@
+//! Private keys held by Windows CNG key storage providers.
+//!
+//! [`CngPrivateKey`] performs the raw private key operation with
+//! `NCryptDecrypt` and the `NCRYPT_NO_PADDING_FLAG`, while the padding is
+//! applied by this crate. This works with keys which cannot be exported, e.g.
+//! machine keys of the certificate store or keys of smart card providers, as
+//! long as the key allows decryption and its provider supports unpadded
+//! operations. The key implements [`RsaPrivateOps`], so it can be used with the
+//! padding schemes and the signing and decrypting keys of this crate:
+//!
+//! ```no_run
+//! use rsa::cng::CngPrivateKey;
+//! use rsa::pkcs1v15::SigningKey;
+//! use rsa::signature::Signer;
+//! use sha2::Sha256;
+//!
+//! let key = CngPrivateKey::open("Microsoft Software Key Storage Provider", "service key", true)?;
+//! let signing_key = SigningKey::<Sha256, _>::new(key);
+//! let signature = signing_key.sign(b"hello world");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::vec::Vec;
+use core::{fmt, ptr};
+use std::sync::Mutex;
+
+use crypto_bigint::modular::BoxedMontyParams;
+use crypto_bigint::{BoxedUint, NonZero};
+use rand_core::CryptoRngCore;
+use windows_sys::core::HRESULT;
+use windows_sys::Win32::Security::Cryptography::{
+    NCryptDecrypt, NCryptExportKey, NCryptFreeObject, NCryptOpenKey, NCryptOpenStorageProvider,
+    BCRYPT_RSAPUBLIC_BLOB, BCRYPT_RSAPUBLIC_MAGIC, NCRYPT_KEY_HANDLE, NCRYPT_MACHINE_KEY_FLAG,
+    NCRYPT_NO_PADDING_FLAG, NCRYPT_PROV_HANDLE,
+};
+use zeroize::Zeroizing;
+
+use crate::algorithms::rsa::rsa_external_op;
+use crate::errors::{Error, Result};
+use crate::traits::{PublicKeyParts, RsaPrivateOps};
+use crate::RsaPublicKey;
+
+/// RSA private key of a CNG key storage provider.
+///
+/// Operations are serialized, as not all providers support concurrent use of
+/// a key handle. Blinding is left to the provider, and its results are
+/// checked with the public key before they are used. The handle is freed when
+/// the key is dropped.
+pub struct CngPrivateKey {
+    handle: NCRYPT_KEY_HANDLE,
+    lock: Mutex<()>,
+    public_key: RsaPublicKey,
+}
+
+impl CngPrivateKey {
+    /// Open the persisted key `key_name` of the key storage provider
+    /// `provider`, from the machine key store if `machine_key` is set and
+    /// from the store of the current user otherwise.
+    pub fn open(provider: &str, key_name: &str, machine_key: bool) -> Result<Self> {
+        let provider = wide(provider);
+        let key_name = wide(key_name);
+        let flags = if machine_key {
+            NCRYPT_MACHINE_KEY_FLAG
+        } else {
+            0
+        };
+
+        let mut provider_handle: NCRYPT_PROV_HANDLE = 0;
+        // SAFETY: the provider name is a NUL-terminated wide string.
+        check(unsafe { NCryptOpenStorageProvider(&mut provider_handle, provider.as_ptr(), 0) })?;
+
+        let mut handle: NCRYPT_KEY_HANDLE = 0;
+        // SAFETY: the provider handle is valid and the key name is a
+        // NUL-terminated wide string.
+        let status =
+            unsafe { NCryptOpenKey(provider_handle, &mut handle, key_name.as_ptr(), 0, flags) };
+        // SAFETY: the provider handle is valid and no longer used. The key
+        // keeps a reference to its provider.
+        unsafe { NCryptFreeObject(provider_handle) };
+        check(status)?;
+
+        // SAFETY: the handle was opened above and is owned by the key.
+        unsafe { Self::from_raw_handle(handle) }
+    }
+
+    /// Use the key `handle`, e.g. obtained from a certificate of the
+    /// certificate store with `CryptAcquireCertificatePrivateKey`.
+    ///
+    /// The public key is exported from the provider, which is allowed for
+    /// keys which cannot be exported.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid CNG key handle. It is owned by the returned
+    /// key, and freed when it is dropped, also on errors.
+    pub unsafe fn from_raw_handle(handle: NCRYPT_KEY_HANDLE) -> Result<Self> {
+        let public_key = match export_public_key(handle) {
+            Ok(public_key) => public_key,
+            Err(err) => {
+                NCryptFreeObject(handle);
+                return Err(err);
+            }
+        };
+
+        Ok(Self {
+            handle,
+            lock: Mutex::new(()),
+            public_key,
+        })
+    }
+
+    /// Returns the handle of the key.
+    pub fn handle(&self) -> NCRYPT_KEY_HANDLE {
+        self.handle
+    }
+
+    /// Returns the public key.
+    pub fn to_public_key(&self) -> RsaPublicKey {
+        self.public_key.clone()
+    }
+}
+
+impl Drop for CngPrivateKey {
+    fn drop(&mut self) {
+        // SAFETY: the handle is owned by the key.
+        unsafe { NCryptFreeObject(self.handle) };
+    }
+}
+
+impl fmt::Debug for CngPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CngPrivateKey")
+            .field("handle", &self.handle)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsRef<RsaPublicKey> for CngPrivateKey {
+    fn as_ref(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+}
+
+impl PublicKeyParts for CngPrivateKey {
+    fn n(&self) -> &NonZero<BoxedUint> {
+        self.public_key.n()
+    }
+
+    fn e(&self) -> &BoxedUint {
+        self.public_key.e()
+    }
+
+    fn n_params(&self) -> &BoxedMontyParams {
+        self.public_key.n_params()
+    }
+}
+
+impl RsaPrivateOps for CngPrivateKey {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        _rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        rsa_external_op(&self.public_key, c, |input| {
+            let _guard = self.lock.lock().map_err(|_| Error::Backend)?;
+            let mut output = Zeroizing::new(vec![0u8; input.len()]);
+            let mut len = 0u32;
+            // SAFETY: the handle is valid, and the buffers outlive the call
+            // and have the given lengths.
+            check(unsafe {
+                NCryptDecrypt(
+                    self.handle,
+                    input.as_ptr(),
+                    input.len() as u32,
+                    ptr::null(),
+                    output.as_mut_ptr(),
+                    output.len() as u32,
+                    &mut len,
+                    NCRYPT_NO_PADDING_FLAG,
+                )
+            })?;
+            output.truncate(len as usize);
+            Ok(output)
+        })
+    }
+}
+
+/// Exports the public key of `handle` as a `BCRYPT_RSAPUBLIC_BLOB`.
+///
+/// # Safety
+///
+/// `handle` must be a valid CNG key handle.
+unsafe fn export_public_key(handle: NCRYPT_KEY_HANDLE) -> Result<RsaPublicKey> {
+    let mut len = 0u32;
+    check(NCryptExportKey(
+        handle,
+        0,
+        BCRYPT_RSAPUBLIC_BLOB,
+        ptr::null(),
+        ptr::null_mut(),
+        0,
+        &mut len,
+        0,
+    ))?;
+
+    let mut blob = vec![0u8; len as usize];
+    check(NCryptExportKey(
+        handle,
+        0,
+        BCRYPT_RSAPUBLIC_BLOB,
+        ptr::null(),
+        blob.as_mut_ptr(),
+        len,
+        &mut len,
+        0,
+    ))?;
+    blob.truncate(len as usize);
+    public_key_from_blob(&blob)
+}
+
+/// Decodes a `BCRYPT_RSAPUBLIC_BLOB`: a `BCRYPT_RSAKEY_BLOB` header of six
+/// little-endian `u32`s, followed by the big-endian exponent and modulus.
+fn public_key_from_blob(blob: &[u8]) -> Result<RsaPublicKey> {
+    const HEADER_LEN: usize = 24;
+    if blob.len() < HEADER_LEN {
+        return Err(Error::Backend);
+    }
+    let field = |i: usize| {
+        let mut bytes = [0u8; 4];
+        bytes.copy_from_slice(&blob[4 * i..4 * i + 4]);
+        u32::from_le_bytes(bytes) as usize
+    };
+    let (magic, e_len, n_len) = (field(0), field(2), field(3));
+    if magic != BCRYPT_RSAPUBLIC_MAGIC as usize || blob.len() < HEADER_LEN + e_len + n_len {
+        return Err(Error::Backend);
+    }
+
+    let e = &blob[HEADER_LEN..HEADER_LEN + e_len];
+    let n = &blob[HEADER_LEN + e_len..HEADER_LEN + e_len + n_len];
+    RsaPublicKey::new(
+        BoxedUint::from_be_slice(n, n.len() as u32 * 8)?,
+        BoxedUint::from_be_slice(e, e.len() as u32 * 8)?,
+    )
+}
+
+/// Encodes `s` as a NUL-terminated wide string.
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(core::iter::once(0)).collect()
+}
+
+/// Maps failure statuses to [`Error::Backend`].
+fn check(status: HRESULT) -> Result<()> {
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(Error::Backend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RsaPrivateKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_public_key_from_blob() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let pub_key = RsaPrivateKey::new(&mut rng, 1024).unwrap().to_public_key();
+        let e = pub_key.e().to_be_bytes();
+        let n = pub_key.n().to_be_bytes();
+
+        let mut blob = Vec::new();
+        for field in [
+            BCRYPT_RSAPUBLIC_MAGIC,
+            1024,
+            e.len() as u32,
+            n.len() as u32,
+            0,
+            0,
+        ] {
+            blob.extend_from_slice(&field.to_le_bytes());
+        }
+        blob.extend_from_slice(&e);
+        blob.extend_from_slice(&n);
+        assert_eq!(public_key_from_blob(&blob).unwrap(), pub_key);
+
+        blob.truncate(blob.len() - 1);
+        assert!(public_key_from_blob(&blob).is_err());
+    }
+}
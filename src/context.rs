@@ -0,0 +1,279 @@
+//! Reusable buffers for repeated RSA operations.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crypto_bigint::{BoxedUint, Limb, Word};
+use rand_core::CryptoRngCore;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::algorithms::pad::uint_to_be_pad_into;
+use crate::algorithms::pkcs1v15::{
+    pkcs1v15_encrypt_pad_into, pkcs1v15_encrypt_unpad_index, pkcs1v15_sign_pad_into,
+    pkcs1v15_sign_unpad,
+};
+use crate::algorithms::rsa::{rsa_decrypt_and_check, rsa_encrypt};
+use crate::errors::{opaque_decryption, Error, Result};
+use crate::key::{check_public, RsaPrivateKey, RsaPublicKey};
+use crate::pkcs1v15::Pkcs1v15Sign;
+use crate::traits::PublicKeyParts;
+
+/// Scratch space for PKCS#1 v1.5 operations.
+///
+/// The context owns the buffers holding the encoded message and its integer
+/// representation, which the operations of [`RsaPrivateKey`] and
+/// [`RsaPublicKey`] allocate anew on every call. Reusing one context for
+/// repeated operations with keys of the same size saves these allocations,
+/// and results are written to caller-provided buffers.
+///
+/// Operations are not free of allocations: the modular arithmetic itself,
+/// including the Montgomery representations of its operands, allocates its
+/// temporaries through `crypto-bigint` on every call.
+///
+/// Decrypted messages are wiped from the context before returning, and the
+/// buffers are zeroized when the context is dropped.
+///
+/// ```
+/// use rsa::{Pkcs1v15Sign, RsaOpContext, RsaPrivateKey};
+///
+/// let mut rng = rand::thread_rng();
+/// let private_key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+/// let public_key = private_key.to_public_key();
+/// let scheme = Pkcs1v15Sign::new_unprefixed();
+///
+/// let mut ctx = RsaOpContext::new();
+/// let mut signature = [0u8; 256];
+/// for msg in [b"first", b"other"] {
+///     let len = ctx
+///         .sign_pkcs1v15(&mut rng, &private_key, &scheme, msg, &mut signature)
+///         .expect("failed to sign");
+///     ctx.verify_pkcs1v15(&public_key, &scheme, msg, &signature[..len])
+///         .expect("failed to verify");
+/// }
+/// ```
+#[derive(Default)]
+pub struct RsaOpContext {
+    /// Encoded message
+    em: Vec<u8>,
+    /// Integer representation of the input
+    input: Option<BoxedUint>,
+}
+
+impl fmt::Debug for RsaOpContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RsaOpContext").finish_non_exhaustive()
+    }
+}
+
+impl Drop for RsaOpContext {
+    fn drop(&mut self) {
+        self.em.zeroize();
+        self.input.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for RsaOpContext {}
+
+impl RsaOpContext {
+    /// Create an empty context. Buffers are allocated by the first operation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sign the digest `hashed` with PKCS#1 v1.5 padding into the start of
+    /// `out`, returning the length of the signature.
+    ///
+    /// `rng` is used to blind the private key operation.
+    pub fn sign_pkcs1v15<R: CryptoRngCore + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        key: &RsaPrivateKey,
+        scheme: &Pkcs1v15Sign,
+        hashed: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        if scheme.hash_len.is_some_and(|len| len != hashed.len()) {
+            return Err(Error::InputNotHashed);
+        }
+
+        let k = key.size();
+        let em = em_buffer(&mut self.em, k);
+        pkcs1v15_sign_pad_into(&scheme.prefix, hashed, em)?;
+        let em = load_be(&mut self.input, em, key.n_bits_precision())?;
+        let sig = rsa_decrypt_and_check(key, Some(rng), em)?;
+        uint_to_be_pad_into(&sig, k, out)
+    }
+
+    /// Verify a PKCS#1 v1.5 signature of the digest `hashed`.
+    pub fn verify_pkcs1v15(
+        &mut self,
+        key: &RsaPublicKey,
+        scheme: &Pkcs1v15Sign,
+        hashed: &[u8],
+        sig: &[u8],
+    ) -> Result<()> {
+        if scheme.hash_len.is_some_and(|len| len != hashed.len()) {
+            return Err(Error::InputNotHashed);
+        }
+
+        let k = key.size();
+        if sig.len() != k {
+            return Err(Error::Verification);
+        }
+        let sig = load_be(&mut self.input, sig, key.n_bits_precision())?;
+        if sig >= key.n().as_ref() {
+            return Err(Error::Verification);
+        }
+
+        let em = em_buffer(&mut self.em, k);
+        uint_to_be_pad_into(&key.verify_raw(sig)?, k, em)?;
+        pkcs1v15_sign_unpad(&scheme.prefix, hashed, em, k)
+    }
+
+    /// Encrypt `msg` with PKCS#1 v1.5 padding into the start of `out`,
+    /// returning the length of the ciphertext.
+    pub fn encrypt_pkcs1v15<R: CryptoRngCore + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        key: &RsaPublicKey,
+        msg: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        check_public(key)?;
+
+        let k = key.size();
+        let em = em_buffer(&mut self.em, k);
+        pkcs1v15_encrypt_pad_into(rng, msg, em)?;
+        let em = load_be(&mut self.input, em, key.n_bits_precision())?;
+        let c = rsa_encrypt(key, em)?;
+        uint_to_be_pad_into(&c, k, out)
+    }
+
+    /// Decrypt a PKCS#1 v1.5 `ciphertext` into the start of `out`, returning
+    /// the length of the plaintext.
+    ///
+    /// `rng` is used to blind the private key operation. See
+    /// [`Pkcs1v15Encrypt`](crate::Pkcs1v15Encrypt) on the dangers of
+    /// disclosing decryption failures.
+    pub fn decrypt_pkcs1v15<R: CryptoRngCore + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        key: &RsaPrivateKey,
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        let k = key.size();
+        let result =
+            opaque_decryption(self.pkcs1v15_unpad(rng, key, ciphertext)).and_then(|index| {
+                let msg = &self.em[index..k];
+                out.get_mut(..msg.len())
+                    .ok_or(Error::InvalidArguments)?
+                    .copy_from_slice(msg);
+                Ok(msg.len())
+            });
+        let len = k.min(self.em.len());
+        self.em[..len].zeroize();
+        result
+    }
+
+    /// Decrypts `ciphertext` into the encoded message buffer, returning the
+    /// index of the plaintext in it.
+    fn pkcs1v15_unpad<R: CryptoRngCore + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        key: &RsaPrivateKey,
+        ciphertext: &[u8],
+    ) -> Result<usize> {
+        check_public(key)?;
+
+        let k = key.size();
+        let c = load_be(&mut self.input, ciphertext, key.n_bits_precision())?;
+        let m = Zeroizing::new(rsa_decrypt_and_check(key, Some(rng), c)?);
+        let em = em_buffer(&mut self.em, k);
+        uint_to_be_pad_into(&m, k, em)?;
+
+        pkcs1v15_encrypt_unpad_index(em, k)
+    }
+}
+
+/// Returns the first `len` bytes of `buf`, growing it if needed.
+fn em_buffer(buf: &mut Vec<u8>, len: usize) -> &mut [u8] {
+    if buf.len() < len {
+        buf.zeroize();
+        buf.resize(len, 0);
+    }
+    &mut buf[..len]
+}
+
+/// Decodes the big-endian `bytes` into `slot`, reusing its integer if it has
+/// the requested precision.
+fn load_be<'a>(
+    slot: &'a mut Option<BoxedUint>,
+    bytes: &[u8],
+    bits_precision: u32,
+) -> Result<&'a BoxedUint> {
+    if bytes.len() * 8 > bits_precision as usize {
+        return Err(Error::InvalidArguments);
+    }
+    if slot
+        .as_ref()
+        .is_none_or(|x| x.bits_precision() != bits_precision)
+    {
+        slot.zeroize();
+        *slot = Some(BoxedUint::zero_with_precision(bits_precision));
+    }
+    let x = slot.as_mut().expect("initialized above");
+
+    let mut bytes = bytes.iter().rev();
+    for limb in x.as_limbs_mut() {
+        let mut word: Word = 0;
+        for (i, byte) in bytes.by_ref().take(Limb::BYTES).enumerate() {
+            word |= Word::from(*byte) << (8 * i);
+        }
+        *limb = Limb(word);
+    }
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pkcs1v15Encrypt;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_context() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let public_key = key.to_public_key();
+        let scheme = Pkcs1v15Sign::new_unprefixed();
+        let mut ctx = RsaOpContext::new();
+        let mut buf = [0u8; 64];
+
+        for msg in [&b"first message"[..], b"second"] {
+            let len = ctx
+                .sign_pkcs1v15(&mut rng, &key, &scheme, msg, &mut buf)
+                .unwrap();
+            assert_eq!(&buf[..len], &key.sign(scheme.clone(), msg).unwrap()[..]);
+            assert_eq!(
+                ctx.verify_pkcs1v15(&public_key, &scheme, msg, &buf[..len]),
+                Ok(())
+            );
+            assert_eq!(
+                ctx.verify_pkcs1v15(&public_key, &scheme, b"other", &buf[..len]),
+                Err(Error::Verification)
+            );
+
+            let len = ctx
+                .encrypt_pkcs1v15(&mut rng, &public_key, msg, &mut buf)
+                .unwrap();
+            let ciphertext = buf[..len].to_vec();
+            assert_eq!(key.decrypt(Pkcs1v15Encrypt, &ciphertext).unwrap(), msg);
+            let len = ctx
+                .decrypt_pkcs1v15(&mut rng, &key, &ciphertext, &mut buf)
+                .unwrap();
+            assert_eq!(&buf[..len], msg);
+            assert!(ctx.em.iter().all(|&b| b == 0));
+        }
+    }
+}
@@ -0,0 +1,453 @@
+//! PKCS#10 certificate signing requests.
+//!
+//! [`CsrBuilder`] builds a certification request as described in
+//! [RFC2986], with a subject distinguished name, subject alternative names
+//! and additional attributes, and signs it with any of the signing keys of
+//! this crate:
+//!
+//! ```
+//! use rsa::csr::{CsrBuilder, SubjectAltName};
+//! use rsa::pkcs1v15::SigningKey;
+//! use rsa::sha2::Sha256;
+//!
+//! let mut rng = rand::thread_rng();
+//! let key = rsa::RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+//! let csr = CsrBuilder::new()
+//!     .common_name("device-0042.example.com")
+//!     .organization("Example Inc.")
+//!     .country("US")
+//!     .subject_alt_name(SubjectAltName::Dns("device-0042.example.com".into()))
+//!     .build(&SigningKey::<Sha256>::new(key))
+//!     .expect("failed to build the request");
+//! ```
+//!
+//! The subject alternative names are requested with the `extensionRequest`
+//! attribute of [RFC2985 § 5.4.2].
+//!
+//! [RFC2986]: https://datatracker.ietf.org/doc/html/rfc2986
+//! [RFC2985 § 5.4.2]: https://datatracker.ietf.org/doc/html/rfc2985#section-5.4.2
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use const_oid::ObjectIdentifier;
+use core::net::IpAddr;
+use pkcs8::der::Encode;
+use pkcs8::spki::{DynSignatureAlgorithmIdentifier, EncodePublicKey, SignatureBitStringEncoding};
+use rand_core::CryptoRngCore;
+use signature::{Keypair, RandomizedSigner, Signer};
+
+use crate::errors::{Error, Result};
+
+/// `commonName` attribute type.
+pub const COMMON_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.3");
+/// `serialNumber` attribute type.
+pub const SERIAL_NUMBER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.5");
+/// `countryName` attribute type.
+pub const COUNTRY: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.6");
+/// `localityName` attribute type.
+pub const LOCALITY: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.7");
+/// `stateOrProvinceName` attribute type.
+pub const STATE_OR_PROVINCE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.8");
+/// `organizationName` attribute type.
+pub const ORGANIZATION: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.10");
+/// `organizationalUnitName` attribute type.
+pub const ORGANIZATIONAL_UNIT: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.11");
+/// `emailAddress` attribute type of PKCS#9.
+pub const EMAIL_ADDRESS: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.1");
+
+/// `challengePassword` attribute type of PKCS#9.
+pub const CHALLENGE_PASSWORD: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.7");
+/// `extensionRequest` attribute type of PKCS#9.
+const EXTENSION_REQUEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.14");
+/// `subjectAltName` extension.
+const SUBJECT_ALT_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.17");
+
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_BIT_STRING: u8 = 0x03;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_OID: u8 = 0x06;
+const TAG_UTF8_STRING: u8 = 0x0c;
+const TAG_PRINTABLE_STRING: u8 = 0x13;
+const TAG_IA5_STRING: u8 = 0x16;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_ATTRIBUTES: u8 = 0xa0;
+
+/// Subject alternative name of a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubjectAltName {
+    /// DNS name, e.g. `"www.example.com"`.
+    Dns(String),
+    /// Email address.
+    Email(String),
+    /// URI, e.g. a SPIFFE ID.
+    Uri(String),
+    /// IPv4 or IPv6 address.
+    Ip(IpAddr),
+}
+
+/// Builder for PKCS#10 certificate signing requests.
+///
+/// Subject attributes are encoded in the order they are added, one per
+/// relative distinguished name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsrBuilder {
+    subject: Vec<(ObjectIdentifier, String)>,
+    subject_alt_names: Vec<SubjectAltName>,
+    attributes: Vec<(ObjectIdentifier, Vec<Vec<u8>>)>,
+}
+
+impl CsrBuilder {
+    /// Create a builder for a request with an empty subject.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an attribute of type `oid` to the subject.
+    ///
+    /// Values are encoded as `PrintableString` for [`COUNTRY`] and
+    /// [`SERIAL_NUMBER`], as `IA5String` for [`EMAIL_ADDRESS`], and as
+    /// `UTF8String` otherwise.
+    pub fn subject_attribute(mut self, oid: ObjectIdentifier, value: &str) -> Self {
+        self.subject.push((oid, value.into()));
+        self
+    }
+
+    /// Add a common name to the subject.
+    pub fn common_name(self, value: &str) -> Self {
+        self.subject_attribute(COMMON_NAME, value)
+    }
+
+    /// Add an organization to the subject.
+    pub fn organization(self, value: &str) -> Self {
+        self.subject_attribute(ORGANIZATION, value)
+    }
+
+    /// Add an organizational unit to the subject.
+    pub fn organizational_unit(self, value: &str) -> Self {
+        self.subject_attribute(ORGANIZATIONAL_UNIT, value)
+    }
+
+    /// Add a two-letter country code to the subject.
+    pub fn country(self, value: &str) -> Self {
+        self.subject_attribute(COUNTRY, value)
+    }
+
+    /// Add a subject alternative name.
+    pub fn subject_alt_name(mut self, name: SubjectAltName) -> Self {
+        self.subject_alt_names.push(name);
+        self
+    }
+
+    /// Add an attribute of type `oid` to the request, with DER encoded
+    /// `values`.
+    pub fn attribute(mut self, oid: ObjectIdentifier, values: Vec<Vec<u8>>) -> Self {
+        self.attributes.push((oid, values));
+        self
+    }
+
+    /// Add a challenge password attribute, as used by SCEP.
+    pub fn challenge_password(self, password: &str) -> Self {
+        let value = tlv(TAG_UTF8_STRING, password.as_bytes());
+        self.attribute(CHALLENGE_PASSWORD, vec![value])
+    }
+
+    /// Build the request for the public key of `signer`, and sign it.
+    ///
+    /// Returns the DER encoding of the request. Fails with
+    /// [`Error::InvalidArguments`] if a country or serial number is not a
+    /// printable string, an email address is not ASCII or an attribute has
+    /// no value, and with [`Error::Backend`] if signing fails.
+    pub fn build<S, Sig>(&self, signer: &S) -> Result<Vec<u8>>
+    where
+        S: Keypair + DynSignatureAlgorithmIdentifier + Signer<Sig>,
+        S::VerifyingKey: EncodePublicKey,
+        Sig: SignatureBitStringEncoding,
+    {
+        self.build_with(signer, |info| signer.try_sign(info))
+    }
+
+    /// Build and sign the request like [`CsrBuilder::build`], with a signer
+    /// which needs randomness, e.g. a [`BlindedSigningKey`].
+    ///
+    /// [`BlindedSigningKey`]: crate::pss::BlindedSigningKey
+    pub fn build_with_rng<R, S, Sig>(&self, rng: &mut R, signer: &S) -> Result<Vec<u8>>
+    where
+        R: CryptoRngCore,
+        S: Keypair + DynSignatureAlgorithmIdentifier + RandomizedSigner<Sig>,
+        S::VerifyingKey: EncodePublicKey,
+        Sig: SignatureBitStringEncoding,
+    {
+        self.build_with(signer, |info| signer.try_sign_with_rng(rng, info))
+    }
+
+    fn build_with<S, Sig>(
+        &self,
+        signer: &S,
+        sign: impl FnOnce(&[u8]) -> signature::Result<Sig>,
+    ) -> Result<Vec<u8>>
+    where
+        S: Keypair + DynSignatureAlgorithmIdentifier,
+        S::VerifyingKey: EncodePublicKey,
+        Sig: SignatureBitStringEncoding,
+    {
+        let public_key = signer.verifying_key().to_public_key_der()?;
+        let info = concat(&[
+            tlv(TAG_INTEGER, &[0]),
+            encode_name(&self.subject)?,
+            public_key.as_bytes().to_vec(),
+            self.encode_attributes()?,
+        ]);
+        signed(signer, tlv(TAG_SEQUENCE, &info), sign)
+    }
+
+    /// Encodes the `[0] IMPLICIT Attributes`, including the extension
+    /// request for the subject alternative names.
+    fn encode_attributes(&self) -> Result<Vec<u8>> {
+        let mut attributes = Vec::with_capacity(self.attributes.len() + 1);
+        for (oid, values) in &self.attributes {
+            if values.is_empty() {
+                return Err(Error::InvalidArguments);
+            }
+            attributes.push(attribute(oid, values.clone()));
+        }
+
+        if !self.subject_alt_names.is_empty() {
+            let names = self
+                .subject_alt_names
+                .iter()
+                .map(|name| match name {
+                    SubjectAltName::Email(email) => tlv(0x81, email.as_bytes()),
+                    SubjectAltName::Dns(dns) => tlv(0x82, dns.as_bytes()),
+                    SubjectAltName::Uri(uri) => tlv(0x86, uri.as_bytes()),
+                    SubjectAltName::Ip(IpAddr::V4(ip)) => tlv(0x87, &ip.octets()),
+                    SubjectAltName::Ip(IpAddr::V6(ip)) => tlv(0x87, &ip.octets()),
+                })
+                .collect::<Vec<_>>();
+            let extension = concat(&[
+                tlv(TAG_OID, SUBJECT_ALT_NAME.as_bytes()),
+                tlv(TAG_OCTET_STRING, &tlv(TAG_SEQUENCE, &concat(&names))),
+            ]);
+            let extensions = tlv(TAG_SEQUENCE, &tlv(TAG_SEQUENCE, &extension));
+            attributes.push(attribute(&EXTENSION_REQUEST, vec![extensions]));
+        }
+
+        Ok(set_of(TAG_ATTRIBUTES, attributes))
+    }
+}
+
+/// Returns the PEM encoding of a request, with the `CERTIFICATE REQUEST`
+/// label.
+#[cfg(feature = "pem")]
+pub fn to_pem(csr: &[u8], line_ending: pkcs8::LineEnding) -> Result<String> {
+    pkcs8::der::pem::encode_string("CERTIFICATE REQUEST", line_ending, csr)
+        .map_err(|err| Error::Der(err.into()))
+}
+
+/// Signs `tbs` with `sign`, and encodes it with the signature algorithm of
+/// `signer` and the signature, as both certificates and requests are.
+pub(crate) fn signed<S, Sig>(
+    signer: &S,
+    tbs: Vec<u8>,
+    sign: impl FnOnce(&[u8]) -> signature::Result<Sig>,
+) -> Result<Vec<u8>>
+where
+    S: DynSignatureAlgorithmIdentifier,
+    Sig: SignatureBitStringEncoding,
+{
+    let algorithm = signer.signature_algorithm_identifier()?.to_der()?;
+    let signature = sign(&tbs).map_err(|_| Error::Backend)?;
+    let signature = signature.to_bitstring()?.to_der()?;
+    Ok(tlv(TAG_SEQUENCE, &concat(&[tbs, algorithm, signature])))
+}
+
+/// Encodes a `Name` with one attribute per relative distinguished name.
+///
+/// Values are encoded as `PrintableString` for [`COUNTRY`] and
+/// [`SERIAL_NUMBER`], as `IA5String` for [`EMAIL_ADDRESS`], and as
+/// `UTF8String` otherwise. Fails with [`Error::InvalidArguments`] if they are
+/// not in the character set of their type.
+pub(crate) fn encode_name<V: AsRef<str>>(name: &[(ObjectIdentifier, V)]) -> Result<Vec<u8>> {
+    let mut rdns = Vec::with_capacity(name.len());
+    for (oid, value) in name {
+        let value = value.as_ref();
+        let tag = match *oid {
+            COUNTRY | SERIAL_NUMBER => {
+                if !value.bytes().all(is_printable) {
+                    return Err(Error::InvalidArguments);
+                }
+                TAG_PRINTABLE_STRING
+            }
+            EMAIL_ADDRESS => {
+                if !value.is_ascii() {
+                    return Err(Error::InvalidArguments);
+                }
+                TAG_IA5_STRING
+            }
+            _ => TAG_UTF8_STRING,
+        };
+        let atv = concat(&[tlv(TAG_OID, oid.as_bytes()), tlv(tag, value.as_bytes())]);
+        rdns.push(tlv(TAG_SET, &tlv(TAG_SEQUENCE, &atv)));
+    }
+    Ok(tlv(TAG_SEQUENCE, &concat(&rdns)))
+}
+
+/// Encodes an `Attribute` with the given values.
+fn attribute(oid: &ObjectIdentifier, values: Vec<Vec<u8>>) -> Vec<u8> {
+    let attribute = concat(&[tlv(TAG_OID, oid.as_bytes()), set_of(TAG_SET, values)]);
+    tlv(TAG_SEQUENCE, &attribute)
+}
+
+/// Encodes a `SET OF`, whose elements DER sorts by their encodings.
+fn set_of(tag: u8, mut elements: Vec<Vec<u8>>) -> Vec<u8> {
+    elements.sort();
+    tlv(tag, &concat(&elements))
+}
+
+/// Encodes `contents` with the given tag and a DER length.
+pub(crate) fn tlv(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let len = contents.len();
+    let mut out = Vec::with_capacity(len + 6);
+    out.push(tag);
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes = len.to_be_bytes();
+        let skip = bytes.iter().take_while(|&&b| b == 0).count();
+        out.push(0x80 | (bytes.len() - skip) as u8);
+        out.extend_from_slice(&bytes[skip..]);
+    }
+    out.extend_from_slice(contents);
+    out
+}
+
+pub(crate) fn concat(parts: &[Vec<u8>]) -> Vec<u8> {
+    parts.concat()
+}
+
+/// Returns whether `b` is in the character set of `PrintableString`.
+fn is_printable(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b" '()+,-./:=?".contains(&b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pkcs1v15, pss, RsaPrivateKey};
+    use hex_literal::hex;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::Sha256;
+    use signature::Verifier;
+
+    /// Splits the first TLV off `der`, returning its tag, its whole encoding,
+    /// its contents and the remaining bytes.
+    fn read_tlv(der: &[u8]) -> (u8, &[u8], &[u8], &[u8]) {
+        let (len, header) = match der[1] {
+            len if len < 0x80 => (len as usize, 2),
+            0x81 => (der[2] as usize, 3),
+            0x82 => ((usize::from(der[2]) << 8) | usize::from(der[3]), 4),
+            _ => unreachable!(),
+        };
+        let end = header + len;
+        (der[0], &der[..end], &der[header..end], &der[end..])
+    }
+
+    #[test]
+    fn test_tlv() {
+        assert_eq!(tlv(TAG_INTEGER, &[0]), [0x02, 0x01, 0x00]);
+        let long = tlv(TAG_OCTET_STRING, &[0xab; 0x1234]);
+        assert_eq!(long[..4], [0x04, 0x82, 0x12, 0x34]);
+        assert_eq!(long.len(), 0x1234 + 4);
+        assert_eq!(
+            set_of(TAG_SET, vec![vec![2, 1], vec![1, 2, 3]]),
+            [0x31, 0x05, 1, 2, 3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn test_build() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let signing_key = pkcs1v15::SigningKey::<Sha256>::new(key.clone());
+
+        let csr = CsrBuilder::new()
+            .common_name("device")
+            .country("FR")
+            .subject_alt_name(SubjectAltName::Dns("device.example".into()))
+            .subject_alt_name(SubjectAltName::Ip("192.0.2.1".parse().unwrap()))
+            .challenge_password("secret")
+            .build(&signing_key)
+            .unwrap();
+
+        let (tag, _, contents, rest) = read_tlv(&csr);
+        assert_eq!((tag, rest), (TAG_SEQUENCE, &[][..]));
+        let (_, info, info_contents, rest) = read_tlv(contents);
+        let (_, algorithm, _, rest) = read_tlv(rest);
+        let (tag, _, signature, rest) = read_tlv(rest);
+        assert_eq!((tag, rest), (TAG_BIT_STRING, &[][..]));
+
+        // sha256WithRSAEncryption
+        assert_eq!(algorithm, hex!("300d06092a864886f70d01010b0500"));
+        assert_eq!(signature[0], 0);
+        let signature = pkcs1v15::Signature::try_from(&signature[1..]).unwrap();
+        pkcs1v15::VerifyingKey::<Sha256>::new(key.to_public_key())
+            .verify(info, &signature)
+            .unwrap();
+
+        let (_, version, _, rest) = read_tlv(info_contents);
+        assert_eq!(version, [0x02, 0x01, 0x00]);
+        let (_, subject, _, rest) = read_tlv(rest);
+        assert_eq!(
+            subject,
+            hex!("301e" "310f300d0603550403" "0c06646576696365" "310b3009060355040613024652")
+        );
+        let (_, spki, _, rest) = read_tlv(rest);
+        assert_eq!(
+            spki,
+            key.to_public_key().to_public_key_der().unwrap().as_bytes()
+        );
+        let (tag, _, attributes, rest) = read_tlv(rest);
+        assert_eq!((tag, rest), (TAG_ATTRIBUTES, &[][..]));
+
+        // Attributes are sorted: challengePassword comes first.
+        let (_, password, _, rest) = read_tlv(attributes);
+        assert_eq!(
+            password,
+            hex!("3015" "06092a864886f70d010907" "3108" "0c06736563726574")
+        );
+        let (_, extension_request, _, rest) = read_tlv(rest);
+        assert!(rest.is_empty());
+        assert_eq!(
+            extension_request,
+            hex!(
+                "3030" "06092a864886f70d01090e"
+                "3123" "3021" "301f" "0603551d11"
+                "0418" "3016" "820e6465766963652e6578616d706c65" "8704c0000201"
+            )
+        );
+    }
+
+    #[test]
+    fn test_build_pss() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let csr = CsrBuilder::new()
+            .common_name("pss")
+            .build_with_rng(&mut rng, &pss::BlindedSigningKey::<Sha256>::new(key))
+            .unwrap();
+        assert_eq!(csr[0], TAG_SEQUENCE);
+        #[cfg(feature = "pem")]
+        assert!(to_pem(&csr, pkcs8::LineEnding::LF)
+            .unwrap()
+            .starts_with("-----BEGIN CERTIFICATE REQUEST-----\n"));
+
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        assert_eq!(
+            CsrBuilder::new()
+                .country("F*")
+                .build(&pkcs1v15::SigningKey::<Sha256>::new(key)),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
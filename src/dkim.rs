@@ -0,0 +1,203 @@
+//! RSA parts of DomainKeys Identified Mail ([RFC6376]) signatures.
+//!
+//! This module covers the cryptographic steps of the `rsa-sha256` signing
+//! algorithm; parsing messages and canonicalizing them are left to mail
+//! libraries:
+//!
+//! - [`body_hash`] computes the `bh=` tag from the canonicalized body.
+//! - [`sign`] computes the `b=` tag from the hash input of § 3.7: the
+//!   canonicalized signed header fields followed by the canonicalized
+//!   `DKIM-Signature` header field with an empty `b=` tag and no trailing
+//!   CRLF. [`verify`] checks it.
+//! - [`parse_dns_record`] extracts the public key from the TXT record of a
+//!   selector, and [`dns_record`] creates such a record.
+//!
+//! Canonicalization is the same for `simple` and `relaxed`: only the bytes
+//! given to these functions matter.
+//!
+//! ```
+//! use rsa::dkim;
+//!
+//! let mut rng = rand::thread_rng();
+//! let key = rsa::RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+//! let record = dkim::dns_record(&key.to_public_key()).expect("failed to encode the key");
+//!
+//! let header = b"from:alice@example.com\r\ndkim-signature:v=1; a=rsa-sha256; b=";
+//! let b = dkim::sign(&key, header).expect("failed to sign");
+//!
+//! let public_key = dkim::parse_dns_record(&record).expect("invalid record");
+//! assert!(dkim::verify(&public_key, header, &b).is_ok());
+//! ```
+//!
+//! [RFC6376]: https://datatracker.ietf.org/doc/html/rfc6376
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use base64ct::{Base64, Encoding};
+use pkcs1::DecodeRsaPublicKey;
+use pkcs8::{DecodePublicKey, EncodePublicKey};
+use sha2::{Digest, Sha256};
+
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::traits::PublicKeyParts;
+use crate::Pkcs1v15Sign;
+
+/// Minimum modulus size verifiers must accept, as required by RFC 8301.
+const MIN_KEY_BITS: u32 = 1024;
+
+/// Returns the base64 encoded SHA-256 digest of the canonicalized body, the
+/// value of the `bh=` tag.
+pub fn body_hash(canonicalized_body: &[u8]) -> String {
+    Base64::encode_string(&Sha256::digest(canonicalized_body))
+}
+
+/// Sign the canonicalized header hash input with `rsa-sha256`, returning
+/// the base64 encoded value of the `b=` tag.
+pub fn sign(key: &RsaPrivateKey, hash_input: &[u8]) -> Result<String> {
+    let signature = key.sign(Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(hash_input))?;
+    Ok(Base64::encode_string(&signature))
+}
+
+/// Verify the `b=` tag `signature` over the canonicalized header hash input
+/// with `rsa-sha256`.
+///
+/// Folding whitespace in `signature` is ignored. Keys smaller than 1024
+/// bits are rejected as required by RFC 8301.
+pub fn verify(key: &RsaPublicKey, hash_input: &[u8], signature: &str) -> Result<()> {
+    if key.n_bits_precision() < MIN_KEY_BITS {
+        return Err(Error::KeyTooSmall);
+    }
+    let signature = decode_base64(signature).map_err(|_| Error::Verification)?;
+    key.verify(
+        Pkcs1v15Sign::new::<Sha256>(),
+        &Sha256::digest(hash_input),
+        &signature,
+    )
+}
+
+/// Parse the TXT record of a DKIM selector, returning its RSA public key.
+///
+/// The key of the `p=` tag is accepted as a `SubjectPublicKeyInfo`, as
+/// specified, or as a bare PKCS#1 `RSAPublicKey`, as some providers publish.
+/// Fails with [`Error::InvalidArguments`] if the record is malformed, is
+/// not for RSA or SHA-256, or has been revoked by an empty `p=` tag.
+pub fn parse_dns_record(record: &str) -> Result<RsaPublicKey> {
+    let mut public_key = None;
+    for (i, tag) in record.split(';').enumerate() {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            continue;
+        }
+        let (name, value) = tag.split_once('=').ok_or(Error::InvalidArguments)?;
+        let value = value.trim();
+        match name.trim_end() {
+            // `v=`, if present, must be the first tag.
+            "v" if i != 0 || value != "DKIM1" => return Err(Error::InvalidArguments),
+            "k" if value != "rsa" => return Err(Error::InvalidArguments),
+            "h" if !value.split(':').any(|h| h.trim() == "sha256") => {
+                return Err(Error::InvalidArguments)
+            }
+            "p" => public_key = Some(value),
+            _ => {}
+        }
+    }
+
+    let der = match public_key {
+        Some(p) if !p.is_empty() => decode_base64(p)?,
+        _ => return Err(Error::InvalidArguments),
+    };
+    RsaPublicKey::from_public_key_der(&der)
+        .or_else(|_| RsaPublicKey::from_pkcs1_der(&der))
+        .map_err(|_| Error::InvalidArguments)
+}
+
+/// Create the TXT record publishing `key` for a DKIM selector.
+pub fn dns_record(key: &RsaPublicKey) -> Result<String> {
+    let der = key.to_public_key_der()?;
+    Ok(format!(
+        "v=DKIM1; k=rsa; p={}",
+        Base64::encode_string(der.as_bytes())
+    ))
+}
+
+/// Decodes base64 with folding whitespace.
+fn decode_base64(value: &str) -> Result<Vec<u8>> {
+    let value: String = value.split_ascii_whitespace().collect();
+    Base64::decode_vec(&value).map_err(|_| Error::InvalidArguments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_bigint::BoxedUint;
+    use pkcs1::EncodeRsaPublicKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_body_hash() {
+        // RFC 6376 § 3.4.3: the simple canonicalization of an empty body.
+        assert_eq!(
+            body_hash(b"\r\n"),
+            "frcCV1k9oG9oKj3dpUqdJg1PxRT2RSN/XKdLCPjaYaY="
+        );
+        // RFC 6376 § 3.4.4: the relaxed canonicalization of an empty body.
+        assert_eq!(
+            body_hash(b""),
+            "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU="
+        );
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let public_key = key.to_public_key();
+        let input = b"from:Joe SixPack <joe@football.example.com>\r\n\
+            dkim-signature:v=1; a=rsa-sha256; d=example.net; s=brisbane; b=";
+
+        let b = sign(&key, input).unwrap();
+        assert!(verify(&public_key, input, &b).is_ok());
+
+        let folded = format!("{}\r\n {}", &b[..40], &b[40..]);
+        assert!(verify(&public_key, input, &folded).is_ok());
+        assert_eq!(
+            verify(&public_key, b"from:eve@example.com", &b),
+            Err(Error::Verification)
+        );
+        assert_eq!(
+            verify(&public_key, input, "not base64!"),
+            Err(Error::Verification)
+        );
+
+        let n = BoxedUint::from_be_slice(&[0xff; 64], 512).unwrap();
+        let small = RsaPublicKey::new_unchecked(n, BoxedUint::from(65_537u32)).unwrap();
+        assert_eq!(verify(&small, input, &b), Err(Error::KeyTooSmall));
+    }
+
+    #[test]
+    fn test_dns_record() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let public_key = RsaPrivateKey::new(&mut rng, 1024).unwrap().to_public_key();
+
+        let record = dns_record(&public_key).unwrap();
+        assert!(record.starts_with("v=DKIM1; k=rsa; p=MIGfMA0GCSqGSIb3DQEBAQUAA4GNADCBiQKBgQ"));
+        assert_eq!(parse_dns_record(&record).unwrap(), public_key);
+
+        let pkcs1 = Base64::encode_string(public_key.to_pkcs1_der().unwrap().as_bytes());
+        let (head, tail) = pkcs1.split_at(64);
+        let record = format!("k=rsa ; h=sha1:sha256; t=y;\r\n\tp={head}\r\n\t{tail};");
+        assert_eq!(parse_dns_record(&record).unwrap(), public_key);
+
+        for record in [
+            "v=DKIM1; k=rsa; p=",
+            "k=rsa; v=DKIM1; p=MIGf",
+            "v=DKIM1; k=ed25519; p=11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=",
+            "v=DKIM1; h=sha1; p=MIGf",
+            "v=DKIM1; k=rsa",
+            "v=DKIM1; k=rsa; p=AAAA",
+        ] {
+            assert_eq!(parse_dns_record(record), Err(Error::InvalidArguments));
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Statistical timing leakage tests in the style of [dudect].
+//!
+//! Each test times an operation on inputs of two classes, a single fixed
+//! input and fresh random inputs, interleaved in random order. If the running
+//! time does not depend on secret data, both classes have the same timing
+//! distribution. Welch's t-test compares their means, on all measurements and
+//! on measurements cropped at several percentiles to discard outliers, and
+//! the largest `|t|` is reported. Values above [`LEAKAGE_THRESHOLD`] are
+//! evidence of leakage.
+//!
+//! The tests target the operations attacked by Bleichenbacher-style oracles
+//! such as the Marvin attack:
+//!
+//! - [`Target::Pkcs1v15Unpad`]: PKCS#1 v1.5 encryption unpadding of a valid
+//!   encoded message against random, almost always invalid, ones.
+//! - [`Target::OaepDecode`]: OAEP decoding with SHA-256, likewise.
+//! - [`Target::CrtPrivateOp`]: the unblinded CRT private key operation on a
+//!   fixed against random ciphertexts.
+//!
+//! Results depend on the target machine and the build profile, so the tests
+//! should be run in release builds on the hardware the crate is deployed to,
+//! with as many samples as time allows. Passing does not prove the absence of
+//! leaks, it only shows none was measured.
+//!
+//! ```no_run
+//! use rsa::dudect::{measure_all, LEAKAGE_THRESHOLD};
+//!
+//! let mut rng = rand::thread_rng();
+//! let key = rsa::RsaPrivateKey::new(&mut rng, 2048)?;
+//! for report in measure_all(&mut rng, &key, 1_000_000)? {
+//!     println!("{:?}: t = {:.2}", report.target, report.t);
+//!     assert!(!report.is_leaking());
+//! }
+//! # Ok::<(), rsa::Error>(())
+//! ```
+//!
+//! [dudect]: https://eprint.iacr.org/2016/1123
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::hint::black_box;
+use crypto_bigint::BoxedUint;
+use rand_core::CryptoRngCore;
+use sha2::Sha256;
+use std::time::Instant;
+
+use crate::algorithms::oaep::{oaep_decrypt_digest, oaep_encrypt_digest};
+use crate::algorithms::pkcs1v15::{pkcs1v15_encrypt_pad, pkcs1v15_encrypt_unpad_index};
+use crate::algorithms::rsa::{rsa_decrypt, rsa_encrypt};
+use crate::dummy_rng::DummyRng;
+use crate::errors::{Error, Result};
+use crate::key::RsaPrivateKey;
+use crate::traits::PublicKeyParts;
+
+/// Threshold on `|t|` above which a test is considered leaking.
+///
+/// dudect uses the same value: timing differences this significant are very
+/// unlikely to be due to chance.
+pub const LEAKAGE_THRESHOLD: f64 = 4.5;
+
+/// Percentiles at which measurements are cropped, in addition to testing
+/// all of them.
+const CROP_PERCENTILES: [f64; 3] = [0.5, 0.75, 0.9];
+
+/// Length of the messages of the fixed class.
+const MESSAGE_LEN: usize = 16;
+
+/// Operation timed by a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// PKCS#1 v1.5 encryption unpadding.
+    Pkcs1v15Unpad,
+    /// OAEP decoding with SHA-256.
+    OaepDecode,
+    /// Unblinded CRT private key operation.
+    CrtPrivateOp,
+}
+
+impl Target {
+    /// All targets.
+    pub const ALL: [Target; 3] = [
+        Target::Pkcs1v15Unpad,
+        Target::OaepDecode,
+        Target::CrtPrivateOp,
+    ];
+}
+
+/// Result of a test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeakageReport {
+    /// Operation which was timed.
+    pub target: Target,
+    /// Number of measurements of the fixed and the random class.
+    pub samples: [usize; 2],
+    /// Welch's t statistic with the largest magnitude.
+    pub t: f64,
+}
+
+impl LeakageReport {
+    /// Returns whether `|t|` exceeds [`LEAKAGE_THRESHOLD`].
+    pub fn is_leaking(&self) -> bool {
+        self.t.abs() > LEAKAGE_THRESHOLD
+    }
+}
+
+/// Times `target` with `samples` inputs using `key`.
+///
+/// Inputs are generated with `rng`. Fails with [`Error::InvalidArguments`] if
+/// `samples` is too small for both classes to be measured.
+pub fn measure<R: CryptoRngCore>(
+    rng: &mut R,
+    key: &RsaPrivateKey,
+    target: Target,
+    samples: usize,
+) -> Result<LeakageReport> {
+    let k = key.size();
+    let mut message = [0u8; MESSAGE_LEN];
+    rng.fill_bytes(&mut message);
+
+    let mut measurements = Vec::with_capacity(samples);
+    match target {
+        Target::Pkcs1v15Unpad => {
+            let fixed = pkcs1v15_encrypt_pad(rng, &message, k)?;
+            for _ in 0..samples {
+                let (class, em) = input(rng, &fixed);
+                let start = Instant::now();
+                let _ = black_box(pkcs1v15_encrypt_unpad_index(black_box(&em), k));
+                measurements.push((class, start.elapsed().as_nanos() as f64));
+            }
+        }
+        Target::OaepDecode => {
+            let fixed = oaep_encrypt_digest::<_, Sha256, Sha256>(rng, &message, None, k)?;
+            for _ in 0..samples {
+                let (class, mut em) = input(rng, &fixed);
+                let start = Instant::now();
+                let _ = black_box(oaep_decrypt_digest::<Sha256, Sha256>(
+                    black_box(&mut em),
+                    None,
+                    k,
+                ));
+                measurements.push((class, start.elapsed().as_nanos() as f64));
+            }
+        }
+        Target::CrtPrivateOp => {
+            let precision = key.n_bits_precision();
+            let m = BoxedUint::from_be_slice(&message, precision)?;
+            let fixed = rsa_encrypt(key, &m)?.to_be_bytes();
+            for _ in 0..samples {
+                let (class, mut bytes) = input(rng, &fixed);
+                // Random values must be smaller than the modulus.
+                bytes[0] = 0;
+                let c = BoxedUint::from_be_slice(&bytes, precision)?;
+                let start = Instant::now();
+                let _ = black_box(rsa_decrypt(None::<&mut DummyRng>, key, black_box(&c)));
+                measurements.push((class, start.elapsed().as_nanos() as f64));
+            }
+        }
+    }
+
+    let samples = [0, 1].map(|class| measurements.iter().filter(|(c, _)| *c == class).count());
+    if samples.contains(&0) {
+        return Err(Error::InvalidArguments);
+    }
+    Ok(LeakageReport {
+        target,
+        samples,
+        t: max_t(&measurements),
+    })
+}
+
+/// Times every [`Target`] with `samples` inputs each.
+pub fn measure_all<R: CryptoRngCore>(
+    rng: &mut R,
+    key: &RsaPrivateKey,
+    samples: usize,
+) -> Result<Vec<LeakageReport>> {
+    Target::ALL
+        .iter()
+        .map(|&target| measure(rng, key, target, samples))
+        .collect()
+}
+
+/// Draws the class of the next input, 0 for `fixed` and 1 for random bytes
+/// of the same length.
+fn input<R: CryptoRngCore>(rng: &mut R, fixed: &[u8]) -> (u8, Vec<u8>) {
+    let class = (rng.next_u32() & 1) as u8;
+    if class == 0 {
+        return (class, fixed.to_vec());
+    }
+    let mut bytes = vec![0u8; fixed.len()];
+    rng.fill_bytes(&mut bytes);
+    (class, bytes)
+}
+
+/// Returns the t statistic with the largest magnitude, on all measurements
+/// and on those below each of [`CROP_PERCENTILES`].
+fn max_t(measurements: &[(u8, f64)]) -> f64 {
+    let mut sorted = measurements.iter().map(|&(_, t)| t).collect::<Vec<_>>();
+    sorted.sort_by(f64::total_cmp);
+
+    let thresholds = CROP_PERCENTILES
+        .iter()
+        .map(|p| sorted[((sorted.len() - 1) as f64 * p) as usize])
+        .chain([f64::INFINITY]);
+    thresholds
+        .map(|threshold| welch_t(measurements.iter().filter(|&&(_, t)| t <= threshold)))
+        .fold(0.0, |max: f64, t| if t.abs() > max.abs() { t } else { max })
+}
+
+/// Welch's t statistic comparing the means of the two classes, or 0 if a
+/// class has less than two measurements.
+fn welch_t<'a>(measurements: impl Iterator<Item = &'a (u8, f64)>) -> f64 {
+    let mut stats = [Welford::default(); 2];
+    for &(class, t) in measurements {
+        stats[usize::from(class)].push(t);
+    }
+    if stats.iter().any(|s| s.n < 2) {
+        return 0.0;
+    }
+    let [a, b] = stats;
+    let se = (a.variance() / a.n as f64 + b.variance() / b.n as f64).sqrt();
+    if se == 0.0 {
+        return 0.0;
+    }
+    (a.mean - b.mean) / se
+}
+
+/// Online mean and variance.
+#[derive(Clone, Copy, Default)]
+struct Welford {
+    n: usize,
+    mean: f64,
+    m2: f64,
+}
+
+impl Welford {
+    fn push(&mut self, x: f64) {
+        self.n += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.n as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_welch_t() {
+        let same = (0..1000)
+            .map(|i| ((i % 2) as u8, (i % 7) as f64))
+            .collect::<Vec<_>>();
+        assert!(max_t(&same).abs() < LEAKAGE_THRESHOLD);
+
+        let leaking = (0..1000)
+            .map(|i| ((i % 2) as u8, (i % 7) as f64 + (i % 2) as f64 * 3.0))
+            .collect::<Vec<_>>();
+        assert!(max_t(&leaking) < -LEAKAGE_THRESHOLD);
+    }
+
+    #[test]
+    fn test_measure_all() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let reports = measure_all(&mut rng, &key, 200).unwrap();
+        assert_eq!(reports.len(), Target::ALL.len());
+        for report in reports {
+            assert_eq!(report.samples.iter().sum::<usize>(), 200);
+            assert!(report.t.is_finite());
+        }
+
+        assert_eq!(
+            measure(&mut rng, &key, Target::CrtPrivateOp, 1),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Byte strings produced by RSA operations, typed by their use.
+//!
+//! Both ciphertexts and signatures are byte strings of the size of the
+//! modulus. [`Ciphertext`] and [`EncodedSignature`] keep them apart, and
+//! check their length against the key when constructed, so that one can not
+//! be passed where the other is expected.
+//!
+//! Values which were deserialized have not been checked against a key yet,
+//! which is done with [`Ciphertext::check`] and [`EncodedSignature::check`].
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter, LowerHex, UpperHex};
+
+#[cfg(feature = "serde")]
+use serdect::serde::{de, Deserialize, Serialize};
+
+use crate::errors::{Error, Result};
+use crate::traits::PublicKeyParts;
+
+macro_rules! encoded_bytes {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $name {
+            bytes: Box<[u8]>,
+        }
+
+        impl $name {
+            /// Wrap `bytes`, which must have the size of the modulus of `key`.
+            pub fn new(key: &impl PublicKeyParts, bytes: impl Into<Box<[u8]>>) -> Result<Self> {
+                let value = Self {
+                    bytes: bytes.into(),
+                };
+                value.check(key)?;
+                Ok(value)
+            }
+
+            /// Check that the length is the size of the modulus of `key`.
+            pub fn check(&self, key: &impl PublicKeyParts) -> Result<()> {
+                if self.bytes.len() != key.size() {
+                    return Err(Error::InvalidArguments);
+                }
+                Ok(())
+            }
+
+            /// Returns the bytes.
+            pub fn as_bytes(&self) -> &[u8] {
+                &self.bytes
+            }
+
+            /// Returns the length in bytes.
+            pub fn len(&self) -> usize {
+                self.bytes.len()
+            }
+
+            /// Returns whether there are no bytes, which is never the case
+            /// of values checked against a key.
+            pub fn is_empty(&self) -> bool {
+                self.bytes.is_empty()
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.bytes
+            }
+        }
+
+        impl From<$name> for Box<[u8]> {
+            fn from(value: $name) -> Box<[u8]> {
+                value.bytes
+            }
+        }
+
+        impl From<$name> for Vec<u8> {
+            fn from(value: $name) -> Vec<u8> {
+                value.bytes.into_vec()
+            }
+        }
+
+        impl LowerHex for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                for byte in self.bytes.iter() {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl UpperHex for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                for byte in self.bytes.iter() {
+                    write!(f, "{:02X}", byte)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+                write!(f, "{:X}", self)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+            where
+                S: serdect::serde::Serializer,
+            {
+                serdect::slice::serialize_hex_lower_or_bin(&self.bytes, serializer)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+            where
+                D: serdect::serde::Deserializer<'de>,
+            {
+                let bytes = serdect::slice::deserialize_hex_or_bin_vec(deserializer)?;
+                if bytes.is_empty() {
+                    return Err(de::Error::invalid_length(0, &"a non-empty byte string"));
+                }
+                Ok(Self {
+                    bytes: bytes.into_boxed_slice(),
+                })
+            }
+        }
+    };
+}
+
+encoded_bytes! {
+    /// Ciphertext of an RSA encryption, e.g. with OAEP or PKCS#1 v1.5
+    /// padding.
+    Ciphertext
+}
+
+encoded_bytes! {
+    /// `RSASSA-PKCS1-v1_5` signature encoded as a byte string of the size of
+    /// the modulus, as described in [RFC8017 § 8.2.1].
+    ///
+    /// [RFC8017 § 8.2.1]: https://datatracker.ietf.org/doc/html/rfc8017#section-8.2.1
+    EncodedSignature
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pkcs1v15Encrypt, RsaPrivateKey};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_ciphertext() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let bytes = key
+            .to_public_key()
+            .encrypt(&mut rng, Pkcs1v15Encrypt, b"typed")
+            .unwrap();
+
+        let ciphertext = Ciphertext::new(&key, bytes.clone()).unwrap();
+        assert_eq!(ciphertext.len(), 64);
+        assert_eq!(
+            key.decrypt(Pkcs1v15Encrypt, ciphertext.as_bytes()).unwrap(),
+            b"typed"
+        );
+        assert_eq!(format!("{:x}", ciphertext).len(), 128);
+        assert_eq!(
+            format!("{}", ciphertext),
+            format!("{:x}", ciphertext).to_uppercase()
+        );
+        assert_eq!(Vec::from(ciphertext), bytes);
+
+        assert_eq!(
+            EncodedSignature::new(&key, &bytes[1..]),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        use serde_test::{assert_de_tokens_error, assert_tokens, Configure, Readable, Token};
+        let signature = EncodedSignature {
+            bytes: Box::new([0, 42]),
+        };
+
+        let tokens = [Token::Str("002a")];
+        assert_tokens(&signature.readable(), &tokens);
+        assert_de_tokens_error::<Readable<EncodedSignature>>(
+            &[Token::Str("")],
+            "invalid length 0, expected a non-empty byte string",
+        );
+    }
+}
@@ -4,6 +4,8 @@
 //! `pkcs1` crate's traits for types which impl the `pkcs8` crate's traits.
 
 use crate::{
+    algorithms::arith::resize,
+    key::check_size_cap,
     traits::{PrivateKeyParts, PublicKeyParts},
     RsaPrivateKey, RsaPublicKey,
 };
@@ -18,6 +20,9 @@ use zeroize::Zeroizing;
 /// ObjectID for the RSA PSS keys
 pub const ID_RSASSA_PSS: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.10");
 
+/// ObjectID for the RSA OAEP keys
+pub const ID_RSAES_OAEP: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.7");
+
 /// Verify that the `AlgorithmIdentifier` for a key is correct.
 pub(crate) fn verify_algorithm_id(
     algorithm: &pkcs8::AlgorithmIdentifierRef,
@@ -39,7 +44,40 @@ pub(crate) fn verify_algorithm_id(
     Ok(())
 }
 
+/// Returns a new hasher for the digest identified by `oid`, as named in the
+/// parameters of RSASSA-PSS and RSAES-OAEP algorithm identifiers.
+#[cfg(feature = "sha2")]
+pub(crate) fn new_dyn_digest(
+    oid: ObjectIdentifier,
+) -> pkcs8::spki::Result<alloc::boxed::Box<dyn digest::DynDigest + Send + Sync>> {
+    use alloc::boxed::Box;
+    use const_oid::AssociatedOid;
+    use sha2::{Digest, Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256};
+
+    #[cfg(feature = "sha1")]
+    if oid == sha1::Sha1::OID {
+        return Ok(Box::new(sha1::Sha1::new()));
+    }
+    Ok(if oid == Sha224::OID {
+        Box::new(Sha224::new())
+    } else if oid == Sha256::OID {
+        Box::new(Sha256::new())
+    } else if oid == Sha384::OID {
+        Box::new(Sha384::new())
+    } else if oid == Sha512::OID {
+        Box::new(Sha512::new())
+    } else if oid == Sha512_224::OID {
+        Box::new(Sha512_224::new())
+    } else if oid == Sha512_256::OID {
+        Box::new(Sha512_256::new())
+    } else {
+        return Err(pkcs8::spki::Error::OidUnknown { oid });
+    })
+}
+
 fn uint_from_slice(data: &[u8], bits: u32) -> pkcs8::Result<BoxedUint> {
+    // Reject oversized keys before allocating their components.
+    check_size_cap(bits as usize).map_err(|_| pkcs8::Error::KeyMalformed)?;
     BoxedUint::from_be_slice(data, bits).map_err(|_| pkcs8::Error::KeyMalformed)
 }
 
@@ -103,26 +141,27 @@ impl TryFrom<pkcs8::SubjectPublicKeyInfoRef<'_>> for RsaPublicKey {
 impl EncodePrivateKey for RsaPrivateKey {
     fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
         // Check if the key is multi prime
-        if self.primes.len() > 2 {
+        let [p, q] = &self.primes[..] else {
             return Err(pkcs1::Error::Version.into());
-        }
+        };
 
         let modulus = self.n().to_be_bytes();
         let public_exponent = self.e().to_be_bytes();
         let private_exponent = Zeroizing::new(self.d().to_be_bytes());
-        let prime1 = Zeroizing::new(self.primes[0].to_be_bytes());
-        let prime2 = Zeroizing::new(self.primes[1].to_be_bytes());
+        let prime1 = Zeroizing::new(p.to_be_bytes());
+        let prime2 = Zeroizing::new(q.to_be_bytes());
 
         let bits = self.d().bits_precision();
-
-        let exponent1 = Zeroizing::new(
-            (self.d() % NonZero::new(&self.primes[0].widen(bits) - &BoxedUint::one()).unwrap())
-                .to_be_bytes(),
-        );
-        let exponent2 = Zeroizing::new(
-            (self.d() % NonZero::new(&self.primes[1].widen(bits) - &BoxedUint::one()).unwrap())
-                .to_be_bytes(),
-        );
+        let crt_exponent = |prime: &BoxedUint| {
+            let prime_minus_one = resize(prime, bits).wrapping_sub(&BoxedUint::one());
+            NonZero::new(prime_minus_one)
+                .into_option()
+                .map(|m| Zeroizing::new((self.d() % m).to_be_bytes()))
+                .ok_or(pkcs1::Error::Crypto)
+        };
+
+        let exponent1 = crt_exponent(p)?;
+        let exponent2 = crt_exponent(q)?;
         let coefficient = Zeroizing::new(
             self.crt_coefficient()
                 .ok_or(pkcs1::Error::Crypto)?
@@ -0,0 +1,275 @@
+//! Hybrid "sealed box" envelope encryption.
+//!
+//! RSA can only encrypt short messages. To encrypt data of arbitrary length,
+//! [`seal`] generates a fresh symmetric key, encrypts the data with an AEAD
+//! (AES-256-GCM by default) and wraps the key with RSA-OAEP. The result is a
+//! single blob which [`open`] turns back into the plaintext:
+//!
+//! ```text
+//! version (1) || wrapped key length (4, BE) || wrapped key || nonce || AEAD ciphertext
+//! ```
+//!
+//! Everything preceding the nonce is authenticated as associated data.
+//!
+//! Any AEAD implementing the [`aead`] traits can be used instead of AES-GCM
+//! through [`seal_with`] and [`open_with`]; both sides must agree on the
+//! AEAD and the OAEP digest.
+//!
+//! # Example
+#![cfg_attr(feature = "sha2", doc = "```")]
+#![cfg_attr(not(feature = "sha2"), doc = "```ignore")]
+//! use rsa::{envelope, RsaPrivateKey};
+//!
+//! let mut rng = rand::thread_rng();
+//! let private_key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+//! let public_key = private_key.to_public_key();
+//!
+//! let data = b"a message longer than any RSA modulus would allow...";
+//! let sealed = envelope::seal(&mut rng, &public_key, data).expect("failed to seal");
+//! let opened = envelope::open(&mut rng, &private_key, &sealed).expect("failed to open");
+//! assert_eq!(&opened[..], &data[..]);
+//! ```
+
+use aead::{Aead, KeyInit, Nonce, Payload};
+use alloc::vec::Vec;
+use digest::{Digest, DynDigest};
+use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
+
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::oaep::Oaep;
+
+#[cfg(feature = "sha2")]
+use {
+    aes_gcm::Aes256Gcm,
+    pkcs8::{DecodePrivateKey, EncodePrivateKey},
+    sha2::Sha256,
+};
+
+/// Version byte of the sealed box format.
+const VERSION: u8 = 1;
+
+/// Length of the header preceding the wrapped key.
+const HEADER_LEN: usize = 5;
+
+/// Seal `plaintext` for the owner of `public_key`, using AES-256-GCM and
+/// RSA-OAEP with SHA-256.
+#[cfg(feature = "sha2")]
+pub fn seal<R: CryptoRngCore>(
+    rng: &mut R,
+    public_key: &RsaPublicKey,
+    plaintext: &[u8],
+) -> Result<Vec<u8>> {
+    seal_with::<Aes256Gcm, Sha256, R>(rng, public_key, plaintext)
+}
+
+/// Open a box sealed with [`seal`].
+#[cfg(feature = "sha2")]
+pub fn open<R: CryptoRngCore>(
+    rng: &mut R,
+    private_key: &RsaPrivateKey,
+    sealed: &[u8],
+) -> Result<Vec<u8>> {
+    open_with::<Aes256Gcm, Sha256, R>(rng, private_key, sealed)
+}
+
+/// Seal `plaintext` for the owner of `public_key`, using the AEAD `A` and
+/// RSA-OAEP with the digest `D`.
+pub fn seal_with<A, D, R>(
+    rng: &mut R,
+    public_key: &RsaPublicKey,
+    plaintext: &[u8],
+) -> Result<Vec<u8>>
+where
+    A: Aead + KeyInit,
+    D: 'static + Digest + DynDigest + Send + Sync,
+    R: CryptoRngCore,
+{
+    let mut key = Zeroizing::new(vec![0u8; A::key_size()]);
+    rng.fill_bytes(&mut key);
+    let cipher = A::new_from_slice(&key).map_err(|_| Error::Aead)?;
+    let wrapped = public_key.encrypt(rng, Oaep::new::<D>(), &key)?;
+
+    let mut nonce = Nonce::<A>::default();
+    rng.fill_bytes(&mut nonce);
+
+    let wrapped_len = u32::try_from(wrapped.len()).map_err(|_| Error::ModulusTooLarge)?;
+    let mut out = Vec::with_capacity(HEADER_LEN + wrapped.len() + nonce.len() + plaintext.len());
+    out.push(VERSION);
+    out.extend_from_slice(&wrapped_len.to_be_bytes());
+    out.extend_from_slice(&wrapped);
+
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &out,
+            },
+        )
+        .map_err(|_| Error::Aead)?;
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Open a box sealed with [`seal_with`] using the same AEAD `A` and digest
+/// `D`.
+///
+/// `rng` is used to blind the RSA decryption.
+pub fn open_with<A, D, R>(
+    rng: &mut R,
+    private_key: &RsaPrivateKey,
+    sealed: &[u8],
+) -> Result<Vec<u8>>
+where
+    A: Aead + KeyInit,
+    D: 'static + Digest + DynDigest + Send + Sync,
+    R: CryptoRngCore,
+{
+    if sealed.len() < HEADER_LEN || sealed[0] != VERSION {
+        return Err(Error::Decryption);
+    }
+    let mut len = [0u8; 4];
+    len.copy_from_slice(&sealed[1..HEADER_LEN]);
+    let wrapped_len = usize::try_from(u32::from_be_bytes(len)).map_err(|_| Error::Decryption)?;
+
+    let nonce_len = Nonce::<A>::default().len();
+    let aad_len = HEADER_LEN
+        .checked_add(wrapped_len)
+        .ok_or(Error::Decryption)?;
+    if sealed.len() < aad_len + nonce_len {
+        return Err(Error::Decryption);
+    }
+    let (aad, rest) = sealed.split_at(aad_len);
+    let (nonce, ciphertext) = rest.split_at(nonce_len);
+
+    let key =
+        Zeroizing::new(private_key.decrypt_blinded(rng, Oaep::new::<D>(), &aad[HEADER_LEN..])?);
+    let cipher = A::new_from_slice(&key).map_err(|_| Error::Decryption)?;
+    let nonce = Nonce::<A>::try_from(nonce).map_err(|_| Error::Decryption)?;
+
+    cipher
+        .decrypt(
+            &nonce,
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| Error::Decryption)
+}
+
+#[cfg(feature = "sha2")]
+impl RsaPrivateKey {
+    /// Wrap this key for escrow or transfer to the owner of `wrapping_key`.
+    ///
+    /// The key is serialized as PKCS#8 and protected with [`seal`].
+    pub fn wrap_under<R: CryptoRngCore>(
+        &self,
+        wrapping_key: &RsaPublicKey,
+        rng: &mut R,
+    ) -> Result<Vec<u8>> {
+        let der = self.to_pkcs8_der()?;
+        seal(rng, wrapping_key, der.as_bytes())
+    }
+
+    /// Unwrap a key wrapped with [`RsaPrivateKey::wrap_under`], using the
+    /// private key matching the wrapping key.
+    pub fn unwrap_from<R: CryptoRngCore>(
+        wrapped: &[u8],
+        unwrapping_key: &RsaPrivateKey,
+        rng: &mut R,
+    ) -> Result<RsaPrivateKey> {
+        let der = Zeroizing::new(open(rng, unwrapping_key, wrapped)?);
+        Ok(RsaPrivateKey::from_pkcs8_der(&der)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes_gcm::Aes128Gcm;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::Sha256;
+
+    fn key(rng: &mut ChaCha8Rng) -> RsaPrivateKey {
+        RsaPrivateKey::new(rng, 1024).unwrap()
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let private_key = key(&mut rng);
+        let public_key = private_key.to_public_key();
+
+        for len in [0, 1, 100, 10_000] {
+            let plaintext = vec![0x5a; len];
+            let sealed =
+                seal_with::<Aes128Gcm, Sha256, _>(&mut rng, &public_key, &plaintext).unwrap();
+            let opened =
+                open_with::<Aes128Gcm, Sha256, _>(&mut rng, &private_key, &sealed).unwrap();
+            assert_eq!(opened, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_tampering() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let private_key = key(&mut rng);
+        let public_key = private_key.to_public_key();
+        let sealed =
+            seal_with::<Aes128Gcm, Sha256, _>(&mut rng, &public_key, b"secret message").unwrap();
+
+        for i in [0, 3, 10, sealed.len() - 20, sealed.len() - 1] {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            assert_eq!(
+                open_with::<Aes128Gcm, Sha256, _>(&mut rng, &private_key, &tampered),
+                Err(Error::Decryption)
+            );
+        }
+
+        assert_eq!(
+            open_with::<Aes128Gcm, Sha256, _>(&mut rng, &private_key, &sealed[..10]),
+            Err(Error::Decryption)
+        );
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_wrap_under() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let escrow_key = key(&mut rng);
+        let private_key = key(&mut rng);
+
+        let wrapped = private_key
+            .wrap_under(&escrow_key.to_public_key(), &mut rng)
+            .unwrap();
+        let unwrapped = RsaPrivateKey::unwrap_from(&wrapped, &escrow_key, &mut rng).unwrap();
+        assert_eq!(unwrapped, private_key);
+
+        assert_eq!(
+            RsaPrivateKey::unwrap_from(&wrapped, &private_key, &mut rng),
+            Err(Error::Decryption)
+        );
+    }
+
+    #[test]
+    fn test_wrong_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let private_key = key(&mut rng);
+        let other_key = key(&mut rng);
+        let sealed = seal_with::<Aes128Gcm, Sha256, _>(
+            &mut rng,
+            &private_key.to_public_key(),
+            b"secret message",
+        )
+        .unwrap();
+        assert_eq!(
+            open_with::<Aes128Gcm, Sha256, _>(&mut rng, &other_key, &sealed),
+            Err(Error::Decryption)
+        );
+    }
+}
@@ -17,7 +17,10 @@ pub enum Error {
     Verification,
 
     /// Message too long.
-    MessageTooLong,
+    MessageTooLong {
+        /// Maximum length of the message in bytes.
+        max_len: usize,
+    },
 
     /// Input must be hashed.
     InputNotHashed,
@@ -55,6 +58,12 @@ pub enum Error {
     /// PKCS#8 error.
     Pkcs8(pkcs8::Error),
 
+    /// SubjectPublicKeyInfo error.
+    Spki(spki::Error),
+
+    /// DER encoding error.
+    Der(pkcs8::der::Error),
+
     /// Internal error.
     Internal,
 
@@ -68,18 +77,105 @@ pub enum Error {
     InvalidArguments,
 
     /// Decoding error.
+    #[cfg(feature = "alloc")]
     Decode(crypto_bigint::DecodeError),
+
+    /// Key generation was cancelled by the progress callback.
+    KeyGenerationCancelled,
+
+    /// Modulus smaller than required by the key policy.
+    ModulusTooSmall,
+
+    /// Public exponent smaller than required by the key policy.
+    WeakPublicExponent,
+
+    /// Key generation gave up without finding a valid key.
+    KeyGenerationFailed {
+        /// Number of candidate keys which were tried, or of prime
+        /// candidates rejected by the prime constraints or, for strong
+        /// primes, by the search for the auxiliary prime.
+        attempts: usize,
+    },
+
+    /// Key has the structure of keys affected by ROCA (CVE-2017-15361).
+    RocaVulnerable,
+
+    /// Primes of the key are close enough for the modulus to be factored.
+    ClosePrimes,
+
+    /// Key is on a blocklist of weak or compromised keys.
+    BlockedKey,
+
+    /// Pairwise consistency test of a key pair failed.
+    PairwiseConsistencyFailed,
+
+    /// Private key operation of an external key, e.g. on a token, failed.
+    Backend,
+
+    /// Random number generator failed.
+    Rng,
+
+    /// Result of a private key operation does not match the public key, e.g.
+    /// because of a fault in the CRT computation or in an external key.
+    FaultDetected,
+
+    /// Modulus too small for the parameters of the padding scheme, e.g. the
+    /// digest and salt lengths of PSS.
+    KeyTooSmall,
+
+    /// Private components of a sealed key failed to decrypt.
+    SealedKeyCorrupted,
+
+    /// Key share is malformed, e.g. its commitment group is not a safe
+    /// prime.
+    InvalidKeyShare,
+
+    /// Authenticated encryption of a payload failed, e.g. because it is too
+    /// long for the AEAD.
+    Aead,
+
+    /// Signature error, e.g. for a signature of the wrong length returned by
+    /// a remote key.
+    Signature(SignatureError),
+}
+
+/// [`signature::Error`] wrapped by [`Error::Signature`].
+///
+/// Signature errors are opaque, so any two of them compare equal.
+#[derive(Debug)]
+pub struct SignatureError(pub signature::Error);
+
+impl PartialEq for SignatureError {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for SignatureError {}
+
+impl core::error::Error for Error {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Error::Pkcs1(err) => Some(err),
+            Error::Pkcs8(err) => Some(err),
+            Error::Spki(err) => Some(err),
+            Error::Der(err) => Some(err),
+            #[cfg(feature = "std")]
+            Error::Signature(err) => Some(&err.0),
+            _ => None,
+        }
+    }
 }
 
-#[cfg(feature = "std")]
-impl std::error::Error for Error {}
 impl core::fmt::Display for Error {
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
             Error::InvalidPaddingScheme => write!(f, "invalid padding scheme"),
             Error::Decryption => write!(f, "decryption error"),
             Error::Verification => write!(f, "verification error"),
-            Error::MessageTooLong => write!(f, "message too long"),
+            Error::MessageTooLong { max_len } => {
+                write!(f, "message too long, at most {} bytes", max_len)
+            }
             Error::InputNotHashed => write!(f, "input must be hashed"),
             Error::NprimesTooSmall => write!(f, "nprimes must be >= 2"),
             Error::TooFewPrimes => {
@@ -94,15 +190,51 @@ impl core::fmt::Display for Error {
             Error::PublicExponentTooLarge => write!(f, "public exponent too large"),
             Error::Pkcs1(err) => write!(f, "{}", err),
             Error::Pkcs8(err) => write!(f, "{}", err),
+            Error::Spki(err) => write!(f, "{}", err),
+            Error::Der(err) => write!(f, "{}", err),
             Error::Internal => write!(f, "internal error"),
             Error::LabelTooLong => write!(f, "label too long"),
             Error::InvalidPadLen => write!(f, "invalid padding length"),
             Error::InvalidArguments => write!(f, "invalid arguments"),
+            #[cfg(feature = "alloc")]
             Error::Decode(err) => write!(f, "{:?}", err),
+            Error::KeyGenerationCancelled => write!(f, "key generation cancelled"),
+            Error::ModulusTooSmall => write!(f, "modulus too small"),
+            Error::WeakPublicExponent => write!(f, "public exponent too small for the key policy"),
+            Error::KeyGenerationFailed { attempts } => {
+                write!(f, "key generation failed after {} attempts", attempts)
+            }
+            Error::RocaVulnerable => write!(f, "key is vulnerable to ROCA"),
+            Error::ClosePrimes => write!(f, "primes of the key are too close"),
+            Error::BlockedKey => write!(f, "key is blocklisted"),
+            Error::PairwiseConsistencyFailed => write!(f, "pairwise consistency test failed"),
+            Error::Backend => write!(f, "private key operation of the backend failed"),
+            Error::Rng => write!(f, "random number generator failed"),
+            Error::FaultDetected => write!(f, "private key operation failed its consistency check"),
+            Error::KeyTooSmall => write!(f, "modulus too small for the padding parameters"),
+            Error::SealedKeyCorrupted => write!(f, "sealed private key is corrupted"),
+            Error::InvalidKeyShare => write!(f, "invalid key share"),
+            Error::Aead => write!(f, "authenticated encryption failed"),
+            Error::Signature(err) => write!(f, "{}", err.0),
         }
     }
 }
 
+/// Collapses every failure of a padded decryption into [`Error::Decryption`].
+///
+/// Distinct errors, e.g. for ciphertexts of the wrong size or faults of the
+/// private key operation, make it easier to turn a service into a padding
+/// oracle. The `debug-oracle` feature keeps them for diagnosing test
+/// environments, and must not be enabled in production.
+#[inline]
+pub(crate) fn opaque_decryption<T>(result: Result<T>) -> Result<T> {
+    if cfg!(feature = "debug-oracle") {
+        result
+    } else {
+        result.map_err(|_| Error::Decryption)
+    }
+}
+
 impl From<pkcs1::Error> for Error {
     fn from(err: pkcs1::Error) -> Error {
         Error::Pkcs1(err)
@@ -114,6 +246,25 @@ impl From<pkcs8::Error> for Error {
         Error::Pkcs8(err)
     }
 }
+
+impl From<spki::Error> for Error {
+    fn from(err: spki::Error) -> Error {
+        Error::Spki(err)
+    }
+}
+
+impl From<pkcs8::der::Error> for Error {
+    fn from(err: pkcs8::der::Error) -> Error {
+        Error::Der(err)
+    }
+}
+impl From<signature::Error> for Error {
+    fn from(err: signature::Error) -> Error {
+        Error::Signature(SignatureError(err))
+    }
+}
+
+#[cfg(feature = "alloc")]
 impl From<crypto_bigint::DecodeError> for Error {
     fn from(err: crypto_bigint::DecodeError) -> Error {
         Error::Decode(err)
@@ -133,3 +284,24 @@ impl From<Error> for signature::Error {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::error::Error as _;
+
+    #[test]
+    fn test_source() {
+        let err = Error::from(pkcs8::der::Error::from(pkcs8::der::ErrorKind::Failed));
+        assert_eq!(err.to_string(), err.source().unwrap().to_string());
+        assert!(Error::Decryption.source().is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_signature_source() {
+        let err = Error::from(signature::Error::new());
+        assert_eq!(err, Error::from(signature::Error::new()));
+        assert_eq!(err.to_string(), err.source().unwrap().to_string());
+    }
+}
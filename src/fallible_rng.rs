@@ -0,0 +1,126 @@
+use rand_core::{CryptoRng, RngCore};
+
+use crate::errors::{Error, Result};
+
+/// Wrapper drawing from a fallible RNG with `try_fill_bytes`, recording
+/// failures instead of panicking.
+///
+/// Once the RNG failed, the output is replaced with a SplitMix64 stream so
+/// that loops waiting for suitable random values still terminate. Anything
+/// computed from it is discarded by [`FallibleRng::run`].
+pub(crate) struct FallibleRng<'a, R: ?Sized> {
+    rng: &'a mut R,
+    failed: bool,
+    state: u64,
+}
+
+impl<'a, R: RngCore + ?Sized> FallibleRng<'a, R> {
+    /// Runs `f` with a wrapper around `rng`, returning [`Error::Rng`] if the
+    /// RNG failed at any point.
+    pub(crate) fn run<T>(rng: &'a mut R, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let mut wrapper = Self {
+            rng,
+            failed: false,
+            state: 0,
+        };
+        let result = f(&mut wrapper);
+        if wrapper.failed {
+            return Err(Error::Rng);
+        }
+        result
+    }
+
+    fn fallback(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl<R: RngCore + ?Sized> RngCore for FallibleRng<'_, R> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if !self.failed && self.rng.try_fill_bytes(dest).is_ok() {
+            return;
+        }
+        self.failed = true;
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.fallback().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<R: CryptoRng + ?Sized> CryptoRng for FallibleRng<'_, R> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::num::NonZeroU32;
+
+    /// RNG yielding `good` bytes of zeros before failing.
+    struct FailingRng {
+        good: usize,
+    }
+
+    impl RngCore for FailingRng {
+        fn next_u32(&mut self) -> u32 {
+            rand_core::impls::next_u32_via_fill(self)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            rand_core::impls::next_u64_via_fill(self)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.try_fill_bytes(dest).expect("RNG failed");
+        }
+
+        fn try_fill_bytes(
+            &mut self,
+            dest: &mut [u8],
+        ) -> core::result::Result<(), rand_core::Error> {
+            if dest.len() > self.good {
+                let code = NonZeroU32::new(rand_core::Error::CUSTOM_START).expect("non zero");
+                return Err(code.into());
+            }
+            self.good -= dest.len();
+            dest.fill(0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fallible_rng() {
+        let mut rng = FailingRng { good: 16 };
+        let result = FallibleRng::run(&mut rng, |rng| {
+            let mut buf = [0u8; 16];
+            rng.fill_bytes(&mut buf);
+            Ok(buf)
+        });
+        assert_eq!(result, Ok([0; 16]));
+
+        let result = FallibleRng::run(&mut rng, |rng| {
+            let mut buf = [0u8; 16];
+            rng.fill_bytes(&mut buf);
+            // The fallback stream keeps rejection sampling going.
+            assert_ne!(buf, [0; 16]);
+            Ok(())
+        });
+        assert_eq!(result, Err(Error::Rng));
+    }
+}
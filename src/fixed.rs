@@ -0,0 +1,544 @@
+//! Fixed-size RSA keys backed by stack-allocated integers.
+//!
+//! [`RsaPublicKeyFixed`] and [`RsaPrivateKeyFixed`] hold their components in
+//! [`Uint`]s of `LIMBS` limbs instead of `BoxedUint`s, so that the size of
+//! the key is known at compile time and their operations perform no heap
+//! allocations. Aliases are provided for the common sizes, such as
+//! [`RsaPublicKey2048`] and [`RsaPrivateKey4096`].
+//!
+//! Fixed-size keys are converted from `RsaPublicKey` and `RsaPrivateKey`,
+//! which are still used to generate, encode and decode keys. Private key
+//! operations do not use the CRT, and are therefore slower than those of
+//! `RsaPrivateKey`.
+//!
+//! # Without `alloc`
+//!
+//! This module is the only part of the crate which is available when the
+//! `alloc` feature is disabled, e.g. in bootloaders without an allocator.
+//! Public keys are then created from their components with
+//! [`RsaPublicKeyFixed::new`], and support signature verification and
+//! encryption into caller-provided buffers:
+//!
+//! ```
+//! use rsa::fixed::RsaPublicKey2048;
+//! # fn verify(n: rsa::fixed::U2048, signature: &[u8; 256], hashed: &[u8]) -> rsa::errors::Result<()> {
+//! use sha2::Sha256;
+//!
+//! let public_key = RsaPublicKey2048::new(n, 65537)?;
+//! public_key.verify_pkcs1v15_prehash::<Sha256>(hashed, signature)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Private keys and the methods taking the scheme types of the crate, such as
+//! `Pkcs1v15Sign`, require `alloc`.
+//!
+#![cfg_attr(feature = "sha2", doc = "```")]
+#![cfg_attr(not(feature = "sha2"), doc = "```ignore")]
+//! use rsa::fixed::{RsaPrivateKey2048, RsaPublicKey2048};
+//! use rsa::sha2::{Digest, Sha256};
+//! use rsa::{Pkcs1v15Sign, RsaPrivateKey};
+//!
+//! let mut rng = rand::thread_rng();
+//! let private_key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+//! let private_key = RsaPrivateKey2048::try_from(&private_key).expect("key size mismatch");
+//! let public_key: RsaPublicKey2048 = private_key.public_key().clone();
+//!
+//! let scheme = Pkcs1v15Sign::new::<Sha256>();
+//! let hashed = Sha256::digest(b"hello world");
+//! let mut signature = [0u8; 256];
+//! let len = private_key
+//!     .sign_pkcs1v15(&mut rng, &scheme, &hashed, &mut signature)
+//!     .expect("failed to sign");
+//! assert!(public_key
+//!     .verify_pkcs1v15(&scheme, &hashed, &signature[..len])
+//!     .is_ok());
+//! ```
+
+use crypto_bigint::modular::{MontyForm, MontyParams};
+use crypto_bigint::{Encoding, Odd, Uint, U64};
+use digest::{Digest, FixedOutputReset};
+use pkcs8::AssociatedOid;
+use rand_core::CryptoRngCore;
+
+use crate::algorithms::oaep::oaep_encrypt_digest_into;
+use crate::algorithms::pkcs1v15::{
+    pkcs1v15_encrypt_pad_into, pkcs1v15_generate_prefix_into, pkcs1v15_sign_unpad,
+};
+use crate::algorithms::pss::emsa_pss_verify_digest;
+use crate::errors::{Error, Result};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+#[cfg(feature = "alloc")]
+use {
+    crate::algorithms::arith::low_u64,
+    crate::algorithms::pkcs1v15::pkcs1v15_sign_pad_into,
+    crate::key::{RsaPrivateKey, RsaPublicKey},
+    crate::pkcs1v15::Pkcs1v15Sign,
+    crate::traits::{PrivateKeyParts, PublicKeyParts},
+    core::fmt,
+    crypto_bigint::{BoxedUint, Limb, RandomMod},
+};
+
+pub use crypto_bigint::{U2048, U3072, U4096};
+
+/// Minimum value of the public exponent, as for `RsaPublicKey`.
+const MIN_PUB_EXPONENT: u64 = 2;
+
+/// Maximum value of the public exponent, as for `RsaPublicKey`.
+const MAX_PUB_EXPONENT: u64 = (1 << 33) - 1;
+
+/// Maximum length of the `DigestInfo` prefix of PKCS#1 v1.5 signatures.
+const MAX_PREFIX_LEN: usize = 32;
+
+/// 2048-bit RSA public key.
+pub type RsaPublicKey2048 = RsaPublicKeyFixed<{ U2048::LIMBS }>;
+
+/// 3072-bit RSA public key.
+pub type RsaPublicKey3072 = RsaPublicKeyFixed<{ U3072::LIMBS }>;
+
+/// 4096-bit RSA public key.
+pub type RsaPublicKey4096 = RsaPublicKeyFixed<{ U4096::LIMBS }>;
+
+/// 2048-bit RSA private key.
+#[cfg(feature = "alloc")]
+pub type RsaPrivateKey2048 = RsaPrivateKeyFixed<{ U2048::LIMBS }>;
+
+/// 3072-bit RSA private key.
+#[cfg(feature = "alloc")]
+pub type RsaPrivateKey3072 = RsaPrivateKeyFixed<{ U3072::LIMBS }>;
+
+/// 4096-bit RSA private key.
+#[cfg(feature = "alloc")]
+pub type RsaPrivateKey4096 = RsaPrivateKeyFixed<{ U4096::LIMBS }>;
+
+/// RSA public key with a modulus of at most `LIMBS` limbs.
+#[derive(Debug, Clone)]
+pub struct RsaPublicKeyFixed<const LIMBS: usize> {
+    /// Modulus
+    n: Odd<Uint<LIMBS>>,
+    /// Public exponent
+    e: u64,
+    n_params: MontyParams<LIMBS>,
+}
+
+impl<const LIMBS: usize> Eq for RsaPublicKeyFixed<LIMBS> {}
+
+impl<const LIMBS: usize> PartialEq for RsaPublicKeyFixed<LIMBS> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n && self.e == other.e
+    }
+}
+
+impl<const LIMBS: usize> RsaPublicKeyFixed<LIMBS> {
+    /// Create a new public key from its components.
+    pub fn new(n: Uint<LIMBS>, e: u64) -> Result<Self> {
+        let n: Odd<Uint<LIMBS>> = Option::from(Odd::new(n)).ok_or(Error::InvalidModulus)?;
+        if &Uint::from_u64(e) >= n.as_ref() {
+            return Err(Error::InvalidModulus);
+        }
+        if e % 2 == 0 {
+            return Err(Error::InvalidExponent);
+        }
+        if e < MIN_PUB_EXPONENT {
+            return Err(Error::PublicExponentTooSmall);
+        }
+        if e > MAX_PUB_EXPONENT {
+            return Err(Error::PublicExponentTooLarge);
+        }
+
+        let n_params = MontyParams::new(n);
+        Ok(Self { n, e, n_params })
+    }
+
+    /// Modulus of the key.
+    pub fn n(&self) -> &Uint<LIMBS> {
+        self.n.as_ref()
+    }
+
+    /// Public exponent of the key.
+    pub fn e(&self) -> u64 {
+        self.e
+    }
+
+    /// Size of the modulus in bytes.
+    pub fn size(&self) -> usize {
+        (self.n.as_ref().bits() as usize).div_ceil(8)
+    }
+
+    /// ⚠️ Raw RSA encryption of `m`. No padding is performed.
+    ///
+    /// Raw RSA should never be used without an appropriate padding or
+    /// signature scheme. Fails with [`Error::MessageTooLong`], reporting the
+    /// size of the modulus, if `m` is not smaller than the modulus.
+    pub fn encrypt_raw(&self, m: &Uint<LIMBS>) -> Result<Uint<LIMBS>> {
+        if m >= self.n.as_ref() {
+            return Err(Error::MessageTooLong {
+                max_len: self.size(),
+            });
+        }
+        let m = MontyForm::new(m, self.n_params);
+        Ok(m.pow(&U64::from_u64(self.e)).retrieve())
+    }
+}
+
+impl<const LIMBS: usize> RsaPublicKeyFixed<LIMBS>
+where
+    Uint<LIMBS>: Encoding,
+{
+    /// Verify a PKCS#1 v1.5 signature of the message digest `hashed`.
+    #[cfg(feature = "alloc")]
+    pub fn verify_pkcs1v15(&self, scheme: &Pkcs1v15Sign, hashed: &[u8], sig: &[u8]) -> Result<()> {
+        if scheme.hash_len.is_some_and(|len| len != hashed.len()) {
+            return Err(Error::InputNotHashed);
+        }
+        self.verify_pkcs1v15_inner(&scheme.prefix, hashed, sig)
+    }
+
+    /// Verify a PKCS#1 v1.5 signature of the message digest `hashed`,
+    /// computed with `D`.
+    pub fn verify_pkcs1v15_prehash<D>(&self, hashed: &[u8], sig: &[u8]) -> Result<()>
+    where
+        D: Digest + AssociatedOid,
+    {
+        if hashed.len() != <D as Digest>::output_size() {
+            return Err(Error::InputNotHashed);
+        }
+        let mut prefix = [0u8; MAX_PREFIX_LEN];
+        let prefix = pkcs1v15_generate_prefix_into::<D>(&mut prefix)?;
+        self.verify_pkcs1v15_inner(prefix, hashed, sig)
+    }
+
+    fn verify_pkcs1v15_inner(&self, prefix: &[u8], hashed: &[u8], sig: &[u8]) -> Result<()> {
+        let k = self.size();
+        let mut em = self.recover_em(sig)?;
+        pkcs1v15_sign_unpad(prefix, hashed, &em.as_mut()[..k], k)
+    }
+
+    /// Verify a PSS signature of the message digest `hashed`, computed with
+    /// `D`, with a salt of `salt_len` bytes.
+    pub fn verify_pss_prehash<D>(&self, salt_len: usize, hashed: &[u8], sig: &[u8]) -> Result<()>
+    where
+        D: Digest + FixedOutputReset,
+    {
+        let k = self.size();
+        let mut em = self.recover_em(sig)?;
+        emsa_pss_verify_digest::<D>(
+            hashed,
+            &mut em.as_mut()[..k],
+            salt_len,
+            self.n.as_ref().bits() as usize,
+        )
+    }
+
+    /// Recovers the encoded message of `sig`, which ends up in the last `k`
+    /// bytes of the result, where `k` is the size of the key.
+    fn recover_em(&self, sig: &[u8]) -> Result<<Uint<LIMBS> as Encoding>::Repr> {
+        let k = self.size();
+        if sig.len() != k {
+            return Err(Error::Verification);
+        }
+
+        let sig = uint_from_be_slice::<LIMBS>(sig)?;
+        let mut em = self
+            .encrypt_raw(&sig)
+            .map_err(|_| Error::Verification)?
+            .to_be_bytes();
+        // Move the last `k` bytes to the front.
+        em.as_mut().rotate_right(k);
+        Ok(em)
+    }
+
+    /// Encrypt `msg` with PKCS#1 v1.5 padding.
+    ///
+    /// The ciphertext is written to the start of `out`, which must hold at
+    /// least [`RsaPublicKeyFixed::size`] bytes, and its length is returned.
+    pub fn encrypt_pkcs1v15<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+        msg: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        let em = out.get_mut(..self.size()).ok_or(Error::InvalidArguments)?;
+        pkcs1v15_encrypt_pad_into(rng, msg, em)?;
+        self.encrypt_in_place(em)
+    }
+
+    /// Encrypt `msg` with OAEP padding, using `D` to hash the `label` and `MGD`
+    /// for MGF1.
+    ///
+    /// The ciphertext is written to the start of `out`, which must hold at
+    /// least [`RsaPublicKeyFixed::size`] bytes, and its length is returned.
+    pub fn encrypt_oaep<R, D, MGD>(
+        &self,
+        rng: &mut R,
+        msg: &[u8],
+        label: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize>
+    where
+        R: CryptoRngCore + ?Sized,
+        D: Digest,
+        MGD: Digest + FixedOutputReset,
+    {
+        let em = out.get_mut(..self.size()).ok_or(Error::InvalidArguments)?;
+        oaep_encrypt_digest_into::<_, D, MGD>(rng, msg, label, em)?;
+        self.encrypt_in_place(em)
+    }
+
+    /// Replaces the encoded message `em` with its encryption.
+    fn encrypt_in_place(&self, em: &mut [u8]) -> Result<usize> {
+        let k = em.len();
+        let m = uint_from_be_slice::<LIMBS>(em)?;
+        let c = self.encrypt_raw(&m)?.to_be_bytes();
+        let c = c.as_ref();
+        em.copy_from_slice(&c[c.len() - k..]);
+        Ok(k)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const LIMBS: usize> TryFrom<&RsaPublicKey> for RsaPublicKeyFixed<LIMBS> {
+    type Error = Error;
+
+    fn try_from(key: &RsaPublicKey) -> Result<Self> {
+        let n = uint_from_boxed::<LIMBS>(key.n()).ok_or(Error::ModulusTooLarge)?;
+        if key.e().bits() > u64::BITS {
+            return Err(Error::PublicExponentTooLarge);
+        }
+        Self::new(n, low_u64(key.e()))
+    }
+}
+
+/// RSA private key with a modulus of at most `LIMBS` limbs.
+#[derive(Clone)]
+pub struct RsaPrivateKeyFixed<const LIMBS: usize> {
+    /// Public components of the private key.
+    public_key: RsaPublicKeyFixed<LIMBS>,
+    /// Private exponent
+    d: Uint<LIMBS>,
+}
+
+#[cfg(feature = "alloc")]
+impl<const LIMBS: usize> fmt::Debug for RsaPrivateKeyFixed<LIMBS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RsaPrivateKeyFixed")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const LIMBS: usize> Drop for RsaPrivateKeyFixed<LIMBS> {
+    fn drop(&mut self) {
+        self.d.zeroize();
+    }
+}
+
+impl<const LIMBS: usize> ZeroizeOnDrop for RsaPrivateKeyFixed<LIMBS> {}
+
+#[cfg(feature = "alloc")]
+impl<const LIMBS: usize> RsaPrivateKeyFixed<LIMBS> {
+    /// Get the public key of this private key.
+    pub fn public_key(&self) -> &RsaPublicKeyFixed<LIMBS> {
+        &self.public_key
+    }
+
+    /// ⚠️ Raw RSA decryption of `c` with blinding. No padding is removed.
+    ///
+    /// The result is checked against `c` to defend against faults in the
+    /// computation. Raw RSA should never be used without an appropriate
+    /// padding or signature scheme.
+    pub fn decrypt_raw<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+        c: &Uint<LIMBS>,
+    ) -> Result<Uint<LIMBS>> {
+        let public_key = &self.public_key;
+        if c >= public_key.n.as_ref() {
+            return Err(Error::Decryption);
+        }
+        let params = public_key.n_params;
+
+        // Blind c with a random r, as c·r^e, and unblind the result with r^-1.
+        let (r_e, r_inv) = loop {
+            let r = Uint::random_mod(rng, public_key.n.as_nz_ref());
+            let r = MontyForm::new(&r, params);
+            let r_inv: Option<MontyForm<LIMBS>> = Option::from(r.invert());
+            if let Some(r_inv) = r_inv {
+                break (r.pow(&U64::from_u64(public_key.e)), r_inv);
+            }
+        };
+        let blinded = MontyForm::new(c, params) * r_e;
+        let m = (blinded.pow(&self.d) * r_inv).retrieve();
+
+        if &public_key.encrypt_raw(&m)? != c {
+            return Err(Error::FaultDetected);
+        }
+        Ok(m)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const LIMBS: usize> RsaPrivateKeyFixed<LIMBS>
+where
+    Uint<LIMBS>: Encoding,
+{
+    /// Sign the message digest `hashed` with PKCS#1 v1.5 padding.
+    ///
+    /// The signature is written to the start of `sig`, which must hold at
+    /// least [`RsaPublicKeyFixed::size`] bytes, and its length is returned.
+    pub fn sign_pkcs1v15<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+        scheme: &Pkcs1v15Sign,
+        hashed: &[u8],
+        sig: &mut [u8],
+    ) -> Result<usize> {
+        if scheme.hash_len.is_some_and(|len| len != hashed.len()) {
+            return Err(Error::InputNotHashed);
+        }
+        let k = self.public_key.size();
+        let sig = sig.get_mut(..k).ok_or(Error::InvalidArguments)?;
+
+        pkcs1v15_sign_pad_into(&scheme.prefix, hashed, sig)?;
+        let em = uint_from_be_slice::<LIMBS>(sig)?;
+        let s = self.decrypt_raw(rng, &em)?.to_be_bytes();
+        let s = s.as_ref();
+        sig.copy_from_slice(&s[s.len() - k..]);
+        Ok(k)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const LIMBS: usize> TryFrom<&RsaPrivateKey> for RsaPrivateKeyFixed<LIMBS> {
+    type Error = Error;
+
+    fn try_from(key: &RsaPrivateKey) -> Result<Self> {
+        let public_key: &RsaPublicKey = key.as_ref();
+        let public_key = RsaPublicKeyFixed::try_from(public_key)?;
+        let d = uint_from_boxed::<LIMBS>(key.d()).ok_or(Error::InvalidExponent)?;
+        Ok(Self { public_key, d })
+    }
+}
+
+#[cfg(feature = "alloc")]
+/// Converts `x` to a fixed-size integer, if it fits.
+fn uint_from_boxed<const LIMBS: usize>(x: &BoxedUint) -> Option<Uint<LIMBS>> {
+    if x.bits() > Uint::<LIMBS>::BITS {
+        return None;
+    }
+    let mut limbs = [Limb::ZERO; LIMBS];
+    for (limb, x) in limbs.iter_mut().zip(x.as_limbs()) {
+        *limb = *x;
+    }
+    Some(Uint::new(limbs))
+}
+
+/// Decodes a big-endian integer of at most the size of `Uint<LIMBS>`.
+fn uint_from_be_slice<const LIMBS: usize>(bytes: &[u8]) -> Result<Uint<LIMBS>>
+where
+    Uint<LIMBS>: Encoding,
+{
+    let mut repr = Uint::<LIMBS>::ZERO.to_be_bytes();
+    let buf = repr.as_mut();
+    let offset = buf
+        .len()
+        .checked_sub(bytes.len())
+        .ok_or(Error::InvalidArguments)?;
+    buf[offset..].copy_from_slice(bytes);
+    Ok(Uint::from_be_bytes(repr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto_bigint::U1024;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::{Digest, Sha256};
+
+    type RsaPrivateKey1024 = RsaPrivateKeyFixed<{ U1024::LIMBS }>;
+
+    #[test]
+    fn test_fixed_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let fixed = RsaPrivateKey1024::try_from(&key).unwrap();
+        let public_key = fixed.public_key();
+        assert_eq!(public_key.size(), key.size());
+        assert_eq!(public_key.e(), 65537);
+
+        let scheme = Pkcs1v15Sign::new::<Sha256>();
+        let hashed = Sha256::digest(b"fixed size keys");
+        let mut sig = [0u8; 128];
+        assert_eq!(
+            fixed.sign_pkcs1v15(&mut rng, &scheme, &hashed, &mut sig),
+            Ok(128)
+        );
+
+        // Signatures match those of the heap-allocated keys.
+        let expected = key.sign(Pkcs1v15Sign::new::<Sha256>(), &hashed).unwrap();
+        assert_eq!(&sig[..], &expected[..]);
+        assert!(public_key.verify_pkcs1v15(&scheme, &hashed, &sig).is_ok());
+        sig[0] ^= 1;
+        assert_eq!(
+            public_key.verify_pkcs1v15(&scheme, &hashed, &sig),
+            Err(Error::Verification)
+        );
+
+        let larger = RsaPrivateKey::new(&mut rng, 2048).unwrap();
+        assert_eq!(
+            RsaPrivateKey1024::try_from(&larger).unwrap_err(),
+            Error::ModulusTooLarge
+        );
+    }
+
+    #[test]
+    fn test_fixed_public_key_heapless() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let public_key =
+            RsaPublicKeyFixed::<{ U1024::LIMBS }>::try_from(&key.to_public_key()).unwrap();
+        assert_eq!(MIN_PUB_EXPONENT, RsaPublicKey::MIN_PUB_EXPONENT);
+        assert_eq!(MAX_PUB_EXPONENT, RsaPublicKey::MAX_PUB_EXPONENT);
+
+        let hashed = Sha256::digest(b"heapless");
+        let sig = key.sign(Pkcs1v15Sign::new::<Sha256>(), &hashed).unwrap();
+        assert!(public_key
+            .verify_pkcs1v15_prehash::<Sha256>(&hashed, &sig)
+            .is_ok());
+        assert_eq!(
+            public_key.verify_pkcs1v15_prehash::<Sha256>(&hashed[1..], &sig),
+            Err(Error::InputNotHashed)
+        );
+
+        let sig = key
+            .sign_with_rng(&mut rng, crate::Pss::new::<Sha256>(), &hashed)
+            .unwrap();
+        assert!(public_key
+            .verify_pss_prehash::<Sha256>(32, &hashed, &sig)
+            .is_ok());
+        assert_eq!(
+            public_key.verify_pss_prehash::<Sha256>(32, &hashed, &sig[1..]),
+            Err(Error::Verification)
+        );
+
+        let mut ciphertext = [0u8; 128];
+        let len = public_key
+            .encrypt_pkcs1v15(&mut rng, b"heapless", &mut ciphertext)
+            .unwrap();
+        let plaintext = key.decrypt(crate::Pkcs1v15Encrypt, &ciphertext[..len]);
+        assert_eq!(plaintext.unwrap(), b"heapless");
+
+        let len = public_key
+            .encrypt_oaep::<_, Sha256, Sha256>(&mut rng, b"heapless", b"label", &mut ciphertext)
+            .unwrap();
+        let scheme = crate::Oaep::new_with_label::<Sha256, _>("label".as_bytes());
+        let plaintext = key.decrypt(scheme, &ciphertext[..len]);
+        assert_eq!(plaintext.unwrap(), b"heapless");
+
+        assert_eq!(
+            public_key.encrypt_raw(public_key.n.as_ref()),
+            Err(Error::MessageTooLong { max_len: 128 })
+        );
+    }
+}
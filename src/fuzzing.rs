@@ -0,0 +1,234 @@
+//! [`Arbitrary`] implementations for structure-aware fuzzing and property
+//! tests.
+//!
+//! Generating keys is far too slow for fuzzing, so private keys are built
+//! from pairs of a small set of pregenerated 512-bit primes, which gives 66
+//! distinct 1024-bit keys. Such keys are only suitable for tests.
+
+use alloc::vec::Vec;
+use arbitrary::{Arbitrary, Result, Unstructured};
+use crypto_bigint::BoxedUint;
+
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::{pkcs1v15, pss, Pkcs1v15Encrypt, Pkcs1v15Sign};
+
+/// Primes `p` with both top bits set and `gcd(p - 1, 65537) = 1`, so that
+/// every pair forms a 1024-bit key with the usual public exponent.
+const PRIMES: [&str; 12] = [
+    concat!(
+        "f34410dcbdb6bd26b2a02aa60b700fec566234e8019a46d183f24316d486f15f",
+        "cd405440f0a3f754031ba5794dd0b861135420532224c9e230a66431f63d173d"
+    ),
+    concat!(
+        "dfe1e72899174dc9410babdb26cd7a28c1101bb2ceaed9387a6f96ed2e415549",
+        "b0168d6927cdea19536b12c68196c35075209261649b0f7293ce860e3d8220d3"
+    ),
+    concat!(
+        "f564c124849918c68cadd9dcf34da91d9f44592bfd52292f2116d55176eda07f",
+        "6af75c181dae200fd24127e12fcf2fee26aeb5921f99117c892f1cac3903957b"
+    ),
+    concat!(
+        "f873926c8b36750bb737d1779d3fb8bddff2777a785629da5cc594665dc61624",
+        "636a827633957d67115f7fa2f1c9f092ad75ded2f0594d686ca452bd1b559f75"
+    ),
+    concat!(
+        "cf524ba1e40662059307f23517481376648748c7844caa51932bc78d33eb6e14",
+        "2800fb3a37fc5db835d5d28f4c65c547799c99beb78c6465a20696e5166a9bf1"
+    ),
+    concat!(
+        "d469b576da3a9aa6e8ac7a94880e7f279d4a83a98decefc75b16ee71713d3df8",
+        "c7aa7653c0b0a11d16bc59ca4d28c2ef97bb4ff6217dc46f81f284f5c1645135"
+    ),
+    concat!(
+        "d65e26827fb8843aafbdfd9efb75f0582824e9e2c06945e9984b4de590637a17",
+        "8e9da965847ae8f118e7795475f6eeac363fa2089fefd8f2f3386450c65076ef"
+    ),
+    concat!(
+        "d0ac280dba29a2a05cc01ef51212c4da8606627202c73d943ad6b39c7b19c95a",
+        "7886a3f05af0605ba889815b0ab5e2f38ed0dd649a96e494b22a8270b7edb147"
+    ),
+    concat!(
+        "e67ed2d0a7410da2b605bbc10a2b7e6aa4df08f9c5c6bc80fe5159822db951af",
+        "9b82d0496a14f3bc429b7904c6b279bddc0ec73d295d4c37389428b27bc7010f"
+    ),
+    concat!(
+        "d948d241ff6db80f45ed1af90efbaad715725de4dface6c79ce0b0d9e4e6b425",
+        "05f012f30297454806045472eadf6ea481f6278814714c14f3e22c4b40990415"
+    ),
+    concat!(
+        "fa70e9018aa777921502fd9a05f38876943544e70567671cceae88ee4aae4649",
+        "bb81cede00e57e2a298c4031eee78a1c8a0e03e4d3267595fe1cb85078fafff7"
+    ),
+    concat!(
+        "eafbb523f6cf567edd3c30e8539fce1c7c6bd54a7167289804f4f94249d1ad31",
+        "ba42d53aa13bc6a664dae761f771705608cf5903e422630f83a16cc259c2bd3f"
+    ),
+];
+
+/// Size in bytes of the moduli of the generated keys.
+const KEY_SIZE: usize = 128;
+
+fn prime(index: usize) -> BoxedUint {
+    let bytes = (0..PRIMES[index].len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&PRIMES[index][i..i + 2], 16).expect("valid hex"))
+        .collect::<Vec<_>>();
+    BoxedUint::from_be_slice(&bytes, 512).expect("prime fits in 512 bits")
+}
+
+impl<'a> Arbitrary<'a> for RsaPrivateKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let p = u.choose_index(PRIMES.len())?;
+        let q = (p + 1 + u.choose_index(PRIMES.len() - 1)?) % PRIMES.len();
+        RsaPrivateKey::from_p_q(prime(p), prime(q), BoxedUint::from(65_537u64))
+            .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (2, Some(2))
+    }
+}
+
+impl<'a> Arbitrary<'a> for RsaPublicKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(RsaPrivateKey::arbitrary(u)?.to_public_key())
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        RsaPrivateKey::size_hint(depth)
+    }
+}
+
+/// Byte string of the size of the moduli of the generated keys.
+fn signature_bytes<'a>(u: &mut Unstructured<'a>) -> Result<&'a [u8]> {
+    u.bytes(KEY_SIZE)
+}
+
+impl<'a> Arbitrary<'a> for pkcs1v15::Signature {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::try_from(signature_bytes(u)?).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (KEY_SIZE, Some(KEY_SIZE))
+    }
+}
+
+impl<'a> Arbitrary<'a> for pss::Signature {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Self::try_from(signature_bytes(u)?).map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (KEY_SIZE, Some(KEY_SIZE))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Pkcs1v15Encrypt {
+    fn arbitrary(_u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, Some(0))
+    }
+}
+
+impl<'a> Arbitrary<'a> for Pkcs1v15Sign {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        #[cfg(feature = "sha2")]
+        {
+            Ok(match u.int_in_range(0..=3u8)? {
+                0 => Self::new_unprefixed(),
+                1 => Self::new::<sha2::Sha256>(),
+                2 => Self::new::<sha2::Sha384>(),
+                _ => Self::new::<sha2::Sha512>(),
+            })
+        }
+        #[cfg(not(feature = "sha2"))]
+        {
+            let _ = u;
+            Ok(Self::new_unprefixed())
+        }
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (0, Some(1))
+    }
+}
+
+/// Schemes using a digest, which are only generated with the `sha2` feature.
+#[cfg(feature = "sha2")]
+mod digest_schemes {
+    use super::*;
+    use crate::{Oaep, Pss};
+    use sha2::{Sha256, Sha384, Sha512};
+
+    impl<'a> Arbitrary<'a> for Pss {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let blinded = bool::arbitrary(u)?;
+            // Salts are at most as long as the digest, and short enough for
+            // SHA-512 to fit 1024-bit keys.
+            Ok(match u.int_in_range(0..=2u8)? {
+                0 => pss_with::<Sha256>(blinded, u.int_in_range(0..=32)?),
+                1 => pss_with::<Sha384>(blinded, u.int_in_range(0..=48)?),
+                _ => pss_with::<Sha512>(blinded, u.int_in_range(0..=KEY_SIZE - 64 - 2)?),
+            })
+        }
+
+        fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+            (0, Some(3))
+        }
+    }
+
+    fn pss_with<D>(blinded: bool, salt_len: usize) -> Pss
+    where
+        D: 'static + sha2::Digest + digest::DynDigest + Send + Sync,
+    {
+        if blinded {
+            Pss::new_blinded_with_salt::<D>(salt_len)
+        } else {
+            Pss::new_with_salt::<D>(salt_len)
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for Oaep {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let label = Option::<Vec<u8>>::arbitrary(u)?;
+            // SHA-512 leaves no room for messages in 1024-bit keys.
+            Ok(match (bool::arbitrary(u)?, label) {
+                (false, None) => Oaep::new::<Sha256>(),
+                (false, Some(label)) => Oaep::new_with_label::<Sha256, _>(label),
+                (true, None) => Oaep::new_with_mgf_hash::<Sha256, Sha384>(),
+                (true, Some(label)) => {
+                    Oaep::new_with_mgf_hash_and_label::<Sha256, Sha384, _>(label)
+                }
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::PublicKeyParts;
+    use ::signature::SignatureEncoding;
+
+    #[test]
+    fn test_arbitrary_keys() {
+        let data = (0u8..=255).collect::<Vec<_>>();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..8 {
+            let key = RsaPrivateKey::arbitrary(&mut u).unwrap();
+            assert_eq!(key.size(), KEY_SIZE);
+            key.validate().unwrap();
+        }
+        assert_eq!(
+            pkcs1v15::Signature::arbitrary(&mut u)
+                .unwrap()
+                .to_vec()
+                .len(),
+            KEY_SIZE
+        );
+    }
+}
@@ -11,4 +11,84 @@
 //! We do NOT recommend using it to implement any algorithm which has not
 //! received extensive peer review by cryptographers.
 
-pub use crate::algorithms::rsa::{rsa_decrypt, rsa_decrypt_and_check, rsa_encrypt};
+pub use crate::algorithms::rsa::{
+    rsa_decrypt, rsa_decrypt_and_check, rsa_encrypt, rsa_encrypt_vartime,
+};
+
+use crypto_bigint::BoxedUint;
+use zeroize::Zeroizing;
+
+use crate::errors::Result;
+use crate::key::RsaPrivateKey;
+
+pub mod analysis;
+pub mod bleichenbacher;
+pub mod distributed_keygen;
+pub mod mediated;
+pub mod ring;
+pub mod sra;
+pub mod timelock;
+pub mod vdf;
+
+impl RsaPrivateKey {
+    /// Compute Euler's totient `φ(n) = Π(p_i - 1)`, the order of the
+    /// multiplicative group modulo `n`.
+    ///
+    /// Anyone knowing `φ(n)` can factor `n`, so it must be kept as secret as
+    /// the key itself.
+    pub fn euler_totient(&self) -> Result<Zeroizing<BoxedUint>> {
+        Ok(Zeroizing::new(self.totient()?.as_ref().clone()))
+    }
+
+    /// Compute the Carmichael function `λ(n) = lcm(p_i - 1)`, the exponent of
+    /// the multiplicative group modulo `n`.
+    ///
+    /// Anyone knowing `λ(n)` can factor `n`, so it must be kept as secret as
+    /// the key itself.
+    pub fn carmichael_lambda(&self) -> Result<Zeroizing<BoxedUint>> {
+        Ok(Zeroizing::new(self.lambda()?.as_ref().clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{PrivateKeyParts, PublicKeyParts};
+    use crypto_bigint::{Gcd, NonZero};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_totient_and_lambda() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let phi = key.euler_totient().unwrap();
+        let lambda = key.carmichael_lambda().unwrap();
+
+        let one = BoxedUint::one();
+        let p1 = key.primes()[0].wrapping_sub(&one);
+        let q1 = key.primes()[1].wrapping_sub(&one);
+        assert_eq!(*phi, p1.widen(512).wrapping_mul(&q1.widen(512)));
+
+        // φ(n) = λ(n) · gcd(p - 1, q - 1) for two primes.
+        let gcd = p1.gcd(&q1).widen(512);
+        assert_eq!(*phi, lambda.wrapping_mul(&gcd));
+
+        // e·d ≡ 1 mod λ(n).
+        let lambda = NonZero::new(lambda.widen(1024)).unwrap();
+        let de = key.d().widen(1024).wrapping_mul(&key.e().widen(1024));
+        assert!(bool::from(de.rem_vartime(&lambda).is_one()));
+    }
+
+    #[test]
+    fn test_raw_private_op() {
+        use crate::dummy_rng::DummyRng;
+        use crate::traits::RsaPrivateOps;
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let c = BoxedUint::from(42u64).widen(key.n_bits_precision());
+        let m = rsa_decrypt_and_check::<DummyRng>(&key, None, &c).unwrap();
+        assert_eq!(key.raw_private_op(Some(&mut rng), &c).unwrap(), m);
+        assert_eq!(key.raw_decrypt_op(Some(&mut rng), &c).unwrap(), m);
+    }
+}
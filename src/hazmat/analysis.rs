@@ -0,0 +1,338 @@
+//! ⚠️ Attacks on weak keys and misused raw RSA.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! These functions recover plaintexts or private keys in classic situations
+//! where RSA is misused, for incident response, audits and CTF tooling:
+//!
+//! - [`common_modulus_attack`]: the same message encrypted under two keys
+//!   sharing a modulus with coprime exponents.
+//! - [`hastad_broadcast_attack`]: the same message encrypted without padding
+//!   under `e` keys with the same small exponent `e` [Håstad].
+//! - [`wiener_attack`]: keys whose private exponent is smaller than about
+//!   `n^(1/4) / 3` [Wiener].
+//!
+//! The first two only apply to raw (textbook) RSA or deterministic padding;
+//! ciphertexts produced with OAEP or PKCS#1 v1.5 are not affected. All
+//! functions run in variable time.
+//!
+//! [Håstad]: https://doi.org/10.1137/0217019
+//! [Wiener]: https://doi.org/10.1109/18.54902
+
+use alloc::vec::Vec;
+use crypto_bigint::{BoxedUint, NonZero};
+
+use crate::algorithms::arith::{inv_mod, mul_mod, pow2, pow_mod, reduce, resize};
+use crate::algorithms::pad::uint_to_be_pad;
+use crate::errors::{Error, Result};
+use crate::key::RsaPublicKey;
+use crate::traits::PublicKeyParts;
+
+/// Private key recovered by [`wiener_attack`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmallPrivateExponent {
+    /// The private exponent.
+    pub d: BoxedUint,
+    /// The larger prime factor of the modulus.
+    pub p: BoxedUint,
+    /// The smaller prime factor of the modulus.
+    pub q: BoxedUint,
+}
+
+/// Recovers the message encrypted as `c1` with `key1` and as `c2` with
+/// `key2`, two keys sharing their modulus.
+///
+/// With `a·e1 - b·e2 = 1`, the message is `c1^a · c2^-b mod n`. Fails with
+/// [`Error::InvalidModulus`] if the moduli differ, with
+/// [`Error::InvalidExponent`] if the exponents are not coprime, and with
+/// [`Error::InvalidArguments`] if a ciphertext is out of range.
+pub fn common_modulus_attack(
+    key1: &RsaPublicKey,
+    c1: &[u8],
+    key2: &RsaPublicKey,
+    c2: &[u8],
+) -> Result<Vec<u8>> {
+    if key1.n().as_ref() != key2.n().as_ref() {
+        return Err(Error::InvalidModulus);
+    }
+
+    let bits = core::cmp::max(key1.e().bits_precision(), key2.e().bits_precision());
+    let e1 = resize(key1.e(), bits);
+    let e2 = resize(key2.e(), bits);
+    let a = e1
+        .inv_mod(&e2)
+        .into_option()
+        .ok_or(Error::InvalidExponent)?;
+
+    // a·e1 ≡ 1 mod e2, so a·e1 - 1 = b·e2 for some b ≥ 0.
+    let wide = 2 * bits;
+    let e2 = NonZero::new(resize(&e2, wide))
+        .into_option()
+        .ok_or(Error::InvalidExponent)?;
+    let b = resize(&a, wide)
+        .wrapping_mul(&resize(&e1, wide))
+        .wrapping_sub(&BoxedUint::one())
+        / e2;
+
+    let params = key1.n_params();
+    let c1 = ciphertext(key1, c1)?;
+    let c2 = ciphertext(key1, c2)?;
+    let c2_inv = inv_mod(&c2, params).ok_or(Error::InvalidArguments)?;
+    let m = mul_mod(
+        &pow_mod(&c1, &a, params),
+        &pow_mod(&c2_inv, &b, params),
+        params,
+    );
+    uint_to_be_pad(m, key1.size())
+}
+
+/// Recovers the message encrypted without padding as `ciphertexts[i]` with
+/// `keys[i]`, all of which have the same small public exponent `e`.
+///
+/// The first `e` ciphertexts are combined with the Chinese remainder theorem
+/// into `m^e`, whose integer `e`-th root is the message. Fails with
+/// [`Error::InvalidArguments`] if there are fewer than `e` keys or the
+/// exponents differ, with [`Error::InvalidModulus`] if two moduli are not
+/// coprime, which means they share a prime factor, and with
+/// [`Error::Decryption`] if the result is not an `e`-th power, e.g. because
+/// the messages differ or were padded.
+pub fn hastad_broadcast_attack(keys: &[RsaPublicKey], ciphertexts: &[&[u8]]) -> Result<Vec<u8>> {
+    let e = keys.first().ok_or(Error::InvalidArguments)?.e();
+    if e.bits() > 16 || keys.iter().any(|key| key.e() != e) {
+        return Err(Error::InvalidArguments);
+    }
+    let e = e
+        .to_be_bytes()
+        .iter()
+        .fold(0u32, |e, &b| (e << 8) | u32::from(b));
+    let count = e as usize;
+    if e < 2 || keys.len() < count || ciphertexts.len() < count {
+        return Err(Error::InvalidArguments);
+    }
+    let keys = &keys[..count];
+
+    // Room for the products, and for the powers of the root estimates.
+    let bits = keys.iter().map(|key| key.n_bits_precision()).sum::<u32>() + e + 64;
+    let mut n = BoxedUint::one_with_precision(bits);
+    for key in keys {
+        n = n.wrapping_mul(&resize(key.n(), bits));
+    }
+    let n = NonZero::new(n).into_option().ok_or(Error::InvalidModulus)?;
+
+    let mut c = BoxedUint::zero_with_precision(bits);
+    for (key, ciphertext_i) in keys.iter().zip(ciphertexts) {
+        let n_i = n.as_ref() / NonZero::new(resize(key.n(), bits)).expect("non zero");
+        let inv = inv_mod(&n_i, key.n_params()).ok_or(Error::InvalidModulus)?;
+        let t = mul_mod(&ciphertext(key, ciphertext_i)?, &inv, key.n_params());
+        c = reduce(&c.wrapping_add(&resize(&t, bits).wrapping_mul(&n_i)), &n);
+    }
+
+    let m = nth_root(&c, e);
+    if pow(&m, e) != c {
+        return Err(Error::Decryption);
+    }
+    let k = keys.iter().map(|key| key.size()).min().unwrap_or_default();
+    uint_to_be_pad(m, k)
+}
+
+/// Recovers the private key of `pub_key` if its private exponent is small
+/// enough for Wiener's attack, which finds `k/d` among the continued
+/// fraction convergents of `e/n`.
+///
+/// Returns `None` if no convergent yields the factorization of the modulus,
+/// which is the case of all properly generated keys.
+pub fn wiener_attack(pub_key: &RsaPublicKey) -> Option<SmallPrivateExponent> {
+    let bits = 2 * pub_key.n_bits_precision();
+    let n = resize(pub_key.n(), bits);
+    let e = resize(pub_key.e(), bits);
+    let zero = BoxedUint::zero_with_precision(bits);
+    let one = BoxedUint::one_with_precision(bits);
+
+    // Convergents k_i/d_i of e/n, starting from k_-1/d_-1 = 1/0 and
+    // k_-2/d_-2 = 0/1.
+    let (mut a, mut b) = (e.clone(), n.clone());
+    let (mut k1, mut k2) = (one.clone(), zero.clone());
+    let (mut d1, mut d2) = (zero, one);
+    while let Some(divisor) = NonZero::new(b.clone()).into_option() {
+        let (q, r) = a.div_rem(&divisor);
+        (a, b) = (b, r);
+        let k = q.wrapping_mul(&k1).wrapping_add(&k2);
+        let d = q.wrapping_mul(&d1).wrapping_add(&d2);
+        if let Some(recovered) = try_convergent(pub_key, &n, &e, &k, &d) {
+            return Some(recovered);
+        }
+        (k2, k1) = (k1, k);
+        (d2, d1) = (d1, d);
+    }
+    None
+}
+
+/// Checks whether `e·d = 1 + k·φ(n)` for a `φ(n)` which factors `n`.
+fn try_convergent(
+    pub_key: &RsaPublicKey,
+    n: &BoxedUint,
+    e: &BoxedUint,
+    k: &BoxedUint,
+    d: &BoxedUint,
+) -> Option<SmallPrivateExponent> {
+    let bits = n.bits_precision();
+    let one = BoxedUint::one_with_precision(bits);
+    let k = NonZero::new(k.clone()).into_option()?;
+    if bool::from(d.is_zero()) {
+        return None;
+    }
+    let (phi, rem) = e.wrapping_mul(d).wrapping_sub(&one).div_rem(&k);
+    if !bool::from(rem.is_zero()) || &phi >= n {
+        return None;
+    }
+
+    // p + q = n - φ(n) + 1 and (p - q)² = (p + q)² - 4n.
+    let sum = n.wrapping_sub(&phi).wrapping_add(&one);
+    let square = sum.wrapping_mul(&sum);
+    let four_n = n.wrapping_mul(&resize(&BoxedUint::from(4u64), bits));
+    if square < four_n {
+        return None;
+    }
+    let disc = square.wrapping_sub(&four_n);
+    let diff = disc.sqrt_vartime();
+    if diff.wrapping_mul(&diff) != disc || bool::from(sum.is_odd() ^ diff.is_odd()) {
+        return None;
+    }
+
+    let precision = pub_key.n_bits_precision();
+    let p = sum.wrapping_add(&diff) >> 1;
+    let q = sum.wrapping_sub(&diff) >> 1;
+    if p.wrapping_mul(&q) != *n {
+        return None;
+    }
+    Some(SmallPrivateExponent {
+        d: resize(d, precision),
+        p: resize(&p, precision),
+        q: resize(&q, precision),
+    })
+}
+
+/// Decodes a ciphertext for `key`, which must be smaller than the modulus.
+fn ciphertext(key: &RsaPublicKey, c: &[u8]) -> Result<BoxedUint> {
+    let c =
+        BoxedUint::from_be_slice(c, key.n_bits_precision()).map_err(|_| Error::InvalidArguments)?;
+    if &c >= key.n().as_ref() {
+        return Err(Error::InvalidArguments);
+    }
+    Ok(c)
+}
+
+/// Computes `x^e` with the precision of `x`, which must hold the result.
+fn pow(x: &BoxedUint, e: u32) -> BoxedUint {
+    (1..e).fold(x.clone(), |acc, _| acc.wrapping_mul(x))
+}
+
+/// Computes `⌊x^(1/e)⌋` with Newton's method, starting above the root.
+///
+/// The precision of `x` must leave room for `e` more bits than its value.
+fn nth_root(x: &BoxedUint, e: u32) -> BoxedUint {
+    let bits = x.bits_precision();
+    if bool::from(x.is_zero()) {
+        return x.clone();
+    }
+    let e_minus_one = resize(&BoxedUint::from(u64::from(e - 1)), bits);
+    let e_wide = NonZero::new(resize(&BoxedUint::from(u64::from(e)), bits)).expect("e ≥ 2");
+
+    let mut root = pow2(x.bits().div_ceil(e), bits);
+    loop {
+        let power = NonZero::new(pow(&root, e - 1)).expect("root is non zero");
+        let next = (root.wrapping_mul(&e_minus_one).wrapping_add(&(x / power))) / e_wide.clone();
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::generate::generate_prime_with_rng;
+    use crate::algorithms::rsa::rsa_encrypt;
+    use crate::RsaPrivateKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    fn encrypt(key: &RsaPublicKey, m: &[u8]) -> Vec<u8> {
+        let m = BoxedUint::from_be_slice(m, key.n_bits_precision()).unwrap();
+        uint_to_be_pad(rsa_encrypt(key, &m).unwrap(), key.size()).unwrap()
+    }
+
+    #[test]
+    fn test_common_modulus_attack() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let key1 = key.to_public_key();
+        let key2 = RsaPublicKey::new(key.n().as_ref().clone(), BoxedUint::from(65_539u64)).unwrap();
+
+        let mut m = [0u8; 64];
+        m[32..].copy_from_slice(&[7; 32]);
+        let c1 = encrypt(&key1, &m);
+        let c2 = encrypt(&key2, &m);
+        assert_eq!(common_modulus_attack(&key1, &c1, &key2, &c2).unwrap(), m);
+
+        assert_eq!(
+            common_modulus_attack(&key1, &c1, &key1, &c1),
+            Err(Error::InvalidExponent)
+        );
+    }
+
+    #[test]
+    fn test_hastad_broadcast_attack() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let keys = (0..3)
+            .map(|_| {
+                RsaPrivateKey::new_with_exp(&mut rng, 512, BoxedUint::from(3u64))
+                    .unwrap()
+                    .to_public_key()
+            })
+            .collect::<Vec<_>>();
+
+        let mut m = [0u8; 64];
+        m[16..].copy_from_slice(&[0x5a; 48]);
+        let ciphertexts = keys.iter().map(|key| encrypt(key, &m)).collect::<Vec<_>>();
+        let ciphertexts = ciphertexts.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        assert_eq!(hastad_broadcast_attack(&keys, &ciphertexts).unwrap(), m);
+
+        assert_eq!(
+            hastad_broadcast_attack(&keys[..2], &ciphertexts[..2]),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    fn test_wiener_attack() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let p = generate_prime_with_rng(&mut rng, 256);
+        let q = generate_prime_with_rng(&mut rng, 256);
+        let n = p.widen(512).wrapping_mul(&q.widen(512));
+        let phi = p
+            .wrapping_sub(&BoxedUint::one())
+            .widen(512)
+            .wrapping_mul(&q.wrapping_sub(&BoxedUint::one()).widen(512));
+
+        // d = 2^100 + 1 + 2i, far below n^(1/4) / 3.
+        let (d, e) = (0u64..)
+            .find_map(|i| {
+                let d = pow2(100, 512).wrapping_add(&BoxedUint::from(1 + 2 * i).widen(512));
+                d.inv_mod(&phi).into_option().map(|e| (d, e))
+            })
+            .unwrap();
+        let key = RsaPublicKey::new_unchecked(n, e).unwrap();
+
+        let recovered = wiener_attack(&key).unwrap();
+        assert_eq!(recovered.d, d);
+        let mut primes = [recovered.p, recovered.q];
+        primes.sort();
+        let mut expected = [p.widen(512), q.widen(512)];
+        expected.sort();
+        assert_eq!(primes, expected);
+
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap().to_public_key();
+        assert_eq!(wiener_attack(&key), None);
+    }
+}
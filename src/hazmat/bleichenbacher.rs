@@ -0,0 +1,269 @@
+//! ⚠️ Bleichenbacher padding oracle probes.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! A service decrypting PKCS#1 v1.5 ciphertexts is a padding oracle if its
+//! observable behaviour, e.g. its error messages, status codes or whether it
+//! closes the connection, depends on whether the plaintext is PKCS#1 v1.5
+//! conformant. Such an oracle lets an attacker decrypt arbitrary ciphertexts
+//! and forge signatures with the key [Bleichenbacher98].
+//!
+//! [`probe_oracle`] sends the probes of the [ROBOT] scan to a decryption
+//! callback wrapping the service: ciphertexts of a valid encoding and of
+//! encodings broken in the ways implementations typically check
+//! separately. The probes are crafted with the public key only, by applying
+//! raw RSA to the encodings. If the callback can tell any of them apart from
+//! a ciphertext with a wrong block type, the service is an oracle.
+//!
+//! The callback should return everything an attacker could observe, for
+//! example the error of the service, or nothing at all if the service hides
+//! every failure. Timing is not taken into account.
+//!
+//! [Bleichenbacher98]: https://archiv.infsec.ethz.ch/education/fs08/secsem/bleichenbacher98.pdf
+//! [ROBOT]: https://robotattack.org/
+
+use alloc::vec::Vec;
+use crypto_bigint::BoxedUint;
+use rand_core::CryptoRngCore;
+
+use crate::algorithms::pad::uint_to_be_pad;
+use crate::algorithms::rsa::rsa_encrypt;
+use crate::errors::{Error, Result};
+use crate::key::RsaPublicKey;
+use crate::traits::PublicKeyParts;
+
+/// Minimum length of the padding string of a conformant encoding.
+const MIN_PS_LEN: usize = 8;
+
+/// Malformed (or not) encodings sent to the oracle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Probe {
+    /// Conformant encoding of a message of the expected length.
+    Valid,
+    /// Block type `0x17` instead of `0x02`, which any implementation must
+    /// reject. The responses to this probe are the baseline.
+    WrongBlockType,
+    /// No zero byte separating the padding from the message.
+    NoSeparator,
+    /// Separator in the middle of the message, which is then shorter than
+    /// expected.
+    MisplacedSeparator,
+    /// Separator right after 7 bytes of padding, one less than required.
+    ShortPadding,
+}
+
+impl Probe {
+    /// All probes, the baseline first.
+    pub const ALL: [Probe; 5] = [
+        Probe::WrongBlockType,
+        Probe::Valid,
+        Probe::NoSeparator,
+        Probe::MisplacedSeparator,
+        Probe::ShortPadding,
+    ];
+}
+
+/// Conclusion of [`probe_oracle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OracleVerdict {
+    /// All probes got the same response.
+    NotVulnerable,
+    /// Some probes got a different response than the baseline.
+    Oracle,
+    /// Repeated probes of the same kind got different responses, so no
+    /// conclusion can be drawn, e.g. because the responses include
+    /// varying data or the service is unreliable.
+    Inconsistent,
+}
+
+/// Responses to a probe.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeResult<O> {
+    /// The probe sent.
+    pub probe: Probe,
+    /// Responses of each round, in order.
+    pub responses: Vec<O>,
+}
+
+/// Report of [`probe_oracle`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OracleReport<O> {
+    /// Responses to each probe, in the order of [`Probe::ALL`].
+    pub results: Vec<ProbeResult<O>>,
+    /// Conclusion drawn from the responses.
+    pub verdict: OracleVerdict,
+}
+
+impl<O: PartialEq> OracleReport<O> {
+    /// Returns the probes whose first response differs from the baseline.
+    pub fn distinguishable(&self) -> impl Iterator<Item = Probe> + '_ {
+        let baseline = self.results.first().and_then(|r| r.responses.first());
+        self.results
+            .iter()
+            .filter(move |result| result.responses.first() != baseline)
+            .map(|result| result.probe)
+    }
+}
+
+/// Sends each [`Probe`] `rounds` times to `decrypt`, encrypted with
+/// `pub_key`, and compares the responses.
+///
+/// `message_len` is the length of the plaintexts the service expects, e.g.
+/// 48 for TLS premaster secrets. Padding and messages are drawn from `rng`,
+/// so every round uses fresh ciphertexts.
+///
+/// Fails with [`Error::InvalidArguments`] if `rounds` is zero or the message
+/// does not fit the key with the minimum padding.
+pub fn probe_oracle<R, O, F>(
+    rng: &mut R,
+    pub_key: &RsaPublicKey,
+    message_len: usize,
+    rounds: usize,
+    mut decrypt: F,
+) -> Result<OracleReport<O>>
+where
+    R: CryptoRngCore + ?Sized,
+    O: PartialEq,
+    F: FnMut(&[u8]) -> O,
+{
+    let k = pub_key.size();
+    // The misplaced separator needs a message of at least two bytes.
+    if rounds == 0 || message_len < 2 || message_len + MIN_PS_LEN + 3 > k {
+        return Err(Error::InvalidArguments);
+    }
+
+    let mut results = Vec::with_capacity(Probe::ALL.len());
+    for probe in Probe::ALL {
+        let mut responses = Vec::with_capacity(rounds);
+        for _ in 0..rounds {
+            let em = encode(rng, probe, message_len, k);
+            let c = rsa_encrypt(
+                pub_key,
+                &BoxedUint::from_be_slice(&em, pub_key.n_bits_precision())?,
+            )?;
+            responses.push(decrypt(&uint_to_be_pad(c, k)?));
+        }
+        results.push(ProbeResult { probe, responses });
+    }
+
+    let consistent = results
+        .iter()
+        .all(|result| result.responses.iter().all(|r| *r == result.responses[0]));
+    let baseline = &results[0].responses[0];
+    let verdict = if !consistent {
+        OracleVerdict::Inconsistent
+    } else if results
+        .iter()
+        .any(|result| result.responses[0] != *baseline)
+    {
+        OracleVerdict::Oracle
+    } else {
+        OracleVerdict::NotVulnerable
+    };
+    Ok(OracleReport { results, verdict })
+}
+
+/// Builds the `k`-byte encoding of `probe`, starting with a zero byte so
+/// that it is smaller than the modulus.
+fn encode<R: CryptoRngCore + ?Sized>(
+    rng: &mut R,
+    probe: Probe,
+    message_len: usize,
+    k: usize,
+) -> Vec<u8> {
+    let mut em = vec![0u8; k];
+    em[1] = 2;
+    let separator = k - message_len - 1;
+    for byte in &mut em[2..separator] {
+        *byte = nonzero_byte(rng);
+    }
+    rng.fill_bytes(&mut em[separator + 1..]);
+
+    match probe {
+        Probe::Valid => {}
+        Probe::WrongBlockType => em[1] = 0x17,
+        Probe::NoSeparator => {
+            for byte in &mut em[separator..] {
+                *byte = nonzero_byte(rng);
+            }
+        }
+        Probe::MisplacedSeparator => {
+            em[separator] = nonzero_byte(rng);
+            for byte in &mut em[separator + 1..] {
+                if *byte == 0 {
+                    *byte = nonzero_byte(rng);
+                }
+            }
+            em[separator + 1 + message_len / 2] = 0;
+        }
+        Probe::ShortPadding => {
+            for byte in &mut em[separator..] {
+                if *byte == 0 {
+                    *byte = nonzero_byte(rng);
+                }
+            }
+            em[2 + MIN_PS_LEN - 1] = 0;
+        }
+    }
+    em
+}
+
+fn nonzero_byte<R: CryptoRngCore + ?Sized>(rng: &mut R) -> u8 {
+    loop {
+        let mut byte = [0u8];
+        rng.fill_bytes(&mut byte);
+        if byte[0] != 0 {
+            return byte[0];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Pkcs1v15Encrypt, RsaPrivateKey};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_probe_oracle() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let pub_key = key.to_public_key();
+
+        // Explicit decryption errors make an oracle.
+        let report = probe_oracle(&mut rng, &pub_key, 48, 3, |ct| {
+            key.decrypt(Pkcs1v15Encrypt, ct).is_ok()
+        })
+        .unwrap();
+        assert_eq!(report.verdict, OracleVerdict::Oracle);
+        assert_eq!(
+            report.distinguishable().collect::<Vec<_>>(),
+            [Probe::Valid, Probe::MisplacedSeparator]
+        );
+
+        // Checking the length of the message too still leaves the valid
+        // probe distinguishable.
+        let report = probe_oracle(&mut rng, &pub_key, 48, 3, |ct| {
+            key.decrypt(Pkcs1v15Encrypt, ct)
+                .is_ok_and(|msg| msg.len() == 48)
+        })
+        .unwrap();
+        assert_eq!(report.distinguishable().collect::<Vec<_>>(), [Probe::Valid]);
+
+        let report = probe_oracle(&mut rng, &pub_key, 48, 2, |_| ()).unwrap();
+        assert_eq!(report.verdict, OracleVerdict::NotVulnerable);
+
+        let mut calls = 0;
+        let report = probe_oracle(&mut rng, &pub_key, 48, 2, |_| {
+            calls += 1;
+            calls
+        })
+        .unwrap();
+        assert_eq!(report.verdict, OracleVerdict::Inconsistent);
+
+        assert_eq!(
+            probe_oracle(&mut rng, &pub_key, 128 - 10, 1, |_| ()),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
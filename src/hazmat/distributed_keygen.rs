@@ -0,0 +1,754 @@
+//! ⚠️ Distributed (multi-party) RSA key generation.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! This module implements the honest-but-curious variant of the
+//! [Boneh–Franklin] protocol for jointly generating an RSA modulus
+//! `n = p·q` among `k ≥ 3` parties, such that no single party learns the
+//! factorization of `n`. At the end of the protocol every party holds an
+//! additive share of the private exponent `d`.
+//!
+//! The protocol is transport agnostic: every round produces messages which
+//! the caller must deliver to the other parties over authenticated and
+//! (for [`ModulusShare`]) confidential channels.
+//!
+//! 1. One party creates the public [`DkgParams`] and distributes them.
+//! 2. Every party creates a [`Party`] candidate and sends one
+//!    [`ModulusShare`] to each party (including itself).
+//! 3. Every party combines the received shares into a [`ModulusProduct`]
+//!    and broadcasts it.
+//! 4. Every party recovers the candidate modulus with
+//!    [`Party::combine_modulus`].
+//! 5. The parties repeatedly agree on a random [`biprimality_base`], exchange
+//!    [`BiprimalityShare`]s and run [`Party::check_biprimality`].
+//! 6. The parties exchange [`ExponentShare`]s and each derives its
+//!    [`PrivateExponentShare`] using [`Party::finish`].
+//! 7. Finally, the rounding error of the shares is fixed up with
+//!    [`exponent_correction`] and [`PrivateExponentShare::apply_correction`].
+//!
+//! Whenever a step returns [`Error::InvalidPrime`] the candidate has been
+//! rejected, and all parties must restart from step 2.
+//!
+//! Note that step 6 reveals `φ(n) mod e`, which leaks `log2(e)` bits about
+//! the factorization. This is inherent to the protocol and is only
+//! acceptable for small public exponents such as `65537`.
+//!
+//! [Boneh–Franklin]: https://crypto.stanford.edu/~dabo/pubs/papers/sharing.pdf
+
+use alloc::vec::Vec;
+use crypto_bigint::modular::{BoxedMontyForm, BoxedMontyParams};
+use crypto_bigint::{BoxedUint, NonZero, Odd, RandomMod};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::algorithms::arith::{
+    ceil_log2, inv_mod, jacobi_vartime, monty_params, mul_mod, pow2, pow_mod, reduce, resize,
+    to_monty,
+};
+use crate::algorithms::generate::generate_prime_with_rng;
+use crate::errors::{Error, Result};
+use crate::key::RsaPublicKey;
+use crate::traits::PublicKeyParts;
+
+/// Largest small prime used for trial division of candidate moduli.
+const TRIAL_DIVISION_LIMIT: u32 = 2048;
+
+/// Public parameters shared by all parties of a distributed key generation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DkgParams {
+    parties: usize,
+    modulus_bits: u32,
+    exp: BoxedUint,
+    field: BoxedUint,
+}
+
+impl DkgParams {
+    /// Create parameters for `parties` parties generating a `modulus_bits`
+    /// modulus with the public exponent `exp`.
+    ///
+    /// This generates the prime used for the secret sharing field, so it
+    /// should be run by a single party and the result distributed.
+    pub fn new<R: CryptoRngCore>(
+        rng: &mut R,
+        parties: usize,
+        modulus_bits: u32,
+        exp: BoxedUint,
+    ) -> Result<Self> {
+        Self::check(parties, modulus_bits, &exp)?;
+        let field = generate_prime_with_rng(rng, modulus_bits + 2);
+        Ok(Self {
+            parties,
+            modulus_bits,
+            exp,
+            field,
+        })
+    }
+
+    /// Reconstruct parameters received from another party.
+    pub fn from_parts(
+        parties: usize,
+        modulus_bits: u32,
+        exp: BoxedUint,
+        field: BoxedUint,
+    ) -> Result<Self> {
+        Self::check(parties, modulus_bits, &exp)?;
+        if field.bits() <= modulus_bits + 1 || bool::from(!field.is_odd()) {
+            return Err(Error::InvalidArguments);
+        }
+        Ok(Self {
+            parties,
+            modulus_bits,
+            exp,
+            field,
+        })
+    }
+
+    fn check(parties: usize, modulus_bits: u32, exp: &BoxedUint) -> Result<()> {
+        if parties < 3 {
+            return Err(Error::InvalidArguments);
+        }
+        if modulus_bits % 2 != 0 || modulus_bits / 2 < 16 + ceil_log2(parties) {
+            return Err(Error::InvalidArguments);
+        }
+        if bool::from(!exp.is_odd()) || exp.bits() < 2 {
+            return Err(Error::InvalidExponent);
+        }
+        Ok(())
+    }
+
+    /// Number of parties.
+    pub fn parties(&self) -> usize {
+        self.parties
+    }
+
+    /// Maximum number of colluding parties which learn nothing about the
+    /// factorization.
+    pub fn threshold(&self) -> usize {
+        (self.parties - 1) / 2
+    }
+
+    /// Size of the generated modulus in bits.
+    pub fn modulus_bits(&self) -> u32 {
+        self.modulus_bits
+    }
+
+    /// Public exponent.
+    pub fn exponent(&self) -> &BoxedUint {
+        &self.exp
+    }
+
+    /// Prime defining the field used for secret sharing.
+    pub fn field_prime(&self) -> &BoxedUint {
+        &self.field
+    }
+
+    fn precision(&self) -> u32 {
+        self.field.bits_precision()
+    }
+}
+
+/// Shares of a party's candidate prime summands, sent to one other party.
+///
+/// This message contains secret material and must be sent over a
+/// confidential channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModulusShare {
+    /// Index of the sending party.
+    pub from: usize,
+    /// Index of the receiving party.
+    pub to: usize,
+    p: BoxedUint,
+    q: BoxedUint,
+    zero: BoxedUint,
+}
+
+impl Drop for ModulusShare {
+    fn drop(&mut self) {
+        self.p.zeroize();
+        self.q.zeroize();
+        self.zero.zeroize();
+    }
+}
+
+/// A party's share of the candidate modulus, broadcast to all parties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModulusProduct {
+    /// Index of the sending party.
+    pub from: usize,
+    value: BoxedUint,
+}
+
+/// A party's contribution to one round of the biprimality test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BiprimalityShare {
+    /// Index of the sending party.
+    pub from: usize,
+    value: BoxedUint,
+}
+
+/// A party's share of `φ(n) mod e`, broadcast to all parties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExponentShare {
+    /// Index of the sending party.
+    pub from: usize,
+    value: BoxedUint,
+}
+
+/// A party's state during distributed key generation.
+#[derive(Debug, Clone)]
+pub struct Party {
+    params: DkgParams,
+    index: usize,
+    p: BoxedUint,
+    q: BoxedUint,
+    field_params: BoxedMontyParams,
+    n: Option<Odd<BoxedUint>>,
+}
+
+impl Drop for Party {
+    fn drop(&mut self) {
+        self.p.zeroize();
+        self.q.zeroize();
+    }
+}
+
+impl Party {
+    /// Pick a new candidate for the party with the given (1-based) `index`.
+    ///
+    /// The first party's summands are `≡ 3 (mod 4)` and have their two top
+    /// bits set, all other summands are `≡ 0 (mod 4)`, so that the primes are
+    /// `≡ 3 (mod 4)` and the modulus has exactly `modulus_bits` bits.
+    pub fn new<R: CryptoRngCore>(rng: &mut R, params: &DkgParams, index: usize) -> Result<Self> {
+        if index == 0 || index > params.parties {
+            return Err(Error::InvalidArguments);
+        }
+
+        let bits = params.precision();
+        let prime_bits = params.modulus_bits / 2;
+        let summand_bits = prime_bits - 3 - ceil_log2(params.parties);
+        let bound = NonZero::new(pow2(summand_bits - 2, bits)).expect("non zero");
+        let four = BoxedUint::from(4u64).widen(bits);
+
+        let mut summand = || {
+            let r = BoxedUint::random_mod(rng, &bound).wrapping_mul(&four);
+            if index == 1 {
+                r.wrapping_add(&BoxedUint::from(3u64).widen(bits))
+                    .wrapping_add(&pow2(prime_bits - 1, bits))
+                    .wrapping_add(&pow2(prime_bits - 2, bits))
+            } else {
+                r
+            }
+        };
+        let p = summand();
+        let q = summand();
+
+        Ok(Self {
+            params: params.clone(),
+            index,
+            p,
+            q,
+            field_params: monty_params(&params.field)?,
+            n: None,
+        })
+    }
+
+    /// Index of this party.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Secret share the candidate summands, producing one message per party.
+    pub fn modulus_shares<R: CryptoRngCore>(&self, rng: &mut R) -> Vec<ModulusShare> {
+        let t = self.params.threshold();
+        let zero = BoxedUint::zero_with_precision(self.params.precision());
+        let f = self.random_polynomial(rng, &self.p, t);
+        let g = self.random_polynomial(rng, &self.q, t);
+        let h = self.random_polynomial(rng, &zero, 2 * t);
+
+        (1..=self.params.parties)
+            .map(|to| ModulusShare {
+                from: self.index,
+                to,
+                p: self.evaluate(&f, to),
+                q: self.evaluate(&g, to),
+                zero: self.evaluate(&h, to),
+            })
+            .collect()
+    }
+
+    /// Combine the shares received from all parties into this party's share
+    /// of the candidate modulus.
+    pub fn receive_modulus_shares(&self, shares: &[ModulusShare]) -> Result<ModulusProduct> {
+        if shares.iter().any(|share| share.to != self.index) {
+            return Err(Error::InvalidArguments);
+        }
+        check_senders(self.params.parties, shares.iter().map(|share| share.from))?;
+
+        let zero = BoxedMontyForm::zero(self.field_params.clone());
+        let mut p = zero.clone();
+        let mut q = zero.clone();
+        let mut h = zero;
+        for share in shares {
+            p = p + to_monty(&share.p, &self.field_params);
+            q = q + to_monty(&share.q, &self.field_params);
+            h = h + to_monty(&share.zero, &self.field_params);
+        }
+
+        Ok(ModulusProduct {
+            from: self.index,
+            value: (p * q + h).retrieve(),
+        })
+    }
+
+    /// Recover the candidate modulus from the products broadcast by all
+    /// parties.
+    ///
+    /// Returns [`Error::InvalidPrime`] if the candidate is divisible by a
+    /// small prime.
+    pub fn combine_modulus(&mut self, products: &[ModulusProduct]) -> Result<RsaPublicKey> {
+        check_senders(
+            self.params.parties,
+            products.iter().map(|product| product.from),
+        )?;
+
+        let points: Vec<usize> = products.iter().map(|product| product.from).collect();
+        let mut n = BoxedMontyForm::zero(self.field_params.clone());
+        for (product, x) in products.iter().zip(points.iter()) {
+            let lambda = self.lagrange_coefficient(*x, &points)?;
+            n = n + lambda * to_monty(&product.value, &self.field_params);
+        }
+        let n = n.retrieve();
+
+        if n.bits() != self.params.modulus_bits {
+            return Err(Error::InvalidModulus);
+        }
+        let n = n.shorten(self.params.modulus_bits);
+        if small_primes().any(|prime| {
+            let prime = BoxedUint::from(u64::from(prime)).widen(n.bits_precision());
+            bool::from(
+                n.rem_vartime(&NonZero::new(prime).expect("non zero"))
+                    .is_zero(),
+            )
+        }) {
+            return Err(Error::InvalidPrime);
+        }
+
+        let n = Odd::new(n).into_option().ok_or(Error::InvalidModulus)?;
+        let public_key = RsaPublicKey::new(n.as_ref().clone(), self.params.exp.clone())?;
+        self.n = Some(n);
+        Ok(public_key)
+    }
+
+    /// Compute this party's contribution to the biprimality test for the
+    /// agreed upon base `g`.
+    pub fn biprimality_share(&self, g: &BoxedUint) -> Result<BiprimalityShare> {
+        let n = self.modulus()?;
+        let n_params = BoxedMontyParams::new(n.clone());
+        let four = NonZero::new(BoxedUint::from(4u64).widen(n.bits_precision())).expect("four");
+
+        let exponent = if self.index == 1 {
+            // (n + 1 - p_1 - q_1) / 4
+            self.phi_share(n)
+        } else {
+            // (p_i + q_i) / 4
+            self.summand_sum(n.bits_precision())
+        };
+
+        Ok(BiprimalityShare {
+            from: self.index,
+            value: pow_mod(g, &(exponent / four), &n_params),
+        })
+    }
+
+    /// Run one round of the biprimality test for the base `g`.
+    ///
+    /// Returns [`Error::InvalidPrime`] if `n` is not the product of two
+    /// primes. Each successful round halves the probability of accepting a
+    /// modulus which is not, so callers should run at least 40 rounds with
+    /// fresh bases.
+    pub fn check_biprimality(&self, g: &BoxedUint, shares: &[BiprimalityShare]) -> Result<()> {
+        let n = self.modulus()?;
+        check_senders(self.params.parties, shares.iter().map(|share| share.from))?;
+        if jacobi_vartime(g, n) != 1 {
+            return Err(Error::InvalidArguments);
+        }
+
+        let n_params = BoxedMontyParams::new(n.clone());
+        let mut first = None;
+        let mut rest = BoxedUint::one_with_precision(n.bits_precision());
+        for share in shares {
+            if share.from == 1 {
+                first = Some(reduce(&share.value, n.as_nz_ref()));
+            } else {
+                rest = mul_mod(&rest, &share.value, &n_params);
+            }
+        }
+
+        let first = first.ok_or(Error::InvalidArguments)?;
+        let minus_rest = n.as_ref().wrapping_sub(&rest);
+        if first == rest || first == minus_rest {
+            Ok(())
+        } else {
+            Err(Error::InvalidPrime)
+        }
+    }
+
+    /// Reveal this party's share of `φ(n) mod e`.
+    pub fn exponent_share(&self) -> Result<ExponentShare> {
+        let n = self.modulus()?;
+        let e = NonZero::new(self.params.exp.clone()).expect("odd exponent");
+        let share = if self.index == 1 {
+            self.phi_share(n)
+        } else {
+            self.summand_sum(n.bits_precision())
+        };
+
+        Ok(ExponentShare {
+            from: self.index,
+            value: reduce(&share, &e),
+        })
+    }
+
+    /// Derive this party's additive share of the private exponent.
+    ///
+    /// Returns [`Error::InvalidPrime`] if `e` is not coprime to `φ(n)`.
+    pub fn finish(&self, shares: &[ExponentShare]) -> Result<PrivateExponentShare> {
+        let n = self.modulus()?;
+        check_senders(self.params.parties, shares.iter().map(|share| share.from))?;
+
+        let e = &self.params.exp;
+        let e_params = monty_params(e)?;
+
+        // ψ = φ(n) mod e = φ_1 - Σ (p_i + q_i) mod e
+        let mut psi = BoxedMontyForm::zero(e_params.clone());
+        for share in shares {
+            let value = to_monty(&share.value, &e_params);
+            if share.from == 1 {
+                psi = psi + value;
+            } else {
+                psi = psi - value;
+            }
+        }
+
+        // ζ' = -ψ^-1 mod e, such that ζ'·φ(n) ≡ -1 (mod e)
+        let zeta = inv_mod(&psi.retrieve(), &e_params).ok_or(Error::InvalidPrime)?;
+        let zeta = (BoxedMontyForm::zero(e_params.clone()) - to_monty(&zeta, &e_params)).retrieve();
+
+        let bits = n.bits_precision() + e.bits_precision();
+        let e_wide = NonZero::new(e.widen(bits)).expect("odd exponent");
+        let zeta = zeta.widen(bits);
+
+        // d = (1 + ζ'·φ(n)) / e
+        //   = (1 + ζ'·φ_1) / e - Σ ζ'·(p_i + q_i) / e (up to rounding)
+        let (value, negative) = if self.index == 1 {
+            let phi = self.phi_share(n).widen(bits);
+            let num = zeta
+                .wrapping_mul(&phi)
+                .wrapping_add(&BoxedUint::one_with_precision(bits));
+            (num / e_wide, false)
+        } else {
+            let sum = self.summand_sum(n.bits_precision()).widen(bits);
+            (zeta.wrapping_mul(&sum) / e_wide, true)
+        };
+
+        Ok(PrivateExponentShare {
+            index: self.index,
+            n: n.as_ref().clone(),
+            value: resize(&value, n.bits_precision()),
+            negative,
+        })
+    }
+
+    fn modulus(&self) -> Result<&Odd<BoxedUint>> {
+        self.n.as_ref().ok_or(Error::InvalidArguments)
+    }
+
+    /// `n + 1 - p_1 - q_1` with the precision of `n`.
+    fn phi_share(&self, n: &Odd<BoxedUint>) -> BoxedUint {
+        let bits = n.bits_precision();
+        n.as_ref()
+            .wrapping_add(&BoxedUint::one_with_precision(bits))
+            .wrapping_sub(&self.summand_sum(bits))
+    }
+
+    /// `p_i + q_i` with the given precision.
+    fn summand_sum(&self, bits: u32) -> BoxedUint {
+        resize(&self.p, bits).wrapping_add(&resize(&self.q, bits))
+    }
+
+    fn random_polynomial<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+        constant: &BoxedUint,
+        degree: usize,
+    ) -> Vec<BoxedMontyForm> {
+        let field = NonZero::new(self.params.field.clone()).expect("prime");
+        let mut coefficients = Vec::with_capacity(degree + 1);
+        coefficients.push(to_monty(constant, &self.field_params));
+        for _ in 0..degree {
+            let c = BoxedUint::random_mod(rng, &field);
+            coefficients.push(to_monty(&c, &self.field_params));
+        }
+        coefficients
+    }
+
+    fn evaluate(&self, coefficients: &[BoxedMontyForm], x: usize) -> BoxedUint {
+        let x = to_monty(&BoxedUint::from(x as u64), &self.field_params);
+        let mut acc = BoxedMontyForm::zero(self.field_params.clone());
+        for c in coefficients.iter().rev() {
+            acc = acc * &x + c;
+        }
+        acc.retrieve()
+    }
+
+    /// Lagrange coefficient for interpolating at `0` from the given points.
+    fn lagrange_coefficient(&self, x: usize, points: &[usize]) -> Result<BoxedMontyForm> {
+        let xj = to_monty(&BoxedUint::from(x as u64), &self.field_params);
+        let mut num = BoxedMontyForm::one(self.field_params.clone());
+        let mut den = BoxedMontyForm::one(self.field_params.clone());
+        for &m in points.iter().filter(|&&m| m != x) {
+            let xm = to_monty(&BoxedUint::from(m as u64), &self.field_params);
+            num = num * &xm;
+            den = den * &(xm - &xj);
+        }
+        let den = den.invert().into_option().ok_or(Error::InvalidArguments)?;
+        Ok(num * den)
+    }
+}
+
+/// A party's additive share of the private exponent.
+///
+/// The shares sum up to `d`, with all shares except the first one being
+/// negative.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PrivateExponentShare {
+    index: usize,
+    n: BoxedUint,
+    value: BoxedUint,
+    negative: bool,
+}
+
+impl Drop for PrivateExponentShare {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl PrivateExponentShare {
+    /// Index of the party holding this share.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Compute this share's partial private operation `m^d_i mod n`.
+    pub fn partial(&self, m: &BoxedUint) -> Result<BoxedUint> {
+        let n_params = monty_params(&self.n)?;
+        let res = pow_mod(m, &self.value, &n_params);
+        if self.negative {
+            inv_mod(&res, &n_params).ok_or(Error::InvalidArguments)
+        } else {
+            Ok(res)
+        }
+    }
+
+    /// Apply the rounding correction computed by [`exponent_correction`].
+    ///
+    /// Only the share of the first party can be corrected.
+    pub fn apply_correction(&mut self, correction: u32) -> Result<()> {
+        if self.index != 1 {
+            return Err(Error::InvalidArguments);
+        }
+        let correction = BoxedUint::from(u64::from(correction)).widen(self.value.bits_precision());
+        self.value = self.value.wrapping_sub(&correction);
+        Ok(())
+    }
+}
+
+/// Multiply the partial private operations of all parties.
+pub fn combine_partials(public_key: &RsaPublicKey, partials: &[BoxedUint]) -> BoxedUint {
+    let n_params = public_key.n_params();
+    let mut res = BoxedUint::one_with_precision(public_key.n_bits_precision());
+    for partial in partials {
+        res = mul_mod(&res, partial, n_params);
+    }
+    res
+}
+
+/// Compute the rounding correction for the first party's share, from the
+/// partial private operations of all parties on the agreed test value `m`.
+///
+/// Fails with [`Error::FaultDetected`] if no correction turns the partials
+/// into the private operation on `m`, e.g. because one of them is wrong.
+pub fn exponent_correction(
+    public_key: &RsaPublicKey,
+    m: &BoxedUint,
+    partials: &[BoxedUint],
+) -> Result<u32> {
+    let n_params = public_key.n_params();
+    let m = reduce(m, public_key.n());
+    let m_inv = inv_mod(&m, n_params).ok_or(Error::InvalidArguments)?;
+
+    let mut candidate = combine_partials(public_key, partials);
+    for correction in 0..=partials.len() as u32 {
+        if pow_mod(&candidate, public_key.e(), n_params) == m {
+            return Ok(correction);
+        }
+        candidate = mul_mod(&candidate, &m_inv, n_params);
+    }
+
+    Err(Error::FaultDetected)
+}
+
+/// Pick a random base with Jacobi symbol `1` for the biprimality test.
+pub fn biprimality_base<R: CryptoRngCore>(rng: &mut R, public_key: &RsaPublicKey) -> BoxedUint {
+    let n = Odd::new(public_key.n().as_ref().clone()).expect("odd modulus");
+    loop {
+        let g = BoxedUint::random_mod(rng, public_key.n());
+        if jacobi_vartime(&g, &n) == 1 {
+            return g;
+        }
+    }
+}
+
+/// Check that messages were received from each party exactly once.
+fn check_senders(parties: usize, senders: impl Iterator<Item = usize>) -> Result<()> {
+    let mut seen = vec![false; parties];
+    for from in senders {
+        if from == 0 || from > parties || seen[from - 1] {
+            return Err(Error::InvalidArguments);
+        }
+        seen[from - 1] = true;
+    }
+    if seen.iter().all(|&s| s) {
+        Ok(())
+    } else {
+        Err(Error::InvalidArguments)
+    }
+}
+
+/// Odd primes below [`TRIAL_DIVISION_LIMIT`].
+fn small_primes() -> impl Iterator<Item = u32> {
+    let mut composite = vec![false; TRIAL_DIVISION_LIMIT as usize];
+    (3..TRIAL_DIVISION_LIMIT).step_by(2).filter(move |&i| {
+        if composite[i as usize] {
+            return false;
+        }
+        for multiple in (i * i..TRIAL_DIVISION_LIMIT).step_by(i as usize) {
+            composite[multiple as usize] = true;
+        }
+        true
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    fn exchange_modulus(
+        rng: &mut ChaCha8Rng,
+        params: &DkgParams,
+    ) -> Result<(Vec<Party>, RsaPublicKey)> {
+        let mut parties: Vec<Party> = (1..=params.parties())
+            .map(|i| Party::new(rng, params, i).unwrap())
+            .collect();
+
+        let mut inboxes: Vec<Vec<ModulusShare>> = vec![Vec::new(); parties.len()];
+        for party in &parties {
+            for share in party.modulus_shares(rng) {
+                inboxes[share.to - 1].push(share);
+            }
+        }
+        let products: Vec<ModulusProduct> = parties
+            .iter()
+            .zip(inboxes.iter())
+            .map(|(party, inbox)| party.receive_modulus_shares(inbox).unwrap())
+            .collect();
+
+        let mut public_key = None;
+        for party in parties.iter_mut() {
+            public_key = Some(party.combine_modulus(&products)?);
+        }
+        let public_key = public_key.unwrap();
+
+        for _ in 0..40 {
+            let g = biprimality_base(rng, &public_key);
+            let shares: Vec<BiprimalityShare> = parties
+                .iter()
+                .map(|party| party.biprimality_share(&g).unwrap())
+                .collect();
+            for party in &parties {
+                party.check_biprimality(&g, &shares)?;
+            }
+        }
+
+        Ok((parties, public_key))
+    }
+
+    #[test]
+    fn test_distributed_keygen() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let params = DkgParams::new(&mut rng, 3, 128, BoxedUint::from(65_537u64)).unwrap();
+
+        let (mut shares, public_key) = loop {
+            let (parties, public_key) = match exchange_modulus(&mut rng, &params) {
+                Ok(res) => res,
+                Err(Error::InvalidPrime) | Err(Error::InvalidModulus) => continue,
+                Err(err) => panic!("unexpected error: {err}"),
+            };
+
+            let exponent_shares: Vec<ExponentShare> = parties
+                .iter()
+                .map(|party| party.exponent_share().unwrap())
+                .collect();
+            match parties
+                .iter()
+                .map(|party| party.finish(&exponent_shares))
+                .collect::<Result<Vec<_>>>()
+            {
+                Ok(shares) => break (shares, public_key),
+                Err(Error::InvalidPrime) => continue,
+                Err(err) => panic!("unexpected error: {err}"),
+            }
+        };
+        assert_eq!(public_key.n().bits(), 128);
+
+        let test = BoxedUint::from(2u64);
+        let partials: Vec<BoxedUint> = shares.iter().map(|s| s.partial(&test).unwrap()).collect();
+        let correction = exponent_correction(&public_key, &test, &partials).unwrap();
+        shares[0].apply_correction(correction).unwrap();
+
+        let m = BoxedUint::from(0x1234_5678u64);
+        let partials: Vec<BoxedUint> = shares.iter().map(|s| s.partial(&m).unwrap()).collect();
+        let signature = combine_partials(&public_key, &partials);
+        let recovered = pow_mod(&signature, public_key.e(), public_key.n_params());
+        assert_eq!(recovered, m.widen(public_key.n_bits_precision()));
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let e = BoxedUint::from(65_537u64);
+        assert!(DkgParams::new(&mut rng, 2, 128, e.clone()).is_err());
+        assert!(DkgParams::new(&mut rng, 3, 127, e.clone()).is_err());
+        assert!(DkgParams::new(&mut rng, 3, 128, BoxedUint::from(4u64)).is_err());
+
+        let params = DkgParams::new(&mut rng, 3, 128, e).unwrap();
+        assert!(Party::new(&mut rng, &params, 0).is_err());
+        assert!(Party::new(&mut rng, &params, 4).is_err());
+    }
+
+    #[test]
+    fn test_small_primes() {
+        let primes: Vec<u32> = small_primes().take(6).collect();
+        assert_eq!(primes, [3, 5, 7, 11, 13, 17]);
+    }
+}
@@ -0,0 +1,237 @@
+//! ⚠️ Mediated (two-party) RSA private key operations.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! In mediated RSA ("mRSA") the private exponent `d` is split into a
+//! [`ClientKeyShare`] and a [`ServerKeyShare`], such that neither share on
+//! its own allows performing the private key operation. Every signature or
+//! decryption requires a round trip to the server, which allows revoking the
+//! key instantly by deleting the server's share.
+//!
+//! The protocol operates on raw message representatives, so padding must be
+//! applied by the caller before sending the request:
+//!
+//! 1. The client sends the padded message representative `m` to the server.
+//! 2. The server answers with [`ServerKeyShare::respond`].
+//! 3. The client recombines the response into the regular RSA result using
+//!    [`ClientKeyShare::finish`], which also verifies the result against the
+//!    public key.
+
+use crypto_bigint::modular::BoxedMontyParams;
+use crypto_bigint::{BoxedUint, RandomMod};
+use rand_core::CryptoRngCore;
+use zeroize::Zeroize;
+
+use crate::algorithms::arith::{mul_mod, pow_mod, reduce, resize};
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::traits::{PrivateKeyParts, PublicKeyParts};
+
+/// How the private exponent is split between client and server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareKind {
+    /// `d = d_client + d_server mod φ(n)`.
+    ///
+    /// The client and server exponentiations are independent of each other.
+    Additive,
+    /// `d = d_client · d_server mod φ(n)`.
+    ///
+    /// The client exponentiates the server's response.
+    Multiplicative,
+}
+
+/// The client's share of a split private key.
+#[derive(Debug, Clone)]
+pub struct ClientKeyShare {
+    public_key: RsaPublicKey,
+    kind: ShareKind,
+    d: BoxedUint,
+}
+
+/// The server's share of a split private key.
+#[derive(Debug, Clone)]
+pub struct ServerKeyShare {
+    public_key: RsaPublicKey,
+    d: BoxedUint,
+}
+
+impl Drop for ClientKeyShare {
+    fn drop(&mut self) {
+        self.d.zeroize();
+    }
+}
+
+impl Drop for ServerKeyShare {
+    fn drop(&mut self) {
+        self.d.zeroize();
+    }
+}
+
+/// Split the private exponent of `key` into a client and a server share.
+///
+/// The original key should be destroyed after the shares have been
+/// distributed.
+pub fn split_key<R: CryptoRngCore>(
+    rng: &mut R,
+    key: &RsaPrivateKey,
+    kind: ShareKind,
+) -> Result<(ClientKeyShare, ServerKeyShare)> {
+    let bits = key.n_bits_precision();
+    let phi = key.totient()?;
+    let d = reduce(key.d(), &phi);
+
+    let (client, server) = match kind {
+        ShareKind::Additive => {
+            let client = BoxedUint::random_mod(rng, &phi);
+            let server = d.sub_mod(&client, &phi);
+            (client, server)
+        }
+        ShareKind::Multiplicative => loop {
+            let client = BoxedUint::random_mod(rng, &phi);
+            if let Some(inv) = client.inv_mod(&phi).into_option() {
+                let server = reduce(&d.widen(2 * bits).wrapping_mul(&inv.widen(2 * bits)), &phi);
+                break (client, server);
+            }
+        },
+    };
+
+    let public_key = key.to_public_key();
+    Ok((
+        ClientKeyShare {
+            public_key: public_key.clone(),
+            kind,
+            d: client,
+        },
+        ServerKeyShare {
+            public_key,
+            d: server,
+        },
+    ))
+}
+
+impl ServerKeyShare {
+    /// Get the public key this share belongs to.
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+
+    /// Compute the server's partial result for the message representative
+    /// `m` sent by the client.
+    pub fn respond(&self, m: &BoxedUint) -> Result<BoxedUint> {
+        let m = check_input(&self.public_key, m)?;
+        Ok(pow_mod(&m, &self.d, self.public_key.n_params()))
+    }
+}
+
+impl ClientKeyShare {
+    /// Get the public key this share belongs to.
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+
+    /// Get how the private exponent was split.
+    pub fn kind(&self) -> ShareKind {
+        self.kind
+    }
+
+    /// Combine the server's `response` for the message representative `m`
+    /// into the result of the private key operation.
+    ///
+    /// Returns [`Error::Verification`] if the result does not match the
+    /// public key, e.g. because the server's response was wrong.
+    pub fn finish(&self, m: &BoxedUint, response: &BoxedUint) -> Result<BoxedUint> {
+        let m = check_input(&self.public_key, m)?;
+        let response = check_input(&self.public_key, response)?;
+        let n_params: &BoxedMontyParams = self.public_key.n_params();
+
+        let res = match self.kind {
+            ShareKind::Additive => mul_mod(&pow_mod(&m, &self.d, n_params), &response, n_params),
+            ShareKind::Multiplicative => pow_mod(&response, &self.d, n_params),
+        };
+
+        if pow_mod(&res, self.public_key.e(), n_params) != m {
+            return Err(Error::Verification);
+        }
+        Ok(res)
+    }
+}
+
+/// Check that `m < n` and return it with the precision of `n`.
+fn check_input(public_key: &RsaPublicKey, m: &BoxedUint) -> Result<BoxedUint> {
+    let n = public_key.n();
+    let bits = core::cmp::max(m.bits_precision(), n.bits_precision());
+    if resize(m, bits) >= resize(n.as_ref(), bits) {
+        return Err(Error::InvalidArguments);
+    }
+    Ok(resize(m, n.bits_precision()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hazmat::rsa_decrypt_and_check;
+    use hex_literal::hex;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    fn check_split(kind: ShareKind) {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let (client, server) = split_key(&mut rng, &key, kind).unwrap();
+        assert_eq!(client.kind(), kind);
+
+        let m = BoxedUint::from(0x1234_5678u64).widen(key.n_bits_precision());
+        let expected = rsa_decrypt_and_check(&key, Some(&mut rng), &m).unwrap();
+
+        let response = server.respond(&m).unwrap();
+        assert_eq!(client.finish(&m, &response).unwrap(), expected);
+
+        let bogus = BoxedUint::from(42u64);
+        assert_eq!(client.finish(&m, &bogus), Err(Error::Verification));
+    }
+
+    #[test]
+    fn test_additive() {
+        check_split(ShareKind::Additive);
+    }
+
+    #[test]
+    fn test_multiplicative() {
+        check_split(ShareKind::Multiplicative);
+    }
+
+    #[test]
+    fn test_additive_large_totient() {
+        // Primes just below 2^256, so that d + φ(n) overflows 512 bits.
+        let p = BoxedUint::from_be_slice(
+            &hex!("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff43"),
+            256,
+        )
+        .unwrap();
+        let q = BoxedUint::from_be_slice(
+            &hex!("fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe9b"),
+            256,
+        )
+        .unwrap();
+        let key = RsaPrivateKey::from_p_q(p, q, BoxedUint::from(65_537u64)).unwrap();
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        for _ in 0..8 {
+            let (client, server) = split_key(&mut rng, &key, ShareKind::Additive).unwrap();
+            let m = BoxedUint::from(0x1234_5678u64).widen(key.n_bits_precision());
+            let expected = rsa_decrypt_and_check(&key, Some(&mut rng), &m).unwrap();
+            let response = server.respond(&m).unwrap();
+            assert_eq!(client.finish(&m, &response).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_input_too_large() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let (_, server) = split_key(&mut rng, &key, ShareKind::Additive).unwrap();
+        assert_eq!(
+            server.respond(key.n().as_ref()),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! ⚠️ Rivest–Shamir–Tauman ring signatures.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! A [ring signature] proves that a message was signed by the owner of one of
+//! the keys in a ring, without revealing which one. The ring may be formed
+//! from arbitrary RSA public keys, which do not need to cooperate.
+//!
+//! Keys of different sizes are supported by extending each trapdoor
+//! permutation to a common domain of `max(|n_i|) + 160` bits. The symmetric
+//! permutation used by the combining function is a four round Feistel
+//! network keyed by the message digest, with MGF1 round functions.
+//!
+//! [ring signature]: https://people.csail.mit.edu/rivest/pubs/RST01.pdf
+
+use alloc::vec::Vec;
+use crypto_bigint::{BoxedUint, NonZero};
+use digest::{Digest, FixedOutputReset};
+use rand_core::CryptoRngCore;
+
+use crate::algorithms::arith::{pow2, resize};
+use crate::algorithms::mgf::mgf1_xor_digest;
+use crate::algorithms::rsa::{rsa_decrypt_and_check, rsa_encrypt};
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::traits::PublicKeyParts;
+
+/// Number of bits the common domain exceeds the largest modulus by.
+const DOMAIN_EXTENSION_BITS: u32 = 160;
+
+/// Number of Feistel rounds of the symmetric permutation.
+const FEISTEL_ROUNDS: u8 = 4;
+
+/// A ring signature: the glue value `v` and one value `x_i` per ring member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RingSignature {
+    glue: Vec<u8>,
+    values: Vec<Vec<u8>>,
+}
+
+impl RingSignature {
+    /// Create a signature from its parts, e.g. after deserialization.
+    pub fn from_parts(glue: Vec<u8>, values: Vec<Vec<u8>>) -> Self {
+        Self { glue, values }
+    }
+
+    /// The glue value `v`.
+    pub fn glue(&self) -> &[u8] {
+        &self.glue
+    }
+
+    /// The values `x_i`, in the order of the ring.
+    pub fn values(&self) -> &[Vec<u8>] {
+        &self.values
+    }
+}
+
+/// Sign `msg` on behalf of `ring`, which must contain the public key of
+/// `signer`.
+pub fn sign<D, R>(
+    rng: &mut R,
+    ring: &[RsaPublicKey],
+    signer: &RsaPrivateKey,
+    msg: &[u8],
+) -> Result<RingSignature>
+where
+    D: Digest + FixedOutputReset,
+    R: CryptoRngCore,
+{
+    let s = ring
+        .iter()
+        .position(|key| key.n() == signer.n() && key.e() == signer.e())
+        .ok_or(Error::InvalidArguments)?;
+    let domain = Domain::new(ring);
+    let key = D::digest(msg);
+
+    let mut glue = vec![0u8; domain.len];
+    rng.fill_bytes(&mut glue);
+
+    let mut values = vec![vec![0u8; domain.len]; ring.len()];
+    let mut images = vec![vec![0u8; domain.len]; ring.len()];
+    for i in (0..ring.len()).filter(|&i| i != s) {
+        rng.fill_bytes(&mut values[i]);
+        images[i] = domain.forward(&ring[i], &values[i])?;
+    }
+
+    // z_{i+1} = E(y_i ⊕ z_i), from z_0 = v up to z_s
+    let mut forward = glue.clone();
+    for image in &images[..s] {
+        xor(&mut forward, image);
+        feistel::<D>(&key, &mut forward, false);
+    }
+
+    // z_i = E^-1(z_{i+1}) ⊕ y_i, from z_r = v down to z_{s+1}
+    let mut backward = glue.clone();
+    for image in images[s + 1..].iter().rev() {
+        feistel::<D>(&key, &mut backward, true);
+        xor(&mut backward, image);
+    }
+
+    // close the ring: y_s = E^-1(z_{s+1}) ⊕ z_s
+    feistel::<D>(&key, &mut backward, true);
+    xor(&mut backward, &forward);
+    values[s] = domain.backward(rng, signer, &backward)?;
+
+    Ok(RingSignature { glue, values })
+}
+
+/// Verify that `sig` is a ring signature of `msg` by one of the keys in
+/// `ring`.
+pub fn verify<D>(ring: &[RsaPublicKey], msg: &[u8], sig: &RingSignature) -> Result<()>
+where
+    D: Digest + FixedOutputReset,
+{
+    let domain = Domain::new(ring);
+    if ring.is_empty()
+        || sig.values.len() != ring.len()
+        || sig.glue.len() != domain.len
+        || sig.values.iter().any(|x| x.len() != domain.len)
+    {
+        return Err(Error::Verification);
+    }
+
+    let key = D::digest(msg);
+    let mut z = sig.glue.clone();
+    for (public_key, x) in ring.iter().zip(sig.values.iter()) {
+        let image = domain.forward(public_key, x)?;
+        xor(&mut z, &image);
+        feistel::<D>(&key, &mut z, false);
+    }
+
+    if z == sig.glue {
+        Ok(())
+    } else {
+        Err(Error::Verification)
+    }
+}
+
+/// Common domain `[0, 2^b)` of the extended trapdoor permutations.
+struct Domain {
+    bits: u32,
+    len: usize,
+}
+
+impl Domain {
+    fn new(ring: &[RsaPublicKey]) -> Self {
+        let max = ring.iter().map(|key| key.n().bits()).max().unwrap_or(0);
+        // keep an even number of bytes for the Feistel network
+        let bits = (max + DOMAIN_EXTENSION_BITS).next_multiple_of(16);
+        Self {
+            bits,
+            len: (bits / 8) as usize,
+        }
+    }
+
+    /// Split `x = q·n + r`, returning `None` if `(q + 1)·n > 2^b`, in which
+    /// case the extended permutation is the identity.
+    fn split(&self, key: &RsaPublicKey, x: &BoxedUint) -> Option<(BoxedUint, BoxedUint)> {
+        let precision = self.bits + BoxedUint::one().bits_precision();
+        let x = resize(x, precision);
+        let n = NonZero::new(resize(key.n(), precision)).expect("non zero");
+        let (q, r) = x.div_rem(&n);
+
+        let top = q
+            .wrapping_add(&BoxedUint::one_with_precision(precision))
+            .wrapping_mul(&n);
+        if top > pow2(self.bits, precision) {
+            return None;
+        }
+        Some((q.wrapping_mul(&n), resize(&r, key.n_bits_precision())))
+    }
+
+    /// `g_i(x) = q·n + r^e mod n`.
+    fn forward(&self, key: &RsaPublicKey, x: &[u8]) -> Result<Vec<u8>> {
+        let x = BoxedUint::from_be_slice(x, self.bits).map_err(|_| Error::Verification)?;
+        let y = match self.split(key, &x) {
+            Some((base, r)) => {
+                let r = rsa_encrypt(key, &r)?;
+                base.wrapping_add(&resize(&r, base.bits_precision()))
+            }
+            None => resize(&x, self.bits + BoxedUint::one().bits_precision()),
+        };
+        Ok(self.encode(&y))
+    }
+
+    /// `g_i^-1(y) = q·n + r^d mod n`.
+    fn backward<R: CryptoRngCore>(
+        &self,
+        rng: &mut R,
+        key: &RsaPrivateKey,
+        y: &[u8],
+    ) -> Result<Vec<u8>> {
+        let public_key = key.to_public_key();
+        let y = BoxedUint::from_be_slice(y, self.bits)?;
+        let x = match self.split(&public_key, &y) {
+            Some((base, r)) => {
+                let r = rsa_decrypt_and_check(key, Some(rng), &r)?;
+                base.wrapping_add(&resize(&r, base.bits_precision()))
+            }
+            None => resize(&y, self.bits + BoxedUint::one().bits_precision()),
+        };
+        Ok(self.encode(&x))
+    }
+
+    fn encode(&self, x: &BoxedUint) -> Vec<u8> {
+        let bytes = x.to_be_bytes();
+        bytes[bytes.len() - self.len..].to_vec()
+    }
+}
+
+/// Keyed Feistel permutation of `block`, or its inverse.
+fn feistel<D>(key: &[u8], block: &mut [u8], inverse: bool)
+where
+    D: Digest + FixedOutputReset,
+{
+    let half = block.len() / 2;
+    let mut digest = D::new();
+    let mut seed = Vec::with_capacity(key.len() + 1 + half);
+
+    // even rounds mask the left half with F(right), odd rounds the right half
+    // with F(left); every round is an involution, so the inverse permutation
+    // applies them in reverse order
+    for i in 0..FEISTEL_ROUNDS {
+        let round = if inverse { FEISTEL_ROUNDS - 1 - i } else { i };
+        let (left, right) = block.split_at_mut(half);
+        let (input, output) = if round % 2 == 0 {
+            (&*right, left)
+        } else {
+            (&*left, right)
+        };
+
+        seed.clear();
+        seed.extend_from_slice(key);
+        seed.push(round);
+        seed.extend_from_slice(input);
+        mgf1_xor_digest(output, &mut digest, &seed);
+    }
+}
+
+fn xor(out: &mut [u8], rhs: &[u8]) {
+    for (a, b) in out.iter_mut().zip(rhs.iter()) {
+        *a ^= b;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::Sha256;
+
+    fn keys(rng: &mut ChaCha8Rng) -> Vec<RsaPrivateKey> {
+        [512, 768, 640]
+            .iter()
+            .map(|&bits| RsaPrivateKey::new(rng, bits).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_feistel_inverse() {
+        let mut block = *b"0123456789abcdef";
+        let original = block;
+        feistel::<Sha256>(b"key", &mut block, false);
+        assert_ne!(block, original);
+        feistel::<Sha256>(b"key", &mut block, true);
+        assert_eq!(block, original);
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let keys = keys(&mut rng);
+        let ring: Vec<RsaPublicKey> = keys.iter().map(|k| k.to_public_key()).collect();
+        let msg = b"ring signature";
+
+        for signer in &keys {
+            let sig = sign::<Sha256, _>(&mut rng, &ring, signer, msg).unwrap();
+            verify::<Sha256>(&ring, msg, &sig).unwrap();
+            assert_eq!(
+                verify::<Sha256>(&ring, b"other message", &sig),
+                Err(Error::Verification)
+            );
+            assert_eq!(
+                verify::<Sha256>(&ring[..2], msg, &sig),
+                Err(Error::Verification)
+            );
+        }
+    }
+
+    #[test]
+    fn test_signer_not_in_ring() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let keys = keys(&mut rng);
+        let ring: Vec<RsaPublicKey> = keys[..2].iter().map(|k| k.to_public_key()).collect();
+        assert_eq!(
+            sign::<Sha256, _>(&mut rng, &ring, &keys[2], b"msg"),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! ⚠️ SRA commutative encryption.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! In the Shamir–Rivest–Adleman ("mental poker") scheme all parties share a
+//! modulus whose factorization they all know, and each party picks an
+//! independent, secret exponent pair `(e, d)`. Encryption is then
+//! commutative:
+//!
+//! ```text
+//! E_a(E_b(m)) = E_b(E_a(m))
+//! ```
+//!
+//! which is the building block of protocols such as private set
+//! intersection, secure comparison and mental poker. Since the factorization
+//! of the modulus is public among the parties, both exponents of a
+//! [`SraKey`] must be kept secret.
+//!
+//! Like raw RSA, SRA encryption is deterministic and malleable, and
+//! preserves the Jacobi symbol of the message, so messages should be chosen
+//! (e.g. hashed into the group) with that in mind.
+
+use alloc::vec::Vec;
+use crypto_bigint::modular::BoxedMontyParams;
+use crypto_bigint::{BoxedUint, NonZero, Odd, RandomMod};
+use rand_core::CryptoRngCore;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::algorithms::arith::{pow_mod, resize};
+use crate::algorithms::generate::generate_prime_with_rng;
+use crate::algorithms::rsa::compute_modulus;
+use crate::errors::{Error, Result};
+
+/// The modulus shared by all parties of the SRA scheme.
+#[derive(Debug, Clone)]
+pub struct SraGroup {
+    primes: Vec<BoxedUint>,
+    n: Odd<BoxedUint>,
+    phi: NonZero<BoxedUint>,
+}
+
+impl SraGroup {
+    /// Generate a new shared modulus of `bit_size` bits from two primes.
+    pub fn new<R: CryptoRngCore>(rng: &mut R, bit_size: usize) -> Result<Self> {
+        if bit_size < 64 || bit_size % 2 != 0 {
+            return Err(Error::InvalidArguments);
+        }
+        loop {
+            let p = generate_prime_with_rng(rng, (bit_size / 2) as u32);
+            let q = generate_prime_with_rng(rng, (bit_size / 2) as u32);
+            if p != q {
+                return Self::from_primes(vec![p, q]);
+            }
+        }
+    }
+
+    /// Create the shared modulus from its (distinct, odd) prime factors.
+    pub fn from_primes(primes: Vec<BoxedUint>) -> Result<Self> {
+        if primes.is_empty() || primes.iter().any(|p| bool::from(!p.is_odd())) {
+            return Err(Error::InvalidPrime);
+        }
+        let n = compute_modulus(&primes)?;
+        let bits = n.bits_precision();
+
+        let one = BoxedUint::one_with_precision(bits);
+        let mut phi = one.clone();
+        for prime in &primes {
+            phi = phi.wrapping_mul(&resize(prime, bits).wrapping_sub(&one));
+        }
+        let phi = NonZero::new(phi).into_option().ok_or(Error::InvalidPrime)?;
+
+        Ok(Self { primes, n, phi })
+    }
+
+    /// The shared modulus.
+    pub fn modulus(&self) -> &BoxedUint {
+        &self.n
+    }
+
+    /// The prime factors of the shared modulus.
+    pub fn primes(&self) -> &[BoxedUint] {
+        &self.primes
+    }
+
+    /// Generate a fresh, secret exponent pair for one party.
+    pub fn generate_key<R: CryptoRngCore>(&self, rng: &mut R) -> SraKey {
+        let three = BoxedUint::from(3u64).widen(self.phi.bits_precision());
+        loop {
+            let e = BoxedUint::random_mod(rng, &self.phi);
+            if e < three {
+                continue;
+            }
+            if let Some(d) = e.inv_mod(&self.phi).into_option() {
+                return SraKey {
+                    n_params: BoxedMontyParams::new(self.n.clone()),
+                    n: self.n.as_ref().clone(),
+                    e,
+                    d,
+                };
+            }
+        }
+    }
+}
+
+/// A party's secret exponent pair for SRA encryption.
+#[derive(Debug, Clone)]
+pub struct SraKey {
+    n: BoxedUint,
+    n_params: BoxedMontyParams,
+    e: BoxedUint,
+    d: BoxedUint,
+}
+
+impl Drop for SraKey {
+    fn drop(&mut self) {
+        self.e.zeroize();
+        self.d.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SraKey {}
+
+impl SraKey {
+    /// Encrypt `m`, which must be smaller than the modulus: `m^e mod n`.
+    pub fn encrypt(&self, m: &BoxedUint) -> Result<BoxedUint> {
+        let m = self.check_input(m)?;
+        Ok(pow_mod(&m, &self.e, &self.n_params))
+    }
+
+    /// Decrypt `c`, removing this party's layer of encryption: `c^d mod n`.
+    pub fn decrypt(&self, c: &BoxedUint) -> Result<BoxedUint> {
+        let c = self.check_input(c)?;
+        Ok(pow_mod(&c, &self.d, &self.n_params))
+    }
+
+    fn check_input(&self, m: &BoxedUint) -> Result<BoxedUint> {
+        let bits = core::cmp::max(m.bits_precision(), self.n.bits_precision());
+        if resize(m, bits) >= resize(&self.n, bits) {
+            return Err(Error::MessageTooLong {
+                max_len: (self.n.bits() as usize - 1) / 8,
+            });
+        }
+        Ok(resize(m, self.n.bits_precision()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_commutative() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let group = SraGroup::new(&mut rng, 512).unwrap();
+        let alice = group.generate_key(&mut rng);
+        let bob = group.generate_key(&mut rng);
+
+        let m = BoxedUint::from(0x1234_5678u64);
+        let ab = bob.encrypt(&alice.encrypt(&m).unwrap()).unwrap();
+        let ba = alice.encrypt(&bob.encrypt(&m).unwrap()).unwrap();
+        assert_eq!(ab, ba);
+        assert_ne!(ab, resize(&m, ab.bits_precision()));
+
+        // layers can be removed in any order
+        let m_a = bob.decrypt(&ab).unwrap();
+        assert_eq!(
+            alice.decrypt(&m_a).unwrap(),
+            resize(&m, ab.bits_precision())
+        );
+        let m_b = alice.decrypt(&ab).unwrap();
+        assert_eq!(bob.decrypt(&m_b).unwrap(), resize(&m, ab.bits_precision()));
+    }
+
+    #[test]
+    fn test_message_too_long() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let group = SraGroup::new(&mut rng, 512).unwrap();
+        let key = group.generate_key(&mut rng);
+        assert_eq!(
+            key.encrypt(group.modulus()),
+            Err(Error::MessageTooLong { max_len: 63 })
+        );
+    }
+}
@@ -0,0 +1,174 @@
+//! ⚠️ Rivest–Shamir–Wagner time-lock puzzles.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! A [time-lock puzzle] hides a secret such that recovering it requires
+//! computing `x^(2^t) mod n` with `t` sequential squarings. The holder of the
+//! private key can create the puzzle cheaply by reducing the exponent modulo
+//! `φ(n)`, everybody else has to perform the squarings one after the other.
+//!
+//! The private key must be destroyed once the puzzle has been created.
+//!
+//! [time-lock puzzle]: https://people.csail.mit.edu/rivest/pubs/RSW96.pdf
+
+use crypto_bigint::{BoxedUint, Limb, NonZero, RandomMod};
+use rand_core::CryptoRngCore;
+
+use crate::algorithms::arith::{pow2_mod_vartime, pow_mod, resize, square_repeatedly, to_monty};
+use crate::errors::{Error, Result};
+use crate::key::RsaPrivateKey;
+use crate::traits::PublicKeyParts;
+
+/// A time-lock puzzle: the base `x` and number of squarings `t`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeLockPuzzle {
+    base: BoxedUint,
+    squarings: u64,
+}
+
+impl TimeLockPuzzle {
+    /// Create a puzzle from its base and number of squarings.
+    pub fn new(base: BoxedUint, squarings: u64) -> Self {
+        Self { base, squarings }
+    }
+
+    /// Create a puzzle with a random base modulo the key's modulus.
+    pub fn random<R: CryptoRngCore>(
+        rng: &mut R,
+        key: &impl PublicKeyParts,
+        squarings: u64,
+    ) -> Self {
+        let two = BoxedUint::from(2u64).widen(key.n_bits_precision());
+        let range = NonZero::new(key.n().wrapping_sub(&two)).expect("n > 2");
+        // base in [2, n)
+        let base = BoxedUint::random_mod(rng, &range).wrapping_add(&two);
+        Self::new(base, squarings)
+    }
+
+    /// The base `x` of the puzzle.
+    pub fn base(&self) -> &BoxedUint {
+        &self.base
+    }
+
+    /// The number of sequential squarings `t` needed to solve the puzzle.
+    pub fn squarings(&self) -> u64 {
+        self.squarings
+    }
+
+    /// Solve the puzzle by computing `x^(2^t) mod n` with `t` sequential
+    /// squarings.
+    pub fn solve(&self, key: &impl PublicKeyParts) -> BoxedUint {
+        square_repeatedly(&self.base, self.squarings, key.n_params())
+    }
+
+    /// Solve the puzzle using the trapdoor `φ(n)`, which only requires a
+    /// single modular exponentiation.
+    ///
+    /// The reduction of the exponent is not constant time with respect to
+    /// `φ(n)`.
+    pub fn solve_with_key(&self, key: &RsaPrivateKey) -> Result<BoxedUint> {
+        let phi = key.totient()?;
+        let mut exp = pow2_mod_vartime(self.squarings, &phi);
+        if u64::from(phi.bits()) <= self.squarings {
+            // keep the exponent at least φ(n) so that bases which are not
+            // coprime to `n` are handled correctly as well, with an extra
+            // limb as the sum may not fit the precision of `n`
+            let bits = phi.bits_precision() + Limb::BITS;
+            exp = exp.widen(bits).wrapping_add(&phi.widen(bits));
+        }
+        Ok(pow_mod(&self.base, &exp, key.n_params()))
+    }
+
+    /// Lock `secret` with this puzzle: `secret + x^(2^t) mod n`.
+    pub fn lock(&self, key: &RsaPrivateKey, secret: &BoxedUint) -> Result<BoxedUint> {
+        let secret = check_input(key, secret)?;
+        let solution = self.solve_with_key(key)?;
+        let n_params = key.n_params();
+        Ok((to_monty(&secret, n_params) + to_monty(&solution, n_params)).retrieve())
+    }
+
+    /// Unlock a secret locked with [`TimeLockPuzzle::lock`], by solving the
+    /// puzzle.
+    pub fn unlock(&self, key: &impl PublicKeyParts, locked: &BoxedUint) -> Result<BoxedUint> {
+        let locked = check_input(key, locked)?;
+        let solution = self.solve(key);
+        let n_params = key.n_params();
+        Ok((to_monty(&locked, n_params) - to_monty(&solution, n_params)).retrieve())
+    }
+}
+
+/// Check that `m < n` and return it with the precision of `n`.
+fn check_input(key: &impl PublicKeyParts, m: &BoxedUint) -> Result<BoxedUint> {
+    let n = key.n();
+    let bits = core::cmp::max(m.bits_precision(), n.bits_precision());
+    if resize(m, bits) >= resize(n.as_ref(), bits) {
+        return Err(Error::MessageTooLong {
+            max_len: (n.bits() as usize - 1) / 8,
+        });
+    }
+    Ok(resize(m, n.bits_precision()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_solve_with_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        for t in [0, 1, 100, 511, 512, 1000] {
+            let puzzle = TimeLockPuzzle::random(&mut rng, &key, t);
+            assert_eq!(puzzle.solve_with_key(&key).unwrap(), puzzle.solve(&key));
+        }
+    }
+
+    #[test]
+    fn test_solve_with_key_large_totient() {
+        // Primes just below 2^256, so that 2^t mod φ(n) + φ(n) overflows
+        // 512 bits.
+        let p = BoxedUint::from_be_slice(
+            &hex!("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff43"),
+            256,
+        )
+        .unwrap();
+        let q = BoxedUint::from_be_slice(
+            &hex!("fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe9b"),
+            256,
+        )
+        .unwrap();
+        let key = RsaPrivateKey::from_p_q(p.clone(), q, BoxedUint::from(65_537u64)).unwrap();
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        for t in [512, 513, 1000, 4096] {
+            let puzzle = TimeLockPuzzle::random(&mut rng, &key, t);
+            assert_eq!(puzzle.solve_with_key(&key).unwrap(), puzzle.solve(&key));
+        }
+
+        // a base which is not coprime to `n`
+        let puzzle = TimeLockPuzzle::new(p.widen(key.n_bits_precision()), 1000);
+        assert_eq!(puzzle.solve_with_key(&key).unwrap(), puzzle.solve(&key));
+    }
+
+    #[test]
+    fn test_lock_unlock() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let puzzle = TimeLockPuzzle::random(&mut rng, &key, 1000);
+
+        let secret = BoxedUint::from(0x1234_5678u64);
+        let locked = puzzle.lock(&key, &secret).unwrap();
+        assert_ne!(locked, secret.widen(key.n_bits_precision()));
+
+        let public_key = key.to_public_key();
+        let unlocked = puzzle.unlock(&public_key, &locked).unwrap();
+        assert_eq!(unlocked, secret.widen(key.n_bits_precision()));
+
+        assert_eq!(
+            puzzle.lock(&key, key.n().as_ref()),
+            Err(Error::MessageTooLong { max_len: 63 })
+        );
+    }
+}
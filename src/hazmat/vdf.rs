@@ -0,0 +1,290 @@
+//! ⚠️ Verifiable delay functions over RSA groups.
+//!
+//! # ☢️️ WARNING: HAZARDOUS API ☢️
+//!
+//! A VDF evaluates `y = x^(2^t) mod n` using `t` sequential squarings, and
+//! produces a proof which allows checking the result much faster. Both
+//! [Wesolowski] and [Pietrzak] proofs are supported.
+//!
+//! The modulus must be generated such that nobody knows its factorization
+//! (e.g. by a trusted setup that destroys the private key, or using
+//! [`distributed_keygen`][super::distributed_keygen]), otherwise the delay can
+//! be bypassed using `φ(n)`. Soundness additionally relies on the hardness of
+//! finding elements of low order in `Z/nZ`, the usual assumption for RSA
+//! based VDFs.
+//!
+//! [Wesolowski]: https://eprint.iacr.org/2018/623
+//! [Pietrzak]: https://eprint.iacr.org/2018/627
+
+use alloc::vec::Vec;
+use crypto_bigint::modular::BoxedMontyForm;
+use crypto_bigint::BoxedUint;
+use digest::Digest;
+
+use crate::algorithms::arith::{
+    is_prime_vartime, monty_params, mul_mod, pow_mod, reduce, resize, square_repeatedly, to_monty,
+};
+use crate::errors::{Error, Result};
+use crate::traits::PublicKeyParts;
+
+/// Size in bits of the Fiat–Shamir challenges.
+const CHALLENGE_BITS: u32 = u128::BITS;
+
+/// Wesolowski VDF output together with its proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WesolowskiProof {
+    output: BoxedUint,
+    proof: BoxedUint,
+}
+
+impl WesolowskiProof {
+    /// Evaluate the VDF on `x` with `t` sequential squarings modulo the key's
+    /// modulus, and compute the proof.
+    ///
+    /// This performs about `2·t` modular squarings.
+    pub fn eval<D: Digest>(key: &impl PublicKeyParts, x: &BoxedUint, t: u64) -> Result<Self> {
+        let n_params = key.n_params();
+        let x = reduce(x, key.n());
+        let output = square_repeatedly(&x, t, n_params);
+
+        let l = hash_to_prime::<D>(&x, &output, key.n_bits_precision());
+
+        // π = x^floor(2^t / l), computed by long division of 2^t by l
+        let x_monty = to_monty(&x, n_params);
+        let mut r = 1u128;
+        let mut proof = BoxedMontyForm::one(n_params.clone());
+        for _ in 0..t {
+            proof = proof.square();
+            r <<= 1;
+            if r >= l {
+                proof = proof * &x_monty;
+                r -= l;
+            }
+        }
+
+        Ok(Self {
+            output,
+            proof: proof.retrieve(),
+        })
+    }
+
+    /// Create a proof from its parts, e.g. after deserialization.
+    pub fn from_parts(output: BoxedUint, proof: BoxedUint) -> Self {
+        Self { output, proof }
+    }
+
+    /// The VDF output `y = x^(2^t) mod n`.
+    pub fn output(&self) -> &BoxedUint {
+        &self.output
+    }
+
+    /// The proof `π`.
+    pub fn proof(&self) -> &BoxedUint {
+        &self.proof
+    }
+
+    /// Verify that the output is `x^(2^t) mod n`.
+    pub fn verify<D: Digest>(
+        &self,
+        key: &impl PublicKeyParts,
+        x: &BoxedUint,
+        t: u64,
+    ) -> Result<()> {
+        let n_params = key.n_params();
+        let bits = key.n_bits_precision();
+        let x = reduce(x, key.n());
+        let output = reduce(&self.output, key.n());
+
+        let l = BoxedUint::from(hash_to_prime::<D>(&x, &output, bits));
+        let l_params = monty_params(&l)?;
+        let r = pow_mod(&BoxedUint::from(2u64), &BoxedUint::from(t), &l_params);
+
+        let lhs = mul_mod(
+            &pow_mod(&self.proof, &l, n_params),
+            &pow_mod(&x, &r, n_params),
+            n_params,
+        );
+        if lhs == output {
+            Ok(())
+        } else {
+            Err(Error::Verification)
+        }
+    }
+}
+
+/// Pietrzak VDF output together with its proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PietrzakProof {
+    output: BoxedUint,
+    proof: Vec<BoxedUint>,
+}
+
+impl PietrzakProof {
+    /// Evaluate the VDF on `x` with `t` sequential squarings modulo the key's
+    /// modulus, and compute the proof.
+    ///
+    /// The proof consists of `log2(t)` group elements. Computing it performs
+    /// about `2·t` modular squarings.
+    pub fn eval<D: Digest>(key: &impl PublicKeyParts, x: &BoxedUint, t: u64) -> Result<Self> {
+        let n_params = key.n_params();
+        let mut x = reduce(x, key.n());
+        let output = square_repeatedly(&x, t, n_params);
+
+        let mut y = output.clone();
+        let mut t = t;
+        let mut proof = Vec::new();
+        while t > 1 {
+            if t % 2 == 1 {
+                // x^(2^t) = y  <=>  x^(2^(t+1)) = y^2
+                y = mul_mod(&y, &y, n_params);
+                t += 1;
+            }
+            t /= 2;
+            let mu = square_repeatedly(&x, t, n_params);
+            let r = challenge::<D>(&[&x, &y, &mu], key.n_bits_precision());
+            x = mul_mod(&pow_mod(&x, &r, n_params), &mu, n_params);
+            y = mul_mod(&pow_mod(&mu, &r, n_params), &y, n_params);
+            proof.push(mu);
+        }
+
+        Ok(Self { output, proof })
+    }
+
+    /// Create a proof from its parts, e.g. after deserialization.
+    pub fn from_parts(output: BoxedUint, proof: Vec<BoxedUint>) -> Self {
+        Self { output, proof }
+    }
+
+    /// The VDF output `y = x^(2^t) mod n`.
+    pub fn output(&self) -> &BoxedUint {
+        &self.output
+    }
+
+    /// The intermediate values `μ` of the proof.
+    pub fn proof(&self) -> &[BoxedUint] {
+        &self.proof
+    }
+
+    /// Verify that the output is `x^(2^t) mod n`.
+    pub fn verify<D: Digest>(
+        &self,
+        key: &impl PublicKeyParts,
+        x: &BoxedUint,
+        t: u64,
+    ) -> Result<()> {
+        let n_params = key.n_params();
+        let mut x = reduce(x, key.n());
+        let mut y = reduce(&self.output, key.n());
+        let mut t = t;
+        let mut proof = self.proof.iter();
+
+        while t > 1 {
+            if t % 2 == 1 {
+                y = mul_mod(&y, &y, n_params);
+                t += 1;
+            }
+            t /= 2;
+            let mu = reduce(proof.next().ok_or(Error::Verification)?, key.n());
+            let r = challenge::<D>(&[&x, &y, &mu], key.n_bits_precision());
+            x = mul_mod(&pow_mod(&x, &r, n_params), &mu, n_params);
+            y = mul_mod(&pow_mod(&mu, &r, n_params), &y, n_params);
+        }
+
+        if proof.next().is_some() {
+            return Err(Error::Verification);
+        }
+        if square_repeatedly(&x, t, n_params) == y {
+            Ok(())
+        } else {
+            Err(Error::Verification)
+        }
+    }
+}
+
+/// Hash the given group elements, using their fixed size encoding.
+fn hash_elements<D: Digest>(elements: &[&BoxedUint], bits: u32, counter: u64) -> digest::Output<D> {
+    let mut hasher = D::new();
+    for element in elements {
+        hasher.update(resize(element, bits).to_be_bytes());
+    }
+    hasher.update(counter.to_be_bytes());
+    hasher.finalize()
+}
+
+/// Interpret the leading bytes of a digest as a challenge.
+fn digest_to_u128(digest: &[u8]) -> u128 {
+    let mut bytes = [0u8; (CHALLENGE_BITS / 8) as usize];
+    let n = core::cmp::min(bytes.len(), digest.len());
+    bytes[..n].copy_from_slice(&digest[..n]);
+    u128::from_be_bytes(bytes)
+}
+
+/// Fiat–Shamir challenge for the Pietrzak halving protocol.
+fn challenge<D: Digest>(elements: &[&BoxedUint], bits: u32) -> BoxedUint {
+    BoxedUint::from(digest_to_u128(&hash_elements::<D>(elements, bits, 0)))
+}
+
+/// Map `(x, y)` to a prime of `CHALLENGE_BITS - 1` bits.
+///
+/// The top bit is left clear, so that twice the remainder of the long
+/// division in [`WesolowskiProof::eval`] does not overflow.
+fn hash_to_prime<D: Digest>(x: &BoxedUint, y: &BoxedUint, bits: u32) -> u128 {
+    let mut counter = 0u64;
+    loop {
+        let digest = hash_elements::<D>(&[x, y], bits, counter);
+        let candidate = (digest_to_u128(&digest) >> 1) | (1 << (CHALLENGE_BITS - 2)) | 1;
+        if is_prime_vartime(&BoxedUint::from(candidate)) {
+            return candidate;
+        }
+        counter += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RsaPrivateKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::Sha256;
+
+    fn key() -> crate::RsaPublicKey {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        RsaPrivateKey::new(&mut rng, 512).unwrap().to_public_key()
+    }
+
+    #[test]
+    fn test_wesolowski() {
+        let key = key();
+        let x = BoxedUint::from(0xdead_beefu64);
+        for t in [1, 2, 17, 100] {
+            let proof = WesolowskiProof::eval::<Sha256>(&key, &x, t).unwrap();
+            assert_eq!(proof.output(), &square_repeatedly(&x, t, key.n_params()));
+            proof.verify::<Sha256>(&key, &x, t).unwrap();
+            assert!(proof.verify::<Sha256>(&key, &x, t + 1).is_err());
+
+            let forged = WesolowskiProof::from_parts(
+                mul_mod(proof.output(), &BoxedUint::from(2u64), key.n_params()),
+                proof.proof().clone(),
+            );
+            assert!(forged.verify::<Sha256>(&key, &x, t).is_err());
+        }
+    }
+
+    #[test]
+    fn test_pietrzak() {
+        let key = key();
+        let x = BoxedUint::from(0xdead_beefu64);
+        for t in [1, 2, 17, 100] {
+            let proof = PietrzakProof::eval::<Sha256>(&key, &x, t).unwrap();
+            assert_eq!(proof.output(), &square_repeatedly(&x, t, key.n_params()));
+            proof.verify::<Sha256>(&key, &x, t).unwrap();
+            assert!(proof.verify::<Sha256>(&key, &x, t + 1).is_err());
+
+            let forged = PietrzakProof::from_parts(
+                mul_mod(proof.output(), &BoxedUint::from(2u64), key.n_params()),
+                proof.proof().to_vec(),
+            );
+            assert!(forged.verify::<Sha256>(&key, &x, t).is_err());
+        }
+    }
+}
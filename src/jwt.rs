@@ -0,0 +1,353 @@
+//! JSON Web Signatures ([RFC7515]) and Tokens ([RFC7519]) with the `RS*` and
+//! `PS*` algorithms of [RFC7518 § 3].
+//!
+//! The signing and verifying keys of [`pkcs1v15`](crate::pkcs1v15) and
+//! [`pss`](crate::pss) gain `sign_jws`, `verify_jws`, `sign_jwt` and
+//! `verify_jwt` methods when the digest implements [`JwtDigest`], i.e. for
+//! SHA-256, SHA-384 and SHA-512:
+//!
+//! | Digest   | PKCS#1 v1.5 | PSS     |
+//! |----------|-------------|---------|
+//! | SHA-256  | `RS256`     | `PS256` |
+//! | SHA-384  | `RS384`     | `PS384` |
+//! | SHA-512  | `RS512`     | `PS512` |
+//!
+//! They build and check the compact serialization, i.e. the base64url
+//! encoded header, payload and signature joined by dots, and the `alg`
+//! header. JWS payloads are arbitrary bytes, and may be left out of the
+//! serialization, see [`JwsSerialization::Detached`]. JWT claims are passed
+//! and returned as JSON, and are not interpreted: checking `exp`, `nbf`,
+//! `aud` and the like is up to the caller. `PS*` tokens must be
+//! signed and verified with keys using a salt as long as the digest, which is
+//! the default.
+//!
+//! ```
+//! # fn main() -> rsa::Result<()> {
+//! use rsa::pkcs1v15::SigningKey;
+//! use rsa::sha2::Sha256;
+//! use rsa::signature::Keypair;
+//! use serde_json::{json, Map};
+//!
+//! # let mut rng = rand::thread_rng();
+//! # let private_key = rsa::RsaPrivateKey::new(&mut rng, 1024)?;
+//! let signing_key = SigningKey::<Sha256>::new(private_key);
+//! let mut header = Map::new();
+//! header.insert("kid".into(), json!("2024-01"));
+//! let token = signing_key.sign_jwt(r#"{"sub":"alice"}"#, &header)?;
+//!
+//! let claims = signing_key.verifying_key().verify_jwt(&token)?;
+//! assert_eq!(claims["sub"], "alice");
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [RFC7515]: https://datatracker.ietf.org/doc/html/rfc7515
+//! [RFC7519]: https://datatracker.ietf.org/doc/html/rfc7519
+//! [RFC7518 § 3]: https://datatracker.ietf.org/doc/html/rfc7518#section-3
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use base64ct::{Base64UrlUnpadded, Encoding};
+use crypto_bigint::BoxedUint;
+use digest::{Digest, FixedOutputReset};
+use serde_json::{Map, Value};
+use sha2::{Sha256, Sha384, Sha512};
+
+use crate::errors::{Error, Result};
+
+/// Digests with a JWS algorithm name, for `RSASSA-PKCS1-v1_5` and
+/// `RSASSA-PSS` signatures.
+pub trait JwtDigest: Digest + FixedOutputReset {
+    /// `alg` of `RSASSA-PKCS1-v1_5` signatures, e.g. `"RS256"`.
+    const PKCS1V15_ALG: &'static str;
+    /// `alg` of `RSASSA-PSS` signatures, e.g. `"PS256"`.
+    const PSS_ALG: &'static str;
+}
+
+impl JwtDigest for Sha256 {
+    const PKCS1V15_ALG: &'static str = "RS256";
+    const PSS_ALG: &'static str = "PS256";
+}
+
+impl JwtDigest for Sha384 {
+    const PKCS1V15_ALG: &'static str = "RS384";
+    const PSS_ALG: &'static str = "PS384";
+}
+
+impl JwtDigest for Sha512 {
+    const PKCS1V15_ALG: &'static str = "RS512";
+    const PSS_ALG: &'static str = "PS512";
+}
+
+/// Serialization of a JWS produced by the `sign_jws` methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwsSerialization {
+    /// Encoded header, payload and signature joined by dots.
+    Compact,
+    /// Compact serialization with the payload left out, i.e.
+    /// `header..signature`, as described in [RFC7515 Appendix F]. The
+    /// payload is conveyed separately and must be passed back to the
+    /// `verify_jws_detached` methods.
+    ///
+    /// [RFC7515 Appendix F]: https://datatracker.ietf.org/doc/html/rfc7515#appendix-F
+    Detached,
+}
+
+/// Builds the signing input of a JWS, i.e. its encoded header and payload
+/// joined by a dot.
+///
+/// The header has `alg`, and `typ` if given, replaced or complemented by
+/// `header_overrides`, which must not set `alg`.
+pub(crate) fn signing_input(
+    alg: &str,
+    typ: Option<&str>,
+    header_overrides: &Map<String, Value>,
+    payload: &[u8],
+) -> Result<String> {
+    if header_overrides.contains_key("alg") {
+        return Err(Error::InvalidArguments);
+    }
+
+    let mut header = Map::new();
+    header.insert("alg".into(), alg.into());
+    if let Some(typ) = typ {
+        header.insert("typ".into(), typ.into());
+    }
+    header.extend(header_overrides.clone());
+    let header = serde_json::to_string(&header).map_err(|_| Error::InvalidArguments)?;
+
+    let mut input = Base64UrlUnpadded::encode_string(header.as_bytes());
+    input.push('.');
+    input.push_str(&Base64UrlUnpadded::encode_string(payload));
+    Ok(input)
+}
+
+/// Builds the signing input of a JWT with `typ: "JWT"`. `claims_json` must
+/// be a JSON object, and is encoded as is.
+pub(crate) fn jwt_signing_input(
+    alg: &str,
+    claims_json: &str,
+    header_overrides: &Map<String, Value>,
+) -> Result<String> {
+    serde_json::from_str::<Map<String, Value>>(claims_json).map_err(|_| Error::InvalidArguments)?;
+    signing_input(alg, Some("JWT"), header_overrides, claims_json.as_bytes())
+}
+
+/// Appends `signature` to `signing_input`, completing the JWS, and drops
+/// the payload in the detached serialization.
+pub(crate) fn encode(
+    mut signing_input: String,
+    signature: &[u8],
+    serialization: JwsSerialization,
+) -> String {
+    if serialization == JwsSerialization::Detached {
+        if let Some(dot) = signing_input.find('.') {
+            signing_input.truncate(dot + 1);
+        }
+    }
+    signing_input.push('.');
+    signing_input.push_str(&Base64UrlUnpadded::encode_string(signature));
+    signing_input
+}
+
+/// JWS in compact serialization, whose signature is yet to be verified.
+pub(crate) struct Token<'a> {
+    /// Encoded header and payload joined by a dot.
+    pub(crate) signing_input: String,
+    signature: Vec<u8>,
+    payload: &'a str,
+}
+
+impl<'a> Token<'a> {
+    /// Splits `token`, checking that its header has the given `alg`.
+    ///
+    /// With a `detached_payload`, the payload of `token` must be empty and
+    /// the signing input is rebuilt from `detached_payload`.
+    ///
+    /// Fails with [`Error::Verification`] on malformed tokens, on tokens
+    /// signed with another algorithm, and on tokens with critical header
+    /// parameters, none of which are understood.
+    pub(crate) fn parse(
+        alg: &str,
+        token: &'a str,
+        detached_payload: Option<&[u8]>,
+    ) -> Result<Self> {
+        let (signing_input, signature) = token.rsplit_once('.').ok_or(Error::Verification)?;
+        let (header, payload) = signing_input.split_once('.').ok_or(Error::Verification)?;
+
+        let header_json = Base64UrlUnpadded::decode_vec(header).map_err(|_| Error::Verification)?;
+        let header_json = serde_json::from_slice::<Map<String, Value>>(&header_json)
+            .map_err(|_| Error::Verification)?;
+        if header_json.get("alg").and_then(Value::as_str) != Some(alg)
+            || header_json.contains_key("crit")
+        {
+            return Err(Error::Verification);
+        }
+
+        let signing_input = match detached_payload {
+            None => signing_input.into(),
+            Some(_) if !payload.is_empty() => return Err(Error::Verification),
+            Some(detached) => {
+                let mut input = String::from(header);
+                input.push('.');
+                input.push_str(&Base64UrlUnpadded::encode_string(detached));
+                input
+            }
+        };
+        let signature =
+            Base64UrlUnpadded::decode_vec(signature).map_err(|_| Error::Verification)?;
+        Ok(Self {
+            signing_input,
+            signature,
+            payload,
+        })
+    }
+
+    /// Decodes the signature as an integer with the given precision.
+    pub(crate) fn signature(&self, bits_precision: u32) -> Result<BoxedUint> {
+        BoxedUint::from_be_slice(&self.signature, bits_precision).map_err(|_| Error::Verification)
+    }
+
+    /// Decodes the payload of a compact JWS.
+    pub(crate) fn payload(&self) -> Result<Vec<u8>> {
+        Base64UrlUnpadded::decode_vec(self.payload).map_err(|_| Error::Verification)
+    }
+
+    /// Decodes the claims of a JWT, which must be a JSON object.
+    pub(crate) fn claims(&self) -> Result<Map<String, Value>> {
+        serde_json::from_slice(&self.payload()?).map_err(|_| Error::Verification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pkcs1v15, pss, RsaPrivateKey};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use serde_json::json;
+    use signature::Keypair;
+
+    const CLAIMS: &str = r#"{"sub":"1234567890","admin":true}"#;
+
+    fn header(token: &str) -> Value {
+        let header = token.split('.').next().unwrap();
+        serde_json::from_slice(&Base64UrlUnpadded::decode_vec(header).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_pkcs1v15_jwt() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let signing_key = pkcs1v15::SigningKey::<Sha384>::new(key);
+        let verifying_key = signing_key.verifying_key();
+
+        let mut overrides = Map::new();
+        overrides.insert("kid".into(), json!("key-1"));
+        let token = signing_key.sign_jwt(CLAIMS, &overrides).unwrap();
+        assert_eq!(
+            header(&token),
+            json!({"alg": "RS384", "kid": "key-1", "typ": "JWT"})
+        );
+        // Signatures are deterministic.
+        assert_eq!(signing_key.sign_jwt(CLAIMS, &overrides).unwrap(), token);
+
+        let claims = verifying_key.verify_jwt(&token).unwrap();
+        assert_eq!(
+            Value::Object(claims),
+            json!({"sub": "1234567890", "admin": true})
+        );
+
+        let mut tampered = token.clone();
+        tampered.insert(token.find('.').unwrap() + 2, 'A');
+        assert_eq!(
+            verifying_key.verify_jwt(&tampered),
+            Err(Error::Verification)
+        );
+        let other = pkcs1v15::VerifyingKey::<Sha256>::new(verifying_key.as_ref().clone());
+        assert_eq!(other.verify_jwt(&token), Err(Error::Verification));
+
+        overrides.insert("alg".into(), json!("none"));
+        assert_eq!(
+            signing_key.sign_jwt(CLAIMS, &overrides),
+            Err(Error::InvalidArguments)
+        );
+        assert_eq!(
+            signing_key.sign_jwt("[1, 2]", &Map::new()),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    fn test_jws() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let signing_key = pkcs1v15::SigningKey::<Sha256>::new(key);
+        let verifying_key = signing_key.verifying_key();
+        let payload = b"\x00not json\xff";
+
+        let compact = signing_key
+            .sign_jws(payload, &Map::new(), JwsSerialization::Compact)
+            .unwrap();
+        assert_eq!(header(&compact), json!({"alg": "RS256"}));
+        assert_eq!(verifying_key.verify_jws(&compact).unwrap(), payload);
+
+        // The detached JWS is the compact one without its payload.
+        let detached = signing_key
+            .sign_jws(payload, &Map::new(), JwsSerialization::Detached)
+            .unwrap();
+        let (header_part, rest) = compact.split_once('.').unwrap();
+        let signature = rest.split_once('.').unwrap().1;
+        assert_eq!(detached, format!("{header_part}..{signature}"));
+        verifying_key
+            .verify_jws_detached(&detached, payload)
+            .unwrap();
+        assert_eq!(
+            verifying_key.verify_jws_detached(&detached, b"other"),
+            Err(Error::Verification)
+        );
+        assert_eq!(
+            verifying_key.verify_jws_detached(&compact, payload),
+            Err(Error::Verification)
+        );
+
+        let signing_key = pss::SigningKey::<Sha384>::new(signing_key.as_ref().clone());
+        let verifying_key = signing_key.verifying_key();
+        let detached = signing_key
+            .sign_jws_with_rng(&mut rng, payload, &Map::new(), JwsSerialization::Detached)
+            .unwrap();
+        assert_eq!(header(&detached), json!({"alg": "PS384"}));
+        verifying_key
+            .verify_jws_detached(&detached, payload)
+            .unwrap();
+        assert_eq!(
+            verifying_key.verify_jws(&detached),
+            Err(Error::Verification)
+        );
+    }
+
+    #[test]
+    fn test_pss_jwt() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let signing_key = pss::SigningKey::<Sha256>::new(key);
+        let verifying_key = signing_key.verifying_key();
+
+        let token = signing_key
+            .sign_jwt_with_rng(&mut rng, CLAIMS, &Map::new())
+            .unwrap();
+        assert_eq!(header(&token), json!({"alg": "PS256", "typ": "JWT"}));
+        assert_eq!(verifying_key.verify_jwt(&token).unwrap()["admin"], true);
+
+        // A PKCS#1 v1.5 token must not verify as PSS, even with the same key.
+        let pkcs1v15_key =
+            pkcs1v15::SigningKey::<Sha256>::new(RsaPrivateKey::new(&mut rng, 1024).unwrap());
+        let token = pkcs1v15_key.sign_jwt(CLAIMS, &Map::new()).unwrap();
+        let verifying_key =
+            pss::VerifyingKey::<Sha256>::new(pkcs1v15_key.verifying_key().as_ref().clone());
+        assert_eq!(verifying_key.verify_jwt(&token), Err(Error::Verification));
+        assert_eq!(
+            verifying_key.verify_jwt("not.a-token"),
+            Err(Error::Verification)
+        );
+    }
+}
@@ -0,0 +1,439 @@
+//! Known answer tests with [Wycheproof] test vectors.
+//!
+//! [`run_known_answer_tests`] runs the vectors of a Wycheproof test file
+//! against this build of the crate, which allows integrators to check the
+//! exact configuration they ship. The test files are not embedded, they can
+//! be loaded at runtime or with [`include_str!`]. The supported schemas are:
+//!
+//! - `rsassa_pkcs1_verify_schema.json`
+//! - `rsassa_pss_verify_schema.json`
+//! - `rsaes_oaep_decrypt_schema.json`
+//! - `rsaes_pkcs1_decrypt_schema.json`
+//!
+//! Test cases using digests which are not enabled, or parameters this crate
+//! does not support, e.g. PSS with distinct MGF1 digest, are skipped.
+//!
+//! ```no_run
+//! let vectors = std::fs::read_to_string("rsa_oaep_2048_sha256_mgf1sha256_test.json")?;
+//! let report = rsa::kat::run_known_answer_tests(&vectors)?;
+//! assert!(report.is_success(), "failed: {:?}", report.failures().collect::<Vec<_>>());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [Wycheproof]: https://github.com/C2SP/wycheproof
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use crypto_bigint::BoxedUint;
+use digest::{Digest, DynDigest};
+use pkcs8::{AssociatedOid, DecodePrivateKey, DecodePublicKey};
+use serde_json::Value;
+
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::{Oaep, Pkcs1v15Encrypt, Pkcs1v15Sign, Pss};
+
+/// Expected result of a test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KatExpectation {
+    /// The input must be accepted.
+    Valid,
+    /// The input must be rejected.
+    Invalid,
+    /// The input may be accepted or rejected, e.g. legacy encodings.
+    Acceptable,
+}
+
+/// Outcome of a test case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KatOutcome {
+    /// The crate behaved as expected.
+    Passed,
+    /// The crate did not behave as expected.
+    Failed,
+    /// The test case was not run.
+    Skipped,
+}
+
+/// Result of a test case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KatResult {
+    /// Identifier of the test case in its file.
+    pub tc_id: u64,
+    /// Description of the test case.
+    pub comment: String,
+    /// Expected result.
+    pub expected: KatExpectation,
+    /// Outcome of the test case.
+    pub outcome: KatOutcome,
+}
+
+/// Results of the test cases of a test file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KatReport {
+    /// Name of the algorithm tested by the file.
+    pub algorithm: String,
+    /// Results of the test cases, in the order of the file.
+    pub results: Vec<KatResult>,
+}
+
+impl KatReport {
+    /// Returns the test cases which failed.
+    pub fn failures(&self) -> impl Iterator<Item = &KatResult> {
+        self.results
+            .iter()
+            .filter(|result| result.outcome == KatOutcome::Failed)
+    }
+
+    /// Returns the number of test cases with the given `outcome`.
+    pub fn count(&self, outcome: KatOutcome) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.outcome == outcome)
+            .count()
+    }
+
+    /// Returns whether no test case failed.
+    pub fn is_success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+}
+
+/// Runs the test vectors of the Wycheproof test file `vectors`.
+///
+/// Fails with [`Error::InvalidArguments`] if the file is malformed or uses an
+/// unsupported schema.
+pub fn run_known_answer_tests(vectors: &str) -> Result<KatReport> {
+    let file: Value = serde_json::from_str(vectors).map_err(|_| Error::InvalidArguments)?;
+    let schema = str_field(&file, "schema")?;
+    let kind = if schema.starts_with("rsassa_pkcs1_verify") {
+        Kind::Pkcs1v15Verify
+    } else if schema.starts_with("rsassa_pss_verify") {
+        Kind::PssVerify
+    } else if schema.starts_with("rsaes_oaep_decrypt") {
+        Kind::OaepDecrypt
+    } else if schema.starts_with("rsaes_pkcs1_decrypt") {
+        Kind::Pkcs1v15Decrypt
+    } else {
+        return Err(Error::InvalidArguments);
+    };
+
+    let mut report = KatReport {
+        algorithm: str_field(&file, "algorithm")
+            .unwrap_or_default()
+            .to_string(),
+        results: Vec::new(),
+    };
+    for group in array_field(&file, "testGroups")? {
+        let run = kind.group(group)?;
+        for test in array_field(group, "tests")? {
+            let expected = match str_field(test, "result")? {
+                "valid" => KatExpectation::Valid,
+                "invalid" => KatExpectation::Invalid,
+                "acceptable" => KatExpectation::Acceptable,
+                _ => return Err(Error::InvalidArguments),
+            };
+            let outcome = match &run {
+                Some(run) => match (run(test)?, expected) {
+                    (_, KatExpectation::Acceptable)
+                    | (true, KatExpectation::Valid)
+                    | (false, KatExpectation::Invalid) => KatOutcome::Passed,
+                    _ => KatOutcome::Failed,
+                },
+                None => KatOutcome::Skipped,
+            };
+            report.results.push(KatResult {
+                tc_id: test
+                    .get("tcId")
+                    .and_then(Value::as_u64)
+                    .ok_or(Error::InvalidArguments)?,
+                comment: str_field(test, "comment").unwrap_or_default().to_string(),
+                expected,
+                outcome,
+            });
+        }
+    }
+    Ok(report)
+}
+
+/// Runs a test case of a group, returning whether the input was accepted.
+type TestCase = Box<dyn Fn(&Value) -> Result<bool>>;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Pkcs1v15Verify,
+    PssVerify,
+    OaepDecrypt,
+    Pkcs1v15Decrypt,
+}
+
+impl Kind {
+    /// Prepares the test cases of `group`, or returns `None` if they must be
+    /// skipped.
+    fn group(self, group: &Value) -> Result<Option<TestCase>> {
+        Ok(match self {
+            Kind::Pkcs1v15Verify => {
+                let Some(hash) = Hash::parse(str_field(group, "sha")?) else {
+                    return Ok(None);
+                };
+                let key = public_key(group)?;
+                Some(Box::new(move |test| {
+                    let hashed = hash.digest(&hex_field(test, "msg")?);
+                    let sig = hex_field(test, "sig")?;
+                    Ok(key.verify(hash.pkcs1v15(), &hashed, &sig).is_ok())
+                }))
+            }
+            Kind::PssVerify => {
+                let (Some(hash), Some(mgf_hash)) = (
+                    Hash::parse(str_field(group, "sha")?),
+                    Hash::parse(str_field(group, "mgfSha")?),
+                ) else {
+                    return Ok(None);
+                };
+                if hash != mgf_hash || str_field(group, "mgf")? != "MGF1" {
+                    return Ok(None);
+                }
+                let salt_len = group
+                    .get("sLen")
+                    .and_then(Value::as_u64)
+                    .and_then(|len| usize::try_from(len).ok())
+                    .ok_or(Error::InvalidArguments)?;
+                let key = public_key(group)?;
+                Some(Box::new(move |test| {
+                    let hashed = hash.digest(&hex_field(test, "msg")?);
+                    let sig = hex_field(test, "sig")?;
+                    let pss = Pss {
+                        blinded: false,
+                        digest: hash.dyn_digest(),
+                        salt_len,
+                    };
+                    Ok(key.verify(pss, &hashed, &sig).is_ok())
+                }))
+            }
+            Kind::OaepDecrypt => {
+                let (Some(hash), Some(mgf_hash)) = (
+                    Hash::parse(str_field(group, "sha")?),
+                    Hash::parse(str_field(group, "mgfSha")?),
+                ) else {
+                    return Ok(None);
+                };
+                if str_field(group, "mgf")? != "MGF1" {
+                    return Ok(None);
+                }
+                let key = private_key(group)?;
+                Some(Box::new(move |test| {
+                    let label = hex_field(test, "label")?;
+                    let oaep = Oaep {
+                        digest: hash.dyn_digest(),
+                        mgf_digest: mgf_hash.dyn_digest(),
+                        label: (!label.is_empty()).then(|| label.into_boxed_slice()),
+                    };
+                    let ciphertext = hex_field(test, "ct")?;
+                    Ok(key.decrypt(oaep, &ciphertext).ok() == Some(hex_field(test, "msg")?))
+                }))
+            }
+            Kind::Pkcs1v15Decrypt => {
+                let key = private_key(group)?;
+                Some(Box::new(move |test| {
+                    let ciphertext = hex_field(test, "ct")?;
+                    let plaintext = key.decrypt(Pkcs1v15Encrypt, &ciphertext).ok();
+                    Ok(plaintext == Some(hex_field(test, "msg")?))
+                }))
+            }
+        })
+    }
+}
+
+/// Digests used by the test vectors.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Hash {
+    #[cfg(feature = "sha1")]
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Sha512_224,
+    Sha512_256,
+}
+
+impl Hash {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            #[cfg(feature = "sha1")]
+            "SHA-1" => Hash::Sha1,
+            "SHA-224" => Hash::Sha224,
+            "SHA-256" => Hash::Sha256,
+            "SHA-384" => Hash::Sha384,
+            "SHA-512" => Hash::Sha512,
+            "SHA-512/224" => Hash::Sha512_224,
+            "SHA-512/256" => Hash::Sha512_256,
+            _ => return None,
+        })
+    }
+
+    fn digest(self, msg: &[u8]) -> Vec<u8> {
+        let mut digest = self.dyn_digest();
+        digest.update(msg);
+        digest.finalize().into_vec()
+    }
+
+    fn dyn_digest(self) -> Box<dyn DynDigest + Send + Sync> {
+        match self {
+            #[cfg(feature = "sha1")]
+            Hash::Sha1 => Box::new(sha1::Sha1::new()),
+            Hash::Sha224 => Box::new(sha2::Sha224::new()),
+            Hash::Sha256 => Box::new(sha2::Sha256::new()),
+            Hash::Sha384 => Box::new(sha2::Sha384::new()),
+            Hash::Sha512 => Box::new(sha2::Sha512::new()),
+            Hash::Sha512_224 => Box::new(sha2::Sha512_224::new()),
+            Hash::Sha512_256 => Box::new(sha2::Sha512_256::new()),
+        }
+    }
+
+    fn pkcs1v15(self) -> Pkcs1v15Sign {
+        fn new<D: Digest + AssociatedOid>() -> Pkcs1v15Sign {
+            Pkcs1v15Sign::new::<D>()
+        }
+        match self {
+            #[cfg(feature = "sha1")]
+            Hash::Sha1 => new::<sha1::Sha1>(),
+            Hash::Sha224 => new::<sha2::Sha224>(),
+            Hash::Sha256 => new::<sha2::Sha256>(),
+            Hash::Sha384 => new::<sha2::Sha384>(),
+            Hash::Sha512 => new::<sha2::Sha512>(),
+            Hash::Sha512_224 => new::<sha2::Sha512_224>(),
+            Hash::Sha512_256 => new::<sha2::Sha512_256>(),
+        }
+    }
+}
+
+fn str_field<'a>(value: &'a Value, name: &str) -> Result<&'a str> {
+    value
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or(Error::InvalidArguments)
+}
+
+fn array_field<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>> {
+    value
+        .get(name)
+        .and_then(Value::as_array)
+        .ok_or(Error::InvalidArguments)
+}
+
+fn hex_field(value: &Value, name: &str) -> Result<Vec<u8>> {
+    let hex = str_field(value, name)?;
+    if hex.len() % 2 != 0 {
+        return Err(Error::InvalidArguments);
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| Error::InvalidArguments))
+        .collect()
+}
+
+/// Decodes a big-endian integer, ignoring the leading zeros of the encoding.
+fn uint_field(value: &Value, name: &str) -> Result<BoxedUint> {
+    let bytes = hex_field(value, name)?;
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    let bytes = &bytes[start..];
+    Ok(BoxedUint::from_be_slice(bytes, bytes.len() as u32 * 8)?)
+}
+
+/// Public key of a group, given by `n` and `e` in older files, and by a
+/// `publicKey` object or `publicKeyDer` in newer ones.
+fn public_key(group: &Value) -> Result<RsaPublicKey> {
+    if group.get("n").is_some() {
+        return RsaPublicKey::new(uint_field(group, "n")?, uint_field(group, "e")?);
+    }
+    if let Some(key) = group.get("publicKey").filter(|key| key.is_object()) {
+        return RsaPublicKey::new(
+            uint_field(key, "modulus")?,
+            uint_field(key, "publicExponent")?,
+        );
+    }
+    RsaPublicKey::from_public_key_der(&hex_field(group, "publicKeyDer")?)
+        .map_err(|_| Error::InvalidArguments)
+}
+
+fn private_key(group: &Value) -> Result<RsaPrivateKey> {
+    RsaPrivateKey::from_pkcs8_der(&hex_field(group, "privateKeyPkcs8")?)
+        .map_err(|_| Error::InvalidArguments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::PublicKeyParts;
+    use pkcs8::EncodePrivateKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_run_known_answer_tests() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let hashed = sha2::Sha256::digest(b"kat");
+        let sig = key
+            .sign(Pkcs1v15Sign::new::<sha2::Sha256>(), &hashed)
+            .unwrap();
+        let mut forged = sig.clone();
+        forged[10] ^= 1;
+
+        let vectors = format!(
+            r#"{{
+                "algorithm": "RSASSA-PKCS1-v1_5",
+                "schema": "rsassa_pkcs1_verify_schema.json",
+                "testGroups": [
+                    {{"n": "00{n}", "e": "010001", "sha": "SHA-256", "tests": [
+                        {{"tcId": 1, "comment": "", "msg": "{msg}", "sig": "{sig}", "result": "valid"}},
+                        {{"tcId": 2, "comment": "", "msg": "{msg}", "sig": "{forged}", "result": "invalid"}},
+                        {{"tcId": 3, "comment": "", "msg": "{msg}", "sig": "{forged}", "result": "valid"}}
+                    ]}},
+                    {{"n": "{n}", "e": "010001", "sha": "SHA3-256", "tests": [
+                        {{"tcId": 4, "comment": "", "msg": "", "sig": "", "result": "valid"}}
+                    ]}}
+                ]
+            }}"#,
+            n = hex(&key.n().to_be_bytes()),
+            msg = hex(b"kat"),
+            sig = hex(&sig),
+            forged = hex(&forged),
+        );
+        let report = run_known_answer_tests(&vectors).unwrap();
+        assert_eq!(report.algorithm, "RSASSA-PKCS1-v1_5");
+        assert_eq!(report.count(KatOutcome::Passed), 2);
+        assert_eq!(report.count(KatOutcome::Skipped), 1);
+        assert_eq!(report.failures().map(|r| r.tc_id).collect::<Vec<_>>(), [3]);
+        assert!(!report.is_success());
+
+        let der = key.to_pkcs8_der().unwrap();
+        let ciphertext = key
+            .to_public_key()
+            .encrypt(&mut rng, Pkcs1v15Encrypt, b"kat")
+            .unwrap();
+        let vectors = format!(
+            r#"{{
+                "algorithm": "RSAES-PKCS1-v1_5",
+                "schema": "rsaes_pkcs1_decrypt_schema.json",
+                "testGroups": [{{"privateKeyPkcs8": "{der}", "tests": [
+                    {{"tcId": 1, "comment": "", "msg": "{msg}", "ct": "{ct}", "result": "valid"}}
+                ]}}]
+            }}"#,
+            der = hex(der.as_bytes()),
+            msg = hex(b"kat"),
+            ct = hex(&ciphertext),
+        );
+        assert!(run_known_answer_tests(&vectors).unwrap().is_success());
+
+        assert_eq!(
+            run_known_answer_tests(r#"{"schema": "ecdsa_verify_schema.json"}"#),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
@@ -1,9 +1,19 @@
+#[cfg(feature = "fingerprint")]
+use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+#[cfg(feature = "fingerprint")]
+use base64ct::{Base64, Base64Unpadded, Encoding};
+use core::fmt;
 use core::hash::{Hash, Hasher};
 use crypto_bigint::modular::{BoxedMontyForm, BoxedMontyParams};
-use crypto_bigint::{BoxedUint, Integer, NonZero, Odd};
+use crypto_bigint::{BoxedUint, Gcd, Integer, NonZero, Odd};
+use digest::Digest;
 use rand_core::CryptoRngCore;
-use zeroize::{Zeroize, ZeroizeOnDrop};
+#[cfg(feature = "os_rng")]
+use rand_core::OsRng;
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 #[cfg(feature = "serde")]
 use {
     pkcs8::{DecodePrivateKey, EncodePrivateKey},
@@ -11,17 +21,46 @@ use {
     spki::{DecodePublicKey, EncodePublicKey},
 };
 
-use crate::algorithms::generate::generate_multi_prime_key_with_exp;
+use crate::algorithms::arith::{is_prime_vartime, pow2, reduce, rem_u64, resize};
+use crate::algorithms::generate::{
+    generate_multi_prime_key_with_exp, generate_multi_prime_key_with_strength,
+    generate_provable_key_with_exp, PrimeCertificate, PrimeStrength,
+};
 use crate::algorithms::rsa::{
     compute_modulus, compute_private_exponent_carmicheal, compute_private_exponent_euler_totient,
-    recover_primes,
+    recover_primes, rsa_decrypt_and_check, rsa_encrypt, rsa_encrypt_vartime,
 };
 
+use crate::blocklist::KeyBlocklist;
 use crate::dummy_rng::DummyRng;
 use crate::errors::{Error, Result};
+use crate::fallible_rng::FallibleRng;
+use crate::pkcs1v15::{Pkcs1v15Encrypt, Pkcs1v15Sign};
+use crate::policy::KeyPolicy;
+#[cfg(feature = "sha2")]
+use crate::seeded_rng::SeededRng;
+#[cfg(feature = "sha2")]
+use crate::shares::{self, KeyShare};
+#[cfg(feature = "std")]
+use crate::traits::keys::BlindingCache;
+#[cfg(not(feature = "hazmat"))]
+use crate::traits::keys::PrivateKeyOps;
+#[cfg(feature = "hazmat")]
+use crate::traits::keys::RsaPrivateOps;
 use crate::traits::keys::{CrtValue, PrivateKeyParts, PublicKeyParts};
 use crate::traits::{PaddingScheme, SignatureScheme};
 
+#[cfg(feature = "sealing")]
+mod sealed;
+mod secret_box;
+mod shared;
+
+#[cfg(feature = "sealing")]
+pub use sealed::SealedRsaPrivateKey;
+pub use shared::SharedRsaPrivateKey;
+
+use secret_box::SecretBox;
+
 /// Represents the public part of an RSA key.
 #[derive(Debug, Clone)]
 pub struct RsaPublicKey {
@@ -33,7 +72,10 @@ pub struct RsaPublicKey {
     /// Typically `0x10001` (`65537`)
     e: BoxedUint,
 
-    n_params: BoxedMontyParams,
+    n_params: Arc<BoxedMontyParams>,
+
+    /// Verify signatures with variable time arithmetic
+    vartime_verify: bool,
 }
 
 impl Eq for RsaPublicKey {}
@@ -55,25 +97,73 @@ impl Hash for RsaPublicKey {
 }
 
 /// Represents a whole RSA key, public and private parts.
-#[derive(Debug, Clone)]
+///
+/// # Thread safety
+///
+/// The key is `Send + Sync`, and all private key operations take `&self`, so
+/// a key can be shared between threads, e.g. through [`SharedRsaPrivateKey`],
+/// and used concurrently. The state mutated through `&self` is synchronized:
+/// CRT values computed lazily are stored in a `OnceLock`, and the cache of
+/// blinding factors is guarded by a mutex.
+///
+/// # Memory locking
+///
+/// With the `mlock` feature, the buffers holding `d`, the primes and the CRT
+/// values are locked into memory, so that long-lived keys are not written
+/// to swap, and are surrounded by canaries checked when they are released.
+/// The Montgomery constants derived from the primes, other than the primes
+/// themselves, cannot be reached through `crypto-bigint` and stay unlocked.
+///
+/// # Debug output
+///
+/// The [`Debug`](fmt::Debug) output only identifies the key by the leading
+/// bytes of its modulus, its size and its number of primes, so that it is safe
+/// to log. Use [`RsaPrivateKey::debug_full`] to print the private components.
+#[derive(Clone)]
 pub struct RsaPrivateKey {
     /// Public components of the private key.
     pubkey_components: RsaPublicKey,
     /// Private exponent
-    pub(crate) d: BoxedUint,
+    pub(crate) d: SecretBox<BoxedUint>,
     /// Prime factors of N, contains >= 2 elements.
-    pub(crate) primes: Vec<BoxedUint>,
+    pub(crate) primes: SecretBox<Vec<BoxedUint>>,
     /// Precomputed values to speed up private operations
-    pub(crate) precomputed: Option<PrecomputedValues>,
+    pub(crate) precomputed: PrecomputedCell,
+    /// Blinding factors reused across private operations
+    #[cfg(feature = "std")]
+    pub(crate) blinding: Option<BlindingCache>,
 }
 
+// Guarantee that keys can be shared across threads, see the documentation of
+// `RsaPrivateKey`.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<RsaPublicKey>();
+    assert_send_sync::<RsaPrivateKey>();
+    assert_send_sync::<SharedRsaPrivateKey>();
+};
+
 impl Eq for RsaPrivateKey {}
 impl PartialEq for RsaPrivateKey {
+    /// Compares the private components in constant time, see
+    /// [`ConstantTimeEq`].
     #[inline]
     fn eq(&self, other: &RsaPrivateKey) -> bool {
-        self.pubkey_components == other.pubkey_components
-            && self.d == other.d
-            && self.primes == other.primes
+        self.ct_eq(other).into()
+    }
+}
+
+impl ConstantTimeEq for RsaPrivateKey {
+    /// Compares `d` and the primes in constant time. The public components
+    /// and the number of primes are not secret and compared in variable time.
+    fn ct_eq(&self, other: &RsaPrivateKey) -> Choice {
+        let public_eq = self.pubkey_components == other.pubkey_components
+            && self.primes.len() == other.primes.len();
+        let mut eq = Choice::from(u8::from(public_eq)) & self.d.ct_eq(&other.d);
+        for (a, b) in self.primes.iter().zip(other.primes.iter()) {
+            eq &= a.ct_eq(b);
+        }
+        eq
     }
 }
 
@@ -91,6 +181,50 @@ impl Hash for RsaPrivateKey {
     }
 }
 
+/// Number of leading bytes of the modulus in the [`Debug`](fmt::Debug) output.
+const DEBUG_MODULUS_BYTES: usize = 8;
+
+impl fmt::Debug for RsaPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Modulus<'a>(&'a [u8]);
+
+        impl fmt::Debug for Modulus<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                for byte in self.0.iter().take(DEBUG_MODULUS_BYTES) {
+                    write!(f, "{byte:02x}")?;
+                }
+                f.write_str("..")
+            }
+        }
+
+        let n = self.pubkey_components.n.to_be_bytes();
+        let leading_zeros = self.pubkey_components.n.leading_zeros() as usize / 8;
+        f.debug_struct("RsaPrivateKey")
+            .field("modulus", &Modulus(&n[leading_zeros..]))
+            .field("bits", &self.pubkey_components.n.bits())
+            .field("primes", &self.primes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Full [`Debug`](fmt::Debug) output of a private key, see
+/// [`RsaPrivateKey::debug_full`].
+struct FullDebug<'a>(&'a RsaPrivateKey);
+
+impl fmt::Debug for FullDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = self.0;
+        let mut f = f.debug_struct("RsaPrivateKey");
+        f.field("pubkey_components", &key.pubkey_components)
+            .field("d", &key.d)
+            .field("primes", &key.primes)
+            .field("precomputed", &key.precomputed);
+        #[cfg(feature = "std")]
+        f.field("blinding", &key.blinding);
+        f.finish()
+    }
+}
+
 impl Drop for RsaPrivateKey {
     fn drop(&mut self) {
         self.d.zeroize();
@@ -104,16 +238,19 @@ impl ZeroizeOnDrop for RsaPrivateKey {}
 #[derive(Debug, Clone)]
 pub(crate) struct PrecomputedValues {
     /// D mod (P-1)
-    pub(crate) dp: BoxedUint,
+    pub(crate) dp: SecretBox<BoxedUint>,
     /// D mod (Q-1)
-    pub(crate) dq: BoxedUint,
+    pub(crate) dq: SecretBox<BoxedUint>,
     /// Q^-1 mod P
-    pub(crate) qinv: BoxedMontyForm,
+    pub(crate) qinv: SecretBox<BoxedMontyForm>,
 
     /// Montgomery params for `p`
-    pub(crate) p_params: BoxedMontyParams,
+    pub(crate) p_params: SecretBox<Arc<BoxedMontyParams>>,
     /// Montgomery params for `q`
-    pub(crate) q_params: BoxedMontyParams,
+    pub(crate) q_params: SecretBox<Arc<BoxedMontyParams>>,
+
+    /// Window size of the exponentiations mod `p` and `q`
+    pub(crate) window: Option<u32>,
 }
 
 impl ZeroizeOnDrop for PrecomputedValues {}
@@ -122,6 +259,7 @@ impl Zeroize for PrecomputedValues {
     fn zeroize(&mut self) {
         self.dp.zeroize();
         self.dq.zeroize();
+        self.qinv.zeroize();
         // TODO: once these have landed in crypto-bigint
         // self.p_params.zeroize();
         // self.q_params.zeroize();
@@ -134,6 +272,86 @@ impl Drop for PrecomputedValues {
     }
 }
 
+/// Cell holding the [`PrecomputedValues`] of a key.
+///
+/// With the `std` feature the values can be initialized through a shared
+/// reference, so that private key operations compute them on first use. A
+/// failure to compute them is cached as well, until the cell is set or
+/// cleared.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PrecomputedCell {
+    #[cfg(feature = "std")]
+    inner: std::sync::OnceLock<Option<PrecomputedValues>>,
+    #[cfg(not(feature = "std"))]
+    inner: Option<PrecomputedValues>,
+}
+
+impl PrecomputedCell {
+    #[cfg(feature = "std")]
+    pub(crate) fn get(&self) -> Option<&PrecomputedValues> {
+        self.inner.get().and_then(Option::as_ref)
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn get(&self) -> Option<&PrecomputedValues> {
+        self.inner.as_ref()
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn get_mut(&mut self) -> Option<&mut PrecomputedValues> {
+        self.inner.get_mut().and_then(Option::as_mut)
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn get_mut(&mut self) -> Option<&mut PrecomputedValues> {
+        self.inner.as_mut()
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn set(&mut self, values: PrecomputedValues) {
+        self.inner = Some(values).into();
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn set(&mut self, values: PrecomputedValues) {
+        self.inner = Some(values);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.inner = Default::default();
+    }
+
+    /// Returns the values, computing them with `f` if they are not set yet.
+    ///
+    /// `f` is called at most once, also if it fails.
+    #[cfg(feature = "std")]
+    pub(crate) fn get_or_compute(
+        &self,
+        f: impl FnOnce() -> Result<PrecomputedValues>,
+    ) -> Option<&PrecomputedValues> {
+        self.inner.get_or_init(|| f().ok()).as_ref()
+    }
+
+    /// Returns the values set through [`PrecomputedCell::set`], as they cannot
+    /// be initialized through a shared reference without `std`.
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn get_or_compute(
+        &self,
+        _f: impl FnOnce() -> Result<PrecomputedValues>,
+    ) -> Option<&PrecomputedValues> {
+        self.inner.as_ref()
+    }
+}
+
+impl Zeroize for PrecomputedCell {
+    fn zeroize(&mut self) {
+        if let Some(values) = self.get_mut() {
+            values.zeroize();
+        }
+        self.clear();
+    }
+}
+
 impl From<RsaPrivateKey> for RsaPublicKey {
     fn from(private_key: RsaPrivateKey) -> Self {
         (&private_key).into()
@@ -142,14 +360,7 @@ impl From<RsaPrivateKey> for RsaPublicKey {
 
 impl From<&RsaPrivateKey> for RsaPublicKey {
     fn from(private_key: &RsaPrivateKey) -> Self {
-        let n = PublicKeyParts::n(private_key);
-        let e = PublicKeyParts::e(private_key);
-        let n_params = PublicKeyParts::n_params(private_key);
-        RsaPublicKey {
-            n: n.clone(),
-            e: e.clone(),
-            n_params: n_params.clone(),
-        }
+        private_key.pubkey_components.clone()
     }
 }
 
@@ -167,6 +378,23 @@ impl PublicKeyParts for RsaPublicKey {
     }
 }
 
+/// Renders the key as `RSA-<bits> SHA256:<fingerprint>`, in the style of
+/// OpenSSH. The fingerprint is the SHA-256 digest of the DER encoded
+/// `SubjectPublicKeyInfo` of the key, in Base64 without padding, so it does
+/// not depend on the precision of `n` or on how the key was loaded.
+///
+/// Available with the `fingerprint` feature.
+#[cfg(feature = "fingerprint")]
+impl fmt::Display for RsaPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let der = spki::EncodePublicKey::to_public_key_der(self).map_err(|_| fmt::Error)?;
+        let digest = sha2::Sha256::digest(der.as_bytes());
+        let mut buf = [0u8; 43];
+        let fingerprint = Base64Unpadded::encode(&digest, &mut buf).map_err(|_| fmt::Error)?;
+        write!(f, "RSA-{} SHA256:{}", self.n.bits(), fingerprint)
+    }
+}
+
 impl RsaPublicKey {
     /// Encrypt the given message.
     pub fn encrypt<R: CryptoRngCore, P: PaddingScheme>(
@@ -178,6 +406,39 @@ impl RsaPublicKey {
         padding.encrypt(rng, self, msg)
     }
 
+    /// Encrypt the given message into the start of `out`, returning the
+    /// length of the ciphertext.
+    ///
+    /// `out` must hold at least [`PublicKeyParts::size`] bytes.
+    pub fn encrypt_into<R: CryptoRngCore, P: PaddingScheme>(
+        &self,
+        rng: &mut R,
+        padding: P,
+        msg: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        padding.encrypt_into(rng, self, msg, out)
+    }
+
+    /// Encrypt the given message, drawing randomness with
+    /// [`RngCore::try_fill_bytes`](rand_core::RngCore::try_fill_bytes).
+    ///
+    /// Returns [`Error::Rng`] instead of panicking if `rng` fails.
+    pub fn encrypt_with_fallible_rng<R: CryptoRngCore + ?Sized, P: PaddingScheme>(
+        &self,
+        rng: &mut R,
+        padding: P,
+        msg: &[u8],
+    ) -> Result<Vec<u8>> {
+        FallibleRng::run(rng, |rng| self.encrypt(rng, padding, msg))
+    }
+
+    /// Encrypt the given message using the operating system's RNG.
+    #[cfg(feature = "os_rng")]
+    pub fn encrypt_with_os_rng<P: PaddingScheme>(&self, padding: P, msg: &[u8]) -> Result<Vec<u8>> {
+        self.encrypt(&mut OsRng, padding, msg)
+    }
+
     /// Verify a signed message.
     ///
     /// `hashed` must be the result of hashing the input using the hashing function
@@ -187,6 +448,33 @@ impl RsaPublicKey {
     pub fn verify<S: SignatureScheme>(&self, scheme: S, hashed: &[u8], sig: &[u8]) -> Result<()> {
         scheme.verify(self, hashed, sig)
     }
+
+    /// Returns the SHA-256 pin of the key, as used by the `pin-sha256`
+    /// directives of HTTP Public Key Pinning ([RFC7469]) and by Android
+    /// network security configurations: the Base64 encoded SHA-256 digest of
+    /// the DER encoded `SubjectPublicKeyInfo` of the key.
+    ///
+    /// [RFC7469]: https://datatracker.ietf.org/doc/html/rfc7469#section-2.4
+    #[cfg(feature = "fingerprint")]
+    pub fn spki_pin_sha256(&self) -> Result<String> {
+        let der = spki::EncodePublicKey::to_public_key_der(self)?;
+        let digest = sha2::Sha256::digest(der.as_bytes());
+        let mut buf = [0u8; 44];
+        let pin = Base64::encode(&digest, &mut buf).expect("SHA-256 pins are 44 bytes long");
+        Ok(pin.into())
+    }
+
+    /// Check whether `pin` is the SHA-256 pin of the key, as returned by
+    /// [`RsaPublicKey::spki_pin_sha256`].
+    ///
+    /// Surrounding whitespace and the `sha256/` prefix used by some HTTP
+    /// clients are ignored.
+    #[cfg(feature = "fingerprint")]
+    pub fn matches_pin(&self, pin: &str) -> bool {
+        let pin = pin.trim();
+        let pin = pin.strip_prefix("sha256/").unwrap_or(pin);
+        self.spki_pin_sha256().is_ok_and(|expected| expected == pin)
+    }
 }
 
 impl RsaPublicKey {
@@ -196,8 +484,31 @@ impl RsaPublicKey {
     /// Maximum value of the public exponent `e`.
     pub const MAX_PUB_EXPONENT: u64 = (1 << 33) - 1;
 
-    /// Maximum size of the modulus `n` in bits.
-    pub const MAX_SIZE: usize = 4096;
+    /// Maximum size of the modulus `n` in bits of any key handled by the
+    /// crate, set at compile time with the `max-2048`, `max-3072` or
+    /// `max-4096` features. The smallest enabled cap applies, and there is
+    /// no cap without these features.
+    ///
+    /// Larger keys are rejected by key generation, decoding and all
+    /// constructors except [`RsaPublicKey::new_unchecked`], and decoding
+    /// rejects them before allocating their components.
+    pub const SIZE_CAP: usize = if cfg!(feature = "max-2048") {
+        2048
+    } else if cfg!(feature = "max-3072") {
+        3072
+    } else if cfg!(feature = "max-4096") {
+        4096
+    } else {
+        usize::MAX
+    };
+
+    /// Maximum size of the modulus `n` in bits accepted by
+    /// [`RsaPublicKey::new`]: 4096, or [`RsaPublicKey::SIZE_CAP`] if smaller.
+    pub const MAX_SIZE: usize = if Self::SIZE_CAP < 4096 {
+        Self::SIZE_CAP
+    } else {
+        4096
+    };
 
     /// Create a new public key from its components.
     ///
@@ -208,16 +519,47 @@ impl RsaPublicKey {
     }
 
     /// Create a new public key from its components.
+    ///
+    /// Sizes above [`RsaPublicKey::SIZE_CAP`] are rejected regardless of
+    /// `max_size`.
     pub fn new_with_max_size(n: BoxedUint, e: BoxedUint, max_size: usize) -> Result<Self> {
         check_public_with_max_size(&n, &e, max_size)?;
 
         let n_odd = Odd::new(n.clone())
             .into_option()
             .ok_or(Error::InvalidModulus)?;
-        let n_params = BoxedMontyParams::new(n_odd);
-        let n = NonZero::new(n).expect("checked above");
+        let n_params = Arc::new(BoxedMontyParams::new(n_odd));
+        let n = NonZero::new(n).into_option().ok_or(Error::InvalidModulus)?;
+
+        Ok(Self {
+            n,
+            e,
+            n_params,
+            vartime_verify: false,
+        })
+    }
 
-        Ok(Self { n, e, n_params })
+    /// Create a new public key from its components, rejecting moduli which
+    /// are on the given `blocklist`.
+    pub fn new_with_blocklist(
+        n: BoxedUint,
+        e: BoxedUint,
+        blocklist: &(impl KeyBlocklist + ?Sized),
+    ) -> Result<Self> {
+        let key = Self::new(n, e)?;
+        key.check_blocklist(blocklist)?;
+        Ok(key)
+    }
+
+    /// Check that the modulus is not on the given `blocklist`.
+    ///
+    /// Decoded keys are not checked against any blocklist, so call this after
+    /// decoding keys from untrusted sources.
+    pub fn check_blocklist(&self, blocklist: &(impl KeyBlocklist + ?Sized)) -> Result<()> {
+        if blocklist.is_blocked(&self.n) {
+            return Err(Error::BlockedKey);
+        }
+        Ok(())
     }
 
     /// Create a new public key, bypassing checks around the modulus and public
@@ -226,15 +568,111 @@ impl RsaPublicKey {
     /// This method is not recommended, and only intended for unusual use cases.
     /// Most applications should use [`RsaPublicKey::new`] or
     /// [`RsaPublicKey::new_with_max_size`] instead.
-    pub fn new_unchecked(n: BoxedUint, e: BoxedUint) -> Self {
-        let n_odd = Odd::new(n.clone()).expect("n must be odd");
-        let n_params = BoxedMontyParams::new(n_odd);
-        let n = NonZero::new(n).expect("odd numbers are non zero");
+    ///
+    /// Fails with [`Error::InvalidModulus`] if `n` is even, as the key
+    /// operations require an odd modulus.
+    pub fn new_unchecked(n: BoxedUint, e: BoxedUint) -> Result<Self> {
+        let n_odd = Odd::new(n.clone())
+            .into_option()
+            .ok_or(Error::InvalidModulus)?;
+        let n_params = Arc::new(BoxedMontyParams::new(n_odd));
+        let n = NonZero::new(n).into_option().ok_or(Error::InvalidModulus)?;
+
+        Ok(Self {
+            n,
+            e,
+            n_params,
+            vartime_verify: false,
+        })
+    }
+
+    /// Enable or disable variable time arithmetic for verifying signatures
+    /// with this key.
+    ///
+    /// Signatures and public keys are public, so verification does not need
+    /// the constant time exponentiation, and a square-and-multiply on the bits
+    /// of `e` is several times faster for the usual exponents. See
+    /// [`rsa_encrypt_vartime`]. Encryption is not affected and always runs in
+    /// constant time, as the message is secret.
+    ///
+    /// [`rsa_encrypt_vartime`]: crate::hazmat::rsa_encrypt_vartime
+    pub fn set_vartime_verification(&mut self, enabled: bool) {
+        self.vartime_verify = enabled;
+    }
+
+    /// Raw RSA operation recovering the encoded message of the signature `sig`.
+    pub(crate) fn verify_raw(&self, sig: &BoxedUint) -> Result<BoxedUint> {
+        if self.vartime_verify {
+            rsa_encrypt_vartime(self, sig)
+        } else {
+            rsa_encrypt(self, sig)
+        }
+    }
+
+    /// Check the modulus for the fingerprint of keys generated by the
+    /// Infineon RSA library affected by ROCA (CVE-2017-15361).
+    ///
+    /// The primes of affected keys have the form `k·M + (65537^a mod M)`,
+    /// which makes `n mod r` a power of 65537 for every small prime `r` of the
+    /// primorial `M`. Keys with this structure can be factored and should be
+    /// rejected with [`Error::RocaVulnerable`]. The chance of an unaffected key
+    /// matching the fingerprint is negligible.
+    pub fn check_roca(&self) -> Result<()> {
+        let fingerprinted = ROCA_PRIMES.iter().all(|&r| {
+            let residue = rem_u64(&self.n, r);
+            let generator = 65537 % r;
+            let mut power = 1;
+            loop {
+                if power == residue {
+                    return true;
+                }
+                power = power * generator % r;
+                if power == 1 {
+                    return false;
+                }
+            }
+        });
+        if fingerprinted {
+            return Err(Error::RocaVulnerable);
+        }
+        Ok(())
+    }
 
-        Self { n, e, n_params }
+    /// Attempt `rounds` steps of Fermat's factorization method on the modulus.
+    ///
+    /// Fermat's method writes `n = a² - b²` and walks `a` upwards from `⌈√n⌉`,
+    /// so it quickly factors moduli whose primes are much closer together
+    /// than those of properly generated keys. Returns
+    /// [`Error::ClosePrimes`] if the modulus was factored.
+    ///
+    /// A few hundred rounds are cheap and suffice to catch broken key
+    /// generators which derive both primes from the same starting point.
+    pub fn check_close_primes(&self, rounds: u32) -> Result<()> {
+        let bits = 2 * self.n.bits_precision();
+        let n = self.n.widen(bits);
+        let mut a = n.sqrt_vartime();
+        if a.wrapping_mul(&a) < n {
+            a = a.wrapping_add(&BoxedUint::one());
+        }
+        for _ in 0..rounds {
+            let b2 = a.wrapping_mul(&a).wrapping_sub(&n);
+            let b = b2.sqrt_vartime();
+            if b.wrapping_mul(&b) == b2 {
+                return Err(Error::ClosePrimes);
+            }
+            a = a.wrapping_add(&BoxedUint::one());
+        }
+        Ok(())
     }
 }
 
+/// Small primes of the primorial used by the Infineon RSA library, see
+/// [`RsaPublicKey::check_roca`].
+const ROCA_PRIMES: [u64; 38] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157, 163, 167,
+];
+
 impl PublicKeyParts for RsaPrivateKey {
     fn n(&self) -> &NonZero<BoxedUint> {
         &self.pubkey_components.n
@@ -258,6 +696,24 @@ impl RsaPrivateKey {
         Self::new_with_exp(rng, bit_size, BoxedUint::from(Self::EXP))
     }
 
+    /// Generate a new Rsa key pair of the given bit size, drawing randomness
+    /// with [`RngCore::try_fill_bytes`](rand_core::RngCore::try_fill_bytes).
+    ///
+    /// Returns [`Error::Rng`] instead of panicking if `rng` fails.
+    pub fn new_with_fallible_rng<R: CryptoRngCore + ?Sized>(
+        rng: &mut R,
+        bit_size: usize,
+    ) -> Result<RsaPrivateKey> {
+        FallibleRng::run(rng, |rng| Self::new(rng, bit_size))
+    }
+
+    /// Generate a new Rsa key pair of the given bit size using the operating
+    /// system's RNG.
+    #[cfg(feature = "os_rng")]
+    pub fn generate(bit_size: usize) -> Result<RsaPrivateKey> {
+        Self::new(&mut OsRng, bit_size)
+    }
+
     /// Generate a new RSA key pair of the given bit size and the public exponent
     /// using the passed in `rng`.
     ///
@@ -271,6 +727,80 @@ impl RsaPrivateKey {
         RsaPrivateKey::from_components(components.n, components.e, components.d, components.primes)
     }
 
+    /// Deterministically generate a new RSA key pair of the given bit size
+    /// from `seed`, using the default public exponent.
+    ///
+    /// The same seed always yields the same key. The seed, which must be at
+    /// least 32 bytes long, is expanded into the random stream consumed by
+    /// the prime search with Hash_DRBG of [NIST SP 800-90A Rev. 1] with
+    /// SHA-256, instantiated with the seed as entropy input, an empty nonce
+    /// and the personalization string `"rsa keygen seed expansion v2"`. The
+    /// stream is the concatenation of generate requests of 256 bytes each,
+    /// without additional input.
+    ///
+    /// Note that the resulting key also depends on the prime search
+    /// algorithm, including the sieve of `crypto-primes`, so keys are only
+    /// reproducible across versions of this crate and of `crypto-primes`
+    /// which search primes in the same way.
+    ///
+    /// [NIST SP 800-90A Rev. 1]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-90Ar1.pdf
+    ///
+    /// The seed must have as much entropy as the key is supposed to have:
+    /// anybody knowing it can regenerate the private key.
+    #[cfg(feature = "sha2")]
+    pub fn new_from_seed(seed: &[u8], bit_size: usize) -> Result<RsaPrivateKey> {
+        if seed.len() < 32 {
+            return Err(Error::InvalidArguments);
+        }
+        let mut rng = SeededRng::new(seed);
+        Self::new(&mut rng, bit_size)
+    }
+
+    /// Generate a new RSA key pair of the given bit size and public exponent,
+    /// whose factors are primes of the given `strength`.
+    ///
+    /// Note that [`PrimeStrength::Safe`] makes key generation dramatically
+    /// slower.
+    pub fn new_with_prime_strength<R: CryptoRngCore>(
+        rng: &mut R,
+        bit_size: usize,
+        exp: BoxedUint,
+        strength: PrimeStrength,
+    ) -> Result<RsaPrivateKey> {
+        let components = generate_multi_prime_key_with_strength(rng, 2, bit_size, exp, strength)?;
+        RsaPrivateKey::from_components(components.n, components.e, components.d, components.primes)
+    }
+
+    /// Generate a new RSA key pair from provable primes, using the
+    /// Shawe-Taylor construction of FIPS 186-5 Appendix A.1.2 with the hash
+    /// function `D`.
+    ///
+    /// The seed of the construction is twice as long as the security strength
+    /// of the modulus, both `p - 1` and `q - 1` are coprime to `exp`, and
+    /// `|p - q| > 2^(bit_size/2 - 100)`, as FIPS 186-5 A.1.2 requires. The
+    /// private exponent is computed modulo `λ(n)` and exceeds
+    /// `2^(bit_size/2)`, so keys of at least 2048 bits pass
+    /// [`RsaPrivateKey::validate_fips`].
+    ///
+    /// Returns the key together with the construction certificates of `p` and
+    /// `q`, which allow checking the primality of the factors without relying
+    /// on probabilistic tests.
+    pub fn new_provable<D: Digest, R: CryptoRngCore>(
+        rng: &mut R,
+        bit_size: usize,
+        exp: BoxedUint,
+    ) -> Result<(RsaPrivateKey, Vec<PrimeCertificate>)> {
+        let (components, certificates) =
+            generate_provable_key_with_exp::<D, R>(rng, bit_size, exp)?;
+        let key = RsaPrivateKey::from_components(
+            components.n,
+            components.e,
+            components.d,
+            components.primes,
+        )?;
+        Ok((key, certificates))
+    }
+
     /// Constructs an RSA key pair from individual components:
     ///
     /// - `n`: RSA modulus
@@ -279,52 +809,33 @@ impl RsaPrivateKey {
     /// - `primes`: prime factors of `n`: typically two primes `p` and `q`. More than two primes can
     ///   be provided for multiprime RSA, however this is generally not recommended. If no `primes`
     ///   are provided, a prime factor recovery algorithm will be employed to attempt to recover the
-    ///   factors (as described in [NIST SP 800-56B Revision 2] Appendix C.2). This algorithm only
-    ///   works if there are just two prime factors `p` and `q` (as opposed to multiprime), and `e`
-    ///   is between 2^16 and 2^256.
+    ///   factors (as described in [NIST SP 800-56B Revision 2] Appendix C). This algorithm only
+    ///   works if there are just two prime factors `p` and `q` (as opposed to multiprime).
     ///
     ///  [NIST SP 800-56B Revision 2]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-56Br2.pdf
     pub fn from_components(
         n: Odd<BoxedUint>,
         e: BoxedUint,
         d: BoxedUint,
-        mut primes: Vec<BoxedUint>,
+        primes: Vec<BoxedUint>,
     ) -> Result<RsaPrivateKey> {
-        let n_params = BoxedMontyParams::new(n.clone());
-        let n_c = NonZero::new(n.get())
-            .into_option()
-            .ok_or(Error::InvalidModulus)?;
-
-        match primes.len() {
-            0 => {
-                // Recover `p` and `q` from `d`.
-                // See method in Appendix C.2: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-56Br2.pdf
-                let (p, q) = recover_primes(&n_c, &e, &d)?;
-                primes.push(p);
-                primes.push(q);
-            }
-            1 => return Err(Error::NprimesTooSmall),
-            _ => {}
-        }
-
-        let mut k = RsaPrivateKey {
-            pubkey_components: RsaPublicKey {
-                n: n_c,
-                e,
-                n_params,
-            },
-            d,
-            primes,
-            precomputed: None,
-        };
-
-        // Alaways validate the key, to ensure precompute can't fail
-        k.validate()?;
-
-        // Precompute when possible, ignore error otherwise.
-        k.precompute().ok();
+        Self::from_components_with_options(n, e, d, primes, ImportOptions::new())
+    }
 
-        Ok(k)
+    /// Constructs an RSA key pair from individual components like
+    /// [`RsaPrivateKey::from_components`], adjusting the key as configured by
+    /// `options`.
+    pub fn from_components_with_options(
+        n: Odd<BoxedUint>,
+        e: BoxedUint,
+        d: BoxedUint,
+        primes: Vec<BoxedUint>,
+        options: ImportOptions,
+    ) -> Result<RsaPrivateKey> {
+        RsaPrivateKeyBuilder::new(n, e, d)
+            .primes(primes)
+            .options(options)
+            .build()
     }
 
     /// Constructs an RSA key pair from its two primes p and q.
@@ -344,7 +855,7 @@ impl RsaPrivateKey {
 
         let d = compute_private_exponent_carmicheal(&p, &q, &public_exponent)?;
         let primes = vec![p, q];
-        let n = compute_modulus(&primes);
+        let n = compute_modulus(&primes)?;
 
         Self::from_components(n, public_exponent, d, primes)
     }
@@ -369,12 +880,38 @@ impl RsaPrivateKey {
             }
         }
 
-        let n = compute_modulus(&primes);
+        let n = compute_modulus(&primes)?;
         let d = compute_private_exponent_euler_totient(&primes, &public_exponent)?;
 
         Self::from_components(n, public_exponent, d, primes)
     }
 
+    /// Constructs a key from components validated beforehand, without checking
+    /// them or computing the CRT values.
+    #[cfg(feature = "sealing")]
+    pub(crate) fn from_validated_components(
+        pubkey_components: RsaPublicKey,
+        d: BoxedUint,
+        primes: Vec<BoxedUint>,
+    ) -> RsaPrivateKey {
+        RsaPrivateKey {
+            pubkey_components,
+            d: SecretBox::new(d),
+            primes: SecretBox::new(primes),
+            precomputed: PrecomputedCell::default(),
+            blinding: None,
+        }
+    }
+
+    /// Returns a [`Debug`](fmt::Debug) wrapper printing all the components of
+    /// the key, including the private ones, unlike the [`Debug`](fmt::Debug)
+    /// implementation of the key itself.
+    ///
+    /// The output reveals the private key, so it must not end up in logs.
+    pub fn debug_full(&self) -> impl fmt::Debug + '_ {
+        FullDebug(self)
+    }
+
     /// Get the public key from the private key, cloning `n` and `e`.
     ///
     /// Generally this is not needed since `RsaPrivateKey` implements the `PublicKey` trait,
@@ -384,34 +921,31 @@ impl RsaPrivateKey {
     }
 
     /// Performs some calculations to speed up private key operations.
+    ///
+    /// With the `std` feature, private key operations also perform them on
+    /// first use if needed, so calling this is only required to surface errors
+    /// or to avoid the cost on the first operation.
     pub fn precompute(&mut self) -> Result<()> {
-        if self.precomputed.is_some() {
-            return Ok(());
+        if self.precomputed.get().is_none() {
+            let values = self.compute_precomputed()?;
+            self.precomputed.set(values);
         }
+        Ok(())
+    }
+
+    /// Returns the precomputed values, computing them on first use if possible.
+    fn precomputed(&self) -> Option<&PrecomputedValues> {
+        self.precomputed
+            .get_or_compute(|| self.compute_precomputed())
+    }
 
+    fn compute_precomputed(&self) -> Result<PrecomputedValues> {
         let d = &self.d;
         let bits = d.bits_precision();
-        let p = self.primes[0].widen(bits);
-        let q = self.primes[1].widen(bits);
-
-        let p_odd = Odd::new(p.clone())
-            .into_option()
-            .ok_or(Error::InvalidPrime)?;
-        let p_params = BoxedMontyParams::new(p_odd);
-        let q_odd = Odd::new(q.clone())
-            .into_option()
-            .ok_or(Error::InvalidPrime)?;
-        let q_params = BoxedMontyParams::new(q_odd);
-
-        let x = NonZero::new(p.wrapping_sub(&BoxedUint::one()))
-            .into_option()
-            .ok_or(Error::InvalidPrime)?;
-        let dp = d.rem_vartime(&x);
-
-        let x = NonZero::new(q.wrapping_sub(&BoxedUint::one()))
-            .into_option()
-            .ok_or(Error::InvalidPrime)?;
-        let dq = d.rem_vartime(&x);
+        let (p_params, q_params) = self.crt_params()?;
+        let p = p_params.modulus().as_ref().clone();
+        let q = q_params.modulus().as_ref().clone();
+        let (dp, dq) = self.crt_exponents(&p, &q)?;
 
         let qinv = BoxedMontyForm::new(q.clone(), p_params.clone());
         let qinv = qinv.invert().into_option().ok_or(Error::InvalidPrime)?;
@@ -422,20 +956,150 @@ impl RsaPrivateKey {
         debug_assert_eq!(p_params.bits_precision(), bits);
         debug_assert_eq!(q_params.bits_precision(), bits);
 
-        self.precomputed = Some(PrecomputedValues {
-            dp,
-            dq,
-            qinv,
-            p_params,
-            q_params,
-        });
+        Ok(PrecomputedValues {
+            dp: SecretBox::new(dp),
+            dq: SecretBox::new(dq),
+            qinv: SecretBox::new(qinv),
+            p_params: SecretBox::new(Arc::new(p_params)),
+            q_params: SecretBox::new(Arc::new(q_params)),
+            window: None,
+        })
+    }
+
+    /// Montgomery parameters for the first two primes, at the precision of `d`.
+    /// Computes `d mod (p - 1)` and `d mod (q - 1)`.
+    fn crt_exponents(&self, p: &BoxedUint, q: &BoxedUint) -> Result<(BoxedUint, BoxedUint)> {
+        let exponent = |prime: &BoxedUint| {
+            NonZero::new(prime.wrapping_sub(&BoxedUint::one()))
+                .into_option()
+                .map(|x| self.d.rem_vartime(&x))
+                .ok_or(Error::InvalidPrime)
+        };
+        Ok((exponent(p)?, exponent(q)?))
+    }
+
+    fn crt_params(&self) -> Result<(BoxedMontyParams, BoxedMontyParams)> {
+        if self.primes.len() < 2 {
+            return Err(Error::TooFewPrimes);
+        }
+        let bits = self.d.bits_precision();
+        let params = |prime: &BoxedUint| {
+            if prime.bits() > bits {
+                return Err(Error::InvalidPrime);
+            }
+            Odd::new(resize(prime, bits))
+                .into_option()
+                .map(BoxedMontyParams::new)
+                .ok_or(Error::InvalidPrime)
+        };
+        Ok((params(&self.primes[0])?, params(&self.primes[1])?))
+    }
+
+    /// Export the precomputed CRT values, if any.
+    ///
+    /// The encoding is `dp || dq || qinv`, each big-endian and padded to the
+    /// byte length of `d`. It can be stored next to the key and restored with
+    /// [`RsaPrivateKey::precompute_from_bytes`] to skip [`RsaPrivateKey::precompute`]
+    /// when loading the key again. The [`ExponentWindow`] is not included.
+    ///
+    /// The CRT values reveal the factorization of `n`, so they must be
+    /// protected like the key itself.
+    pub fn precomputed_to_bytes(&self) -> Option<Zeroizing<Vec<u8>>> {
+        let precomputed = self.precomputed.get()?;
+        let mut bytes = Zeroizing::new(Vec::new());
+        bytes.extend_from_slice(&Zeroizing::new(precomputed.dp.to_be_bytes()));
+        bytes.extend_from_slice(&Zeroizing::new(precomputed.dq.to_be_bytes()));
+        bytes.extend_from_slice(&Zeroizing::new(precomputed.qinv.retrieve().to_be_bytes()));
+        Some(bytes)
+    }
+
+    /// Restore CRT values exported by [`RsaPrivateKey::precomputed_to_bytes`],
+    /// replacing any precomputed values.
+    ///
+    /// This checks that `dp = d mod (p - 1)`, `dq = d mod (q - 1)` and that
+    /// `qinv` is the inverse of `q` modulo `p`, which is cheaper than
+    /// computing the inverse. Values that do not belong to this key are
+    /// rejected with [`Error::InvalidArguments`] or
+    /// [`Error::InvalidCoefficient`].
+    pub fn precompute_from_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        let bits = self.d.bits_precision();
+        let len = bits as usize / 8;
+        if bytes.len() != 3 * len {
+            return Err(Error::InvalidArguments);
+        }
+        let (p_params, q_params) = self.crt_params()?;
+        let p = p_params.modulus().as_ref();
+        let q = q_params.modulus().as_ref();
+
+        let mut values = bytes.chunks_exact(len);
+        let mut next = || -> Result<BoxedUint> {
+            let chunk = values.next().ok_or(Error::InvalidArguments)?;
+            Ok(BoxedUint::from_be_slice(chunk, bits)?)
+        };
+        let dp = next()?;
+        let dq = next()?;
+        let qinv = Zeroizing::new(next()?);
+        if &dp >= p || &dq >= q || &*qinv >= p {
+            return Err(Error::InvalidArguments);
+        }
+        let (expected_dp, expected_dq) = self.crt_exponents(p, q)?;
+        if !bool::from(dp.ct_eq(&expected_dp) & dq.ct_eq(&expected_dq)) {
+            return Err(Error::InvalidArguments);
+        }
+
+        let qinv = BoxedMontyForm::new(qinv.as_ref().clone(), p_params.clone());
+        let q_mod_p = BoxedMontyForm::new(q.clone(), p_params.clone());
+        if !bool::from((qinv.clone() * q_mod_p).retrieve().is_one()) {
+            return Err(Error::InvalidCoefficient);
+        }
 
+        let window = self.precomputed.get().and_then(|p| p.window);
+        self.precomputed.set(PrecomputedValues {
+            dp: SecretBox::new(dp),
+            dq: SecretBox::new(dq),
+            qinv: SecretBox::new(qinv),
+            p_params: SecretBox::new(Arc::new(p_params)),
+            q_params: SecretBox::new(Arc::new(q_params)),
+            window,
+        });
         Ok(())
     }
 
     /// Clears precomputed values by setting to None
+    ///
+    /// With the `std` feature, they are computed again by the next private key
+    /// operation.
+    ///
+    /// This also resets the [`ExponentWindow`] to the default.
     pub fn clear_precomputed(&mut self) {
-        self.precomputed = None;
+        self.precomputed.clear();
+    }
+
+    /// Set the window size of the exponentiations of private key operations
+    /// using CRT values, precomputing them if necessary.
+    ///
+    /// Larger windows trade memory and a table setup per operation for fewer
+    /// multiplications, which pays off for large keys.
+    pub fn set_exponent_window(&mut self, window: ExponentWindow) -> Result<()> {
+        let prime_bits = self.primes.first().map_or(0, |p| p.bits());
+        let window = window.bits(prime_bits)?;
+        self.precompute()?;
+        if let Some(precomputed) = self.precomputed.get_mut() {
+            precomputed.window = window;
+        }
+        Ok(())
+    }
+
+    /// Enable or disable caching of blinding factors.
+    ///
+    /// By default every blinded private key operation generates and inverts a
+    /// fresh random blinding factor. With the cache enabled, the blinding
+    /// factors are instead updated by squaring between operations and only
+    /// regenerated from the RNG every [`BlindingCache::MAX_USES`] operations,
+    /// which saves a modular inversion and exponentiation per operation.
+    #[cfg(feature = "std")]
+    pub fn set_blinding_cache(&mut self, enabled: bool) {
+        self.blinding = enabled.then(BlindingCache::new);
     }
 
     /// Compute CRT coefficient: `(1/q) mod p`.
@@ -446,36 +1110,191 @@ impl RsaPrivateKey {
         Option::from(q.inv_mod(p))
     }
 
-    /// Performs basic sanity checks on the key.
-    /// Returns `Ok(())` if everything is good, otherwise an appropriate error.
-    pub fn validate(&self) -> Result<()> {
-        check_public(self)?;
+    /// Split the key into `count` verifiable Shamir shares, any `threshold`
+    /// of which can recover it with [`RsaPrivateKey::from_shares`].
+    ///
+    /// Only keys with two primes are supported.
+    #[cfg(feature = "sha2")]
+    pub fn split_shares<R: CryptoRngCore>(
+        &self,
+        threshold: usize,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<KeyShare>> {
+        shares::split(self, threshold, count, rng)
+    }
 
-        // Check that Πprimes == n.
-        let mut m = BoxedUint::one_with_precision(self.pubkey_components.n.bits_precision());
-        let one = BoxedUint::one();
+    /// Recover a key from shares created by [`RsaPrivateKey::split_shares`].
+    ///
+    /// All shares are verified before recombination. The private exponent is
+    /// recomputed from the primes using Euler's totient.
+    #[cfg(feature = "sha2")]
+    pub fn from_shares(shares: &[KeyShare]) -> Result<RsaPrivateKey> {
+        shares::combine(shares)
+    }
+
+    /// Compute Euler's totient `φ(n) = Π(p_i - 1)`, the order of the
+    /// multiplicative group modulo `n`.
+    pub(crate) fn totient(&self) -> Result<NonZero<BoxedUint>> {
+        let bits = self.pubkey_components.n.bits_precision();
+        let one = BoxedUint::one_with_precision(bits);
+        let mut phi = one.clone();
         for prime in &self.primes {
-            // Any primes ≤ 1 will cause divide-by-zero panics later.
-            if prime < &one {
-                return Err(Error::InvalidPrime);
-            }
-            m = m.wrapping_mul(prime);
+            let prime = if prime.bits_precision() < bits {
+                prime.widen(bits)
+            } else {
+                prime.clone()
+            };
+            phi = phi.wrapping_mul(&prime.wrapping_sub(&one));
         }
-        if m != *self.pubkey_components.n {
+        NonZero::new(phi).into_option().ok_or(Error::InvalidPrime)
+    }
+
+    /// Compute the Carmichael function `λ(n) = lcm(p_i - 1)`, the exponent of
+    /// the multiplicative group modulo `n`.
+    pub(crate) fn lambda(&self) -> Result<NonZero<BoxedUint>> {
+        let bits = self.pubkey_components.n.bits_precision();
+        let one = BoxedUint::one_with_precision(bits);
+        let mut lambda = Zeroizing::new(one.clone());
+        for prime in &self.primes {
+            let prime_minus_one = Zeroizing::new(resize(prime, bits).wrapping_sub(&one));
+            let gcd = NonZero::new(lambda.gcd(&prime_minus_one))
+                .into_option()
+                .ok_or(Error::InvalidPrime)?;
+            let quotient = Zeroizing::new((*lambda).clone() / gcd);
+            lambda = Zeroizing::new(quotient.wrapping_mul(&prime_minus_one));
+        }
+        NonZero::new((*lambda).clone())
+            .into_option()
+            .ok_or(Error::InvalidPrime)
+    }
+
+    /// Validates the key with the checks of the given [`ValidationLevel`].
+    /// Returns `Ok(())` if everything is good, otherwise an appropriate error.
+    pub fn validate_with(&self, level: ValidationLevel) -> Result<()> {
+        match level {
+            ValidationLevel::Basic => {
+                check_public(self)?;
+                if self.primes.len() < 2 {
+                    return Err(Error::NprimesTooSmall);
+                }
+                let one = BoxedUint::one();
+                if self.primes.iter().any(|prime| prime <= &one) {
+                    return Err(Error::InvalidPrime);
+                }
+                if self.d.is_zero().into() || *self.d >= *self.pubkey_components.n {
+                    return Err(Error::InvalidExponent);
+                }
+                Ok(())
+            }
+            ValidationLevel::Partial => self.validate(),
+            ValidationLevel::Full => {
+                self.validate()?;
+                if !self.primes.iter().all(is_prime_vartime) {
+                    return Err(Error::InvalidPrime);
+                }
+                self.pubkey_components
+                    .check_close_primes(ValidationLevel::FERMAT_ROUNDS)
+            }
+        }
+    }
+
+    /// Performs basic sanity checks on the key.
+    /// Returns `Ok(())` if everything is good, otherwise an appropriate error.
+    pub fn validate(&self) -> Result<()> {
+        check_public(self)?;
+        self.check_modulus()?;
+        self.check_private_exponent()
+    }
+
+    /// Runs all checks of [`ValidationLevel::Full`] and
+    /// [`RsaPublicKey::check_roca`], and collects every problem found instead
+    /// of stopping at the first one.
+    ///
+    /// Keys weaker than the default [`KeyPolicy`] are reported with
+    /// [`DefectSeverity::Warning`]. An empty report means the key is valid.
+    pub fn validate_report(&self) -> Vec<KeyDefect> {
+        let mut defects = Vec::new();
+        let mut report = |severity, result: Result<()>| {
+            if let Err(error) = result {
+                defects.push(KeyDefect { severity, error });
+            }
+        };
+
+        report(DefectSeverity::Critical, check_public(self));
+
+        report(DefectSeverity::Warning, KeyPolicy::new().check(self));
+
+        if self.primes.len() < 2 {
+            report(DefectSeverity::Critical, Err(Error::NprimesTooSmall));
+        }
+        let one = BoxedUint::one();
+        if self.primes.iter().any(|prime| prime <= &one) {
+            // Checks involving the primes would divide by zero.
+            report(DefectSeverity::Critical, Err(Error::InvalidPrime));
+        } else {
+            report(DefectSeverity::Critical, self.check_modulus());
+            report(DefectSeverity::Critical, self.check_private_exponent());
+            if !self.primes.iter().all(is_prime_vartime) {
+                report(DefectSeverity::Critical, Err(Error::InvalidPrime));
+            }
+        }
+
+        report(
+            DefectSeverity::Critical,
+            self.pubkey_components
+                .check_close_primes(ValidationLevel::FERMAT_ROUNDS),
+        );
+        report(
+            DefectSeverity::Critical,
+            self.pubkey_components.check_roca(),
+        );
+
+        defects
+    }
+
+    /// Check that Πprimes == n.
+    fn check_modulus(&self) -> Result<()> {
+        let mut m = BoxedUint::one_with_precision(self.pubkey_components.n.bits_precision());
+        let one = BoxedUint::one();
+        for prime in &self.primes {
+            // Any primes ≤ 1 will cause divide-by-zero panics later.
+            if prime <= &one {
+                return Err(Error::InvalidPrime);
+            }
+            m = m.wrapping_mul(prime);
+        }
+        if m != *self.pubkey_components.n {
             return Err(Error::InvalidModulus);
         }
 
-        // Check that de ≡ 1 mod p-1, for each prime.
+        Ok(())
+    }
+
+    /// Check that de ≡ 1 mod p-1, for each prime.
+    fn check_private_exponent(&self) -> Result<()> {
         // This implies that e is coprime to each p-1 as e has a multiplicative
         // inverse. Therefore e is coprime to lcm(p-1,q-1,r-1,...) =
         // exponent(ℤ/nℤ). It also implies that a^de ≡ a mod p as a^(p-1) ≡ 1
         // mod p. Thus a^de ≡ a mod n for all a coprime to n, as required.
-        let d = self.d.widen(2 * self.d.bits_precision());
-        let de = d.wrapping_mul(&self.pubkey_components.e);
+        let bits = self
+            .primes
+            .iter()
+            .map(BoxedUint::bits_precision)
+            .chain([
+                self.d.bits_precision(),
+                self.pubkey_components.e.bits_precision(),
+            ])
+            .max()
+            .unwrap_or_default();
+        let d = resize(&self.d, 2 * bits);
+        let de = d.wrapping_mul(&resize(&self.pubkey_components.e, 2 * bits));
 
         for prime in &self.primes {
-            let prime = prime.widen(d.bits_precision());
-            let x = NonZero::new(prime.wrapping_sub(&BoxedUint::one())).unwrap();
+            let prime = resize(prime, 2 * bits);
+            let x = NonZero::new(prime.wrapping_sub(&BoxedUint::one()))
+                .into_option()
+                .ok_or(Error::InvalidPrime)?;
             let congruence = de.rem_vartime(&x);
             if !bool::from(congruence.is_one()) {
                 return Err(Error::InvalidExponent);
@@ -485,6 +1304,108 @@ impl RsaPrivateKey {
         Ok(())
     }
 
+    /// Performs the key-pair validation of NIST SP 800-56B §6.4.1.2, which is
+    /// considerably stricter than [`RsaPrivateKey::validate`].
+    ///
+    /// In addition to the basic checks, the key must have exactly two primes
+    /// and an even modulus length `nlen` of at least 2048 bits, and
+    /// `2^16 < e < 2^256`. Both primes must be probable primes of `nlen / 2`
+    /// bits, at least `√2·2^(nlen/2 - 1)` and more than `2^(nlen/2 - 100)`
+    /// apart. The private exponent must satisfy `2^(nlen/2) < d < LCM(p-1, q-1)`
+    /// and `de ≡ 1 mod LCM(p-1, q-1)`, and precomputed CRT values must match
+    /// the key. Finally a pairwise encrypt/decrypt consistency test is run.
+    ///
+    /// Only keys whose private exponent is reduced modulo `λ(n) = LCM(p-1,
+    /// q-1)` pass, such as keys generated with [`PrimeStrength::Fips186`] or
+    /// by [`RsaPrivateKey::new_provable`]. [`RsaPrivateKey::new`] computes
+    /// `d` modulo `φ(n)`, which exceeds `λ(n)` for about half of the keys:
+    /// re-import those with [`ImportOptions::normalize_d`] (or
+    /// [`ImportOptions::recompute_d`]) to reduce `d` first.
+    pub fn validate_fips(&self) -> Result<()> {
+        self.validate()?;
+
+        if self.primes.len() != 2 {
+            return Err(Error::InvalidPrime);
+        }
+
+        let nlen = self.pubkey_components.n.bits();
+        if nlen < 2048 || nlen % 2 != 0 {
+            return Err(Error::InvalidModulus);
+        }
+        let half = nlen / 2;
+
+        // The upper bound on `e` is already enforced by `check_public`.
+        if self.pubkey_components.e.bits() <= 16 {
+            return Err(Error::PublicExponentTooSmall);
+        }
+
+        let bits = 2 * self.pubkey_components.n.bits_precision();
+        let p = resize(&self.primes[0], bits);
+        let q = resize(&self.primes[1], bits);
+
+        // p, q ≥ √2·2^(nlen/2 - 1) is equivalent to p², q² ≥ 2^(nlen - 1).
+        let lower = pow2(nlen - 1, bits);
+        for prime in [&p, &q] {
+            if prime.bits() != half || prime.wrapping_mul(prime) < lower || !is_prime_vartime(prime)
+            {
+                return Err(Error::InvalidPrime);
+            }
+        }
+
+        let diff = if p > q {
+            p.wrapping_sub(&q)
+        } else {
+            q.wrapping_sub(&p)
+        };
+        if diff <= pow2(half - 100, bits) {
+            return Err(Error::InvalidPrime);
+        }
+
+        let one = BoxedUint::one_with_precision(bits);
+        let p1 = p.wrapping_sub(&one);
+        let q1 = q.wrapping_sub(&one);
+        let gcd = NonZero::new(p1.gcd(&q1))
+            .into_option()
+            .ok_or(Error::InvalidPrime)?;
+        let lcm = (p1.clone() / gcd).wrapping_mul(&q1);
+        let lcm = NonZero::new(lcm).into_option().ok_or(Error::InvalidPrime)?;
+
+        let d = resize(&self.d, bits);
+        if d <= pow2(half, bits) || d >= *lcm {
+            return Err(Error::InvalidExponent);
+        }
+        let e = resize(&self.pubkey_components.e, bits);
+        if !bool::from(d.wrapping_mul(&e).rem_vartime(&lcm).is_one()) {
+            return Err(Error::InvalidExponent);
+        }
+
+        if let Some(precomputed) = self.precomputed.get() {
+            let p1 = NonZero::new(p1).into_option().ok_or(Error::InvalidPrime)?;
+            let q1 = NonZero::new(q1).into_option().ok_or(Error::InvalidPrime)?;
+            if d.rem_vartime(&p1) != resize(&precomputed.dp, bits)
+                || d.rem_vartime(&q1) != resize(&precomputed.dq, bits)
+            {
+                return Err(Error::InvalidExponent);
+            }
+
+            let p = NonZero::new(p).into_option().ok_or(Error::InvalidPrime)?;
+            let qinv = resize(&precomputed.qinv.retrieve(), bits);
+            if !bool::from(qinv.wrapping_mul(&q).rem_vartime(&p).is_one()) {
+                return Err(Error::InvalidCoefficient);
+            }
+        }
+
+        // Pairwise consistency test on a fixed message.
+        let m = BoxedUint::from(0x5253_4150_4354_0001u64)
+            .widen(self.pubkey_components.n.bits_precision());
+        let c = rsa_encrypt(self, &m)?;
+        if rsa_decrypt_and_check::<DummyRng>(self, None, &c)? != m {
+            return Err(Error::PairwiseConsistencyFailed);
+        }
+
+        Ok(())
+    }
+
     /// Decrypt the given message.
     pub fn decrypt<P: PaddingScheme>(&self, padding: P, ciphertext: &[u8]) -> Result<Vec<u8>> {
         padding.decrypt(Option::<&mut DummyRng>::None, self, ciphertext)
@@ -502,11 +1423,71 @@ impl RsaPrivateKey {
         padding.decrypt(Some(rng), self, ciphertext)
     }
 
+    /// Decrypt the given message into a buffer which is wiped when dropped.
+    pub fn decrypt_zeroizing<P: PaddingScheme>(
+        &self,
+        padding: P,
+        ciphertext: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>> {
+        self.decrypt(padding, ciphertext).map(Zeroizing::new)
+    }
+
+    /// Decrypt the given message into a buffer which is wiped when dropped.
+    ///
+    /// Uses `rng` to blind the decryption process.
+    pub fn decrypt_blinded_zeroizing<R: CryptoRngCore, P: PaddingScheme>(
+        &self,
+        rng: &mut R,
+        padding: P,
+        ciphertext: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>> {
+        self.decrypt_blinded(rng, padding, ciphertext)
+            .map(Zeroizing::new)
+    }
+
+    /// Decrypt the given message into the start of `out`, returning the
+    /// length of the plaintext.
+    pub fn decrypt_into<P: PaddingScheme>(
+        &self,
+        padding: P,
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        padding.decrypt_into(Option::<&mut DummyRng>::None, self, ciphertext, out)
+    }
+
+    /// Decrypt the given message into the start of `out`, returning the
+    /// length of the plaintext.
+    ///
+    /// Uses `rng` to blind the decryption process.
+    pub fn decrypt_blinded_into<R: CryptoRngCore, P: PaddingScheme>(
+        &self,
+        rng: &mut R,
+        padding: P,
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        padding.decrypt_into(Some(rng), self, ciphertext, out)
+    }
+
     /// Sign the given digest.
     pub fn sign<S: SignatureScheme>(&self, padding: S, digest_in: &[u8]) -> Result<Vec<u8>> {
         padding.sign(Option::<&mut DummyRng>::None, self, digest_in)
     }
 
+    /// Sign the given digest into the start of `out`, returning the length of
+    /// the signature.
+    ///
+    /// `out` must hold at least [`PublicKeyParts::size`] bytes.
+    pub fn sign_into<S: SignatureScheme>(
+        &self,
+        padding: S,
+        digest_in: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        padding.sign_into(Option::<&mut DummyRng>::None, self, digest_in, out)
+    }
+
     /// Sign the given digest using the provided `rng`, which is used in the
     /// following ways depending on the [`SignatureScheme`]:
     ///
@@ -525,6 +1506,78 @@ impl RsaPrivateKey {
     ) -> Result<Vec<u8>> {
         padding.sign(Some(rng), self, digest_in)
     }
+
+    /// Sign the given digest like [`RsaPrivateKey::sign_with_rng`], drawing
+    /// randomness with [`RngCore::try_fill_bytes`](rand_core::RngCore::try_fill_bytes).
+    ///
+    /// Returns [`Error::Rng`] instead of panicking if `rng` fails.
+    pub fn sign_with_fallible_rng<R: CryptoRngCore + ?Sized, S: SignatureScheme>(
+        &self,
+        rng: &mut R,
+        padding: S,
+        digest_in: &[u8],
+    ) -> Result<Vec<u8>> {
+        FallibleRng::run(rng, |rng| self.sign_with_rng(rng, padding, digest_in))
+    }
+
+    /// Sign the given digest using the operating system's RNG, for blinding
+    /// and salts as described in [`RsaPrivateKey::sign_with_rng`].
+    #[cfg(feature = "os_rng")]
+    pub fn sign_with_os_rng<S: SignatureScheme>(
+        &self,
+        padding: S,
+        digest_in: &[u8],
+    ) -> Result<Vec<u8>> {
+        self.sign_with_rng(&mut OsRng, padding, digest_in)
+    }
+
+    /// Sign the given digest into the start of `out` using the provided
+    /// `rng`, returning the length of the signature.
+    ///
+    /// See [`RsaPrivateKey::sign_with_rng`] for the uses of `rng`.
+    pub fn sign_with_rng_into<R: CryptoRngCore, S: SignatureScheme>(
+        &self,
+        rng: &mut R,
+        padding: S,
+        digest_in: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        padding.sign_into(Some(rng), self, digest_in, out)
+    }
+
+    /// Run a pairwise consistency test, as required of FIPS 140 modules after
+    /// key generation and at power-on.
+    ///
+    /// A fixed message is signed and verified with PKCS#1 v1.5, and encrypted
+    /// and decrypted with PKCS#1 v1.5 padding. Both operations use `rng` for
+    /// blinding. Returns [`Error::PairwiseConsistencyFailed`] if any step
+    /// fails or the round trip does not reproduce the message.
+    pub fn pairwise_consistency_test<R: CryptoRngCore>(&self, rng: &mut R) -> Result<()> {
+        const MESSAGE: &[u8] = b"rsa pairwise consistency test";
+
+        let public_key = self.to_public_key();
+        let signature = self
+            .sign_with_rng(rng, Pkcs1v15Sign::new_unprefixed(), MESSAGE)
+            .map_err(|_| Error::PairwiseConsistencyFailed)?;
+        public_key
+            .verify(Pkcs1v15Sign::new_unprefixed(), MESSAGE, &signature)
+            .map_err(|_| Error::PairwiseConsistencyFailed)?;
+
+        let ciphertext = public_key
+            .encrypt(rng, Pkcs1v15Encrypt, MESSAGE)
+            .map_err(|_| Error::PairwiseConsistencyFailed)?;
+        if ciphertext == MESSAGE {
+            return Err(Error::PairwiseConsistencyFailed);
+        }
+        let plaintext = self
+            .decrypt_blinded(rng, Pkcs1v15Encrypt, &ciphertext)
+            .map_err(|_| Error::PairwiseConsistencyFailed)?;
+        if plaintext != MESSAGE {
+            return Err(Error::PairwiseConsistencyFailed);
+        }
+
+        Ok(())
+    }
 }
 
 impl PrivateKeyParts for RsaPrivateKey {
@@ -537,15 +1590,15 @@ impl PrivateKeyParts for RsaPrivateKey {
     }
 
     fn dp(&self) -> Option<&BoxedUint> {
-        self.precomputed.as_ref().map(|p| &p.dp)
+        self.precomputed().map(|p| &*p.dp)
     }
 
     fn dq(&self) -> Option<&BoxedUint> {
-        self.precomputed.as_ref().map(|p| &p.dq)
+        self.precomputed().map(|p| &*p.dq)
     }
 
     fn qinv(&self) -> Option<&BoxedMontyForm> {
-        self.precomputed.as_ref().map(|p| &p.qinv)
+        self.precomputed().map(|p| &*p.qinv)
     }
 
     fn crt_values(&self) -> Option<&[CrtValue]> {
@@ -553,11 +1606,362 @@ impl PrivateKeyParts for RsaPrivateKey {
     }
 
     fn p_params(&self) -> Option<&BoxedMontyParams> {
-        self.precomputed.as_ref().map(|p| &p.p_params)
+        self.precomputed().map(|p| &**p.p_params)
     }
 
     fn q_params(&self) -> Option<&BoxedMontyParams> {
-        self.precomputed.as_ref().map(|p| &p.q_params)
+        self.precomputed().map(|p| &**p.q_params)
+    }
+
+    fn exp_window(&self) -> Option<u32> {
+        self.precomputed.get().and_then(|p| p.window)
+    }
+
+    #[cfg(feature = "std")]
+    fn blinding_cache(&self) -> Option<&BlindingCache> {
+        self.blinding.as_ref()
+    }
+}
+
+#[cfg(not(feature = "hazmat"))]
+impl PrivateKeyOps for RsaPrivateKey {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        rsa_decrypt_and_check(self, rng, c)
+    }
+}
+
+#[cfg(feature = "hazmat")]
+impl RsaPrivateOps for RsaPrivateKey {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        rsa_decrypt_and_check(self, rng, c)
+    }
+}
+
+/// Window size of the exponentiations of private key operations using CRT
+/// values, see [`RsaPrivateKey::set_exponent_window`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExponentWindow {
+    /// Use the exponentiation of `crypto-bigint`.
+    #[default]
+    Backend,
+
+    /// Use a fixed window picked from the size of the primes.
+    Auto,
+
+    /// Use a fixed window of the given number of bits, from 1 to 8.
+    Fixed(u32),
+}
+
+impl ExponentWindow {
+    /// Resolve the window size for primes of `prime_bits` bits.
+    fn bits(self, prime_bits: u32) -> Result<Option<u32>> {
+        match self {
+            ExponentWindow::Backend => Ok(None),
+            ExponentWindow::Auto => Ok(Some(match prime_bits {
+                0..=512 => 4,
+                513..=1024 => 5,
+                _ => 6,
+            })),
+            ExponentWindow::Fixed(bits @ 1..=8) => Ok(Some(bits)),
+            ExponentWindow::Fixed(_) => Err(Error::InvalidArguments),
+        }
+    }
+}
+
+/// Options for importing keys with
+/// [`RsaPrivateKey::from_components_with_options`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ImportOptions {
+    normalize_d: bool,
+    recompute_d: bool,
+}
+
+impl ImportOptions {
+    /// Create the default options, which import the components unchanged.
+    pub const fn new() -> Self {
+        Self {
+            normalize_d: false,
+            recompute_d: false,
+        }
+    }
+
+    /// Replace the private exponent `d` with `d mod λ(n)`.
+    ///
+    /// Some generators compute `d` modulo `φ(n)`, which can make it larger
+    /// than necessary. Reducing it speeds up private key operations without
+    /// CRT values, and gives every key a canonical private exponent.
+    pub const fn normalize_d(mut self, normalize_d: bool) -> Self {
+        self.normalize_d = normalize_d;
+        self
+    }
+
+    /// Recompute the private exponent `d` from the primes and `e` if the
+    /// provided one is inconsistent with them, instead of failing.
+    ///
+    /// The recomputed exponent is `e^-1 mod λ(n)`. This allows importing
+    /// damaged or nonstandard keys, such as JWKs with a mismatched `d`, as
+    /// long as their primes are present and correct.
+    pub const fn recompute_d(mut self, recompute_d: bool) -> Self {
+        self.recompute_d = recompute_d;
+        self
+    }
+}
+
+/// Builder for constructing [`RsaPrivateKey`]s from individual components,
+/// controlling the work done on import.
+///
+/// The defaults match [`RsaPrivateKey::from_components`]: missing primes are
+/// recovered, the key is checked with [`ValidationLevel::Partial`] and the CRT
+/// values are precomputed. Bulk imports of trusted keys can skip the checks
+/// and defer the precomputation, while interactive tools can check the keys
+/// thoroughly:
+///
+/// ```
+/// use rsa::{RsaPrivateKey, RsaPrivateKeyBuilder, ValidationLevel};
+/// use rsa::traits::{PrivateKeyParts, PublicKeyParts};
+/// use crypto_bigint::Odd;
+///
+/// let mut rng = rand::thread_rng();
+/// let key = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate a key");
+/// let n = Odd::new(key.n().as_ref().clone()).unwrap();
+///
+/// let imported = RsaPrivateKeyBuilder::new(n, key.e().clone(), key.d().clone())
+///     .primes(key.primes().to_vec())
+///     .validation(Some(ValidationLevel::Full))
+///     .build()
+///     .expect("invalid key");
+/// assert_eq!(imported, key);
+/// ```
+///
+/// Private key operations on keys which were not validated may fail or
+/// produce wrong results if the components are inconsistent.
+#[derive(Clone)]
+pub struct RsaPrivateKeyBuilder {
+    n: Odd<BoxedUint>,
+    e: BoxedUint,
+    d: Zeroizing<BoxedUint>,
+    primes: Zeroizing<Vec<BoxedUint>>,
+    recover_primes: bool,
+    validation: Option<ValidationLevel>,
+    precompute: bool,
+    options: ImportOptions,
+}
+
+impl RsaPrivateKeyBuilder {
+    /// Start building a key from the modulus `n`, the public exponent `e`
+    /// and the private exponent `d`.
+    pub fn new(n: Odd<BoxedUint>, e: BoxedUint, d: BoxedUint) -> Self {
+        Self {
+            n,
+            e,
+            d: Zeroizing::new(d),
+            primes: Zeroizing::new(Vec::new()),
+            recover_primes: true,
+            validation: Some(ValidationLevel::Partial),
+            precompute: true,
+            options: ImportOptions::new(),
+        }
+    }
+
+    /// Set the prime factors of `n`.
+    ///
+    /// Without them, `p` and `q` are recovered from `d` if enabled with
+    /// [`RsaPrivateKeyBuilder::recover_primes`], see
+    /// [`RsaPrivateKey::from_components`].
+    pub fn primes(mut self, primes: Vec<BoxedUint>) -> Self {
+        self.primes = Zeroizing::new(primes);
+        self
+    }
+
+    /// Recover `p` and `q` from `d` if no primes are set, instead of failing
+    /// with [`Error::NprimesTooSmall`]. Enabled by default.
+    pub fn recover_primes(mut self, recover_primes: bool) -> Self {
+        self.recover_primes = recover_primes;
+        self
+    }
+
+    /// Check the key with [`RsaPrivateKey::validate_with`] at the given level,
+    /// or not at all with `None`. Defaults to [`ValidationLevel::Partial`].
+    pub fn validation(mut self, validation: Option<ValidationLevel>) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Precompute the CRT values, see [`RsaPrivateKey::precompute`]. Enabled by
+    /// default. Failures are ignored, as the values are optional.
+    pub fn precompute(mut self, precompute: bool) -> Self {
+        self.precompute = precompute;
+        self
+    }
+
+    /// Replace `d` with `d mod λ(n)`, see [`ImportOptions::normalize_d`].
+    pub fn normalize_d(mut self, normalize_d: bool) -> Self {
+        self.options = self.options.normalize_d(normalize_d);
+        self
+    }
+
+    /// Recompute `d` if it is inconsistent with the primes, see
+    /// [`ImportOptions::recompute_d`].
+    pub fn recompute_d(mut self, recompute_d: bool) -> Self {
+        self.options = self.options.recompute_d(recompute_d);
+        self
+    }
+
+    /// Set the [`ImportOptions`] at once.
+    pub fn options(mut self, options: ImportOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Build the key.
+    pub fn build(self) -> Result<RsaPrivateKey> {
+        let Self {
+            n,
+            e,
+            mut d,
+            mut primes,
+            recover_primes: recover,
+            validation,
+            precompute,
+            options,
+        } = self;
+
+        check_size_cap(n.bits_precision() as usize)?;
+        let n_params = Arc::new(BoxedMontyParams::new(n.clone()));
+        let n_c = NonZero::new(n.get())
+            .into_option()
+            .ok_or(Error::InvalidModulus)?;
+
+        let primes_provided = !primes.is_empty();
+        match primes.len() {
+            0 if recover => {
+                // Recover `p` and `q` from `d`.
+                // See method in Appendix C.2: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-56Br2.pdf
+                let (p, q) = recover_primes(&n_c, &e, &d)?;
+                primes.push(p);
+                primes.push(q);
+            }
+            0 | 1 => return Err(Error::NprimesTooSmall),
+            _ => {}
+        }
+
+        let mut k = RsaPrivateKey {
+            pubkey_components: RsaPublicKey {
+                n: n_c,
+                e,
+                n_params,
+                vartime_verify: false,
+            },
+            d: SecretBox::new(core::mem::replace(&mut *d, BoxedUint::zero())),
+            primes: SecretBox::new(core::mem::take(&mut *primes)),
+            precomputed: PrecomputedCell::default(),
+            #[cfg(feature = "std")]
+            blinding: None,
+        };
+
+        if options.recompute_d && primes_provided {
+            check_public(&k)?;
+            k.check_modulus()?;
+            if k.check_private_exponent().is_err() {
+                let lambda = k.lambda()?;
+                let e = resize(&k.pubkey_components.e, lambda.bits_precision());
+                k.d.zeroize();
+                let d = Option::from(e.inv_mod(&lambda)).ok_or(Error::InvalidExponent)?;
+                k.d = SecretBox::new(d);
+            }
+        }
+
+        if let Some(level) = validation {
+            k.validate_with(level)?;
+        }
+
+        if options.normalize_d {
+            let lambda = k.lambda()?;
+            let d = Zeroizing::new(reduce(&k.d, &lambda));
+            k.d = SecretBox::new(resize(&d, k.d.bits_precision()));
+        }
+
+        if precompute {
+            // Precompute when possible, ignore error otherwise.
+            k.precompute().ok();
+        }
+
+        Ok(k)
+    }
+}
+
+impl fmt::Debug for RsaPrivateKeyBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RsaPrivateKeyBuilder")
+            .field("n", &self.n)
+            .field("e", &self.e)
+            .field("primes", &self.primes.len())
+            .field("recover_primes", &self.recover_primes)
+            .field("validation", &self.validation)
+            .field("precompute", &self.precompute)
+            .field("options", &self.options)
+            .finish_non_exhaustive()
+    }
+}
+
+/// How thoroughly [`RsaPrivateKey::validate_with`] checks a key.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValidationLevel {
+    /// Quick structural checks: the public key is well formed, there are at
+    /// least two primes greater than one and `0 < d < n`.
+    Basic,
+
+    /// The checks of [`RsaPrivateKey::validate`]: the primes multiply to `n`
+    /// and `de ≡ 1 mod p-1` for each prime.
+    #[default]
+    Partial,
+
+    /// The checks of [`ValidationLevel::Partial`], plus re-verification of
+    /// the primality of every prime and a bounded Fermat factorization attempt
+    /// catching primes which are too close, see
+    /// [`RsaPublicKey::check_close_primes`].
+    Full,
+}
+
+impl ValidationLevel {
+    /// Number of Fermat factorization rounds of [`ValidationLevel::Full`].
+    const FERMAT_ROUNDS: u32 = 256;
+}
+
+/// How serious a [`KeyDefect`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DefectSeverity {
+    /// The key is usable but weaker than recommended.
+    Warning,
+
+    /// The key is malformed or insecure and must be rejected.
+    Critical,
+}
+
+/// A problem with a key found by [`RsaPrivateKey::validate_report`].
+#[derive(Debug, PartialEq, Eq)]
+pub struct KeyDefect {
+    severity: DefectSeverity,
+    error: Error,
+}
+
+impl KeyDefect {
+    /// How serious the problem is.
+    pub fn severity(&self) -> DefectSeverity {
+        self.severity
+    }
+
+    /// The error describing the problem.
+    pub fn error(&self) -> &Error {
+        &self.error
     }
 }
 
@@ -567,12 +1971,22 @@ pub fn check_public(public_key: &impl PublicKeyParts) -> Result<()> {
     check_public_with_max_size(public_key.n(), public_key.e(), RsaPublicKey::MAX_SIZE)
 }
 
+/// Check that a modulus of `bits` bits is within [`RsaPublicKey::SIZE_CAP`].
+#[inline]
+pub(crate) fn check_size_cap(bits: usize) -> Result<()> {
+    if bits > RsaPublicKey::SIZE_CAP {
+        return Err(Error::ModulusTooLarge);
+    }
+    Ok(())
+}
+
 /// Check that the public key is well formed and has an exponent within acceptable bounds.
 #[inline]
 fn check_public_with_max_size(n: &BoxedUint, e: &BoxedUint, max_size: usize) -> Result<()> {
     if n.bits_precision() as usize > max_size {
         return Err(Error::ModulusTooLarge);
     }
+    check_size_cap(n.bits_precision() as usize)?;
 
     if e >= n || n.is_even().into() || n.is_zero().into() {
         return Err(Error::InvalidModulus);
@@ -640,13 +2054,613 @@ impl<'de> Deserialize<'de> for RsaPrivateKey {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::algorithms::rsa::{rsa_decrypt_and_check, rsa_encrypt};
     use crate::traits::{PrivateKeyParts, PublicKeyParts};
 
     use hex_literal::hex;
     use pkcs8::DecodePrivateKey;
     use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
 
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn test_new_from_seed() {
+        let key = RsaPrivateKey::new_from_seed(&[7; 32], 512).unwrap();
+        assert_eq!(key.n().bits(), 512);
+        assert_eq!(key, RsaPrivateKey::new_from_seed(&[7; 32], 512).unwrap());
+        assert_ne!(key, RsaPrivateKey::new_from_seed(&[8; 32], 512).unwrap());
+        assert_eq!(
+            RsaPrivateKey::new_from_seed(&[7; 31], 512),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[cfg(feature = "fingerprint")]
+    #[test]
+    fn test_spki_pin_sha256() {
+        use spki::DecodePublicKey;
+
+        let key = RsaPublicKey::from_public_key_der(include_bytes!(
+            "../tests/examples/pkcs8/rsa2048-pub.der"
+        ))
+        .unwrap();
+        let pin = "7+2pv+rZ/QWU9qXPb99sFjEWo7H61tc86gUpW2j9F5Q=";
+        assert_eq!(key.spki_pin_sha256().unwrap(), pin);
+        assert!(key.matches_pin(pin));
+        assert!(key.matches_pin(&format!(" sha256/{pin}\n")));
+        assert!(!key.matches_pin("sha1/7+2pv+rZ/QWU9qXPb99sFjEWo7H61tc86gUpW2j9F5Q="));
+        assert!(!key.matches_pin("AAAAv+rZ/QWU9qXPb99sFjEWo7H61tc86gUpW2j9F5Q="));
+    }
+
+    #[test]
+    fn test_size_cap() {
+        assert!(RsaPublicKey::MAX_SIZE <= RsaPublicKey::SIZE_CAP);
+        assert!(check_size_cap(RsaPublicKey::MAX_SIZE).is_ok());
+
+        if RsaPublicKey::SIZE_CAP < 4096 {
+            let mut rng = ChaCha8Rng::from_seed([42; 32]);
+            let bits = RsaPublicKey::SIZE_CAP + 1024;
+            assert_eq!(
+                RsaPrivateKey::new(&mut rng, bits).unwrap_err(),
+                Error::ModulusTooLarge
+            );
+
+            let n = BoxedUint::one_with_precision(bits as u32);
+            let e = BoxedUint::from(65537u64);
+            assert_eq!(
+                RsaPublicKey::new_with_max_size(n, e, bits).unwrap_err(),
+                Error::ModulusTooLarge
+            );
+        }
+    }
+
+    #[cfg(all(feature = "os_rng", feature = "sha2"))]
+    #[test]
+    fn test_os_rng() {
+        use crate::{Pkcs1v15Encrypt, Pss};
+        use sha2::{Digest, Sha256};
+
+        let priv_key = RsaPrivateKey::generate(1024).unwrap();
+        let pub_key = priv_key.to_public_key();
+
+        let ciphertext = pub_key
+            .encrypt_with_os_rng(Pkcs1v15Encrypt, b"os rng")
+            .unwrap();
+        assert_eq!(
+            priv_key.decrypt(Pkcs1v15Encrypt, &ciphertext).unwrap(),
+            b"os rng"
+        );
+
+        let digest = Sha256::digest(b"os rng");
+        let sig = priv_key
+            .sign_with_os_rng(Pss::new::<Sha256>(), &digest)
+            .unwrap();
+        pub_key.verify(Pss::new::<Sha256>(), &digest, &sig).unwrap();
+    }
+
+    #[test]
+    fn test_malformed_components() {
+        let n = Odd::new(BoxedUint::from(3233u64)).unwrap();
+        let e = BoxedUint::from(17u64);
+        let d = BoxedUint::from(2753u64);
+
+        // A trivial factorization of `n` used to divide by zero.
+        let primes = vec![n.get(), BoxedUint::one()];
+        assert_eq!(
+            RsaPrivateKey::from_components(n.clone(), e.clone(), d, primes).unwrap_err(),
+            Error::InvalidPrime
+        );
+        assert!(RsaPrivateKey::from_components(n, e.clone(), BoxedUint::zero(), vec![]).is_err());
+
+        assert_eq!(
+            RsaPrivateKey::from_p_q(BoxedUint::zero(), BoxedUint::from(61u64), e.clone())
+                .unwrap_err(),
+            Error::InvalidPrime
+        );
+        assert_eq!(
+            RsaPrivateKey::from_primes(vec![BoxedUint::from(2u64), BoxedUint::from(61u64)], e)
+                .unwrap_err(),
+            Error::InvalidPrime
+        );
+    }
+
+    #[test]
+    fn test_fallible_rng() {
+        // The wrapper passes the output of working RNGs through unchanged.
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new_with_fallible_rng(&mut rng, 512).unwrap();
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        assert_eq!(key, RsaPrivateKey::new(&mut rng, 512).unwrap());
+
+        let ciphertext = key
+            .to_public_key()
+            .encrypt_with_fallible_rng(&mut rng, Pkcs1v15Encrypt, b"fallible")
+            .unwrap();
+        assert_eq!(
+            key.decrypt(Pkcs1v15Encrypt, &ciphertext).unwrap(),
+            b"fallible"
+        );
+    }
+
+    #[test]
+    fn test_check_roca() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        assert_eq!(key.to_public_key().check_roca(), Ok(()));
+
+        // 65537^3 is trivially a power of 65537 modulo every prime.
+        let n = BoxedUint::from(65537u64 * 65537 * 65537);
+        let key = RsaPublicKey::new_unchecked(n, BoxedUint::from(3u64)).unwrap();
+        assert_eq!(key.check_roca(), Err(Error::RocaVulnerable));
+    }
+
+    #[test]
+    fn test_check_close_primes() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        assert_eq!(key.to_public_key().check_close_primes(100), Ok(()));
+
+        let n = BoxedUint::from(1_000_003u64 * 1_000_033);
+        let key = RsaPublicKey::new_unchecked(n, BoxedUint::from(RsaPrivateKey::EXP)).unwrap();
+        assert_eq!(key.check_close_primes(0), Ok(()));
+        assert_eq!(key.check_close_primes(1), Err(Error::ClosePrimes));
+    }
+
+    #[test]
+    fn test_validate_with() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        for level in [
+            ValidationLevel::Basic,
+            ValidationLevel::Partial,
+            ValidationLevel::Full,
+        ] {
+            assert_eq!(key.validate_with(level), Ok(()));
+        }
+
+        // Replacing a prime keeps the key structurally sound only.
+        let mut broken = key.clone();
+        broken
+            .primes
+            .update(|primes| primes[1] = BoxedUint::from(9u64));
+        assert_eq!(broken.validate_with(ValidationLevel::Basic), Ok(()));
+        assert_eq!(
+            broken.validate_with(ValidationLevel::Partial),
+            Err(Error::InvalidModulus)
+        );
+    }
+
+    #[test]
+    fn test_validate_report() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let report = key.validate_report();
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].severity(), DefectSeverity::Warning);
+        assert_eq!(report[0].error(), &Error::ModulusTooSmall);
+
+        let mut broken = key.clone();
+        broken
+            .primes
+            .update(|primes| primes[1] = BoxedUint::from(9u64));
+        broken.d = SecretBox::new(BoxedUint::from(5u64).widen(key.d.bits_precision()));
+        let report = broken.validate_report();
+        let errors: Vec<_> = report
+            .iter()
+            .filter(|defect| defect.severity() == DefectSeverity::Critical)
+            .map(KeyDefect::error)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                &Error::InvalidModulus,
+                &Error::InvalidExponent,
+                &Error::InvalidPrime
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pairwise_consistency_test() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        assert_eq!(key.pairwise_consistency_test(&mut rng), Ok(()));
+
+        let mut broken = key.clone();
+        broken.d = SecretBox::new(broken.d.wrapping_add(&BoxedUint::from(2u64)));
+        broken.clear_precomputed();
+        assert_eq!(
+            broken.pairwise_consistency_test(&mut rng),
+            Err(Error::PairwiseConsistencyFailed)
+        );
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        assert!(bool::from(key.ct_eq(&key.clone())));
+
+        let mut other = key.clone();
+        other.d = SecretBox::new(other.d.wrapping_add(&BoxedUint::one()));
+        assert!(!bool::from(key.ct_eq(&other)));
+        assert_ne!(key, other);
+
+        let mut other = key.clone();
+        other.primes.update(|primes| primes.swap(0, 1));
+        assert!(!bool::from(key.ct_eq(&other)));
+    }
+
+    #[test]
+    fn test_debug_redacted() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let n = key.n().to_be_bytes();
+        let prefix: String = n[..8].iter().map(|b| format!("{b:02x}")).collect();
+
+        let redacted = format!("{key:?}");
+        assert_eq!(
+            redacted,
+            format!("RsaPrivateKey {{ modulus: {prefix}.., bits: 512, primes: 2, .. }}")
+        );
+
+        let full = format!("{:?}", key.debug_full());
+        assert!(full.contains(&format!("{:?}", *key.d)));
+        assert!(full.contains(&format!("{:?}", key.primes[0])));
+    }
+
+    #[test]
+    fn test_from_components_normalize_d() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let (p, q) = (key.primes()[0].clone(), key.primes()[1].clone());
+        let expected = RsaPrivateKey::from_p_q(p, q, key.e().clone()).unwrap();
+
+        let n = Odd::new(key.n().as_ref().clone()).unwrap();
+        let options = ImportOptions::new().normalize_d(true);
+        let normalized = RsaPrivateKey::from_components_with_options(
+            n,
+            key.e().clone(),
+            key.d().clone(),
+            key.primes().to_vec(),
+            options,
+        )
+        .unwrap();
+        assert!(normalized.d() < key.d() || normalized.d() == key.d());
+        assert_eq!(normalized, expected);
+    }
+
+    #[test]
+    fn test_private_key_builder() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let n = Odd::new(key.n().as_ref().clone()).unwrap();
+        let builder = RsaPrivateKeyBuilder::new(n, key.e().clone(), key.d().clone());
+
+        let recovered = builder.clone().build().unwrap();
+        assert_eq!(recovered.primes().len(), 2);
+        assert_eq!(
+            builder.clone().recover_primes(false).build(),
+            Err(Error::NprimesTooSmall)
+        );
+
+        // An inconsistent key is only rejected when validated.
+        let broken = builder
+            .primes(vec![key.primes()[0].clone(), BoxedUint::from(9u64)])
+            .precompute(false);
+        assert_eq!(broken.clone().build(), Err(Error::InvalidModulus));
+        let unchecked = broken.validation(None).build().unwrap();
+        assert!(unchecked.precomputed.get().is_none());
+    }
+
+    #[test]
+    fn test_from_components_recompute_d() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let (p, q) = (key.primes()[0].clone(), key.primes()[1].clone());
+        let expected = RsaPrivateKey::from_p_q(p, q, key.e().clone()).unwrap();
+
+        let n = Odd::new(key.n().as_ref().clone()).unwrap();
+        let d = key.d().wrapping_add(&BoxedUint::from(2u64));
+        assert_eq!(
+            RsaPrivateKey::from_components(
+                n.clone(),
+                key.e().clone(),
+                d.clone(),
+                key.primes().to_vec()
+            ),
+            Err(Error::InvalidExponent)
+        );
+
+        let options = ImportOptions::new().recompute_d(true);
+        let recovered = RsaPrivateKey::from_components_with_options(
+            n,
+            key.e().clone(),
+            d,
+            key.primes().to_vec(),
+            options,
+        )
+        .unwrap();
+        assert_eq!(recovered, expected);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_blinding_cache() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let mut key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        key.set_blinding_cache(true);
+        let public_key = key.to_public_key();
+
+        for i in 0..2 * BlindingCache::MAX_USES {
+            let msg = i.to_be_bytes();
+            let ciphertext = public_key.encrypt(&mut rng, Pkcs1v15Encrypt, &msg).unwrap();
+            let plaintext = key
+                .decrypt_blinded(&mut rng, Pkcs1v15Encrypt, &ciphertext)
+                .unwrap();
+            assert_eq!(plaintext, msg);
+        }
+        assert!(key.clone().blinding_cache().is_some());
+
+        key.set_blinding_cache(false);
+        assert!(key.blinding_cache().is_none());
+    }
+
+    #[test]
+    fn test_clone_shares_params() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let clone = key.clone();
+        let public_key = key.to_public_key();
+        assert!(Arc::ptr_eq(
+            &key.pubkey_components.n_params,
+            &public_key.n_params
+        ));
+        assert!(Arc::ptr_eq(
+            &key.pubkey_components.n_params,
+            &clone.pubkey_components.n_params
+        ));
+
+        let (precomputed, cloned) = (key.precomputed.get(), clone.precomputed.get());
+        assert!(Arc::ptr_eq(
+            &*precomputed.unwrap().p_params,
+            &*cloned.unwrap().p_params
+        ));
+    }
+
+    #[test]
+    fn test_exponent_window() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let mut key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let msg = b"fixed window";
+        let ciphertext = key
+            .to_public_key()
+            .encrypt(&mut rng, Pkcs1v15Encrypt, msg)
+            .unwrap();
+
+        for window in [
+            ExponentWindow::Auto,
+            ExponentWindow::Fixed(1),
+            ExponentWindow::Fixed(8),
+            ExponentWindow::Backend,
+        ] {
+            key.set_exponent_window(window).unwrap();
+            assert_eq!(key.decrypt(Pkcs1v15Encrypt, &ciphertext).unwrap(), msg);
+        }
+        assert_eq!(key.exp_window(), None);
+
+        assert_eq!(
+            key.set_exponent_window(ExponentWindow::Fixed(9)),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    fn test_precomputed_bytes() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let bytes = key.precomputed_to_bytes().unwrap();
+        assert_eq!(bytes.len(), 3 * 64);
+
+        let mut restored = key.clone();
+        restored.clear_precomputed();
+        assert!(restored.precomputed_to_bytes().is_none());
+        restored.precompute_from_bytes(&bytes).unwrap();
+        assert_eq!(restored.dp(), key.dp());
+        assert_eq!(restored.dq(), key.dq());
+        assert_eq!(restored.qinv(), key.qinv());
+        assert_eq!(restored.validate(), Ok(()));
+
+        let mut broken = bytes.to_vec();
+        broken[3 * 64 - 1] ^= 1;
+        assert_eq!(
+            restored.precompute_from_bytes(&broken),
+            Err(Error::InvalidCoefficient)
+        );
+        for index in [64 - 1, 2 * 64 - 1] {
+            let mut broken = bytes.to_vec();
+            broken[index] ^= 1;
+            assert_eq!(
+                restored.precompute_from_bytes(&broken),
+                Err(Error::InvalidArguments)
+            );
+        }
+        assert_eq!(
+            restored.precompute_from_bytes(&bytes[1..]),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_lazy_precompute() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let mut key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let expected = key.precomputed_to_bytes().unwrap();
+        key.clear_precomputed();
+        assert!(key.precomputed_to_bytes().is_none());
+
+        let msg = b"lazy";
+        let ciphertext = key
+            .to_public_key()
+            .encrypt(&mut rng, Pkcs1v15Encrypt, msg)
+            .unwrap();
+        assert_eq!(key.decrypt(Pkcs1v15Encrypt, &ciphertext).unwrap(), msg);
+        assert_eq!(key.precomputed_to_bytes().unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_precomputed_cell_caches_failure() {
+        let cell = PrecomputedCell::default();
+        let mut calls = 0;
+        for _ in 0..2 {
+            let values = cell.get_or_compute(|| {
+                calls += 1;
+                Err(Error::TooFewPrimes)
+            });
+            assert!(values.is_none());
+        }
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_concurrent_private_ops() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let mut key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        key.clear_precomputed();
+        key.set_blinding_cache(true);
+        let public_key = key.to_public_key();
+        let msg = b"concurrent";
+        let ciphertext = public_key.encrypt(&mut rng, Pkcs1v15Encrypt, msg).unwrap();
+        let scheme = Pkcs1v15Sign::new_unprefixed();
+
+        let key = SharedRsaPrivateKey::new(key);
+        std::thread::scope(|s| {
+            for seed in 0..4 {
+                let key = key.clone();
+                let (public_key, ciphertext, scheme) = (&public_key, &ciphertext, &scheme);
+                s.spawn(move || {
+                    let mut rng = ChaCha8Rng::from_seed([seed; 32]);
+                    for _ in 0..8 {
+                        let decrypted = key
+                            .decrypt_blinded(&mut rng, Pkcs1v15Encrypt, ciphertext)
+                            .unwrap();
+                        assert_eq!(decrypted, msg);
+                        let signature = key.sign_with_rng(&mut rng, scheme.clone(), msg).unwrap();
+                        public_key.verify(scheme.clone(), msg, &signature).unwrap();
+                    }
+                });
+            }
+        });
+        assert!(key.precomputed_to_bytes().is_some());
+    }
+
+    #[test]
+    fn test_vartime_verification() {
+        use crate::hazmat::rsa_encrypt_vartime;
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let mut public_key = key.to_public_key();
+        public_key.set_vartime_verification(true);
+        assert_eq!(public_key, key.to_public_key());
+
+        let m = BoxedUint::from(0xdead_beef_u64).widen(512);
+        assert_eq!(
+            rsa_encrypt_vartime(&public_key, &m).unwrap(),
+            rsa_encrypt(&public_key, &m).unwrap()
+        );
+
+        let msg = b"vartime verification";
+        let signature = key
+            .sign_with_rng(&mut rng, Pkcs1v15Sign::new_unprefixed(), msg)
+            .unwrap();
+        assert!(public_key
+            .verify(Pkcs1v15Sign::new_unprefixed(), msg, &signature)
+            .is_ok());
+        assert!(public_key
+            .verify(Pkcs1v15Sign::new_unprefixed(), b"other message", &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn test_into_buffers() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let public_key = key.to_public_key();
+        let mut buf = [0u8; 80];
+
+        let msg = b"into buffers";
+        let len = public_key
+            .encrypt_into(&mut rng, Pkcs1v15Encrypt, msg, &mut buf)
+            .unwrap();
+        assert_eq!(len, 64);
+        let ciphertext = buf[..len].to_vec();
+        let len = key
+            .decrypt_blinded_into(&mut rng, Pkcs1v15Encrypt, &ciphertext, &mut buf)
+            .unwrap();
+        assert_eq!(&buf[..len], msg);
+        assert_eq!(
+            key.decrypt_into(Pkcs1v15Encrypt, &ciphertext, &mut buf[..4]),
+            Err(Error::InvalidArguments)
+        );
+
+        let len = key
+            .sign_into(Pkcs1v15Sign::new_unprefixed(), msg, &mut buf)
+            .unwrap();
+        let expected = key.sign(Pkcs1v15Sign::new_unprefixed(), msg).unwrap();
+        assert_eq!(&buf[..len], &expected[..]);
+        assert_eq!(
+            key.sign_into(Pkcs1v15Sign::new_unprefixed(), msg, &mut buf[..63]),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    fn test_validate_fips() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let exp = BoxedUint::from(RsaPrivateKey::EXP);
+        let mut key =
+            RsaPrivateKey::new_with_prime_strength(&mut rng, 2048, exp, PrimeStrength::Fips186)
+                .unwrap();
+        assert_eq!(key.validate_fips(), Ok(()));
+        key.clear_precomputed();
+        assert_eq!(key.validate_fips(), Ok(()));
+
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        assert_eq!(key.validate(), Ok(()));
+        assert_eq!(key.validate_fips(), Err(Error::InvalidModulus));
+    }
+
+    #[test]
+    fn test_validate_fips_provable() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let exp = BoxedUint::from(RsaPrivateKey::EXP);
+        let (key, _) = RsaPrivateKey::new_provable::<sha2::Sha256, _>(&mut rng, 2048, exp).unwrap();
+        assert_eq!(key.validate_fips(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_fips_default_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 2048).unwrap();
+        // d mod φ(n) is only accepted if it happens to be below λ(n).
+        assert!(matches!(
+            key.validate_fips(),
+            Ok(()) | Err(Error::InvalidExponent)
+        ));
+
+        let normalized = RsaPrivateKey::from_components_with_options(
+            Odd::new(key.n().as_ref().clone()).unwrap(),
+            key.e().clone(),
+            key.d().clone(),
+            key.primes().to_vec(),
+            ImportOptions::new().normalize_d(true),
+        )
+        .unwrap();
+        assert_eq!(normalized.validate_fips(), Ok(()));
+    }
+
     #[test]
     fn test_from_into() {
         let raw_n = BoxedUint::from(101u64);
@@ -655,11 +2669,14 @@ mod tests {
             pubkey_components: RsaPublicKey {
                 n: NonZero::new(raw_n.clone()).unwrap(),
                 e: BoxedUint::from(200u64),
-                n_params: BoxedMontyParams::new(n_odd),
+                n_params: Arc::new(BoxedMontyParams::new(n_odd)),
+                vartime_verify: false,
             },
-            d: BoxedUint::from(123u64),
-            primes: vec![],
-            precomputed: None,
+            d: SecretBox::new(BoxedUint::from(123u64)),
+            primes: SecretBox::new(vec![]),
+            precomputed: PrecomputedCell::default(),
+            #[cfg(feature = "std")]
+            blinding: None,
         };
         let public_key: RsaPublicKey = private_key.into();
 
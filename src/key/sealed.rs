@@ -0,0 +1,271 @@
+use aead::{Aead, KeyInit, Nonce, Payload};
+use aes_gcm::Aes256Gcm;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use crypto_bigint::modular::BoxedMontyParams;
+use crypto_bigint::{BoxedUint, NonZero};
+use rand_core::{CryptoRngCore, OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+use zeroize::Zeroizing;
+
+use crate::errors::{Error, Result};
+use crate::key::ExponentWindow;
+use crate::traits::keys::{PrivateKeyOps, PrivateKeyParts, PublicKeyParts};
+use crate::{RsaPrivateKey, RsaPublicKey};
+
+/// Length of the random prekey the sealing key is derived from.
+///
+/// The sealing key is only derived for the duration of an operation, so a
+/// memory disclosure has to reveal the whole prekey to recover it, like with
+/// the shielded private keys of OpenSSH.
+const PREKEY_LEN: usize = 16 * 1024;
+
+/// Prekey of the process, generated on first use.
+static PREKEY: OnceLock<Box<[u8]>> = OnceLock::new();
+
+fn cipher() -> Result<Aes256Gcm> {
+    let prekey = match PREKEY.get() {
+        Some(prekey) => prekey,
+        None => {
+            let mut prekey = vec![0u8; PREKEY_LEN].into_boxed_slice();
+            OsRng.try_fill_bytes(&mut prekey).map_err(|_| Error::Rng)?;
+            // Another thread may win the race, and its prekey is used instead.
+            PREKEY.get_or_init(|| prekey)
+        }
+    };
+
+    let mut key = Zeroizing::new([0u8; 32]);
+    key.copy_from_slice(&Sha256::digest(prekey));
+    Aes256Gcm::new_from_slice(&*key).map_err(|_| Error::Aead)
+}
+
+/// Splits a value prefixed with its length off `bytes`.
+fn split_value<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8]> {
+    let (len, rest) = bytes
+        .split_first_chunk::<4>()
+        .ok_or(Error::SealedKeyCorrupted)?;
+    let len = usize::try_from(u32::from_be_bytes(*len)).map_err(|_| Error::SealedKeyCorrupted)?;
+    if rest.len() < len {
+        return Err(Error::SealedKeyCorrupted);
+    }
+    let (value, rest) = rest.split_at(len);
+    *bytes = rest;
+    Ok(value)
+}
+
+/// RSA private key whose private components are held encrypted in memory.
+///
+/// The private exponent, the primes and the CRT values are encrypted with
+/// AES-256-GCM under a key derived from a random prekey of the process. Each
+/// private key operation decrypts them into a transient [`RsaPrivateKey`],
+/// which is zeroized as soon as the operation is done, so that a bug
+/// disclosing memory of the process is unlikely to reveal them.
+///
+/// The key can be used with the padding schemes and the signing and
+/// decrypting keys of this crate. Each operation
+/// also restores the Montgomery parameters of the primes, which makes it
+/// slightly slower than with an [`RsaPrivateKey`], and the blinding factors
+/// are not cached.
+///
+/// ```
+/// use rsa::oaep::{DecryptingKey, EncryptingKey};
+/// use rsa::traits::{Decryptor, RandomizedEncryptor};
+/// use rsa::{RsaPrivateKey, SealedRsaPrivateKey};
+/// use sha2::Sha256;
+///
+/// let mut rng = rand::thread_rng();
+/// let key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+/// let encrypting_key = EncryptingKey::<Sha256>::new(key.to_public_key());
+/// let sealed = SealedRsaPrivateKey::new(key).expect("failed to seal the key");
+/// let decrypting_key = DecryptingKey::<Sha256, Sha256, _>::new(sealed);
+///
+/// let ciphertext = encrypting_key
+///     .encrypt_with_rng(&mut rng, b"secret")
+///     .expect("failed to encrypt");
+/// let plaintext = decrypting_key.decrypt(&ciphertext).expect("failed to decrypt");
+/// assert_eq!(plaintext, b"secret");
+/// ```
+#[derive(Clone)]
+pub struct SealedRsaPrivateKey {
+    public_key: RsaPublicKey,
+    /// Number of primes of the key.
+    primes: usize,
+    /// Window size of the exponentiations using CRT values.
+    window: Option<u32>,
+    nonce: Nonce<Aes256Gcm>,
+    /// Encrypted `d`, primes and CRT values, each prefixed with its length in
+    /// bytes as a big-endian `u32`.
+    ciphertext: Vec<u8>,
+}
+
+impl SealedRsaPrivateKey {
+    /// Seal `key`, which is zeroized afterwards.
+    ///
+    /// The CRT values are computed if needed, so that they are not computed
+    /// again by every operation.
+    pub fn new(mut key: RsaPrivateKey) -> Result<Self> {
+        key.precompute()?;
+
+        let mut plaintext = Zeroizing::new(Vec::new());
+        let mut push = |value: &[u8]| -> Result<()> {
+            let len = u32::try_from(value.len()).map_err(|_| Error::ModulusTooLarge)?;
+            plaintext.extend_from_slice(&len.to_be_bytes());
+            plaintext.extend_from_slice(value);
+            Ok(())
+        };
+        push(&Zeroizing::new(key.d().to_be_bytes()))?;
+        for prime in key.primes() {
+            push(&Zeroizing::new(prime.to_be_bytes()))?;
+        }
+        if let Some(crt) = key.precomputed_to_bytes() {
+            push(&crt)?;
+        }
+
+        let public_key = key.to_public_key();
+        let mut nonce = Nonce::<Aes256Gcm>::default();
+        OsRng.try_fill_bytes(&mut nonce).map_err(|_| Error::Rng)?;
+        let aad = public_key.n().to_be_bytes();
+        let ciphertext = cipher()?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| Error::Aead)?;
+
+        Ok(Self {
+            public_key,
+            primes: key.primes().len(),
+            window: key.exp_window(),
+            nonce,
+            ciphertext,
+        })
+    }
+
+    /// Returns the public key.
+    pub fn to_public_key(&self) -> RsaPublicKey {
+        self.public_key.clone()
+    }
+
+    /// Decrypt the private components into an [`RsaPrivateKey`].
+    ///
+    /// The components were validated when the key was sealed, so this only
+    /// checks the consistency of the CRT values.
+    pub fn unseal(&self) -> Result<RsaPrivateKey> {
+        let aad = self.public_key.n().to_be_bytes();
+        let plaintext = Zeroizing::new(
+            cipher()?
+                .decrypt(
+                    &self.nonce,
+                    Payload {
+                        msg: &self.ciphertext,
+                        aad: &aad,
+                    },
+                )
+                .map_err(|_| Error::SealedKeyCorrupted)?,
+        );
+
+        let mut rest = &plaintext[..];
+        let mut next_uint = || -> Result<BoxedUint> {
+            let bytes = split_value(&mut rest)?;
+            Ok(BoxedUint::from_be_slice(bytes, bytes.len() as u32 * 8)?)
+        };
+        let d = next_uint()?;
+        let primes = (0..self.primes)
+            .map(|_| next_uint())
+            .collect::<Result<Vec<_>>>()?;
+        let crt = if rest.is_empty() {
+            None
+        } else {
+            Some(split_value(&mut rest)?)
+        };
+
+        let mut key = RsaPrivateKey::from_validated_components(self.public_key.clone(), d, primes);
+        if let Some(crt) = crt {
+            key.precompute_from_bytes(crt)?;
+        }
+        if let Some(window) = self.window {
+            key.set_exponent_window(ExponentWindow::Fixed(window))?;
+        }
+        Ok(key)
+    }
+}
+
+impl fmt::Debug for SealedRsaPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SealedRsaPrivateKey")
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TryFrom<RsaPrivateKey> for SealedRsaPrivateKey {
+    type Error = Error;
+
+    fn try_from(key: RsaPrivateKey) -> Result<Self> {
+        Self::new(key)
+    }
+}
+
+impl AsRef<RsaPublicKey> for SealedRsaPrivateKey {
+    fn as_ref(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+}
+
+impl PublicKeyParts for SealedRsaPrivateKey {
+    fn n(&self) -> &NonZero<BoxedUint> {
+        self.public_key.n()
+    }
+
+    fn e(&self) -> &BoxedUint {
+        self.public_key.e()
+    }
+
+    fn n_params(&self) -> &BoxedMontyParams {
+        self.public_key.n_params()
+    }
+}
+
+impl PrivateKeyOps for SealedRsaPrivateKey {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        self.unseal()?.raw_private_op(rng, c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oaep::{DecryptingKey, EncryptingKey};
+    use crate::traits::{Decryptor, RandomizedEncryptor};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_sealed_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let sealed = SealedRsaPrivateKey::new(key.clone()).unwrap();
+        assert_eq!(sealed.to_public_key(), key.to_public_key());
+        assert_eq!(sealed.unseal().unwrap(), key);
+        assert!(!format!("{:?}", sealed).contains("primes"));
+
+        let encrypting_key = EncryptingKey::<Sha256>::new(key.to_public_key());
+        let ciphertext = encrypting_key
+            .encrypt_with_rng(&mut rng, b"sealed")
+            .unwrap();
+        let decrypting_key = DecryptingKey::<Sha256, Sha256, _>::new(sealed.clone());
+        assert_eq!(decrypting_key.decrypt(&ciphertext).unwrap(), b"sealed");
+
+        let mut tampered = sealed;
+        tampered.ciphertext[0] ^= 1;
+        assert_eq!(tampered.unseal(), Err(Error::SealedKeyCorrupted));
+    }
+}
@@ -0,0 +1,290 @@
+//! Storage for the secret components of private keys.
+//!
+//! With the `mlock` feature, the buffers of the values held in a [`SecretBox`]
+//! are locked into memory, so that they are never written to swap, and are
+//! excluded from core dumps where supported. The buffers are allocated by
+//! `crypto-bigint`, so they are locked in place, without guard pages. Instead,
+//! each [`SecretBox`] places random canaries around the value, and aborts the
+//! process when releasing a value whose canaries were overwritten.
+//!
+//! Only the buffers reachable through the `crypto-bigint` API are locked: of
+//! Montgomery parameters, this is the modulus, while the other constants
+//! derived from it stay in unlocked memory, and are not wiped on drop.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Deref;
+use crypto_bigint::modular::{BoxedMontyForm, BoxedMontyParams};
+use crypto_bigint::BoxedUint;
+use zeroize::Zeroize;
+
+/// Value holding secrets in heap buffers.
+pub(crate) trait SecretBuffers {
+    /// Calls `f` with the address and length in bytes of each buffer.
+    ///
+    /// `f` only locks or unlocks the pages of the buffer, so the buffers of
+    /// shared values may be passed as well.
+    fn for_each_buffer(&mut self, f: &mut dyn FnMut(*mut u8, usize));
+}
+
+impl SecretBuffers for BoxedUint {
+    fn for_each_buffer(&mut self, f: &mut dyn FnMut(*mut u8, usize)) {
+        let limbs = self.as_limbs_mut();
+        f(limbs.as_mut_ptr().cast(), core::mem::size_of_val(limbs));
+    }
+}
+
+impl SecretBuffers for BoxedMontyForm {
+    fn for_each_buffer(&mut self, f: &mut dyn FnMut(*mut u8, usize)) {
+        let limbs = self.as_montgomery().as_limbs();
+        f(
+            limbs.as_ptr().cast_mut().cast(),
+            core::mem::size_of_val(limbs),
+        );
+    }
+}
+
+impl SecretBuffers for Arc<BoxedMontyParams> {
+    fn for_each_buffer(&mut self, f: &mut dyn FnMut(*mut u8, usize)) {
+        let limbs = self.modulus().as_ref().as_limbs();
+        f(
+            limbs.as_ptr().cast_mut().cast(),
+            core::mem::size_of_val(limbs),
+        );
+    }
+}
+
+impl SecretBuffers for Vec<BoxedUint> {
+    fn for_each_buffer(&mut self, f: &mut dyn FnMut(*mut u8, usize)) {
+        for value in self {
+            value.for_each_buffer(f);
+        }
+    }
+}
+
+/// Owner of a secret value, keeping its buffers locked into memory with the
+/// `mlock` feature.
+///
+/// The value is only exposed through shared references, as replacing its
+/// buffers would leave them unlocked.
+#[repr(C)]
+pub(crate) struct SecretBox<T: SecretBuffers> {
+    #[cfg(feature = "mlock")]
+    head: u64,
+    inner: T,
+    #[cfg(feature = "mlock")]
+    locked: bool,
+    #[cfg(feature = "mlock")]
+    tail: u64,
+}
+
+impl<T: SecretBuffers> SecretBox<T> {
+    pub(crate) fn new(inner: T) -> Self {
+        let mut secret = Self {
+            #[cfg(feature = "mlock")]
+            head: canary::value(),
+            inner,
+            #[cfg(feature = "mlock")]
+            locked: false,
+            #[cfg(feature = "mlock")]
+            tail: canary::value(),
+        };
+        secret.lock();
+        secret
+    }
+
+    /// Replaces the value with a copy modified by `f`.
+    #[cfg(test)]
+    pub(crate) fn update(&mut self, f: impl FnOnce(&mut T))
+    where
+        T: Clone,
+    {
+        let mut inner = self.inner.clone();
+        f(&mut inner);
+        *self = Self::new(inner);
+    }
+
+    #[cfg(feature = "mlock")]
+    fn lock(&mut self) {
+        if !self.locked {
+            self.inner.for_each_buffer(&mut pages::lock);
+            self.locked = true;
+        }
+    }
+
+    #[cfg(not(feature = "mlock"))]
+    fn lock(&mut self) {}
+
+    #[cfg(feature = "mlock")]
+    fn unlock(&mut self) {
+        canary::check(self.head, self.tail);
+        if self.locked {
+            self.inner.for_each_buffer(&mut pages::unlock);
+            self.locked = false;
+        }
+    }
+
+    #[cfg(not(feature = "mlock"))]
+    fn unlock(&mut self) {}
+}
+
+impl<T: SecretBuffers> Deref for SecretBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<'a, T: SecretBuffers> IntoIterator for &'a SecretBox<T>
+where
+    &'a T: IntoIterator,
+{
+    type Item = <&'a T as IntoIterator>::Item;
+    type IntoIter = <&'a T as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.into_iter()
+    }
+}
+
+impl<T: SecretBuffers + Clone> Clone for SecretBox<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.inner.clone())
+    }
+}
+
+impl<T: SecretBuffers + fmt::Debug> fmt::Debug for SecretBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+impl<T: SecretBuffers + Zeroize> Zeroize for SecretBox<T> {
+    /// Unlocks the buffers before wiping the value, as wiping may free them.
+    fn zeroize(&mut self) {
+        self.unlock();
+        self.inner.zeroize();
+    }
+}
+
+impl<T: SecretBuffers> Drop for SecretBox<T> {
+    fn drop(&mut self) {
+        self.unlock();
+    }
+}
+
+/// Canaries guarding the values against overflows of neighbouring memory.
+#[cfg(feature = "mlock")]
+mod canary {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    use std::sync::OnceLock;
+
+    /// Value of the canaries, random for each process so that an overflow
+    /// cannot restore them.
+    pub(super) fn value() -> u64 {
+        static VALUE: OnceLock<u64> = OnceLock::new();
+        *VALUE.get_or_init(|| RandomState::new().build_hasher().finish())
+    }
+
+    /// Aborts if a canary was overwritten, as the buffers of the value can no
+    /// longer be trusted to be unlocked and wiped.
+    pub(super) fn check(head: u64, tail: u64) {
+        if head != value() || tail != value() {
+            std::process::abort();
+        }
+    }
+}
+
+/// Reference counting of locked pages.
+///
+/// Locks do not nest, so a page shared by several buffers may only be unlocked
+/// once none of them is in use anymore.
+#[cfg(feature = "mlock")]
+mod pages {
+    use std::collections::BTreeMap;
+    use std::sync::{Mutex, PoisonError};
+
+    /// Unit of the reference counts. This is a multiple of the page sizes in
+    /// use, so that every page lies within a single granule.
+    const GRANULE: usize = 1 << 16;
+
+    /// Number of locked buffers overlapping each granule.
+    static LOCKED: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+    /// Splits a buffer along granule boundaries.
+    fn pieces(ptr: *mut u8, len: usize) -> impl Iterator<Item = (usize, *mut u8, usize)> {
+        let start = ptr as usize;
+        let end = start + len;
+        (start / GRANULE..end.div_ceil(GRANULE)).map(move |granule| {
+            let piece_start = start.max(granule * GRANULE);
+            let piece_end = end.min((granule + 1) * GRANULE);
+            (
+                granule,
+                ptr.wrapping_add(piece_start - start),
+                piece_end - piece_start,
+            )
+        })
+    }
+
+    pub(super) fn lock(ptr: *mut u8, len: usize) {
+        let mut locked = LOCKED.lock().unwrap_or_else(PoisonError::into_inner);
+        for (granule, piece, piece_len) in pieces(ptr, len) {
+            // Locking is best effort, e.g. `RLIMIT_MEMLOCK` may be exceeded.
+            // The count is kept regardless, as unlocking unlocked pages is
+            // harmless.
+            // SAFETY: the piece lies within a live buffer of `len` bytes.
+            unsafe { memsec::mlock(piece, piece_len) };
+            *locked.entry(granule).or_default() += 1;
+        }
+    }
+
+    pub(super) fn unlock(ptr: *mut u8, len: usize) {
+        let mut locked = LOCKED.lock().unwrap_or_else(PoisonError::into_inner);
+        for (granule, piece, piece_len) in pieces(ptr, len) {
+            let count = locked.get_mut(&granule).expect("granule was locked");
+            *count -= 1;
+            if *count == 0 {
+                locked.remove(&granule);
+                // Pages of the granule only used by buffers unlocked before
+                // stay locked until they are reused for a locked buffer.
+                // SAFETY: the piece lies within a live buffer of `len` bytes,
+                // which is about to be wiped, so it may be overwritten.
+                unsafe { memsec::munlock(piece, piece_len) };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_box() {
+        let mut secret = SecretBox::new(vec![BoxedUint::from(5u64), BoxedUint::from(7u64)]);
+        let copy = secret.clone();
+        assert_eq!(*copy, *secret);
+        assert_eq!(format!("{:?}", copy), format!("{:?}", *secret));
+        assert_eq!((&secret).into_iter().count(), 2);
+
+        secret.update(|primes| primes.swap(0, 1));
+        assert_eq!(secret[0], copy[1]);
+
+        secret.zeroize();
+        assert!(secret.is_empty());
+        drop(copy);
+    }
+
+    #[cfg(feature = "mlock")]
+    #[test]
+    fn test_canaries() {
+        let secret = SecretBox::new(BoxedUint::from(5u64));
+        assert_eq!(secret.head, canary::value());
+        assert_eq!(secret.tail, canary::value());
+        assert_ne!(canary::value(), 0);
+        canary::check(secret.head, secret.tail);
+    }
+}
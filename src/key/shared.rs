@@ -0,0 +1,196 @@
+use alloc::sync::Arc;
+use core::ops::Deref;
+use crypto_bigint::modular::{BoxedMontyForm, BoxedMontyParams};
+use crypto_bigint::{BoxedUint, NonZero};
+use rand_core::CryptoRngCore;
+
+use crate::errors::Result;
+#[cfg(feature = "std")]
+use crate::traits::keys::BlindingCache;
+use crate::traits::keys::{CrtValue, PrivateKeyOps, PrivateKeyParts, PublicKeyParts};
+use crate::{RsaPrivateKey, RsaPublicKey};
+
+/// Reference-counted handle to an [`RsaPrivateKey`].
+///
+/// Cloning the handle only increments a reference count, instead of copying
+/// the private exponent, the primes and their Montgomery parameters like
+/// [`RsaPrivateKey::clone`] does. All clones share the same key, including
+/// its lazily computed CRT values, so handing the key to many workers costs
+/// one precomputation in total. The key is zeroized when the last handle is
+/// dropped.
+///
+/// The handle dereferences to the key, so all its operations are available:
+///
+/// ```
+/// use rsa::{Pkcs1v15Sign, RsaPrivateKey, SharedRsaPrivateKey};
+///
+/// let mut rng = rand::thread_rng();
+/// let key = RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+/// let shared = SharedRsaPrivateKey::from(key);
+///
+/// let worker_key = shared.clone();
+/// let signature = worker_key
+///     .sign(Pkcs1v15Sign::new_unprefixed(), b"digest")
+///     .expect("failed to sign");
+/// assert_eq!(signature.len(), shared.size());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SharedRsaPrivateKey(Arc<RsaPrivateKey>);
+
+impl SharedRsaPrivateKey {
+    /// Wrap `key` in a shared handle.
+    pub fn new(key: RsaPrivateKey) -> Self {
+        Self(Arc::new(key))
+    }
+
+    /// Returns the owned key, cloning it only if other handles to it exist.
+    pub fn into_owned(self) -> RsaPrivateKey {
+        Arc::unwrap_or_clone(self.0)
+    }
+
+    /// Returns whether both handles refer to the same key.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for SharedRsaPrivateKey {
+    type Target = RsaPrivateKey;
+
+    fn deref(&self) -> &RsaPrivateKey {
+        &self.0
+    }
+}
+
+impl AsRef<RsaPrivateKey> for SharedRsaPrivateKey {
+    fn as_ref(&self) -> &RsaPrivateKey {
+        &self.0
+    }
+}
+
+impl AsRef<RsaPublicKey> for SharedRsaPrivateKey {
+    fn as_ref(&self) -> &RsaPublicKey {
+        self.0.as_ref().as_ref()
+    }
+}
+
+impl From<RsaPrivateKey> for SharedRsaPrivateKey {
+    fn from(key: RsaPrivateKey) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<Arc<RsaPrivateKey>> for SharedRsaPrivateKey {
+    fn from(key: Arc<RsaPrivateKey>) -> Self {
+        Self(key)
+    }
+}
+
+impl From<SharedRsaPrivateKey> for Arc<RsaPrivateKey> {
+    fn from(key: SharedRsaPrivateKey) -> Self {
+        key.0
+    }
+}
+
+impl From<SharedRsaPrivateKey> for RsaPrivateKey {
+    fn from(key: SharedRsaPrivateKey) -> Self {
+        key.into_owned()
+    }
+}
+
+impl From<&SharedRsaPrivateKey> for RsaPublicKey {
+    fn from(key: &SharedRsaPrivateKey) -> Self {
+        key.to_public_key()
+    }
+}
+
+impl PublicKeyParts for SharedRsaPrivateKey {
+    fn n(&self) -> &NonZero<BoxedUint> {
+        self.0.n()
+    }
+
+    fn e(&self) -> &BoxedUint {
+        self.0.e()
+    }
+
+    fn n_params(&self) -> &BoxedMontyParams {
+        self.0.n_params()
+    }
+}
+
+impl PrivateKeyParts for SharedRsaPrivateKey {
+    fn d(&self) -> &BoxedUint {
+        self.0.d()
+    }
+
+    fn primes(&self) -> &[BoxedUint] {
+        self.0.primes()
+    }
+
+    fn dp(&self) -> Option<&BoxedUint> {
+        self.0.dp()
+    }
+
+    fn dq(&self) -> Option<&BoxedUint> {
+        self.0.dq()
+    }
+
+    fn qinv(&self) -> Option<&BoxedMontyForm> {
+        self.0.qinv()
+    }
+
+    fn crt_values(&self) -> Option<&[CrtValue]> {
+        self.0.crt_values()
+    }
+
+    fn p_params(&self) -> Option<&BoxedMontyParams> {
+        self.0.p_params()
+    }
+
+    fn q_params(&self) -> Option<&BoxedMontyParams> {
+        self.0.q_params()
+    }
+
+    fn exp_window(&self) -> Option<u32> {
+        self.0.exp_window()
+    }
+
+    #[cfg(feature = "std")]
+    fn blinding_cache(&self) -> Option<&BlindingCache> {
+        self.0.blinding_cache()
+    }
+}
+
+impl PrivateKeyOps for SharedRsaPrivateKey {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        self.0.raw_private_op(rng, c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Pkcs1v15Sign;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_shared_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let shared = SharedRsaPrivateKey::new(key.clone());
+        let other = shared.clone();
+        assert!(shared.ptr_eq(&other));
+        assert_eq!(other.d(), key.d());
+
+        let scheme = Pkcs1v15Sign::new_unprefixed();
+        let signature = other.sign(scheme.clone(), b"digest").unwrap();
+        assert_eq!(signature, key.sign(scheme, b"digest").unwrap());
+
+        drop(other);
+        assert_eq!(shared.into_owned(), key);
+    }
+}
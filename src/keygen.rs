@@ -0,0 +1,302 @@
+//! Configurable RSA key generation.
+//!
+//! [`RsaKeyGenerator`] gathers the knobs of key generation in one place:
+//!
+//! ```
+//! use rsa::keygen::{PrimeStrength, RsaKeyGenerator};
+//!
+//! let mut rng = rand::thread_rng();
+//! let key = RsaKeyGenerator::new()
+//!     .bits(1024)
+//!     .exponent(65537)
+//!     .primes(2)
+//!     .prime_quality(PrimeStrength::Probable)
+//!     .build(&mut rng)
+//!     .expect("failed to generate a key");
+//! ```
+//!
+//! Key generation may take a long time, especially for large keys or with
+//! [`PrimeStrength::Safe`]. [`RsaKeyGenerator::build_with_progress`] reports
+//! its progress to a callback, which can also cancel it:
+//!
+//! ```
+//! use core::ops::ControlFlow;
+//! use rsa::keygen::{KeygenEvent, RsaKeyGenerator};
+//!
+//! let mut rng = rand::thread_rng();
+//! let mut candidates = 0;
+//! let result = RsaKeyGenerator::new().build_with_progress(&mut rng, |event| {
+//!     if let KeygenEvent::CandidateTested = event {
+//!         candidates += 1;
+//!     }
+//!     if candidates > 10_000 {
+//!         ControlFlow::Break(())
+//!     } else {
+//!         ControlFlow::Continue(())
+//!     }
+//! });
+//! ```
+
+use core::ops::ControlFlow;
+use crypto_bigint::BoxedUint;
+use rand_core::CryptoRngCore;
+
+use crate::algorithms::generate::{
+    generate_multi_prime_key_with_progress, generate_multi_prime_key_with_source, PrimeSearch,
+    Progress,
+};
+use crate::errors::Result;
+use crate::key::RsaPrivateKey;
+use crate::policy::KeyPolicy;
+
+pub use crate::algorithms::generate::{
+    KeygenEvent, PrimalityTest, PrimeConstraints, PrimeSource, PrimeStrength, SievePrimeSource,
+};
+
+/// Builder for generating [`RsaPrivateKey`]s.
+///
+/// Defaults to 2048-bit, 2-prime keys with the public exponent 65537 and
+/// random probable primes, like [`RsaPrivateKey::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaKeyGenerator {
+    bits: usize,
+    exponent: BoxedUint,
+    primes: usize,
+    prime_quality: PrimeStrength,
+    search: PrimeSearch,
+    policy: Option<KeyPolicy>,
+}
+
+impl Default for RsaKeyGenerator {
+    fn default() -> Self {
+        Self {
+            bits: 2048,
+            exponent: BoxedUint::from(65537u64),
+            primes: 2,
+            prime_quality: PrimeStrength::Probable,
+            search: PrimeSearch::default(),
+            policy: None,
+        }
+    }
+}
+
+impl RsaKeyGenerator {
+    /// Create a generator with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the bit size of the modulus.
+    pub fn bits(mut self, bits: usize) -> Self {
+        self.bits = bits;
+        self
+    }
+
+    /// Set the public exponent.
+    pub fn exponent(mut self, exponent: u64) -> Self {
+        self.exponent = BoxedUint::from(exponent);
+        self
+    }
+
+    /// Set a public exponent which does not fit in a `u64`.
+    pub fn exponent_uint(mut self, exponent: BoxedUint) -> Self {
+        self.exponent = exponent;
+        self
+    }
+
+    /// Set the number of prime factors of the modulus.
+    ///
+    /// Keys with more than two primes may not be supported by other
+    /// implementations, see [`RsaPrivateKey::new_with_exp`].
+    pub fn primes(mut self, primes: usize) -> Self {
+        self.primes = primes;
+        self
+    }
+
+    /// Set the kind of primes to generate.
+    pub fn prime_quality(mut self, prime_quality: PrimeStrength) -> Self {
+        self.prime_quality = prime_quality;
+        self
+    }
+
+    /// Set additional constraints all primes have to satisfy.
+    pub fn prime_constraints(mut self, prime_constraints: PrimeConstraints) -> Self {
+        self.search.constraints = prime_constraints;
+        self
+    }
+
+    /// Set the primality test applied to the prime candidates.
+    ///
+    /// ```
+    /// use rsa::keygen::{PrimalityTest, RsaKeyGenerator};
+    ///
+    /// // error probability below 2^-128 even for adversarial candidates
+    /// let generator = RsaKeyGenerator::new().primality_test(PrimalityTest::with_error_bound(128));
+    /// ```
+    pub fn primality_test(mut self, primality_test: PrimalityTest) -> Self {
+        self.search.test = primality_test;
+        self
+    }
+
+    /// Refuse to generate keys which do not comply with `policy`.
+    ///
+    /// No policy is enforced by default.
+    pub fn policy(mut self, policy: KeyPolicy) -> Self {
+        self.policy = Some(policy);
+        self
+    }
+
+    /// Generate a key with the configured settings.
+    pub fn build<R: CryptoRngCore>(&self, rng: &mut R) -> Result<RsaPrivateKey> {
+        self.build_with_progress(rng, |_| ControlFlow::Continue(()))
+    }
+
+    /// Generate a key with the configured settings, reporting the progress
+    /// of the generation to `progress`.
+    ///
+    /// Returning [`ControlFlow::Break`] from `progress` cancels the
+    /// generation, which then fails with [`Error::KeyGenerationCancelled`].
+    ///
+    /// [`Error::KeyGenerationCancelled`]: crate::Error::KeyGenerationCancelled
+    pub fn build_with_progress<R, F>(&self, rng: &mut R, mut progress: F) -> Result<RsaPrivateKey>
+    where
+        R: CryptoRngCore,
+        F: FnMut(KeygenEvent) -> ControlFlow<()>,
+    {
+        self.check_policy()?;
+        let components = generate_multi_prime_key_with_progress(
+            rng,
+            self.primes,
+            self.bits,
+            self.exponent.clone(),
+            self.prime_quality,
+            &self.search,
+            &mut Progress::new(&mut progress),
+        )?;
+        RsaPrivateKey::from_components(components.n, components.e, components.d, components.primes)
+    }
+
+    /// Generate a key with the configured size, exponent and number of
+    /// primes, taking the primes from `source`.
+    ///
+    /// The prime quality, constraints and primality test configured on this
+    /// generator are not used: they only apply to the built-in prime search.
+    pub fn build_with_source<R, S>(&self, rng: &mut R, source: &mut S) -> Result<RsaPrivateKey>
+    where
+        R: CryptoRngCore,
+        S: PrimeSource,
+    {
+        self.check_policy()?;
+        let components = generate_multi_prime_key_with_source(
+            rng,
+            self.primes,
+            self.bits,
+            self.exponent.clone(),
+            source,
+            &mut Progress::new(&mut |_| ControlFlow::Continue(())),
+        )?;
+        RsaPrivateKey::from_components(components.n, components.e, components.d, components.primes)
+    }
+
+    fn check_policy(&self) -> Result<()> {
+        match &self.policy {
+            Some(policy) => policy.check_parameters(self.bits, &self.exponent),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::Error;
+    use crate::traits::{PrivateKeyParts, PublicKeyParts};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_build() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaKeyGenerator::new()
+            .bits(512)
+            .exponent(3)
+            .primes(3)
+            .build(&mut rng)
+            .unwrap();
+        assert_eq!(key.n().bits(), 512);
+        assert_eq!(key.e(), &BoxedUint::from(3u64));
+        assert_eq!(key.primes().len(), 3);
+        key.validate().unwrap();
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let mut events = 0;
+        let result = RsaKeyGenerator::new().build_with_progress(&mut rng, |_| {
+            events += 1;
+            if events < 5 {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        assert_eq!(result, Err(Error::KeyGenerationCancelled));
+        assert_eq!(events, 5);
+    }
+
+    #[test]
+    fn test_build_with_source() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let mut source =
+            SievePrimeSource::new().primality_test(PrimalityTest::with_error_bound(64));
+        let key = RsaKeyGenerator::new()
+            .bits(512)
+            .build_with_source(&mut rng, &mut source)
+            .unwrap();
+        assert_eq!(key.n().bits(), 512);
+        key.validate().unwrap();
+    }
+
+    #[test]
+    fn test_policy() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let policy = KeyPolicy::new().min_bits(512);
+        assert_eq!(
+            RsaKeyGenerator::new()
+                .bits(256)
+                .policy(policy)
+                .build(&mut rng),
+            Err(Error::ModulusTooSmall)
+        );
+        assert_eq!(
+            RsaKeyGenerator::new()
+                .bits(512)
+                .exponent(3)
+                .policy(policy)
+                .build(&mut rng),
+            Err(Error::WeakPublicExponent)
+        );
+        let key = RsaKeyGenerator::new()
+            .bits(512)
+            .policy(policy)
+            .build(&mut rng)
+            .unwrap();
+        policy.check(&key).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        assert_eq!(
+            RsaKeyGenerator::new().primes(1).build(&mut rng),
+            Err(Error::NprimesTooSmall)
+        );
+        assert_eq!(
+            RsaKeyGenerator::new()
+                .bits(512)
+                .prime_quality(PrimeStrength::Fips186)
+                .build(&mut rng),
+            Err(Error::InvalidArguments)
+        );
+    }
+}
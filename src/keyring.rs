@@ -0,0 +1,241 @@
+//! Decryption with a set of private keys, e.g. during key rotation.
+//!
+//! [`DecryptKeyring`] holds private keys under identifiers. Ciphertexts
+//! carrying the identifier of their key are decrypted with that key, while
+//! the others are tried with every key of the ring:
+//!
+#![cfg_attr(feature = "sha2", doc = "```")]
+#![cfg_attr(not(feature = "sha2"), doc = "```ignore")]
+//! use rsa::keyring::DecryptKeyring;
+//! use rsa::{sha2::Sha256, Oaep, RsaPrivateKey};
+//!
+//! let mut rng = rand::thread_rng();
+//! let old_key = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate a key");
+//! let new_key = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate a key");
+//! let ciphertext = old_key
+//!     .to_public_key()
+//!     .encrypt(&mut rng, Oaep::new::<Sha256>(), b"token")
+//!     .expect("failed to encrypt");
+//!
+//! let mut keyring = DecryptKeyring::new();
+//! keyring.insert("2023", old_key);
+//! keyring.insert("2024", new_key);
+//!
+//! let (id, plaintext) = keyring
+//!     .decrypt(Oaep::new::<Sha256>(), None, &ciphertext)
+//!     .expect("failed to decrypt");
+//! assert_eq!(*id, "2023");
+//! assert_eq!(plaintext, b"token");
+//! ```
+//!
+//! # PKCS#1 v1.5
+//!
+//! Trying every key is only supported by padding schemes implementing
+//! [`PaddingScheme::trial_decrypt`], i.e. OAEP. With [`Pkcs1v15Encrypt`],
+//! which key accepts the padding of a forged ciphertext would be a
+//! Bleichenbacher oracle, so ciphertexts must name the key they were
+//! encrypted with: without a matching identifier, decryption fails with
+//! [`Error::InvalidPaddingScheme`].
+//!
+//! [`Pkcs1v15Encrypt`]: crate::Pkcs1v15Encrypt
+//!
+//! # Timing
+//!
+//! Without a usable identifier, every key whose modulus has the size of the
+//! ciphertext is used, even after one succeeded, so the time taken does not
+//! reveal which key the ciphertext belongs to. Whether decryption fails is
+//! still observable, as with a single key.
+
+use alloc::vec::Vec;
+use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
+
+use crate::dummy_rng::DummyRng;
+use crate::errors::{Error, Result};
+use crate::key::RsaPrivateKey;
+use crate::traits::{PaddingScheme, PublicKeyParts};
+
+/// Private keys indexed by identifiers of type `Id`.
+#[derive(Debug, Clone)]
+pub struct DecryptKeyring<Id> {
+    keys: Vec<(Id, RsaPrivateKey)>,
+}
+
+impl<Id> Default for DecryptKeyring<Id> {
+    fn default() -> Self {
+        Self { keys: Vec::new() }
+    }
+}
+
+impl<Id: PartialEq> DecryptKeyring<Id> {
+    /// Create an empty keyring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `key` under `id`, returning the key previously stored under it.
+    pub fn insert(&mut self, id: Id, key: RsaPrivateKey) -> Option<RsaPrivateKey> {
+        match self.keys.iter_mut().find(|(key_id, _)| *key_id == id) {
+            Some((_, old)) => Some(core::mem::replace(old, key)),
+            None => {
+                self.keys.push((id, key));
+                None
+            }
+        }
+    }
+
+    /// Remove the key stored under `id`.
+    pub fn remove(&mut self, id: &Id) -> Option<RsaPrivateKey> {
+        let index = self.keys.iter().position(|(key_id, _)| key_id == id)?;
+        Some(self.keys.remove(index).1)
+    }
+
+    /// Returns the key stored under `id`.
+    pub fn get(&self, id: &Id) -> Option<&RsaPrivateKey> {
+        self.keys
+            .iter()
+            .find(|(key_id, _)| key_id == id)
+            .map(|(_, key)| key)
+    }
+
+    /// Returns the identifiers of the keys, in insertion order.
+    pub fn ids(&self) -> impl Iterator<Item = &Id> {
+        self.keys.iter().map(|(id, _)| id)
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether the keyring holds no key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Decrypt `ciphertext`, returning the plaintext together with the
+    /// identifier of the key which decrypted it.
+    ///
+    /// If `key_id_hint` names a key of the ring, only that key is used.
+    /// Otherwise, every key is tried if `padding` supports it, see the
+    /// [module documentation](self).
+    pub fn decrypt<P: PaddingScheme>(
+        &self,
+        padding: P,
+        key_id_hint: Option<&Id>,
+        ciphertext: &[u8],
+    ) -> Result<(&Id, Vec<u8>)> {
+        self.decrypt_with(
+            Option::<&mut DummyRng>::None,
+            padding,
+            key_id_hint,
+            ciphertext,
+        )
+    }
+
+    /// Like [`DecryptKeyring::decrypt`], using `rng` to blind the decryptions.
+    pub fn decrypt_blinded<R: CryptoRngCore, P: PaddingScheme>(
+        &self,
+        rng: &mut R,
+        padding: P,
+        key_id_hint: Option<&Id>,
+        ciphertext: &[u8],
+    ) -> Result<(&Id, Vec<u8>)> {
+        self.decrypt_with(Some(rng), padding, key_id_hint, ciphertext)
+    }
+
+    fn decrypt_with<R: CryptoRngCore, P: PaddingScheme>(
+        &self,
+        mut rng: Option<&mut R>,
+        mut padding: P,
+        key_id_hint: Option<&Id>,
+        ciphertext: &[u8],
+    ) -> Result<(&Id, Vec<u8>)> {
+        if let Some((id, key)) =
+            key_id_hint.and_then(|hint| self.keys.iter().find(|(key_id, _)| key_id == hint))
+        {
+            let plaintext = padding.decrypt(rng, key, ciphertext)?;
+            return Ok((id, plaintext));
+        }
+
+        let mut found = None;
+        for (id, key) in &self.keys {
+            if key.size() != ciphertext.len() {
+                continue;
+            }
+            let result = padding
+                .trial_decrypt(rng.as_deref_mut(), key, ciphertext)
+                .map(Zeroizing::new);
+            match result {
+                Err(Error::InvalidPaddingScheme) => return Err(Error::InvalidPaddingScheme),
+                Ok(plaintext) if found.is_none() => found = Some((id, plaintext)),
+                _ => {}
+            }
+        }
+
+        let (id, mut plaintext) = found.ok_or(Error::Decryption)?;
+        Ok((id, core::mem::take(&mut *plaintext)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Oaep, Pkcs1v15Encrypt};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::Sha256;
+
+    #[test]
+    fn test_keyring() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let old_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let new_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let ciphertext = old_key
+            .to_public_key()
+            .encrypt(&mut rng, Oaep::new::<Sha256>(), b"secret")
+            .unwrap();
+
+        let mut keyring = DecryptKeyring::new();
+        assert!(keyring.insert(1, new_key.clone()).is_none());
+        assert!(keyring.insert(2, old_key.clone()).is_none());
+        assert_eq!(keyring.insert(2, old_key.clone()), Some(old_key));
+        assert_eq!(keyring.ids().collect::<Vec<_>>(), [&1, &2]);
+
+        let decrypted = keyring.decrypt(Oaep::new::<Sha256>(), None, &ciphertext);
+        assert_eq!(decrypted, Ok((&2, b"secret".to_vec())));
+        let decrypted =
+            keyring.decrypt_blinded(&mut rng, Oaep::new::<Sha256>(), Some(&3), &ciphertext);
+        assert_eq!(decrypted, Ok((&2, b"secret".to_vec())));
+
+        // A matching hint restricts decryption to that key.
+        let decrypted = keyring.decrypt(Oaep::new::<Sha256>(), Some(&1), &ciphertext);
+        assert_eq!(decrypted, Err(Error::Decryption));
+
+        assert!(keyring.remove(&2).is_some());
+        let decrypted = keyring.decrypt(Oaep::new::<Sha256>(), None, &ciphertext);
+        assert_eq!(decrypted, Err(Error::Decryption));
+        assert_eq!(keyring.len(), 1);
+    }
+
+    #[test]
+    fn test_keyring_pkcs1v15() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let ciphertext = key
+            .to_public_key()
+            .encrypt(&mut rng, Pkcs1v15Encrypt, b"secret")
+            .unwrap();
+
+        let mut keyring = DecryptKeyring::new();
+        keyring.insert(1, key);
+
+        let decrypted = keyring.decrypt(Pkcs1v15Encrypt, Some(&1), &ciphertext);
+        assert_eq!(decrypted, Ok((&1, b"secret".to_vec())));
+
+        // Without a matching hint, the keys are not tried.
+        for hint in [None, Some(&2)] {
+            let decrypted = keyring.decrypt(Pkcs1v15Encrypt, hint, &ciphertext);
+            assert_eq!(decrypted, Err(Error::InvalidPaddingScheme));
+        }
+    }
+}
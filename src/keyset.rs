@@ -0,0 +1,267 @@
+//! Signature verification with a set of trusted public keys.
+//!
+//! [`VerifyingKeySet`] maps key identifiers, like the `kid` header of a JSON
+//! Web Token, to public keys. Signatures are verified either with the key
+//! named by their identifier, or with every key of the set when they carry
+//! none.
+//!
+//! With the `serde` feature, a set can be deserialized from a [JWKS]
+//! document, of which the RSA keys are imported, e.g. the example set of
+//! RFC 7517:
+//!
+//! ```
+//! # #[cfg(feature = "serde")]
+//! # {
+//! use rsa::keyset::VerifyingKeySet;
+//!
+//! let jwks = r#"{"keys": [{
+//!     "kty": "RSA",
+//!     "kid": "2011-04-29",
+//!     "use": "sig",
+//!     "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+//!     "e": "AQAB"
+//! }]}"#;
+//! let keys: VerifyingKeySet = serde_json::from_str(jwks).expect("invalid JWKS");
+//! assert!(keys.get("2011-04-29").is_some());
+//! # }
+//! ```
+//!
+//! [JWKS]: https://www.rfc-editor.org/rfc/rfc7517#section-5
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::errors::{Error, Result};
+use crate::key::RsaPublicKey;
+use crate::traits::SignatureScheme;
+
+/// Public keys trusted for verification, indexed by key identifiers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyingKeySet {
+    keys: Vec<(String, RsaPublicKey)>,
+}
+
+impl VerifyingKeySet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add `key` under `kid`, returning the key previously stored under it.
+    pub fn insert(&mut self, kid: impl Into<String>, key: RsaPublicKey) -> Option<RsaPublicKey> {
+        let kid = kid.into();
+        match self.keys.iter_mut().find(|(key_id, _)| *key_id == kid) {
+            Some((_, old)) => Some(core::mem::replace(old, key)),
+            None => {
+                self.keys.push((kid, key));
+                None
+            }
+        }
+    }
+
+    /// Remove the key stored under `kid`.
+    pub fn remove(&mut self, kid: &str) -> Option<RsaPublicKey> {
+        let index = self.keys.iter().position(|(key_id, _)| key_id == kid)?;
+        Some(self.keys.remove(index).1)
+    }
+
+    /// Returns the key stored under `kid`.
+    pub fn get(&self, kid: &str) -> Option<&RsaPublicKey> {
+        self.keys
+            .iter()
+            .find(|(key_id, _)| key_id == kid)
+            .map(|(_, key)| key)
+    }
+
+    /// Returns the identifiers of the keys, in insertion order.
+    pub fn kids(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(|(kid, _)| kid.as_str())
+    }
+
+    /// Returns the number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns whether the set holds no key.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Verify the signature `sig` of the digest `hashed`, returning the
+    /// identifier of the key which verified it.
+    ///
+    /// With a `kid`, only the key stored under it is used, and unknown
+    /// identifiers are rejected. Without one, the signature is accepted if any
+    /// key of the set verifies it.
+    pub fn verify<S: SignatureScheme + Clone>(
+        &self,
+        kid: Option<&str>,
+        scheme: S,
+        hashed: &[u8],
+        sig: &[u8],
+    ) -> Result<&str> {
+        if let Some(kid) = kid {
+            let (kid, key) = self
+                .keys
+                .iter()
+                .find(|(key_id, _)| key_id == kid)
+                .ok_or(Error::Verification)?;
+            key.verify(scheme, hashed, sig)?;
+            return Ok(kid);
+        }
+
+        self.keys
+            .iter()
+            .find(|(_, key)| key.verify(scheme.clone(), hashed, sig).is_ok())
+            .map(|(kid, _)| kid.as_str())
+            .ok_or(Error::Verification)
+    }
+}
+
+impl Extend<(String, RsaPublicKey)> for VerifyingKeySet {
+    fn extend<T: IntoIterator<Item = (String, RsaPublicKey)>>(&mut self, iter: T) {
+        for (kid, key) in iter {
+            self.insert(kid, key);
+        }
+    }
+}
+
+impl FromIterator<(String, RsaPublicKey)> for VerifyingKeySet {
+    fn from_iter<T: IntoIterator<Item = (String, RsaPublicKey)>>(iter: T) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+#[cfg(feature = "serde")]
+mod jwks {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use base64ct::{Base64UrlUnpadded, Encoding};
+    use crypto_bigint::BoxedUint;
+    use serde::{de, Deserialize, Deserializer};
+
+    use super::VerifyingKeySet;
+    use crate::key::RsaPublicKey;
+
+    /// JSON Web Key Set, see RFC 7517 section 5.
+    #[derive(Deserialize)]
+    struct Jwks {
+        keys: Vec<Jwk>,
+    }
+
+    /// Members of a JSON Web Key used to import RSA public keys.
+    #[derive(Deserialize)]
+    struct Jwk {
+        kty: String,
+        kid: Option<String>,
+        #[serde(rename = "use")]
+        key_use: Option<String>,
+        n: Option<String>,
+        e: Option<String>,
+    }
+
+    fn decode_uint<E: de::Error>(value: Option<&str>, name: &str) -> Result<BoxedUint, E> {
+        let value = value.ok_or_else(|| E::missing_field(name))?;
+        let bytes = Base64UrlUnpadded::decode_vec(value)
+            .map_err(|_| E::custom("invalid base64url integer"))?;
+        let bits = u32::try_from(bytes.len() * 8).map_err(|_| E::custom("integer too large"))?;
+        BoxedUint::from_be_slice(&bytes, bits).map_err(|_| E::custom("invalid integer"))
+    }
+
+    /// Imports the RSA keys of a JWKS document which have a `kid` and may be
+    /// used for signatures. Keys of other types are skipped, so that sets
+    /// mixing key types can be used, but invalid RSA keys are rejected.
+    impl<'de> Deserialize<'de> for VerifyingKeySet {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let jwks = Jwks::deserialize(deserializer)?;
+            let mut set = VerifyingKeySet::new();
+            for jwk in jwks.keys {
+                if jwk.kty != "RSA" || jwk.key_use.as_deref().is_some_and(|u| u != "sig") {
+                    continue;
+                }
+                let Some(kid) = jwk.kid else {
+                    continue;
+                };
+                let n = decode_uint(jwk.n.as_deref(), "n")?;
+                let e = decode_uint(jwk.e.as_deref(), "e")?;
+                let key = RsaPublicKey::new(n, e).map_err(de::Error::custom)?;
+                set.insert(kid, key);
+            }
+            Ok(set)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pkcs1v15::Pkcs1v15Sign;
+    use crate::RsaPrivateKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::{Digest, Sha256};
+
+    #[test]
+    fn test_verify() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let old_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let new_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        let hashed = Sha256::digest(b"token");
+        let sig = old_key
+            .sign(Pkcs1v15Sign::new::<Sha256>(), &hashed)
+            .unwrap();
+
+        let keys: VerifyingKeySet = [
+            ("new".into(), new_key.to_public_key()),
+            ("old".into(), old_key.to_public_key()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(keys.kids().collect::<Vec<_>>(), ["new", "old"]);
+
+        let scheme = Pkcs1v15Sign::new::<Sha256>();
+        assert_eq!(keys.verify(None, scheme.clone(), &hashed, &sig), Ok("old"));
+        assert_eq!(
+            keys.verify(Some("old"), scheme.clone(), &hashed, &sig),
+            Ok("old")
+        );
+        assert_eq!(
+            keys.verify(Some("new"), scheme.clone(), &hashed, &sig),
+            Err(Error::Verification)
+        );
+        assert_eq!(
+            keys.verify(Some("other"), scheme.clone(), &hashed, &sig),
+            Err(Error::Verification)
+        );
+        assert_eq!(
+            keys.verify(None, scheme, &Sha256::digest(b"forged"), &sig),
+            Err(Error::Verification)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_jwks() {
+        use crate::traits::PublicKeyParts;
+        use base64ct::{Base64UrlUnpadded, Encoding};
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 512).unwrap().to_public_key();
+        let n = Base64UrlUnpadded::encode_string(&key.n().to_be_bytes());
+        let jwks = format!(
+            r#"{{"keys": [
+                {{"kty": "EC", "kid": "ec", "crv": "P-256", "x": "", "y": ""}},
+                {{"kty": "RSA", "kid": "enc", "use": "enc", "n": "{n}", "e": "AQAB"}},
+                {{"kty": "RSA", "kid": "sig", "use": "sig", "n": "{n}", "e": "AQAB"}}
+            ]}}"#
+        );
+        let keys: VerifyingKeySet = serde_json::from_str(&jwks).unwrap();
+        assert_eq!(keys.kids().collect::<Vec<_>>(), ["sig"]);
+        assert_eq!(keys.get("sig"), Some(&key));
+
+        let invalid = r#"{"keys": [{"kty": "RSA", "kid": "bad", "n": "AQAB", "e": "AQAB"}]}"#;
+        assert!(serde_json::from_str::<VerifyingKeySet>(invalid).is_err());
+    }
+}
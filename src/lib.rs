@@ -218,39 +218,130 @@
 #[cfg(doctest)]
 pub struct ReadmeDoctests;
 
+#[cfg(feature = "alloc")]
 #[macro_use]
 extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+#[cfg(feature = "alloc")]
 pub use crypto_bigint::BoxedUint;
 pub use rand_core;
 pub use signature;
 
 mod algorithms;
 pub mod errors;
+pub mod fixed;
+
+#[cfg(feature = "acvp")]
+pub mod acvp;
+#[cfg(feature = "pem")]
+pub mod armor;
+#[cfg(feature = "alloc")]
+pub mod blocklist;
+#[cfg(feature = "alloc")]
+pub mod cert;
+#[cfg(feature = "cms")]
+pub mod cms;
+#[cfg(feature = "alloc")]
+pub mod csr;
+#[cfg(feature = "dkim")]
+pub mod dkim;
+#[cfg(feature = "dudect")]
+pub mod dudect;
+#[cfg(feature = "alloc")]
+pub mod encoded;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+#[cfg(feature = "kat")]
+pub mod kat;
+#[cfg(feature = "alloc")]
+pub mod keygen;
+#[cfg(feature = "alloc")]
+pub mod keyring;
+#[cfg(feature = "alloc")]
+pub mod keyset;
+#[cfg(feature = "alloc")]
 pub mod oaep;
+#[cfg(feature = "alloc")]
 pub mod pkcs1v15;
+#[cfg(feature = "alloc")]
+pub mod policy;
+#[cfg(feature = "alloc")]
 pub mod pss;
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+pub mod self_test;
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+pub mod shares;
+#[cfg(feature = "alloc")]
+pub mod strictness;
+#[cfg(feature = "alloc")]
 pub mod traits;
 
+#[cfg(feature = "alloc")]
+mod context;
+#[cfg(feature = "alloc")]
 mod dummy_rng;
+#[cfg(feature = "alloc")]
 mod encoding;
+#[cfg(feature = "alloc")]
+mod fallible_rng;
+#[cfg(feature = "arbitrary")]
+mod fuzzing;
+#[cfg(feature = "alloc")]
 mod key;
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+mod seeded_rng;
 
+#[cfg(any(feature = "envelope", feature = "cms"))]
+pub use aead;
 pub use pkcs1;
 pub use pkcs8;
 #[cfg(feature = "sha2")]
 pub use sha2;
 
+#[cfg(feature = "alloc")]
 pub use crate::{
+    algorithms::generate::{PrimeCertificate, PrimeStrength},
+    context::RsaOpContext,
+    encoded::{Ciphertext, EncodedSignature},
     errors::{Error, Result},
-    key::{RsaPrivateKey, RsaPublicKey},
+    key::{
+        DefectSeverity, ExponentWindow, ImportOptions, KeyDefect, RsaPrivateKey,
+        RsaPrivateKeyBuilder, RsaPublicKey, SharedRsaPrivateKey, ValidationLevel,
+    },
+    keygen::RsaKeyGenerator,
     oaep::Oaep,
     pkcs1v15::{Pkcs1v15Encrypt, Pkcs1v15Sign},
+    policy::KeyPolicy,
     pss::Pss,
     traits::keys::CrtValue,
 };
 
+#[cfg(all(feature = "alloc", feature = "sha2"))]
+pub use crate::self_test::self_test;
+#[cfg(feature = "std")]
+pub use crate::traits::keys::BlindingCache;
+#[cfg(feature = "sealing")]
+pub use crate::key::SealedRsaPrivateKey;
+
+#[cfg(all(feature = "apple", target_vendor = "apple"))]
+pub mod apple;
+#[cfg(all(feature = "cng", windows))]
+pub mod cng;
+#[cfg(feature = "envelope")]
+pub mod envelope;
 #[cfg(feature = "hazmat")]
 pub mod hazmat;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+#[cfg(feature = "psa")]
+pub mod psa;
+#[cfg(feature = "rand_core_0_9")]
+pub mod rand_compat;
+#[cfg(feature = "remote")]
+pub mod remote;
+#[cfg(feature = "tpm")]
+pub mod tpm;
+#[cfg(all(feature = "webcrypto", target_arch = "wasm32"))]
+pub mod webcrypto;
@@ -14,15 +14,26 @@ use alloc::vec::Vec;
 use core::fmt;
 use crypto_bigint::BoxedUint;
 
+use const_oid::AssociatedOid;
 use digest::{Digest, DynDigest, FixedOutputReset};
+use pkcs1::RsaOaepParams;
+use pkcs8::der::{asn1::OctetStringRef, AnyRef, Decode, Encode};
+use pkcs8::spki::{
+    der::Any, AlgorithmIdentifier, AlgorithmIdentifierOwned, AlgorithmIdentifierRef,
+    ObjectIdentifier,
+};
 use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
 
 use crate::algorithms::oaep::*;
-use crate::algorithms::pad::{uint_to_be_pad, uint_to_zeroizing_be_pad};
-use crate::algorithms::rsa::{rsa_decrypt_and_check, rsa_encrypt};
-use crate::errors::{Error, Result};
-use crate::key::{self, RsaPrivateKey, RsaPublicKey};
-use crate::traits::{PaddingScheme, PublicKeyParts};
+use crate::algorithms::pad::{uint_to_be_pad, uint_to_be_pad_into, uint_to_zeroizing_be_pad};
+use crate::algorithms::rsa::rsa_encrypt;
+#[cfg(feature = "sha2")]
+use crate::encoding::new_dyn_digest;
+use crate::encoding::ID_RSAES_OAEP;
+use crate::errors::{opaque_decryption, Error, Result};
+use crate::key::{self, RsaPublicKey};
+use crate::traits::{PaddingScheme, PrivateKeyOps, PublicKeyParts};
 
 /// Encryption and Decryption using [OAEP padding](https://datatracker.ietf.org/doc/html/rfc8017#section-7.1).
 ///
@@ -132,13 +143,82 @@ impl Oaep {
             label: Some(label.into()),
         }
     }
+
+    /// Returns the maximum length of the messages which can be encrypted
+    /// with `key` using this padding, `k - 2 * h_len - 2`.
+    pub fn max_message_len(&self, key: &impl PublicKeyParts) -> usize {
+        max_message_len(key.size(), self.digest.output_size())
+    }
+
+    /// ⚠️ Encrypts `msg` for `pub_key` like [`PaddingScheme::encrypt`], but
+    /// with the given `seed` instead of a random one.
+    ///
+    /// This reproduces the ciphertexts of known answer tests with fixed
+    /// seeds, e.g. the RFC 3447 and CAVP vectors, byte for byte. The seed
+    /// must be as long as the output of `digest`.
+    ///
+    /// # ☢️️ WARNING: HAZARDOUS API ☢️
+    ///
+    /// OAEP is only secure with a fresh random seed for every message. Never
+    /// use this function outside of tests.
+    #[cfg(feature = "hazmat")]
+    pub fn encrypt_with_seed(
+        mut self,
+        pub_key: &RsaPublicKey,
+        msg: &[u8],
+        seed: &[u8],
+    ) -> Result<Vec<u8>> {
+        if seed.len() != self.digest.output_size() {
+            return Err(Error::InvalidArguments);
+        }
+        encrypt(
+            &mut FixedSeed(seed),
+            pub_key,
+            msg,
+            &mut *self.digest,
+            &mut *self.mgf_digest,
+            self.label,
+        )
+    }
 }
 
+/// Hands out a fixed OAEP seed as if it was drawn from a random generator.
+///
+/// OAEP encryption draws exactly one seed, so running out of bytes is a bug.
+#[cfg(feature = "hazmat")]
+struct FixedSeed<'a>(&'a [u8]);
+
+#[cfg(feature = "hazmat")]
+impl rand_core::RngCore for FixedSeed<'_> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        assert!(dest.len() <= self.0.len(), "OAEP seed exhausted");
+        let (seed, rest) = self.0.split_at(dest.len());
+        dest.copy_from_slice(seed);
+        self.0 = rest;
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "hazmat")]
+impl rand_core::CryptoRng for FixedSeed<'_> {}
+
 impl PaddingScheme for Oaep {
     fn decrypt<Rng: CryptoRngCore>(
         mut self,
         rng: Option<&mut Rng>,
-        priv_key: &RsaPrivateKey,
+        priv_key: &impl PrivateKeyOps,
         ciphertext: &[u8],
     ) -> Result<Vec<u8>> {
         decrypt(
@@ -151,6 +231,22 @@ impl PaddingScheme for Oaep {
         )
     }
 
+    fn trial_decrypt<Rng: CryptoRngCore>(
+        &mut self,
+        rng: Option<&mut Rng>,
+        priv_key: &impl PrivateKeyOps,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>> {
+        decrypt(
+            rng,
+            priv_key,
+            ciphertext,
+            &mut *self.digest,
+            &mut *self.mgf_digest,
+            self.label.clone(),
+        )
+    }
+
     fn encrypt<Rng: CryptoRngCore>(
         mut self,
         rng: &mut Rng,
@@ -166,6 +262,24 @@ impl PaddingScheme for Oaep {
             self.label,
         )
     }
+
+    fn encrypt_into<Rng: CryptoRngCore>(
+        mut self,
+        rng: &mut Rng,
+        pub_key: &RsaPublicKey,
+        msg: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        let c = encrypt_uint(
+            rng,
+            pub_key,
+            msg,
+            &mut *self.digest,
+            &mut *self.mgf_digest,
+            self.label,
+        )?;
+        uint_to_be_pad_into(&c, pub_key.size(), out)
+    }
 }
 
 impl fmt::Debug for Oaep {
@@ -194,12 +308,25 @@ fn encrypt<R: CryptoRngCore + ?Sized>(
     mgf_digest: &mut dyn DynDigest,
     label: Option<Box<[u8]>>,
 ) -> Result<Vec<u8>> {
+    let c = encrypt_uint(rng, pub_key, msg, digest, mgf_digest, label)?;
+    uint_to_be_pad(c, pub_key.size())
+}
+
+/// Like [`encrypt`], but returns the ciphertext as an integer.
+fn encrypt_uint<R: CryptoRngCore + ?Sized>(
+    rng: &mut R,
+    pub_key: &RsaPublicKey,
+    msg: &[u8],
+    digest: &mut dyn DynDigest,
+    mgf_digest: &mut dyn DynDigest,
+    label: Option<Box<[u8]>>,
+) -> Result<BoxedUint> {
     key::check_public(pub_key)?;
 
     let em = oaep_encrypt(rng, msg, digest, mgf_digest, label, pub_key.size())?;
 
-    let int = BoxedUint::from_be_slice(&em, pub_key.n_bits_precision())?;
-    uint_to_be_pad(rsa_encrypt(pub_key, &int)?, pub_key.size())
+    let int = Zeroizing::new(BoxedUint::from_be_slice(&em, pub_key.n_bits_precision())?);
+    rsa_encrypt(pub_key, &int)
 }
 
 /// Encrypts the given message with RSA and the padding scheme from
@@ -219,7 +346,7 @@ fn encrypt_digest<R: CryptoRngCore + ?Sized, D: Digest, MGD: Digest + FixedOutpu
 
     let em = oaep_encrypt_digest::<_, D, MGD>(rng, msg, label, pub_key.size())?;
 
-    let int = BoxedUint::from_be_slice(&em, pub_key.n_bits_precision())?;
+    let int = Zeroizing::new(BoxedUint::from_be_slice(&em, pub_key.n_bits_precision())?);
     uint_to_be_pad(rsa_encrypt(pub_key, &int)?, pub_key.size())
 }
 
@@ -238,7 +365,20 @@ fn encrypt_digest<R: CryptoRngCore + ?Sized, D: Digest, MGD: Digest + FixedOutpu
 #[inline]
 fn decrypt<R: CryptoRngCore + ?Sized>(
     rng: Option<&mut R>,
-    priv_key: &RsaPrivateKey,
+    priv_key: &impl PrivateKeyOps,
+    ciphertext: &[u8],
+    digest: &mut dyn DynDigest,
+    mgf_digest: &mut dyn DynDigest,
+    label: Option<Box<[u8]>>,
+) -> Result<Vec<u8>> {
+    opaque_decryption(decrypt_detailed(
+        rng, priv_key, ciphertext, digest, mgf_digest, label,
+    ))
+}
+
+fn decrypt_detailed<R: CryptoRngCore + ?Sized>(
+    rng: Option<&mut R>,
+    priv_key: &impl PrivateKeyOps,
     ciphertext: &[u8],
     digest: &mut dyn DynDigest,
     mgf_digest: &mut dyn DynDigest,
@@ -250,7 +390,7 @@ fn decrypt<R: CryptoRngCore + ?Sized>(
 
     let ciphertext = BoxedUint::from_be_slice(ciphertext, priv_key.n_bits_precision())?;
 
-    let em = rsa_decrypt_and_check(priv_key, rng, &ciphertext)?;
+    let em = priv_key.raw_decrypt_op(rng, &ciphertext)?;
     let mut em = uint_to_zeroizing_be_pad(em, priv_key.size())?;
 
     oaep_decrypt(&mut em, digest, mgf_digest, label, priv_key.size())
@@ -271,7 +411,18 @@ fn decrypt<R: CryptoRngCore + ?Sized>(
 #[inline]
 fn decrypt_digest<R: CryptoRngCore + ?Sized, D: Digest, MGD: Digest + FixedOutputReset>(
     rng: Option<&mut R>,
-    priv_key: &RsaPrivateKey,
+    priv_key: &impl PrivateKeyOps,
+    ciphertext: &[u8],
+    label: Option<Box<[u8]>>,
+) -> Result<Vec<u8>> {
+    opaque_decryption(decrypt_digest_detailed::<_, D, MGD>(
+        rng, priv_key, ciphertext, label,
+    ))
+}
+
+fn decrypt_digest_detailed<R: CryptoRngCore + ?Sized, D: Digest, MGD: Digest + FixedOutputReset>(
+    rng: Option<&mut R>,
+    priv_key: &impl PrivateKeyOps,
     ciphertext: &[u8],
     label: Option<Box<[u8]>>,
 ) -> Result<Vec<u8>> {
@@ -282,12 +433,157 @@ fn decrypt_digest<R: CryptoRngCore + ?Sized, D: Digest, MGD: Digest + FixedOutpu
     }
 
     let ciphertext = BoxedUint::from_be_slice(ciphertext, priv_key.n_bits_precision())?;
-    let em = rsa_decrypt_and_check(priv_key, rng, &ciphertext)?;
+    let em = priv_key.raw_decrypt_op(rng, &ciphertext)?;
     let mut em = uint_to_zeroizing_be_pad(em, priv_key.size())?;
 
     oaep_decrypt_digest::<D, MGD>(&mut em, label, priv_key.size())
 }
 
+/// ObjectID of the MGF1 mask generation function.
+const ID_MGF1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.8");
+
+/// ObjectID of the `pSpecified` label source.
+const ID_P_SPECIFIED: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.9");
+
+/// Decoded `RSAES-OAEP-params`.
+struct OaepParams {
+    digest: ObjectIdentifier,
+    mgf_digest: ObjectIdentifier,
+    label: Option<Box<[u8]>>,
+}
+
+impl OaepParams {
+    /// Decodes the parameters of an `id-RSAES-OAEP` algorithm identifier,
+    /// which default to SHA-1 and MGF1 with SHA-1 when absent.
+    fn decode(params: Option<AnyRef<'_>>) -> pkcs8::spki::Result<Self> {
+        let der = match params {
+            Some(params) => params.to_der()?,
+            None => vec![0x30, 0x00],
+        };
+        let params = RsaOaepParams::from_der(&der)?;
+        if params.mask_gen.oid != ID_MGF1 || params.p_source.oid != ID_P_SPECIFIED {
+            return Err(pkcs8::spki::Error::KeyMalformed);
+        }
+        let mgf_digest = params
+            .mask_gen
+            .parameters
+            .ok_or(pkcs8::spki::Error::KeyMalformed)?;
+
+        let label = match params.p_source.parameters {
+            Some(label) => label.to_der()?,
+            None => return Err(pkcs8::spki::Error::KeyMalformed),
+        };
+        let label = OctetStringRef::from_der(&label)?.as_bytes();
+
+        Ok(Self {
+            digest: params.hash.oid,
+            mgf_digest: mgf_digest.oid,
+            label: (!label.is_empty()).then(|| label.into()),
+        })
+    }
+}
+
+/// Returns the label of a key restricted to RSAES-OAEP with digest `D` and
+/// MGF1 with `MGD` by its `id-RSAES-OAEP` algorithm identifier, as described
+/// in [RFC4055 § 4.1].
+///
+/// `rsaEncryption` keys and `id-RSAES-OAEP` keys without parameters may be
+/// used with any digests, and get no label. Parameters naming other digests
+/// or another mask generation function or label source are rejected.
+///
+/// [RFC4055 § 4.1]: https://datatracker.ietf.org/doc/html/rfc4055#section-4.1
+fn get_oaep_key_label<D, MGD>(
+    algorithm: &AlgorithmIdentifierRef<'_>,
+) -> pkcs8::spki::Result<Option<Box<[u8]>>>
+where
+    D: Digest + AssociatedOid,
+    MGD: Digest + AssociatedOid,
+{
+    match algorithm.oid {
+        pkcs1::ALGORITHM_OID => return Ok(None),
+        ID_RSAES_OAEP => (),
+        _ => return Err(pkcs8::spki::Error::OidUnknown { oid: algorithm.oid }),
+    }
+    if algorithm.parameters.is_none() {
+        return Ok(None);
+    }
+
+    let params = OaepParams::decode(algorithm.parameters)?;
+    if params.digest != D::OID || params.mgf_digest != MGD::OID {
+        return Err(pkcs8::spki::Error::KeyMalformed);
+    }
+    Ok(params.label)
+}
+
+/// Returns the [`AlgorithmIdentifierOwned`] associated with OAEP encryption
+/// using digest `D` for the label, and MGF1 with `MGD`.
+pub fn get_default_oaep_algo_id<D>() -> pkcs8::spki::Result<AlgorithmIdentifierOwned>
+where
+    D: Digest + AssociatedOid,
+{
+    get_oaep_algo_id::<D, D>(None)
+}
+
+/// Returns the [`AlgorithmIdentifierOwned`] associated with OAEP encryption
+/// using digest `D` for the label, MGF1 with `MGD` and the given label, as
+/// described in [RFC4055 § 4.1].
+///
+/// [RFC4055 § 4.1]: https://datatracker.ietf.org/doc/html/rfc4055#section-4.1
+pub fn get_oaep_algo_id<D, MGD>(
+    label: Option<&[u8]>,
+) -> pkcs8::spki::Result<AlgorithmIdentifierOwned>
+where
+    D: Digest + AssociatedOid,
+    MGD: Digest + AssociatedOid,
+{
+    let label = OctetStringRef::new(label.unwrap_or_default())?;
+    let oaep_params = RsaOaepParams {
+        hash: AlgorithmIdentifierRef {
+            oid: D::OID,
+            parameters: Some(AnyRef::NULL),
+        },
+        mask_gen: AlgorithmIdentifier {
+            oid: ID_MGF1,
+            parameters: Some(AlgorithmIdentifierRef {
+                oid: MGD::OID,
+                parameters: Some(AnyRef::NULL),
+            }),
+        },
+        p_source: AlgorithmIdentifierRef {
+            oid: ID_P_SPECIFIED,
+            parameters: Some(label.into()),
+        },
+    };
+
+    Ok(AlgorithmIdentifierOwned {
+        oid: ID_RSAES_OAEP,
+        parameters: Some(Any::encode_from(&oaep_params)?),
+    })
+}
+
+/// Parses an `id-RSAES-OAEP` algorithm identifier, e.g. from the key
+/// transport of CMS enveloped data, into the OAEP scheme it describes.
+///
+/// The digests must be SHA-1, with the `sha1` feature enabled, or one of
+/// SHA-2, and the mask generation function must be MGF1.
+#[cfg(feature = "sha2")]
+impl TryFrom<AlgorithmIdentifierRef<'_>> for Oaep {
+    type Error = pkcs8::spki::Error;
+
+    fn try_from(algorithm: AlgorithmIdentifierRef<'_>) -> pkcs8::spki::Result<Self> {
+        if algorithm.oid != ID_RSAES_OAEP {
+            return Err(pkcs8::spki::Error::OidUnknown { oid: algorithm.oid });
+        }
+        let params = OaepParams::decode(algorithm.parameters)?;
+
+        Ok(Self {
+            digest: new_dyn_digest(params.digest)?,
+            mgf_digest: new_dyn_digest(params.mgf_digest)?,
+            label: params.label,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::key::{RsaPrivateKey, RsaPublicKey};
@@ -582,4 +878,86 @@ mod tests {
             "decrypt should have failed on hash verification"
         );
     }
+
+    #[test]
+    #[cfg(feature = "hazmat")]
+    fn test_encrypt_with_seed() {
+        use hex_literal::hex;
+
+        let priv_key = get_private_key();
+        let pub_key: RsaPublicKey = (&priv_key).into();
+        let seed = (0u8..20).collect::<Vec<_>>();
+        let ciphertext = Oaep::new::<Sha1>()
+            .encrypt_with_seed(&pub_key, b"hello world", &seed)
+            .unwrap();
+        assert_eq!(
+            ciphertext,
+            hex!("11c5b5d89fdf5551c9d879b12addfbadbf1ee3c3e5c6f8a49459e3e594d950e6bf22c16df23486d258d7053bdd28c9d0743d1627663a7d3e9cb0e32501647e174df1a68640d7d307b812757ffc3d2835fe1452a8ad10b11a7ad5bda811658c45fb07688d59041234d30faf9a5a5e5b1bd8b51edb70dbaed60a3a0a71087f417942d93f422daa578c514d0871a3c6baea8cbce7949a03ffefe731c03989f1e745c73653550019926c9e7d25b60e88b137031955fffc8572481fc1801f6af95b2ce7c1ecede2934fa5043c62de787b180a32924bb245fb293e774206d28daef523e1c3caf540416233f35527b2f2f233f430bf2b32b7e21172eb63c51c0806035a")
+        );
+        assert_eq!(
+            priv_key.decrypt(Oaep::new::<Sha1>(), &ciphertext).unwrap(),
+            b"hello world"
+        );
+        assert_eq!(
+            Oaep::new::<Sha1>().encrypt_with_seed(&pub_key, b"hello world", &seed[1..]),
+            Err(crate::Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    fn test_max_message_len() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let pub_key: RsaPublicKey = (&get_private_key()).into();
+        let max_len = Oaep::new::<Sha256>().max_message_len(&pub_key);
+        assert_eq!(max_len, 256 - 2 * 32 - 2);
+
+        let encrypting_key = EncryptingKey::<Sha256>::new(pub_key);
+        assert_eq!(encrypting_key.max_message_len(), max_len);
+        assert!(encrypting_key
+            .encrypt_with_rng(&mut rng, &vec![1; max_len])
+            .is_ok());
+        assert_eq!(
+            encrypting_key.encrypt_with_rng(&mut rng, &vec![1; max_len + 1]),
+            Err(crate::Error::MessageTooLong { max_len })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn test_oaep_from_algorithm_identifier() {
+        use crate::oaep::{get_default_oaep_algo_id, get_oaep_algo_id};
+        use pkcs8::der::{Decode, Encode};
+        use pkcs8::spki::AlgorithmIdentifierRef;
+
+        let priv_key = get_private_key();
+        let pub_key: RsaPublicKey = (&priv_key).into();
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+
+        let algorithm = get_oaep_algo_id::<Sha256, Sha384>(Some(b"label")).unwrap();
+        let oaep =
+            Oaep::try_from(AlgorithmIdentifierRef::from_der(&algorithm.to_der().unwrap()).unwrap())
+                .unwrap();
+        assert_eq!(oaep.label.as_deref(), Some(&b"label"[..]));
+        let ciphertext = pub_key.encrypt(&mut rng, oaep, b"secret").unwrap();
+        let oaep = Oaep::new_with_mgf_hash_and_label::<Sha256, Sha384, _>("label");
+        assert_eq!(priv_key.decrypt(oaep, &ciphertext).unwrap(), b"secret");
+
+        // RSAES-OAEP with SHA-256, MGF1 with SHA-256 and the empty label,
+        // as emitted by OpenSSL.
+        assert_eq!(
+            get_default_oaep_algo_id::<Sha256>()
+                .unwrap()
+                .to_der()
+                .unwrap(),
+            hex_literal::hex!(
+                "303c06092a864886f70d010107302fa00f300d06096086480165030402010500"
+                "a11c301a06092a864886f70d010108300d06096086480165030402010500"
+            )
+        );
+        let algorithm = get_default_oaep_algo_id::<Sha3_256>().unwrap();
+        assert!(Oaep::try_from(
+            AlgorithmIdentifierRef::from_der(&algorithm.to_der().unwrap()).unwrap()
+        )
+        .is_err());
+    }
 }
@@ -1,12 +1,14 @@
-use super::decrypt_digest;
+use super::{decrypt_digest, get_oaep_algo_id, get_oaep_key_label};
 use crate::{
     dummy_rng::DummyRng,
-    traits::{Decryptor, RandomizedDecryptor},
+    traits::{Decryptor, PrivateKeyOps, RandomizedDecryptor},
     Result, RsaPrivateKey,
 };
 use alloc::{boxed::Box, vec::Vec};
+use const_oid::AssociatedOid;
 use core::marker::PhantomData;
 use digest::{Digest, FixedOutputReset};
+use pkcs8::{der::AnyRef, spki::AlgorithmIdentifierRef, EncodePrivateKey, SecretDocument};
 use rand_core::CryptoRngCore;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -14,27 +16,30 @@ use zeroize::ZeroizeOnDrop;
 
 /// Decryption key for PKCS#1 v1.5 decryption as described in [RFC8017 § 7.1].
 ///
+/// The private key operation is performed by `K`, which is an [`RsaPrivateKey`]
+/// by default but can be any [`RsaPrivateOps`] implementation.
+///
 /// [RFC8017 § 7.1]: https://datatracker.ietf.org/doc/html/rfc8017#section-7.1
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct DecryptingKey<D, MGD = D>
+pub struct DecryptingKey<D, MGD = D, K = RsaPrivateKey>
 where
     D: Digest,
     MGD: Digest + FixedOutputReset,
 {
-    inner: RsaPrivateKey,
+    inner: K,
     label: Option<Box<[u8]>>,
     phantom: PhantomData<D>,
     mg_phantom: PhantomData<MGD>,
 }
 
-impl<D, MGD> DecryptingKey<D, MGD>
+impl<D, MGD, K> DecryptingKey<D, MGD, K>
 where
     D: Digest,
     MGD: Digest + FixedOutputReset,
 {
     /// Create a new verifying key from an RSA public key.
-    pub fn new(key: RsaPrivateKey) -> Self {
+    pub fn new(key: K) -> Self {
         Self {
             inner: key,
             label: None,
@@ -44,7 +49,7 @@ where
     }
 
     /// Create a new verifying key from an RSA public key using provided label
-    pub fn new_with_label<S: Into<Box<[u8]>>>(key: RsaPrivateKey, label: S) -> Self {
+    pub fn new_with_label<S: Into<Box<[u8]>>>(key: K, label: S) -> Self {
         Self {
             inner: key,
             label: Some(label.into()),
@@ -54,20 +59,22 @@ where
     }
 }
 
-impl<D, MGD> Decryptor for DecryptingKey<D, MGD>
+impl<D, MGD, K> Decryptor for DecryptingKey<D, MGD, K>
 where
     D: Digest,
     MGD: Digest + FixedOutputReset,
+    K: PrivateKeyOps,
 {
     fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
         decrypt_digest::<DummyRng, D, MGD>(None, &self.inner, ciphertext, self.label.clone())
     }
 }
 
-impl<D, MGD> RandomizedDecryptor for DecryptingKey<D, MGD>
+impl<D, MGD, K> RandomizedDecryptor for DecryptingKey<D, MGD, K>
 where
     D: Digest,
     MGD: Digest + FixedOutputReset,
+    K: PrivateKeyOps,
 {
     fn decrypt_with_rng<R: CryptoRngCore + ?Sized>(
         &self,
@@ -78,6 +85,57 @@ where
     }
 }
 
+/// Encoded as PKCS#8 with the `id-RSAES-OAEP` algorithm and parameters
+/// holding the digests and the label, as described in [RFC4055 § 4.1].
+///
+/// [RFC4055 § 4.1]: https://datatracker.ietf.org/doc/html/rfc4055#section-4.1
+impl<D, MGD> EncodePrivateKey for DecryptingKey<D, MGD>
+where
+    D: Digest + AssociatedOid,
+    MGD: Digest + FixedOutputReset + AssociatedOid,
+{
+    fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
+        let algorithm = get_oaep_algo_id::<D, MGD>(self.label.as_deref())?;
+        let der = self.inner.to_pkcs8_der()?;
+        let private_key_info = pkcs8::PrivateKeyInfoRef::try_from(der.as_bytes())?;
+
+        pkcs8::PrivateKeyInfoRef {
+            algorithm: AlgorithmIdentifierRef {
+                oid: algorithm.oid,
+                parameters: algorithm.parameters.as_ref().map(AnyRef::from),
+            },
+            ..private_key_info
+        }
+        .try_into()
+    }
+}
+
+/// Decoded from PKCS#8 with the `rsaEncryption` or the `id-RSAES-OAEP`
+/// algorithm. The parameters of the latter must name `D` and MGF1 with
+/// `MGD`, and give the label.
+impl<D, MGD> TryFrom<pkcs8::PrivateKeyInfoRef<'_>> for DecryptingKey<D, MGD>
+where
+    D: Digest + AssociatedOid,
+    MGD: Digest + FixedOutputReset + AssociatedOid,
+{
+    type Error = pkcs8::Error;
+
+    fn try_from(private_key_info: pkcs8::PrivateKeyInfoRef<'_>) -> pkcs8::Result<Self> {
+        let label = get_oaep_key_label::<D, MGD>(&private_key_info.algorithm)?;
+        let private_key_info = pkcs8::PrivateKeyInfoRef {
+            algorithm: pkcs1::ALGORITHM_ID,
+            ..private_key_info
+        };
+
+        Ok(Self {
+            inner: RsaPrivateKey::try_from(private_key_info)?,
+            label,
+            phantom: Default::default(),
+            mg_phantom: Default::default(),
+        })
+    }
+}
+
 impl<D, MGD> ZeroizeOnDrop for DecryptingKey<D, MGD>
 where
     D: Digest,
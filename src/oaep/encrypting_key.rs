@@ -1,8 +1,12 @@
-use super::encrypt_digest;
+use super::{encrypt_digest, get_oaep_algo_id, get_oaep_key_label};
+use crate::algorithms::oaep::max_message_len;
+use crate::traits::PublicKeyParts;
 use crate::{traits::RandomizedEncryptor, Result, RsaPublicKey};
 use alloc::{boxed::Box, vec::Vec};
+use const_oid::AssociatedOid;
 use core::marker::PhantomData;
 use digest::{Digest, FixedOutputReset};
+use pkcs8::{der::AnyRef, spki::AlgorithmIdentifierRef, Document, EncodePublicKey};
 use rand_core::CryptoRngCore;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -47,6 +51,12 @@ where
             mg_phantom: Default::default(),
         }
     }
+
+    /// Returns the maximum length of the messages which can be encrypted
+    /// with this key.
+    pub fn max_message_len(&self) -> usize {
+        max_message_len(self.inner.size(), <D as Digest>::output_size())
+    }
 }
 
 impl<D, MGD> RandomizedEncryptor for EncryptingKey<D, MGD>
@@ -63,6 +73,58 @@ where
     }
 }
 
+/// Encoded as a `SubjectPublicKeyInfo` with the `id-RSAES-OAEP` algorithm
+/// and parameters holding the digests and the label, as described in
+/// [RFC4055 § 4.1].
+///
+/// [RFC4055 § 4.1]: https://datatracker.ietf.org/doc/html/rfc4055#section-4.1
+impl<D, MGD> EncodePublicKey for EncryptingKey<D, MGD>
+where
+    D: Digest + AssociatedOid,
+    MGD: Digest + FixedOutputReset + AssociatedOid,
+{
+    fn to_public_key_der(&self) -> pkcs8::spki::Result<Document> {
+        let algorithm = get_oaep_algo_id::<D, MGD>(self.label.as_deref())?;
+        let der = self.inner.to_public_key_der()?;
+        let spki = pkcs8::SubjectPublicKeyInfoRef::try_from(der.as_bytes())?;
+
+        pkcs8::SubjectPublicKeyInfoRef {
+            algorithm: AlgorithmIdentifierRef {
+                oid: algorithm.oid,
+                parameters: algorithm.parameters.as_ref().map(AnyRef::from),
+            },
+            subject_public_key: spki.subject_public_key,
+        }
+        .try_into()
+    }
+}
+
+/// Decoded from a `SubjectPublicKeyInfo` with the `rsaEncryption` or the
+/// `id-RSAES-OAEP` algorithm. The parameters of the latter must name `D`
+/// and MGF1 with `MGD`, and give the label.
+impl<D, MGD> TryFrom<pkcs8::SubjectPublicKeyInfoRef<'_>> for EncryptingKey<D, MGD>
+where
+    D: Digest + AssociatedOid,
+    MGD: Digest + FixedOutputReset + AssociatedOid,
+{
+    type Error = pkcs8::spki::Error;
+
+    fn try_from(spki: pkcs8::SubjectPublicKeyInfoRef<'_>) -> pkcs8::spki::Result<Self> {
+        let label = get_oaep_key_label::<D, MGD>(&spki.algorithm)?;
+        let spki = pkcs8::SubjectPublicKeyInfoRef {
+            algorithm: pkcs1::ALGORITHM_ID,
+            subject_public_key: spki.subject_public_key,
+        };
+
+        Ok(Self {
+            inner: RsaPublicKey::try_from(spki)?,
+            label,
+            phantom: Default::default(),
+            mg_phantom: Default::default(),
+        })
+    }
+}
+
 impl<D, MGD> PartialEq for EncryptingKey<D, MGD>
 where
     D: Digest,
@@ -75,6 +137,59 @@ where
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::oaep::DecryptingKey;
+    use crate::traits::Decryptor;
+    use crate::RsaPrivateKey;
+    use hex_literal::hex;
+    use pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::{Sha256, Sha384};
+
+    #[test]
+    fn test_encoding() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let priv_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let encrypting_key =
+            EncryptingKey::<Sha256>::new_with_label(priv_key.to_public_key(), &b"label"[..]);
+
+        let der = encrypting_key.to_public_key_der().unwrap();
+        // The `AlgorithmIdentifier` holds `RSAES-OAEP-params` with SHA-256,
+        // MGF1 with SHA-256 and the label.
+        assert_eq!(
+            &der.as_bytes()[3..87],
+            hex!(
+                "305206092a864886f70d0101073045a00f300d06096086480165030402010500"
+                "a11c301a06092a864886f70d010108300d06096086480165030402010500a214"
+                "301206092a864886f70d01010904056c6162656c"
+            )
+        );
+        assert_eq!(
+            EncryptingKey::<Sha256>::from_public_key_der(der.as_bytes()).unwrap(),
+            encrypting_key
+        );
+        assert!(EncryptingKey::<Sha384>::from_public_key_der(der.as_bytes()).is_err());
+        assert!(EncryptingKey::<Sha256, Sha384>::from_public_key_der(der.as_bytes()).is_err());
+        assert!(RsaPublicKey::from_public_key_der(der.as_bytes()).is_err());
+
+        // `rsaEncryption` keys are accepted without a label.
+        let der = priv_key.to_public_key().to_public_key_der().unwrap();
+        assert_eq!(
+            EncryptingKey::<Sha384>::from_public_key_der(der.as_bytes()).unwrap(),
+            EncryptingKey::new(priv_key.to_public_key())
+        );
+
+        let decrypting_key = DecryptingKey::<Sha256>::new_with_label(priv_key, &b"label"[..]);
+        let der = decrypting_key.to_pkcs8_der().unwrap();
+        let decrypting_key = DecryptingKey::<Sha256>::from_pkcs8_der(der.as_bytes()).unwrap();
+        assert!(DecryptingKey::<Sha384>::from_pkcs8_der(der.as_bytes()).is_err());
+        assert!(RsaPrivateKey::from_pkcs8_der(der.as_bytes()).is_err());
+
+        let ciphertext = encrypting_key
+            .encrypt_with_rng(&mut rng, b"secret")
+            .unwrap();
+        assert_eq!(decrypting_key.decrypt(&ciphertext).unwrap(), b"secret");
+    }
 
     #[test]
     #[cfg(feature = "serde")]
@@ -0,0 +1,218 @@
+//! Private keys held by PKCS#11 tokens.
+//!
+//! [`Pkcs11PrivateKey`] performs the raw private key operation on a token
+//! through [`cryptoki`], using the `CKM_RSA_X_509` mechanism with `C_Sign`
+//! for signatures and `C_Decrypt` for decryption, while the padding is
+//! applied by this crate. The key implements [`RsaPrivateOps`], so
+//! it can be used with the padding schemes and the signing and decrypting keys
+//! of this crate, without the private key ever leaving the token:
+//!
+//! ```no_run
+//! use cryptoki::context::{CInitializeArgs, Pkcs11};
+//! use cryptoki::object::{Attribute, ObjectClass};
+//! use cryptoki::session::UserType;
+//! use cryptoki::types::AuthPin;
+//! use rsa::pkcs11::Pkcs11PrivateKey;
+//! use rsa::pkcs1v15::SigningKey;
+//! use rsa::signature::Signer;
+//! use sha2::Sha256;
+//!
+//! let pkcs11 = Pkcs11::new("/usr/lib/softhsm/libsofthsm2.so")?;
+//! pkcs11.initialize(CInitializeArgs::OsThreads)?;
+//! let slot = pkcs11.get_slots_with_token()?[0];
+//! let session = pkcs11.open_ro_session(slot)?;
+//! session.login(UserType::User, Some(&AuthPin::new("1234".into())))?;
+//! let handle = session.find_objects(&[
+//!     Attribute::Class(ObjectClass::PRIVATE_KEY),
+//!     Attribute::Label(b"signing key".to_vec()),
+//! ])?[0];
+//!
+//! let key = Pkcs11PrivateKey::new(session, handle)?;
+//! let signing_key = SigningKey::<Sha256, _>::new(key);
+//! let signature = signing_key.sign(b"hello world");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use core::fmt;
+use std::sync::Mutex;
+
+use crypto_bigint::modular::BoxedMontyParams;
+use crypto_bigint::{BoxedUint, NonZero};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, ObjectHandle};
+use cryptoki::session::Session;
+use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
+
+use crate::algorithms::rsa::rsa_external_op;
+use crate::errors::{Error, Result};
+use crate::traits::{PublicKeyParts, RsaPrivateOps};
+use crate::RsaPublicKey;
+
+/// RSA private key stored on a PKCS#11 token.
+///
+/// The session is locked for the duration of each operation, so the key can
+/// be shared between threads. Blinding is left to the token, and the results
+/// of the token are checked with the public key before they are used.
+pub struct Pkcs11PrivateKey {
+    session: Mutex<Session>,
+    handle: ObjectHandle,
+    public_key: RsaPublicKey,
+    /// Whether the key object allows signing, i.e. `CKA_SIGN`.
+    sign: bool,
+    /// Whether the key object allows decryption, i.e. `CKA_DECRYPT`.
+    decrypt: bool,
+}
+
+impl Pkcs11PrivateKey {
+    /// Use the private key object `handle` through the logged in `session`.
+    ///
+    /// The modulus and public exponent are read from the key object, which
+    /// must allow signing or decryption. Signing fails with
+    /// [`Error::Backend`] unless the key object has `CKA_SIGN` set, and
+    /// decryption unless it has `CKA_DECRYPT` set.
+    pub fn new(session: Session, handle: ObjectHandle) -> Result<Self> {
+        let attributes = session
+            .get_attributes(
+                handle,
+                &[
+                    AttributeType::Modulus,
+                    AttributeType::PublicExponent,
+                    AttributeType::Sign,
+                    AttributeType::Decrypt,
+                ],
+            )
+            .map_err(|_| Error::Backend)?;
+
+        let (mut n, mut e, mut sign, mut decrypt) = (None, None, false, false);
+        for attribute in attributes {
+            match attribute {
+                Attribute::Modulus(bytes) => n = Some(uint_from_attribute(&bytes)?),
+                Attribute::PublicExponent(bytes) => e = Some(uint_from_attribute(&bytes)?),
+                Attribute::Sign(allowed) => sign = allowed,
+                Attribute::Decrypt(allowed) => decrypt = allowed,
+                _ => {}
+            }
+        }
+
+        if !sign && !decrypt {
+            return Err(Error::Backend);
+        }
+        let public_key = RsaPublicKey::new(
+            n.ok_or(Error::InvalidModulus)?,
+            e.ok_or(Error::InvalidExponent)?,
+        )?;
+
+        Ok(Self {
+            session: Mutex::new(session),
+            handle,
+            public_key,
+            sign,
+            decrypt,
+        })
+    }
+
+    /// Returns the handle of the key object.
+    pub fn handle(&self) -> ObjectHandle {
+        self.handle
+    }
+
+    /// Returns the public key.
+    pub fn to_public_key(&self) -> RsaPublicKey {
+        self.public_key.clone()
+    }
+
+    /// Returns the session, e.g. to log out or close it.
+    pub fn into_session(self) -> Session {
+        self.session
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for Pkcs11PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pkcs11PrivateKey")
+            .field("handle", &self.handle)
+            .field("public_key", &self.public_key)
+            .field("sign", &self.sign)
+            .field("decrypt", &self.decrypt)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsRef<RsaPublicKey> for Pkcs11PrivateKey {
+    fn as_ref(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+}
+
+impl PublicKeyParts for Pkcs11PrivateKey {
+    fn n(&self) -> &NonZero<BoxedUint> {
+        self.public_key.n()
+    }
+
+    fn e(&self) -> &BoxedUint {
+        self.public_key.e()
+    }
+
+    fn n_params(&self) -> &BoxedMontyParams {
+        self.public_key.n_params()
+    }
+}
+
+impl Pkcs11PrivateKey {
+    /// Performs the raw private key operation with `C_Sign` or `C_Decrypt`.
+    fn token_op(&self, operation: Operation, c: &BoxedUint) -> Result<BoxedUint> {
+        let allowed = match operation {
+            Operation::Sign => self.sign,
+            Operation::Decrypt => self.decrypt,
+        };
+        if !allowed {
+            return Err(Error::Backend);
+        }
+
+        rsa_external_op(&self.public_key, c, |input| {
+            let session = self.session.lock().map_err(|_| Error::Backend)?;
+            let mechanism = Mechanism::RsaX509;
+            match operation {
+                Operation::Sign => session.sign(&mechanism, self.handle, input),
+                Operation::Decrypt => session.decrypt(&mechanism, self.handle, input),
+            }
+            .map(Zeroizing::new)
+            .map_err(|_| Error::Backend)
+        })
+    }
+}
+
+/// Token operation computing the raw RSA private key operation.
+#[derive(Clone, Copy, Debug)]
+enum Operation {
+    Sign,
+    Decrypt,
+}
+
+impl RsaPrivateOps for Pkcs11PrivateKey {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        _rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        self.token_op(Operation::Sign, c)
+    }
+
+    fn raw_decrypt_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        _rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        self.token_op(Operation::Decrypt, c)
+    }
+}
+
+/// Decodes a big integer attribute, ignoring leading zeros.
+fn uint_from_attribute(bytes: &[u8]) -> Result<BoxedUint> {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    let bytes = &bytes[start..];
+    Ok(BoxedUint::from_be_slice(bytes, bytes.len() as u32 * 8)?)
+}
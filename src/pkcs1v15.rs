@@ -18,28 +18,38 @@ pub use self::{
 };
 
 use alloc::{boxed::Box, vec::Vec};
+use const_oid::ObjectIdentifier;
 use core::fmt::Debug;
 use crypto_bigint::BoxedUint;
 use digest::Digest;
 use pkcs8::AssociatedOid;
 use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
 
-use crate::algorithms::pad::{uint_to_be_pad, uint_to_zeroizing_be_pad};
+use crate::algorithms::pad::{uint_to_be_pad, uint_to_be_pad_into, uint_to_zeroizing_be_pad};
 use crate::algorithms::pkcs1v15::*;
-use crate::algorithms::rsa::{rsa_decrypt_and_check, rsa_encrypt};
-use crate::errors::{Error, Result};
-use crate::key::{self, RsaPrivateKey, RsaPublicKey};
-use crate::traits::{PaddingScheme, PublicKeyParts, SignatureScheme};
+use crate::algorithms::rsa::rsa_encrypt;
+use crate::errors::{opaque_decryption, Error, Result};
+use crate::key::{self, RsaPublicKey};
+use crate::traits::{PaddingScheme, PrivateKeyOps, PublicKeyParts, SignatureScheme};
 
 /// Encryption using PKCS#1 v1.5 padding.
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct Pkcs1v15Encrypt;
 
+impl Pkcs1v15Encrypt {
+    /// Returns the maximum length of the messages which can be encrypted
+    /// with `key` using this padding, `k - 11`.
+    pub fn max_message_len(&self, key: &impl PublicKeyParts) -> usize {
+        pkcs1v15_encrypt_max_message_len(key.size())
+    }
+}
+
 impl PaddingScheme for Pkcs1v15Encrypt {
     fn decrypt<Rng: CryptoRngCore>(
         self,
         rng: Option<&mut Rng>,
-        priv_key: &RsaPrivateKey,
+        priv_key: &impl PrivateKeyOps,
         ciphertext: &[u8],
     ) -> Result<Vec<u8>> {
         decrypt(rng, priv_key, ciphertext)
@@ -53,8 +63,33 @@ impl PaddingScheme for Pkcs1v15Encrypt {
     ) -> Result<Vec<u8>> {
         encrypt(rng, pub_key, msg)
     }
+
+    fn encrypt_into<Rng: CryptoRngCore>(
+        self,
+        rng: &mut Rng,
+        pub_key: &RsaPublicKey,
+        msg: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        uint_to_be_pad_into(&encrypt_uint(rng, pub_key, msg)?, pub_key.size(), out)
+    }
 }
 
+/// OID of the SM3 digest (GB/T 32905-2016).
+pub const ID_SM3: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.156.10197.1.401");
+
+/// OID of the 256-bit Streebog digest ([RFC6986], [RFC7836]).
+///
+/// [RFC6986]: https://datatracker.ietf.org/doc/html/rfc6986
+/// [RFC7836]: https://datatracker.ietf.org/doc/html/rfc7836
+pub const ID_STREEBOG_256: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.643.7.1.1.2.2");
+
+/// OID of the 512-bit Streebog digest ([RFC6986], [RFC7836]).
+///
+/// [RFC6986]: https://datatracker.ietf.org/doc/html/rfc6986
+/// [RFC7836]: https://datatracker.ietf.org/doc/html/rfc7836
+pub const ID_STREEBOG_512: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.643.7.1.1.2.3");
+
 /// `RSASSA-PKCS1-v1_5`: digital signatures using PKCS#1 v1.5 padding.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Pkcs1v15Sign {
@@ -80,6 +115,18 @@ impl Pkcs1v15Sign {
         }
     }
 
+    /// Create new PKCS#1 v1.5 padding for the digest identified by `oid`,
+    /// with an output of `hash_len` bytes.
+    ///
+    /// This allows digests without an [`AssociatedOid`], such as SM3 or
+    /// Streebog, using e.g. [`ID_SM3`] or [`ID_STREEBOG_256`].
+    pub fn new_with_oid(oid: ObjectIdentifier, hash_len: usize) -> Result<Self> {
+        Ok(Self {
+            hash_len: Some(hash_len),
+            prefix: pkcs1v15_generate_prefix_for_oid(&oid, hash_len)?.into_boxed_slice(),
+        })
+    }
+
     /// Create new PKCS#1 v1.5 padding for computing an unprefixed signature.
     ///
     /// This sets `hash_len` to `None` and uses an empty `prefix`.
@@ -103,7 +150,7 @@ impl SignatureScheme for Pkcs1v15Sign {
     fn sign<Rng: CryptoRngCore>(
         self,
         rng: Option<&mut Rng>,
-        priv_key: &RsaPrivateKey,
+        priv_key: &impl PrivateKeyOps,
         hashed: &[u8],
     ) -> Result<Vec<u8>> {
         if let Some(hash_len) = self.hash_len {
@@ -115,6 +162,23 @@ impl SignatureScheme for Pkcs1v15Sign {
         sign(rng, priv_key, &self.prefix, hashed)
     }
 
+    fn sign_into<Rng: CryptoRngCore>(
+        self,
+        rng: Option<&mut Rng>,
+        priv_key: &impl PrivateKeyOps,
+        hashed: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        if let Some(hash_len) = self.hash_len {
+            if hashed.len() != hash_len {
+                return Err(Error::InputNotHashed);
+            }
+        }
+
+        let sig = sign_uint(rng, priv_key, &self.prefix, hashed)?;
+        uint_to_be_pad_into(&sig, priv_key.size(), out)
+    }
+
     fn verify(self, pub_key: &RsaPublicKey, hashed: &[u8], sig: &[u8]) -> Result<()> {
         if let Some(hash_len) = self.hash_len {
             if hashed.len() != hash_len {
@@ -140,11 +204,20 @@ fn encrypt<R: CryptoRngCore + ?Sized>(
     pub_key: &RsaPublicKey,
     msg: &[u8],
 ) -> Result<Vec<u8>> {
+    uint_to_be_pad(encrypt_uint(rng, pub_key, msg)?, pub_key.size())
+}
+
+/// Like [`encrypt`], but returns the ciphertext as an integer.
+fn encrypt_uint<R: CryptoRngCore + ?Sized>(
+    rng: &mut R,
+    pub_key: &RsaPublicKey,
+    msg: &[u8],
+) -> Result<BoxedUint> {
     key::check_public(pub_key)?;
 
     let em = pkcs1v15_encrypt_pad(rng, msg, pub_key.size())?;
-    let int = BoxedUint::from_be_slice(&em, pub_key.n_bits_precision())?;
-    uint_to_be_pad(rsa_encrypt(pub_key, &int)?, pub_key.size())
+    let int = Zeroizing::new(BoxedUint::from_be_slice(&em, pub_key.n_bits_precision())?);
+    rsa_encrypt(pub_key, &int)
 }
 
 /// Decrypts a plaintext using RSA and the padding scheme from PKCS#1 v1.5.
@@ -159,16 +232,24 @@ fn encrypt<R: CryptoRngCore + ?Sized>(
 #[inline]
 fn decrypt<R: CryptoRngCore + ?Sized>(
     rng: Option<&mut R>,
-    priv_key: &RsaPrivateKey,
+    priv_key: &impl PrivateKeyOps,
+    ciphertext: &[u8],
+) -> Result<Vec<u8>> {
+    opaque_decryption(decrypt_detailed(rng, priv_key, ciphertext))
+}
+
+fn decrypt_detailed<R: CryptoRngCore + ?Sized>(
+    rng: Option<&mut R>,
+    priv_key: &impl PrivateKeyOps,
     ciphertext: &[u8],
 ) -> Result<Vec<u8>> {
     key::check_public(priv_key)?;
 
     let ciphertext = BoxedUint::from_be_slice(ciphertext, priv_key.n_bits_precision())?;
-    let em = rsa_decrypt_and_check(priv_key, rng, &ciphertext)?;
+    let em = priv_key.raw_decrypt_op(rng, &ciphertext)?;
     let em = uint_to_zeroizing_be_pad(em, priv_key.size())?;
 
-    pkcs1v15_encrypt_unpad(em, priv_key.size())
+    pkcs1v15_encrypt_unpad(&em, priv_key.size())
 }
 
 /// Calculates the signature of hashed using
@@ -187,14 +268,24 @@ fn decrypt<R: CryptoRngCore + ?Sized>(
 #[inline]
 fn sign<R: CryptoRngCore + ?Sized>(
     rng: Option<&mut R>,
-    priv_key: &RsaPrivateKey,
+    priv_key: &impl PrivateKeyOps,
     prefix: &[u8],
     hashed: &[u8],
 ) -> Result<Vec<u8>> {
+    uint_to_be_pad(sign_uint(rng, priv_key, prefix, hashed)?, priv_key.size())
+}
+
+/// Like [`sign`], but returns the signature as an integer.
+fn sign_uint<R: CryptoRngCore + ?Sized>(
+    rng: Option<&mut R>,
+    priv_key: &impl PrivateKeyOps,
+    prefix: &[u8],
+    hashed: &[u8],
+) -> Result<BoxedUint> {
     let em = pkcs1v15_sign_pad(prefix, hashed, priv_key.size())?;
 
     let em = BoxedUint::from_be_slice(&em, priv_key.n_bits_precision())?;
-    uint_to_zeroizing_be_pad(rsa_decrypt_and_check(priv_key, rng, &em)?, priv_key.size())
+    priv_key.raw_private_op(rng, &em)
 }
 
 /// Verifies an RSA PKCS#1 v1.5 signature.
@@ -205,7 +296,7 @@ fn verify(pub_key: &RsaPublicKey, prefix: &[u8], hashed: &[u8], sig: &BoxedUint)
         return Err(Error::Verification);
     }
 
-    let em = uint_to_be_pad(rsa_encrypt(pub_key, sig)?, pub_key.size())?;
+    let em = uint_to_be_pad(pub_key.verify_raw(sig)?, pub_key.size())?;
 
     pkcs1v15_sign_unpad(prefix, hashed, &em, pub_key.size())
 }
@@ -349,6 +440,19 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(not(feature = "debug-oracle"))]
+    fn test_decrypt_pkcs1v15_opaque_errors() {
+        let priv_key = get_private_key();
+        let k = priv_key.size();
+        for ciphertext in [vec![0xff; k + 1], vec![0xff; k], vec![0; k], vec![]] {
+            assert_eq!(
+                priv_key.decrypt(Pkcs1v15Encrypt, &ciphertext),
+                Err(Error::Decryption)
+            );
+        }
+    }
+
     #[test]
     fn test_decrypt_pkcs1v15_traits() {
         let priv_key = get_private_key();
@@ -373,6 +477,11 @@ mod tests {
                 .decrypt(&Base64::decode_vec(test[0]).unwrap())
                 .unwrap();
             assert_eq!(out, test[1].as_bytes());
+
+            let out = decrypting_key
+                .decrypt_zeroizing(&Base64::decode_vec(test[0]).unwrap())
+                .unwrap();
+            assert_eq!(out.as_slice(), test[1].as_bytes());
         }
     }
 
@@ -461,6 +570,88 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sign_pkcs1v15_with_oid() {
+        let priv_key = get_private_key();
+        let pub_key = priv_key.to_public_key();
+
+        assert_eq!(
+            Pkcs1v15Sign::new_with_oid(<Sha256 as AssociatedOid>::OID, 32).unwrap(),
+            Pkcs1v15Sign::new::<Sha256>()
+        );
+        assert_eq!(
+            Pkcs1v15Sign::new_with_oid(ID_SM3, 32)
+                .unwrap()
+                .prefix
+                .as_ref(),
+            hex!("3030300c06082a811ccf5501831105000420")
+        );
+        assert_eq!(
+            Pkcs1v15Sign::new_with_oid(ID_STREEBOG_512, 120),
+            Err(Error::InvalidArguments)
+        );
+
+        // SHA-256 stands in for SM3, which has the same output size.
+        let signing_key = SigningKey::<Sha256>::new_with_oid(priv_key, ID_SM3).unwrap();
+        let signature = signing_key.sign(b"message");
+        let verifying_key = VerifyingKey::<Sha256>::new_with_oid(pub_key.clone(), ID_SM3).unwrap();
+        assert!(verifying_key.verify(b"message", &signature).is_ok());
+        assert!(VerifyingKey::<Sha256>::new(pub_key)
+            .verify(b"message", &signature)
+            .is_err());
+    }
+
+    #[test]
+    fn test_verify_pkcs1v15_absent_params() {
+        let priv_key = get_private_key();
+        let verifying_key = VerifyingKey::<Sha256>::new(priv_key.to_public_key());
+        let lenient_key = verifying_key.clone().accept_absent_params();
+        let hashed = Sha256::digest(b"message");
+
+        // `DigestInfo` with the SHA-256 `AlgorithmIdentifier` lacking `NULL`.
+        let digest_info = [&hex!("302f300b06096086480165030402010420")[..], &hashed[..]].concat();
+        let signature = priv_key
+            .sign(Pkcs1v15Sign::new_unprefixed(), &digest_info)
+            .unwrap();
+        let signature = Signature::try_from(signature.as_slice()).unwrap();
+        assert!(verifying_key.verify_prehash(&hashed, &signature).is_err());
+        assert!(lenient_key.verify_prehash(&hashed, &signature).is_ok());
+        assert!(lenient_key.verify(b"message", &signature).is_ok());
+        assert!(lenient_key.verify(b"other", &signature).is_err());
+
+        let signature = SigningKey::<Sha256>::new(priv_key).sign(b"message");
+        assert!(lenient_key.verify(b"message", &signature).is_ok());
+        assert!(lenient_key != verifying_key);
+    }
+
+    #[test]
+    fn test_sign_pkcs1v15_multipart() {
+        let priv_key = get_private_key();
+
+        let expected = hex!(
+            "a4f3fa6ea93bcdd0c57be020c1193ecbfd6f200a3d95c409769b029578fa0e33"
+            "6ad9a347600e40d3ae823b8c7e6bad88cc07c1d54c3a1523cbbb6d58efc362ae"
+        );
+
+        let signing_key = SigningKey::<Sha1>::new(priv_key);
+        let sig = signing_key
+            .try_multipart_sign(&[b"Te", b"st.", b"\n"])
+            .expect("failed to sign");
+        assert_eq!(sig.to_bytes().as_ref(), expected);
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let sig2 = signing_key
+            .try_multipart_sign_with_rng(&mut rng, &[b"Test.\n"])
+            .expect("failed to sign");
+        assert_eq!(sig2, sig);
+
+        let verifying_key = signing_key.verifying_key();
+        verifying_key
+            .multipart_verify(&[b"", b"Test.\n"], &sig)
+            .expect("failed to verify");
+        assert!(verifying_key.multipart_verify(&[b"Test."], &sig).is_err());
+    }
+
     #[test]
     fn test_sign_pkcs1v15_signer_sha2_256() {
         let priv_key = get_private_key();
@@ -695,4 +886,88 @@ mod tests {
             .verify_prehash(msg, &Signature::try_from(expected_sig.as_slice()).unwrap())
             .expect("failed to verify");
     }
+
+    #[test]
+    fn test_external_private_ops() {
+        use crate::traits::RsaPrivateOps;
+        use crypto_bigint::{modular::BoxedMontyParams, NonZero};
+
+        /// Key only exposing the raw private operation, like a token would.
+        struct ExternalKey(RsaPrivateKey);
+
+        impl PublicKeyParts for ExternalKey {
+            fn n(&self) -> &NonZero<BoxedUint> {
+                self.0.n()
+            }
+
+            fn e(&self) -> &BoxedUint {
+                self.0.e()
+            }
+
+            fn n_params(&self) -> &BoxedMontyParams {
+                self.0.n_params()
+            }
+        }
+
+        impl RsaPrivateOps for ExternalKey {
+            fn raw_private_op<R: CryptoRngCore + ?Sized>(
+                &self,
+                rng: Option<&mut R>,
+                c: &BoxedUint,
+            ) -> Result<BoxedUint> {
+                PrivateKeyOps::raw_private_op(&self.0, rng, c)
+            }
+        }
+
+        let priv_key = get_private_key();
+        let pub_key = priv_key.to_public_key();
+        let key = ExternalKey(priv_key.clone());
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+
+        let hashed = Sha256::digest(b"external");
+        let sig = Pkcs1v15Sign::new::<Sha256>()
+            .sign(Some(&mut rng), &key, &hashed)
+            .expect("failed to sign");
+        assert_eq!(
+            sig,
+            priv_key
+                .sign(Pkcs1v15Sign::new::<Sha256>(), &hashed)
+                .unwrap()
+        );
+        pub_key
+            .verify(Pkcs1v15Sign::new::<Sha256>(), &hashed, &sig)
+            .expect("failed to verify");
+
+        let ciphertext = pub_key.encrypt(&mut rng, Pkcs1v15Encrypt, b"msg").unwrap();
+        let plaintext = Pkcs1v15Encrypt
+            .decrypt(Some(&mut rng), &key, &ciphertext)
+            .expect("failed to decrypt");
+        assert_eq!(plaintext, b"msg");
+    }
+
+    #[test]
+    fn test_signing_key_with_private_ops() {
+        use crate::SharedRsaPrivateKey;
+
+        let priv_key = get_private_key();
+        let signing_key = SigningKey::<Sha256, _>::new(SharedRsaPrivateKey::new(priv_key.clone()));
+        let verifying_key = signing_key.verifying_key();
+
+        let sig = signing_key.sign(b"generic key");
+        assert_eq!(
+            sig,
+            SigningKey::<Sha256>::new(priv_key).sign(b"generic key")
+        );
+        verifying_key
+            .verify(b"generic key", &sig)
+            .expect("failed to verify");
+
+        let decrypting_key = DecryptingKey::new(SharedRsaPrivateKey::new(get_private_key()));
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let ciphertext = decrypting_key
+            .encrypting_key()
+            .encrypt_with_rng(&mut rng, b"msg")
+            .unwrap();
+        assert_eq!(decrypting_key.decrypt(&ciphertext).unwrap(), b"msg");
+    }
 }
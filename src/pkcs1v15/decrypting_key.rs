@@ -1,8 +1,8 @@
 use super::{decrypt, EncryptingKey};
 use crate::{
     dummy_rng::DummyRng,
-    traits::{Decryptor, EncryptingKeypair, RandomizedDecryptor},
-    Result, RsaPrivateKey,
+    traits::{Decryptor, EncryptingKeypair, PrivateKeyOps, RandomizedDecryptor},
+    Result, RsaPrivateKey, RsaPublicKey,
 };
 use alloc::vec::Vec;
 use rand_core::CryptoRngCore;
@@ -12,27 +12,30 @@ use zeroize::ZeroizeOnDrop;
 
 /// Decryption key for PKCS#1 v1.5 decryption as described in [RFC8017 § 7.2].
 ///
+/// The private key operation is performed by `K`, which is an [`RsaPrivateKey`]
+/// by default but can be any [`RsaPrivateOps`] implementation.
+///
 /// [RFC8017 § 7.2]: https://datatracker.ietf.org/doc/html/rfc8017#section-7.2
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct DecryptingKey {
-    inner: RsaPrivateKey,
+pub struct DecryptingKey<K = RsaPrivateKey> {
+    inner: K,
 }
 
-impl DecryptingKey {
+impl<K> DecryptingKey<K> {
     /// Create a new verifying key from an RSA public key.
-    pub fn new(key: RsaPrivateKey) -> Self {
+    pub fn new(key: K) -> Self {
         Self { inner: key }
     }
 }
 
-impl Decryptor for DecryptingKey {
+impl<K: PrivateKeyOps> Decryptor for DecryptingKey<K> {
     fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
         decrypt::<DummyRng>(None, &self.inner, ciphertext)
     }
 }
 
-impl RandomizedDecryptor for DecryptingKey {
+impl<K: PrivateKeyOps> RandomizedDecryptor for DecryptingKey<K> {
     fn decrypt_with_rng<R: CryptoRngCore + ?Sized>(
         &self,
         rng: &mut R,
@@ -42,11 +45,11 @@ impl RandomizedDecryptor for DecryptingKey {
     }
 }
 
-impl EncryptingKeypair for DecryptingKey {
+impl<K: AsRef<RsaPublicKey>> EncryptingKeypair for DecryptingKey<K> {
     type EncryptingKey = EncryptingKey;
     fn encrypting_key(&self) -> EncryptingKey {
         EncryptingKey {
-            inner: self.inner.clone().into(),
+            inner: self.inner.as_ref().clone(),
         }
     }
 }
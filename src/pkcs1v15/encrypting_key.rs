@@ -1,4 +1,6 @@
 use super::encrypt;
+use crate::algorithms::pkcs1v15::pkcs1v15_encrypt_max_message_len;
+use crate::traits::PublicKeyParts;
 use crate::{traits::RandomizedEncryptor, Result, RsaPublicKey};
 use alloc::vec::Vec;
 use rand_core::CryptoRngCore;
@@ -19,6 +21,12 @@ impl EncryptingKey {
     pub fn new(key: RsaPublicKey) -> Self {
         Self { inner: key }
     }
+
+    /// Returns the maximum length of the messages which can be encrypted
+    /// with this key.
+    pub fn max_message_len(&self) -> usize {
+        pkcs1v15_encrypt_max_message_len(self.inner.size())
+    }
 }
 
 impl RandomizedEncryptor for EncryptingKey {
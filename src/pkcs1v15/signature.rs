@@ -11,6 +11,11 @@ use spki::{
     der::{asn1::BitString, Result as DerResult},
     SignatureBitStringEncoding,
 };
+#[cfg(feature = "hazmat")]
+use {
+    crate::errors::{Error, Result},
+    core::cmp::Ordering,
+};
 
 /// `RSASSA-PKCS1-v1_5` signatures as described in [RFC8017 § 8.2].
 ///
@@ -20,6 +25,45 @@ pub struct Signature {
     pub(super) inner: BoxedUint,
 }
 
+/// ⚠️ Low-level access to the integer representative of the signature,
+/// for blind signature and proof protocols.
+#[cfg(feature = "hazmat")]
+impl Signature {
+    /// Create a signature from its integer representative `s`, encoded on
+    /// `bits_precision` bits.
+    ///
+    /// To be verified, the precision must be the one of the modulus, as
+    /// returned by [`PublicKeyParts::n_bits_precision`].
+    ///
+    /// [`PublicKeyParts::n_bits_precision`]: crate::traits::PublicKeyParts::n_bits_precision
+    pub fn from_uint(s: BoxedUint, bits_precision: u32) -> Result<Self> {
+        if s.bits() > bits_precision {
+            return Err(Error::InvalidArguments);
+        }
+        let inner = match bits_precision.cmp(&s.bits_precision()) {
+            Ordering::Greater => s.widen(bits_precision),
+            Ordering::Less => s.shorten(bits_precision),
+            Ordering::Equal => s,
+        };
+        Ok(Self { inner })
+    }
+
+    /// Returns the integer representative of the signature.
+    pub fn as_uint(&self) -> &BoxedUint {
+        &self.inner
+    }
+
+    /// Returns the integer representative of the signature.
+    pub fn into_uint(self) -> BoxedUint {
+        self.inner
+    }
+
+    /// Returns the number of bits the signature is encoded on.
+    pub fn bits_precision(&self) -> u32 {
+        self.inner.bits_precision()
+    }
+}
+
 impl SignatureEncoding for Signature {
     type Repr = Box<[u8]>;
 }
@@ -113,4 +157,21 @@ mod tests {
         let tokens = [Token::Str("000000000000002a")];
         assert_tokens(&signature.readable(), &tokens);
     }
+
+    #[test]
+    #[cfg(feature = "hazmat")]
+    fn test_uint() {
+        use super::*;
+        let signature = Signature::from_uint(BoxedUint::from(42u32), 128).unwrap();
+        assert_eq!(signature.bits_precision(), 128);
+        assert_eq!(signature.as_uint(), &BoxedUint::from(42u32).widen(128));
+        assert_eq!(signature.to_bytes().len(), 16);
+        assert_eq!(
+            Signature::try_from(&signature.to_bytes()[..]).unwrap(),
+            signature
+        );
+
+        let s = BoxedUint::from_be_slice(&[0xff; 16], 128).unwrap();
+        assert_eq!(Signature::from_uint(s, 64), Err(Error::InvalidArguments));
+    }
 }
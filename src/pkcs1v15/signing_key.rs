@@ -1,6 +1,9 @@
-use super::{oid, pkcs1v15_generate_prefix, sign, Signature, VerifyingKey};
-use crate::{dummy_rng::DummyRng, Result, RsaPrivateKey};
+use super::{
+    oid, pkcs1v15_generate_prefix, pkcs1v15_generate_prefix_for_oid, sign, Signature, VerifyingKey,
+};
+use crate::{dummy_rng::DummyRng, traits::PrivateKeyOps, Result, RsaPrivateKey, RsaPublicKey};
 use alloc::vec::Vec;
+use const_oid::ObjectIdentifier;
 use core::marker::PhantomData;
 use digest::Digest;
 use pkcs8::{
@@ -21,33 +24,48 @@ use signature::{
     hazmat::PrehashSigner, DigestSigner, Keypair, RandomizedDigestSigner, RandomizedSigner, Signer,
 };
 use zeroize::ZeroizeOnDrop;
+#[cfg(feature = "jwt")]
+use {
+    crate::jwt::{self, JwsSerialization, JwtDigest},
+    alloc::string::String,
+    serde_json::{Map, Value},
+};
 
 /// Signing key for `RSASSA-PKCS1-v1_5` signatures as described in [RFC8017 § 8.2].
 ///
+/// The private key operation is performed by `K`, which is an [`RsaPrivateKey`]
+/// by default but can be any [`RsaPrivateOps`] implementation, e.g. a key
+/// held by a hardware token.
+///
 /// [RFC8017 § 8.2]: https://datatracker.ietf.org/doc/html/rfc8017#section-8.2
 #[derive(Debug, Clone)]
-pub struct SigningKey<D>
+pub struct SigningKey<D, K = RsaPrivateKey>
 where
     D: Digest,
 {
-    inner: RsaPrivateKey,
+    inner: K,
     prefix: Vec<u8>,
     phantom: PhantomData<D>,
 }
 
-impl<D> SigningKey<D>
+impl<D, K> SigningKey<D, K>
 where
     D: Digest + AssociatedOid,
 {
     /// Create a new signing key with a prefix for the digest `D`.
-    pub fn new(key: RsaPrivateKey) -> Self {
+    pub fn new(key: K) -> Self {
         Self {
             inner: key,
             prefix: pkcs1v15_generate_prefix::<D>(),
             phantom: Default::default(),
         }
     }
+}
 
+impl<D> SigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
     /// Generate a new signing key with a prefix for the digest `D`.
     pub fn random<R: CryptoRngCore>(rng: &mut R, bit_size: usize) -> Result<Self> {
         Ok(Self {
@@ -70,7 +88,7 @@ where
     }
 }
 
-impl<D> SigningKey<D>
+impl<D, K> SigningKey<D, K>
 where
     D: Digest,
 {
@@ -79,14 +97,34 @@ where
     /// ## Note: unprefixed signatures are uncommon
     ///
     /// In most cases you'll want to use [`SigningKey::new`].
-    pub fn new_unprefixed(key: RsaPrivateKey) -> Self {
+    pub fn new_unprefixed(key: K) -> Self {
         Self {
             inner: key,
             prefix: Vec::new(),
             phantom: Default::default(),
         }
     }
+}
+
+impl<D, K> SigningKey<D, K>
+where
+    D: Digest,
+{
+    /// Create a new signing key with a prefix for the digest `D` identified
+    /// by `oid`, for digests without an [`AssociatedOid`].
+    pub fn new_with_oid(key: K, oid: ObjectIdentifier) -> Result<Self> {
+        Ok(Self {
+            inner: key,
+            prefix: pkcs1v15_generate_prefix_for_oid(&oid, <D as Digest>::output_size())?,
+            phantom: Default::default(),
+        })
+    }
+}
 
+impl<D> SigningKey<D>
+where
+    D: Digest,
+{
     /// Generate a new signing key with an empty prefix.
     pub fn random_unprefixed<R: CryptoRngCore>(rng: &mut R, bit_size: usize) -> Result<Self> {
         Ok(Self {
@@ -101,9 +139,10 @@ where
 // `*Signer` trait impls
 //
 
-impl<D> DigestSigner<D, Signature> for SigningKey<D>
+impl<D, K> DigestSigner<D, Signature> for SigningKey<D, K>
 where
     D: Digest,
+    K: PrivateKeyOps,
 {
     fn try_sign_digest(&self, digest: D) -> signature::Result<Signature> {
         sign::<DummyRng>(None, &self.inner, &self.prefix, &digest.finalize())?
@@ -112,9 +151,10 @@ where
     }
 }
 
-impl<D> PrehashSigner<Signature> for SigningKey<D>
+impl<D, K> PrehashSigner<Signature> for SigningKey<D, K>
 where
     D: Digest,
+    K: PrivateKeyOps,
 {
     fn sign_prehash(&self, prehash: &[u8]) -> signature::Result<Signature> {
         sign::<DummyRng>(None, &self.inner, &self.prefix, prehash)?
@@ -123,9 +163,10 @@ where
     }
 }
 
-impl<D> RandomizedDigestSigner<D, Signature> for SigningKey<D>
+impl<D, K> RandomizedDigestSigner<D, Signature> for SigningKey<D, K>
 where
     D: Digest,
+    K: PrivateKeyOps,
 {
     fn try_sign_digest_with_rng(
         &self,
@@ -138,9 +179,10 @@ where
     }
 }
 
-impl<D> RandomizedSigner<Signature> for SigningKey<D>
+impl<D, K> RandomizedSigner<Signature> for SigningKey<D, K>
 where
     D: Digest,
+    K: PrivateKeyOps,
 {
     fn try_sign_with_rng(
         &self,
@@ -153,9 +195,10 @@ where
     }
 }
 
-impl<D> Signer<Signature> for SigningKey<D>
+impl<D, K> Signer<Signature> for SigningKey<D, K>
 where
     D: Digest,
+    K: PrivateKeyOps,
 {
     fn try_sign(&self, msg: &[u8]) -> signature::Result<Signature> {
         sign::<DummyRng>(None, &self.inner, &self.prefix, &D::digest(msg))?
@@ -164,6 +207,94 @@ where
     }
 }
 
+//
+// Multipart signing
+//
+
+impl<D, K> SigningKey<D, K>
+where
+    D: Digest,
+    K: PrivateKeyOps,
+{
+    /// Sign the concatenation of `msg`, hashing its parts in order instead
+    /// of copying them into one buffer.
+    ///
+    /// Mirrors `signature::MultipartSigner`, which is not provided by the
+    /// version of the `signature` crate in use.
+    pub fn try_multipart_sign(&self, msg: &[&[u8]]) -> signature::Result<Signature> {
+        self.try_sign_digest(msg.iter().fold(D::new(), |d, part| d.chain_update(part)))
+    }
+
+    /// Sign the concatenation of `msg` like [`SigningKey::try_multipart_sign`],
+    /// using `rng` for blinding.
+    pub fn try_multipart_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[&[u8]],
+    ) -> signature::Result<Signature> {
+        self.try_sign_digest_with_rng(
+            rng,
+            msg.iter().fold(D::new(), |d, part| d.chain_update(part)),
+        )
+    }
+}
+
+//
+// JSON Web Signatures and Tokens
+//
+
+#[cfg(feature = "jwt")]
+impl<D, K> SigningKey<D, K>
+where
+    D: JwtDigest,
+    K: PrivateKeyOps,
+{
+    /// Sign `payload` as a JSON Web Signature with the `RS*` algorithm of
+    /// `D`.
+    ///
+    /// The header is `{"alg":"RS256"}` for SHA-256, with the parameters of
+    /// `header_overrides` added or replaced, e.g. `kid` or `typ`. Fails with
+    /// [`Error::InvalidArguments`] if `header_overrides` sets `alg`.
+    ///
+    /// [`Error::InvalidArguments`]: crate::Error::InvalidArguments
+    pub fn sign_jws(
+        &self,
+        payload: &[u8],
+        header_overrides: &Map<String, Value>,
+        serialization: JwsSerialization,
+    ) -> Result<String> {
+        let input = jwt::signing_input(D::PKCS1V15_ALG, None, header_overrides, payload)?;
+        self.sign_jose(input, serialization)
+    }
+
+    /// Sign `claims_json`, which must be a JSON object, as a JSON Web Token
+    /// with the `RS*` algorithm of `D`.
+    ///
+    /// The header is `{"alg":"RS256","typ":"JWT"}` for SHA-256, with the
+    /// parameters of `header_overrides` added or replaced, e.g. `kid`. Fails
+    /// with [`Error::InvalidArguments`] if `header_overrides` sets `alg`.
+    ///
+    /// [`Error::InvalidArguments`]: crate::Error::InvalidArguments
+    pub fn sign_jwt(
+        &self,
+        claims_json: &str,
+        header_overrides: &Map<String, Value>,
+    ) -> Result<String> {
+        let input = jwt::jwt_signing_input(D::PKCS1V15_ALG, claims_json, header_overrides)?;
+        self.sign_jose(input, JwsSerialization::Compact)
+    }
+
+    fn sign_jose(&self, input: String, serialization: JwsSerialization) -> Result<String> {
+        let signature = sign::<DummyRng>(
+            None,
+            &self.inner,
+            &self.prefix,
+            &D::digest(input.as_bytes()),
+        )?;
+        Ok(jwt::encode(input, &signature, serialization))
+    }
+}
+
 //
 // Other trait impls
 //
@@ -177,7 +308,7 @@ where
     }
 }
 
-impl<D> AssociatedAlgorithmIdentifier for SigningKey<D>
+impl<D, K> AssociatedAlgorithmIdentifier for SigningKey<D, K>
 where
     D: Digest,
 {
@@ -213,22 +344,24 @@ where
     }
 }
 
-impl<D> Keypair for SigningKey<D>
+impl<D, K> Keypair for SigningKey<D, K>
 where
     D: Digest,
+    K: AsRef<RsaPublicKey>,
 {
     type VerifyingKey = VerifyingKey<D>;
 
     fn verifying_key(&self) -> Self::VerifyingKey {
         VerifyingKey {
-            inner: self.inner.to_public_key(),
+            inner: self.inner.as_ref().clone(),
             prefix: self.prefix.clone(),
+            prefix_without_params: None,
             phantom: Default::default(),
         }
     }
 }
 
-impl<D> SignatureAlgorithmIdentifier for SigningKey<D>
+impl<D, K> SignatureAlgorithmIdentifier for SigningKey<D, K>
 where
     D: Digest + oid::RsaSignatureAssociatedOid,
 {
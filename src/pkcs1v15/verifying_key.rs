@@ -1,7 +1,12 @@
-use super::{oid, pkcs1v15_generate_prefix, verify, Signature};
-use crate::RsaPublicKey;
+use super::{
+    oid, pkcs1v15_generate_prefix, pkcs1v15_generate_prefix_for_oid,
+    pkcs1v15_prefix_without_params, verify, Signature,
+};
+use crate::{Result, RsaPublicKey};
 use alloc::vec::Vec;
+use const_oid::ObjectIdentifier;
 use core::marker::PhantomData;
+use crypto_bigint::BoxedUint;
 use digest::Digest;
 use pkcs8::{
     spki::{
@@ -19,6 +24,12 @@ use {
 
 use signature::{hazmat::PrehashVerifier, DigestVerifier, Verifier};
 use spki::{Document, EncodePublicKey};
+#[cfg(feature = "jwt")]
+use {
+    crate::jwt::{JwtDigest, Token},
+    crate::traits::PublicKeyParts,
+    serde_json::{Map, Value},
+};
 
 /// Verifying key for `RSASSA-PKCS1-v1_5` signatures as described in [RFC8017 § 8.2].
 ///
@@ -30,6 +41,8 @@ where
 {
     pub(super) inner: RsaPublicKey,
     pub(super) prefix: Vec<u8>,
+    /// The prefix without `NULL` digest parameters, if also accepted.
+    pub(super) prefix_without_params: Option<Vec<u8>>,
     pub(super) phantom: PhantomData<D>,
 }
 
@@ -42,6 +55,7 @@ where
         Self {
             inner: key,
             prefix: pkcs1v15_generate_prefix::<D>(),
+            prefix_without_params: None,
             phantom: Default::default(),
         }
     }
@@ -57,6 +71,17 @@ impl<D> VerifyingKey<D>
 where
     D: Digest,
 {
+    /// Create a new verifying key with a prefix for the digest `D` identified
+    /// by `oid`, for digests without an [`AssociatedOid`].
+    pub fn new_with_oid(key: RsaPublicKey, oid: ObjectIdentifier) -> Result<Self> {
+        Ok(Self {
+            inner: key,
+            prefix: pkcs1v15_generate_prefix_for_oid(&oid, <D as Digest>::output_size())?,
+            prefix_without_params: None,
+            phantom: Default::default(),
+        })
+    }
+
     /// Create a new verifying key from an RSA public key with an empty prefix.
     ///
     /// ## Note: unprefixed signatures are uncommon
@@ -66,9 +91,31 @@ where
         Self {
             inner: key,
             prefix: Vec::new(),
+            prefix_without_params: None,
             phantom: Default::default(),
         }
     }
+
+    /// Also accept signatures whose `DigestInfo` omits the `NULL` parameters
+    /// of the digest algorithm, as some older signers produce.
+    ///
+    /// Signatures with the parameters are still accepted, and signing keys
+    /// always include them.
+    pub fn accept_absent_params(mut self) -> Self {
+        self.prefix_without_params = pkcs1v15_prefix_without_params(&self.prefix);
+        self
+    }
+
+    /// Verify `signature` over the hash `hashed` with the prefix, or without
+    /// the `NULL` digest parameters if accepted.
+    fn verify_hashed(&self, hashed: &[u8], signature: &BoxedUint) -> Result<()> {
+        verify(&self.inner, &self.prefix, hashed, signature).or_else(|err| {
+            match &self.prefix_without_params {
+                Some(prefix) => verify(&self.inner, prefix, hashed, signature),
+                None => Err(err),
+            }
+        })
+    }
 }
 
 //
@@ -80,13 +127,8 @@ where
     D: Digest,
 {
     fn verify_digest(&self, digest: D, signature: &Signature) -> signature::Result<()> {
-        verify(
-            &self.inner,
-            &self.prefix,
-            &digest.finalize(),
-            &signature.inner,
-        )
-        .map_err(|e| e.into())
+        self.verify_hashed(&digest.finalize(), &signature.inner)
+            .map_err(|e| e.into())
     }
 }
 
@@ -95,7 +137,8 @@ where
     D: Digest,
 {
     fn verify_prehash(&self, prehash: &[u8], signature: &Signature) -> signature::Result<()> {
-        verify(&self.inner, &self.prefix, prehash, &signature.inner).map_err(|e| e.into())
+        self.verify_hashed(prehash, &signature.inner)
+            .map_err(|e| e.into())
     }
 }
 
@@ -104,13 +147,84 @@ where
     D: Digest,
 {
     fn verify(&self, msg: &[u8], signature: &Signature) -> signature::Result<()> {
-        verify(
-            &self.inner,
-            &self.prefix.clone(),
-            &D::digest(msg),
-            &signature.inner,
+        self.verify_hashed(&D::digest(msg), &signature.inner)
+            .map_err(|e| e.into())
+    }
+}
+
+//
+// Multipart verification
+//
+
+impl<D> VerifyingKey<D>
+where
+    D: Digest,
+{
+    /// Verify the signature of the concatenation of `msg`, hashing its parts
+    /// in order instead of copying them into one buffer.
+    ///
+    /// Mirrors `signature::MultipartVerifier`, which is not provided by the
+    /// version of the `signature` crate in use.
+    pub fn multipart_verify(&self, msg: &[&[u8]], signature: &Signature) -> signature::Result<()> {
+        self.verify_digest(
+            msg.iter().fold(D::new(), |d, part| d.chain_update(part)),
+            signature,
+        )
+    }
+}
+
+//
+// JSON Web Signatures and Tokens
+//
+
+#[cfg(feature = "jwt")]
+impl<D> VerifyingKey<D>
+where
+    D: JwtDigest,
+{
+    /// Verify a JSON Web Signature in compact serialization, signed with the
+    /// `RS*` algorithm of `D`, and return its payload.
+    ///
+    /// Fails with [`Error::Verification`] if the JWS is malformed, has
+    /// another `alg` or critical header parameters, or if its signature is
+    /// invalid.
+    ///
+    /// [`Error::Verification`]: crate::Error::Verification
+    pub fn verify_jws(&self, jws: &str) -> Result<Vec<u8>> {
+        let token = Token::parse(D::PKCS1V15_ALG, jws, None)?;
+        self.verify_jose(&token)?;
+        token.payload()
+    }
+
+    /// Verify a JSON Web Signature in detached serialization, i.e. with an
+    /// empty payload, signed over `payload` with the `RS*` algorithm of
+    /// `D`.
+    ///
+    /// Fails like [`VerifyingKey::verify_jws`], and if the payload of `jws`
+    /// is not empty.
+    pub fn verify_jws_detached(&self, jws: &str, payload: &[u8]) -> Result<()> {
+        self.verify_jose(&Token::parse(D::PKCS1V15_ALG, jws, Some(payload))?)
+    }
+
+    /// Verify a JSON Web Token signed with the `RS*` algorithm of `D`, and
+    /// return its claims.
+    ///
+    /// Fails with [`Error::Verification`] if the token is malformed, has
+    /// another `alg` or critical header parameters, or if its signature is
+    /// invalid. The claims themselves, e.g. `exp`, are not checked.
+    ///
+    /// [`Error::Verification`]: crate::Error::Verification
+    pub fn verify_jwt(&self, token: &str) -> Result<Map<String, Value>> {
+        let token = Token::parse(D::PKCS1V15_ALG, token, None)?;
+        self.verify_jose(&token)?;
+        token.claims()
+    }
+
+    fn verify_jose(&self, token: &Token<'_>) -> Result<()> {
+        self.verify_hashed(
+            &D::digest(token.signing_input.as_bytes()),
+            &token.signature(self.inner.n_bits_precision())?,
         )
-        .map_err(|e| e.into())
     }
 }
 
@@ -145,6 +259,7 @@ where
         Self {
             inner: self.inner.clone(),
             prefix: self.prefix.clone(),
+            prefix_without_params: self.prefix_without_params.clone(),
             phantom: Default::default(),
         }
     }
@@ -208,7 +323,9 @@ where
     D: Digest,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.inner == other.inner && self.prefix == other.prefix
+        self.inner == other.inner
+            && self.prefix == other.prefix
+            && self.prefix_without_params == other.prefix_without_params
     }
 }
 
@@ -217,7 +334,7 @@ impl<D> Serialize for VerifyingKey<D>
 where
     D: Digest,
 {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
@@ -231,7 +348,7 @@ impl<'de, D> Deserialize<'de> for VerifyingKey<D>
 where
     D: Digest + AssociatedOid,
 {
-    fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
+    fn deserialize<De>(deserializer: De) -> core::result::Result<Self, De::Error>
     where
         De: serde::Deserializer<'de>,
     {
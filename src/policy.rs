@@ -0,0 +1,110 @@
+//! Minimum strength policies for RSA keys.
+//!
+//! Nothing in this crate prevents generating or using keys which are too
+//! weak for production use, as small keys are useful for tests and legacy
+//! interoperability. A [`KeyPolicy`] is an opt-in check rejecting keys with a
+//! modulus or public exponent below configurable minimums.
+//!
+//! Enforce it on key generation with [`RsaKeyGenerator::policy`], and on
+//! imported keys with [`KeyPolicy::check`]:
+//!
+//! ```
+//! use rsa::{Error, KeyPolicy, RsaKeyGenerator, RsaPrivateKey};
+//!
+//! let mut rng = rand::thread_rng();
+//! let policy = KeyPolicy::new();
+//!
+//! let generator = RsaKeyGenerator::new().bits(1024).policy(policy);
+//! assert_eq!(generator.build(&mut rng), Err(Error::ModulusTooSmall));
+//!
+//! # let imported = RsaPrivateKey::new(&mut rng, 1024).expect("failed to generate a key");
+//! assert_eq!(policy.check(&imported), Err(Error::ModulusTooSmall));
+//! ```
+//!
+//! [`RsaKeyGenerator::policy`]: crate::RsaKeyGenerator::policy
+
+use crypto_bigint::BoxedUint;
+
+use crate::errors::{Error, Result};
+use crate::traits::PublicKeyParts;
+
+/// Minimum modulus size and public exponent accepted for RSA keys.
+///
+/// Defaults to 2048-bit moduli and public exponents of at least 65537.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyPolicy {
+    min_bits: usize,
+    min_exponent: u64,
+}
+
+impl Default for KeyPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyPolicy {
+    /// Create the default policy.
+    pub const fn new() -> Self {
+        Self {
+            min_bits: 2048,
+            min_exponent: 65537,
+        }
+    }
+
+    /// Set the minimum size of the modulus in bits.
+    pub const fn min_bits(mut self, min_bits: usize) -> Self {
+        self.min_bits = min_bits;
+        self
+    }
+
+    /// Set the minimum public exponent.
+    pub const fn min_exponent(mut self, min_exponent: u64) -> Self {
+        self.min_exponent = min_exponent;
+        self
+    }
+
+    /// Check that `key` complies with this policy.
+    pub fn check(&self, key: &impl PublicKeyParts) -> Result<()> {
+        self.check_parameters(key.n().bits() as usize, key.e())
+    }
+
+    /// Check that a key with a modulus of `bits` bits and the public exponent
+    /// `exponent` would comply with this policy.
+    pub fn check_parameters(&self, bits: usize, exponent: &BoxedUint) -> Result<()> {
+        if bits < self.min_bits {
+            return Err(Error::ModulusTooSmall);
+        }
+        if exponent < &BoxedUint::from(self.min_exponent) {
+            return Err(Error::WeakPublicExponent);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RsaPrivateKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_check() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+
+        assert_eq!(KeyPolicy::new().check(&key), Err(Error::ModulusTooSmall));
+        KeyPolicy::new().min_bits(1024).check(&key).unwrap();
+        assert_eq!(
+            KeyPolicy::new()
+                .min_bits(1024)
+                .min_exponent(65539)
+                .check(&key.to_public_key()),
+            Err(Error::WeakPublicExponent)
+        );
+        assert_eq!(
+            KeyPolicy::new().check_parameters(4096, &BoxedUint::from(3u64)),
+            Err(Error::WeakPublicExponent)
+        );
+    }
+}
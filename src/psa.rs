@@ -0,0 +1,382 @@
+//! Private keys held by PSA Crypto implementations, e.g. Mbed TLS or the
+//! secure elements of embedded targets.
+//!
+//! The PSA Crypto API has no raw RSA operation, so [`PsaPrivateKey`] cannot
+//! implement [`RsaPrivateOps`](crate::traits::RsaPrivateOps). Instead, the
+//! keys of this module delegate whole schemes to PSA, and return the
+//! signatures and public keys of this crate:
+//!
+//! - [`SigningKey`]: RSASSA-PKCS1-v1_5 signatures, with the `DigestInfo`
+//!   prefix computed by this crate.
+//! - [`PssSigningKey`]: RSASSA-PSS signatures, with a salt as long as the
+//!   digest.
+//! - [`DecryptingKey`]: RSAES-PKCS1-v1_5 decryption.
+//! - [`OaepDecryptingKey`]: RSAES-OAEP decryption, with the digest also used
+//!   for MGF1.
+//!
+//! The digests supported by the PSA keys are SHA-1 and SHA-2, and the PSA
+//! Crypto library must be initialized with [`psa_crypto::init`] first.
+//!
+//! ```no_run
+//! use psa_crypto::types::key::Id;
+//! use rsa::psa::{PsaPrivateKey, SigningKey};
+//! use rsa::signature::{Keypair, Signer, Verifier};
+//! use sha2::Sha256;
+//!
+//! psa_crypto::init()?;
+//! let key = PsaPrivateKey::new(Id::from_persistent_key_id(1)?)?;
+//! let signing_key = SigningKey::<Sha256>::new(key);
+//! let signature = signing_key.sign(b"hello world");
+//! signing_key.verifying_key().verify(b"hello world", &signature)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use const_oid::{AssociatedOid, ObjectIdentifier};
+use digest::Digest;
+use pkcs1::DecodeRsaPublicKey;
+use psa_crypto::operations::{asym_encryption, asym_signature, key_management};
+use psa_crypto::types::algorithm::{AsymmetricEncryption, AsymmetricSignature, Hash, SignHash};
+use psa_crypto::types::key::{Attributes, Id};
+use signature::{hazmat::PrehashSigner, DigestSigner, Keypair, Signer};
+
+use crate::algorithms::pkcs1v15::pkcs1v15_generate_prefix;
+use crate::errors::{Error, Result};
+use crate::traits::{Decryptor, PublicKeyParts};
+use crate::{pkcs1v15, pss, RsaPublicKey};
+
+/// RSA private key stored by a PSA Crypto implementation.
+#[derive(Debug, Clone)]
+pub struct PsaPrivateKey {
+    id: Id,
+    public_key: RsaPublicKey,
+}
+
+impl PsaPrivateKey {
+    /// Use the key `id`, whose public key is exported from PSA.
+    ///
+    /// The policy of the key must allow the algorithms it is used with.
+    pub fn new(id: Id) -> Result<Self> {
+        let attributes = Attributes::from_key_id(id).map_err(|_| Error::Backend)?;
+        let len = attributes
+            .export_public_key_output_size()
+            .map_err(|_| Error::Backend)?;
+        let mut der = vec![0u8; len];
+        let len = key_management::export_public(id, &mut der).map_err(|_| Error::Backend)?;
+        let public_key = RsaPublicKey::from_pkcs1_der(&der[..len])?;
+        Ok(Self { id, public_key })
+    }
+
+    /// Returns the identifier of the key.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns the public key.
+    pub fn to_public_key(&self) -> RsaPublicKey {
+        self.public_key.clone()
+    }
+
+    /// Signs `input` with `alg`, returning a signature as long as the modulus.
+    fn sign(&self, alg: AsymmetricSignature, input: &[u8]) -> Result<Vec<u8>> {
+        let mut sig = vec![0u8; self.public_key.size()];
+        let len =
+            asym_signature::sign_hash(self.id, alg, input, &mut sig).map_err(|_| Error::Backend)?;
+        if len != sig.len() {
+            return Err(Error::Backend);
+        }
+        Ok(sig)
+    }
+
+    /// Decrypts `ciphertext` with `alg` and the OAEP `label`.
+    fn decrypt(
+        &self,
+        alg: AsymmetricEncryption,
+        ciphertext: &[u8],
+        label: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        let mut plaintext = vec![0u8; self.public_key.size()];
+        let len = asym_encryption::decrypt(self.id, alg, ciphertext, label, &mut plaintext)
+            .map_err(|_| Error::Decryption)?;
+        plaintext.truncate(len);
+        Ok(plaintext)
+    }
+}
+
+impl AsRef<RsaPublicKey> for PsaPrivateKey {
+    fn as_ref(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+}
+
+/// Returns the PSA algorithm of the digest `D`.
+fn psa_hash<D: AssociatedOid>() -> Result<Hash> {
+    const HASHES: [(ObjectIdentifier, Hash); 5] = [
+        (ObjectIdentifier::new_unwrap("1.3.14.3.2.26"), Hash::Sha1),
+        (
+            ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.4"),
+            Hash::Sha224,
+        ),
+        (
+            ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1"),
+            Hash::Sha256,
+        ),
+        (
+            ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.2"),
+            Hash::Sha384,
+        ),
+        (
+            ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.3"),
+            Hash::Sha512,
+        ),
+    ];
+
+    HASHES
+        .iter()
+        .find(|(oid, _)| *oid == D::OID)
+        .map(|(_, hash)| *hash)
+        .ok_or(Error::InvalidArguments)
+}
+
+/// Signing key for `RSASSA-PKCS1-v1_5` signatures computed by PSA.
+#[derive(Debug, Clone)]
+pub struct SigningKey<D>
+where
+    D: Digest,
+{
+    inner: PsaPrivateKey,
+    prefix: Vec<u8>,
+    phantom: PhantomData<D>,
+}
+
+impl<D> SigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    /// Create a new signing key with a prefix for the digest `D`.
+    pub fn new(key: PsaPrivateKey) -> Self {
+        Self {
+            inner: key,
+            prefix: pkcs1v15_generate_prefix::<D>(),
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<D> SigningKey<D>
+where
+    D: Digest,
+{
+    /// Returns the PSA key.
+    pub fn key(&self) -> &PsaPrivateKey {
+        &self.inner
+    }
+}
+
+impl<D> PrehashSigner<pkcs1v15::Signature> for SigningKey<D>
+where
+    D: Digest,
+{
+    fn sign_prehash(&self, prehash: &[u8]) -> signature::Result<pkcs1v15::Signature> {
+        if prehash.len() != <D as Digest>::output_size() {
+            return Err(Error::InputNotHashed.into());
+        }
+        let input = [self.prefix.as_slice(), prehash].concat();
+        let sig = self
+            .inner
+            .sign(AsymmetricSignature::RsaPkcs1v15SignRaw, &input)?;
+        pkcs1v15::Signature::try_from(sig.as_slice())
+    }
+}
+
+impl<D> DigestSigner<D, pkcs1v15::Signature> for SigningKey<D>
+where
+    D: Digest,
+{
+    fn try_sign_digest(&self, digest: D) -> signature::Result<pkcs1v15::Signature> {
+        self.sign_prehash(&digest.finalize())
+    }
+}
+
+impl<D> Signer<pkcs1v15::Signature> for SigningKey<D>
+where
+    D: Digest,
+{
+    fn try_sign(&self, msg: &[u8]) -> signature::Result<pkcs1v15::Signature> {
+        self.sign_prehash(&D::digest(msg))
+    }
+}
+
+impl<D> Keypair for SigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    type VerifyingKey = pkcs1v15::VerifyingKey<D>;
+
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        pkcs1v15::VerifyingKey::new(self.inner.to_public_key())
+    }
+}
+
+/// Signing key for RSASSA-PSS signatures computed by PSA.
+///
+/// The salt is generated by PSA and is as long as the digest.
+#[derive(Debug, Clone)]
+pub struct PssSigningKey<D>
+where
+    D: Digest,
+{
+    inner: PsaPrivateKey,
+    phantom: PhantomData<D>,
+}
+
+impl<D> PssSigningKey<D>
+where
+    D: Digest,
+{
+    /// Create a new signing key.
+    pub fn new(key: PsaPrivateKey) -> Self {
+        Self {
+            inner: key,
+            phantom: Default::default(),
+        }
+    }
+
+    /// Returns the PSA key.
+    pub fn key(&self) -> &PsaPrivateKey {
+        &self.inner
+    }
+}
+
+impl<D> PrehashSigner<pss::Signature> for PssSigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    fn sign_prehash(&self, prehash: &[u8]) -> signature::Result<pss::Signature> {
+        let alg = AsymmetricSignature::RsaPss {
+            hash_alg: SignHash::Specific(psa_hash::<D>()?),
+        };
+        let sig = self.inner.sign(alg, prehash)?;
+        pss::Signature::try_from(sig.as_slice())
+    }
+}
+
+impl<D> DigestSigner<D, pss::Signature> for PssSigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    fn try_sign_digest(&self, digest: D) -> signature::Result<pss::Signature> {
+        self.sign_prehash(&digest.finalize())
+    }
+}
+
+impl<D> Signer<pss::Signature> for PssSigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    fn try_sign(&self, msg: &[u8]) -> signature::Result<pss::Signature> {
+        self.sign_prehash(&D::digest(msg))
+    }
+}
+
+impl<D> Keypair for PssSigningKey<D>
+where
+    D: Digest,
+{
+    type VerifyingKey = pss::VerifyingKey<D>;
+
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        pss::VerifyingKey::new(self.inner.to_public_key())
+    }
+}
+
+/// Decrypting key for RSAES-PKCS1-v1_5 ciphertexts, decrypted by PSA.
+#[derive(Debug, Clone)]
+pub struct DecryptingKey {
+    inner: PsaPrivateKey,
+}
+
+impl DecryptingKey {
+    /// Create a new decrypting key.
+    pub fn new(key: PsaPrivateKey) -> Self {
+        Self { inner: key }
+    }
+
+    /// Returns the PSA key.
+    pub fn key(&self) -> &PsaPrivateKey {
+        &self.inner
+    }
+}
+
+impl Decryptor for DecryptingKey {
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        self.inner
+            .decrypt(AsymmetricEncryption::RsaPkcs1v15Crypt, ciphertext, None)
+    }
+}
+
+/// Decrypting key for RSAES-OAEP ciphertexts, decrypted by PSA.
+#[derive(Debug, Clone)]
+pub struct OaepDecryptingKey<D>
+where
+    D: Digest,
+{
+    inner: PsaPrivateKey,
+    label: Option<Vec<u8>>,
+    phantom: PhantomData<D>,
+}
+
+impl<D> OaepDecryptingKey<D>
+where
+    D: Digest,
+{
+    /// Create a new decrypting key.
+    pub fn new(key: PsaPrivateKey) -> Self {
+        Self {
+            inner: key,
+            label: None,
+            phantom: Default::default(),
+        }
+    }
+
+    /// Create a new decrypting key with the associated `label`.
+    pub fn new_with_label<S: AsRef<[u8]>>(key: PsaPrivateKey, label: S) -> Self {
+        Self {
+            inner: key,
+            label: Some(label.as_ref().to_vec()),
+            phantom: Default::default(),
+        }
+    }
+
+    /// Returns the PSA key.
+    pub fn key(&self) -> &PsaPrivateKey {
+        &self.inner
+    }
+}
+
+impl<D> Decryptor for OaepDecryptingKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let alg = AsymmetricEncryption::RsaOaep {
+            hash_alg: psa_hash::<D>()?,
+        };
+        self.inner.decrypt(alg, ciphertext, self.label.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_psa_hash() {
+        assert_eq!(psa_hash::<sha1::Sha1>().unwrap(), Hash::Sha1);
+        assert_eq!(psa_hash::<sha2::Sha256>().unwrap(), Hash::Sha256);
+        assert_eq!(psa_hash::<sha2::Sha512>().unwrap(), Hash::Sha512);
+        assert!(psa_hash::<sha3::Sha3_256>().is_err());
+    }
+}
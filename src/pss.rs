@@ -10,13 +10,16 @@
 //! [RFC8017 § 8.1]: https://datatracker.ietf.org/doc/html/rfc8017#section-8.1
 
 mod blinded_signing_key;
+mod restricted_signing_key;
+mod restricted_verifying_key;
 mod signature;
 mod signing_key;
 mod verifying_key;
 
 pub use self::{
-    blinded_signing_key::BlindedSigningKey, signature::Signature, signing_key::SigningKey,
-    verifying_key::VerifyingKey,
+    blinded_signing_key::BlindedSigningKey, restricted_signing_key::RestrictedSigningKey,
+    restricted_verifying_key::RestrictedVerifyingKey, signature::Signature,
+    signing_key::SigningKey, verifying_key::VerifyingKey,
 };
 
 use alloc::{boxed::Box, vec::Vec};
@@ -25,18 +28,22 @@ use crypto_bigint::BoxedUint;
 
 use const_oid::AssociatedOid;
 use digest::{Digest, DynDigest, FixedOutputReset};
-use pkcs1::RsaPssParams;
-use pkcs8::spki::{der::Any, AlgorithmIdentifierOwned};
+use pkcs1::{RsaPssParams, TrailerField};
+use pkcs8::der::{Decode, Encode};
+use pkcs8::spki::{
+    der::{Any, AnyRef},
+    AlgorithmIdentifierOwned, AlgorithmIdentifierRef, ObjectIdentifier,
+};
 use rand_core::CryptoRngCore;
 
-use crate::algorithms::pad::{uint_to_be_pad, uint_to_zeroizing_be_pad};
+use crate::algorithms::pad::{uint_to_be_pad, uint_to_be_pad_into};
 use crate::algorithms::pss::*;
-use crate::algorithms::rsa::{rsa_decrypt_and_check, rsa_encrypt};
+#[cfg(feature = "sha2")]
+use crate::encoding::new_dyn_digest;
 use crate::encoding::ID_RSASSA_PSS;
 use crate::errors::{Error, Result};
-use crate::traits::PublicKeyParts;
-use crate::traits::SignatureScheme;
-use crate::{RsaPrivateKey, RsaPublicKey};
+use crate::traits::{PrivateKeyOps, PublicKeyParts, SignatureScheme};
+use crate::RsaPublicKey;
 
 /// Digital signatures using PSS padding.
 pub struct Pss {
@@ -89,7 +96,7 @@ impl SignatureScheme for Pss {
     fn sign<Rng: CryptoRngCore>(
         mut self,
         rng: Option<&mut Rng>,
-        priv_key: &RsaPrivateKey,
+        priv_key: &impl PrivateKeyOps,
         hashed: &[u8],
     ) -> Result<Vec<u8>> {
         sign(
@@ -102,7 +109,28 @@ impl SignatureScheme for Pss {
         )
     }
 
+    fn sign_into<Rng: CryptoRngCore>(
+        mut self,
+        rng: Option<&mut Rng>,
+        priv_key: &impl PrivateKeyOps,
+        hashed: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize> {
+        let sig = sign_uint(
+            rng.ok_or(Error::InvalidPaddingScheme)?,
+            self.blinded,
+            priv_key,
+            hashed,
+            self.salt_len,
+            &mut *self.digest,
+        )?;
+        uint_to_be_pad_into(&sig, priv_key.size(), out)
+    }
+
     fn verify(mut self, pub_key: &RsaPublicKey, hashed: &[u8], sig: &[u8]) -> Result<()> {
+        if sig.len() != pub_key.size() {
+            return Err(Error::Verification);
+        }
         verify(
             pub_key,
             hashed,
@@ -135,8 +163,8 @@ pub(crate) fn verify(
     if sig_len != pub_key.size() {
         return Err(Error::Verification);
     }
-    let raw = rsa_encrypt(pub_key, sig)?;
-    let mut em = uint_to_be_pad(raw, pub_key.size())?;
+    let mut em = vec![0u8; pub_key.size()];
+    recover_em(pub_key, sig, &mut em)?;
 
     emsa_pss_verify(hashed, &mut em, salt_len, digest, pub_key.n().bits() as _)
 }
@@ -155,11 +183,20 @@ where
         return Err(Error::Verification);
     }
 
-    let mut em = uint_to_be_pad(rsa_encrypt(pub_key, sig)?, pub_key.size())?;
+    let mut em = vec![0u8; pub_key.size()];
+    recover_em(pub_key, sig, &mut em)?;
 
     emsa_pss_verify_digest::<D>(hashed, &mut em, salt_len, pub_key.n().bits() as _)
 }
 
+/// Recovers the encoded message of `sig` into `em`, which is then checked in
+/// place by the EMSA-PSS verification.
+#[inline]
+fn recover_em(pub_key: &RsaPublicKey, sig: &BoxedUint, em: &mut [u8]) -> Result<()> {
+    uint_to_be_pad_into(&pub_key.verify_raw(sig)?, em.len(), em)?;
+    Ok(())
+}
+
 /// SignPSS calculates the signature of hashed using RSASSA-PSS.
 ///
 /// Note that hashed must be the result of hashing the input message using the
@@ -168,11 +205,24 @@ where
 pub(crate) fn sign<T: CryptoRngCore>(
     rng: &mut T,
     blind: bool,
-    priv_key: &RsaPrivateKey,
+    priv_key: &impl PrivateKeyOps,
     hashed: &[u8],
     salt_len: usize,
     digest: &mut dyn DynDigest,
 ) -> Result<Vec<u8>> {
+    let sig = sign_uint(rng, blind, priv_key, hashed, salt_len, digest)?;
+    uint_to_be_pad(sig, priv_key.size())
+}
+
+/// Like [`sign`], but returns the signature as an integer.
+fn sign_uint<T: CryptoRngCore>(
+    rng: &mut T,
+    blind: bool,
+    priv_key: &impl PrivateKeyOps,
+    hashed: &[u8],
+    salt_len: usize,
+    digest: &mut dyn DynDigest,
+) -> Result<BoxedUint> {
     let mut salt = vec![0; salt_len];
     rng.fill_bytes(&mut salt[..]);
 
@@ -182,7 +232,7 @@ pub(crate) fn sign<T: CryptoRngCore>(
 pub(crate) fn sign_digest<T: CryptoRngCore + ?Sized, D: Digest + FixedOutputReset>(
     rng: &mut T,
     blind: bool,
-    priv_key: &RsaPrivateKey,
+    priv_key: &impl PrivateKeyOps,
     hashed: &[u8],
     salt_len: usize,
 ) -> Result<Vec<u8>> {
@@ -199,23 +249,22 @@ pub(crate) fn sign_digest<T: CryptoRngCore + ?Sized, D: Digest + FixedOutputRese
 /// later used to verify the signature.
 fn sign_pss_with_salt<T: CryptoRngCore>(
     blind_rng: Option<&mut T>,
-    priv_key: &RsaPrivateKey,
+    priv_key: &impl PrivateKeyOps,
     hashed: &[u8],
     salt: &[u8],
     digest: &mut dyn DynDigest,
-) -> Result<Vec<u8>> {
+) -> Result<BoxedUint> {
     let em_bits = priv_key.n().bits() - 1;
 
     let em = emsa_pss_encode(hashed, em_bits as _, salt, digest)?;
 
     let em = BoxedUint::from_be_slice(&em, priv_key.n_bits_precision())?;
-    let raw = rsa_decrypt_and_check(priv_key, blind_rng, &em)?;
-    uint_to_zeroizing_be_pad(raw, priv_key.size())
+    priv_key.raw_private_op(blind_rng, &em)
 }
 
 fn sign_pss_with_salt_digest<T: CryptoRngCore + ?Sized, D: Digest + FixedOutputReset>(
     blind_rng: Option<&mut T>,
-    priv_key: &RsaPrivateKey,
+    priv_key: &impl PrivateKeyOps,
     hashed: &[u8],
     salt: &[u8],
 ) -> Result<Vec<u8>> {
@@ -223,10 +272,7 @@ fn sign_pss_with_salt_digest<T: CryptoRngCore + ?Sized, D: Digest + FixedOutputR
     let em = emsa_pss_encode_digest::<D>(hashed, em_bits as _, salt)?;
 
     let em = BoxedUint::from_be_slice(&em, priv_key.n_bits_precision())?;
-    uint_to_zeroizing_be_pad(
-        rsa_decrypt_and_check(priv_key, blind_rng, &em)?,
-        priv_key.size(),
-    )
+    uint_to_be_pad(priv_key.raw_private_op(blind_rng, &em)?, priv_key.size())
 }
 
 /// Returns the [`AlgorithmIdentifierOwned`] associated with PSS signature using a given digest.
@@ -238,7 +284,10 @@ where
     get_pss_signature_algo_id::<D>(salt_len)
 }
 
-fn get_pss_signature_algo_id<D>(salt_len: u8) -> pkcs8::spki::Result<AlgorithmIdentifierOwned>
+/// Returns the [`AlgorithmIdentifierOwned`] associated with PSS signature
+/// using a given digest, MGF1 with the same digest and a salt of the given
+/// length.
+pub fn get_pss_signature_algo_id<D>(salt_len: u8) -> pkcs8::spki::Result<AlgorithmIdentifierOwned>
 where
     D: Digest + AssociatedOid,
 {
@@ -250,6 +299,126 @@ where
     })
 }
 
+/// Returns the [`AlgorithmIdentifierOwned`] associated with PSS signature
+/// using digest `D`, MGF1 with digest `MGD` and a salt of the given length,
+/// as described in [RFC4055 § 3.1].
+///
+/// Signatures with different digests can't be created or verified with
+/// [`Pss`], which uses a single digest.
+///
+/// [RFC4055 § 3.1]: https://datatracker.ietf.org/doc/html/rfc4055#section-3.1
+pub fn get_pss_signature_algo_id_with_mgf_hash<D, MGD>(
+    salt_len: u8,
+) -> pkcs8::spki::Result<AlgorithmIdentifierOwned>
+where
+    D: Digest + AssociatedOid,
+    MGD: Digest + AssociatedOid,
+{
+    let mut pss_params = RsaPssParams::new::<D>(salt_len);
+    pss_params.mask_gen.parameters = Some(AlgorithmIdentifierRef {
+        oid: MGD::OID,
+        parameters: pss_params.hash.parameters,
+    });
+
+    Ok(AlgorithmIdentifierOwned {
+        oid: ID_RSASSA_PSS,
+        parameters: Some(Any::encode_from(&pss_params)?),
+    })
+}
+
+/// ObjectID of the MGF1 mask generation function.
+const ID_MGF1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.8");
+
+/// Decoded `RSASSA-PSS-params`.
+struct PssParams {
+    digest: ObjectIdentifier,
+    mgf_digest: ObjectIdentifier,
+    salt_len: usize,
+}
+
+impl PssParams {
+    /// Decodes the parameters of an `id-RSASSA-PSS` algorithm identifier,
+    /// which default to SHA-1, MGF1 with SHA-1 and a salt of 20 bytes when
+    /// absent.
+    fn decode(params: Option<AnyRef<'_>>) -> pkcs8::spki::Result<Self> {
+        let der = match params {
+            Some(params) => params.to_der()?,
+            None => vec![0x30, 0x00],
+        };
+        let params = RsaPssParams::from_der(&der)?;
+        if params.mask_gen.oid != ID_MGF1 || params.trailer_field != TrailerField::BC {
+            return Err(pkcs8::spki::Error::KeyMalformed);
+        }
+        let mgf_digest = params
+            .mask_gen
+            .parameters
+            .ok_or(pkcs8::spki::Error::KeyMalformed)?;
+
+        Ok(Self {
+            digest: params.hash.oid,
+            mgf_digest: mgf_digest.oid,
+            salt_len: params.salt_len.into(),
+        })
+    }
+}
+
+/// Returns the salt length of a key restricted to RSASSA-PSS with digest `D`
+/// by its `id-RSASSA-PSS` algorithm identifier, as described in
+/// [RFC4055 § 3.1].
+///
+/// Keys without parameters may be used with any digest, and get a salt as
+/// long as the digest output. Parameters naming another digest, a mask
+/// generation function other than MGF1 with `D`, or another trailer field
+/// are rejected.
+///
+/// [RFC4055 § 3.1]: https://datatracker.ietf.org/doc/html/rfc4055#section-3.1
+pub(crate) fn get_pss_key_salt_len<D>(
+    algorithm: &AlgorithmIdentifierRef<'_>,
+) -> pkcs8::spki::Result<usize>
+where
+    D: Digest + AssociatedOid,
+{
+    if algorithm.oid != ID_RSASSA_PSS {
+        return Err(pkcs8::spki::Error::OidUnknown { oid: algorithm.oid });
+    }
+    if algorithm.parameters.is_none() {
+        return Ok(<D as Digest>::output_size());
+    }
+
+    let params = PssParams::decode(algorithm.parameters)?;
+    if params.digest != D::OID || params.mgf_digest != D::OID {
+        return Err(pkcs8::spki::Error::KeyMalformed);
+    }
+    Ok(params.salt_len)
+}
+
+/// Parses an `id-RSASSA-PSS` algorithm identifier, e.g. the signature
+/// algorithm of a certificate, into the PSS scheme it describes.
+///
+/// The digest must be SHA-1, with the `sha1` feature enabled, or one of
+/// SHA-2, and the mask generation function must be MGF1 with the same
+/// digest.
+#[cfg(feature = "sha2")]
+impl TryFrom<AlgorithmIdentifierRef<'_>> for Pss {
+    type Error = pkcs8::spki::Error;
+
+    fn try_from(algorithm: AlgorithmIdentifierRef<'_>) -> pkcs8::spki::Result<Self> {
+        if algorithm.oid != ID_RSASSA_PSS {
+            return Err(pkcs8::spki::Error::OidUnknown { oid: algorithm.oid });
+        }
+        let params = PssParams::decode(algorithm.parameters)?;
+        if params.mgf_digest != params.digest {
+            return Err(pkcs8::spki::Error::KeyMalformed);
+        }
+
+        Ok(Self {
+            blinded: false,
+            digest: new_dyn_digest(params.digest)?,
+            salt_len: params.salt_len,
+        })
+    }
+}
+
 #[cfg(all(test, feature = "pem"))]
 mod test {
     use crate::pss::{BlindedSigningKey, Pss, Signature, SigningKey, VerifyingKey};
@@ -460,6 +629,34 @@ tAboUGBxTDq3ZroNism3DaMIbKPyYrAqhKov1h5V
         }
     }
 
+    #[test]
+    fn test_sign_and_verify_roundtrip_multipart() {
+        let priv_key = get_private_key();
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let signing_key = SigningKey::<Sha1>::new(priv_key.clone());
+        let verifying_key = signing_key.verifying_key();
+
+        let sig = signing_key
+            .try_multipart_sign_with_rng(&mut rng, &[b"te", b"", b"st\n"])
+            .expect("failed to sign");
+        verifying_key
+            .verify(b"test\n", &sig)
+            .expect("failed to verify");
+        verifying_key
+            .multipart_verify(&[b"test", b"\n"], &sig)
+            .expect("failed to verify");
+        assert!(verifying_key.multipart_verify(&[b"test"], &sig).is_err());
+
+        let signing_key = BlindedSigningKey::<Sha1>::new(priv_key);
+        let sig = signing_key
+            .try_multipart_sign_with_rng(&mut rng, &[b"te", b"st\n"])
+            .expect("failed to sign");
+        verifying_key
+            .multipart_verify(&[b"test\n"], &sig)
+            .expect("failed to verify");
+    }
+
     #[test]
     fn test_sign_and_verify_roundtrip_blinded_signer() {
         let priv_key = get_private_key();
@@ -616,4 +813,37 @@ tAboUGBxTDq3ZroNism3DaMIbKPyYrAqhKov1h5V
                 .expect("failed to verify");
         }
     }
+
+    #[test]
+    #[cfg(feature = "sha2")]
+    fn test_pss_from_algorithm_identifier() {
+        use super::{get_pss_signature_algo_id, get_pss_signature_algo_id_with_mgf_hash};
+        use pkcs8::der::{Decode, Encode};
+        use pkcs8::spki::AlgorithmIdentifierRef;
+        use sha2::{Sha256, Sha384};
+
+        let priv_key = get_private_key();
+        let pub_key: RsaPublicKey = priv_key.clone().into();
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let digest = Sha256::digest(b"message");
+
+        let algorithm = get_pss_signature_algo_id::<Sha256>(10).unwrap();
+        let pss =
+            Pss::try_from(AlgorithmIdentifierRef::from_der(&algorithm.to_der().unwrap()).unwrap())
+                .unwrap();
+        assert_eq!(pss.salt_len, 10);
+        let sig = priv_key.sign_with_rng(&mut rng, pss, &digest).unwrap();
+        pub_key
+            .verify(Pss::new_with_salt::<Sha256>(10), &digest, &sig)
+            .expect("failed to verify");
+
+        // `Pss` can't use different digests for the hash and MGF1.
+        let algorithm = get_pss_signature_algo_id_with_mgf_hash::<Sha256, Sha384>(32).unwrap();
+        assert!(Pss::try_from(
+            AlgorithmIdentifierRef::from_der(&algorithm.to_der().unwrap()).unwrap()
+        )
+        .is_err());
+        let algorithm = get_pss_signature_algo_id_with_mgf_hash::<Sha256, Sha256>(32).unwrap();
+        assert_eq!(algorithm, get_pss_signature_algo_id::<Sha256>(32).unwrap());
+    }
 }
@@ -129,6 +129,32 @@ where
     }
 }
 
+//
+// Multipart signing
+//
+
+impl<D> BlindedSigningKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    /// Sign the concatenation of `msg`, hashing its parts in order instead
+    /// of copying them into one buffer, and generating the salt and blinding
+    /// with `rng`.
+    ///
+    /// Mirrors `signature::RandomizedMultipartSigner`, which is not provided
+    /// by the version of the `signature` crate in use.
+    pub fn try_multipart_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[&[u8]],
+    ) -> signature::Result<Signature> {
+        self.try_sign_digest_with_rng(
+            rng,
+            msg.iter().fold(D::new(), |d, part| d.chain_update(part)),
+        )
+    }
+}
+
 //
 // Other trait impls
 //
@@ -0,0 +1,274 @@
+use super::{
+    get_pss_key_salt_len, get_pss_signature_algo_id, RestrictedVerifyingKey, Signature, SigningKey,
+};
+use crate::{Result, RsaPrivateKey};
+use const_oid::AssociatedOid;
+use digest::{Digest, FixedOutputReset};
+use pkcs8::{
+    der::AnyRef,
+    spki::{AlgorithmIdentifierOwned, AlgorithmIdentifierRef, DynSignatureAlgorithmIdentifier},
+    EncodePrivateKey, SecretDocument,
+};
+use rand_core::CryptoRngCore;
+use signature::{
+    hazmat::RandomizedPrehashSigner, Keypair, RandomizedDigestSigner, RandomizedSigner,
+};
+use zeroize::ZeroizeOnDrop;
+
+#[cfg(feature = "getrandom")]
+use signature::{hazmat::PrehashSigner, Signer};
+
+/// Signing key restricted to RSASSA-PSS with the digest `D`, as declared by
+/// an `id-RSASSA-PSS` key algorithm identifier ([RFC4055 § 3.1]).
+///
+/// Unlike [`SigningKey`], it does not give access to the [`RsaPrivateKey`],
+/// so it can't be used with PKCS#1 v1.5 or another digest. It is decoded
+/// from and encoded to PKCS#8 with `RSASSA-PSS-params`, as used by the
+/// certificates of the `rsa_pss_pss_*` TLS signature schemes.
+///
+/// [RFC4055 § 3.1]: https://datatracker.ietf.org/doc/html/rfc4055#section-3.1
+#[derive(Debug, Clone)]
+pub struct RestrictedSigningKey<D>
+where
+    D: Digest,
+{
+    inner: SigningKey<D>,
+}
+
+impl<D> RestrictedSigningKey<D>
+where
+    D: Digest,
+{
+    /// Restrict `key` to RSASSA-PSS with the digest `D`.
+    /// Digest output size is used as a salt length.
+    pub fn new(key: RsaPrivateKey) -> Self {
+        Self::new_with_salt_len(key, <D as Digest>::output_size())
+    }
+
+    /// Restrict `key` to RSASSA-PSS with the digest `D` and a salt of the
+    /// given length.
+    pub fn new_with_salt_len(key: RsaPrivateKey, salt_len: usize) -> Self {
+        Self {
+            inner: SigningKey::new_with_salt_len(key, salt_len),
+        }
+    }
+
+    /// Generate a new random RSASSA-PSS restricted signing key.
+    /// Digest output size is used as a salt length.
+    pub fn random<R: CryptoRngCore>(rng: &mut R, bit_size: usize) -> Result<Self> {
+        Ok(Self::new(RsaPrivateKey::new(rng, bit_size)?))
+    }
+
+    /// Return specified salt length for this key
+    pub fn salt_len(&self) -> usize {
+        self.inner.salt_len()
+    }
+}
+
+//
+// `*Signer` trait impls
+//
+
+impl<D> RandomizedDigestSigner<D, Signature> for RestrictedSigningKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    fn try_sign_digest_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        digest: D,
+    ) -> signature::Result<Signature> {
+        self.inner.try_sign_digest_with_rng(rng, digest)
+    }
+}
+
+impl<D> RandomizedSigner<Signature> for RestrictedSigningKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    fn try_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> signature::Result<Signature> {
+        self.inner.try_sign_with_rng(rng, msg)
+    }
+}
+
+impl<D> RandomizedPrehashSigner<Signature> for RestrictedSigningKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    fn sign_prehash_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        prehash: &[u8],
+    ) -> signature::Result<Signature> {
+        self.inner.sign_prehash_with_rng(rng, prehash)
+    }
+}
+
+#[cfg(feature = "getrandom")]
+impl<D> PrehashSigner<Signature> for RestrictedSigningKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    fn sign_prehash(&self, prehash: &[u8]) -> signature::Result<Signature> {
+        self.inner.sign_prehash(prehash)
+    }
+}
+
+#[cfg(feature = "getrandom")]
+impl<D> Signer<Signature> for RestrictedSigningKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    fn try_sign(&self, msg: &[u8]) -> signature::Result<Signature> {
+        self.inner.try_sign(msg)
+    }
+}
+
+//
+// Other trait impls
+//
+
+impl<D> DynSignatureAlgorithmIdentifier for RestrictedSigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    fn signature_algorithm_identifier(&self) -> pkcs8::spki::Result<AlgorithmIdentifierOwned> {
+        self.inner.signature_algorithm_identifier()
+    }
+}
+
+impl<D> EncodePrivateKey for RestrictedSigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    fn to_pkcs8_der(&self) -> pkcs8::Result<SecretDocument> {
+        let salt_len = u8::try_from(self.salt_len()).map_err(|_| pkcs8::Error::KeyMalformed)?;
+        let algorithm = get_pss_signature_algo_id::<D>(salt_len)?;
+        let der = self.inner.to_pkcs8_der()?;
+        let private_key_info = pkcs8::PrivateKeyInfoRef::try_from(der.as_bytes())?;
+
+        pkcs8::PrivateKeyInfoRef {
+            algorithm: AlgorithmIdentifierRef {
+                oid: algorithm.oid,
+                parameters: algorithm.parameters.as_ref().map(AnyRef::from),
+            },
+            ..private_key_info
+        }
+        .try_into()
+    }
+}
+
+impl<D> Keypair for RestrictedSigningKey<D>
+where
+    D: Digest,
+{
+    type VerifyingKey = RestrictedVerifyingKey<D>;
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        RestrictedVerifyingKey {
+            inner: self.inner.verifying_key(),
+        }
+    }
+}
+
+impl<D> TryFrom<pkcs8::PrivateKeyInfoRef<'_>> for RestrictedSigningKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    type Error = pkcs8::Error;
+
+    fn try_from(private_key_info: pkcs8::PrivateKeyInfoRef<'_>) -> pkcs8::Result<Self> {
+        let salt_len = get_pss_key_salt_len::<D>(&private_key_info.algorithm)?;
+        let private_key_info = pkcs8::PrivateKeyInfoRef {
+            algorithm: AlgorithmIdentifierRef {
+                oid: private_key_info.algorithm.oid,
+                parameters: None,
+            },
+            ..private_key_info
+        };
+
+        RsaPrivateKey::try_from(private_key_info).map(|key| Self::new_with_salt_len(key, salt_len))
+    }
+}
+
+impl<D> ZeroizeOnDrop for RestrictedSigningKey<D> where D: Digest {}
+
+impl<D> PartialEq for RestrictedSigningKey<D>
+where
+    D: Digest,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RsaPublicKey;
+    use pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePublicKey};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::{Sha256, Sha384};
+    use signature::Verifier;
+
+    #[test]
+    fn test_encoding() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let signing_key = RestrictedSigningKey::<Sha256>::random(&mut rng, 1024).unwrap();
+        let verifying_key = signing_key.verifying_key();
+
+        let der = signing_key.to_pkcs8_der().unwrap();
+        assert_eq!(
+            RestrictedSigningKey::<Sha256>::from_pkcs8_der(der.as_bytes()).unwrap(),
+            signing_key
+        );
+        assert!(RestrictedSigningKey::<Sha384>::from_pkcs8_der(der.as_bytes()).is_err());
+        assert!(RsaPrivateKey::from_pkcs8_der(der.as_bytes()).is_err());
+
+        let der = verifying_key.to_public_key_der().unwrap();
+        // The `AlgorithmIdentifier` holds `RSASSA-PSS-params` with SHA-256,
+        // MGF1 with SHA-256 and a salt of 32 bytes.
+        assert_eq!(
+            &der.as_bytes()[3..70],
+            hex_literal::hex!(
+                "304106092a864886f70d01010a3034a00f300d06096086480165030402010500"
+                "a11c301a06092a864886f70d010108300d06096086480165030402010500a203"
+                "020120"
+            )
+        );
+        assert_eq!(
+            RestrictedVerifyingKey::<Sha256>::from_public_key_der(der.as_bytes()).unwrap(),
+            verifying_key
+        );
+        assert!(RestrictedVerifyingKey::<Sha384>::from_public_key_der(der.as_bytes()).is_err());
+        assert!(RsaPublicKey::from_public_key_der(der.as_bytes()).is_err());
+
+        // Keys for any scheme are not implicitly restricted.
+        let key: &RsaPrivateKey = signing_key.inner.as_ref();
+        let der = key.to_public_key().to_public_key_der().unwrap();
+        assert!(RestrictedVerifyingKey::<Sha256>::from_public_key_der(der.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_salt_len() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let signing_key = RestrictedSigningKey::<Sha256>::new_with_salt_len(key.clone(), 20);
+        let der = signing_key.verifying_key().to_public_key_der().unwrap();
+        let verifying_key =
+            RestrictedVerifyingKey::<Sha256>::from_public_key_der(der.as_bytes()).unwrap();
+        assert_eq!(verifying_key.salt_len(), 20);
+
+        let signature = signing_key.sign_with_rng(&mut rng, b"message");
+        assert!(verifying_key.verify(b"message", &signature).is_ok());
+        assert!(verifying_key.verify(b"other", &signature).is_err());
+
+        // Signatures with another salt length than declared are rejected.
+        let signature =
+            RestrictedSigningKey::<Sha256>::new(key).sign_with_rng(&mut rng, b"message");
+        assert!(verifying_key.verify(b"message", &signature).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use super::{get_pss_key_salt_len, get_pss_signature_algo_id, Signature, VerifyingKey};
+use crate::RsaPublicKey;
+use const_oid::AssociatedOid;
+use digest::{Digest, FixedOutputReset};
+use pkcs8::{
+    der::AnyRef,
+    spki::{AlgorithmIdentifierRef, Error::KeyMalformed},
+    Document, EncodePublicKey,
+};
+use signature::{hazmat::PrehashVerifier, DigestVerifier, Verifier};
+
+/// Verifying key restricted to RSASSA-PSS with the digest `D`, as declared
+/// by an `id-RSASSA-PSS` key algorithm identifier ([RFC4055 § 3.1]).
+///
+/// Unlike [`VerifyingKey`], it does not give access to the [`RsaPublicKey`],
+/// so it can't be used with PKCS#1 v1.5 or another digest. It is decoded
+/// from and encoded to a `SubjectPublicKeyInfo` with `RSASSA-PSS-params`,
+/// and only accepts signatures with the declared salt length.
+///
+/// [RFC4055 § 3.1]: https://datatracker.ietf.org/doc/html/rfc4055#section-3.1
+#[derive(Debug)]
+pub struct RestrictedVerifyingKey<D>
+where
+    D: Digest,
+{
+    pub(super) inner: VerifyingKey<D>,
+}
+
+impl<D> RestrictedVerifyingKey<D>
+where
+    D: Digest,
+{
+    /// Restrict `key` to RSASSA-PSS with the digest `D`.
+    /// Digest output size is used as a salt length.
+    pub fn new(key: RsaPublicKey) -> Self {
+        Self::new_with_salt_len(key, <D as Digest>::output_size())
+    }
+
+    /// Restrict `key` to RSASSA-PSS with the digest `D` and a salt of the
+    /// given length.
+    pub fn new_with_salt_len(key: RsaPublicKey, salt_len: usize) -> Self {
+        Self {
+            inner: VerifyingKey::new_with_salt_len(key, salt_len),
+        }
+    }
+
+    /// Return specified salt length for this key
+    pub fn salt_len(&self) -> usize {
+        self.inner.salt_len
+    }
+}
+
+//
+// `*Verifier` trait impls
+//
+
+impl<D> DigestVerifier<D, Signature> for RestrictedVerifyingKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    fn verify_digest(&self, digest: D, signature: &Signature) -> signature::Result<()> {
+        self.inner.verify_digest(digest, signature)
+    }
+}
+
+impl<D> PrehashVerifier<Signature> for RestrictedVerifyingKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    fn verify_prehash(&self, prehash: &[u8], signature: &Signature) -> signature::Result<()> {
+        self.inner.verify_prehash(prehash, signature)
+    }
+}
+
+impl<D> Verifier<Signature> for RestrictedVerifyingKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    fn verify(&self, msg: &[u8], signature: &Signature) -> signature::Result<()> {
+        self.inner.verify(msg, signature)
+    }
+}
+
+//
+// Other trait impls
+//
+
+// Implemented manually so we don't have to bind D with Clone
+impl<D> Clone for RestrictedVerifyingKey<D>
+where
+    D: Digest,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<D> EncodePublicKey for RestrictedVerifyingKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    fn to_public_key_der(&self) -> pkcs8::spki::Result<Document> {
+        let salt_len = u8::try_from(self.salt_len()).map_err(|_| KeyMalformed)?;
+        let algorithm = get_pss_signature_algo_id::<D>(salt_len)?;
+        let der = self.inner.inner.to_public_key_der()?;
+        let spki = pkcs8::SubjectPublicKeyInfoRef::try_from(der.as_bytes())?;
+
+        pkcs8::SubjectPublicKeyInfoRef {
+            algorithm: AlgorithmIdentifierRef {
+                oid: algorithm.oid,
+                parameters: algorithm.parameters.as_ref().map(AnyRef::from),
+            },
+            subject_public_key: spki.subject_public_key,
+        }
+        .try_into()
+    }
+}
+
+impl<D> TryFrom<pkcs8::SubjectPublicKeyInfoRef<'_>> for RestrictedVerifyingKey<D>
+where
+    D: Digest + AssociatedOid,
+{
+    type Error = pkcs8::spki::Error;
+
+    fn try_from(spki: pkcs8::SubjectPublicKeyInfoRef<'_>) -> pkcs8::spki::Result<Self> {
+        let salt_len = get_pss_key_salt_len::<D>(&spki.algorithm)?;
+        let spki = pkcs8::SubjectPublicKeyInfoRef {
+            algorithm: AlgorithmIdentifierRef {
+                oid: spki.algorithm.oid,
+                parameters: None,
+            },
+            subject_public_key: spki.subject_public_key,
+        };
+
+        RsaPublicKey::try_from(spki).map(|key| Self::new_with_salt_len(key, salt_len))
+    }
+}
+
+impl<D> PartialEq for RestrictedVerifyingKey<D>
+where
+    D: Digest,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
@@ -1,6 +1,6 @@
 use super::{get_pss_signature_algo_id, sign_digest, Signature, VerifyingKey};
 use crate::encoding::verify_algorithm_id;
-use crate::{Result, RsaPrivateKey};
+use crate::{traits::PrivateKeyOps, Result, RsaPrivateKey, RsaPublicKey};
 use const_oid::AssociatedOid;
 use core::marker::PhantomData;
 use digest::{Digest, FixedOutputReset};
@@ -16,6 +16,12 @@ use signature::{
     hazmat::RandomizedPrehashSigner, Keypair, RandomizedDigestSigner, RandomizedSigner,
 };
 use zeroize::ZeroizeOnDrop;
+#[cfg(feature = "jwt")]
+use {
+    crate::jwt::{self, JwsSerialization, JwtDigest},
+    alloc::string::String,
+    serde_json::{Map, Value},
+};
 #[cfg(feature = "serde")]
 use {
     pkcs8::DecodePrivateKey,
@@ -31,29 +37,32 @@ use {
 /// Signing key for producing RSASSA-PSS signatures as described in
 /// [RFC8017 § 8.1].
 ///
+/// The private key operation is performed by `K`, which is an [`RsaPrivateKey`]
+/// by default but can be any [`RsaPrivateOps`] implementation.
+///
 /// [RFC8017 § 8.1]: https://datatracker.ietf.org/doc/html/rfc8017#section-8.1
 #[derive(Debug, Clone)]
-pub struct SigningKey<D>
+pub struct SigningKey<D, K = RsaPrivateKey>
 where
     D: Digest,
 {
-    inner: RsaPrivateKey,
+    inner: K,
     salt_len: usize,
     phantom: PhantomData<D>,
 }
 
-impl<D> SigningKey<D>
+impl<D, K> SigningKey<D, K>
 where
     D: Digest,
 {
     /// Create a new RSASSA-PSS signing key.
     /// Digest output size is used as a salt length.
-    pub fn new(key: RsaPrivateKey) -> Self {
+    pub fn new(key: K) -> Self {
         Self::new_with_salt_len(key, <D as Digest>::output_size())
     }
 
     /// Create a new RSASSA-PSS signing key with a salt of the given length.
-    pub fn new_with_salt_len(key: RsaPrivateKey, salt_len: usize) -> Self {
+    pub fn new_with_salt_len(key: K, salt_len: usize) -> Self {
         Self {
             inner: key,
             salt_len,
@@ -61,6 +70,16 @@ where
         }
     }
 
+    /// Return specified salt length for this key
+    pub fn salt_len(&self) -> usize {
+        self.salt_len
+    }
+}
+
+impl<D> SigningKey<D>
+where
+    D: Digest,
+{
     /// Generate a new random RSASSA-PSS signing key.
     /// Digest output size is used as a salt length.
     pub fn random<R: CryptoRngCore>(rng: &mut R, bit_size: usize) -> Result<Self> {
@@ -79,20 +98,16 @@ where
             phantom: Default::default(),
         })
     }
-
-    /// Return specified salt length for this key
-    pub fn salt_len(&self) -> usize {
-        self.salt_len
-    }
 }
 
 //
 // `*Signer` trait impls
 //
 
-impl<D> RandomizedDigestSigner<D, Signature> for SigningKey<D>
+impl<D, K> RandomizedDigestSigner<D, Signature> for SigningKey<D, K>
 where
     D: Digest + FixedOutputReset,
+    K: PrivateKeyOps,
 {
     fn try_sign_digest_with_rng(
         &self,
@@ -105,9 +120,10 @@ where
     }
 }
 
-impl<D> RandomizedSigner<Signature> for SigningKey<D>
+impl<D, K> RandomizedSigner<Signature> for SigningKey<D, K>
 where
     D: Digest + FixedOutputReset,
+    K: PrivateKeyOps,
 {
     fn try_sign_with_rng(
         &self,
@@ -118,9 +134,10 @@ where
     }
 }
 
-impl<D> RandomizedPrehashSigner<Signature> for SigningKey<D>
+impl<D, K> RandomizedPrehashSigner<Signature> for SigningKey<D, K>
 where
     D: Digest + FixedOutputReset,
+    K: PrivateKeyOps,
 {
     fn sign_prehash_with_rng(
         &self,
@@ -134,9 +151,10 @@ where
 }
 
 #[cfg(feature = "getrandom")]
-impl<D> PrehashSigner<Signature> for SigningKey<D>
+impl<D, K> PrehashSigner<Signature> for SigningKey<D, K>
 where
     D: Digest + FixedOutputReset,
+    K: PrivateKeyOps,
 {
     fn sign_prehash(&self, prehash: &[u8]) -> signature::Result<Signature> {
         self.sign_prehash_with_rng(&mut OsRng, prehash)
@@ -144,15 +162,137 @@ where
 }
 
 #[cfg(feature = "getrandom")]
-impl<D> Signer<Signature> for SigningKey<D>
+impl<D, K> Signer<Signature> for SigningKey<D, K>
 where
     D: Digest + FixedOutputReset,
+    K: PrivateKeyOps,
 {
     fn try_sign(&self, msg: &[u8]) -> signature::Result<Signature> {
         self.try_sign_with_rng(&mut OsRng, msg)
     }
 }
 
+//
+// Multipart signing
+//
+
+impl<D, K> SigningKey<D, K>
+where
+    D: Digest + FixedOutputReset,
+    K: PrivateKeyOps,
+{
+    /// Sign the concatenation of `msg`, hashing its parts in order instead
+    /// of copying them into one buffer, and generating the salt with `rng`.
+    ///
+    /// Mirrors `signature::RandomizedMultipartSigner`, which is not provided
+    /// by the version of the `signature` crate in use.
+    pub fn try_multipart_sign_with_rng(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[&[u8]],
+    ) -> signature::Result<Signature> {
+        self.try_sign_digest_with_rng(
+            rng,
+            msg.iter().fold(D::new(), |d, part| d.chain_update(part)),
+        )
+    }
+
+    /// Sign the concatenation of `msg` like
+    /// [`SigningKey::try_multipart_sign_with_rng`], using the operating
+    /// system's RNG.
+    #[cfg(feature = "getrandom")]
+    pub fn try_multipart_sign(&self, msg: &[&[u8]]) -> signature::Result<Signature> {
+        self.try_multipart_sign_with_rng(&mut OsRng, msg)
+    }
+}
+
+//
+// JSON Web Signatures and Tokens
+//
+
+#[cfg(feature = "jwt")]
+impl<D, K> SigningKey<D, K>
+where
+    D: JwtDigest,
+    K: PrivateKeyOps,
+{
+    /// Sign `payload` as a JSON Web Signature with the `PS*` algorithm of
+    /// `D`, using `rng` for the salt.
+    ///
+    /// The header is `{"alg":"PS256"}` for SHA-256, with the parameters of
+    /// `header_overrides` added or replaced, e.g. `kid` or `typ`. Fails with
+    /// [`Error::InvalidArguments`] if `header_overrides` sets `alg`.
+    ///
+    /// [`Error::InvalidArguments`]: crate::Error::InvalidArguments
+    pub fn sign_jws_with_rng<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+        payload: &[u8],
+        header_overrides: &Map<String, Value>,
+        serialization: JwsSerialization,
+    ) -> Result<String> {
+        let input = jwt::signing_input(D::PSS_ALG, None, header_overrides, payload)?;
+        self.sign_jose(rng, input, serialization)
+    }
+
+    /// Sign `payload` like [`SigningKey::sign_jws_with_rng`], drawing the
+    /// salt from the operating system.
+    #[cfg(feature = "getrandom")]
+    pub fn sign_jws(
+        &self,
+        payload: &[u8],
+        header_overrides: &Map<String, Value>,
+        serialization: JwsSerialization,
+    ) -> Result<String> {
+        self.sign_jws_with_rng(&mut OsRng, payload, header_overrides, serialization)
+    }
+
+    /// Sign `claims_json`, which must be a JSON object, as a JSON Web Token
+    /// with the `PS*` algorithm of `D`, using `rng` for the salt.
+    ///
+    /// The header is `{"alg":"PS256","typ":"JWT"}` for SHA-256, with the
+    /// parameters of `header_overrides` added or replaced, e.g. `kid`. Fails
+    /// with [`Error::InvalidArguments`] if `header_overrides` sets `alg`.
+    ///
+    /// [`Error::InvalidArguments`]: crate::Error::InvalidArguments
+    pub fn sign_jwt_with_rng<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+        claims_json: &str,
+        header_overrides: &Map<String, Value>,
+    ) -> Result<String> {
+        let input = jwt::jwt_signing_input(D::PSS_ALG, claims_json, header_overrides)?;
+        self.sign_jose(rng, input, JwsSerialization::Compact)
+    }
+
+    /// Sign `claims_json` like [`SigningKey::sign_jwt_with_rng`], drawing
+    /// the salt from the operating system.
+    #[cfg(feature = "getrandom")]
+    pub fn sign_jwt(
+        &self,
+        claims_json: &str,
+        header_overrides: &Map<String, Value>,
+    ) -> Result<String> {
+        self.sign_jwt_with_rng(&mut OsRng, claims_json, header_overrides)
+    }
+
+    fn sign_jose<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+        input: String,
+        serialization: JwsSerialization,
+    ) -> Result<String> {
+        let signature = sign_digest::<_, D>(
+            rng,
+            false,
+            &self.inner,
+            &D::digest(input.as_bytes()),
+            self.salt_len,
+        )?;
+        Ok(jwt::encode(input, &signature, serialization))
+    }
+}
+
 //
 // Other trait impls
 //
@@ -166,7 +306,7 @@ where
     }
 }
 
-impl<D> AssociatedAlgorithmIdentifier for SigningKey<D>
+impl<D, K> AssociatedAlgorithmIdentifier for SigningKey<D, K>
 where
     D: Digest,
 {
@@ -175,7 +315,7 @@ where
     const ALGORITHM_IDENTIFIER: AlgorithmIdentifierRef<'static> = pkcs1::ALGORITHM_ID;
 }
 
-impl<D> DynSignatureAlgorithmIdentifier for SigningKey<D>
+impl<D, K> DynSignatureAlgorithmIdentifier for SigningKey<D, K>
 where
     D: Digest + AssociatedOid,
 {
@@ -211,14 +351,15 @@ where
     }
 }
 
-impl<D> Keypair for SigningKey<D>
+impl<D, K> Keypair for SigningKey<D, K>
 where
     D: Digest,
+    K: AsRef<RsaPublicKey>,
 {
     type VerifyingKey = VerifyingKey<D>;
     fn verifying_key(&self) -> Self::VerifyingKey {
         VerifyingKey {
-            inner: self.inner.to_public_key(),
+            inner: self.inner.as_ref().clone(),
             salt_len: self.salt_len,
             phantom: Default::default(),
         }
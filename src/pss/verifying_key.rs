@@ -8,6 +8,14 @@ use pkcs8::{
     AssociatedOid, Document, EncodePublicKey,
 };
 use signature::{hazmat::PrehashVerifier, DigestVerifier, Verifier};
+#[cfg(feature = "jwt")]
+use {
+    crate::jwt::{JwtDigest, Token},
+    crate::traits::PublicKeyParts,
+    crate::Result,
+    alloc::vec::Vec,
+    serde_json::{Map, Value},
+};
 #[cfg(feature = "serde")]
 use {
     serdect::serde::{de, ser, Deserialize, Serialize},
@@ -97,6 +105,84 @@ where
     }
 }
 
+//
+// Multipart verification
+//
+
+impl<D> VerifyingKey<D>
+where
+    D: Digest + FixedOutputReset,
+{
+    /// Verify the signature of the concatenation of `msg`, hashing its parts
+    /// in order instead of copying them into one buffer.
+    ///
+    /// Mirrors `signature::MultipartVerifier`, which is not provided by the
+    /// version of the `signature` crate in use.
+    pub fn multipart_verify(&self, msg: &[&[u8]], signature: &Signature) -> signature::Result<()> {
+        self.verify_digest(
+            msg.iter().fold(D::new(), |d, part| d.chain_update(part)),
+            signature,
+        )
+    }
+}
+
+//
+// JSON Web Signatures and Tokens
+//
+
+#[cfg(feature = "jwt")]
+impl<D> VerifyingKey<D>
+where
+    D: JwtDigest,
+{
+    /// Verify a JSON Web Signature in compact serialization, signed with the
+    /// `PS*` algorithm of `D`, and return its payload.
+    ///
+    /// Fails with [`Error::Verification`] if the JWS is malformed, has
+    /// another `alg` or critical header parameters, or if its signature is
+    /// invalid.
+    ///
+    /// [`Error::Verification`]: crate::Error::Verification
+    pub fn verify_jws(&self, jws: &str) -> Result<Vec<u8>> {
+        let token = Token::parse(D::PSS_ALG, jws, None)?;
+        self.verify_jose(&token)?;
+        token.payload()
+    }
+
+    /// Verify a JSON Web Signature in detached serialization, i.e. with an
+    /// empty payload, signed over `payload` with the `PS*` algorithm of
+    /// `D`.
+    ///
+    /// Fails like [`VerifyingKey::verify_jws`], and if the payload of `jws`
+    /// is not empty.
+    pub fn verify_jws_detached(&self, jws: &str, payload: &[u8]) -> Result<()> {
+        self.verify_jose(&Token::parse(D::PSS_ALG, jws, Some(payload))?)
+    }
+
+    /// Verify a JSON Web Token signed with the `PS*` algorithm of `D`, and
+    /// return its claims.
+    ///
+    /// Fails with [`Error::Verification`] if the token is malformed, has
+    /// another `alg` or critical header parameters, or if its signature is
+    /// invalid. The claims themselves, e.g. `exp`, are not checked.
+    ///
+    /// [`Error::Verification`]: crate::Error::Verification
+    pub fn verify_jwt(&self, token: &str) -> Result<Map<String, Value>> {
+        let token = Token::parse(D::PSS_ALG, token, None)?;
+        self.verify_jose(&token)?;
+        token.claims()
+    }
+
+    fn verify_jose(&self, token: &Token<'_>) -> Result<()> {
+        verify_digest::<D>(
+            &self.inner,
+            &D::digest(token.signing_input.as_bytes()),
+            &token.signature(self.inner.n_bits_precision())?,
+            self.salt_len,
+        )
+    }
+}
+
 //
 // Other trait impls
 //
@@ -0,0 +1,102 @@
+//! Use of RNGs implementing the traits of `rand_core` 0.9.
+//!
+//! The crate is generic over the `rand_core` 0.6 traits. [`RngCompat`]
+//! borrows an RNG implementing [`TryCryptoRng`] of `rand_core` 0.9, which
+//! includes all its infallible [`CryptoRng`](rand_core_0_9::CryptoRng)s, and
+//! implements the `rand_core` 0.6 traits, so it can be passed to key
+//! generation, blinding and the padding schemes:
+//!
+#![cfg_attr(feature = "alloc", doc = "```")]
+#![cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+//! use rsa::rand_compat::RngCompat;
+//! use rsa::RsaPrivateKey;
+//!
+//! fn generate(rng: &mut impl rand_core_0_9::TryCryptoRng) -> rsa::Result<RsaPrivateKey> {
+//!     RsaPrivateKey::new(&mut RngCompat::new(rng), 2048)
+//! }
+//! ```
+//!
+//! Failures of fallible RNGs are reported to `try_fill_bytes`, so the
+//! `*_with_fallible_rng` methods of the keys return
+//! [`Error::Rng`](crate::errors::Error::Rng) for them. Other methods panic,
+//! like `rand_core` 0.6 RNGs do.
+
+use core::num::NonZeroU32;
+
+use rand_core::{CryptoRng, RngCore};
+use rand_core_0_9::{TryCryptoRng, TryRngCore};
+
+/// Adapter implementing the `rand_core` 0.6 traits for an RNG implementing
+/// [`TryCryptoRng`] of `rand_core` 0.9.
+#[derive(Debug)]
+pub struct RngCompat<'a, R: ?Sized> {
+    rng: &'a mut R,
+}
+
+impl<'a, R: TryCryptoRng + ?Sized> RngCompat<'a, R> {
+    /// Borrow `rng`.
+    pub fn new(rng: &'a mut R) -> Self {
+        Self { rng }
+    }
+}
+
+impl<R: TryCryptoRng + ?Sized> RngCore for RngCompat<'_, R> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if self.try_fill_bytes(dest).is_err() {
+            panic!("RNG failed");
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand_core::Error> {
+        self.rng.try_fill_bytes(dest).map_err(|_| {
+            NonZeroU32::new(rand_core::Error::CUSTOM_START)
+                .expect("non zero")
+                .into()
+        })
+    }
+}
+
+impl<R: TryCryptoRng + ?Sized> CryptoRng for RngCompat<'_, R> {}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+    use crate::RsaPrivateKey;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    /// `rand_core` 0.9 RNG drawing from a `rand_core` 0.6 one.
+    struct Rng09(ChaCha8Rng);
+
+    impl rand_core_0_9::RngCore for Rng09 {
+        fn next_u32(&mut self) -> u32 {
+            self.0.next_u32()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0.next_u64()
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.0.fill_bytes(dest)
+        }
+    }
+
+    impl rand_core_0_9::CryptoRng for Rng09 {}
+
+    #[test]
+    fn test_rng_compat() {
+        let mut rng = Rng09(ChaCha8Rng::from_seed([42; 32]));
+        let key = RsaPrivateKey::new(&mut RngCompat::new(&mut rng), 512).unwrap();
+
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        assert_eq!(key, RsaPrivateKey::new(&mut rng, 512).unwrap());
+    }
+}
@@ -0,0 +1,299 @@
+//! Signing with keys held by remote services.
+//!
+//! Key management services, e.g. AWS KMS, Google Cloud KMS or HashiCorp
+//! Vault, are plugged in by implementing [`AsyncRsaPrivateOps`] with a client
+//! of the service. The signing keys of this module compute the padding
+//! locally, so only the modular exponentiation goes over the wire, and
+//! implement the traits of [`async_signature`].
+
+use alloc::vec::Vec;
+use core::future::Future;
+use core::marker::PhantomData;
+
+use async_signature::{AsyncDigestSigner, AsyncRandomizedSigner, AsyncSigner};
+use crypto_bigint::BoxedUint;
+use digest::{Digest, FixedOutputReset};
+use pkcs8::AssociatedOid;
+use rand_core::CryptoRngCore;
+use signature::Keypair;
+
+use crate::algorithms::pad::uint_to_be_pad;
+use crate::algorithms::pkcs1v15::{pkcs1v15_generate_prefix, pkcs1v15_sign_pad};
+use crate::algorithms::pss::emsa_pss_encode_digest;
+use crate::algorithms::rsa::rsa_encrypt;
+use crate::errors::{Error, Result};
+use crate::traits::PublicKeyParts;
+use crate::{pkcs1v15, pss, RsaPublicKey};
+
+/// Raw RSA private key operation performed by a remote service.
+pub trait AsyncRsaPrivateOps: PublicKeyParts {
+    /// Computes `c^d mod n`, where `c` has the precision of `n`.
+    ///
+    /// The result is checked with the public key by the callers of this
+    /// method, as faulty signatures leak the private key.
+    fn raw_private_op(&self, c: &BoxedUint) -> impl Future<Output = Result<BoxedUint>> + Send;
+}
+
+/// Computes the raw private key operation on the encoded message `em` with
+/// `key`, checking the result.
+async fn remote_sign<K: AsyncRsaPrivateOps>(key: &K, em: &[u8]) -> Result<Vec<u8>> {
+    let em = BoxedUint::from_be_slice(em, key.n_bits_precision())?;
+    let sig = key.raw_private_op(&em).await?;
+    if &sig >= key.n().as_ref() || rsa_encrypt(key, &sig)? != em {
+        return Err(Error::FaultDetected);
+    }
+    uint_to_be_pad(sig, key.size())
+}
+
+/// Signing key for `RSASSA-PKCS1-v1_5` signatures computed by a remote service.
+#[derive(Debug, Clone)]
+pub struct RemoteSigningKey<D, K>
+where
+    D: Digest,
+{
+    inner: K,
+    prefix: Vec<u8>,
+    phantom: PhantomData<D>,
+}
+
+impl<D, K> RemoteSigningKey<D, K>
+where
+    D: Digest + AssociatedOid,
+{
+    /// Create a new signing key with a prefix for the digest `D`.
+    pub fn new(key: K) -> Self {
+        Self {
+            inner: key,
+            prefix: pkcs1v15_generate_prefix::<D>(),
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<D, K> RemoteSigningKey<D, K>
+where
+    D: Digest,
+{
+    /// Create a new signing key with an empty prefix.
+    pub fn new_unprefixed(key: K) -> Self {
+        Self {
+            inner: key,
+            prefix: Vec::new(),
+            phantom: Default::default(),
+        }
+    }
+
+    /// Returns the remote key.
+    pub fn key(&self) -> &K {
+        &self.inner
+    }
+}
+
+impl<D, K> RemoteSigningKey<D, K>
+where
+    D: Digest,
+    K: AsyncRsaPrivateOps,
+{
+    /// Sign the digest `prehash`.
+    pub async fn sign_prehash_async(&self, prehash: &[u8]) -> Result<pkcs1v15::Signature> {
+        let em = pkcs1v15_sign_pad(&self.prefix, prehash, self.inner.size())?;
+        let sig = remote_sign(&self.inner, &em).await?;
+        Ok(pkcs1v15::Signature::try_from(sig.as_slice())?)
+    }
+}
+
+impl<D, K> AsyncDigestSigner<D, pkcs1v15::Signature> for RemoteSigningKey<D, K>
+where
+    D: Digest,
+    K: AsyncRsaPrivateOps,
+{
+    async fn sign_digest_async(&self, digest: D) -> signature::Result<pkcs1v15::Signature> {
+        Ok(self.sign_prehash_async(&digest.finalize()).await?)
+    }
+}
+
+impl<D, K> AsyncSigner<pkcs1v15::Signature> for RemoteSigningKey<D, K>
+where
+    D: Digest,
+    K: AsyncRsaPrivateOps,
+{
+    async fn sign_async(&self, msg: &[u8]) -> signature::Result<pkcs1v15::Signature> {
+        Ok(self.sign_prehash_async(&D::digest(msg)).await?)
+    }
+}
+
+impl<D, K> Keypair for RemoteSigningKey<D, K>
+where
+    D: Digest + AssociatedOid,
+    K: AsRef<RsaPublicKey>,
+{
+    type VerifyingKey = pkcs1v15::VerifyingKey<D>;
+
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        pkcs1v15::VerifyingKey::new(self.inner.as_ref().clone())
+    }
+}
+
+/// Signing key for RSASSA-PSS signatures computed by a remote service.
+///
+/// The salt is generated locally.
+#[derive(Debug, Clone)]
+pub struct RemotePssSigningKey<D, K>
+where
+    D: Digest,
+{
+    inner: K,
+    salt_len: usize,
+    phantom: PhantomData<D>,
+}
+
+impl<D, K> RemotePssSigningKey<D, K>
+where
+    D: Digest,
+{
+    /// Create a new signing key. Digest output size is used as a salt length.
+    pub fn new(key: K) -> Self {
+        Self::new_with_salt_len(key, <D as Digest>::output_size())
+    }
+
+    /// Create a new signing key with a salt of the given length.
+    pub fn new_with_salt_len(key: K, salt_len: usize) -> Self {
+        Self {
+            inner: key,
+            salt_len,
+            phantom: Default::default(),
+        }
+    }
+
+    /// Returns the remote key.
+    pub fn key(&self) -> &K {
+        &self.inner
+    }
+}
+
+impl<D, K> RemotePssSigningKey<D, K>
+where
+    D: Digest + FixedOutputReset,
+    K: AsyncRsaPrivateOps,
+{
+    /// Sign the digest `prehash`, generating the salt with `rng`.
+    pub async fn sign_prehash_with_rng_async<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+        prehash: &[u8],
+    ) -> Result<pss::Signature> {
+        let mut salt = vec![0; self.salt_len];
+        rng.fill_bytes(&mut salt);
+
+        let em_bits = self.inner.n().bits() as usize - 1;
+        let em = emsa_pss_encode_digest::<D>(prehash, em_bits, &salt)?;
+        let sig = remote_sign(&self.inner, &em).await?;
+        Ok(pss::Signature::try_from(sig.as_slice())?)
+    }
+}
+
+impl<D, K> AsyncRandomizedSigner<pss::Signature> for RemotePssSigningKey<D, K>
+where
+    D: Digest + FixedOutputReset,
+    K: AsyncRsaPrivateOps,
+{
+    async fn try_sign_with_rng_async(
+        &self,
+        rng: &mut impl CryptoRngCore,
+        msg: &[u8],
+    ) -> signature::Result<pss::Signature> {
+        Ok(self
+            .sign_prehash_with_rng_async(rng, &D::digest(msg))
+            .await?)
+    }
+}
+
+impl<D, K> Keypair for RemotePssSigningKey<D, K>
+where
+    D: Digest,
+    K: AsRef<RsaPublicKey>,
+{
+    type VerifyingKey = pss::VerifyingKey<D>;
+
+    fn verifying_key(&self) -> Self::VerifyingKey {
+        pss::VerifyingKey::new_with_salt_len(self.inner.as_ref().clone(), self.salt_len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithms::rsa::rsa_decrypt_and_check;
+    use crate::dummy_rng::DummyRng;
+    use crate::RsaPrivateKey;
+    use alloc::sync::Arc;
+    use core::pin::pin;
+    use core::task::{Context, Poll, Waker};
+    use crypto_bigint::{modular::BoxedMontyParams, NonZero};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+    use sha2::Sha256;
+    use signature::Verifier;
+    use std::task::Wake;
+
+    /// Key computing the private operation locally, standing in for a service.
+    struct LocalKey(RsaPrivateKey);
+
+    impl PublicKeyParts for LocalKey {
+        fn n(&self) -> &NonZero<BoxedUint> {
+            self.0.n()
+        }
+
+        fn e(&self) -> &BoxedUint {
+            self.0.e()
+        }
+
+        fn n_params(&self) -> &BoxedMontyParams {
+            self.0.n_params()
+        }
+    }
+
+    impl AsRef<RsaPublicKey> for LocalKey {
+        fn as_ref(&self) -> &RsaPublicKey {
+            self.0.as_ref()
+        }
+    }
+
+    impl AsyncRsaPrivateOps for LocalKey {
+        async fn raw_private_op(&self, c: &BoxedUint) -> Result<BoxedUint> {
+            rsa_decrypt_and_check::<DummyRng>(&self.0, None, c)
+        }
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        struct NoopWaker;
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = pin!(fut);
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+        }
+    }
+
+    #[test]
+    fn test_remote_signing_keys() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let priv_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let msg = b"remote";
+
+        let signing_key = RemoteSigningKey::<Sha256, _>::new(LocalKey(priv_key.clone()));
+        let sig = block_on(signing_key.sign_async(msg)).unwrap();
+        let local = pkcs1v15::SigningKey::<Sha256>::new(priv_key.clone());
+        assert_eq!(sig, signature::Signer::sign(&local, msg));
+        signing_key.verifying_key().verify(msg, &sig).unwrap();
+
+        let signing_key = RemotePssSigningKey::<Sha256, _>::new(LocalKey(priv_key));
+        let sig = block_on(signing_key.try_sign_with_rng_async(&mut rng, msg)).unwrap();
+        signing_key.verifying_key().verify(msg, &sig).unwrap();
+    }
+}
@@ -0,0 +1,198 @@
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+/// Personalization string of the generator.
+const LABEL: &[u8] = b"rsa keygen seed expansion v2";
+
+/// Length in bytes of the state `V` and the constant `C`, i.e. `seedlen`
+/// of Hash_DRBG with SHA-256.
+const SEED_LEN: usize = 55;
+
+/// Number of bytes produced by each generate request.
+const REQUEST_LEN: usize = 256;
+
+/// Deterministic random generator expanding a seed with Hash_DRBG, as
+/// specified in [NIST SP 800-90A Rev. 1 § 10.1.1], with SHA-256.
+///
+/// The generator is instantiated with the seed as entropy input, an empty
+/// nonce and `LABEL` as personalization string. The output stream is the
+/// concatenation of the outputs of successive generate requests of
+/// `REQUEST_LEN` bytes each, without additional input or reseeding, and
+/// bytes are handed out in order.
+///
+/// [NIST SP 800-90A Rev. 1 § 10.1.1]: https://nvlpubs.nist.gov/nistpubs/SpecialPublications/NIST.SP.800-90Ar1.pdf#page=44
+pub(crate) struct SeededRng {
+    v: [u8; SEED_LEN],
+    c: [u8; SEED_LEN],
+    reseed_counter: u64,
+    block: [u8; REQUEST_LEN],
+    offset: usize,
+}
+
+impl SeededRng {
+    pub(crate) fn new(seed: &[u8]) -> Self {
+        let mut v = [0; SEED_LEN];
+        hash_df(&[seed, LABEL], &mut v);
+        let mut c = [0; SEED_LEN];
+        hash_df(&[&[0x00][..], &v[..]], &mut c);
+        Self {
+            v,
+            c,
+            reseed_counter: 1,
+            block: [0; REQUEST_LEN],
+            offset: REQUEST_LEN,
+        }
+    }
+
+    /// Generate the next block with the Hash_DRBG generate process.
+    fn refill(&mut self) {
+        // Hashgen
+        let mut data = self.v;
+        for chunk in self.block.chunks_mut(32) {
+            chunk.copy_from_slice(&Sha256::digest(data));
+            add(&mut data, &[1]);
+        }
+        data.zeroize();
+
+        // V = (V + Hash(0x03 || V) + C + reseed_counter) mod 2^seedlen
+        let h = Sha256::new()
+            .chain_update([0x03])
+            .chain_update(self.v)
+            .finalize();
+        add(&mut self.v, &h);
+        add(&mut self.v, &self.c);
+        add(&mut self.v, &self.reseed_counter.to_be_bytes());
+        self.reseed_counter += 1;
+        self.offset = 0;
+    }
+}
+
+/// Hash derivation function `Hash_df` of SP 800-90A § 10.3.1, filling `out`
+/// from the concatenation of `input`.
+fn hash_df(input: &[&[u8]], out: &mut [u8; SEED_LEN]) {
+    let bits = (SEED_LEN * 8) as u32;
+    for (counter, chunk) in (1u8..).zip(out.chunks_mut(32)) {
+        let mut hash = Sha256::new()
+            .chain_update([counter])
+            .chain_update(bits.to_be_bytes());
+        for part in input {
+            hash.update(part);
+        }
+        chunk.copy_from_slice(&hash.finalize()[..chunk.len()]);
+    }
+}
+
+/// Adds the big endian integer `y` to `x`, modulo `2^seedlen`.
+fn add(x: &mut [u8; SEED_LEN], y: &[u8]) {
+    let mut carry = 0u16;
+    for (i, byte) in x.iter_mut().rev().enumerate() {
+        let other = if i < y.len() { y[y.len() - 1 - i] } else { 0 };
+        let sum = u16::from(*byte) + u16::from(other) + carry;
+        *byte = sum as u8;
+        carry = sum >> 8;
+    }
+}
+
+impl Drop for SeededRng {
+    fn drop(&mut self) {
+        self.v.zeroize();
+        self.c.zeroize();
+        self.block.zeroize();
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut dest = dest;
+        while !dest.is_empty() {
+            if self.offset == self.block.len() {
+                self.refill();
+            }
+            let n = core::cmp::min(dest.len(), self.block.len() - self.offset);
+            dest[..n].copy_from_slice(&self.block[self.offset..self.offset + n]);
+            self.offset += n;
+            dest = &mut dest[n..];
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> core::result::Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for SeededRng {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn test_instantiate() {
+        let rng = SeededRng::new(b"seed");
+        assert_eq!(
+            rng.v,
+            hex!(
+                "67016e3af160e8ce902eac2d09f2827d63ae55cf385afad42fe35de0dd87b025"
+                "58dfb5df007e81237807e5c5cc4042327c97909f45988f"
+            )
+        );
+        assert_eq!(
+            rng.c,
+            hex!(
+                "190e1647ee59114552a39b6950412e969c3a97629af4eb24b6c21c13bfb4bc9b"
+                "0a489a573ac9e69286c49ca58b679501112af3b326aea0"
+            )
+        );
+    }
+
+    #[test]
+    fn test_stream() {
+        let mut a = SeededRng::new(b"seed");
+        let mut b = SeededRng::new(b"seed");
+
+        let mut whole = [0u8; 300];
+        a.fill_bytes(&mut whole);
+        let mut pieces = [0u8; 300];
+        for chunk in pieces.chunks_mut(7) {
+            b.fill_bytes(chunk);
+        }
+        assert_eq!(whole, pieces);
+
+        // first bytes of the first and second generate requests
+        assert_eq!(
+            whole[..32],
+            hex!("315f48a63c3d47ccf335600218f490b99dc9b3a09932e46c7933750a3c1e54a2")
+        );
+        assert_eq!(
+            whole[256..288],
+            hex!("568802c33207ef93ed82fff4059391e785aa77ba266bba9d6dfc0046edb1c202")
+        );
+
+        let mut other = [0u8; 300];
+        SeededRng::new(b"seee").fill_bytes(&mut other);
+        assert_ne!(whole, other);
+    }
+
+    #[test]
+    fn test_add() {
+        let mut x = [0xff; SEED_LEN];
+        add(&mut x, &[1]);
+        assert_eq!(x, [0; SEED_LEN]);
+
+        let mut x = [0; SEED_LEN];
+        x[SEED_LEN - 1] = 0xff;
+        add(&mut x, &[0x01, 0x01]);
+        assert_eq!(x[SEED_LEN - 2..], [0x02, 0x00]);
+    }
+}
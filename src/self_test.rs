@@ -0,0 +1,262 @@
+//! Power-on self-test.
+//!
+//! [`self_test`] runs known-answer tests of signing, verification,
+//! encryption and decryption with PKCS#1 v1.5, PSS and OAEP, in the manner
+//! of the self-tests FIPS 140 modules run before being used. The tests use
+//! an embedded 2048-bit key, and the randomness consumed by PSS and by
+//! encryption is drawn from generators with fixed seeds, so that every
+//! output is known in advance.
+//!
+//! Expected outputs are stored as their SHA-256 digests.
+
+use alloc::vec::Vec;
+use core::fmt;
+use pkcs8::DecodePrivateKey;
+use sha2::{Digest, Sha256};
+
+use crate::errors::{Error, Result};
+use crate::key::RsaPrivateKey;
+use crate::seeded_rng::SeededRng;
+use crate::{Oaep, Pkcs1v15Encrypt, Pkcs1v15Sign, Pss};
+
+/// PKCS#8 encoding of the 2048-bit key used by the tests.
+const KEY: &[u8] = include_bytes!("self_test/rsa2048-priv.der");
+
+/// Message signed and encrypted by the tests.
+const MESSAGE: &[u8] = b"rsa power-on self-test";
+
+/// Length of the PSS salt.
+const PSS_SALT_LEN: usize = 32;
+
+const PSS_SEED: &[u8] = b"rsa self-test pss";
+const OAEP_SEED: &[u8] = b"rsa self-test oaep";
+const PKCS1V15_SEED: &[u8] = b"rsa self-test pkcs1v15";
+
+/// SHA-256 of the PKCS#1 v1.5 signature of SHA-256(`MESSAGE`).
+const PKCS1V15_SIGNATURE: [u8; 32] = [
+    0x4d, 0xcc, 0x36, 0x88, 0xca, 0x1b, 0xb8, 0x27, 0x21, 0x95, 0x33, 0xe2, 0xb4, 0xb8, 0x1c, 0xca,
+    0x2e, 0xf7, 0xf7, 0x43, 0xdd, 0xce, 0x55, 0xbe, 0xee, 0x87, 0xa0, 0xd3, 0x7c, 0x01, 0x81, 0xac,
+];
+
+/// SHA-256 of the PSS signature of SHA-256(`MESSAGE`), with the salt drawn
+/// from `PSS_SEED`.
+const PSS_SIGNATURE: [u8; 32] = [
+    0xc2, 0x41, 0x38, 0x44, 0x12, 0xdb, 0xfb, 0x02, 0xb3, 0x5a, 0x3b, 0xa9, 0x81, 0xbc, 0x1c, 0x7f,
+    0x37, 0x15, 0x63, 0xcd, 0x4b, 0xe9, 0x5a, 0x33, 0x21, 0xc0, 0x3a, 0x04, 0x8e, 0x9e, 0xd3, 0x1d,
+];
+
+/// SHA-256 of the OAEP encryption of `MESSAGE`, with the seed drawn from
+/// `OAEP_SEED`.
+const OAEP_CIPHERTEXT: [u8; 32] = [
+    0x6f, 0x26, 0x81, 0x83, 0xf6, 0x80, 0x9c, 0x6f, 0x1e, 0xc8, 0x0a, 0x0c, 0xb8, 0x10, 0xdc, 0xa5,
+    0x62, 0xe2, 0x61, 0xc4, 0xd9, 0x86, 0x9e, 0x48, 0x49, 0xad, 0xd9, 0x93, 0x54, 0x8c, 0x13, 0x16,
+];
+
+/// SHA-256 of the PKCS#1 v1.5 encryption of `MESSAGE`, with the padding
+/// drawn from `PKCS1V15_SEED`.
+const PKCS1V15_CIPHERTEXT: [u8; 32] = [
+    0x90, 0xfb, 0x7e, 0xdb, 0x28, 0x64, 0x72, 0x8a, 0x43, 0xae, 0xb5, 0x34, 0xc6, 0x41, 0xf7, 0x4f,
+    0x12, 0xbe, 0xb2, 0x7e, 0x32, 0xe6, 0x0e, 0x07, 0xe4, 0x3b, 0xa3, 0x6f, 0x6a, 0x4d, 0xf3, 0x7a,
+];
+
+/// Outcome of a single self-test.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SelfTestOutcome {
+    /// The operation produced the expected output.
+    Passed,
+    /// The operation succeeded, but produced an unexpected output.
+    Mismatch,
+    /// The operation failed.
+    Error(Error),
+    /// The test was not run, because the operation producing its input
+    /// failed.
+    Skipped,
+}
+
+/// Result of a single self-test.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SelfTestResult {
+    /// Name of the test, e.g. `"pss-sign"`.
+    pub name: &'static str,
+    /// Outcome of the test.
+    pub outcome: SelfTestOutcome,
+}
+
+impl SelfTestResult {
+    /// Returns whether the test passed.
+    pub fn passed(&self) -> bool {
+        self.outcome == SelfTestOutcome::Passed
+    }
+}
+
+impl fmt::Display for SelfTestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            SelfTestOutcome::Passed => write!(f, "{}: passed", self.name),
+            SelfTestOutcome::Mismatch => write!(f, "{}: unexpected output", self.name),
+            SelfTestOutcome::Error(err) => write!(f, "{}: {}", self.name, err),
+            SelfTestOutcome::Skipped => write!(f, "{}: skipped", self.name),
+        }
+    }
+}
+
+/// Report of [`self_test`], listing the results of all tests in the order
+/// they ran.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Results of the tests.
+    pub results: Vec<SelfTestResult>,
+}
+
+impl SelfTestReport {
+    /// Returns whether every test passed.
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(SelfTestResult::passed)
+    }
+
+    /// Returns the tests which did not pass.
+    pub fn failures(&self) -> impl Iterator<Item = &SelfTestResult> {
+        self.results.iter().filter(|result| !result.passed())
+    }
+}
+
+/// Run the power-on self-test.
+///
+/// Each operation is checked against its known answer, and each signature
+/// and ciphertext is then verified or decrypted back. A tampered signature
+/// must also fail to verify. A key which fails to load is reported as a
+/// single failed `"key"` test. Applications which must not use a faulty
+/// implementation should refuse to start unless
+/// [`SelfTestReport::is_success`] holds.
+///
+/// ```
+/// let report = rsa::self_test();
+/// assert!(report.is_success());
+/// ```
+pub fn self_test() -> SelfTestReport {
+    let mut results = Vec::new();
+    let mut record = |name: &'static str, outcome: SelfTestOutcome| {
+        results.push(SelfTestResult { name, outcome });
+    };
+
+    let key = match RsaPrivateKey::from_pkcs8_der(KEY) {
+        Ok(key) => key,
+        Err(err) => {
+            record("key", SelfTestOutcome::Error(err.into()));
+            return SelfTestReport { results };
+        }
+    };
+    let public_key = key.to_public_key();
+    let hashed = Sha256::digest(MESSAGE);
+
+    let signature = key.sign(Pkcs1v15Sign::new::<Sha256>(), &hashed);
+    let (sign, verify) = check_signature(signature, &PKCS1V15_SIGNATURE, |sig| {
+        public_key.verify(Pkcs1v15Sign::new::<Sha256>(), &hashed, sig)
+    });
+    record("pkcs1v15-sign", sign);
+    record("pkcs1v15-verify", verify);
+
+    let signature = key.sign_with_rng(
+        &mut SeededRng::new(PSS_SEED),
+        Pss::new_with_salt::<Sha256>(PSS_SALT_LEN),
+        &hashed,
+    );
+    let (sign, verify) = check_signature(signature, &PSS_SIGNATURE, |sig| {
+        public_key.verify(Pss::new_with_salt::<Sha256>(PSS_SALT_LEN), &hashed, sig)
+    });
+    record("pss-sign", sign);
+    record("pss-verify", verify);
+
+    let ciphertext = public_key.encrypt(
+        &mut SeededRng::new(OAEP_SEED),
+        Oaep::new::<Sha256>(),
+        MESSAGE,
+    );
+    let (encrypt, decrypt) = check_ciphertext(ciphertext, &OAEP_CIPHERTEXT, |ct| {
+        key.decrypt(Oaep::new::<Sha256>(), ct)
+    });
+    record("oaep-encrypt", encrypt);
+    record("oaep-decrypt", decrypt);
+
+    let ciphertext =
+        public_key.encrypt(&mut SeededRng::new(PKCS1V15_SEED), Pkcs1v15Encrypt, MESSAGE);
+    let (encrypt, decrypt) = check_ciphertext(ciphertext, &PKCS1V15_CIPHERTEXT, |ct| {
+        key.decrypt(Pkcs1v15Encrypt, ct)
+    });
+    record("pkcs1v15-encrypt", encrypt);
+    record("pkcs1v15-decrypt", decrypt);
+
+    SelfTestReport { results }
+}
+
+/// Compare the SHA-256 digest of `output` with `expected`.
+fn known_answer(output: &[u8], expected: &[u8; 32]) -> SelfTestOutcome {
+    if Sha256::digest(output).as_slice() == expected {
+        SelfTestOutcome::Passed
+    } else {
+        SelfTestOutcome::Mismatch
+    }
+}
+
+/// Check `signature` against its known answer, then check that it verifies
+/// and that it no longer does with its last byte flipped.
+fn check_signature(
+    signature: Result<Vec<u8>>,
+    expected: &[u8; 32],
+    verify: impl Fn(&[u8]) -> Result<()>,
+) -> (SelfTestOutcome, SelfTestOutcome) {
+    let mut signature = match signature {
+        Ok(signature) => signature,
+        Err(err) => return (SelfTestOutcome::Error(err), SelfTestOutcome::Skipped),
+    };
+    let sign = known_answer(&signature, expected);
+    if let Err(err) = verify(&signature) {
+        return (sign, SelfTestOutcome::Error(err));
+    }
+
+    if let Some(last) = signature.last_mut() {
+        *last ^= 1;
+    }
+    let verify = match verify(&signature) {
+        Ok(()) => SelfTestOutcome::Mismatch,
+        Err(_) => SelfTestOutcome::Passed,
+    };
+    (sign, verify)
+}
+
+/// Check `ciphertext` against its known answer, then check that it decrypts
+/// back to `MESSAGE`.
+fn check_ciphertext(
+    ciphertext: Result<Vec<u8>>,
+    expected: &[u8; 32],
+    decrypt: impl Fn(&[u8]) -> Result<Vec<u8>>,
+) -> (SelfTestOutcome, SelfTestOutcome) {
+    let ciphertext = match ciphertext {
+        Ok(ciphertext) => ciphertext,
+        Err(err) => return (SelfTestOutcome::Error(err), SelfTestOutcome::Skipped),
+    };
+    let encrypt = known_answer(&ciphertext, expected);
+    let decrypt = match decrypt(&ciphertext) {
+        Ok(plaintext) if plaintext == MESSAGE => SelfTestOutcome::Passed,
+        Ok(_) => SelfTestOutcome::Mismatch,
+        Err(err) => SelfTestOutcome::Error(err),
+    };
+    (encrypt, decrypt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_test() {
+        let report = self_test();
+        assert_eq!(report.results.len(), 8);
+        assert!(
+            report.is_success(),
+            "{:?}",
+            report.failures().collect::<Vec<_>>()
+        );
+        assert_eq!(report.results[2].to_string(), "pss-sign: passed");
+    }
+}
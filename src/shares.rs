@@ -0,0 +1,372 @@
+//! Verifiable Shamir secret sharing of RSA private keys.
+//!
+//! A private key is split into `n` [`KeyShare`]s, any `t` of which allow
+//! recovering the key with [`RsaPrivateKey::from_shares`], while fewer
+//! shares reveal nothing about it. This is intended for backup and escrow of
+//! long-lived keys.
+//!
+//! The shared secret is the first prime factor of the modulus, from which the
+//! remaining components are recomputed. Every share carries [Pedersen]
+//! commitments to the sharing polynomial in a prime order subgroup, which
+//! allow each shareholder to check their share with [`KeyShare::verify`].
+//! The commitments are perfectly hiding, so fewer than `t` shares and the
+//! commitments reveal nothing about the key, while a dealer handing out
+//! inconsistent shares is detected unless it can compute discrete
+//! logarithms in the subgroup. The dealer chooses the group, so the second
+//! generator is derived from it by hashing, and nobody knows its discrete
+//! logarithm to the base of the first.
+//!
+//! [Pedersen]: https://doi.org/10.1007/3-540-46766-1_9
+
+use alloc::vec::Vec;
+use core::fmt;
+use crypto_bigint::modular::{BoxedMontyForm, BoxedMontyParams};
+use crypto_bigint::{BoxedUint, NonZero, RandomMod};
+use rand_core::CryptoRngCore;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::algorithms::arith::{is_prime_vartime, monty_params, resize, to_monty};
+use crate::algorithms::generate::generate_safe_prime_with_rng;
+use crate::algorithms::mgf::mgf1_xor_digest;
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::traits::{PrivateKeyParts, PublicKeyParts};
+
+/// Generator `g` of the order `(group - 1) / 2` subgroup used for commitments.
+const GENERATOR: u64 = 4;
+
+/// Domain separation tag for deriving the second generator `h` of the
+/// subgroup, see [`blinding_generator`].
+const BLINDING_GENERATOR_TAG: &[u8] = b"rsa key share blinding generator v1";
+
+/// One share of an RSA private key.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyShare {
+    /// Index of the share, starting at `1`.
+    index: u32,
+    /// Public key the share belongs to.
+    public_key: RsaPublicKey,
+    /// Safe prime `2q + 1` defining the commitment group.
+    group: BoxedUint,
+    /// Commitments `g^a_j h^b_j mod group` to the coefficients of the
+    /// polynomials.
+    commitments: Vec<BoxedUint>,
+    /// Evaluation of the sharing polynomial at `index`, modulo `q`.
+    value: BoxedUint,
+    /// Evaluation of the blinding polynomial at `index`, modulo `q`.
+    blinding: BoxedUint,
+}
+
+impl fmt::Debug for KeyShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyShare")
+            .field("index", &self.index)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for KeyShare {
+    fn drop(&mut self) {
+        self.value.zeroize();
+        self.blinding.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for KeyShare {}
+
+impl KeyShare {
+    /// Index of this share, starting at `1`.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Number of shares needed to recover the key.
+    pub fn threshold(&self) -> usize {
+        self.commitments.len()
+    }
+
+    /// Public key the share belongs to.
+    pub fn public_key(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+
+    /// Check this share against the commitments it carries.
+    ///
+    /// Shareholders should compare the commitments of their shares with each
+    /// other, to make sure they all belong to the same sharing.
+    ///
+    /// Fails with [`Error::InvalidKeyShare`] if the commitment group is not a
+    /// safe prime, and with [`Error::Verification`] if the share doesn't
+    /// match the commitments.
+    pub fn verify(&self) -> Result<()> {
+        if !is_prime_vartime(&self.group) || !is_prime_vartime(&(&self.group >> 1)) {
+            return Err(Error::InvalidKeyShare);
+        }
+        let group = monty_params(&self.group)?;
+        let order = subgroup_order(&self.group)?;
+        let g = to_monty(&BoxedUint::from(GENERATOR), &group);
+        let h = blinding_generator(&self.group, &group)?;
+
+        // g^f(i) h^r(i) == Π C_j^(i^j)
+        let x = to_monty(&BoxedUint::from(u64::from(self.index)), &order);
+        let mut power = BoxedMontyForm::one(order.clone());
+        let mut expected = BoxedMontyForm::one(group.clone());
+        for commitment in &self.commitments {
+            expected = expected * to_monty(commitment, &group).pow(&power.retrieve());
+            power = power * &x;
+        }
+
+        let actual = g.pow(&self.value) * h.pow(&self.blinding);
+        if actual.retrieve() == expected.retrieve() {
+            Ok(())
+        } else {
+            Err(Error::Verification)
+        }
+    }
+
+    /// Whether both shares belong to the same sharing.
+    fn same_sharing(&self, other: &Self) -> bool {
+        self.public_key == other.public_key
+            && self.group == other.group
+            && self.commitments == other.commitments
+    }
+}
+
+/// Split `key` into `count` shares, any `threshold` of which recover it.
+pub(crate) fn split<R: CryptoRngCore>(
+    key: &RsaPrivateKey,
+    threshold: usize,
+    count: usize,
+    rng: &mut R,
+) -> Result<Vec<KeyShare>> {
+    if threshold == 0 || threshold > count || count > u32::MAX as usize {
+        return Err(Error::InvalidArguments);
+    }
+    let p = match key.primes() {
+        [p, _] => p,
+        _ => return Err(Error::InvalidArguments),
+    };
+
+    // The subgroup order q = (group - 1) / 2 must be larger than the secret.
+    let group = generate_safe_prime_with_rng(rng, p.bits() + 2);
+    let group_params = monty_params(&group)?;
+    let order = subgroup_order(&group)?;
+    let order_nz = order.modulus().as_nz_ref().clone();
+    let g = to_monty(&BoxedUint::from(GENERATOR), &group_params);
+    let h = blinding_generator(&group, &group_params)?;
+
+    let mut coefficients = Vec::with_capacity(threshold);
+    coefficients.push(to_monty(p, &order));
+    for _ in 1..threshold {
+        let c = BoxedUint::random_mod(rng, &order_nz);
+        coefficients.push(to_monty(&c, &order));
+    }
+    let blindings: Vec<BoxedMontyForm> = (0..threshold)
+        .map(|_| to_monty(&BoxedUint::random_mod(rng, &order_nz), &order))
+        .collect();
+    let commitments: Vec<BoxedUint> = coefficients
+        .iter()
+        .zip(&blindings)
+        .map(|(a, b)| (g.pow(&a.retrieve()) * h.pow(&b.retrieve())).retrieve())
+        .collect();
+
+    let public_key = key.to_public_key();
+    let shares = (1..=count as u32)
+        .map(|index| {
+            let x = to_monty(&BoxedUint::from(u64::from(index)), &order);
+            KeyShare {
+                index,
+                public_key: public_key.clone(),
+                group: group.clone(),
+                commitments: commitments.clone(),
+                value: evaluate(&coefficients, &x).retrieve(),
+                blinding: evaluate(&blindings, &x).retrieve(),
+            }
+        })
+        .collect();
+
+    Ok(shares)
+}
+
+/// Recover a private key from at least `threshold` shares.
+pub(crate) fn combine(shares: &[KeyShare]) -> Result<RsaPrivateKey> {
+    let first = shares.first().ok_or(Error::InvalidArguments)?;
+    let threshold = first.threshold();
+    if shares.len() < threshold {
+        return Err(Error::InvalidArguments);
+    }
+    for (i, share) in shares.iter().enumerate() {
+        if !share.same_sharing(first) || shares[..i].iter().any(|s| s.index == share.index) {
+            return Err(Error::InvalidArguments);
+        }
+        share.verify()?;
+    }
+
+    let order = subgroup_order(&first.group)?;
+    let shares = &shares[..threshold];
+    let mut secret = BoxedMontyForm::zero(order.clone());
+    for share in shares {
+        let xj = to_monty(&BoxedUint::from(u64::from(share.index)), &order);
+        let mut num = BoxedMontyForm::one(order.clone());
+        let mut den = BoxedMontyForm::one(order.clone());
+        for other in shares.iter().filter(|s| s.index != share.index) {
+            let xm = to_monty(&BoxedUint::from(u64::from(other.index)), &order);
+            den = den * &(&xm - &xj);
+            num = num * &xm;
+        }
+        let den = den.invert().into_option().ok_or(Error::InvalidKeyShare)?;
+        secret = secret + num * den * to_monty(&share.value, &order);
+    }
+    let mut secret = secret.retrieve();
+
+    let public_key = &first.public_key;
+    let n = public_key.n();
+    let bits = n.bits_precision();
+    let p = resize(&secret, bits);
+    secret.zeroize();
+
+    let p_nz = NonZero::new(p.clone())
+        .into_option()
+        .ok_or(Error::InvalidPrime)?;
+    if !bool::from(n.rem_vartime(&p_nz).is_zero()) {
+        return Err(Error::InvalidPrime);
+    }
+    let q = n.as_ref().clone() / p_nz;
+
+    let prime_bits = bits / 2;
+    if p.bits() > prime_bits || q.bits() > prime_bits {
+        return Err(Error::InvalidPrime);
+    }
+    RsaPrivateKey::from_primes(
+        vec![resize(&p, prime_bits), resize(&q, prime_bits)],
+        public_key.e().clone(),
+    )
+}
+
+/// Evaluates the polynomial with the given coefficients at `x`.
+fn evaluate(coefficients: &[BoxedMontyForm], x: &BoxedMontyForm) -> BoxedMontyForm {
+    let mut value = BoxedMontyForm::zero(x.params().clone());
+    for c in coefficients.iter().rev() {
+        value = value * x + c;
+    }
+    value
+}
+
+/// Derives the second generator `h` of the order `(group - 1) / 2` subgroup
+/// from `group`.
+///
+/// `h` is the square of `MGF1-SHA-256(tag || group || counter)` modulo
+/// `group`, for the first big-endian `u32` counter for which it is not `0`
+/// or `1`. The hash output is 16 bytes longer than `group` to make the
+/// reduction unbiased.
+fn blinding_generator(group: &BoxedUint, params: &BoxedMontyParams) -> Result<BoxedMontyForm> {
+    let group_bytes = group.to_be_bytes();
+    let one = BoxedMontyForm::one(params.clone()).retrieve();
+    let mut seed = [BLINDING_GENERATOR_TAG, &group_bytes[..]].concat();
+    seed.extend_from_slice(&[0; 4]);
+    let mut hashed = vec![0u8; group_bytes.len() + 16];
+    for counter in 0..=u32::MAX {
+        let len = seed.len();
+        seed[len - 4..].copy_from_slice(&counter.to_be_bytes());
+        hashed.fill(0);
+        mgf1_xor_digest(&mut hashed, &mut Sha256::default(), &seed);
+
+        let x = BoxedUint::from_be_slice(&hashed, hashed.len() as u32 * 8)?;
+        let h = to_monty(&x, params).square();
+        let value = h.retrieve();
+        if !bool::from(value.is_zero()) && value != one {
+            return Ok(h);
+        }
+    }
+    Err(Error::InvalidKeyShare)
+}
+
+/// Montgomery parameters for the prime order `(group - 1) / 2` of the
+/// commitment subgroup.
+fn subgroup_order(group: &BoxedUint) -> Result<BoxedMontyParams> {
+    monty_params(&(group >> 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    #[test]
+    fn test_split_and_combine() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 256).unwrap();
+        let shares = key.split_shares(3, 5, &mut rng).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for share in &shares {
+            share.verify().unwrap();
+            assert_eq!(share.threshold(), 3);
+        }
+
+        let restored = RsaPrivateKey::from_shares(&shares[1..4]).unwrap();
+        assert_eq!(restored.n(), key.n());
+        assert_eq!(restored.d(), key.d());
+
+        let restored =
+            RsaPrivateKey::from_shares(&[shares[4].clone(), shares[0].clone(), shares[2].clone()])
+                .unwrap();
+        assert_eq!(restored.n(), key.n());
+
+        assert_eq!(
+            RsaPrivateKey::from_shares(&shares[..2]),
+            Err(Error::InvalidArguments)
+        );
+        assert_eq!(
+            RsaPrivateKey::from_shares(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]),
+            Err(Error::InvalidArguments)
+        );
+    }
+
+    #[test]
+    fn test_tampered_share() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 256).unwrap();
+        let mut shares = key.split_shares(2, 3, &mut rng).unwrap();
+
+        let one = BoxedUint::one_with_precision(shares[0].value.bits_precision());
+        shares[0].value = shares[0].value.wrapping_add(&one);
+        assert_eq!(shares[0].verify(), Err(Error::Verification));
+        shares[1].blinding = shares[1].blinding.wrapping_add(&one);
+        assert_eq!(shares[1].verify(), Err(Error::Verification));
+        assert_eq!(
+            RsaPrivateKey::from_shares(&shares[..2]),
+            Err(Error::Verification)
+        );
+    }
+
+    #[test]
+    fn test_unsafe_group() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 256).unwrap();
+        let mut shares = key.split_shares(2, 3, &mut rng).unwrap();
+
+        let two = BoxedUint::from(2u64).widen(shares[0].group.bits_precision());
+        for share in &mut shares {
+            share.group = share.group.wrapping_add(&two);
+            assert_eq!(share.verify(), Err(Error::InvalidKeyShare));
+        }
+        assert_eq!(
+            RsaPrivateKey::from_shares(&shares[..2]),
+            Err(Error::InvalidKeyShare)
+        );
+    }
+
+    #[test]
+    fn test_invalid_threshold() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 256).unwrap();
+        assert!(key.split_shares(0, 3, &mut rng).is_err());
+        assert!(key.split_shares(4, 3, &mut rng).is_err());
+    }
+}
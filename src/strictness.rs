@@ -0,0 +1,356 @@
+//! Strict and lenient decoding of keys and signatures.
+//!
+//! The decoders of [`RsaPublicKey`] and [`RsaPrivateKey`] only accept DER.
+//! Keys found in the wild sometimes are not: their integers have extra
+//! leading zero octets or lack the zero octet keeping them positive, their
+//! lengths use the long form when the short one would do, or junk follows
+//! the encoded structure. [`Strictness::Lenient`] accepts these by
+//! re-encoding the input as DER before decoding it.
+//!
+//! Both modes reject keys with zero components, which the decoders would
+//! otherwise silently recompute or accept.
+//!
+//! ```
+//! use rsa::pkcs1::EncodeRsaPublicKey;
+//! use rsa::strictness::Strictness;
+//! use rsa::RsaPublicKey;
+//!
+//! let mut rng = rand::thread_rng();
+//! let key = rsa::RsaPrivateKey::new(&mut rng, 2048).expect("failed to generate a key");
+//! let public_key = key.to_public_key();
+//!
+//! let mut der = public_key.to_pkcs1_der().expect("failed to encode").as_bytes().to_vec();
+//! der.extend_from_slice(b"\r\n");
+//! assert!(RsaPublicKey::from_pkcs1_der_with_strictness(&der, Strictness::Strict).is_err());
+//! assert_eq!(
+//!     RsaPublicKey::from_pkcs1_der_with_strictness(&der, Strictness::Lenient).unwrap(),
+//!     public_key
+//! );
+//! ```
+
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+use pkcs1::der::Decode;
+use pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey, UintRef};
+use pkcs8::{DecodePrivateKey, DecodePublicKey};
+
+use crate::csr::{tlv, TAG_BIT_STRING, TAG_INTEGER, TAG_OCTET_STRING, TAG_SEQUENCE};
+use crate::encoded::EncodedSignature;
+use crate::errors::{Error, Result};
+use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::traits::PublicKeyParts;
+
+/// Maximum nesting of the structures re-encoded in lenient mode.
+const MAX_DEPTH: usize = 8;
+
+/// How strictly encoded keys and signatures are checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Only accept DER: minimal integers and lengths, positive integers and
+    /// no trailing data. Signatures must have the size of the modulus.
+    #[default]
+    Strict,
+    /// Accept BER-like sloppiness: non-minimal integers and lengths,
+    /// integers missing their sign octet, which are read as unsigned, and
+    /// trailing data. Signatures may have extra or missing leading zeros.
+    Lenient,
+}
+
+impl Strictness {
+    /// Returns the DER encoding of `der` to decode with this strictness.
+    fn prepare(self, der: &[u8]) -> Result<Cow<'_, [u8]>> {
+        match self {
+            Strictness::Strict => Ok(Cow::Borrowed(der)),
+            Strictness::Lenient => reencode(der, 0).map(|(der, _)| Cow::Owned(der)),
+        }
+    }
+}
+
+impl RsaPublicKey {
+    /// Decode a PKCS#1 `RSAPublicKey` with the given strictness.
+    pub fn from_pkcs1_der_with_strictness(der: &[u8], strictness: Strictness) -> Result<Self> {
+        let der = strictness.prepare(der)?;
+        check_public_key(&der)?;
+        Ok(Self::from_pkcs1_der(&der)?)
+    }
+
+    /// Decode a `SubjectPublicKeyInfo` with the given strictness.
+    pub fn from_public_key_der_with_strictness(der: &[u8], strictness: Strictness) -> Result<Self> {
+        let der = strictness.prepare(der)?;
+        let spki = pkcs8::SubjectPublicKeyInfoRef::from_der(&der)?;
+        check_public_key(spki.subject_public_key.as_bytes().ok_or_else(malformed)?)?;
+        Ok(Self::from_public_key_der(&der)?)
+    }
+}
+
+impl RsaPrivateKey {
+    /// Decode a PKCS#1 `RSAPrivateKey` with the given strictness.
+    pub fn from_pkcs1_der_with_strictness(der: &[u8], strictness: Strictness) -> Result<Self> {
+        let der = strictness.prepare(der)?;
+        check_private_key(&der)?;
+        Ok(Self::from_pkcs1_der(&der)?)
+    }
+
+    /// Decode a PKCS#8 `PrivateKeyInfo` with the given strictness.
+    pub fn from_pkcs8_der_with_strictness(der: &[u8], strictness: Strictness) -> Result<Self> {
+        let der = strictness.prepare(der)?;
+        let private_key_info = pkcs8::PrivateKeyInfoRef::from_der(&der)?;
+        check_private_key(private_key_info.private_key.as_bytes())?;
+        Ok(Self::from_pkcs8_der(&der)?)
+    }
+}
+
+/// Decode a signature made with `key` with the given strictness.
+///
+/// In strict mode, `signature` must have the size of the modulus. In
+/// lenient mode, leading zeros are added or removed to reach it, as some
+/// signers strip them and others prepend a sign octet.
+pub fn decode_signature(
+    key: &impl PublicKeyParts,
+    signature: &[u8],
+    strictness: Strictness,
+) -> Result<EncodedSignature> {
+    let size = key.size();
+    match strictness {
+        Strictness::Strict => EncodedSignature::new(key, signature),
+        Strictness::Lenient => {
+            let zeros = signature.iter().take_while(|&&b| b == 0).count();
+            let significant = &signature[zeros..];
+            if significant.len() > size {
+                return Err(Error::Verification);
+            }
+            let mut bytes = vec![0; size];
+            bytes[size - significant.len()..].copy_from_slice(significant);
+            EncodedSignature::new(key, bytes)
+        }
+    }
+}
+
+/// Checks the components of a PKCS#1 `RSAPublicKey` are positive.
+fn check_public_key(der: &[u8]) -> Result<()> {
+    let key = pkcs1::RsaPublicKey::from_der(der)?;
+    check_positive(&[key.modulus, key.public_exponent])
+}
+
+/// Checks the components of a PKCS#1 `RSAPrivateKey` are positive.
+fn check_private_key(der: &[u8]) -> Result<()> {
+    let key = pkcs1::RsaPrivateKey::from_der(der)?;
+    check_positive(&[
+        key.modulus,
+        key.public_exponent,
+        key.private_exponent,
+        key.prime1,
+        key.prime2,
+        key.exponent1,
+        key.exponent2,
+        key.coefficient,
+    ])
+}
+
+fn check_positive(components: &[UintRef<'_>]) -> Result<()> {
+    if components
+        .iter()
+        .any(|c| c.as_bytes().iter().all(|&b| b == 0))
+    {
+        return Err(malformed());
+    }
+    Ok(())
+}
+
+fn malformed() -> Error {
+    Error::Pkcs1(pkcs1::Error::KeyMalformed)
+}
+
+/// Re-encodes the first TLV of `ber` as DER, returning it and the rest of
+/// the input.
+///
+/// Structures, and keys nested in bit and octet strings, are re-encoded
+/// recursively. Integers are read as unsigned.
+fn reencode(ber: &[u8], depth: usize) -> Result<(Vec<u8>, &[u8])> {
+    if depth > MAX_DEPTH {
+        return Err(malformed());
+    }
+
+    let (&tag, rest) = ber.split_first().ok_or_else(malformed)?;
+    let (&first, rest) = rest.split_first().ok_or_else(malformed)?;
+    // High tag numbers and indefinite lengths do not occur in keys.
+    if tag & 0x1f == 0x1f || first == 0x80 {
+        return Err(malformed());
+    }
+    let (len, rest) = if first < 0x80 {
+        (usize::from(first), rest)
+    } else {
+        let count = usize::from(first & 0x7f);
+        if count > rest.len() {
+            return Err(malformed());
+        }
+        let (bytes, rest) = rest.split_at(count);
+        let len = bytes.iter().try_fold(0usize, |len, &b| {
+            len.checked_mul(256)
+                .and_then(|len| len.checked_add(usize::from(b)))
+        });
+        (len.ok_or_else(malformed)?, rest)
+    };
+    if len > rest.len() {
+        return Err(malformed());
+    }
+    let (contents, rest) = rest.split_at(len);
+
+    let contents = match tag {
+        TAG_INTEGER => {
+            if contents.is_empty() {
+                return Err(malformed());
+            }
+            let zeros = contents
+                .windows(2)
+                .take_while(|w| w[0] == 0 && w[1] & 0x80 == 0)
+                .count();
+            let contents = &contents[zeros..];
+            if contents[0] & 0x80 != 0 {
+                [&[0][..], contents].concat()
+            } else {
+                contents.to_vec()
+            }
+        }
+        TAG_SEQUENCE => {
+            let mut out = Vec::with_capacity(contents.len());
+            let mut elements = contents;
+            while !elements.is_empty() {
+                let (element, rest) = reencode(elements, depth + 1)?;
+                out.extend_from_slice(&element);
+                elements = rest;
+            }
+            out
+        }
+        TAG_BIT_STRING if contents.first() == Some(&0) => {
+            [&[0][..], &nested(&contents[1..], depth)].concat()
+        }
+        TAG_OCTET_STRING => nested(contents, depth),
+        _ => contents.to_vec(),
+    };
+    Ok((tlv(tag, &contents), rest))
+}
+
+/// Re-encodes `contents` if it holds exactly one structure, such as the key
+/// of a `SubjectPublicKeyInfo` or `PrivateKeyInfo`.
+fn nested(contents: &[u8], depth: usize) -> Vec<u8> {
+    match contents.first() {
+        Some(&TAG_SEQUENCE) => match reencode(contents, depth + 1) {
+            Ok((der, rest)) if rest.is_empty() => der,
+            _ => contents.to_vec(),
+        },
+        _ => contents.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::csr::concat;
+    use pkcs1::{EncodeRsaPrivateKey, EncodeRsaPublicKey};
+    use pkcs8::{EncodePrivateKey, EncodePublicKey};
+    use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+
+    /// Encodes an `RSAPublicKey` with the given raw integer contents.
+    fn public_key_der(n: &[u8], e: &[u8]) -> Vec<u8> {
+        tlv(
+            TAG_SEQUENCE,
+            &concat(&[tlv(TAG_INTEGER, n), tlv(TAG_INTEGER, e)]),
+        )
+    }
+
+    #[test]
+    fn test_public_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap().to_public_key();
+        let der = key.to_pkcs1_der().unwrap();
+        let n = key.n().to_be_bytes();
+
+        for strictness in [Strictness::Strict, Strictness::Lenient] {
+            assert_eq!(
+                RsaPublicKey::from_pkcs1_der_with_strictness(der.as_bytes(), strictness).unwrap(),
+                key
+            );
+        }
+
+        let sloppy = [
+            // Extra leading zeros.
+            public_key_der(&[&[0, 0, 0][..], &n].concat(), &[0, 1, 0, 1]),
+            // Missing sign octet.
+            public_key_der(&n, &[1, 0, 1]),
+            // Trailing garbage.
+            [der.as_bytes(), &[0xde, 0xad]].concat(),
+        ];
+        for der in sloppy {
+            assert!(
+                RsaPublicKey::from_pkcs1_der_with_strictness(&der, Strictness::Strict).is_err()
+            );
+            assert_eq!(
+                RsaPublicKey::from_pkcs1_der_with_strictness(&der, Strictness::Lenient).unwrap(),
+                key
+            );
+        }
+
+        // A long-form length in the key nested in a `SubjectPublicKeyInfo`.
+        let spki = key.to_public_key_der().unwrap();
+        let nested = [&[0x30, 0x82, 0x00, 0x89][..], &der.as_bytes()[3..]].concat();
+        let bit_string = tlv(TAG_BIT_STRING, &[&[0][..], &nested].concat());
+        let sloppy = tlv(
+            TAG_SEQUENCE,
+            &[&spki.as_bytes()[3..18], &bit_string[..]].concat(),
+        );
+        assert!(
+            RsaPublicKey::from_public_key_der_with_strictness(&sloppy, Strictness::Strict).is_err()
+        );
+        assert_eq!(
+            RsaPublicKey::from_public_key_der_with_strictness(&sloppy, Strictness::Lenient)
+                .unwrap(),
+            key
+        );
+    }
+
+    #[test]
+    fn test_private_key() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+
+        let der = key.to_pkcs8_der().unwrap();
+        let sloppy = [der.as_bytes(), &[0]].concat();
+        assert!(
+            RsaPrivateKey::from_pkcs8_der_with_strictness(&sloppy, Strictness::Strict).is_err()
+        );
+        assert_eq!(
+            RsaPrivateKey::from_pkcs8_der_with_strictness(&sloppy, Strictness::Lenient).unwrap(),
+            key
+        );
+
+        // A zero CRT coefficient, which decoding would otherwise recompute.
+        let der = key.to_pkcs1_der().unwrap();
+        let mut components = pkcs1::RsaPrivateKey::from_der(der.as_bytes()).unwrap();
+        components.coefficient = UintRef::new(&[0]).unwrap();
+        let der = pkcs1::der::Encode::to_der(&components).unwrap();
+        for strictness in [Strictness::Strict, Strictness::Lenient] {
+            assert!(RsaPrivateKey::from_pkcs1_der_with_strictness(&der, strictness).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decode_signature() {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let key = RsaPrivateKey::new(&mut rng, 1024).unwrap().to_public_key();
+        let mut signature = vec![0; 128];
+        signature[1..].fill(0xab);
+
+        for strictness in [Strictness::Strict, Strictness::Lenient] {
+            let decoded = decode_signature(&key, &signature, strictness).unwrap();
+            assert_eq!(decoded.as_ref(), &signature[..]);
+        }
+        for sloppy in [&signature[1..], &[&[0][..], &signature].concat()[..]] {
+            assert!(decode_signature(&key, sloppy, Strictness::Strict).is_err());
+            let decoded = decode_signature(&key, sloppy, Strictness::Lenient).unwrap();
+            assert_eq!(decoded.as_ref(), &signature[..]);
+        }
+        assert!(decode_signature(&key, &[0xff; 129], Strictness::Lenient).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+//! Private keys held by TPM 2.0 devices.
+//!
+//! [`TpmPrivateKey`] performs the raw private key operation with the
+//! `TPM2_RSA_Decrypt` command through [`tss_esapi`], while the padding is
+//! applied by this crate. The key must allow decryption and have the null
+//! scheme, so that the TPM computes raw RSA. It implements [`RsaPrivateOps`],
+//! so it can be used with the padding schemes and the signing and decrypting
+//! keys of this crate:
+//!
+//! ```no_run
+//! use rsa::oaep::DecryptingKey;
+//! use rsa::tpm::TpmPrivateKey;
+//! use rsa::traits::Decryptor;
+//! use sha2::Sha256;
+//! use tss_esapi::handles::{PersistentTpmHandle, TpmHandle};
+//! use tss_esapi::interface_types::session_handles::AuthSession;
+//! use tss_esapi::{Context, TctiNameConf};
+//!
+//! let mut context = Context::new(TctiNameConf::from_environment_variable()?)?;
+//! let persistent = PersistentTpmHandle::new(0x8100_0001)?;
+//! let handle = context.tr_from_tpm_public(TpmHandle::Persistent(persistent))?;
+//! context.set_sessions((Some(AuthSession::Password), None, None));
+//!
+//! let key = TpmPrivateKey::new(context, handle.into())?;
+//! let decrypting_key = DecryptingKey::<Sha256, Sha256, _>::new(key);
+//! let ciphertext = std::fs::read("secret.bin")?;
+//! let plaintext = decrypting_key.decrypt(&ciphertext)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use core::fmt;
+use std::sync::Mutex;
+
+use crypto_bigint::modular::BoxedMontyParams;
+use crypto_bigint::{BoxedUint, NonZero};
+use rand_core::CryptoRngCore;
+use tss_esapi::handles::KeyHandle;
+use tss_esapi::structures::{Data, Public, PublicKeyRsa, RsaDecryptionScheme};
+use tss_esapi::Context;
+use zeroize::Zeroizing;
+
+use crate::algorithms::rsa::rsa_external_op;
+use crate::errors::{Error, Result};
+use crate::traits::{PublicKeyParts, RsaPrivateOps};
+use crate::RsaPublicKey;
+
+/// RSA private key loaded in a TPM 2.0.
+///
+/// The context is locked for the duration of each operation. Blinding is
+/// left to the TPM, and its results are checked with the public key before
+/// they are used.
+pub struct TpmPrivateKey {
+    context: Mutex<Context>,
+    handle: KeyHandle,
+    public_key: RsaPublicKey,
+}
+
+impl TpmPrivateKey {
+    /// Use the loaded key `handle` through `context`.
+    ///
+    /// The public key is read from the TPM. The sessions authorizing the use
+    /// of the key must be set on the context by the caller.
+    pub fn new(mut context: Context, handle: KeyHandle) -> Result<Self> {
+        let (public, _, _) = context.read_public(handle).map_err(|_| Error::Backend)?;
+        let public_key = RsaPublicKey::try_from(&public)?;
+
+        Ok(Self {
+            context: Mutex::new(context),
+            handle,
+            public_key,
+        })
+    }
+
+    /// Returns the handle of the key.
+    pub fn handle(&self) -> KeyHandle {
+        self.handle
+    }
+
+    /// Returns the public key.
+    pub fn to_public_key(&self) -> RsaPublicKey {
+        self.public_key.clone()
+    }
+
+    /// Returns the context, e.g. to flush the key.
+    pub fn into_context(self) -> Context {
+        self.context
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for TpmPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TpmPrivateKey")
+            .field("handle", &self.handle)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AsRef<RsaPublicKey> for TpmPrivateKey {
+    fn as_ref(&self) -> &RsaPublicKey {
+        &self.public_key
+    }
+}
+
+impl PublicKeyParts for TpmPrivateKey {
+    fn n(&self) -> &NonZero<BoxedUint> {
+        self.public_key.n()
+    }
+
+    fn e(&self) -> &BoxedUint {
+        self.public_key.e()
+    }
+
+    fn n_params(&self) -> &BoxedMontyParams {
+        self.public_key.n_params()
+    }
+}
+
+impl RsaPrivateOps for TpmPrivateKey {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        _rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        rsa_external_op(&self.public_key, c, |input| {
+            let cipher_text = PublicKeyRsa::try_from(input.to_vec()).map_err(|_| Error::Backend)?;
+            let mut context = self.context.lock().map_err(|_| Error::Backend)?;
+            context
+                .rsa_decrypt(
+                    self.handle,
+                    cipher_text,
+                    RsaDecryptionScheme::Null,
+                    Data::default(),
+                )
+                .map(|m| Zeroizing::new(m.value().to_vec()))
+                .map_err(|_| Error::Backend)
+        })
+    }
+}
+
+impl TryFrom<&Public> for RsaPublicKey {
+    type Error = Error;
+
+    /// Extracts the public key from the public area of a TPM RSA key.
+    fn try_from(public: &Public) -> Result<Self> {
+        let Public::Rsa {
+            parameters, unique, ..
+        } = public
+        else {
+            return Err(Error::InvalidArguments);
+        };
+
+        // An exponent of zero denotes the default exponent.
+        let e = match parameters.exponent().value() {
+            0 => 65537,
+            e => e,
+        };
+        let n = unique.value();
+        let n = BoxedUint::from_be_slice(n, n.len() as u32 * 8)?;
+        RsaPublicKey::new(n, BoxedUint::from(u64::from(e)))
+    }
+}
@@ -5,5 +5,7 @@ pub(crate) mod keys;
 mod padding;
 
 pub use encryption::{Decryptor, EncryptingKeypair, RandomizedDecryptor, RandomizedEncryptor};
-pub use keys::{PrivateKeyParts, PublicKeyParts};
+#[doc(hidden)]
+pub use keys::PrivateKeyOps;
+pub use keys::{PrivateKeyParts, PublicKeyParts, RsaPrivateOps};
 pub use padding::{PaddingScheme, SignatureScheme};
@@ -2,6 +2,7 @@
 
 use alloc::vec::Vec;
 use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
 
 use crate::errors::Result;
 
@@ -19,6 +20,11 @@ pub trait RandomizedEncryptor {
 pub trait Decryptor {
     /// Decrypt the given message.
     fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decrypt the given message into a buffer which is wiped when dropped.
+    fn decrypt_zeroizing(&self, ciphertext: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        self.decrypt(ciphertext).map(Zeroizing::new)
+    }
 }
 
 /// Decrypt the given message using provided random source
@@ -29,6 +35,15 @@ pub trait RandomizedDecryptor {
         rng: &mut R,
         ciphertext: &[u8],
     ) -> Result<Vec<u8>>;
+
+    /// Decrypt the given message into a buffer which is wiped when dropped.
+    fn decrypt_with_rng_zeroizing<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: &mut R,
+        ciphertext: &[u8],
+    ) -> Result<Zeroizing<Vec<u8>>> {
+        self.decrypt_with_rng(rng, ciphertext).map(Zeroizing::new)
+    }
 }
 
 /// Encryption keypair with an associated encryption key.
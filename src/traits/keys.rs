@@ -4,8 +4,11 @@ use crypto_bigint::{
     modular::{BoxedMontyForm, BoxedMontyParams},
     BoxedUint, NonZero,
 };
+use rand_core::CryptoRngCore;
 use zeroize::Zeroize;
 
+use crate::errors::Result;
+
 /// Components of an RSA public key.
 pub trait PublicKeyParts {
     /// Returns the modulus of the key.
@@ -54,6 +57,105 @@ pub trait PrivateKeyParts: PublicKeyParts {
 
     /// Returns the params for `q` if precomupted.
     fn q_params(&self) -> Option<&BoxedMontyParams>;
+
+    /// Returns the window size in bits of the fixed-window exponentiations
+    /// using CRT values, or `None` to use the exponentiation of `crypto-bigint`.
+    fn exp_window(&self) -> Option<u32> {
+        None
+    }
+
+    /// Returns the cache of blinding factors, if enabled.
+    #[cfg(feature = "std")]
+    fn blinding_cache(&self) -> Option<&BlindingCache> {
+        None
+    }
+}
+
+/// Raw RSA private key operation of keys held outside of memory.
+///
+/// # ☢️️ WARNING: HAZARDOUS API ☢️
+///
+/// This trait computes RSA's core private key primitive on raw data, without
+/// any padding. Calling it directly has very few valid use cases, see
+/// [`hazmat`](crate::hazmat).
+///
+/// The padding and signature schemes only need this operation and the public
+/// components of the key, so keys held outside of memory, e.g. by HSMs, TPMs,
+/// smartcards or remote signers, can implement this trait and be used with
+/// [`Pkcs1v15Sign`](crate::Pkcs1v15Sign), [`Pss`](crate::Pss),
+/// [`Pkcs1v15Encrypt`](crate::Pkcs1v15Encrypt) and [`Oaep`](crate::Oaep).
+///
+/// [`RsaPrivateKey`](crate::RsaPrivateKey) only implements it with the
+/// `hazmat` feature.
+pub trait RsaPrivateOps: PublicKeyParts {
+    /// Computes `c^d mod n`, where `c` has the precision of `n`.
+    ///
+    /// If an `rng` is passed, implementations performing the computation
+    /// themselves should use it for RSA blinding. The result must be checked
+    /// against faults before it is returned, as a faulty signature can leak
+    /// the private key.
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint>;
+
+    /// Computes `c^d mod n` to decrypt the ciphertext `c`.
+    ///
+    /// The padding schemes use this operation for decryption and
+    /// [`RsaPrivateOps::raw_private_op`] for signing, so that keys whose
+    /// usage is restricted by their backend can perform the matching
+    /// operation. Defaults to [`RsaPrivateOps::raw_private_op`].
+    fn raw_decrypt_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        self.raw_private_op(rng, c)
+    }
+}
+
+/// Private key operations used by the padding and signature schemes.
+///
+/// Implemented by the private keys of this crate and by every
+/// [`RsaPrivateOps`] implementation. Only implementations of padding schemes
+/// should call it: the raw operation of in-memory keys is otherwise only
+/// exposed by the `hazmat` feature.
+#[doc(hidden)]
+pub trait PrivateKeyOps: PublicKeyParts {
+    /// See [`RsaPrivateOps::raw_private_op`].
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint>;
+
+    /// See [`RsaPrivateOps::raw_decrypt_op`].
+    fn raw_decrypt_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        self.raw_private_op(rng, c)
+    }
+}
+
+impl<K: RsaPrivateOps> PrivateKeyOps for K {
+    fn raw_private_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        RsaPrivateOps::raw_private_op(self, rng, c)
+    }
+
+    fn raw_decrypt_op<R: CryptoRngCore + ?Sized>(
+        &self,
+        rng: Option<&mut R>,
+        c: &BoxedUint,
+    ) -> Result<BoxedUint> {
+        RsaPrivateOps::raw_decrypt_op(self, rng, c)
+    }
 }
 
 /// Contains the precomputed Chinese remainder theorem values.
@@ -80,3 +182,60 @@ impl Drop for CrtValue {
         self.zeroize();
     }
 }
+
+/// Blinding factors cached between private key operations.
+///
+/// Instead of generating and inverting a fresh random blinding factor `r` for
+/// every operation, the cached pair `(r^e, r^-1)` is squared after each use
+/// and only regenerated every [`BlindingCache::MAX_USES`] operations.
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct BlindingCache {
+    pub(crate) pair: std::sync::Mutex<Option<BlindingPair>>,
+}
+
+#[cfg(feature = "std")]
+impl BlindingCache {
+    /// Number of operations after which the blinding factors are regenerated.
+    pub const MAX_USES: u32 = 32;
+
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clone for BlindingCache {
+    /// Creates an empty cache, so that clones of a key never share blinding
+    /// factors.
+    fn clone(&self) -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Debug for BlindingCache {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("BlindingCache { .. }")
+    }
+}
+
+/// A blinding factor `r` as the pair `(r^e, r^-1)` modulo `n`.
+#[cfg(feature = "std")]
+pub(crate) struct BlindingPair {
+    /// r^e mod n
+    pub(crate) r_e: BoxedUint,
+    /// r^-1 mod n
+    pub(crate) r_inv: BoxedUint,
+    /// Number of operations which used this pair or its predecessors.
+    pub(crate) uses: u32,
+}
+
+#[cfg(feature = "std")]
+impl Drop for BlindingPair {
+    fn drop(&mut self) {
+        self.r_e.zeroize();
+        self.r_inv.zeroize();
+    }
+}
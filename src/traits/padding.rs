@@ -3,9 +3,11 @@
 use alloc::vec::Vec;
 
 use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
 
-use crate::errors::Result;
-use crate::key::{RsaPrivateKey, RsaPublicKey};
+use crate::errors::{Error, Result};
+use crate::key::RsaPublicKey;
+use crate::traits::PrivateKeyOps;
 
 /// Padding scheme used for encryption.
 pub trait PaddingScheme {
@@ -16,7 +18,7 @@ pub trait PaddingScheme {
     fn decrypt<Rng: CryptoRngCore>(
         self,
         rng: Option<&mut Rng>,
-        priv_key: &RsaPrivateKey,
+        priv_key: &impl PrivateKeyOps,
         ciphertext: &[u8],
     ) -> Result<Vec<u8>>;
 
@@ -27,6 +29,59 @@ pub trait PaddingScheme {
         pub_key: &RsaPublicKey,
         msg: &[u8],
     ) -> Result<Vec<u8>>;
+
+    /// Decrypt the given message into the start of `out`, returning the
+    /// length of the plaintext.
+    ///
+    /// Fails with [`Error::InvalidArguments`] if `out` is too small.
+    fn decrypt_into<Rng: CryptoRngCore>(
+        self,
+        rng: Option<&mut Rng>,
+        priv_key: &impl PrivateKeyOps,
+        ciphertext: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize>
+    where
+        Self: Sized,
+    {
+        let msg = Zeroizing::new(self.decrypt(rng, priv_key, ciphertext)?);
+        copy_into(&msg, out)
+    }
+
+    /// Decrypt the given message without consuming the padding scheme, so
+    /// that a ciphertext can be tried with several private keys.
+    ///
+    /// Only schemes whose failed decryptions reveal nothing but the failure
+    /// support this. The default fails with [`Error::InvalidPaddingScheme`]:
+    /// with PKCS#1 v1.5, learning which key accepted the padding of a forged
+    /// ciphertext is a [Bleichenbacher] oracle.
+    ///
+    /// [Bleichenbacher]: https://link.springer.com/content/pdf/10.1007/BFb0055716.pdf
+    fn trial_decrypt<Rng: CryptoRngCore>(
+        &mut self,
+        _rng: Option<&mut Rng>,
+        _priv_key: &impl PrivateKeyOps,
+        _ciphertext: &[u8],
+    ) -> Result<Vec<u8>> {
+        Err(Error::InvalidPaddingScheme)
+    }
+
+    /// Encrypt the given message into the start of `out`, returning the length
+    /// of the ciphertext.
+    ///
+    /// Fails with [`Error::InvalidArguments`] if `out` is too small.
+    fn encrypt_into<Rng: CryptoRngCore>(
+        self,
+        rng: &mut Rng,
+        pub_key: &RsaPublicKey,
+        msg: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize>
+    where
+        Self: Sized,
+    {
+        copy_into(&self.encrypt(rng, pub_key, msg)?, out)
+    }
 }
 
 /// Digital signature scheme.
@@ -35,7 +90,7 @@ pub trait SignatureScheme {
     fn sign<Rng: CryptoRngCore>(
         self,
         rng: Option<&mut Rng>,
-        priv_key: &RsaPrivateKey,
+        priv_key: &impl PrivateKeyOps,
         hashed: &[u8],
     ) -> Result<Vec<u8>>;
 
@@ -46,4 +101,29 @@ pub trait SignatureScheme {
     ///
     /// If the message is valid `Ok(())` is returned, otherwise an `Err` indicating failure.
     fn verify(self, pub_key: &RsaPublicKey, hashed: &[u8], sig: &[u8]) -> Result<()>;
+
+    /// Sign the given digest into the start of `out`, returning the length of
+    /// the signature.
+    ///
+    /// Fails with [`Error::InvalidArguments`] if `out` is too small.
+    fn sign_into<Rng: CryptoRngCore>(
+        self,
+        rng: Option<&mut Rng>,
+        priv_key: &impl PrivateKeyOps,
+        hashed: &[u8],
+        out: &mut [u8],
+    ) -> Result<usize>
+    where
+        Self: Sized,
+    {
+        copy_into(&self.sign(rng, priv_key, hashed)?, out)
+    }
+}
+
+/// Copies `data` to the start of `out`, returning its length.
+fn copy_into(data: &[u8], out: &mut [u8]) -> Result<usize> {
+    out.get_mut(..data.len())
+        .ok_or(Error::InvalidArguments)?
+        .copy_from_slice(data);
+    Ok(data.len())
 }
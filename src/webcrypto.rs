@@ -0,0 +1,262 @@
+//! Operations delegated to the WebCrypto API of browsers on `wasm32` targets.
+//!
+//! The big integer arithmetic of this crate is slow in the browser and runs
+//! on the calling thread. [`WebCryptoKey`] imports a key of this crate into
+//! the browser's `SubtleCrypto`, using the PKCS#8 and SPKI encodings of this
+//! crate, and performs the operations of a scheme there, asynchronously.
+//!
+//! WebCrypto supports RSASSA-PKCS1-v1_5 and RSASSA-PSS signatures and
+//! RSAES-OAEP encryption. The scheme and digest are fixed when the key is
+//! imported.
+//!
+//! ```no_run
+//! # async fn example() -> rsa::Result<()> {
+//! use rsa::webcrypto::{WebCryptoHash, WebCryptoKey, WebCryptoScheme};
+//! use rsa::RsaPrivateKey;
+//!
+//! let mut rng = rand::thread_rng();
+//! let private_key = RsaPrivateKey::new(&mut rng, 2048)?;
+//! let scheme = WebCryptoScheme::Pss { salt_len: 32 };
+//!
+//! let signing_key =
+//!     WebCryptoKey::import_private(&private_key, scheme.clone(), WebCryptoHash::Sha256).await?;
+//! let signature = signing_key.sign(b"hello world").await?;
+//!
+//! let verifying_key = WebCryptoKey::import_public(
+//!     &private_key.to_public_key(),
+//!     scheme,
+//!     WebCryptoHash::Sha256,
+//! )
+//! .await?;
+//! verifying_key.verify(b"hello world", &signature).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use js_sys::{Array, Object, Promise, Reflect, Uint8Array};
+use pkcs8::{EncodePrivateKey, EncodePublicKey};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Crypto, CryptoKey, SubtleCrypto};
+
+use crate::errors::{Error, Result};
+use crate::{RsaPrivateKey, RsaPublicKey};
+
+/// Digest used by a [`WebCryptoScheme`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebCryptoHash {
+    /// SHA-1
+    Sha1,
+    /// SHA-256
+    Sha256,
+    /// SHA-384
+    Sha384,
+    /// SHA-512
+    Sha512,
+}
+
+impl WebCryptoHash {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Sha384 => "SHA-384",
+            Self::Sha512 => "SHA-512",
+        }
+    }
+}
+
+/// Scheme performed by a [`WebCryptoKey`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebCryptoScheme {
+    /// RSASSA-PKCS1-v1_5 signatures, see [`Pkcs1v15Sign`](crate::Pkcs1v15Sign).
+    Pkcs1v15Sign,
+    /// RSASSA-PSS signatures, see [`Pss`](crate::Pss).
+    Pss {
+        /// Length of the salt in bytes.
+        salt_len: usize,
+    },
+    /// RSAES-OAEP encryption, see [`Oaep`](crate::Oaep). The digest is also
+    /// used for MGF1.
+    Oaep {
+        /// Optional label.
+        label: Option<Box<[u8]>>,
+    },
+}
+
+impl WebCryptoScheme {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Pkcs1v15Sign => "RSASSA-PKCS1-v1_5",
+            Self::Pss { .. } => "RSA-PSS",
+            Self::Oaep { .. } => "RSA-OAEP",
+        }
+    }
+
+    /// Returns the key usage of private or public keys.
+    fn usage(&self, private: bool) -> &'static str {
+        match (self, private) {
+            (Self::Oaep { .. }, true) => "decrypt",
+            (Self::Oaep { .. }, false) => "encrypt",
+            (_, true) => "sign",
+            (_, false) => "verify",
+        }
+    }
+
+    /// Returns the parameters of the operations.
+    fn params(&self) -> Result<Object> {
+        let params = object(&[("name", self.name().into())])?;
+        match self {
+            Self::Pkcs1v15Sign => {}
+            Self::Pss { salt_len } => set(&params, "saltLength", (*salt_len as f64).into())?,
+            Self::Oaep { label: Some(label) } => {
+                set(&params, "label", Uint8Array::from(&label[..]).into())?
+            }
+            Self::Oaep { label: None } => {}
+        }
+        Ok(params)
+    }
+}
+
+/// Key imported into the WebCrypto API of the browser.
+#[derive(Debug, Clone)]
+pub struct WebCryptoKey {
+    key: CryptoKey,
+    scheme: WebCryptoScheme,
+}
+
+impl WebCryptoKey {
+    /// Import a private key for signing or decryption with `scheme`.
+    ///
+    /// The key is imported as not extractable.
+    pub async fn import_private(
+        key: &RsaPrivateKey,
+        scheme: WebCryptoScheme,
+        hash: WebCryptoHash,
+    ) -> Result<Self> {
+        let der = key.to_pkcs8_der()?;
+        Self::import("pkcs8", der.as_bytes(), scheme, hash, true).await
+    }
+
+    /// Import a public key for verification or encryption with `scheme`.
+    pub async fn import_public(
+        key: &RsaPublicKey,
+        scheme: WebCryptoScheme,
+        hash: WebCryptoHash,
+    ) -> Result<Self> {
+        let der = key.to_public_key_der().map_err(pkcs8::Error::from)?;
+        Self::import("spki", der.as_bytes(), scheme, hash, false).await
+    }
+
+    async fn import(
+        format: &str,
+        der: &[u8],
+        scheme: WebCryptoScheme,
+        hash: WebCryptoHash,
+        private: bool,
+    ) -> Result<Self> {
+        let algorithm = object(&[("name", scheme.name().into()), ("hash", hash.name().into())])?;
+        let usages = Array::of1(&scheme.usage(private).into());
+        let promise = subtle()?
+            .import_key_with_object(format, &Uint8Array::from(der), &algorithm, false, &usages)
+            .map_err(|_| Error::Backend)?;
+        let key = resolve(promise)
+            .await?
+            .dyn_into()
+            .map_err(|_| Error::Backend)?;
+        Ok(Self { key, scheme })
+    }
+
+    /// Returns the scheme of the key.
+    pub fn scheme(&self) -> &WebCryptoScheme {
+        &self.scheme
+    }
+
+    /// Sign `msg`, which is hashed by the browser.
+    pub async fn sign(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        let promise = subtle()?
+            .sign_with_object_and_buffer_source(
+                &self.scheme.params()?,
+                &self.key,
+                &Uint8Array::from(msg),
+            )
+            .map_err(|_| Error::Backend)?;
+        Ok(Uint8Array::new(&resolve(promise).await?).to_vec())
+    }
+
+    /// Verify the signature `sig` of `msg`.
+    pub async fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<()> {
+        let promise = subtle()?
+            .verify_with_object_and_buffer_source_and_buffer_source(
+                &self.scheme.params()?,
+                &self.key,
+                &Uint8Array::from(sig),
+                &Uint8Array::from(msg),
+            )
+            .map_err(|_| Error::Backend)?;
+        match resolve(promise).await?.as_bool() {
+            Some(true) => Ok(()),
+            _ => Err(Error::Verification),
+        }
+    }
+
+    /// Encrypt `msg`.
+    pub async fn encrypt(&self, msg: &[u8]) -> Result<Vec<u8>> {
+        let promise = subtle()?
+            .encrypt_with_object_and_buffer_source(
+                &self.scheme.params()?,
+                &self.key,
+                &Uint8Array::from(msg),
+            )
+            .map_err(|_| Error::Backend)?;
+        Ok(Uint8Array::new(&resolve(promise).await?).to_vec())
+    }
+
+    /// Decrypt `ciphertext`.
+    ///
+    /// Decryption failures are reported as [`Error::Decryption`].
+    pub async fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let promise = subtle()?
+            .decrypt_with_object_and_buffer_source(
+                &self.scheme.params()?,
+                &self.key,
+                &Uint8Array::from(ciphertext),
+            )
+            .map_err(|_| Error::Decryption)?;
+        let plaintext = JsFuture::from(promise)
+            .await
+            .map_err(|_| Error::Decryption)?;
+        Ok(Uint8Array::new(&plaintext).to_vec())
+    }
+}
+
+/// Returns the `SubtleCrypto` of the global object, of a window or a worker.
+fn subtle() -> Result<SubtleCrypto> {
+    let crypto = Reflect::get(&js_sys::global(), &"crypto".into())
+        .ok()
+        .and_then(|crypto| crypto.dyn_into::<Crypto>().ok())
+        .ok_or(Error::Backend)?;
+    Ok(crypto.subtle())
+}
+
+/// Waits for `promise`, mapping rejections to [`Error::Backend`].
+async fn resolve(promise: Promise) -> Result<JsValue> {
+    JsFuture::from(promise).await.map_err(|_| Error::Backend)
+}
+
+fn object(entries: &[(&str, JsValue)]) -> Result<Object> {
+    let object = Object::new();
+    for (key, value) in entries {
+        set(&object, key, value.clone())?;
+    }
+    Ok(object)
+}
+
+fn set(object: &Object, key: &str, value: JsValue) -> Result<()> {
+    Reflect::set(object, &key.into(), &value)
+        .map(drop)
+        .map_err(|_| Error::Backend)
+}
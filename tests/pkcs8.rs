@@ -119,6 +119,18 @@ fn decode_rsa2048_pub_der() {
     let _ = pkcs1v15::VerifyingKey::<Sha256>::from_public_key_der(RSA_2048_PUB_DER).unwrap();
 }
 
+#[test]
+#[cfg(feature = "fingerprint")]
+fn display_rsa2048_pub() {
+    let key = RsaPublicKey::from_public_key_der(RSA_2048_PUB_DER).unwrap();
+
+    // Matches `openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64`
+    assert_eq!(
+        key.to_string(),
+        "RSA-2048 SHA256:7+2pv+rZ/QWU9qXPb99sFjEWo7H61tc86gUpW2j9F5Q"
+    );
+}
+
 #[test]
 fn decode_rsa2048_pss_priv_der() {
     let key = RsaPrivateKey::from_pkcs8_der(RSA_2048_PSS_PRIV_DER).unwrap();
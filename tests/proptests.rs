@@ -1,14 +1,18 @@
 //! Property-based tests.
 
+use crypto_bigint::{BoxedUint, Odd};
 use proptest::prelude::*;
 use rand_chacha::ChaCha8Rng;
 use rand_core::SeedableRng;
 use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey, EncodeRsaPrivateKey},
     pkcs1v15,
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
     signature::{Keypair, SignatureEncoding, Signer, Verifier},
-    RsaPrivateKey,
+    RsaPrivateKey, RsaPublicKey,
 };
 use sha2::Sha256;
+use std::sync::OnceLock;
 
 prop_compose! {
     // WARNING: do *NOT* copy and paste this code. It's insecure and optimized for test speed.
@@ -18,7 +22,62 @@ prop_compose! {
     }
 }
 
+/// PKCS#1 encoding of a fixed key, mutated by the decoding tests.
+fn private_key_der() -> &'static [u8] {
+    static DER: OnceLock<Vec<u8>> = OnceLock::new();
+    DER.get_or_init(|| {
+        let mut rng = ChaCha8Rng::from_seed([42; 32]);
+        let private_key = RsaPrivateKey::new(&mut rng, 512).unwrap();
+        private_key.to_pkcs1_der().unwrap().as_bytes().to_vec()
+    })
+}
+
+fn uint(bytes: &[u8]) -> BoxedUint {
+    BoxedUint::from_be_slice(bytes, 8 * bytes.len() as u32).unwrap()
+}
+
 proptest! {
+    // Decoding and constructing keys from untrusted inputs must report
+    // failures as errors and never panic.
+
+    #[test]
+    fn decode_arbitrary_der(der in any::<Vec<u8>>()) {
+        let _ = RsaPrivateKey::from_pkcs1_der(&der);
+        let _ = RsaPrivateKey::from_pkcs8_der(&der);
+        let _ = RsaPublicKey::from_pkcs1_der(&der);
+        let _ = RsaPublicKey::from_public_key_der(&der);
+    }
+
+    #[test]
+    fn decode_mutated_der(
+        mutations in prop::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..4),
+    ) {
+        let mut der = private_key_der().to_vec();
+        for (index, byte) in mutations {
+            let i = index.index(der.len());
+            der[i] = byte;
+        }
+        let _ = RsaPrivateKey::from_pkcs1_der(&der);
+    }
+
+    #[test]
+    fn from_arbitrary_components(
+        n in prop::collection::vec(any::<u8>(), 1..64),
+        e in prop::collection::vec(any::<u8>(), 1..8),
+        d in prop::collection::vec(any::<u8>(), 1..64),
+        p in prop::collection::vec(any::<u8>(), 1..32),
+        q in prop::collection::vec(any::<u8>(), 1..32),
+    ) {
+        let (n, e, d, p, q) = (uint(&n), uint(&e), uint(&d), uint(&p), uint(&q));
+        let _ = RsaPublicKey::new(n.clone(), e.clone());
+        let _ = RsaPrivateKey::from_p_q(p.clone(), q.clone(), e.clone());
+        let _ = RsaPrivateKey::from_primes(vec![p.clone(), q.clone()], e.clone());
+        if let Some(n) = Odd::new(n).into_option() {
+            let _ = RsaPrivateKey::from_components(n.clone(), e.clone(), d.clone(), vec![]);
+            let _ = RsaPrivateKey::from_components(n, e, d, vec![p, q]);
+        }
+    }
+
     #[test]
     fn pkcs1v15_sign_roundtrip(private_key in private_key(), msg in any::<Vec<u8>>()) {
         let signing_key = pkcs1v15::SigningKey::<Sha256>::new(private_key);
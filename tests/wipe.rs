@@ -0,0 +1,87 @@
+//! Checks that secrets are wiped before their buffers are freed.
+//!
+//! The global allocator of this test binary scans every freed buffer for the
+//! secret. Only OAEP is covered: the integer representation of a PKCS#1 v1.5
+//! encoded message holds the plaintext, and its temporaries are allocated by
+//! `crypto-bigint`.
+
+#![cfg(feature = "alloc")]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
+use rsa::oaep::{DecryptingKey, EncryptingKey};
+use rsa::traits::{Decryptor, RandomizedEncryptor};
+use rsa::RsaPrivateKey;
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+struct ScanningAlloc;
+
+#[global_allocator]
+static ALLOC: ScanningAlloc = ScanningAlloc;
+
+std::thread_local! {
+    static SECRET: Cell<(*const u8, usize)> = const { Cell::new((core::ptr::null(), 0)) };
+    static COPIES: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Whether `block` contains `secret` in either byte order, the reversed one
+/// being how a `BoxedUint` holds it on little-endian targets.
+fn contains(block: &[u8], secret: &[u8]) -> bool {
+    block
+        .windows(secret.len())
+        .any(|window| window == secret || window.iter().eq(secret.iter().rev()))
+}
+
+unsafe impl GlobalAlloc for ScanningAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = SECRET.try_with(|secret| {
+            let (secret_ptr, secret_len) = secret.get();
+            if secret_len == 0 || layout.size() < secret_len {
+                return;
+            }
+            // SAFETY: the block is still allocated, and the secret is
+            // borrowed for the duration of `freed_copies`.
+            let block = core::slice::from_raw_parts(ptr, layout.size());
+            let secret = core::slice::from_raw_parts(secret_ptr, secret_len);
+            if contains(block, secret) {
+                COPIES.with(|copies| copies.set(copies.get() + 1));
+            }
+        });
+        System.dealloc(ptr, layout)
+    }
+}
+
+/// Runs `f` and returns how many buffers it freed on this thread while they
+/// still contained `secret`.
+fn freed_copies(secret: &[u8], f: impl FnOnce()) -> usize {
+    assert!(!secret.is_empty());
+    COPIES.with(|copies| copies.set(0));
+    SECRET.with(|cell| cell.set((secret.as_ptr(), secret.len())));
+    f();
+    SECRET.with(|cell| cell.set((core::ptr::null(), 0)));
+    COPIES.with(|copies| copies.get())
+}
+
+#[test]
+fn oaep_wipes_plaintext() {
+    let mut rng = ChaCha8Rng::from_seed([42; 32]);
+    let private_key = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+    let encrypting_key =
+        EncryptingKey::<Sha256>::new_with_label(private_key.to_public_key(), &b"label"[..]);
+    let decrypting_key = DecryptingKey::<Sha256>::new_with_label(private_key, &b"label"[..]);
+
+    let secret = *b"OAEP plaintext";
+    let copies = freed_copies(&secret, || {
+        let ciphertext = encrypting_key.encrypt_with_rng(&mut rng, &secret).unwrap();
+        let msg = Zeroizing::new(decrypting_key.decrypt(&ciphertext).unwrap());
+        assert_eq!(msg[..], secret);
+    });
+    assert_eq!(copies, 0);
+}